@@ -0,0 +1,57 @@
+//! Records build details for `wallet version --verbose`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+/// Crates doing the actual cryptography, reported with their locked versions
+const CRYPTO_CRATES: &[&str] = &["aes-gcm", "argon2", "bip39", "coins-bip32", "hmac", "k256", "pbkdf2", "scrypt", "sha2"];
+
+fn main() {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let manifest_dir = Path::new(&manifest_dir);
+
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .current_dir(manifest_dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|commit| !commit.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=WEB3WALLET_GIT_COMMIT={}", commit);
+    println!("cargo:rustc-env=WEB3WALLET_BUILD_TARGET={}", std::env::var("TARGET").unwrap_or_default());
+    println!("cargo:rustc-env=WEB3WALLET_BUILD_PROFILE={}", std::env::var("PROFILE").unwrap_or_default());
+    println!("cargo:rustc-env=WEB3WALLET_CRYPTO_VERSIONS={}", crypto_versions(&manifest_dir.join("Cargo.lock")));
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+}
+
+/// `name=version` pairs separated by `;`, several versions of one crate joined with `,`
+fn crypto_versions(lock_file: &Path) -> String {
+    let lock = std::fs::read_to_string(lock_file).unwrap_or_default();
+    let mut versions: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+
+    let mut name = None;
+    for line in lock.lines() {
+        if let Some(value) = line.strip_prefix("name = ") {
+            name = Some(value.trim_matches('"'));
+        } else if let (Some(current), Some(value)) = (name, line.strip_prefix("version = ")) {
+            if CRYPTO_CRATES.contains(&current) {
+                versions.entry(current).or_default().push(value.trim_matches('"'));
+            }
+            name = None;
+        }
+    }
+
+    versions
+        .iter()
+        .map(|(name, versions)| format!("{}={}", name, versions.join(",")))
+        .collect::<Vec<_>>()
+        .join(";")
+}
@@ -0,0 +1,75 @@
+/*
+ * C interface to the web3wallet core, built with the `ffi` feature. See src/ffi.rs.
+ *
+ * Every function except the *_free ones and w3w_last_error returns 0 on success, or the
+ * CLI's exit code for the error class:
+ *   1 general, 2 user input, 3 authentication, 4 cryptographic, 5 filesystem,
+ *   6 network, 7 validation
+ * and leaves a message for w3w_last_error on the calling thread. Outputs are only written
+ * on success. Strings returned by the library are freed with w3w_string_free.
+ */
+#ifndef WEB3WALLET_H
+#define WEB3WALLET_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define W3W_OK 0
+#define W3W_ERR_GENERAL 1
+#define W3W_ERR_USER_INPUT 2
+#define W3W_ERR_AUTHENTICATION 3
+#define W3W_ERR_CRYPTOGRAPHIC 4
+#define W3W_ERR_FILESYSTEM 5
+#define W3W_ERR_NETWORK 6
+#define W3W_ERR_VALIDATION 7
+
+/* Decrypted wallet held by the library; its keys are zeroed by w3w_wallet_free */
+typedef struct W3wWallet W3wWallet;
+
+typedef struct W3wDerivedAddress {
+    uint32_t index;
+    /* EIP-55 checksummed, 0x prefixed */
+    char *address;
+    char *derivation_path;
+} W3wDerivedAddress;
+
+typedef struct W3wAddressList {
+    W3wDerivedAddress *items;
+    size_t len;
+} W3wAddressList;
+
+/* Message of the last failed call on this thread, or NULL; valid until the next failure */
+const char *w3w_last_error(void);
+void w3w_string_free(char *value);
+
+int32_t w3w_create(uint8_t word_count, W3wWallet **out_wallet);
+/* passphrase may be NULL */
+int32_t w3w_import_mnemonic(const char *mnemonic, const char *passphrase, W3wWallet **out_wallet);
+int32_t w3w_import_private_key(const char *private_key, W3wWallet **out_wallet);
+
+/* Native keystore JSON, Argon2id */
+int32_t w3w_encrypt(const W3wWallet *wallet, const char *password, char **out_keystore_json);
+/* Native or v3 keystore JSON; cold keystores are refused */
+int32_t w3w_decrypt(const char *keystore_json, const char *password, W3wWallet **out_wallet);
+/* The BIP39 passphrase is not in the keystore: a decrypted wallet that needs it derives
+ * nothing until it is supplied. A wrong one fails with W3W_ERR_AUTHENTICATION. */
+int32_t w3w_wallet_needs_passphrase(const W3wWallet *wallet, bool *out_needed);
+int32_t w3w_unlock_passphrase(W3wWallet *wallet, const char *passphrase);
+
+int32_t w3w_wallet_address(const W3wWallet *wallet, char **out_address);
+int32_t w3w_wallet_mnemonic(const W3wWallet *wallet, char **out_mnemonic);
+int32_t w3w_derive(const W3wWallet *wallet, uint32_t start, uint32_t count, W3wAddressList *out_list);
+
+void w3w_address_list_free(W3wAddressList *list);
+void w3w_wallet_free(W3wWallet *wallet);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* WEB3WALLET_H */
@@ -0,0 +1,67 @@
+# Prompts
+prompt-wallet-password = Enter wallet password
+prompt-keystore-password = Enter keystore password
+prompt-password-for = Enter password for { $file }
+prompt-new-wallet-password = Enter a password to encrypt the wallet: 
+prompt-confirm-password = Confirm password: 
+prompt-export-password = Enter a password for the exported keystore
+prompt-delegate-password = Enter a password for the delegate keystore, not the one of the main wallet
+prompt-archive-password = Enter a password for the archive
+prompt-archive-unlock = Enter the archive password
+prompt-current-password = Enter current password
+prompt-new-password = Enter new password
+prompt-confirm-new-password = Confirm new password
+prompt-mnemonic = Enter wallet mnemonic (BIP39 words separated by spaces)
+prompt-bip39-passphrase = Enter BIP39 passphrase
+prompt-confirm-passphrase = Confirm BIP39 passphrase
+prompt-slip39-passphrase = Enter SLIP-39 passphrase
+prompt-shamir-first-share = Enter a SLIP-39 share: 
+prompt-shamir-share = Enter share { $index } of { $threshold }: 
+
+# Delete confirmation
+delete-about-to = About to delete { $file }
+delete-about-to-unreadable = About to delete { $file }, which is not a readable keystore
+delete-confirm-identity = Type the wallet's alias or address to confirm: 
+delete-confirm-file = Type the wallet's file name to confirm: 
+delete-expected-identity = the wallet's alias or address
+delete-expected-file = the wallet's file name
+
+# Private key display
+derive-keys-warning = WARNING: private keys are about to be printed. Anyone who sees them, the terminal scrollback or a log can take the funds.
+derive-keys-confirm = Type "show keys" to continue: 
+derive-keys-expected = "show keys" to print the private keys
+
+# Error hints, printed after a failed command
+hint-label = Hint
+hint-insufficient-entropy = The system random number generator looks unhealthy; wait a moment or reboot before creating keys.
+hint-invalid-mnemonic = Check the words against the BIP39 word list, their order, and that the phrase has 12, 15, 18, 21 or 24 words.
+hint-invalid-private-key = Expected { $expected }.
+hint-decryption-failed = The keystore could not be decrypted; check the password and that the file is not damaged.
+hint-invalid-derivation-path = Use a path like m/44'/60'/0'/0, or --account and --change.
+hint-hardware-signer = Unlock the device, open the Ethereum app and reconnect it.
+hint-file-not-found = Check the file name; `web3wallet list` shows the saved wallets.
+hint-permission-denied = Check the permissions of the file and its directory.
+hint-file-exists = { $suggestion }
+hint-invalid-format = The file is not valid; restore it from a backup if it was damaged.
+hint-path-traversal = Use a plain file name inside the wallet directory.
+hint-invalid-parameters = Expected { $expected }.
+hint-conflicting-options = { $suggestion }
+hint-missing-parameter = { $hint }
+hint-invalid-network = Known networks: { $supported }. Add others with `web3wallet network add`.
+hint-password-mismatch = The two passwords differ; enter the same password twice.
+hint-prompt-timeout = Run the command from an interactive terminal, or raise --prompt-timeout (prompt_timeout_secs, 0 waits forever).
+hint-wrong-password = The password is wrong; passwords are case sensitive.
+hint-weak-password = Passwords need: { $requirements }. Pass --allow-weak-password to use it anyway in a test environment.
+hint-cold-storage = { $suggestion }
+hint-approval-required = An approver opens the request with `web3wallet approval approve`; pass the token they send back with --approval-token.
+hint-approval-rejected = Ask an approver for a fresh token for exactly this transaction; tokens expire and cover one sender, chain, recipient, value and nonce.
+hint-watch-only = Watch-only wallets hold no private key; sign with the wallet or device the xpub or address came from.
+hint-unlock-throttled = Too many wrong passwords were tried; wait for the lockout to end, the right password then clears the count.
+hint-wrong-passphrase = The BIP39 passphrase is not saved with the wallet; enter the one it was created or imported with.
+hint-network-unreachable = Check the network connection and the RPC endpoint, or raise --timeout.
+hint-invalid-configuration = Check the config file with `web3wallet config validate`.
+hint-rate-limit = The provider is rate limiting requests; wait and retry, or configure your own RPC endpoint.
+hint-address-format = Expected { $expected }.
+hint-keystore-schema = Run `web3wallet migrate` to upgrade older keystores.
+hint-chain-mismatch = Check the chain id or network; pass --force if signing for another chain is intended.
+hint-version-incompatible = This keystore needs version { $required }; upgrade web3wallet or run `web3wallet migrate`.
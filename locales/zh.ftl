@@ -0,0 +1,67 @@
+# 提示输入
+prompt-wallet-password = 请输入钱包密码
+prompt-keystore-password = 请输入 keystore 密码
+prompt-password-for = 请输入 { $file } 的密码
+prompt-new-wallet-password = 请输入用于加密钱包的密码：
+prompt-confirm-password = 请再次输入密码：
+prompt-export-password = 请输入导出 keystore 的密码
+prompt-delegate-password = 请输入委托 keystore 的密码（不要与主钱包相同）
+prompt-archive-password = 请设置备份归档的密码
+prompt-archive-unlock = 请输入备份归档的密码
+prompt-current-password = 请输入当前密码
+prompt-new-password = 请输入新密码
+prompt-confirm-new-password = 请再次输入新密码
+prompt-mnemonic = 请输入钱包助记词（BIP39 单词，以空格分隔）
+prompt-bip39-passphrase = 请输入 BIP39 密码短语
+prompt-confirm-passphrase = 请确认 BIP39 密码短语
+prompt-slip39-passphrase = 请输入 SLIP-39 密码短语
+prompt-shamir-first-share = 请输入一份 SLIP-39 分片：
+prompt-shamir-share = 请输入第 { $index } 份分片（共需 { $threshold } 份）：
+
+# 删除确认
+delete-about-to = 即将删除 { $file }
+delete-about-to-unreadable = 即将删除 { $file }，该文件不是可读取的 keystore
+delete-confirm-identity = 请输入钱包的别名或地址以确认：
+delete-confirm-file = 请输入钱包的文件名以确认：
+delete-expected-identity = 钱包的别名或地址
+delete-expected-file = 钱包的文件名
+
+# 显示私钥
+derive-keys-warning = 警告：即将显示私钥。任何看到私钥、终端历史或日志的人都能转走资金。
+derive-keys-confirm = 输入 "show keys" 继续：
+derive-keys-expected = 输入 "show keys" 以显示私钥
+
+# 命令失败后显示的错误提示
+hint-label = 提示
+hint-insufficient-entropy = 系统随机数生成器状态异常，请稍后再试或重启后再创建密钥。
+hint-invalid-mnemonic = 请对照 BIP39 词表检查单词及顺序，助记词应为 12、15、18、21 或 24 个单词。
+hint-invalid-private-key = 期望格式：{ $expected }。
+hint-decryption-failed = 无法解密 keystore，请检查密码以及文件是否损坏。
+hint-invalid-derivation-path = 请使用形如 m/44'/60'/0'/0 的路径，或使用 --account 和 --change。
+hint-hardware-signer = 请解锁设备、打开以太坊应用后重新连接。
+hint-file-not-found = 请检查文件名；`web3wallet list` 可列出已保存的钱包。
+hint-permission-denied = 请检查文件及其目录的权限。
+hint-file-exists = 文件已存在，请换一个路径或先删除现有文件。
+hint-invalid-format = 文件格式无效，如已损坏请从备份恢复。
+hint-path-traversal = 请使用钱包目录内的普通文件名。
+hint-invalid-parameters = 期望：{ $expected }。
+hint-conflicting-options = 选项互相冲突，请只保留其中一个。
+hint-missing-parameter = 缺少必需参数：{ $hint }
+hint-invalid-network = 已知网络：{ $supported }。可使用 `web3wallet network add` 添加其他网络。
+hint-password-mismatch = 两次输入的密码不一致，请输入相同的密码。
+hint-prompt-timeout = 请在交互式终端中运行该命令，或增大 --prompt-timeout（配置项 prompt_timeout_secs，0 表示一直等待）。
+hint-wrong-password = 密码错误，注意密码区分大小写。
+hint-weak-password = 密码要求：{ $requirements }。测试环境中可使用 --allow-weak-password 跳过此检查。
+hint-cold-storage = 冷钱包只能在离线模式（--offline）下解密并需要助记词密码。
+hint-approval-required = 审批人使用 `web3wallet approval approve` 打开请求；将其返回的令牌通过 --approval-token 传入。
+hint-approval-rejected = 请审批人为这笔交易重新签发令牌；令牌会过期，且只对应一个发送方、链、接收方、金额和 nonce。
+hint-watch-only = 观察钱包不含私钥；请使用导出该 xpub 或地址的钱包或硬件设备签名。
+hint-unlock-throttled = 密码错误次数过多；请等待锁定结束，输入正确密码后计数将被清除。
+hint-wrong-passphrase = BIP39 密码短语不随钱包保存；请输入创建或导入钱包时使用的密码短语。
+hint-network-unreachable = 请检查网络连接和 RPC 节点，或增大 --timeout。
+hint-invalid-configuration = 请使用 `web3wallet config validate` 检查配置文件。
+hint-rate-limit = 服务商正在限流，请稍后重试，或配置自己的 RPC 节点。
+hint-address-format = 期望：{ $expected }。
+hint-keystore-schema = 请运行 `web3wallet migrate` 升级旧版 keystore。
+hint-chain-mismatch = 请检查链 ID 或网络；如确需为其他链签名，请加上 --force。
+hint-version-incompatible = 该 keystore 需要 { $required } 版本，请升级 web3wallet 或运行 `web3wallet migrate`。
@@ -1,22 +1,21 @@
-use std::path::PathBuf;
-
 use crate::config::crypto::{DEFAULT_ARGON2_ITERATIONS, DEFAULT_ARGON2_MEMORY, DEFAULT_ARGON2_PARALLELISM, LOW_MEMORY_ARGON2_ITERATIONS, LOW_MEMORY_ARGON2_MEMORY};
 
 //BIP 44
 pub const DEFAULT_DERIVATION_PATH : &str = "m/44'/60'/0'/0";
 
-pub const DEFAULT_NETWORK : &str = "mainnet";
+/// Account and change are plain BIP32 indices, the account level is hardened on top
+pub const MAX_BIP32_INDEX : u32 = 0x7fff_ffff;
 
-///supported networks
-pub const SUPPORTED_NETWORKS: &[&str] = &[
-    "mainnet",
-    "sepolia",
-    "goerli",
-    "holesky"
-];
+//App-specific signing keys live under their own purpose, away from funds keys
+pub const APP_KEY_PURPOSE_PATH : &str = "m/13'";
+
+pub const DEFAULT_NETWORK : &str = "mainnet";
 
 pub const DEFAULT_WALLET_DIR : &str = ".web3wallet";
 
+/// Read from the wallet dir when `--config` is not given
+pub const DEFAULT_CONFIG_FILE : &str = "config.toml";
+
 pub const KEYSTORE_EXTENSION: &str = "json";
 
 //Cryptographic configuration
@@ -37,25 +36,472 @@ pub mod crypto{
     pub const MIN_PASSWORD_LENGTH : usize = 8;
 
     pub const MAX_PASSWORD_LENGTH: usize = 1024;
+
+    /// Estimated guesses a saved password must need, as bits; 33 is about 10^10
+    pub const MIN_PASSWORD_STRENGTH_BITS : f64 = 33.0;
+
+    /// Keystore v3 scrypt parameters, matching geth's standard and light settings
+    pub const V3_SCRYPT_LOG_N : u8 = 18;
+    pub const V3_SCRYPT_P : u32 = 1;
+    pub const V3_LIGHT_SCRYPT_LOG_N : u8 = 12;
+    pub const V3_LIGHT_SCRYPT_P : u32 = 6;
+    pub const V3_SCRYPT_R : u32 = 8;
+    pub const V3_IV_LENGTH : usize = 16;
+
+    pub const PBKDF2_ITERATIONS : u32 = 100_000;
+
+    /// Bits the Linux kernel reports as available in its input pool
+    pub const LINUX_ENTROPY_AVAIL_PATH : &str = "/proc/sys/kernel/random/entropy_avail";
+    /// Hashes of recent RNG samples, kept under the state dir to spot a replayed VM snapshot
+    pub const ENTROPY_FINGERPRINT_FILE : &str = "entropy-fingerprints";
+    pub const ENTROPY_FINGERPRINT_HISTORY : usize = 32;
+}
+
+pub mod kdf_bench {
+    pub const DEFAULT_TARGET_MS: u64 = 500;
+
+    /// Cheap probe runs, extrapolated to the target latency
+    pub const ARGON2_PROBE_MEMORY: u32 = 8_192;
+    pub const SCRYPT_PROBE_LOG_N: u8 = 10;
+    pub const PBKDF2_PROBE_ITERATIONS: u32 = 10_000;
+
+    /// Suggestions never go below these floors, however slow the machine
+    pub const ARGON2_MIN_MEMORY: u32 = super::crypto::LOW_MEMORY_ARGON2_MEMORY;
+    pub const ARGON2_MAX_MEMORY: u32 = 2_097_152;
+    pub const SCRYPT_MIN_LOG_N: u8 = super::crypto::V3_LIGHT_SCRYPT_LOG_N;
+    pub const SCRYPT_MAX_LOG_N: u8 = 22;
+    pub const PBKDF2_MIN_ITERATIONS: u32 = super::crypto::PBKDF2_ITERATIONS;
+}
+
+pub mod cold {
+    /// Well-known resolvers probed to detect network connectivity
+    pub const CONNECTIVITY_PROBES: &[&str] = &["1.1.1.1:53", "8.8.8.8:53", "9.9.9.9:53"];
+    pub const CONNECTIVITY_TIMEOUT_MS: u64 = 500;
+}
+
+pub mod chains {
+    /// Built-in EVM chains: (name, chain id, public RPC endpoint, canonical wrapped native token,
+    /// block explorer, native currency symbol). More can be added under `[networks]` in the config file.
+    pub const DEFAULT_EVM_CHAINS: &[(&str, u64, &str, &str, &str, &str)] = &[
+        ("mainnet", 1, "https://ethereum-rpc.publicnode.com", "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", "https://etherscan.io", "ETH"),
+        ("sepolia", 11_155_111, "https://ethereum-sepolia-rpc.publicnode.com", "0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14", "https://sepolia.etherscan.io", "ETH"),
+        ("holesky", 17_000, "https://ethereum-holesky-rpc.publicnode.com", "0x94373a4919B3240D86eA41593D5eBa789FEF3848", "https://holesky.etherscan.io", "ETH"),
+        ("optimism", 10, "https://mainnet.optimism.io", "0x4200000000000000000000000000000000000006", "https://optimistic.etherscan.io", "ETH"),
+        ("arbitrum", 42_161, "https://arb1.arbitrum.io/rpc", "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1", "https://arbiscan.io", "ETH"),
+        ("base", 8_453, "https://mainnet.base.org", "0x4200000000000000000000000000000000000006", "https://basescan.org", "ETH"),
+        ("polygon", 137, "https://polygon-rpc.com", "0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270", "https://polygonscan.com", "POL"),
+    ];
+
+    /// Currency symbol of config-defined networks that do not set one
+    pub const DEFAULT_CURRENCY_SYMBOL: &str = "ETH";
+    pub const MAX_NETWORK_NAME_LENGTH: usize = 32;
+}
+
+pub mod explorer {
+    /// Etherscan-compatible indexer, v2 API serves every chain id
+    pub const DEFAULT_API_URL: &str = "https://api.etherscan.io/v2/api";
+    pub const API_KEY_ENV: &str = "ETHERSCAN_API_KEY";
+    /// Transactions `history` lists unless --limit says otherwise
+    pub const DEFAULT_HISTORY_LIMIT: usize = 25;
+    /// Etherscan serves at most this many results per query
+    pub const MAX_HISTORY_LIMIT: usize = 10_000;
+}
+
+pub mod fees {
+    pub const FEE_HISTORY_BLOCKS: u64 = 10;
+    /// Reward percentiles used as the suggested priority fee per tier (slow, standard, fast)
+    pub const PRIORITY_FEE_PERCENTILES: [u64; 3] = [10, 50, 90];
+    pub const MIN_PRIORITY_FEE_WEI: u64 = 1_000_000_000;
+    pub const BASE_FEE_MULTIPLIER: u64 = 2;
+}
+
+pub mod wallet {
+    /// Per-wallet overrides live next to the keystore, `<wallet>.toml`
+    pub const OVERRIDES_EXTENSION: &str = "toml";
+    /// Metadata cache of the wallets directory, see `services::wallet_index`
+    pub const INDEX_FILE_NAME: &str = ".wallets.index";
+    /// Indexes of another version are rebuilt
+    pub const INDEX_VERSION: u32 = 1;
+    /// Copies of a keystore taken before it is rewritten, `<wallet>.json.<UTC time>.bak`
+    pub const BACKUP_EXTENSION: &str = "bak";
+    pub const BACKUP_TIME_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+    /// Backups kept per keystore unless `keystore_backups` says otherwise; 0 keeps none
+    pub const DEFAULT_KEYSTORE_BACKUPS: u32 = 0;
+    /// Encrypted archives of the wallets directory made by `backup create`
+    pub const ARCHIVE_FORMAT: &str = "web3wallet-archive";
+    pub const ARCHIVE_VERSION: u32 = 1;
+    /// Name `account select` takes for the account a keystore was created with
+    pub const PRIMARY_ACCOUNT_NAME: &str = "main";
+    pub const MAX_ACCOUNT_NAME_LENGTH: usize = 64;
+}
+
+pub mod throttle {
+    /// Failed unlocks of the keystores in a directory, see `services::unlock_throttle`
+    pub const ATTEMPTS_FILE_NAME: &str = ".unlock-attempts";
+    /// Wrong passwords allowed before a keystore is locked
+    pub const FREE_ATTEMPTS: u32 = 5;
+    /// Lockout after the first failure past the free ones, doubling with each further one
+    pub const BASE_DELAY_SECS: u64 = 30;
+    pub const MAX_DELAY_SECS: u64 = 3_600;
+    /// Failures older than this are forgotten
+    pub const RESET_AFTER_SECS: i64 = 86_400;
+}
+
+pub mod tokens {
+    pub const REGISTRY_FILE_NAME: &str = "tokens.json";
+    pub const DEFAULT_TOKEN_LIST_URL: &str = "https://tokens.uniswap.org";
+    /// Well-known tokens found without registering them: (chain id, address, symbol, name,
+    /// decimals). Tokens added with `tokens add` take precedence.
+    pub const BUNDLED_TOKENS: &[(u64, &str, &str, &str, u8)] = &[
+        (1, "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", "USDC", "USD Coin", 6),
+        (1, "0xdAC17F958D2ee523a2206206994597C13D831ec7", "USDT", "Tether USD", 6),
+        (1, "0x6B175474E89094C44Da98b954EedeAC495271d0F", "DAI", "Dai Stablecoin", 18),
+        (1, "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", "WETH", "Wrapped Ether", 18),
+        (1, "0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599", "WBTC", "Wrapped BTC", 8),
+        (11_155_111, "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238", "USDC", "USD Coin", 6),
+        (10, "0x0b2C639c533813f4Aa9D7837CAf62653d097Ff85", "USDC", "USD Coin", 6),
+        (42_161, "0xaf88d065e77c8cC2239327C5EDb3A432268e5831", "USDC", "USD Coin", 6),
+        (8_453, "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913", "USDC", "USD Coin", 6),
+        (137, "0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359", "USDC", "USD Coin", 6),
+    ];
+}
+
+pub mod quote {
+    /// 0x v2 indicative price endpoint
+    pub const ZEROEX_API_URL: &str = "https://api.0x.org/swap/permit2/price";
+    /// 1inch v6 quote endpoint, `{chain_id}` is substituted per request
+    pub const ONEINCH_API_URL: &str = "https://api.1inch.dev/swap/v6.0/{chain_id}/quote";
+    pub const API_URL_ENV: &str = "QUOTE_API_URL";
+    pub const API_KEY_ENV: &str = "QUOTE_API_KEY";
+    /// Placeholder both aggregators accept for the native currency
+    pub const NATIVE_TOKEN_ADDRESS: &str = "0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE";
+    pub const NATIVE_SYMBOL: &str = "ETH";
+}
+
+pub mod ens {
+    /// ENS registry, same address on mainnet and the testnets
+    pub const REGISTRY_ADDRESS: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+    pub const REVERSE_SUFFIX: &str = "addr.reverse";
+    /// Names are resolved against L1 whatever chain the command targets
+    pub const RESOLUTION_CHAIN: &str = "mainnet";
+}
+
+pub mod rpc {
+    /// Upper bound of calls per JSON-RPC batch, most providers cap at 100
+    pub const MAX_BATCH_SIZE: usize = 100;
+
+    pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+    pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+    pub const DEFAULT_BACKOFF_MS: u64 = 500;
+    pub const MAX_BACKOFF_MS: u64 = 10_000;
+}
+
+pub mod cache {
+    pub const CACHE_FILE_NAME: &str = "rpc-cache.json";
+
+    /// Reads that never change for a given chain, with their TTL in seconds
+    pub const CACHEABLE_METHODS: &[(&str, u64)] = &[
+        ("eth_chainId", 86_400),
+        ("net_version", 86_400),
+    ];
+
+    /// TTLs for explicit `call_cached` reads
+    pub const TOKEN_METADATA_TTL_SECS: u64 = 7 * 86_400;
+    pub const ENS_TTL_SECS: u64 = 3_600;
+    pub const ABI_TTL_SECS: u64 = 86_400;
+}
+
+pub mod usage {
+    pub const USAGE_FILE_NAME: &str = "usage.json";
+    /// Recent events and anomaly flags kept per wallet
+    pub const HISTORY_LIMIT: usize = 100;
+    /// Signs needed before the hour-of-day profile is trusted
+    pub const MIN_SIGNS_FOR_PROFILE: u64 = 20;
+    /// An hour holding less than this share of past signs is unusual
+    pub const UNUSUAL_HOUR_SHARE: f64 = 0.02;
+    /// Exporting after this long without use is flagged
+    pub const DORMANT_DAYS: i64 = 30;
+}
+
+pub mod history {
+    pub const HISTORY_FILE_NAME: &str = "history.json";
+    /// Decimals of the fiat columns in exports
+    pub const FIAT_DECIMALS: usize = 2;
+}
+
+pub mod nonces {
+    pub const NONCES_FILE_NAME: &str = "nonces.json";
+    /// `sign-tx --nonce` value that signs with the tracked nonce
+    pub const AUTO: &str = "auto";
+}
+
+pub mod prices {
+    pub const DEFAULT_COINGECKO_API_URL: &str = "https://api.coingecko.com/api/v3";
+    pub const DEFAULT_CRYPTOCOMPARE_API_URL: &str = "https://min-api.cryptocompare.com/data";
+    pub const API_KEY_ENV: &str = "PRICE_API_KEY";
+    /// Chains whose native currency has a market price; testnet coins are never priced
+    pub const PRICED_CHAIN_IDS: &[u64] = &[1, 10, 42_161, 8_453, 137];
+    /// CoinGecko coin id per currency symbol
+    pub const COINGECKO_IDS: &[(&str, &str)] = &[("ETH", "ethereum"), ("POL", "polygon-ecosystem-token")];
+    /// Daily prices of past days do not change; today's is the latest price and moves
+    pub const PAST_DAY_TTL_SECS: u64 = 30 * 86_400;
+    pub const TODAY_TTL_SECS: u64 = 600;
+}
+
+pub mod approval {
+    /// First line of the message approvers sign, so a token cannot double as another signature
+    pub const MESSAGE_HEADER: &str = "web3wallet approval";
+    pub const DEFAULT_TOKEN_TTL_SECS: u64 = 3_600;
+    pub const NOTIFICATION_TITLE: &str = "web3wallet approval request";
+    pub const WEBHOOK_EVENT: &str = "web3wallet.approval_request";
+}
+
+/// EIP-2612 permits on the token itself, see `services::permit`
+pub mod permit {
+    /// Domain version of tokens without a version(), as OpenZeppelin's ERC20Permit uses
+    pub const DEFAULT_DOMAIN_VERSION: &str = "1";
+}
+
+/// Uniswap Permit2, see `services::permit2`
+pub mod permit2 {
+    /// Same address on every chain Permit2 is deployed to
+    pub const ADDRESS: &str = "0x000000000022D473030F116dDEE9F6B43aC78BA3";
+    pub const DOMAIN_NAME: &str = "Permit2";
+    /// Allowance lifetime when --expiration is not given
+    pub const DEFAULT_EXPIRATION_SECS: u64 = 30 * 86_400;
+    /// Time the dapp gets to submit the signature when --sig-deadline is not given
+    pub const DEFAULT_SIG_DEADLINE_SECS: u64 = 1_800;
+    pub const MAX_BATCH: usize = 32;
+}
+
+/// Local M-of-N multisig, see `services::multisig`
+pub mod multisig {
+    /// `type` of a multisig keystore, which tells it apart from single-key keystores
+    pub const KEYSTORE_TYPE: &str = "multisig";
+    pub const KEYSTORE_VERSION: u32 = 1;
+    /// Owners sign this to derive their companion key; never sign it anywhere else
+    pub const COMPANION_KEY_MESSAGE: &str = "web3wallet multisig companion key";
+    /// First line of the message owners sign to approve, so it cannot double as another signature
+    pub const APPROVAL_HEADER: &str = "web3wallet multisig approval";
+}
+
+/// `safe sign`
+pub mod safe {
+    /// `origin` of proposals for the Safe transaction service, shown in the Safe app
+    pub const ORIGIN: &str = "web3wallet-cli";
+}
+
+/// `wallet fixtures`, pinned derivations for downstream test suites
+pub mod fixtures {
+    /// Public test mnemonics by short name; anyone can sweep funds sent to them
+    pub const TEST_MNEMONICS: &[(&str, &str)] = &[
+        ("abandon", "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"),
+        ("hardhat", "test test test test test test test test test test test junk"),
+        ("legal", "legal winner thank year wave sausage worth useful legal winner thank yellow"),
+        ("letter", "letter advice cage absurd amount doctor acoustic avoid letter advice cage above"),
+    ];
+    pub const DEFAULT_COUNT: u32 = 20;
+    pub const MAX_COUNT: u32 = 1_000;
+    /// Bumped whenever the layout of the fixtures file changes
+    pub const FORMAT_VERSION: u32 = 1;
+}
+
+/// Derivation paths of other wallets for `--path-preset`, `{index}` being the address number
+pub mod path_presets {
+    pub const METAMASK: &str = "m/44'/60'/0'/0/{index}";
+    pub const LEDGER_LIVE: &str = "m/44'/60'/{index}'/0/0";
+    pub const LEGACY: &str = "m/44'/60'/0'/{index}";
+    pub const MEW: &str = "m/44'/60'/0'/0/{index}";
+}
+
+/// `discover`, BIP44 account discovery, see `services::discovery`
+pub mod discovery {
+    /// Unused addresses in a row that end the scan of an account, the BIP44 gap limit
+    pub const DEFAULT_GAP_LIMIT: u32 = 20;
+    /// Accounts walked at most, in case an RPC reports activity everywhere
+    pub const MAX_ACCOUNTS: u32 = 100;
+}
+
+pub mod shutdown {
+    /// Time in-flight operations get to finish after SIGINT/SIGTERM, inside the
+    /// 30 second default most orchestrators allow before killing the process
+    pub const DRAIN_GRACE_SECS: u64 = 25;
+}
+
+pub mod output {
+    /// Table output longer than this goes through the pager when stdout is a terminal
+    pub const PAGER_MIN_LINES: usize = 50;
+    /// Used when $PAGER is unset; -F quits at once if everything fits
+    pub const DEFAULT_PAGER: &str = "less -FRX";
+    /// Lines written per flush, so long outputs start showing before they are complete
+    pub const CHUNK_LINES: usize = 256;
+}
+
+pub mod genpass {
+    /// Characters of a `genpass` password unless --length says otherwise
+    pub const DEFAULT_LENGTH: usize = 24;
+    pub const PASSWORD_ALPHABET: &str =
+        "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!@#%^*()_+-=[]{}:,.?";
+    /// Passphrase words are BIP39 English words, 11 bits each
+    pub const MIN_WORDS: usize = 4;
+    pub const MAX_WORDS: usize = 64;
+    pub const WORD_SEPARATOR: &str = "-";
+}
+
+pub mod prompt {
+    /// Interactive prompts give up after this long without an answer; 0 waits forever
+    pub const DEFAULT_TIMEOUT_SECS: u64 = 300;
+}
+
+pub mod i18n {
+    /// Checked in order for the interface language when `--lang` is not given
+    pub const LOCALE_ENV_VARS: &[&str] = &["LC_ALL", "LC_MESSAGES", "LANG"];
 }
 
 pub mod fs {
     pub const KEYSTORE_FILE_PERMISSIONS: u32 = 0o600;
-
+    /// What `doctor` expects of the wallets directory
+    pub const WALLETS_DIR_PERMISSIONS: u32 = 0o700;
 }
 
 //BIP 39 configuration
 pub mod bip39 {
-    pub const SUPPORTED_WORD_COUNTS : &[u8] = &[12, 24];
+    pub const SUPPORTED_WORD_COUNTS : &[u8] = &[12, 15, 18, 21, 24];
     pub const DEFAULT_WORD_COUNT : u8 = 12;
+    /// Words asked for when checking a written-down backup
+    pub const QUIZ_QUESTIONS: usize = 3;
 
     pub const ENTROPY_BITS_12: u32 = 128;
+    pub const ENTROPY_BITS_15: u32 = 160;
+    pub const ENTROPY_BITS_18: u32 = 192;
+    pub const ENTROPY_BITS_21: u32 = 224;
     pub const ENTROPY_BITS_24: u32 = 256;
+
+    /// PBKDF2-HMAC-SHA512 output, the seed BIP32 derives from
+    pub const SEED_LENGTH: usize = 64;
+}
+
+pub mod bip32 {
+    /// Seed length bounds from BIP32
+    pub const MIN_SEED_LENGTH: usize = 16;
+    pub const MAX_SEED_LENGTH: usize = 64;
+}
+
+/// BIP85 deterministic entropy for child BIP39 mnemonics
+pub mod bip85 {
+    pub const PURPOSE_PATH: &str = "m/83696968'";
+    pub const BIP39_APPLICATION: u32 = 39;
+    /// HMAC-SHA512 key turning the derived private key into entropy
+    pub const ENTROPY_HMAC_KEY: &[u8] = b"bip-entropy-from-k";
+    /// BIP85 defines child mnemonics of these lengths only
+    pub const WORD_COUNTS: &[u8] = &[12, 18, 24];
+}
+
+/// `m/83696968'/39'/{language}'/{words}'/{index}'`, where the child mnemonic of `words` words
+/// lives; `language` is the BIP85 code of the wordlist, 0 for English
+pub fn bip85_mnemonic_path(language: u32, words: u8, index: u32) -> String {
+    format!(
+        "{}/{}'/{}'/{}'/{}'",
+        bip85::PURPOSE_PATH,
+        bip85::BIP39_APPLICATION,
+        language,
+        words,
+        index
+    )
+}
+
+/// Process exit status per error class, see `WalletError::exit_code`; scripts branch on these
+/// instead of parsing messages, so they never change meaning
+pub mod exit_codes {
+    pub const SUCCESS: i32 = 0;
+    /// Errors outside the classes below, and a shutdown that did not drain in time
+    pub const GENERAL: i32 = 1;
+    /// Bad arguments or input, also clap's status for usage errors
+    pub const USER_INPUT: i32 = 2;
+    pub const AUTHENTICATION: i32 = 3;
+    pub const CRYPTOGRAPHIC: i32 = 4;
+    pub const FILESYSTEM: i32 = 5;
+    pub const NETWORK: i32 = 6;
+    /// Keystore schema, version or chain checks
+    pub const VALIDATION: i32 = 7;
+}
+
+/// `wallet score`, see `services::health`
+pub mod health {
+    /// A password older than this is due for `passwd`
+    pub const PASSWORD_MAX_AGE_DAYS: i64 = 365;
+
+    /// Points off 100 per finding
+    pub const WEAK_KDF_PENALTY: u32 = 30;
+    pub const OUTDATED_KDF_PENALTY: u32 = 15;
+    pub const OLD_PASSWORD_PENALTY: u32 = 10;
+    pub const UNVERIFIED_BACKUP_PENALTY: u32 = 25;
+    pub const OPEN_PERMISSIONS_PENALTY: u32 = 20;
+    pub const PLAINTEXT_RPC_PENALTY: u32 = 10;
+
+    /// Lowest score of each grade, F below the last
+    pub const GRADES: &[(u32, &str)] = &[(90, "A"), (80, "B"), (70, "C"), (60, "D")];
+}
+
+/// Password and key caching agent, see `services::agent`
+pub mod agent {
+    pub const PID_FILE_NAME: &str = "agent.pid";
+    pub const SOCKET_FILE_NAME: &str = "agent.sock";
+    /// Owner-only access to the pid file and socket
+    pub const FILE_PERMISSIONS: u32 = 0o600;
+    /// How long a password stays cached after the unlock that stored it
+    pub const DEFAULT_TTL_SECS: u64 = 900;
+    /// Commands fall back to prompting when the agent does not answer within this time
+    pub const RESPONSE_TIMEOUT_MS: u64 = 500;
+    /// How long a signing request may wait for the agent to decrypt the keystore and for
+    /// the user's confirmation
+    pub const SIGN_TIMEOUT_SECS: u64 = 90;
+    /// An unanswered confirmation at the agent's terminal counts as declined after this time
+    pub const CONFIRM_TIMEOUT_SECS: u64 = 60;
+}
+
+/// JSON-RPC server of `web3wallet serve`
+pub mod serve {
+    pub const DEFAULT_LISTEN: &str = "127.0.0.1:8575";
+    /// Bearer token clients send, kept in the state directory unless `--token-file` says otherwise
+    pub const TOKEN_FILE_NAME: &str = "serve.token";
+    pub const TOKEN_BYTES: usize = 32;
+    /// Request line and headers together
+    pub const MAX_HEADER_BYTES: u64 = 16 * 1024;
+    pub const MAX_BODY_BYTES: u64 = 1024 * 1024;
+    /// A client that has not sent its whole request by then is disconnected
+    pub const READ_TIMEOUT_SECS: u64 = 10;
+    pub const MAX_DERIVE_COUNT: u32 = 100;
+}
+
+/// SLIP-39 Shamir backups
+pub mod slip39 {
+    /// Master secrets are at least 128 bits and an even number of bytes
+    pub const MIN_SECRET_LENGTH: usize = 16;
+    pub const MAX_SHARES: u8 = 16;
+    /// PBKDF2 runs `BASE_ITERATIONS << e` iterations spread over the Feistel rounds
+    pub const ITERATION_EXPONENT: u8 = 1;
+    pub const BASE_ITERATIONS: u32 = 10_000;
+    pub const ROUND_COUNT: u8 = 4;
+    pub const DIGEST_LENGTH: usize = 4;
+    pub const CUSTOMIZATION: &str = "shamir";
+    pub const EXTENDABLE_CUSTOMIZATION: &str = "shamir_extendable";
+}
+
+/// Interactive terminal UI, see the `tui` subcommand
+pub mod tui {
+    /// How often the screen is redrawn while no key is pressed, in milliseconds
+    pub const TICK_MS: u64 = 250;
+    /// Most addresses the derive form lists at once
+    pub const MAX_DERIVE_COUNT: u32 = 100;
 }
 
 pub fn entropy_bits_for_word_count(count: u8) -> Option<usize> {
     match count {
         12 => Some(bip39::ENTROPY_BITS_12 as usize),
+        15 => Some(bip39::ENTROPY_BITS_15 as usize),
+        18 => Some(bip39::ENTROPY_BITS_18 as usize),
+        21 => Some(bip39::ENTROPY_BITS_21 as usize),
         24 => Some(bip39::ENTROPY_BITS_24 as usize),
         _ => None,
     }
@@ -65,8 +511,17 @@ pub fn is_supported_word_count(count: u8) -> bool {
     bip39::SUPPORTED_WORD_COUNTS.contains(&count)
 }
 
-pub fn is_supported_network(network: &str) -> bool{
-    SUPPORTED_NETWORKS.contains(&network)
+/// `m/44'/60'/{account}'/{change}`, the base path addresses are indexed under
+pub fn bip44_base_path(account: u32, change: u32) -> String {
+    format!("m/44'/60'/{}'/{}", account, change)
+}
+
+/// Lowercase letters, digits, `-` and `_`; whether the network exists depends on the config,
+/// see `ChainInfo::resolve`
+pub fn is_valid_network_name(network: &str) -> bool{
+    !network.is_empty()
+        && network.len() <= chains::MAX_NETWORK_NAME_LENGTH
+        && network.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
 }
 
 pub fn get_argon2_config(use_low_memory: bool) -> (u32, u32, u32){
@@ -0,0 +1,729 @@
+use crate::config;
+use crate::errors::{NetworkError, WalletError, WalletResult};
+use crate::models::keystore::KdfAlgorithm;
+use crate::services::chains::{ChainInfo, ExplorerApi};
+use crate::services::crypto::KdfSettings;
+use crate::services::approval::ApprovalSettings;
+use crate::services::fees::FeeTier;
+use crate::services::prices::PriceSource;
+use crate::services::rate_limit::SigningLimits;
+use crate::WalletConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// On-disk TOML configuration; every field is optional and layered over the defaults
+///
+/// ```toml
+/// network = "sepolia"
+/// wallets_path = "~/.web3wallet/wallets"
+///
+/// [kdf]
+/// memory = 65536
+///
+/// [rpc]
+/// timeout_secs = 10
+///
+/// [rpc.endpoints]
+/// sepolia = "https://sepolia.example.org"
+///
+/// [networks.gnosis]
+/// chain_id = 100
+/// rpc_url = "https://rpc.gnosischain.com"
+/// currency_symbol = "xDAI"
+/// ```
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub network: Option<String>,
+    pub wallets_path: Option<String>,
+    pub state_path: Option<String>,
+    pub offline: Option<bool>,
+    pub track_usage: Option<bool>,
+    pub record_history: Option<bool>,
+    pub prompt_timeout_secs: Option<u64>,
+    pub use_agent: Option<bool>,
+    pub keystore_backups: Option<u32>,
+    pub strict_permissions: Option<bool>,
+    pub harden_memory: Option<bool>,
+    pub unlock_throttle: Option<bool>,
+    pub fee_tier: Option<FeeTier>,
+    pub policy_profile: Option<String>,
+    #[serde(default)]
+    pub kdf: KdfSection,
+    #[serde(default)]
+    pub rpc: RpcSection,
+    #[serde(default)]
+    pub explorer: ExplorerSection,
+    #[serde(default)]
+    pub quote: ApiSection,
+    #[serde(default)]
+    pub prices: PricesSection,
+    #[serde(default)]
+    pub rate_limit: RateLimitSection,
+    #[serde(default, skip_serializing_if = "ApprovalSettings::is_empty")]
+    pub approval: ApprovalSettings,
+    /// Chain name to minimum balance in ether units, see `monitor`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub min_balances: BTreeMap<String, String>,
+    /// Chains beyond the built-in ones, keyed by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub networks: BTreeMap<String, NetworkSection>,
+}
+
+/// A chain defined in the config file, see `network add`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkSection {
+    pub chain_id: u64,
+    pub rpc_url: String,
+    pub explorer_url: Option<String>,
+    pub currency_symbol: Option<String>,
+    /// WETH-style wrapper, needed by `wrap`/`unwrap`
+    pub wrapped_native: Option<String>,
+}
+
+impl NetworkSection {
+    fn to_chain(&self, name: &str) -> ChainInfo {
+        ChainInfo {
+            name: name.to_string(),
+            chain_id: self.chain_id,
+            rpc_url: self.rpc_url.clone(),
+            wrapped_native: self.wrapped_native.clone(),
+            explorer_url: self.explorer_url.clone(),
+            currency_symbol: self
+                .currency_symbol
+                .clone()
+                .unwrap_or_else(|| config::chains::DEFAULT_CURRENCY_SYMBOL.to_string()),
+            custom: true,
+        }
+    }
+
+    fn from_chain(chain: &ChainInfo) -> Self {
+        Self {
+            chain_id: chain.chain_id,
+            rpc_url: chain.rpc_url.clone(),
+            explorer_url: chain.explorer_url.clone(),
+            currency_symbol: Some(chain.currency_symbol.clone()),
+            wrapped_native: chain.wrapped_native.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KdfSection {
+    pub algorithm: Option<KdfAlgorithm>,
+    pub iterations: Option<u32>,
+    pub memory: Option<u32>,
+    pub parallelism: Option<u32>,
+    pub scrypt_log_n: Option<u8>,
+    pub pbkdf2_iterations: Option<u32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RpcSection {
+    pub timeout_secs: Option<u64>,
+    pub max_attempts: Option<u32>,
+    pub backoff_ms: Option<u64>,
+    pub jitter: Option<bool>,
+    /// Chain name to RPC URL
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub endpoints: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitSection {
+    /// Per caller identity
+    #[serde(default, skip_serializing_if = "SigningLimits::is_unlimited")]
+    pub identity: SigningLimits,
+    /// Across all callers
+    #[serde(default, skip_serializing_if = "SigningLimits::is_unlimited")]
+    pub global: SigningLimits,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiSection {
+    pub api_url: Option<String>,
+    pub api_key: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExplorerSection {
+    pub api_url: Option<String>,
+    pub api_key: Option<String>,
+    /// Chain name to its own Etherscan-compatible API
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub networks: BTreeMap<String, ExplorerApi>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PricesSection {
+    pub source: Option<PriceSource>,
+    pub api_url: Option<String>,
+    pub api_key: Option<String>,
+    /// CSV of fixed rates for the `file` source
+    pub rates_file: Option<String>,
+}
+
+impl WalletConfig {
+    /// `~/.web3wallet/config.toml`, read when `--config` is not given
+    pub fn default_config_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(config::DEFAULT_WALLET_DIR)
+            .join(config::DEFAULT_CONFIG_FILE)
+    }
+
+    /// Commented config file documenting every key with its built-in default, for `config init`
+    pub fn template() -> String {
+        let defaults = Self::default();
+        format!(
+            r#"# web3wallet configuration
+# Every key is optional; the commented values are the built-in defaults.
+
+# Network used when a command is given no --network/--chain
+# network = "{network}"
+
+# Encrypted keystores, and caches and other non-secret state
+# wallets_path = "~/{dir}/wallets"
+# state_path = "~/{dir}/state"
+
+# Refuse network access, same as --offline
+# offline = false
+
+# Count unlocks, signs and exports per wallet and flag unusual use (`wallet usage`)
+# track_usage = true
+
+# Record signed transactions for bookkeeping exports (`wallet history export`)
+# record_history = true
+
+# Abort when a password or confirmation prompt gets no answer for this long, 0 waits forever
+# prompt_timeout_secs = {prompt_timeout}
+
+# Take wallet passwords from a running `web3wallet agent` and hand it the ones typed in
+# use_agent = true
+
+# Keep this many timestamped copies of a keystore from before each rewrite (`restore-backup`)
+# keystore_backups = {keystore_backups}
+
+# Refuse to unlock keystores readable by other users instead of warning, as `--strict` does
+# strict_permissions = false
+
+# Disable core dumps and lock mnemonics and decrypted keys in memory, as `--harden` does
+# harden_memory = false
+
+# Lock a keystore for a growing delay after repeated wrong passwords
+# unlock_throttle = true
+
+# Fee tier for estimated transactions: slow, standard or fast
+# fee_tier = "standard"
+
+# policy_profile = "default"
+
+[kdf]
+# KDF for new keystores: argon2id, scrypt or pbkdf2 (also --kdf)
+# algorithm = "argon2id"
+# Argon2id parameters. Memory is in KiB: more memory makes a stolen keystore
+# costlier to brute-force, at the price of slower unlocking.
+# iterations = {iterations}
+# memory = {memory}
+# parallelism = {parallelism}
+# scrypt cost as log2(N), for where Argon2's memory use is a problem
+# scrypt_log_n = {scrypt_log_n}
+# pbkdf2_iterations = {pbkdf2_iterations}
+# `web3wallet kdf-bench --write` tunes these for a target unlock time
+
+[rpc]
+# timeout_secs = {timeout}
+# max_attempts = {attempts}
+# backoff_ms = {backoff}
+# jitter = true
+
+[rpc.endpoints]
+# Replace a chain's built-in public RPC endpoint
+# mainnet = "https://ethereum-rpc.publicnode.com"
+
+[explorer]
+# Etherscan-compatible API for history and activity; the key may also come from {explorer_env}
+# api_url = "{explorer_url}"
+# api_key = ""
+
+# A chain's own Etherscan-compatible API, e.g. Blockscout; without api_url only the key changes
+# [explorer.networks.gnosis]
+# api_url = "https://gnosis.blockscout.com/api"
+# api_key = ""
+
+[quote]
+# Swap aggregator for `quote`; the key may also come from {quote_env}
+# api_url = "{quote_url}"
+# api_key = ""
+
+[prices]
+# Daily historical prices for `wallet history export --fiat`: coingecko, cryptocompare, or
+# file for fixed rates without network access. The key may also come from {prices_env}
+# source = "coingecko"
+# api_url = "{prices_url}"
+# api_key = ""
+# CSV of date,asset,fiat,price rows for the file source; a date of * applies to every day
+# rates_file = "~/{dir}/rates.csv"
+
+[rate_limit.identity]
+# Signing operations each caller identity may request from a long-running signer
+# per_minute = 10
+# per_hour = 100
+
+[rate_limit.global]
+# Signing operations across all callers
+# per_minute = 60
+
+[approval]
+# Transactions moving at least this much native currency need a token from a second person
+# threshold = "1.0"
+# approvers = ["0x..."]
+# Companion key requests are sealed to, from `web3wallet approval keygen`
+# public_key = ""
+# Push sealed requests to an ntfy topic or a webhook; without a url they are printed instead
+# channel = "ntfy"
+# url = "https://ntfy.sh/<private topic>"
+# token_ttl_secs = {approval_ttl}
+
+[min_balances]
+# Native balance per chain that `monitor` alerts below
+# mainnet = "0.05"
+
+# Chains beyond the built-in ones, also managed with `web3wallet network add/remove`
+# [networks.gnosis]
+# chain_id = 100
+# rpc_url = "https://rpc.gnosischain.com"
+# explorer_url = "https://gnosisscan.io"
+# currency_symbol = "xDAI"
+# wrapped_native = "0xe91D153E0b41518A2Ce8Dd3D7944Fa863463a97d"
+"#,
+            network = defaults.network,
+            dir = config::DEFAULT_WALLET_DIR,
+            iterations = defaults.kdf_iterations,
+            memory = defaults.kdf_memory,
+            parallelism = defaults.kdf_parallelism,
+            scrypt_log_n = defaults.kdf_scrypt_log_n,
+            pbkdf2_iterations = defaults.kdf_pbkdf2_iterations,
+            prompt_timeout = defaults.prompt_timeout_secs,
+            keystore_backups = defaults.keystore_backups,
+            approval_ttl = config::approval::DEFAULT_TOKEN_TTL_SECS,
+            timeout = config::rpc::DEFAULT_TIMEOUT_SECS,
+            attempts = config::rpc::DEFAULT_MAX_ATTEMPTS,
+            backoff = config::rpc::DEFAULT_BACKOFF_MS,
+            explorer_env = config::explorer::API_KEY_ENV,
+            explorer_url = config::explorer::DEFAULT_API_URL,
+            quote_env = config::quote::API_KEY_ENV,
+            quote_url = config::quote::ZEROEX_API_URL,
+            prices_env = config::prices::API_KEY_ENV,
+            prices_url = config::prices::DEFAULT_COINGECKO_API_URL,
+        )
+    }
+
+    /// The effective configuration as TOML, with API keys redacted
+    pub fn to_toml(&self) -> WalletResult<String> {
+        toml::to_string_pretty(&self.redacted()).map_err(|e| invalid("config", e.to_string()))
+    }
+
+    /// The effective configuration as JSON, with API keys redacted
+    pub fn to_json(&self) -> WalletResult<serde_json::Value> {
+        Ok(serde_json::to_value(self.redacted())?)
+    }
+
+    fn redacted(&self) -> ConfigFile {
+        let redact = |key: &Option<String>| key.as_ref().map(|_| "<redacted>".to_string());
+        ConfigFile {
+            network: Some(self.network.clone()),
+            wallets_path: Some(self.wallets_path.display().to_string()),
+            state_path: Some(self.state_path.display().to_string()),
+            offline: Some(self.offline),
+            track_usage: Some(self.track_usage),
+            record_history: Some(self.record_history),
+            prompt_timeout_secs: Some(self.prompt_timeout_secs),
+            use_agent: Some(self.use_agent),
+            keystore_backups: Some(self.keystore_backups),
+            strict_permissions: Some(self.strict_permissions),
+            harden_memory: Some(self.harden_memory),
+            unlock_throttle: Some(self.unlock_throttle),
+            fee_tier: Some(self.fee_tier),
+            policy_profile: self.policy_profile.clone(),
+            kdf: KdfSection {
+                algorithm: Some(self.kdf),
+                iterations: Some(self.kdf_iterations),
+                memory: Some(self.kdf_memory),
+                parallelism: Some(self.kdf_parallelism),
+                scrypt_log_n: Some(self.kdf_scrypt_log_n),
+                pbkdf2_iterations: Some(self.kdf_pbkdf2_iterations),
+            },
+            rpc: RpcSection {
+                timeout_secs: Some(self.rpc_timeout_secs),
+                max_attempts: Some(self.rpc_max_attempts),
+                backoff_ms: Some(self.rpc_backoff_ms),
+                jitter: Some(self.rpc_jitter),
+                endpoints: self.rpc_overrides.clone(),
+            },
+            explorer: ExplorerSection {
+                api_url: Some(self.explorer_api_url.clone()),
+                api_key: redact(&self.explorer_api_key),
+                networks: self
+                    .explorer_overrides
+                    .iter()
+                    .map(|(chain, api)| {
+                        let api = ExplorerApi {
+                            api_url: api.api_url.clone(),
+                            api_key: redact(&api.api_key),
+                        };
+                        (chain.clone(), api)
+                    })
+                    .collect(),
+            },
+            quote: ApiSection {
+                api_url: self.quote_api_url.clone(),
+                api_key: redact(&self.quote_api_key),
+            },
+            prices: PricesSection {
+                source: Some(self.price_source),
+                api_url: self.price_api_url.clone(),
+                api_key: redact(&self.price_api_key),
+                rates_file: self.price_rates_file.as_ref().map(|path| path.display().to_string()),
+            },
+            rate_limit: RateLimitSection {
+                identity: self.signing_limits,
+                global: self.global_signing_limits,
+            },
+            approval: ApprovalSettings {
+                url: redact(&self.approval.url),
+                ..self.approval.clone()
+            },
+            min_balances: self.min_balances.clone(),
+            networks: self
+                .networks
+                .iter()
+                .map(|(name, chain)| (name.clone(), NetworkSection::from_chain(chain)))
+                .collect(),
+        }
+    }
+
+    /// Record `settings` in the `[kdf]` section of the config file at `path`, creating the file
+    /// if needed; comments and other keys are kept
+    pub async fn write_kdf(path: &Path, settings: &KdfSettings) -> WalletResult<()> {
+        let mut doc = read_document(path).await?;
+
+        let kdf = doc
+            .entry("kdf")
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+            .ok_or_else(|| invalid("kdf", "must be a table".to_string()))?;
+        kdf["algorithm"] = toml_edit::value(settings.algorithm().as_str());
+        match *settings {
+            KdfSettings::Argon2id { memory, iterations, parallelism } => {
+                kdf["memory"] = toml_edit::value(i64::from(memory));
+                kdf["iterations"] = toml_edit::value(i64::from(iterations));
+                kdf["parallelism"] = toml_edit::value(i64::from(parallelism));
+            }
+            KdfSettings::Scrypt { log_n, .. } => kdf["scrypt_log_n"] = toml_edit::value(i64::from(log_n)),
+            KdfSettings::Pbkdf2 { iterations } => kdf["pbkdf2_iterations"] = toml_edit::value(i64::from(iterations)),
+        }
+
+        write_document(path, &doc).await
+    }
+
+    /// Add or replace the `[networks.<name>]` table of the config file at `path`, creating the
+    /// file if needed; the result is validated like any config file before it is written
+    pub async fn write_network(path: &Path, name: &str, network: &NetworkSection) -> WalletResult<()> {
+        let mut doc = read_document(path).await?;
+
+        let networks = doc
+            .entry("networks")
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+            .ok_or_else(|| invalid("networks", "must be a table".to_string()))?;
+        networks.set_implicit(true);
+
+        let mut table = toml_edit::Table::new();
+        table["chain_id"] = toml_edit::value(i64::try_from(network.chain_id).map_err(|_| {
+            invalid(&format!("networks.{}.chain_id", name), "out of range".to_string())
+        })?);
+        table["rpc_url"] = toml_edit::value(network.rpc_url.as_str());
+        for (key, value) in [
+            ("explorer_url", &network.explorer_url),
+            ("currency_symbol", &network.currency_symbol),
+            ("wrapped_native", &network.wrapped_native),
+        ] {
+            if let Some(value) = value {
+                table[key] = toml_edit::value(value.as_str());
+            }
+        }
+        networks.insert(name, toml_edit::Item::Table(table));
+
+        write_document(path, &doc).await
+    }
+
+    /// Drop `[networks.<name>]` from the config file at `path`, returning whether it was there
+    pub async fn remove_network(path: &Path, name: &str) -> WalletResult<bool> {
+        let mut doc = read_document(path).await?;
+        let removed = doc
+            .get_mut("networks")
+            .and_then(toml_edit::Item::as_table_mut)
+            .and_then(|networks| networks.remove(name))
+            .is_some();
+        if removed {
+            write_document(path, &doc).await?;
+        }
+        Ok(removed)
+    }
+
+    /// Defaults overlaid with the TOML file at `path`
+    pub async fn load(path: &Path) -> WalletResult<Self> {
+        let data = tokio::fs::read_to_string(path).await.map_err(|e| NetworkError::InvalidConfiguration {
+            key: path.display().to_string(),
+            details: format!("cannot read config file: {}", e),
+        })?;
+        let file: ConfigFile = toml::from_str(&data).map_err(|e| NetworkError::InvalidConfiguration {
+            key: path.display().to_string(),
+            details: e.to_string(),
+        })?;
+
+        let mut config = Self::default();
+        config.apply_file(file)?;
+        Ok(config)
+    }
+
+    fn apply_file(&mut self, file: ConfigFile) -> WalletResult<()> {
+        // Networks come first, every other chain reference may point at one of them
+        for (name, network) in file.networks {
+            let key = format!("networks.{}", name);
+            if !config::is_valid_network_name(&name) {
+                return Err(invalid(&key, "names are lowercase letters, digits, '-' and '_'".to_string()));
+            }
+            if ChainInfo::by_name(&name).is_ok() {
+                return Err(invalid(&key, "is a built-in network, override its endpoint under [rpc.endpoints]".to_string()));
+            }
+            if network.chain_id == 0 {
+                return Err(invalid(&format!("{}.chain_id", key), "must be greater than zero".to_string()));
+            }
+            if let Some(other) = ChainInfo::registry(self).into_iter().find(|c| c.chain_id == network.chain_id) {
+                return Err(invalid(&format!("{}.chain_id", key), format!("{} is already used by {}", network.chain_id, other.name)));
+            }
+            validate_url(&format!("{}.rpc_url", key), &network.rpc_url)?;
+            if let Some(url) = &network.explorer_url {
+                validate_url(&format!("{}.explorer_url", key), url)?;
+            }
+            if let Some(address) = &network.wrapped_native {
+                crate::utils::validate_ethereum_address(address)
+                    .map_err(|_| invalid(&format!("{}.wrapped_native", key), format!("{:?} is not an address", address)))?;
+            }
+            self.networks.insert(name.clone(), network.to_chain(&name));
+        }
+
+        if let Some(network) = file.network {
+            ChainInfo::resolve(&network, self).map_err(|_| invalid("network", format!("unknown network {:?}", network)))?;
+            self.network = network;
+        }
+        if let Some(path) = file.wallets_path {
+            self.wallets_path = expand_home(&path);
+        }
+        if let Some(path) = file.state_path {
+            self.state_path = expand_home(&path);
+        }
+        if let Some(offline) = file.offline {
+            self.offline = offline;
+        }
+        if let Some(track_usage) = file.track_usage {
+            self.track_usage = track_usage;
+        }
+        if let Some(record_history) = file.record_history {
+            self.record_history = record_history;
+        }
+        if let Some(timeout) = file.prompt_timeout_secs {
+            self.prompt_timeout_secs = timeout;
+        }
+        if let Some(use_agent) = file.use_agent {
+            self.use_agent = use_agent;
+        }
+        if let Some(backups) = file.keystore_backups {
+            self.keystore_backups = backups;
+        }
+        if let Some(strict) = file.strict_permissions {
+            self.strict_permissions = strict;
+        }
+        if let Some(harden) = file.harden_memory {
+            self.harden_memory = harden;
+        }
+        if let Some(throttle) = file.unlock_throttle {
+            self.unlock_throttle = throttle;
+        }
+        if let Some(tier) = file.fee_tier {
+            self.fee_tier = tier;
+        }
+        if file.policy_profile.is_some() {
+            self.policy_profile = file.policy_profile;
+        }
+
+        for (key, value) in [
+            ("kdf.iterations", file.kdf.iterations),
+            ("kdf.memory", file.kdf.memory),
+            ("kdf.parallelism", file.kdf.parallelism),
+            ("kdf.pbkdf2_iterations", file.kdf.pbkdf2_iterations),
+            ("rpc.max_attempts", file.rpc.max_attempts),
+            ("rate_limit.identity.per_minute", file.rate_limit.identity.per_minute),
+            ("rate_limit.identity.per_hour", file.rate_limit.identity.per_hour),
+            ("rate_limit.global.per_minute", file.rate_limit.global.per_minute),
+            ("rate_limit.global.per_hour", file.rate_limit.global.per_hour),
+        ] {
+            if value == Some(0) {
+                return Err(invalid(key, "must be greater than zero".to_string()));
+            }
+        }
+        if let Some(algorithm) = file.kdf.algorithm {
+            self.kdf = algorithm;
+        }
+        if let Some(log_n) = file.kdf.scrypt_log_n {
+            if !(1..32).contains(&log_n) {
+                return Err(invalid("kdf.scrypt_log_n", "must be between 1 and 31".to_string()));
+            }
+            self.kdf_scrypt_log_n = log_n;
+        }
+        if let Some(iterations) = file.kdf.iterations {
+            self.kdf_iterations = iterations;
+        }
+        if let Some(memory) = file.kdf.memory {
+            self.kdf_memory = memory;
+        }
+        if let Some(parallelism) = file.kdf.parallelism {
+            self.kdf_parallelism = parallelism;
+        }
+        if let Some(iterations) = file.kdf.pbkdf2_iterations {
+            self.kdf_pbkdf2_iterations = iterations;
+        }
+
+        if file.rpc.timeout_secs == Some(0) {
+            return Err(invalid("rpc.timeout_secs", "must be greater than zero".to_string()));
+        }
+        if let Some(timeout) = file.rpc.timeout_secs {
+            self.rpc_timeout_secs = timeout;
+        }
+        if let Some(attempts) = file.rpc.max_attempts {
+            self.rpc_max_attempts = attempts;
+        }
+        if let Some(backoff) = file.rpc.backoff_ms {
+            self.rpc_backoff_ms = backoff;
+        }
+        if let Some(jitter) = file.rpc.jitter {
+            self.rpc_jitter = jitter;
+        }
+        for (chain, url) in file.rpc.endpoints {
+            ChainInfo::resolve(&chain, self)
+                .map_err(|_| invalid(&format!("rpc.endpoints.{}", chain), "unknown chain".to_string()))?;
+            validate_url(&format!("rpc.endpoints.{}", chain), &url)?;
+            self.rpc_overrides.insert(chain, url);
+        }
+
+        if let Some(url) = file.explorer.api_url {
+            validate_url("explorer.api_url", &url)?;
+            self.explorer_api_url = url;
+        }
+        if file.explorer.api_key.is_some() {
+            self.explorer_api_key = file.explorer.api_key;
+        }
+        for (chain, api) in file.explorer.networks {
+            let key = format!("explorer.networks.{}", chain);
+            ChainInfo::resolve(&chain, self).map_err(|_| invalid(&key, "unknown chain".to_string()))?;
+            if let Some(url) = &api.api_url {
+                validate_url(&format!("{}.api_url", key), url)?;
+            }
+            self.explorer_overrides.insert(chain, api);
+        }
+        if let Some(url) = file.quote.api_url {
+            validate_url("quote.api_url", &url)?;
+            self.quote_api_url = Some(url);
+        }
+        if file.quote.api_key.is_some() {
+            self.quote_api_key = file.quote.api_key;
+        }
+        if let Some(source) = file.prices.source {
+            self.price_source = source;
+        }
+        if let Some(url) = file.prices.api_url {
+            validate_url("prices.api_url", &url)?;
+            self.price_api_url = Some(url);
+        }
+        if file.prices.api_key.is_some() {
+            self.price_api_key = file.prices.api_key;
+        }
+        if let Some(path) = file.prices.rates_file {
+            self.price_rates_file = Some(expand_home(&path));
+        }
+
+        self.signing_limits = file.rate_limit.identity;
+        self.global_signing_limits = file.rate_limit.global;
+
+        file.approval.validate().map_err(|(key, details)| invalid(key, details))?;
+        if let Some(url) = &file.approval.url {
+            validate_url("approval.url", url)?;
+        }
+        self.approval = file.approval;
+
+        for (chain, amount) in file.min_balances {
+            ChainInfo::resolve(&chain, self)
+                .map_err(|_| invalid(&format!("min_balances.{}", chain), "unknown chain".to_string()))?;
+            self.min_balances.insert(chain, amount);
+        }
+        Ok(())
+    }
+}
+
+/// The config file at `path` as an editable document, empty if it does not exist yet
+async fn read_document(path: &Path) -> WalletResult<toml_edit::DocumentMut> {
+    let source = match tokio::fs::read_to_string(path).await {
+        Ok(source) => source,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    source
+        .parse()
+        .map_err(|e: toml_edit::TomlError| invalid(&path.display().to_string(), e.to_string()))
+}
+
+async fn write_document(path: &Path, doc: &toml_edit::DocumentMut) -> WalletResult<()> {
+    // Never leave behind a file the next run would reject
+    let updated = doc.to_string();
+    let file: ConfigFile = toml::from_str(&updated).map_err(|e| invalid(&path.display().to_string(), e.to_string()))?;
+    WalletConfig::default().apply_file(file)?;
+
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    tokio::fs::write(path, updated).await?;
+    Ok(())
+}
+
+fn validate_url(key: &str, url: &str) -> WalletResult<()> {
+    if ["http://", "https://", "ws://", "wss://"].iter().any(|scheme| url.starts_with(scheme)) {
+        Ok(())
+    } else {
+        Err(invalid(key, format!("{:?} is not an http(s) or ws(s) URL", url)))
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_else(|| PathBuf::from(".")).join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+fn invalid(key: &str, details: String) -> WalletError {
+    NetworkError::InvalidConfiguration {
+        key: key.to_string(),
+        details,
+    }
+    .into()
+}
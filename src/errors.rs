@@ -33,7 +33,7 @@ pub enum WalletError {
 
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum CryptographicError{
-    #[error("CRYPTO_001: Insufficient entropy for secure key generation")]
+    #[error("CRYPTO_001: Insufficient entropy for secure key generation ({available} of {required} bits): {suggestion}")]
     InsufficientEntropy{
         available: u32,
         required: u32,
@@ -91,6 +91,26 @@ pub enum CryptographicError{
         /// Error details
         details: String,
     },
+
+    #[error("CRYPTO_013: Signing failed")]
+    SigningFailed {
+        /// Error details
+        details: String,
+    },
+
+    #[error("CRYPTO_014: Hardware signer error: {details}")]
+    HardwareSignerFailed {
+        /// Device kind, e.g. "ledger"
+        device: String,
+        /// Error details
+        details: String,
+    },
+
+    #[error("CRYPTO_015: Encryption failed")]
+    EncryptionFailed {
+        /// Error details
+        details: String,
+    },
 }
 
 #[derive(Error, Debug, Clone, PartialEq)]
@@ -192,6 +212,13 @@ pub enum UserInputError {
     #[error("INPUT_007: Password confirmation mismatch")]
     PasswordMismatch,
 
+    /// Interactive prompt left unanswered (INPUT_008)
+    #[error("INPUT_008: No answer to prompt \"{prompt}\" within {timeout:?}")]
+    PromptTimeout {
+        prompt: String,
+        timeout: std::time::Duration,
+    },
+
 }
 
 /// Authentication errors (AUTH_xxx)
@@ -209,7 +236,57 @@ pub enum AuthenticationError {
     WeakPassword {
         requirements: Vec<String>,
     },
-    
+
+    /// Cold wallet handled outside its restrictions (AUTH_003)
+    #[error("AUTH_003: Cold storage policy violation")]
+    ColdStorageViolation {
+        rule: String,
+        suggestion: String,
+    },
+
+    /// Signing refused by a rate limit (AUTH_004)
+    #[error("AUTH_004: Signing rate limit of {limit} per {window} exceeded for {scope}, retry in {retry_after:?}")]
+    SigningRateLimited {
+        /// Identity that hit its limit, or "all identities" for the global limit
+        scope: String,
+        limit: u32,
+        /// "minute" or "hour"
+        window: String,
+        retry_after: std::time::Duration,
+    },
+
+    /// Transaction above the approval threshold signed without a token (AUTH_005)
+    #[error("AUTH_005: Transactions of {threshold} or more need a second person's approval")]
+    ApprovalRequired {
+        /// Configured threshold with its currency, e.g. "1.0 ETH"
+        threshold: String,
+    },
+
+    /// Approval token that does not vouch for this transaction (AUTH_006)
+    #[error("AUTH_006: Approval token rejected: {reason}")]
+    ApprovalRejected {
+        reason: String,
+    },
+
+    /// Signing attempted with a wallet imported from an xpub or address (AUTH_007)
+    #[error("AUTH_007: Wallet {address} is watch-only and cannot sign")]
+    WatchOnlyWallet {
+        address: String,
+    },
+
+    /// Unlock refused after repeated wrong passwords (AUTH_008)
+    #[error("AUTH_008: {wallet_file} is locked after {failures} wrong passwords, retry in {retry_after:?}")]
+    UnlockThrottled {
+        wallet_file: String,
+        failures: u32,
+        retry_after: std::time::Duration,
+    },
+
+    /// BIP39 passphrase missing or not the one the wallet was saved with (AUTH_009)
+    #[error("AUTH_009: Wrong or missing BIP39 passphrase for wallet {address}")]
+    WrongPassphrase {
+        address: String,
+    },
 }
 
 #[derive(Error, Debug, Clone, PartialEq)]
@@ -224,7 +301,7 @@ pub enum ValidationError {
     },
 
     /// Keystore schema validation failed
-    #[error("VALIDATION_002: Keystore schema validation failed")]
+    #[error("VALIDATION_002: Keystore schema validation failed: {error}")]
     InvalidKeystoreSchema {
         /// Schema error
         error: String,
@@ -258,6 +335,24 @@ pub enum ValidationError {
         /// Required version
         required: String,
     },
+
+    /// On-chain precondition for an operation does not hold
+    #[error("VALIDATION_006: Precondition failed: {details}")]
+    PreconditionFailed {
+        /// Check that was performed
+        check: String,
+        /// Error details
+        details: String,
+    },
+
+    /// Signing for a chain other than the one recorded in the keystore
+    #[error("VALIDATION_007: Wallet is bound to chain {expected}, refusing to sign for chain {requested}")]
+    ChainMismatch {
+        /// Chain id recorded in the keystore
+        expected: u64,
+        /// Chain id of the signing request
+        requested: u64,
+    },
 }
 
 #[derive(Error, Debug, Clone, PartialEq)]
@@ -272,16 +367,18 @@ pub enum NetworkError {
     },
 
     /// Request timeout
-    #[error("NETWORK_002: Request timeout")]
+    #[error("NETWORK_002: Request timeout after {elapsed:?} (limit {limit:?})")]
     RequestTimeout {
         /// Request type
         request_type: String,
-        /// Timeout duration
-        timeout: std::time::Duration,
+        /// Time spent before giving up
+        elapsed: std::time::Duration,
+        /// Configured timeout
+        limit: std::time::Duration,
     },
 
     /// Invalid network configuration
-    #[error("NETWORK_003: Invalid network configuration")]
+    #[error("NETWORK_003: Invalid network configuration: {key}: {details}")]
     InvalidConfiguration {
         /// Configuration key
         key: String,
@@ -304,16 +401,36 @@ pub enum NetworkError {
         /// Supported protocols
         supported: Vec<String>,
     },
+
+    /// JSON-RPC error object returned by the node
+    #[error("NETWORK_006: JSON-RPC call failed")]
+    RpcError {
+        /// Called method
+        method: String,
+        /// JSON-RPC error code
+        code: i64,
+        /// Error message from the node
+        message: String,
+    },
+
+    /// Response could not be parsed or correlated
+    #[error("NETWORK_007: Invalid response from endpoint")]
+    InvalidResponse {
+        /// Target endpoint
+        endpoint: String,
+        /// Error details
+        details: String,
+    },
 }
 
 macro_rules! impl_error_traits {
     ($error_type:ty, $prefix:expr) => {
         impl $error_type {
-            fn code(&self) -> &'static str {
+            pub fn code(&self) -> &'static str {
                 concat!($prefix, "_001") // Simplified for now
             }
 
-            fn suggestion(&self) -> Option<String> {
+            pub fn suggestion(&self) -> Option<String> {
                 None // Can be expanded for specific suggestions
             }
         }
@@ -326,6 +443,107 @@ impl_error_traits!(AuthenticationError, "AUTH");
 impl_error_traits!(NetworkError, "NETWORK");
 impl_error_traits!(ValidationError, "VALIDATION");
 
+impl WalletError {
+    /// Process exit status of the error's class, from `config::exit_codes`
+    pub fn exit_code(&self) -> i32 {
+        use crate::config::exit_codes;
+
+        match self {
+            WalletError::UserInput(_) => exit_codes::USER_INPUT,
+            WalletError::Authentication(_) => exit_codes::AUTHENTICATION,
+            WalletError::Cryptographic(_) => exit_codes::CRYPTOGRAPHIC,
+            WalletError::Filesystem(_) | WalletError::Io(_) => exit_codes::FILESYSTEM,
+            WalletError::Network(_) => exit_codes::NETWORK,
+            WalletError::Validation(_) => exit_codes::VALIDATION,
+            WalletError::NotImplemented(_) | WalletError::Json(_) => exit_codes::GENERAL,
+        }
+    }
+
+    /// Localized next step for the user, printed under the error by the CLI
+    pub fn suggestion(&self) -> Option<String> {
+        use crate::i18n::{tr, tr_args};
+
+        let hint = match self {
+            WalletError::Cryptographic(err) => match err {
+                CryptographicError::InsufficientEntropy { .. } => tr("hint-insufficient-entropy"),
+                CryptographicError::InvalidMnemonic { .. } => tr("hint-invalid-mnemonic"),
+                CryptographicError::InvalidPrivateKey { expected, .. } => {
+                    tr_args("hint-invalid-private-key", &[("expected", expected)])
+                }
+                CryptographicError::DecryptionFailed { .. } => tr("hint-decryption-failed"),
+                CryptographicError::InvalidDerivationPath { .. } => tr("hint-invalid-derivation-path"),
+                CryptographicError::HardwareSignerFailed { .. } => tr("hint-hardware-signer"),
+                _ => return None,
+            },
+            WalletError::Filesystem(err) => match err {
+                FilesystemError::FileNotFound { .. } => tr("hint-file-not-found"),
+                FilesystemError::PermissionDenied { .. } | FilesystemError::DirectoryNotAccessible { .. } => {
+                    tr("hint-permission-denied")
+                }
+                FilesystemError::FileExists { suggestion, .. } => {
+                    tr_args("hint-file-exists", &[("suggestion", suggestion)])
+                }
+                FilesystemError::InvalidFormat { .. } => tr("hint-invalid-format"),
+                FilesystemError::PathTraversal { .. } => tr("hint-path-traversal"),
+                _ => return None,
+            },
+            WalletError::UserInput(err) => match err {
+                UserInputError::InvalidParameters { expected, .. } => {
+                    tr_args("hint-invalid-parameters", &[("expected", expected)])
+                }
+                UserInputError::ConflictingOptions { suggestion, .. } => {
+                    tr_args("hint-conflicting-options", &[("suggestion", suggestion)])
+                }
+                UserInputError::MissingParameter { hint, .. } => {
+                    tr_args("hint-missing-parameter", &[("hint", hint)])
+                }
+                UserInputError::InvalidNetwork { supported, .. } => {
+                    tr_args("hint-invalid-network", &[("supported", &supported.join(", "))])
+                }
+                UserInputError::PasswordMismatch => tr("hint-password-mismatch"),
+                UserInputError::PromptTimeout { .. } => tr("hint-prompt-timeout"),
+            },
+            WalletError::Authentication(err) => match err {
+                AuthenticationError::WrongPassword { .. } => tr("hint-wrong-password"),
+                AuthenticationError::WeakPassword { requirements } => {
+                    tr_args("hint-weak-password", &[("requirements", &requirements.join(", "))])
+                }
+                AuthenticationError::ColdStorageViolation { suggestion, .. } => {
+                    tr_args("hint-cold-storage", &[("suggestion", suggestion)])
+                }
+                AuthenticationError::SigningRateLimited { .. } => return None,
+                AuthenticationError::ApprovalRequired { .. } => tr("hint-approval-required"),
+                AuthenticationError::ApprovalRejected { .. } => tr("hint-approval-rejected"),
+                AuthenticationError::WatchOnlyWallet { .. } => tr("hint-watch-only"),
+                AuthenticationError::UnlockThrottled { .. } => tr("hint-unlock-throttled"),
+                AuthenticationError::WrongPassphrase { .. } => tr("hint-wrong-passphrase"),
+            },
+            WalletError::Network(err) => match err {
+                NetworkError::ConnectivityFailure { .. } | NetworkError::RequestTimeout { .. } => {
+                    tr("hint-network-unreachable")
+                }
+                NetworkError::InvalidConfiguration { .. } => tr("hint-invalid-configuration"),
+                NetworkError::RateLimitExceeded { .. } => tr("hint-rate-limit"),
+                _ => return None,
+            },
+            WalletError::Validation(err) => match err {
+                ValidationError::InvalidAddressFormat { expected, .. } => {
+                    tr_args("hint-address-format", &[("expected", expected)])
+                }
+                ValidationError::InvalidKeystoreSchema { .. } => tr("hint-keystore-schema"),
+                ValidationError::VersionIncompatible { required, .. } => {
+                    tr_args("hint-version-incompatible", &[("required", required)])
+                }
+                ValidationError::ChainMismatch { .. } => tr("hint-chain-mismatch"),
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        Some(hint)
+    }
+}
+
 
 impl From<std::io::Error> for WalletError {
     fn from(err: std::io::Error) -> Self {
@@ -0,0 +1,342 @@
+//! C interface to the wallet core, so mobile apps embed the same keystore implementation as
+//! the CLI. Wallets stay in Rust memory behind an opaque `W3wWallet` handle and keystores
+//! cross the boundary as JSON strings; `include/web3wallet.h` declares everything here.
+//!
+//! Every function returns 0 on success, or the CLI's exit code for the error class (see
+//! `config::exit_codes`) with the message readable from `w3w_last_error` on the same thread.
+//! Outputs are written through the trailing pointer arguments only on success. Strings the
+//! library returns are freed with `w3w_string_free`, which zeroes them first.
+use crate::config::{self, exit_codes};
+use crate::errors::{AuthenticationError, UserInputError, ValidationError, WalletError, WalletResult};
+use crate::models::{KeystoreFile, Wallet};
+use crate::services::{CryptoService, MnemonicService};
+use crate::utils;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Opaque to C; created by `w3w_create`, `w3w_import_*` or `w3w_decrypt`
+pub struct W3wWallet(Wallet);
+
+#[repr(C)]
+pub struct W3wDerivedAddress {
+    pub index: u32,
+    /// EIP-55 checksummed, `0x` prefixed
+    pub address: *mut c_char,
+    pub derivation_path: *mut c_char,
+}
+
+/// Owned array of `len` addresses, freed with `w3w_address_list_free`
+#[repr(C)]
+pub struct W3wAddressList {
+    pub items: *mut W3wDerivedAddress,
+    pub len: usize,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Run `body`, turning its error or panic into a status code and the thread's last error
+fn run(body: impl FnOnce() -> WalletResult<()>) -> i32 {
+    let (code, message) = match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => return exit_codes::SUCCESS,
+        Ok(Err(e)) => (e.exit_code(), e.to_string()),
+        Err(_) => (exit_codes::GENERAL, "internal error in the wallet core".to_string()),
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(message).ok());
+    code
+}
+
+/// # Safety
+/// `ptr` is null or a NUL-terminated string that outlives the call
+unsafe fn str_arg<'a>(ptr: *const c_char, parameter: &str) -> WalletResult<&'a str> {
+    if ptr.is_null() {
+        return Err(missing(parameter));
+    }
+    CStr::from_ptr(ptr).to_str().map_err(|_| {
+        UserInputError::InvalidParameters {
+            parameter: parameter.to_string(),
+            value: "<not UTF-8>".to_string(),
+            expected: format!("{} as UTF-8 text", parameter),
+        }
+        .into()
+    })
+}
+
+/// # Safety
+/// `ptr` is null or a live handle
+unsafe fn wallet_arg<'a>(ptr: *const W3wWallet) -> WalletResult<&'a Wallet> {
+    ptr.as_ref().map(|handle| &handle.0).ok_or_else(|| missing("wallet"))
+}
+
+/// # Safety
+/// `ptr` is null or a live handle not used elsewhere during the call
+unsafe fn wallet_mut_arg<'a>(ptr: *mut W3wWallet) -> WalletResult<&'a mut Wallet> {
+    ptr.as_mut().map(|handle| &mut handle.0).ok_or_else(|| missing("wallet"))
+}
+
+/// Write the output `value` makes, checking `out` first so nothing is allocated for a null one
+///
+/// # Safety
+/// `out` is null or valid for a write of `T`
+unsafe fn write_out<T>(out: *mut T, parameter: &str, value: impl FnOnce() -> WalletResult<T>) -> WalletResult<()> {
+    if out.is_null() {
+        return Err(missing(parameter));
+    }
+    out.write(value()?);
+    Ok(())
+}
+
+fn missing(parameter: &str) -> WalletError {
+    UserInputError::MissingParameter {
+        parameter: parameter.to_string(),
+        hint: "pass a non-null pointer".to_string(),
+    }
+    .into()
+}
+
+fn c_string(value: &str) -> WalletResult<*mut c_char> {
+    CString::new(value)
+        .map(CString::into_raw)
+        .map_err(|_| WalletError::Io("string contains a NUL byte".to_string()))
+}
+
+fn handle(wallet: Wallet) -> *mut W3wWallet {
+    Box::into_raw(Box::new(W3wWallet(wallet)))
+}
+
+/// Message of the last failed call on this thread, or null. Valid until the next failure.
+#[no_mangle]
+pub extern "C" fn w3w_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Zero and free a string returned by this library
+///
+/// # Safety
+/// `value` is null or a string returned by this library, not freed before
+#[no_mangle]
+pub unsafe extern "C" fn w3w_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        CString::from_raw(value).into_bytes().zeroize();
+    }
+}
+
+/// New HD wallet from a fresh mnemonic of `word_count` words
+///
+/// # Safety
+/// `out_wallet` is valid for a write
+#[no_mangle]
+pub unsafe extern "C" fn w3w_create(word_count: u8, out_wallet: *mut *mut W3wWallet) -> i32 {
+    run(|| {
+        let mnemonic = MnemonicService::generate(word_count)?;
+        let wallet = Wallet::from_mnemonic(mnemonic.phrase(), config::DEFAULT_NETWORK, None)?;
+        write_out(out_wallet, "out_wallet", || Ok(handle(wallet)))
+    })
+}
+
+/// HD wallet from a BIP39 mnemonic; `passphrase` may be null for none
+///
+/// # Safety
+/// `mnemonic` and a non-null `passphrase` are NUL-terminated; `out_wallet` is valid for a write
+#[no_mangle]
+pub unsafe extern "C" fn w3w_import_mnemonic(
+    mnemonic: *const c_char,
+    passphrase: *const c_char,
+    out_wallet: *mut *mut W3wWallet,
+) -> i32 {
+    run(|| {
+        let mnemonic = str_arg(mnemonic, "mnemonic")?;
+        let passphrase = if passphrase.is_null() { "" } else { str_arg(passphrase, "passphrase")? };
+        let wallet = Wallet::from_mnemonic_with_passphrase(mnemonic, passphrase, config::DEFAULT_NETWORK, None)?;
+        write_out(out_wallet, "out_wallet", || Ok(handle(wallet)))
+    })
+}
+
+/// Wallet from a hex private key, with or without `0x`
+///
+/// # Safety
+/// `private_key` is NUL-terminated; `out_wallet` is valid for a write
+#[no_mangle]
+pub unsafe extern "C" fn w3w_import_private_key(private_key: *const c_char, out_wallet: *mut *mut W3wWallet) -> i32 {
+    run(|| {
+        let wallet = Wallet::from_private_key(str_arg(private_key, "private_key")?, config::DEFAULT_NETWORK, None)?;
+        write_out(out_wallet, "out_wallet", || Ok(handle(wallet)))
+    })
+}
+
+/// Native keystore JSON of `wallet`, encrypted with `password` under the default Argon2id cost
+///
+/// # Safety
+/// `wallet` is a live handle, `password` is NUL-terminated; `out_keystore_json` is valid for a write
+#[no_mangle]
+pub unsafe extern "C" fn w3w_encrypt(
+    wallet: *const W3wWallet,
+    password: *const c_char,
+    out_keystore_json: *mut *mut c_char,
+) -> i32 {
+    run(|| {
+        let wallet = wallet_arg(wallet)?;
+        let password = str_arg(password, "password")?;
+        CryptoService::validate_password(password)?;
+        let keystore = CryptoService::encrypt_wallet(wallet, password, true)?;
+        write_out(out_keystore_json, "out_keystore_json", || c_string(&keystore.to_json()?))
+    })
+}
+
+/// Wallet from a native or v3 (geth/MetaMask) keystore. Cold keystores are refused: only the
+/// CLI can check the machine is offline.
+///
+/// # Safety
+/// `keystore_json` and `password` are NUL-terminated; `out_wallet` is valid for a write
+#[no_mangle]
+pub unsafe extern "C" fn w3w_decrypt(
+    keystore_json: *const c_char,
+    password: *const c_char,
+    out_wallet: *mut *mut W3wWallet,
+) -> i32 {
+    run(|| {
+        let password = str_arg(password, "password")?;
+        let wallet = match CryptoService::parse_any_keystore(str_arg(keystore_json, "keystore_json")?)? {
+            KeystoreFile::Native(keystore) => {
+                if keystore.metadata.cold {
+                    return Err(AuthenticationError::ColdStorageViolation {
+                        rule: "cold wallets can only be decrypted by the CLI in --offline mode".to_string(),
+                        suggestion: "Decrypt it with `web3wallet load --offline` on an air-gapped machine".to_string(),
+                    }
+                    .into());
+                }
+                let mut wallet = CryptoService::decrypt_wallet(&keystore, password)?;
+                wallet.set_alias(keystore.metadata.alias.clone());
+                wallet
+            }
+            KeystoreFile::V3(keystore) => {
+                let private_key = CryptoService::decrypt_v3(&keystore, password)?;
+                Wallet::from_private_key(&hex::encode(private_key.as_slice()), config::DEFAULT_NETWORK, None)?
+            }
+        };
+        write_out(out_wallet, "out_wallet", || Ok(handle(wallet)))
+    })
+}
+
+/// Whether `wallet` was decrypted without the BIP39 passphrase its seed takes. The passphrase
+/// is never saved in the keystore; such a wallet derives nothing until `w3w_unlock_passphrase`.
+///
+/// # Safety
+/// `wallet` is a live handle; `out_needed` is valid for a write
+#[no_mangle]
+pub unsafe extern "C" fn w3w_wallet_needs_passphrase(wallet: *const W3wWallet, out_needed: *mut bool) -> i32 {
+    run(|| {
+        let wallet = wallet_arg(wallet)?;
+        write_out(out_needed, "out_needed", || Ok(wallet.needs_passphrase()))
+    })
+}
+
+/// Supply the BIP39 passphrase of a decrypted wallet. One that does not give back the saved
+/// address fails with the authentication code and leaves the wallet locked.
+///
+/// # Safety
+/// `wallet` is a live handle not used by another thread during the call; `passphrase` is NUL-terminated
+#[no_mangle]
+pub unsafe extern "C" fn w3w_unlock_passphrase(wallet: *mut W3wWallet, passphrase: *const c_char) -> i32 {
+    run(|| {
+        let passphrase = str_arg(passphrase, "passphrase")?;
+        wallet_mut_arg(wallet)?.unlock_passphrase(passphrase)
+    })
+}
+
+/// Primary address of `wallet`, EIP-55 checksummed
+///
+/// # Safety
+/// `wallet` is a live handle; `out_address` is valid for a write
+#[no_mangle]
+pub unsafe extern "C" fn w3w_wallet_address(wallet: *const W3wWallet, out_address: *mut *mut c_char) -> i32 {
+    run(|| {
+        let wallet = wallet_arg(wallet)?;
+        write_out(out_address, "out_address", || c_string(&utils::to_checksum_address(wallet.address())?))
+    })
+}
+
+/// Mnemonic of `wallet` for the user to back up; fails for wallets imported without one
+///
+/// # Safety
+/// `wallet` is a live handle; `out_mnemonic` is valid for a write
+#[no_mangle]
+pub unsafe extern "C" fn w3w_wallet_mnemonic(wallet: *const W3wWallet, out_mnemonic: *mut *mut c_char) -> i32 {
+    run(|| {
+        let wallet = wallet_arg(wallet)?;
+        if !wallet.has_mnemonic() {
+            return Err(ValidationError::PreconditionFailed {
+                check: "mnemonic".to_string(),
+                details: "the wallet was not created from a mnemonic".to_string(),
+            }
+            .into());
+        }
+        let mnemonic = Zeroizing::new(wallet.mnemonic().to_string());
+        write_out(out_mnemonic, "out_mnemonic", || c_string(&mnemonic))
+    })
+}
+
+/// `count` addresses from index `start` on the wallet's derivation path
+///
+/// # Safety
+/// `wallet` is a live handle; `out_list` is valid for a write
+#[no_mangle]
+pub unsafe extern "C" fn w3w_derive(wallet: *const W3wWallet, start: u32, count: u32, out_list: *mut W3wAddressList) -> i32 {
+    run(|| {
+        let wallet = wallet_arg(wallet)?;
+        let end = start.checked_add(count).ok_or_else(|| UserInputError::InvalidParameters {
+            parameter: "count".to_string(),
+            value: count.to_string(),
+            expected: "start + count within the 2^32 address indices".to_string(),
+        })?;
+        let derived = (start..end).map(|index| wallet.derive_address(index)).collect::<WalletResult<Vec<_>>>()?;
+
+        write_out(out_list, "out_list", || {
+            let mut items = Vec::with_capacity(derived.len());
+            for address in &derived {
+                items.push(W3wDerivedAddress {
+                    index: address.index(),
+                    address: c_string(&utils::to_checksum_address(address.address())?)?,
+                    derivation_path: c_string(address.derivation_path())?,
+                });
+            }
+            let items = items.into_boxed_slice();
+            let len = items.len();
+            Ok(W3wAddressList { items: Box::into_raw(items).cast(), len })
+        })
+    })
+}
+
+/// # Safety
+/// `list` is null or a list filled by `w3w_derive`, not freed before
+#[no_mangle]
+pub unsafe extern "C" fn w3w_address_list_free(list: *mut W3wAddressList) {
+    let Some(list) = list.as_mut() else {
+        return;
+    };
+    if !list.items.is_null() {
+        let items = Box::from_raw(ptr::slice_from_raw_parts_mut(list.items, list.len));
+        for item in items.iter() {
+            w3w_string_free(item.address);
+            w3w_string_free(item.derivation_path);
+        }
+    }
+    list.items = ptr::null_mut();
+    list.len = 0;
+}
+
+/// Free `wallet`, zeroing its keys
+///
+/// # Safety
+/// `wallet` is null or a handle from this library, not freed before
+#[no_mangle]
+pub unsafe extern "C" fn w3w_wallet_free(wallet: *mut W3wWallet) {
+    if !wallet.is_null() {
+        drop(Box::from_raw(wallet));
+    }
+}
@@ -0,0 +1,140 @@
+//! Translated prompts and error hints.
+//!
+//! Messages live in `locales/<lang>.ftl` and are compiled into the binary.
+//! Lookups fall back to English, then to the message id itself, so a missing
+//! translation never breaks a command.
+
+use crate::config;
+use crate::errors::{UserInputError, WalletError};
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+static ACTIVE: OnceLock<Lang> = OnceLock::new();
+static EN: OnceLock<FluentBundle<FluentResource>> = OnceLock::new();
+static ZH: OnceLock<FluentBundle<FluentResource>> = OnceLock::new();
+
+/// Interface language for prompts and hints
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    En,
+    Zh,
+}
+
+impl Lang {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::Zh => "zh",
+        }
+    }
+
+    /// Language from a POSIX locale such as `zh_CN.UTF-8`; unknown locales are English
+    pub fn from_locale(locale: &str) -> Self {
+        if locale.to_ascii_lowercase().starts_with("zh") {
+            Lang::Zh
+        } else {
+            Lang::En
+        }
+    }
+
+    /// Language from the first non-empty locale variable in the environment
+    pub fn detect() -> Self {
+        config::i18n::LOCALE_ENV_VARS
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .map(|value| Self::from_locale(&value))
+            .unwrap_or_default()
+    }
+
+    fn source(&self) -> &'static str {
+        match self {
+            Lang::En => include_str!("../locales/en.ftl"),
+            Lang::Zh => include_str!("../locales/zh.ftl"),
+        }
+    }
+
+    fn bundle(&self) -> &'static FluentBundle<FluentResource> {
+        let cell = match self {
+            Lang::En => &EN,
+            Lang::Zh => &ZH,
+        };
+        cell.get_or_init(|| {
+            let langid: unic_langid::LanguageIdentifier = self.as_str().parse().unwrap_or_default();
+            let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+            // Terminals render the bidi isolation marks as stray characters
+            bundle.set_use_isolating(false);
+            // Entries that fail to parse are dropped and fall back to English
+            let resource = FluentResource::try_new(self.source().to_string()).unwrap_or_else(|(resource, _)| resource);
+            let _ = bundle.add_resource(resource);
+            bundle
+        })
+    }
+}
+
+impl FromStr for Lang {
+    type Err = WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "en" => Ok(Lang::En),
+            "zh" => Ok(Lang::Zh),
+            _ => Err(UserInputError::InvalidParameters {
+                parameter: "lang".to_string(),
+                value: s.to_string(),
+                expected: "en or zh".to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+impl std::fmt::Display for Lang {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Set the language for the rest of the process; only the first call has an effect
+pub fn set_lang(lang: Lang) {
+    let _ = ACTIVE.set(lang);
+}
+
+/// Active language, detected from the environment if `set_lang` was never called
+pub fn lang() -> Lang {
+    *ACTIVE.get_or_init(Lang::detect)
+}
+
+/// Message `id` in the active language
+pub fn tr(id: &str) -> String {
+    tr_in(lang(), id, &[])
+}
+
+/// Message `id` in the active language with `{ $name }` placeholders filled in
+pub fn tr_args(id: &str, args: &[(&str, &str)]) -> String {
+    tr_in(lang(), id, args)
+}
+
+/// Message `id` in `lang`, falling back to English and then to `id`
+pub fn tr_in(lang: Lang, id: &str, args: &[(&str, &str)]) -> String {
+    format_message(lang, id, args)
+        .or_else(|| format_message(Lang::En, id, args))
+        .unwrap_or_else(|| id.to_string())
+}
+
+fn format_message(lang: Lang, id: &str, args: &[(&str, &str)]) -> Option<String> {
+    let bundle = lang.bundle();
+    let pattern = bundle.get_message(id)?.value()?;
+
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, *value);
+    }
+
+    let mut errors = Vec::new();
+    let text = bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
+    Some(text.into_owned())
+}
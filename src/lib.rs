@@ -1,29 +1,46 @@
+//! Wallet core of the `web3wallet` CLI: BIP39/BIP32 wallets, encrypted keystores, signing
+//! and the chain services around them. `WalletManager` is the entry point for creating,
+//! importing, saving and listing wallets the way the CLI does:
+//!
+//! ```no_run
+//! use web3wallet_cli::WalletManager;
+//!
+//! # async fn example() -> web3wallet_cli::WalletResult<()> {
+//! let manager = WalletManager::default().with_network("sepolia").with_wallet_dir("./wallets");
+//! let wallet = manager.create_wallet(12).await?;
+//! manager.save_wallet(&wallet, &manager.save_path("alice"), "correct horse battery staple").await?;
+//! for entry in manager.list().await?.wallets {
+//!     println!("{} {}", entry.filename, entry.address);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Without the `native` feature, which the default `cli` feature enables, only the core free
+//! of filesystem and network access is built, e.g. for wasm32.
 
 
 
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
 
+#[doc(hidden)]
 pub mod config;
+#[cfg(feature = "native")]
+mod config_file;
 pub mod errors;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod i18n;
 pub mod models;
+pub mod prelude;
 pub mod services;
+#[doc(hidden)]
 pub mod utils;
 
+#[cfg(feature = "native")]
+pub use config_file::NetworkSection;
 pub use errors::{WalletError, WalletResult};
 pub use models::{Address, Keystore, Wallet};
+#[cfg(feature = "native")]
 pub use services::WalletManager;
 
 // pub type LocalWallet = Wallet<ethers_core::k256::>
@@ -31,9 +48,79 @@ pub use services::WalletManager;
 pub struct WalletConfig{
     pub network: String,
     pub wallets_path: std::path::PathBuf,
+    /// Caches and other non-secret runtime state
+    pub state_path: std::path::PathBuf,
+    /// KDF for newly saved keystores
+    pub kdf: models::keystore::KdfAlgorithm,
     pub kdf_iterations: u32,
     pub kdf_memory: u32,
     pub kdf_parallelism: u32,
+    /// scrypt cost as log2(N), used when `kdf` is scrypt
+    pub kdf_scrypt_log_n: u8,
+    /// Used when `kdf` is pbkdf2
+    pub kdf_pbkdf2_iterations: u32,
+    /// Set by `--offline`; required to decrypt cold wallets
+    pub offline: bool,
+    /// Per-request RPC timeout, overridable with `--timeout`
+    pub rpc_timeout_secs: u64,
+    /// Total attempts per RPC request, including the first
+    pub rpc_max_attempts: u32,
+    /// Initial retry backoff, doubled on every attempt
+    pub rpc_backoff_ms: u64,
+    /// Randomize backoff to avoid synchronized retries
+    pub rpc_jitter: bool,
+    /// Etherscan-compatible indexer used for history and activity reports
+    pub explorer_api_url: String,
+    pub explorer_api_key: Option<String>,
+    /// Indexer per chain name, for chains the default one does not serve
+    pub explorer_overrides: std::collections::BTreeMap<String, services::chains::ExplorerApi>,
+    /// Aggregator endpoint for `quote`, defaults to the aggregator's public API
+    pub quote_api_url: Option<String>,
+    pub quote_api_key: Option<String>,
+    /// Per-chain minimum native balance (ether units) that `monitor` alerts below
+    pub min_balances: std::collections::BTreeMap<String, String>,
+    /// Fee tier used when fees are estimated
+    pub fee_tier: services::fees::FeeTier,
+    /// RPC endpoint per chain name, replacing the built-in public endpoint
+    pub rpc_overrides: std::collections::BTreeMap<String, String>,
+    /// Chains defined in the config file, on top of the built-in ones
+    pub networks: std::collections::BTreeMap<String, services::chains::ChainInfo>,
+    /// Named policy profile requested by the active wallet
+    pub policy_profile: Option<String>,
+    /// Signing rate limits per caller identity, enforced by long-running signers
+    pub signing_limits: services::rate_limit::SigningLimits,
+    /// Signing rate limits across all caller identities
+    pub global_signing_limits: services::rate_limit::SigningLimits,
+    /// Count unlocks, signs and exports per wallet under `state_path`, see `wallet usage`
+    pub track_usage: bool,
+    /// Keep a ledger of signed transactions under `state_path`, see `wallet history export`
+    pub record_history: bool,
+    /// Where fiat values in history exports come from
+    pub price_source: services::prices::PriceSource,
+    /// Price API endpoint, defaults to the public API of `price_source`
+    pub price_api_url: Option<String>,
+    pub price_api_key: Option<String>,
+    /// Rates of the `file` price source, see `services::prices::FixedRates`
+    pub price_rates_file: Option<std::path::PathBuf>,
+    /// Seconds an interactive prompt waits for an answer before the command aborts; 0 waits forever
+    pub prompt_timeout_secs: u64,
+    /// Unlock wallets through a running `agent` when one answers, see `services::agent`
+    pub use_agent: bool,
+    /// Timestamped copies kept of a keystore before it is rewritten, see `restore-backup`
+    pub keystore_backups: u32,
+    /// Refuse to unlock keystores other users can read instead of warning; `--strict`
+    pub strict_permissions: bool,
+    /// Disable core dumps and lock mnemonics and decrypted keys in memory; `--harden`
+    pub harden_memory: bool,
+    /// Lock a keystore for a growing delay after repeated wrong passwords, see `services::unlock_throttle`
+    pub unlock_throttle: bool,
+    /// Save wallets under passwords that fail the strength check, with a warning; `--allow-weak-password`
+    pub allow_weak_passwords: bool,
+    /// Second-person approval for high-value transactions, see `services::approval`
+    pub approval: services::approval::ApprovalSettings,
+    /// Asks for the BIP39 passphrase of a wallet being unlocked, which is never saved with it;
+    /// without one such wallets are refused
+    pub passphrase_prompt: Option<fn(&str) -> WalletResult<String>>,
 }
 
 impl Default for WalletConfig{
@@ -41,9 +128,79 @@ impl Default for WalletConfig{
         Self{
             network: "mainnet".to_string(),
             wallets_path: dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from(".")).join(".web3wallet").join("wallets"),
+            state_path: dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from(".")).join(".web3wallet").join("state"),
+            kdf: models::keystore::KdfAlgorithm::default(),
             kdf_iterations: 1,
             kdf_memory: 47_104,
             kdf_parallelism: 1,
+            kdf_scrypt_log_n: config::crypto::V3_SCRYPT_LOG_N,
+            kdf_pbkdf2_iterations: config::crypto::PBKDF2_ITERATIONS,
+            offline: false,
+            rpc_timeout_secs: config::rpc::DEFAULT_TIMEOUT_SECS,
+            rpc_max_attempts: config::rpc::DEFAULT_MAX_ATTEMPTS,
+            rpc_backoff_ms: config::rpc::DEFAULT_BACKOFF_MS,
+            rpc_jitter: true,
+            explorer_api_url: config::explorer::DEFAULT_API_URL.to_string(),
+            explorer_api_key: std::env::var(config::explorer::API_KEY_ENV).ok(),
+            explorer_overrides: std::collections::BTreeMap::new(),
+            quote_api_url: std::env::var(config::quote::API_URL_ENV).ok(),
+            quote_api_key: std::env::var(config::quote::API_KEY_ENV).ok(),
+            min_balances: std::collections::BTreeMap::new(),
+            fee_tier: services::fees::FeeTier::default(),
+            rpc_overrides: std::collections::BTreeMap::new(),
+            networks: std::collections::BTreeMap::new(),
+            policy_profile: None,
+            signing_limits: services::rate_limit::SigningLimits::default(),
+            global_signing_limits: services::rate_limit::SigningLimits::default(),
+            track_usage: true,
+            record_history: true,
+            price_source: services::prices::PriceSource::default(),
+            price_api_url: None,
+            price_api_key: std::env::var(config::prices::API_KEY_ENV).ok(),
+            price_rates_file: None,
+            prompt_timeout_secs: config::prompt::DEFAULT_TIMEOUT_SECS,
+            use_agent: true,
+            keystore_backups: config::wallet::DEFAULT_KEYSTORE_BACKUPS,
+            strict_permissions: false,
+            harden_memory: false,
+            unlock_throttle: true,
+            allow_weak_passwords: false,
+            approval: services::approval::ApprovalSettings::default(),
+            passphrase_prompt: None,
+        }
+    }
+}
+
+impl WalletConfig {
+    /// The wallet file `name` refers to: a path when it contains a separator, otherwise a file
+    /// in `wallets_path`
+    pub fn wallet_path(&self, name: &str) -> std::path::PathBuf {
+        if name.contains('/') || name.contains('\\') {
+            std::path::PathBuf::from(name)
+        } else {
+            self.wallets_path.join(name)
+        }
+    }
+
+    /// Where a wallet saved as `name` goes, as `wallet_path` with `.json` appended when missing
+    pub fn save_path(&self, name: &str) -> std::path::PathBuf {
+        if name.ends_with(".json") {
+            self.wallet_path(name)
+        } else {
+            self.wallet_path(&format!("{}.json", name))
         }
     }
-}   
+
+    /// Refuse to contact `endpoint` under `--offline`; every network client is built through this
+    pub fn require_network(&self, endpoint: &str) -> WalletResult<()> {
+        if self.offline {
+            return Err(errors::UserInputError::InvalidParameters {
+                parameter: "offline".to_string(),
+                value: "true".to_string(),
+                expected: format!("network access; --offline never contacts {}", endpoint),
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
@@ -1,31 +1,212 @@
 use clap::{Args, Parser, Subcommand};
 use rpassword::prompt_password;
-use std::path::PathBuf;
-use tracing::{error, info};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tracing::{error, info, warn};
 use web3wallet_cli::{WalletConfig, WalletError, WalletManager, WalletResult};
-use web3wallet_cli::errors::{UserInputError, FilesystemError};
+use web3wallet_cli::errors::{AuthenticationError, CryptographicError, UserInputError, FilesystemError, ValidationError};
+use web3wallet_cli::i18n::{self, tr, tr_args, Lang};
+use web3wallet_cli::services::script::{ScriptRunner, ScriptSpec, StepStatus};
+use web3wallet_cli::services::build_info::BuildInfo;
+use web3wallet_cli::services::canonical_json;
+use web3wallet_cli::services::chains::{self, ChainInfo};
+use web3wallet_cli::services::ens::{self, EnsResolver};
+use web3wallet_cli::services::commands::{self, ImportSource};
+use web3wallet_cli::services::discovery::AccountDiscovery;
+use web3wallet_cli::services::erc20::{self, Erc20Service, TokenBalance};
+use web3wallet_cli::services::explorer::{ActivitySummary, ExplorerClient, ExplorerTransaction};
+use web3wallet_cli::services::fees::{FeeEstimator, FeeOptions};
+use web3wallet_cli::services::health;
+use web3wallet_cli::services::history::{fiat_prices, ledger_csv, native_amount, HistoryEntry, HistoryLog, Period};
+use web3wallet_cli::services::prices::{self, PriceSource};
+use web3wallet_cli::services::kdf_bench;
+use web3wallet_cli::services::locked_memory;
+use web3wallet_cli::services::monitor::{BalanceMonitor, BalanceThreshold};
+use web3wallet_cli::services::multisig::{MultisigApproval, MultisigKeystore, MultisigProposal, MultisigService};
+use web3wallet_cli::services::nft::{NftService, NftStandard, NftTransfer};
+use web3wallet_cli::services::nonce_store::NonceStore;
+use web3wallet_cli::services::output_file::OutputFile;
+use web3wallet_cli::services::password_generator;
+use web3wallet_cli::services::password_source::PasswordSource;
+use web3wallet_cli::services::permit::{self, PermitDomain, TokenPermit, TokenPermitService};
+use web3wallet_cli::services::permit2::{self, Permit, Permit2Service, PermitDetails};
+use web3wallet_cli::services::quote::{display_amount, QuoteApi, QuoteClient, QuoteToken};
+use web3wallet_cli::services::raw_transaction;
+use web3wallet_cli::services::{mnemonic, MnemonicService, RpcClient};
+use web3wallet_cli::models::{PathPreset, Token, TokenRegistry, Wallet, WalletOverrides, WalletType};
+use web3wallet_cli::models::command::{GeneratedSecret, GenpassOutcome, WalletSort};
+use web3wallet_cli::models::keystore::KdfAlgorithm;
+use web3wallet_cli::services::safe::{SafeOperation, SafeService, SafeTransaction};
+use web3wallet_cli::services::shamir::ShamirService;
+use web3wallet_cli::services::shutdown::{DrainOutcome, Shutdown};
+use web3wallet_cli::services::signer::{LedgerSigner, Signer, SignerKind, SoftwareSigner};
+use web3wallet_cli::services::signing::{SignedPayload, SigningService};
+use web3wallet_cli::services::tokens::TokenListService;
+use web3wallet_cli::services::usage::{UsageKind, UsageLog};
+use web3wallet_cli::services::transaction::{parse_address, parse_bytes, parse_quantity, TransactionService, UnsignedTransaction};
+use web3wallet_cli::services::wrapped::WrappedNative;
+use web3wallet_cli::services::address_format::{self, Chain};
+use web3wallet_cli::services::agent::{AgentClient, AgentPaths, AgentServer, AgentSigner};
+use web3wallet_cli::services::approval::{ApprovalRequest, ApprovalService};
+use web3wallet_cli::utils::{render_template, split_address_path};
 
-// Helper function for password input that supports testing
-fn get_password(prompt: &str) -> Result<String, std::io::Error> {
-    // Check if we're in test mode (environment variable set)
-    if let Ok(test_password) = std::env::var("TEST_WALLET_PASSWORD") {
-        // Ensure password meets minimum requirements for testing
-        if test_password.len() < 8 {
-            return Ok("TestPassword123!".to_string());
+mod serve;
+#[cfg(feature = "tui")]
+mod tui;
+
+/// Prompt timeout set from `--prompt-timeout` or the config file; zero waits forever
+static PROMPT_TIMEOUT: OnceLock<std::time::Duration> = OnceLock::new();
+
+/// Passwords given with `--password-file` or `--password-stdin`, answering every password prompt
+static PASSWORD_SOURCE: Mutex<Option<PasswordSource>> = Mutex::new(None);
+
+/// Read a password from `--password-file`/`--password-stdin` if given, else from the terminal
+fn get_password(prompt: &str) -> WalletResult<String> {
+    let mut source = PASSWORD_SOURCE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(source) = source.as_mut() {
+        return Ok(source.next_password()?.to_string());
+    }
+
+    let owned = prompt.to_string();
+    with_prompt_timeout(prompt, move || prompt_password(owned))
+}
+
+/// Read a secret other than a password, e.g. a mnemonic: hidden at a terminal, otherwise the
+/// next line of the piped stdin
+fn get_secret(prompt: &str) -> WalletResult<String> {
+    use std::io::IsTerminal;
+
+    if std::io::stdin().is_terminal() {
+        let owned = prompt.to_string();
+        return with_prompt_timeout(prompt, move || prompt_password(owned));
+    }
+    let line = with_prompt_timeout(prompt, || {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        Ok(line)
+    })?;
+    Ok(line.trim().to_string())
+}
+
+/// Show `text` to the user on stderr, or on the terminal under `--out`; stdout carries only the output
+fn print_terminal(text: &str) -> WalletResult<()> {
+    use std::io::Write;
+
+    match output_file().as_ref() {
+        Some(file) => {
+            let mut terminal = file.terminal();
+            terminal.write_all(text.as_bytes())?;
+        }
+        None => {
+            eprint!("{}", text);
+            std::io::stderr().flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// `text` in red on the terminal, for output that must not be overlooked
+fn print_warning(text: &str) -> WalletResult<()> {
+    use std::io::IsTerminal;
+
+    if output_file().is_some() || std::io::stderr().is_terminal() {
+        print_terminal(&format!("\x1b[1;31m{}\x1b[0m\n", text))
+    } else {
+        print_terminal(&format!("{}\n", text))
+    }
+}
+
+/// Read one line of non-secret input, e.g. a confirmation
+fn prompt_line(prompt: &str) -> WalletResult<String> {
+    print_terminal(prompt)?;
+    let line = with_prompt_timeout(prompt, || {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        Ok(line)
+    })?;
+    Ok(line.trim().to_string())
+}
+
+/// Ask for the words at a few random positions of `mnemonic`; fails naming the wrong positions
+fn backup_quiz(mnemonic: &mnemonic::SecureMnemonic) -> WalletResult<()> {
+    let quiz = mnemonic.quiz(web3wallet_cli::config::bip39::QUIZ_QUESTIONS);
+    print_terminal("Enter the requested words from your backup.\n")?;
+    let mut answers = Vec::with_capacity(quiz.positions().len());
+    for prompt in quiz.prompts() {
+        answers.push(zeroize::Zeroizing::new(prompt_line(&format!("{}: ", prompt))?));
+    }
+    let answers: Vec<&str> = answers.iter().map(|answer| answer.as_str()).collect();
+    quiz.check(mnemonic, &answers)
+}
+
+/// Prompt for dice rolls until there are enough for a `word_count` word mnemonic; an empty line
+/// stops early and fails the count check
+fn read_dice_rolls(word_count: u8) -> WalletResult<zeroize::Zeroizing<Vec<u8>>> {
+    let required = mnemonic::required_dice_rolls(word_count)?;
+    print_terminal(&format!("Roll a six-sided die {} times and enter the results, several per line if you like.\n", required))?;
+    let mut rolls = zeroize::Zeroizing::new(String::new());
+    loop {
+        let count = rolls.chars().filter(|c| c.is_ascii_digit()).count();
+        if count >= required {
+            break;
+        }
+        let line = zeroize::Zeroizing::new(prompt_line(&format!("Rolls ({}/{}): ", count, required))?);
+        if line.is_empty() {
+            break;
+        }
+        rolls.push_str(&line);
+        rolls.push(' ');
+    }
+    mnemonic::parse_dice_rolls(&rolls, word_count)
+}
+
+/// Run a blocking prompt on its own thread and abort with INPUT_008 when nobody answers in time,
+/// so unattended and scripted runs fail instead of hanging on a TTY prompt
+fn with_prompt_timeout<F>(prompt: &str, read: F) -> WalletResult<String>
+where
+    F: FnOnce() -> std::io::Result<String> + Send + 'static,
+{
+    let Some(timeout) = PROMPT_TIMEOUT.get().copied().filter(|t| !t.is_zero()) else {
+        return Ok(read()?);
+    };
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(read());
+    });
+
+    match rx.recv_timeout(timeout) {
+        Ok(answer) => Ok(answer?),
+        Err(_) => {
+            // The abandoned reader may have turned echo off; the process exits before it can restore it
+            restore_terminal_echo();
+            println!();
+            Err(UserInputError::PromptTimeout {
+                prompt: prompt.trim().trim_end_matches(':').to_string(),
+                timeout,
+            }
+            .into())
         }
-        return Ok(test_password);
     }
+}
 
-    // Normal interactive mode
-    prompt_password(prompt)
+#[cfg(unix)]
+fn restore_terminal_echo() {
+    if let Ok(tty) = std::fs::File::open("/dev/tty") {
+        let _ = std::process::Command::new("stty").arg("echo").stdin(tty).status();
+    }
 }
 
+#[cfg(not(unix))]
+fn restore_terminal_echo() {}
+
 #[derive(Parser)]
 #[command(
     name = "web3wallet",
     version = env!("CARGO_PKG_VERSION"),
     about = "A secure, professional-grade Web3 wallet CLI tool",
-    long_about = "Generate, import, and manage Ethereum wallets with BIP39/BIP44 compliance and MetaMask compatibility"
+    long_about = "Generate, import, and manage Ethereum wallets with BIP39/BIP44 compliance and MetaMask compatibility",
+    after_help = "Exit status: 0 success, 1 other error, 2 invalid input, 3 authentication, 4 cryptographic, 5 filesystem, 6 network, 7 validation"
 )]
 struct Cli {
     /// Enable verbose logging
@@ -40,10 +221,196 @@ struct Cli {
     #[arg(short, long, global = true)]
     config: Option<std::path::PathBuf>,
 
+    /// Timeout in seconds for each network request
+    #[arg(long, global = true)]
+    timeout: Option<u64>,
+
+    /// Refuse network access; required to decrypt cold wallets
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Render output through a handlebars template, e.g. '{{address}} {{network}}'
+    #[arg(long, global = true)]
+    template: Option<String>,
+
+    /// Seconds to wait for an answer at a prompt before aborting, 0 waits forever
+    #[arg(long, global = true, value_name = "SECS")]
+    prompt_timeout: Option<u64>,
+
+    /// Language for prompts and hints (en, zh); detected from LC_ALL/LC_MESSAGES/LANG by default
+    #[arg(long, global = true)]
+    lang: Option<Lang>,
+
+    /// Prompt for wallet passwords even when an agent is running
+    #[arg(long, global = true)]
+    no_agent: bool,
+
+    /// Refuse to unlock keystores other users can read, instead of warning
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Disable core dumps and lock mnemonics and decrypted keys in memory so they are not swapped
+    #[arg(long, global = true)]
+    harden: bool,
+
+    /// Save wallets under passwords that fail the strength check, warning instead; for tests
+    #[arg(long, global = true)]
+    allow_weak_password: bool,
+
+    /// Read passwords from FILE instead of prompting, one line per password prompt; the last
+    /// line answers any further prompts, so one line serves a password and its confirmation
+    #[arg(long, global = true, value_name = "FILE", conflicts_with = "password_stdin")]
+    password_file: Option<PathBuf>,
+
+    /// Read passwords from stdin instead of prompting, one line per password prompt
+    #[arg(long, global = true)]
+    password_stdin: bool,
+
+    /// Write the command's output to FILE, created readable by the owner only and put in place
+    /// once the command succeeds; give it before the subcommand
+    #[arg(long, value_name = "FILE")]
+    out: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+/// Output template set by `--template`, applied to every JSON document a command prints
+static OUTPUT_TEMPLATE: OnceLock<String> = OnceLock::new();
+
+/// Destination of the top-level `--out`, moved into place once the command has succeeded
+static OUTPUT_FILE: Mutex<Option<OutputFile>> = Mutex::new(None);
+
+fn output_file() -> std::sync::MutexGuard<'static, Option<OutputFile>> {
+    OUTPUT_FILE.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Commit the `--out` file after a successful command, discard it otherwise
+fn finish_output(success: bool) -> WalletResult<()> {
+    match output_file().take() {
+        Some(file) if success => file.commit(),
+        _ => Ok(()),
+    }
+}
+
+fn print_json(value: &serde_json::Value) -> WalletResult<()> {
+    match OUTPUT_TEMPLATE.get() {
+        Some(template) => println!("{}", render_template(template, value)?),
+        None => println!("{}", serde_json::to_string_pretty(value)?),
+    }
+    Ok(())
+}
+
+/// JSON document of a command outcome, flagged successful
+fn outcome_json<T: serde::Serialize>(outcome: &T) -> WalletResult<serde_json::Value> {
+    let mut value = serde_json::to_value(outcome)?;
+    value["success"] = true.into();
+    Ok(value)
+}
+
+/// Left-aligned table with every column as wide as its widest cell
+fn table_lines(headers: &[&str], rows: &[Vec<String>]) -> Vec<String> {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let render = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = *width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = Vec::with_capacity(rows.len() + 2);
+    lines.push(render(headers.to_vec()));
+    lines.push("─".repeat(widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1)));
+    lines.extend(rows.iter().map(|row| render(row.iter().map(String::as_str).collect())));
+    lines
+}
+
+/// Print `lines` through $PAGER when stdout is a terminal and they would scroll off screen,
+/// otherwise write them out in chunks
+fn print_paged(lines: &[String], use_pager: bool) -> WalletResult<()> {
+    use std::io::{IsTerminal, Write};
+    use web3wallet_cli::config::output::{CHUNK_LINES, DEFAULT_PAGER, PAGER_MIN_LINES};
+
+    if use_pager && lines.len() > PAGER_MIN_LINES && std::io::stdout().is_terminal() {
+        let pager = std::env::var("PAGER").ok().filter(|p| !p.trim().is_empty());
+        let pager = pager.as_deref().unwrap_or(DEFAULT_PAGER);
+        let mut parts = pager.split_whitespace();
+        let spawned = parts.next().and_then(|program| {
+            std::process::Command::new(program)
+                .args(parts)
+                .stdin(std::process::Stdio::piped())
+                .spawn()
+                .ok()
+        });
+
+        if let Some(mut child) = spawned {
+            if let Some(mut stdin) = child.stdin.take() {
+                for line in lines {
+                    // The user quitting the pager early closes the pipe, which is not an error
+                    if writeln!(stdin, "{}", line).is_err() {
+                        break;
+                    }
+                }
+            }
+            child.wait()?;
+            return Ok(());
+        }
+        warn!("Could not start pager '{}', printing directly", pager);
+    }
+
+    let stdout = std::io::stdout();
+    let mut out = std::io::BufWriter::new(stdout.lock());
+    for chunk in lines.chunks(CHUNK_LINES) {
+        for line in chunk {
+            writeln!(out, "{}", line)?;
+        }
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// `index,address,derivation_path` rows, refusing to overwrite an existing file
+fn write_derive_csv(path: &std::path::Path, outcome: &web3wallet_cli::models::command::DeriveOutcome) -> WalletResult<()> {
+    use std::io::Write;
+
+    if path.exists() {
+        return Err(WalletError::Filesystem(FilesystemError::FileExists {
+            path: path.display().to_string(),
+            suggestion: "choose another --out path or remove the existing file".to_string(),
+        }));
+    }
+
+    let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+    writeln!(out, "index,address,derivation_path")?;
+    for derived in &outcome.addresses {
+        writeln!(out, "{},{},{}", derived.index, derived.address, derived.derivation_path)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Every address shown to the user goes through the chain's `AddressFormatter`
+fn display_address(address: &str) -> String {
+    address_format::display(Chain::Evm, address)
+}
+
+/// JSON document of a registry token, with its address checksummed like every other address
+fn token_json(token: &Token) -> WalletResult<serde_json::Value> {
+    let mut value = serde_json::to_value(token)?;
+    value["address"] = display_address(&token.address).into();
+    Ok(value)
+}
+
 #[derive(clap::ValueEnum, Clone, Debug)]
 enum OutputFormat {
     Table,
@@ -60,8 +427,670 @@ enum Commands {
     Load(LoadArgs),
     /// List all wallets
     List(ListArgs),
+    /// Re-encrypt a wallet into a keystore format other tools can import
+    Export(ExportArgs),
+    /// Print the account-level extended public key, for watch-only setups
+    Xpub(XpubArgs),
+    /// Split a wallet's seed into SLIP-39 Shamir shares, or archive the whole wallets directory
+    #[command(subcommand)]
+    Backup(BackupCommand),
+    /// Change a wallet's password, re-encrypting it with the configured KDF
+    Passwd(PasswdArgs),
+    /// Generate a random password or a passphrase of random words for a new keystore
+    Genpass(GenpassArgs),
+    /// Keep several named accounts in one wallet file and choose the one that signs
+    #[command(subcommand)]
+    Account(AccountCommand),
+    /// Upgrade keystores written by older versions to the current format
+    Migrate(MigrateArgs),
+    /// Delete a wallet file after confirmation
+    Delete(DeleteArgs),
+    /// Check a written-down mnemonic by asking for words at random positions
+    VerifyBackup(VerifyBackupArgs),
+    /// Set or clear a wallet's alias and optionally rename its file
+    #[command(visible_alias = "alias")]
+    Rename(RenameArgs),
+    /// Roll a keystore back to a copy taken before it was rewritten
+    RestoreBackup(RestoreBackupArgs),
+    /// Audit the wallets directory for keystores and backups other users can read
+    Doctor(DoctorArgs),
     /// Derive addresses from wallet
     Derive(DeriveArgs),
+    /// Find the used accounts and addresses of an HD wallet by scanning the chain, BIP44 gap limit
+    Discover(DiscoverArgs),
+    /// Run a declarative batch script of wallet operations
+    Script(ScriptArgs),
+    /// Derive an app-specific signing key and optionally sign a message with it
+    AppKey(AppKeyArgs),
+    /// Derive a child mnemonic for another wallet with BIP85
+    DeriveEntropy(DeriveEntropyArgs),
+    /// Sign a transaction offline and print the raw RLP hex
+    SignTx(SignTxArgs),
+    /// Decode a signed raw transaction and recover its sender, to review it before broadcast
+    DecodeTx(DecodeTxArgs),
+    /// Track the next nonce per address and chain for `sign-tx --nonce auto`
+    #[command(subcommand)]
+    Nonce(NonceCommand),
+    /// M-of-N multisig account kept offline: owners approve proposals, any M of them sign
+    #[command(subcommand)]
+    Multisig(MultisigCommand),
+    /// Sign a message with EIP-191 personal_sign
+    SignMessage(SignMessageArgs),
+    /// Sign a Safe off-chain message (EIP-712 SafeMessage)
+    SignSafeMessage(SignSafeMessageArgs),
+    /// Hash and sign Safe multisig transactions (EIP-712 SafeTx) for the Safe transaction service
+    #[command(subcommand)]
+    Safe(SafeCommand),
+    /// Check that a message or typed-data signature recovers to an expected address
+    VerifySignature(VerifySignatureArgs),
+    /// Transfer an ERC-721 or ERC-1155 token
+    SendNft(SendNftArgs),
+    /// Transfer ERC-20 tokens, or sign the transfer for broadcast elsewhere
+    SendToken(SendTokenArgs),
+    /// Sign an EIP-2612 permit, an allowance the spender submits to the token without an approve transaction
+    SignPermit(SignPermitArgs),
+    /// Wrap native currency into the network's canonical wrapped token (e.g. WETH)
+    Wrap(WrapArgs),
+    /// Unwrap the network's canonical wrapped token back to native currency
+    Unwrap(WrapArgs),
+    /// Fetch an informational swap quote from a DEX aggregator (never executed)
+    Quote(QuoteArgs),
+    /// Native and ERC-20 token balances of an address, read from the chain
+    Balance(BalanceArgs),
+    /// Check an address across all configured EVM chains for existing activity
+    XchainCheck(XchainCheckArgs),
+    /// Summarize the on-chain activity of an address
+    Activity(ActivityArgs),
+    /// List the recent transactions of an address from the chain's block explorer API
+    History(HistoryArgs),
+    /// Watch hot wallet balances and alert when a payer runs low on gas
+    Monitor(MonitorArgs),
+    /// Manage the local token registry
+    #[command(subcommand)]
+    Tokens(TokensCommand),
+    /// Local usage statistics and signing history of saved wallets, and version details for bug reports
+    #[command(subcommand)]
+    Wallet(WalletCommand),
+    /// Resolve ENS names and look up the primary name of an address
+    #[command(subcommand)]
+    Ens(EnsCommand),
+    /// Create, inspect and validate the configuration file
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Manage the chains known besides the built-in ones
+    #[command(subcommand)]
+    Network(NetworkCommand),
+    /// Time the keystore KDFs on this machine and suggest parameters for a target unlock time
+    KdfBench(KdfBenchArgs),
+    /// Cache wallet passwords in a background agent so each wallet is unlocked once
+    #[command(subcommand)]
+    Agent(AgentCommand),
+    /// Second-person approval of high-value transactions, see `[approval]` in the config file
+    #[command(subcommand)]
+    Approval(ApprovalCommand),
+    /// Answer list, derive and signing requests from local dApps and scripts over JSON-RPC
+    Serve(ServeArgs),
+    /// Browse wallets and create, import or derive from an interactive terminal UI
+    #[cfg(feature = "tui")]
+    Tui,
+}
+
+#[derive(Args)]
+struct KdfBenchArgs {
+    /// Unlock time to tune for, in milliseconds
+    #[arg(long, default_value_t = web3wallet_cli::config::kdf_bench::DEFAULT_TARGET_MS)]
+    target_ms: u64,
+
+    /// Only benchmark this KDF: argon2id, scrypt or pbkdf2
+    #[arg(long)]
+    kdf: Option<KdfAlgorithm>,
+
+    /// Write the suggestion for --kdf (default: the configured KDF) into the config file
+    #[arg(long)]
+    write: bool,
+}
+
+#[derive(Subcommand)]
+enum WalletCommand {
+    /// Unlock, sign and export counts with anomaly flags, for one wallet or all tracked ones
+    Usage {
+        /// Keystore file name or path, or a wallet address
+        wallet: Option<String>,
+    },
+    /// Version and git commit; with --verbose also build, config and directory details
+    Version,
+    /// Ledger of the transactions signed on this machine
+    #[command(subcommand)]
+    History(HistoryCommand),
+    /// Sign Uniswap Permit2 allowances (EIP-712), as many dapps ask for instead of EIP-2612 permits
+    #[command(subcommand)]
+    Permit2(Permit2Command),
+    /// Put one derived key in a keystore of its own, for a bot that should sign for that address only
+    Delegate {
+        /// Keystore of the HD wallet to take the key from, e.g. "main.json"
+        #[arg(short, long)]
+        from_file: String,
+        /// Index of the address under the wallet's account, as `derive` lists it
+        #[arg(long, value_parser = bip32_index)]
+        derive_index: u32,
+        /// Keystore to write, e.g. "bot.json"; a bare name goes to the wallets directory
+        #[arg(long)]
+        export_to: String,
+    },
+    /// Grade a keystore's KDF, password age, backup, permissions and network exposure, with fixes
+    Score {
+        /// Keystore file name or path, e.g. "my.json"
+        #[arg(short, long)]
+        from_file: String,
+    },
+    /// Addresses, keys and paths of a public test mnemonic as JSON, for pinning in other test suites
+    Fixtures {
+        /// Test mnemonic or its name: abandon, hardhat, legal or letter; real mnemonics are refused
+        #[arg(long)]
+        mnemonic: String,
+        /// BIP39 passphrase; none by default
+        #[arg(long)]
+        passphrase: Option<String>,
+        /// Accounts from index 0
+        #[arg(short = 'n', long, default_value_t = web3wallet_cli::config::fixtures::DEFAULT_COUNT)]
+        count: u32,
+        /// Write the fixtures to this file instead of stdout, e.g. "fixtures.json"
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// Export signed transactions for accounting: date, chain, from, to, asset, amount, fee, tx hash and note
+    Export {
+        #[arg(short, long, value_enum, default_value = "csv")]
+        format: HistoryFormat,
+        /// Year, quarter or month, e.g. "2025", "2025-Q1" or "2025-03"; everything when omitted
+        #[arg(long)]
+        period: Option<Period>,
+        /// Add value columns in this fiat currency at each day's historical price, e.g. "usd"
+        #[arg(long)]
+        fiat: Option<String>,
+        /// Where prices come from: coingecko, cryptocompare or file; `[prices] source` by default
+        #[arg(long, requires = "fiat")]
+        price_source: Option<PriceSource>,
+        /// CSV of date,asset,fiat,price rates; implies the file price source, works offline
+        #[arg(long, requires = "fiat")]
+        rates_file: Option<PathBuf>,
+        /// Write the ledger to this file instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum Permit2Command {
+    /// PermitSingle: an allowance for one token
+    Single {
+        #[command(flatten)]
+        common: Permit2Args,
+        /// ERC-20 token address
+        #[arg(long)]
+        token: String,
+        /// Allowance in the token's base units, or "max"
+        #[arg(long)]
+        amount: String,
+        /// Permit2 nonce for this owner, token and spender; read from the chain when omitted
+        #[arg(long)]
+        nonce: Option<u64>,
+    },
+    /// PermitBatch: allowances for several tokens under one signature
+    Batch {
+        #[command(flatten)]
+        common: Permit2Args,
+        /// <token>:<amount>[:<nonce>], once per token; amounts in base units or "max"
+        #[arg(long = "permit", required = true)]
+        permits: Vec<String>,
+    },
+}
+
+#[derive(Args)]
+struct Permit2Args {
+    /// Keystore of the token owner, e.g. "my-wallet.json"
+    #[arg(short, long)]
+    from_file: String,
+
+    /// Address (or ENS name) allowed to move the tokens, e.g. a dapp's router
+    #[arg(long)]
+    spender: String,
+
+    /// Chain name, defaults to the wallet's preferred network
+    #[arg(long)]
+    chain: Option<String>,
+
+    /// Unix time the allowance lapses; 30 days from now by default
+    #[arg(long)]
+    expiration: Option<u64>,
+
+    /// Unix time by which the signature must be submitted; 30 minutes from now by default
+    #[arg(long)]
+    sig_deadline: Option<u64>,
+
+    /// Also write the signed EIP-712 document to this file
+    #[arg(long)]
+    out: Option<PathBuf>,
+
+    /// Sign even if the wallet was created for a different chain
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum HistoryFormat {
+    /// Comma-separated values, one transaction per row
+    Csv,
+}
+
+#[derive(Subcommand)]
+enum AgentCommand {
+    /// Run the agent in the foreground until stopped or interrupted
+    Start {
+        /// Seconds a password, and the keys it opens, stay cached after the unlock that stored it
+        #[arg(long, value_name = "SECS", default_value_t = web3wallet_cli::config::agent::DEFAULT_TTL_SECS)]
+        ttl: u64,
+
+        /// Ask at this terminal before signing each request with the keys the agent holds
+        #[arg(long)]
+        confirm: bool,
+    },
+    /// Show whether an agent is running and how many passwords and keys it holds
+    Status,
+    /// Forget every cached password and key and keep the agent running
+    Lock,
+    /// Stop the running agent
+    Stop,
+}
+
+#[derive(Args)]
+struct ServeArgs {
+    /// Loopback address and port to listen on
+    #[arg(long, default_value = web3wallet_cli::config::serve::DEFAULT_LISTEN)]
+    listen: std::net::SocketAddr,
+
+    /// File holding the bearer token clients send, created with a new token when missing
+    /// (default: serve.token in the state directory)
+    #[arg(long)]
+    token_file: Option<PathBuf>,
+
+    /// Origin of a web page allowed to call the API from a browser, e.g. http://localhost:3000
+    #[arg(long)]
+    allow_origin: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum ApprovalCommand {
+    /// Create the companion key approval requests are sealed to; its public half goes in `[approval] public_key`
+    Keygen {
+        /// File for the secret key, created readable by the owner only
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Open a sealed request, show the transaction and sign an approval token for it
+    Approve {
+        /// Sealed request as received from the ntfy topic, webhook or printed by the signer
+        request: String,
+        /// Companion secret key from `approval keygen`
+        #[arg(long)]
+        key: PathBuf,
+        /// Approver keystore, one of `[approval] approvers`; not used with --signer ledger
+        #[arg(short, long)]
+        from_file: Option<String>,
+        /// Signing backend: software (keystore file) or ledger
+        #[arg(long, default_value = "software")]
+        signer: SignerKind,
+        /// BIP44 path on the Ledger, defaults to the first account
+        #[arg(long)]
+        hd_path: Option<String>,
+        /// Approve without showing a confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AccountCommand {
+    /// Add an account derived from the wallet's seed, or an imported private key
+    Add {
+        ///Example: "my-wallet.json" or "/path/to/wallet.json"
+        filename: String,
+        /// Name the account is selected by
+        name: String,
+        /// BIP44 account to derive, m/44'/60'/{index}'/0; the first one not in use by default
+        #[arg(long, conflicts_with_all = ["path", "private_key"])]
+        index: Option<u32>,
+        /// Parent path to derive the account under, its address is index 0 below it
+        #[arg(long, conflicts_with = "private_key")]
+        path: Option<String>,
+        /// Import this private key as the account instead of deriving one
+        #[arg(long)]
+        private_key: Option<String>,
+        /// Make the new account the one that signs
+        #[arg(long)]
+        select: bool,
+    },
+    /// List a wallet's accounts
+    List {
+        ///Example: "my-wallet.json" or "/path/to/wallet.json"
+        filename: String,
+    },
+    /// Choose the account that signs and shows its address; "main" is the wallet's own
+    Select {
+        ///Example: "my-wallet.json" or "/path/to/wallet.json"
+        filename: String,
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum BackupCommand {
+    /// M-of-N SLIP-39 shares of the wallet's BIP32 seed; restore with `import --shamir`
+    Shamir {
+        ///Example: "my-wallet.json" or "/path/to/wallet.json"
+        filename: String,
+        /// Shares needed to restore the wallet
+        #[arg(long)]
+        threshold: u8,
+        /// Shares to create, at most 16
+        #[arg(long)]
+        shares: u8,
+        /// Optional SLIP-39 passphrase the seed is encrypted under; needed again to restore
+        #[arg(long)]
+        passphrase: Option<String>,
+    },
+    /// Encrypt every file of the wallets directory into one archive under a new password
+    Create {
+        /// Archive to write, e.g. "wallets-2024.tar.enc"; an existing file is never overwritten
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Check an archive from `backup create` and unpack it into the wallets directory
+    Restore {
+        archive: PathBuf,
+        /// Overwrite files changed since the archive was made
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum EnsCommand {
+    /// Address record of a name, e.g. "vitalik.eth"
+    Resolve {
+        name: String,
+        /// Always ask the node instead of the local lookup cache
+        #[arg(long)]
+        no_cache: bool,
+    },
+    /// Primary name of an address, only shown if it resolves back to the address
+    Reverse {
+        address: String,
+        #[arg(long)]
+        no_cache: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum NetworkCommand {
+    /// Define a chain in the config file, e.g. "gnosis"
+    Add {
+        name: String,
+        #[arg(long)]
+        chain_id: u64,
+        #[arg(long)]
+        rpc_url: String,
+        #[arg(long)]
+        explorer_url: Option<String>,
+        /// Native currency symbol, defaults to ETH
+        #[arg(long)]
+        symbol: Option<String>,
+        /// WETH-style wrapper contract, needed by wrap/unwrap
+        #[arg(long)]
+        wrapped_native: Option<String>,
+        /// Replace an existing definition of the same name
+        #[arg(long)]
+        force: bool,
+    },
+    /// List built-in and configured chains
+    List,
+    /// Remove a chain defined in the config file
+    Remove {
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Write a commented default config file
+    Init {
+        /// Destination, defaults to --config or ~/.web3wallet/config.toml
+        #[arg(long)]
+        path: Option<PathBuf>,
+        /// Overwrite an existing file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Print the effective configuration after merging the file, environment and flags
+    Show,
+    /// Check a config file for errors
+    Validate {
+        /// File to check, defaults to --config or ~/.web3wallet/config.toml
+        path: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum TokensCommand {
+    /// Register a token
+    Add {
+        symbol: String,
+        address: String,
+        #[arg(short, long)]
+        decimals: u8,
+        #[arg(long, default_value = "mainnet")]
+        chain: String,
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Remove a token by symbol or address
+    Remove {
+        token: String,
+        #[arg(long, default_value = "mainnet")]
+        chain: String,
+    },
+    /// List registered tokens
+    List {
+        /// Only tokens on this chain
+        #[arg(long)]
+        chain: Option<String>,
+    },
+    /// Import tokens from a standard token list URL
+    Seed {
+        #[arg(long, default_value = web3wallet_cli::config::tokens::DEFAULT_TOKEN_LIST_URL)]
+        url: String,
+        /// Only tokens on this chain
+        #[arg(long)]
+        chain: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum NonceCommand {
+    /// Set the tracked nonce from the chain's pending transaction count, keeping nonces signed
+    /// here that are not on chain yet
+    Sync {
+        #[command(flatten)]
+        account: NonceAccountArgs,
+        /// Chain to read the count from, defaults to the configured network
+        #[arg(long)]
+        chain: Option<String>,
+    },
+    /// Forget the tracked nonces of an address
+    Reset {
+        #[command(flatten)]
+        account: NonceAccountArgs,
+        /// Only on this chain, every chain by default
+        #[arg(long)]
+        chain: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SafeCommand {
+    /// Compute the SafeTxHash owners sign, to compare with what the Safe app shows
+    Hash {
+        #[command(flatten)]
+        tx: SafeTxArgs,
+    },
+    /// Sign the SafeTxHash as an owner and print the proposal for the Safe transaction service
+    Sign {
+        /// Keystore of the Safe owner, e.g. "owner.json"
+        #[arg(short, long)]
+        from_file: String,
+        #[command(flatten)]
+        tx: SafeTxArgs,
+        /// Also write the proposal request body to this file, e.g. "proposal.json"
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Sign even if the wallet was created for a different chain
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Args)]
+struct SafeTxArgs {
+    /// Safe contract address (Safe 1.3.0 or later)
+    #[arg(long)]
+    safe: String,
+
+    /// Address the Safe calls
+    #[arg(long)]
+    to: String,
+
+    /// Value in wei
+    #[arg(long, default_value = "0")]
+    value: String,
+
+    /// Calldata as hex
+    #[arg(long)]
+    data: Option<String>,
+
+    /// call or delegatecall
+    #[arg(long, default_value = "call")]
+    operation: SafeOperation,
+
+    /// Gas for the inner call, 0 to forward all gas
+    #[arg(long, default_value = "0")]
+    safe_tx_gas: String,
+
+    /// Gas paid for outside the inner call when refunding, 0 without refund
+    #[arg(long, default_value = "0")]
+    base_gas: String,
+
+    /// Refund gas price, 0 without refund
+    #[arg(long, default_value = "0")]
+    gas_price: String,
+
+    /// Token the refund is paid in, the native currency by default
+    #[arg(long)]
+    gas_token: Option<String>,
+
+    /// Who receives the refund, the executor by default
+    #[arg(long)]
+    refund_receiver: Option<String>,
+
+    /// Safe nonce, read from the Safe when omitted
+    #[arg(long)]
+    nonce: Option<u64>,
+
+    /// Chain name, defaults to the wallet's preferred network or, for `safe hash`, the configured one
+    #[arg(long)]
+    chain: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum MultisigCommand {
+    /// Create a multisig account whose key is split among the owners, any --threshold of whom can sign
+    Create {
+        /// Name to save the multisig keystore under, e.g. "treasury"
+        name: String,
+        /// Approvals needed to sign, at least 2
+        #[arg(long)]
+        threshold: u8,
+        /// Owner keystore to unlock here, or <address>:<public key> from `multisig owner-key`; repeat per owner
+        #[arg(long = "owner", required = true)]
+        owners: Vec<String>,
+    },
+    /// Print an owner's companion public key, for a `multisig create` run on another machine
+    OwnerKey {
+        /// Owner keystore, e.g. "alice.json"
+        #[arg(short, long)]
+        from_file: String,
+    },
+    /// Propose a transaction from the multisig account, to be combined by the owner in --from-file
+    Propose {
+        /// Multisig keystore, e.g. "treasury.json"
+        multisig: String,
+        /// Unsigned transaction JSON file with nonce, gas and fees set, as `sign-tx --tx-json` takes
+        #[arg(long)]
+        tx_json: PathBuf,
+        /// Keystore of the owner who collects the approvals (no password needed)
+        #[arg(short, long)]
+        from_file: String,
+        /// Shown to the owners asked to approve
+        #[arg(long)]
+        note: Option<String>,
+        /// Write the proposal to this file, e.g. "proposal.json"
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Approve a proposal as an owner, passing your share of the key to the coordinator
+    Approve {
+        /// Multisig keystore, e.g. "treasury.json"
+        multisig: String,
+        /// Proposal file from `multisig propose`
+        proposal: PathBuf,
+        /// Owner keystore, e.g. "bob.json"
+        #[arg(short, long)]
+        from_file: String,
+        /// Write the approval to this file, e.g. "bob.approval.json"
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Approve without showing a confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Sign the proposed transaction once enough owners approved
+    Combine {
+        /// Multisig keystore, e.g. "treasury.json"
+        multisig: String,
+        /// Proposal file from `multisig propose`
+        proposal: PathBuf,
+        /// Approval files from `multisig approve`, or - to read them from stdin
+        #[arg(required = true)]
+        approvals: Vec<String>,
+        /// Keystore of the coordinator the proposal names; with approvals on stdin give the
+        /// password with --password-file or at the prompt
+        #[arg(short, long)]
+        from_file: String,
+    },
+}
+
+#[derive(Args)]
+struct NonceAccountArgs {
+    /// Keystore whose address to track, e.g. "my.json" (no password needed)
+    #[arg(short, long, required_unless_present = "address", conflicts_with = "address")]
+    from_file: Option<String>,
+
+    /// Address to track instead of a keystore
+    #[arg(short, long)]
+    address: Option<String>,
 }
 
 #[derive(Args)]
@@ -69,26 +1098,112 @@ struct CreateArgs {
     #[arg(short, long, value_parser = validate_word_count, default_value = "12")]
     words: u8,
 
+    /// Mnemonic wordlist: english, spanish, french, italian, portuguese, czech, japanese, korean, chinese_simplified or chinese_traditional
+    #[arg(long, default_value = "english", value_parser = mnemonic_language)]
+    language: bip39::Language,
+
     #[arg(short, long)]
     save: Option<String>,
     
     #[arg(short, long, default_value = "mainnet")]
     network: String,
+
+    /// Prompt for a BIP39 passphrase mixed into the seed; it is not saved, so every unlock asks for it
+    #[arg(long)]
+    passphrase: bool,
+
+    /// Mark the saved wallet as cold storage (offline-only, passphrase required)
+    #[arg(long, requires = "save")]
+    cold: bool,
+
+    /// Confirm the mnemonic and passphrase backup has been verified
+    #[arg(long)]
+    backup_verified: bool,
+
+    /// Save without the backup quiz that otherwise runs in a terminal
+    #[arg(long, requires = "save")]
+    skip_verify: bool,
+
+    /// KDF for the saved keystore: argon2id, scrypt or pbkdf2; defaults to the config
+    #[arg(long, requires = "save")]
+    kdf: Option<KdfAlgorithm>,
+
+    /// BIP44 account, the primary address is m/44'/60'/{account}'/{change}/0; defaults to 0
+    #[arg(long, value_parser = bip32_index)]
+    account: Option<u32>,
+
+    /// BIP44 change level (0 external, 1 internal); defaults to 0
+    #[arg(long, value_parser = bip32_index)]
+    change: Option<u32>,
+
+    /// Your own entropy as hex (e.g. from coin flips), XORed with the system randomness
+    #[arg(long, conflicts_with = "dice")]
+    entropy_hex: Option<String>,
+
+    /// Enter dice rolls interactively, mixed with the system randomness
+    #[arg(long)]
+    dice: bool,
 }
 
 #[derive(Args)]
+#[command(group = clap::ArgGroup::new("passphrase_source").args(["mnemonic", "shamir"]))]
 struct ImportArgs {
-    #[arg(short, long, conflicts_with = "private_key")]
+    #[arg(short, long, conflicts_with_all = ["private_key", "keystore", "xprv", "seed_hex"])]
     mnemonic: Option<String>,
 
-    #[arg(short, long, conflicts_with = "mnemonic")]
+    #[arg(short, long, conflicts_with_all = ["mnemonic", "keystore", "xprv", "seed_hex"])]
     private_key: Option<String>,
 
+    /// BIP32 root key (xprv...) from tools that never showed a mnemonic; imports an HD wallet
+    #[arg(long, conflicts_with_all = ["keystore", "seed_hex"])]
+    xprv: Option<String>,
+
+    /// Raw 64-byte BIP39 seed as 128 hex characters; imports an HD wallet
+    #[arg(long, conflicts_with = "keystore")]
+    seed_hex: Option<String>,
+
+    /// Keystore v3 JSON file exported from geth, MyEtherWallet or MetaMask
+    #[arg(short, long)]
+    keystore: Option<PathBuf>,
+
+    /// Restore from SLIP-39 shares made by `backup shamir`, entered one per line
+    #[arg(long, conflicts_with_all = ["mnemonic", "private_key", "xprv", "seed_hex", "keystore"])]
+    shamir: bool,
+
+    /// Account-level xpub, e.g. from `xpub`; imports a watch-only wallet that derives but never signs
+    #[arg(long, conflicts_with_all = ["mnemonic", "private_key", "xprv", "seed_hex", "keystore", "shamir"])]
+    xpub: Option<String>,
+
+    /// Imports a watch-only wallet for this one address
+    #[arg(long, conflicts_with_all = ["mnemonic", "private_key", "xprv", "seed_hex", "keystore", "shamir", "xpub"])]
+    address: Option<String>,
+
+    /// Path layout of the wallet the mnemonic comes from: metamask, ledger-live, legacy or mew
+    #[arg(long, conflicts_with_all = ["private_key", "xpub", "address"])]
+    path_preset: Option<PathPreset>,
+
     #[arg(short, long)]
     save: Option<String>,
 
     #[arg(short, long, default_value = "mainnet")]
     network: String,
+
+    /// Prompt for a BIP39 passphrase mixed into the seed, or the SLIP-39 passphrase with --shamir;
+    /// a BIP39 passphrase is not saved, so every unlock asks for it
+    #[arg(long, requires = "passphrase_source")]
+    passphrase: bool,
+
+    /// Mark the saved wallet as cold storage (offline-only, passphrase required)
+    #[arg(long, requires = "save")]
+    cold: bool,
+
+    /// Confirm the mnemonic and passphrase backup has been verified
+    #[arg(long)]
+    backup_verified: bool,
+
+    /// KDF for the saved keystore: argon2id, scrypt or pbkdf2; defaults to the config
+    #[arg(long, requires = "save")]
+    kdf: Option<KdfAlgorithm>,
 }
 
 #[derive(Args)]
@@ -103,533 +1218,5202 @@ struct LoadArgs {
     derive: Option<u32>,
 }
 
-#[derive(Args)]
-struct ListArgs {
-    #[arg(short, long)]
-    path: Option<std::path::PathBuf>,
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum ExportFormat {
+    /// Web3 Secret Storage v3 (geth, MetaMask, MyEtherWallet)
+    V3,
 }
 
 #[derive(Args)]
-struct DeriveArgs {
+struct ExportArgs {
+    ///Example: "my-wallet.json" or "/path/to/wallet.json"
+    filename: String,
 
-    #[arg(short, long)]
-    path: String,
+    #[arg(short, long, value_enum, default_value = "v3")]
+    format: ExportFormat,
 
-    #[arg(short, long)]
-    from_file: Option<String>,
+    /// Where to write the exported keystore
+    #[arg(long)]
+    out: PathBuf,
 
-    #[arg(short = 'n', long, default_value = "1")]
-    count: u32,
+    /// Use geth's light scrypt parameters (faster, weaker)
+    #[arg(long)]
+    light: bool,
+}
 
-    #[arg(short = 'i', long, default_value = "0")]
-    start_index: u32,
+#[derive(Args)]
+struct XpubArgs {
+    ///Example: "my-wallet.json" or "/path/to/wallet.json"
+    filename: String,
+
+    /// Also list the public keys of this many addresses from index 0
+    #[arg(long, value_name = "COUNT", default_value = "0")]
+    public_keys: u32,
 }
 
-fn validate_word_count(s: &str) -> Result<u8, String> {
-    match s.parse::<u8>() {
-        Ok(n @ 12) | Ok(n @ 24) => Ok(n),
-        Ok(n) => Err(format!("Word count must be 12 or 24, got {}", n)),
-        Err(_) => Err(format!("Invalid number: {}", s)),
-    }
+#[derive(Args)]
+struct PasswdArgs {
+    ///Example: "my-wallet.json" or "/path/to/wallet.json"
+    filename: String,
 }
 
-fn init_logging(verbose: bool){
-    let level = if verbose{
-        tracing::Level::DEBUG
-    }else{
-        tracing::Level::INFO
-    };
+#[derive(Args)]
+struct GenpassArgs {
+    /// Characters of a random password; one of 24 is made when --words is not given
+    #[arg(long)]
+    length: Option<usize>,
 
-    tracing_subscriber::fmt()
-        .with_max_level(level)
-        .with_target(false)
+    /// Words of a passphrase from the BIP39 English wordlist, 11 bits each
+    #[arg(long)]
+    words: Option<usize>,
+}
+
+#[derive(Args)]
+#[command(group = clap::ArgGroup::new("change").required(true).multiple(true).args(["alias", "clear_alias", "to"]))]
+struct RenameArgs {
+    ///Example: "my-wallet.json" or "/path/to/wallet.json"
+    filename: String,
+
+    /// New alias
+    #[arg(long, conflicts_with = "clear_alias")]
+    alias: Option<String>,
+
+    /// Remove the alias
+    #[arg(long)]
+    clear_alias: bool,
+
+    /// New file name in the same directory, ".json" is added when missing
+    #[arg(long)]
+    to: Option<String>,
+
+    /// Decrypt the keystore first to prove ownership
+    #[arg(long)]
+    require_password: bool,
+}
+
+#[derive(Args)]
+struct RestoreBackupArgs {
+    ///Example: "my-wallet.json" or "/path/to/wallet.json"
+    filename: String,
+
+    /// Backup file to restore, the newest one when omitted
+    #[arg(long, conflicts_with = "list")]
+    backup: Option<PathBuf>,
+
+    /// Only list the backups
+    #[arg(long)]
+    list: bool,
+}
+
+#[derive(Args)]
+struct DoctorArgs {
+    /// Restrict what the audit finds to the owner: 700 for the directory, 600 for files
+    #[arg(long)]
+    fix: bool,
+}
+
+#[derive(Args)]
+struct VerifyBackupArgs {
+    ///Example: "my-wallet.json" or "/path/to/wallet.json"
+    filename: String,
+}
+
+#[derive(Args)]
+struct DeleteArgs {
+    ///Example: "my-wallet.json" or "/path/to/wallet.json"
+    filename: String,
+
+    /// Skip the confirmation prompt, for scripts
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Overwrite the file with random bytes before removing it
+    #[arg(long)]
+    shred: bool,
+}
+
+#[derive(Args)]
+struct MigrateArgs {
+    ///Example: "my-wallet.json" or "/path/to/wallet.json"
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    filename: Option<String>,
+
+    /// Migrate every keystore in the wallets directory
+    #[arg(long)]
+    all: bool,
+
+    /// Report what would change without rewriting any file
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Also re-encrypt keystores whose KDF is older or cheaper than the configured one (prompts for each password)
+    #[arg(long)]
+    rekey: bool,
+}
+
+#[derive(Args)]
+struct ListArgs {
+    #[arg(short, long)]
+    path: Option<std::path::PathBuf>,
+
+    /// Only wallets created for this network
+    #[arg(long)]
+    network: Option<String>,
+
+    /// Order by filename, created, network, alias or address
+    #[arg(long, default_value = "filename")]
+    sort: WalletSort,
+}
+
+#[derive(Args)]
+struct DeriveArgs {
+
+    /// Full path of the first address, e.g. "m/44'/60'/1'/0/5"; the rest follow its last index
+    #[arg(short, long, conflicts_with_all = ["account", "change", "start_index"])]
+    path: Option<String>,
+
+    /// Path layout of another wallet: metamask, ledger-live, legacy or mew
+    #[arg(long, conflicts_with_all = ["path", "account", "change"])]
+    path_preset: Option<PathPreset>,
+
+    /// BIP44 account, addresses are m/44'/60'/{account}'/{change}/{index}; defaults to 0
+    #[arg(long, value_parser = bip32_index)]
+    account: Option<u32>,
+
+    /// BIP44 change level (0 external, 1 internal); defaults to 0
+    #[arg(long, value_parser = bip32_index)]
+    change: Option<u32>,
+
+    #[arg(short, long)]
+    from_file: Option<String>,
+
+    #[arg(short = 'n', long, default_value = "1")]
+    count: u32,
+
+    #[arg(short = 'i', long, default_value = "0")]
+    start_index: u32,
+
+    /// Signing backend: software (keystore or mnemonic) or ledger
+    #[arg(long, default_value = "software")]
+    signer: SignerKind,
+
+    /// Write the addresses as CSV to this file instead of the terminal, e.g. "addresses.csv"
+    #[arg(long)]
+    out: Option<PathBuf>,
+
+    /// Print straight to the terminal even when the table would not fit on screen
+    #[arg(long)]
+    no_pager: bool,
+
+    /// Include each address's private key, for moving single accounts to other tools; asks for confirmation
+    #[arg(long, conflicts_with = "out")]
+    show_private_keys: bool,
+}
+
+#[derive(Args)]
+struct DiscoverArgs {
+    /// Keystore file name or path, e.g. "restored.json"
+    wallet_file: String,
+
+    /// Unused addresses in a row after which an account is considered fully scanned
+    #[arg(long, default_value_t = web3wallet_cli::config::discovery::DEFAULT_GAP_LIMIT, value_parser = clap::value_parser!(u32).range(1..=1000))]
+    gap_limit: u32,
+
+    /// Scan only this BIP44 account instead of walking accounts from 0
+    #[arg(long, value_parser = bip32_index)]
+    account: Option<u32>,
+
+    /// Chain to scan, defaults to the configured network
+    #[arg(long)]
+    chain: Option<String>,
+}
+
+#[derive(Args)]
+struct ScriptArgs {
+    /// Script file (YAML), e.g. "ops.yaml"
+    file: PathBuf,
+
+    /// Keep running remaining steps after a failure
+    #[arg(long)]
+    continue_on_error: bool,
+}
+
+#[derive(Args)]
+struct AppKeyArgs {
+    /// Application namespace, e.g. "github.com"
+    #[arg(short, long)]
+    app: String,
+
+    #[arg(short, long, default_value = "0")]
+    index: u32,
+
+    #[arg(short, long)]
+    from_file: Option<String>,
+
+    /// Sign this message (EIP-191) with the app key; text, or hex bytes with --hex
+    #[arg(short, long)]
+    message: Option<String>,
+
+    /// Treat the message as hex encoded bytes
+    #[arg(long, requires = "message")]
+    hex: bool,
+}
+
+#[derive(Args)]
+struct DeriveEntropyArgs {
+    /// Child number; each index gives an unrelated mnemonic
+    #[arg(short, long, default_value = "0", value_parser = bip32_index)]
+    index: u32,
+
+    /// Words in the child mnemonic (12, 18 or 24)
+    #[arg(short, long, default_value = "12")]
+    words: u8,
+
+    /// Wordlist of the child mnemonic: english, spanish, french, italian, portuguese, czech, japanese, korean, chinese_simplified or chinese_traditional
+    #[arg(long, default_value = "english", value_parser = mnemonic_language)]
+    language: bip39::Language,
+
+    #[arg(short, long)]
+    from_file: Option<String>,
+}
+
+#[derive(Args)]
+struct SignTxArgs {
+    /// Keystore to sign with, e.g. "my-wallet.json"; not used with --signer ledger
+    #[arg(short, long)]
+    from_file: Option<String>,
+
+    /// Signing backend: software (keystore) or ledger
+    #[arg(long, default_value = "software")]
+    signer: SignerKind,
+
+    /// Ledger account path, defaults to the first account
+    #[arg(long)]
+    hd_path: Option<String>,
+
+    /// Unsigned transaction JSON file instead of individual fields
+    #[arg(long, conflicts_with_all = ["to", "nonce", "gas_price", "max_fee", "max_priority_fee", "chain_id", "data"])]
+    tx_json: Option<PathBuf>,
+
+    /// Recipient address, omit for contract creation
+    #[arg(long)]
+    to: Option<String>,
+
+    /// Value in wei
+    #[arg(long, default_value = "0")]
+    value: String,
+
+    /// Nonce, or "auto" for the one tracked for the signer on this chain (see `nonce sync`)
+    #[arg(long, required_unless_present = "tx_json")]
+    nonce: Option<String>,
+
+    #[arg(long, default_value = "21000")]
+    gas: String,
+
+    /// Gas price in wei, implies a legacy transaction
+    #[arg(long, conflicts_with_all = ["max_fee", "max_priority_fee"])]
+    gas_price: Option<String>,
+
+    /// EIP-1559 max fee per gas in wei, estimated from eth_feeHistory when omitted
+    #[arg(long)]
+    max_fee: Option<String>,
+
+    /// EIP-1559 max priority fee per gas in wei, estimated when omitted
+    #[arg(long)]
+    max_priority_fee: Option<String>,
+
+    /// Build a legacy (type-0) transaction instead of EIP-1559
+    #[arg(long)]
+    legacy: bool,
+
+    #[arg(long, required_unless_present = "tx_json")]
+    chain_id: Option<u64>,
+
+    /// Calldata as hex
+    #[arg(long)]
+    data: Option<String>,
+
+    /// Sign even if the wallet was created for a different chain
+    #[arg(long)]
+    force: bool,
+
+    /// Note kept with the transaction in the signing history, see `wallet history export`
+    #[arg(long)]
+    note: Option<String>,
+
+    /// Token from `approval approve`, needed from `[approval] threshold` on
+    #[arg(long)]
+    approval_token: Option<String>,
+}
+
+#[derive(Args)]
+struct DecodeTxArgs {
+    /// Signed transaction as 0x hex, e.g. the raw output of sign-tx
+    #[arg(required_unless_present = "file", conflicts_with = "file")]
+    raw: Option<String>,
+
+    /// Read the hex from this file instead, e.g. a blob transaction too long for the command line
+    #[arg(long)]
+    file: Option<PathBuf>,
+
+    /// Chain the transaction is meant for, defaults to the configured network
+    #[arg(long, conflicts_with = "chain_id")]
+    chain: Option<String>,
+
+    /// Chain id the transaction is meant for, for chains that are not configured
+    #[arg(long)]
+    chain_id: Option<u64>,
+}
+
+#[derive(Args)]
+struct SignMessageArgs {
+    /// Keystore to sign with, e.g. "my-wallet.json"; not used with --signer ledger
+    #[arg(short, long)]
+    from_file: Option<String>,
+
+    /// Signing backend: software (keystore) or ledger
+    #[arg(long, default_value = "software")]
+    signer: SignerKind,
+
+    /// Ledger account path, defaults to the first account
+    #[arg(long)]
+    hd_path: Option<String>,
+
+    /// Message text, or hex bytes with --hex
+    #[arg(short, long)]
+    message: String,
+
+    /// Treat the message as hex encoded bytes
+    #[arg(long)]
+    hex: bool,
+}
+
+#[derive(Args)]
+struct SignSafeMessageArgs {
+    /// Keystore of the Safe owner, e.g. "owner.json"
+    #[arg(short, long)]
+    from_file: String,
+
+    /// Safe contract address
+    #[arg(long)]
+    safe: String,
+
+    #[arg(long)]
+    chain_id: u64,
+
+    /// Message text, or hex bytes with --hex
+    #[arg(short, long)]
+    message: String,
+
+    /// Treat the message as hex encoded bytes (e.g. an EIP-712 typed data hash)
+    #[arg(long)]
+    hex: bool,
+
+    /// Sign even if the wallet was created for a different chain
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Args)]
+struct VerifySignatureArgs {
+    /// Signed message text (EIP-191 personal_sign)
+    #[arg(short, long, required_unless_present = "typed_data", conflicts_with = "typed_data")]
+    message: Option<String>,
+
+    /// EIP-712 typed data JSON file instead of a message
+    #[arg(long)]
+    typed_data: Option<PathBuf>,
+
+    /// 65 byte signature as hex
+    #[arg(short, long)]
+    signature: String,
+
+    /// Address (or ENS name) the signature is expected to recover to
+    #[arg(short, long)]
+    address: String,
+}
+
+#[derive(Args)]
+struct SendNftArgs {
+    /// Keystore of the current owner, e.g. "my-wallet.json"
+    #[arg(short, long)]
+    from_file: String,
+
+    /// NFT contract address
+    #[arg(long)]
+    contract: String,
+
+    /// Token id, decimal or 0x hex
+    #[arg(long)]
+    token_id: String,
+
+    /// Recipient address or ENS name
+    #[arg(long)]
+    to: String,
+
+    /// Number of tokens to send (ERC-1155 only)
+    #[arg(long)]
+    amount: Option<String>,
+
+    /// Contract standard (erc721 or erc1155), detected via ERC-165 when omitted
+    #[arg(long)]
+    standard: Option<NftStandard>,
+
+    /// Chain name, defaults to the wallet's preferred network
+    #[arg(long)]
+    chain: Option<String>,
+
+    /// Build a legacy (type-0) transaction instead of EIP-1559
+    #[arg(long)]
+    legacy: bool,
+
+    /// Print the signed transaction instead of broadcasting it
+    #[arg(long)]
+    sign_only: bool,
+
+    /// Sign even if the wallet was created for a different chain
+    #[arg(long)]
+    force: bool,
+
+    /// Note kept with the transaction in the signing history, see `wallet history export`
+    #[arg(long)]
+    note: Option<String>,
+}
+
+#[derive(Args)]
+struct SendTokenArgs {
+    /// Keystore to send from, e.g. "my-wallet.json"
+    #[arg(short, long)]
+    from_file: String,
+
+    /// Token symbol or address, from `tokens list` or the bundled well-known tokens
+    #[arg(long)]
+    token: String,
+
+    /// Recipient address or ENS name
+    #[arg(long)]
+    to: String,
+
+    /// Amount in whole tokens, e.g. "100" or "0.5"
+    #[arg(long)]
+    amount: String,
+
+    /// Chain name, defaults to the wallet's preferred network
+    #[arg(long)]
+    chain: Option<String>,
+
+    /// Build a legacy (type-0) transaction instead of EIP-1559
+    #[arg(long)]
+    legacy: bool,
+
+    /// Print the signed transaction instead of broadcasting it
+    #[arg(long)]
+    sign_only: bool,
+
+    /// Write the signed raw transaction to this file instead of broadcasting it, e.g. "transfer.hex"
+    #[arg(long)]
+    out: Option<PathBuf>,
+
+    /// Sign even if the wallet was created for a different chain
+    #[arg(long)]
+    force: bool,
+
+    /// Note kept with the transaction in the signing history, see `wallet history export`
+    #[arg(long)]
+    note: Option<String>,
+
+    /// Token from `approval approve`, needed from `[approval] threshold` on
+    #[arg(long)]
+    approval_token: Option<String>,
+}
+
+#[derive(Args)]
+struct SignPermitArgs {
+    /// Keystore of the token owner, e.g. "my-wallet.json"
+    #[arg(short, long)]
+    from_file: String,
+
+    /// Token symbol or address, from `tokens list` or the bundled well-known tokens
+    #[arg(long)]
+    token: String,
+
+    /// Address (or ENS name) allowed to move the tokens, e.g. a dapp's router
+    #[arg(long)]
+    spender: String,
+
+    /// Allowance in the token's base units, or "max"
+    #[arg(long)]
+    value: String,
+
+    /// Unix time after which the permit can no longer be submitted
+    #[arg(long)]
+    deadline: u64,
+
+    /// Chain name, defaults to the wallet's preferred network
+    #[arg(long)]
+    chain: Option<String>,
+
+    /// The token's nonces(owner); read from the chain when omitted
+    #[arg(long)]
+    nonce: Option<String>,
+
+    /// The token's DOMAIN_SEPARATOR(); read from the chain when omitted, or computed from --domain-name offline
+    #[arg(long)]
+    domain_separator: Option<String>,
+
+    /// Name in the token's EIP-712 domain; the token's name() by default
+    #[arg(long)]
+    domain_name: Option<String>,
+
+    /// Version in the token's EIP-712 domain; the token's version(), or "1", by default
+    #[arg(long)]
+    domain_version: Option<String>,
+
+    /// Also write the signed permit (v, r, s, calldata and EIP-712 document) to this file
+    #[arg(long)]
+    out: Option<PathBuf>,
+
+    /// Sign even if the wallet was created for a different chain
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Args)]
+struct WrapArgs {
+    /// Amount in ether units, e.g. "1.0"
+    amount: String,
+
+    /// Keystore to sign with, e.g. "my-wallet.json"
+    #[arg(short, long)]
+    from_file: String,
+
+    /// Network name, defaults to the wallet's preferred network
+    #[arg(short, long)]
+    network: Option<String>,
+
+    /// Build a legacy (type-0) transaction instead of EIP-1559
+    #[arg(long)]
+    legacy: bool,
+
+    /// Print the signed transaction instead of broadcasting it
+    #[arg(long)]
+    sign_only: bool,
+
+    /// Sign even if the wallet was created for a different chain
+    #[arg(long)]
+    force: bool,
+
+    /// Note kept with the transaction in the signing history, see `wallet history export`
+    #[arg(long)]
+    note: Option<String>,
+
+    /// Token from `approval approve`, needed from `[approval] threshold` on
+    #[arg(long)]
+    approval_token: Option<String>,
+}
+
+#[derive(Args)]
+struct QuoteArgs {
+    /// Token to sell, "ETH" or a symbol/address from the token registry
+    #[arg(long)]
+    sell: String,
+
+    /// Token to buy, "ETH" or a symbol/address from the token registry
+    #[arg(long)]
+    buy: String,
+
+    /// Amount to sell in token units, e.g. "1.5"
+    #[arg(long)]
+    amount: String,
+
+    #[arg(long, default_value = "mainnet")]
+    chain: String,
+
+    /// Aggregator API flavor: 0x or 1inch
+    #[arg(long, default_value = "0x")]
+    aggregator: QuoteApi,
+
+    /// Aggregator endpoint, overrides QUOTE_API_URL and the public default
+    #[arg(long)]
+    api_url: Option<String>,
+}
+
+#[derive(Args)]
+struct BalanceArgs {
+    /// Address or ENS name
+    #[arg(required_unless_present = "from_file", conflicts_with = "from_file")]
+    address: Option<String>,
+
+    /// Keystore whose address to check, e.g. "my.json" (no password needed)
+    #[arg(short, long)]
+    from_file: Option<String>,
+
+    /// Token symbol or address, from `tokens list` or the bundled well-known tokens; repeatable.
+    /// The native balance is shown without one.
+    #[arg(long = "token")]
+    tokens: Vec<String>,
+
+    /// Defaults to the configured network
+    #[arg(long)]
+    chain: Option<String>,
+}
+
+#[derive(Args)]
+struct XchainCheckArgs {
+    /// Keystore whose address to check, e.g. "my.json" (no password needed)
+    #[arg(short, long, required_unless_present = "address", conflicts_with = "address")]
+    from_file: Option<String>,
+
+    /// Address or ENS name to check instead of a keystore
+    #[arg(short, long)]
+    address: Option<String>,
+
+    /// Comma separated chain names, defaults to every configured chain
+    #[arg(long, value_delimiter = ',')]
+    chains: Vec<String>,
+}
+
+#[derive(Args)]
+struct HistoryArgs {
+    /// Address or ENS name
+    address: String,
+
+    /// Chain to list, defaults to the configured network; see `[explorer.networks]` for its API
+    #[arg(long)]
+    chain: Option<String>,
+
+    /// Most recent transactions to show
+    #[arg(long, default_value_t = web3wallet_cli::config::explorer::DEFAULT_HISTORY_LIMIT)]
+    limit: usize,
+}
+
+#[derive(Args)]
+struct ActivityArgs {
+    /// Address or ENS name
+    address: String,
+
+    /// Only count activity on or after this date, e.g. "2024-01-01"
+    #[arg(long)]
+    since: Option<chrono::NaiveDate>,
+
+    #[arg(long, default_value = "mainnet")]
+    chain: String,
+
+    /// Print the summary as CSV
+    #[arg(long)]
+    csv: bool,
+}
+
+#[derive(Args)]
+struct MonitorArgs {
+    /// Minimum native balance per chain, e.g. "mainnet=0.05"; repeatable
+    #[arg(long = "min-balance", value_name = "CHAIN=AMOUNT")]
+    min_balances: Vec<String>,
+
+    /// Seconds between checks
+    #[arg(long, default_value = "60")]
+    interval: u64,
+
+    /// Run a single check and exit, non-zero if any wallet is below its threshold
+    #[arg(long)]
+    once: bool,
+}
+
+fn validate_word_count(s: &str) -> Result<u8, String> {
+    match s.parse::<u8>() {
+        Ok(n) if web3wallet_cli::config::is_supported_word_count(n) => Ok(n),
+        Ok(n) => Err(format!("Word count must be 12, 15, 18, 21 or 24, got {}", n)),
+        Err(_) => Err(format!("Invalid number: {}", s)),
+    }
+}
+
+fn mnemonic_language(s: &str) -> Result<bip39::Language, String> {
+    mnemonic::parse_language(s).map_err(|_| {
+        let names: Vec<&str> = mnemonic::LANGUAGES.iter().map(|(name, _, _)| *name).collect();
+        format!("Unknown wordlist {:?}, expected one of {}", s, names.join(", "))
+    })
+}
+
+fn bip32_index(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if n <= web3wallet_cli::config::MAX_BIP32_INDEX => Ok(n),
+        Ok(n) => Err(format!("Index must be at most {}, got {}", web3wallet_cli::config::MAX_BIP32_INDEX, n)),
+        Err(_) => Err(format!("Invalid number: {}", s)),
+    }
+}
+
+/// `--account`/`--change` as a pair, `None` when neither is given so the wallet's own path stays
+fn account_levels(account: Option<u32>, change: Option<u32>) -> Option<(u32, u32)> {
+    (account.is_some() || change.is_some()).then(|| (account.unwrap_or(0), change.unwrap_or(0)))
+}
+
+/// The password source picked by `--password-file`/`--password-stdin`, if any
+fn password_source(file: Option<&Path>, stdin: bool) -> WalletResult<Option<PasswordSource>> {
+    use std::io::IsTerminal;
+
+    match file {
+        Some(path) => Ok(Some(PasswordSource::from_file(path)?)),
+        // Typed at a terminal the password would echo; the prompt hides it
+        None if stdin && std::io::stdin().is_terminal() => Err(UserInputError::InvalidParameters {
+            parameter: "password-stdin".to_string(),
+            value: "terminal".to_string(),
+            expected: "a pipe or redirected file on stdin; at a terminal leave it out and type the password at the prompt".to_string(),
+        }
+        .into()),
+        None if stdin => Ok(Some(PasswordSource::stdin())),
+        None => Ok(None),
+    }
+}
+
+fn init_logging(verbose: bool, quiet: bool){
+    use tracing_subscriber::fmt::writer::BoxMakeWriter;
+
+    let level = if verbose{
+        tracing::Level::DEBUG
+    }else{
+        tracing::Level::INFO
+    };
+    // Logs go to stderr so stdout carries only the output: JSON, templates, CSV, `--out`
+    let writer = if quiet {
+        BoxMakeWriter::new(std::io::sink)
+    } else {
+        BoxMakeWriter::new(std::io::stderr)
+    };
+
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_target(false)
         .without_time()
+        .with_writer(writer)
         .init();
 }
 
-async fn load_config(config_path: Option<std::path::PathBuf>) -> WalletResult<WalletConfig> {
-    match config_path{
-        Some(path)=>{
-            info!("Loading config from {:?}", path.display());
-            Ok(WalletConfig::default())
+/// Open the signing backend chosen with `--signer`
+async fn load_signer(
+    kind: SignerKind,
+    from_file: Option<&str>,
+    hd_path: Option<&str>,
+    config: &WalletConfig,
+) -> WalletResult<Box<dyn Signer>> {
+    match kind {
+        SignerKind::Ledger => {
+            info!("Connecting to Ledger, unlock it and open the Ethereum app");
+            let chain = ChainInfo::resolve(&config.network, config)?;
+            Ok(Box::new(LedgerSigner::connect(hd_path, chain.chain_id).await?))
+        }
+        SignerKind::Software => {
+            let filename = from_file.ok_or_else(|| {
+                WalletError::UserInput(UserInputError::MissingParameter {
+                    parameter: "from-file".to_string(),
+                    hint: "pass the keystore to sign with, or use --signer ledger".to_string(),
+                })
+            })?;
+            let file_path = config.wallet_path(filename);
+
+            let manager = WalletManager::new(config.clone());
+            manager.check_can_sign(&file_path).await?;
+            if let Some(signer) = agent_signer(&manager, &file_path, None, config).await {
+                return Ok(Box::new(signer));
+            }
+            let wallet = unlock_wallet(&manager, &file_path, config).await?;
+            record_usage(config, wallet.address(), &[UsageKind::Unlock]).await;
+            Ok(Box::new(SoftwareSigner::new(wallet)))
+        }
+    }
+}
+
+/// Refuse to sign for a chain other than the one the keystore was created for, unless `force`
+async fn check_signing_chain(filename: &str, chain_id: u64, force: bool, config: &WalletConfig) -> WalletResult<()> {
+    let file_path = config.wallet_path(filename);
+
+    let manager = WalletManager::new(config.clone());
+    manager.check_can_sign(&file_path).await?;
+    match manager.check_signing_chain(&file_path, chain_id).await {
+        Err(WalletError::Validation(ValidationError::ChainMismatch { expected, requested })) if force => {
+            warn!("Wallet is bound to chain {}, signing for chain {} because of --force", expected, requested);
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+/// Count wallet operations in the local usage log; a failure to update it is logged, never fatal
+/// Agent to take wallet passwords from, unless disabled or the wallet is cold storage, which is
+/// never unlocked through a long-running process
+async fn wallet_agent(manager: &WalletManager, file_path: &Path, config: &WalletConfig) -> Option<AgentClient> {
+    if !config.use_agent || manager.is_cold_wallet(file_path).await {
+        return None;
+    }
+    AgentClient::detect(&AgentPaths::in_state_dir(&config.state_path)).await
+}
+
+/// Signer over the keys a running agent holds for `file_path`, which skips decrypting it here
+async fn agent_signer(manager: &WalletManager, file_path: &Path, base_path: Option<String>, config: &WalletConfig) -> Option<AgentSigner> {
+    let agent = wallet_agent(manager, file_path, config).await?;
+    let signer = agent.signer(file_path, base_path).await?;
+    info!("Using the keys held by the agent (pid {})", agent.pid());
+    Some(signer)
+}
+
+/// Run `open` with the password a running agent holds for `file_path`, or else a prompted one,
+/// which the agent keeps once it has opened the wallet, along with the BIP39 passphrase
+/// `passphrase_of` finds in what `open` returned
+async fn with_wallet_password<T, F, Fut>(
+    manager: &WalletManager,
+    file_path: &Path,
+    config: &WalletConfig,
+    prompt: &str,
+    open: F,
+    passphrase_of: fn(&T) -> Option<&str>,
+) -> WalletResult<T>
+where
+    F: Fn(String) -> Fut,
+    Fut: std::future::Future<Output = WalletResult<T>>,
+{
+    if config.strict_permissions {
+        // Refuse before the password is typed; otherwise the load warns
+        manager.check_permissions(file_path).await?;
+    }
+    let agent = wallet_agent(manager, file_path, config).await;
+    if let Some(agent) = &agent {
+        if let Some(cached) = agent.password(file_path).await {
+            match open(cached.to_string()).await {
+                Err(WalletError::Cryptographic(CryptographicError::DecryptionFailed { .. }))
+                | Err(WalletError::Authentication(AuthenticationError::WrongPassword { .. })) => {
+                    warn!("The agent's password for {} no longer opens it", file_path.display());
+                    let _ = agent.forget(Some(file_path)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    let password = get_password(prompt)?;
+    let result = open(password.clone()).await?;
+    if let Some(agent) = &agent {
+        if let Err(err) = agent.remember(file_path, &password, passphrase_of(&result)).await {
+            warn!("Could not hand the password to the agent: {}", err);
+        }
+    }
+    Ok(result)
+}
+
+async fn unlock_wallet(manager: &WalletManager, file_path: &Path, config: &WalletConfig) -> WalletResult<Wallet> {
+    with_wallet_password(
+        manager,
+        file_path,
+        config,
+        &tr("prompt-wallet-password"),
+        |password| async move { manager.load_wallet(file_path, &password).await },
+        wallet_passphrase,
+    )
+    .await
+}
+
+/// Passphrase of an unlocked wallet, for the agent to open it again without a prompt
+fn wallet_passphrase(wallet: &Wallet) -> Option<&str> {
+    Some(wallet.passphrase()).filter(|passphrase| !passphrase.is_empty())
+}
+
+/// Drop what a running agent caches for `file_path` before its password changes or the file moves
+async fn forget_agent_password(config: &WalletConfig, file_path: &Path) {
+    if let Some(agent) = AgentClient::detect(&AgentPaths::in_state_dir(&config.state_path)).await {
+        let _ = agent.forget(Some(file_path)).await;
+    }
+}
+
+async fn record_usage(config: &WalletConfig, address: &str, kinds: &[UsageKind]) {
+    if !config.track_usage {
+        return;
+    }
+    let result = async {
+        let mut log = UsageLog::load(&UsageLog::default_path(&config.state_path)).await?;
+        for kind in kinds {
+            log.record(&display_address(address), *kind);
+        }
+        log.save().await
+    }
+    .await;
+    if let Err(e) = result {
+        warn!("Could not update the usage log: {}", e);
+    }
+}
+
+/// Enforce `[approval]`: from the threshold on, `token` must come from a configured approver.
+/// Without one, the sealed request is pushed to the approval channel, or printed when there is
+/// none or the machine is offline, and signing is refused.
+async fn require_approval(
+    config: &WalletConfig,
+    unsigned: &UnsignedTransaction,
+    from: &str,
+    chain: Option<&ChainInfo>,
+    note: Option<&str>,
+    token: Option<&str>,
+) -> WalletResult<()> {
+    if !ApprovalService::required(&config.approval, unsigned)? {
+        return Ok(());
+    }
+    let chain_name = chain.map_or_else(|| format!("chain {}", unsigned.chain_id), |c| c.name.clone());
+    let request = ApprovalRequest::new(from, unsigned, &chain_name, note);
+
+    if let Some(token) = token {
+        let approver = ApprovalService::verify(&config.approval, &request, token)?;
+        info!("Transaction approved by {}", display_address(&approver));
+        return Ok(());
+    }
+
+    let public_key = config.approval.public_key.as_deref().unwrap_or_default();
+    let sealed = request.seal(public_key)?;
+    match &config.approval.url {
+        Some(url) if !config.offline => {
+            ApprovalService::notify(config, &sealed).await?;
+            info!("Approval request sent to {}", url);
+        }
+        _ => {
+            println!("Approval request, deliver it to an approver:");
+            println!("{}", sealed);
+        }
+    }
+
+    let currency = chain.map_or(web3wallet_cli::config::chains::DEFAULT_CURRENCY_SYMBOL, |c| c.currency_symbol.as_str());
+    Err(AuthenticationError::ApprovalRequired {
+        threshold: format!("{} {}", config.approval.threshold.as_deref().unwrap_or_default(), currency),
+    }
+    .into())
+}
+
+/// Append a signed transaction to the local history, see `wallet history export`
+async fn record_history(config: &WalletConfig, mut entry: HistoryEntry) {
+    if !config.record_history {
+        return;
+    }
+    entry.from = display_address(&entry.from);
+    if !entry.to.is_empty() {
+        entry.to = display_address(&entry.to);
+    }
+    let result = async {
+        let mut log = HistoryLog::load(&HistoryLog::default_path(&config.state_path)).await?;
+        log.record(entry);
+        log.save().await
+    }
+    .await;
+    if let Err(e) = result {
+        warn!("Could not update the transaction history: {}", e);
+    }
+}
+
+/// The next nonce tracked for `address` on `chain_id`, for `sign-tx --nonce auto`
+async fn tracked_nonce(config: &WalletConfig, address: &str, chain_id: u64) -> WalletResult<u64> {
+    let store = NonceStore::load(&NonceStore::default_path(&config.state_path)).await?;
+    match store.get(address, chain_id) {
+        Some(tracked) => Ok(tracked.next),
+        None => Err(UserInputError::MissingParameter {
+            parameter: "nonce".to_string(),
+            hint: format!(
+                "no nonce is tracked for {} on chain {}; run `web3wallet nonce sync` while online, \
+                 or pass --nonce once and auto continues from it",
+                display_address(address),
+                chain_id
+            ),
+        }
+        .into()),
+    }
+}
+
+/// Move the tracked nonce of `address` past the one `unsigned` was signed with
+async fn record_nonce(config: &WalletConfig, address: &str, unsigned: &UnsignedTransaction) {
+    let result = async {
+        let nonce = parse_quantity("nonce", &unsigned.nonce)?;
+        if nonce > ethers::types::U256::from(u64::MAX) {
+            return Ok(());
+        }
+        let mut store = NonceStore::load(&NonceStore::default_path(&config.state_path)).await?;
+        store.record_signed(address, unsigned.chain_id, nonce.as_u64());
+        store.save().await
+    }
+    .await;
+    if let Err(e) = result {
+        warn!("Could not update the tracked nonces: {}", e);
+    }
+}
+
+/// `config` with the `<wallet>.toml` sidecar of `filename` applied, if there is one
+async fn wallet_config(config: &WalletConfig, filename: &str) -> WalletResult<WalletConfig> {
+    let file_path = config.wallet_path(filename);
+
+    let mut config = config.clone();
+    if let Some(overrides) = WalletOverrides::load(&file_path).await? {
+        info!("Applying wallet overrides from {}", WalletOverrides::sidecar_path(&file_path).display());
+        overrides.apply(&mut config);
+        // The sidecar may name a network only the config file defines, so check it here
+        ChainInfo::resolve(&config.network, &config)?;
+    }
+    Ok(config)
+}
+
+async fn load_config(config_path: Option<std::path::PathBuf>) -> WalletResult<WalletConfig> {
+    match config_path{
+        Some(path)=>{
+            info!("Loading config from {:?}", path.display());
+            WalletConfig::load(&path).await
+        }
+        None=>{
+            let path = WalletConfig::default_config_path();
+            if path.exists() {
+                info!("Loading config from {:?}", path.display());
+                WalletConfig::load(&path).await
+            } else {
+                info!("Using default config");
+                Ok(WalletConfig::default())
+            }
+        }
+    }
+}
+
+// #[tokio::main]
+// async fn main() -> WalletResult<()> {
+//     let cli: Cli = Cli::parse();
+
+//     init_logging(cli.verbose);
+
+//     let config = load_config(cli.config).await?;
+    
+//     if cli.verbose {
+//         info!("Starting Web3 Wallet CLI v{}", env!("CARGO_PKG_VERSION"));
+//     }
+//     let manager = WalletManager::new(config);
+// }
+
+async fn execute_create(args: CreateArgs,
+                        config: &WalletConfig,
+                        output: OutputFormat) -> WalletResult<()> {
+        let mut manager = WalletManager::new(config.clone());
+        if let Some(kdf) = args.kdf {
+            manager = manager.with_kdf(kdf);
+        }
+
+        let user_entropy = match &args.entropy_hex {
+            Some(hex) => Some(mnemonic::parse_entropy_hex(hex, args.words)?),
+            None if args.dice => Some(read_dice_rolls(args.words)?),
+            None => None,
+        };
+        if user_entropy.is_some() {
+            info!("Mixing user-supplied entropy into the system randomness");
+        }
+
+        info!("Creating a new wallet with {} words on {} network", args.words, args.network);
+        let account = account_levels(args.account, args.change);
+        let passphrase = args.passphrase.then(|| read_passphrase(&tr("prompt-bip39-passphrase"))).transpose()?;
+        let wallet = commands::new_wallet(&manager, args.words, args.language, user_entropy.as_deref().map(Vec::as_slice), &args.network, passphrase.as_deref().map(String::as_str), account).await?;
+
+        // Saving is the point of no return for a mnemonic nobody wrote down, so check the backup
+        // first; scripts without a terminal and --skip-verify go straight on
+        let mut backup_verified = args.backup_verified;
+        if args.save.is_some() && !args.skip_verify {
+            use std::io::IsTerminal;
+            if std::io::stdin().is_terminal() {
+                print_terminal(&format!("Write down your mnemonic:\n\n{}\n\n", wallet.mnemonic()))?;
+                prompt_line("Press Enter once it is written down...")?;
+                // Clear the screen so the quiz is answered from the backup
+                print_terminal("\x1b[2J\x1b[H")?;
+                backup_quiz(&MnemonicService::validate(wallet.mnemonic())?)?;
+                print_terminal("Backup verified.\n")?;
+                backup_verified = true;
+            } else {
+                info!("Skipping the backup quiz, stdin is not a terminal; check it later with `verify-backup`");
+            }
+        }
+
+        let save = match &args.save {
+            Some(filename) => Some(manager.save_options(filename, args.cold, backup_verified, get_password)?),
+            None => None,
+        };
+        let outcome = commands::save_created(&manager, &wallet, args.language, save).await?;
+
+        match output{
+            OutputFormat::Table=>{
+                println!("New wallet created:");
+                println!("Address: {}", outcome.address);
+                println!("Mnemonic: {}", outcome.mnemonic);
+                if args.language != bip39::Language::English {
+                    println!("Language: {}", outcome.language);
+                }
+                println!("Network: {}", outcome.network);
+                if outcome.derivation_path != web3wallet_cli::config::DEFAULT_DERIVATION_PATH {
+                    println!("Derivation path: {}/0", outcome.derivation_path);
+                }
+                if let Some(path) = &outcome.saved_to {
+                    println!("\n Wallet saved to: {}", path.display());
+                }
+            }
+            OutputFormat::Json=>{
+                print_json(&outcome_json(&outcome)?)?;
+            }
+        }
+
+        Ok(())
+    }
+
+async fn excute_import(args: ImportArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()>{
+    ChainInfo::resolve(&args.network, config)?;
+    let mut manager = WalletManager::new(config.clone()).with_network(&args.network);
+    if let Some(kdf) = args.kdf {
+        manager = manager.with_kdf(kdf);
+    }
+
+    let source = if let Some(phrase) = args.mnemonic{
+        info!("Importing wallet from mnemonic");
+        let passphrase = args.passphrase.then(|| read_passphrase(&tr("prompt-bip39-passphrase"))).transpose()?;
+        ImportSource::Mnemonic { phrase, passphrase: passphrase.map(|p| p.to_string()) }
+    } else if let Some(private) = args.private_key{
+        info!("Importing wallet from private key...");
+        ImportSource::PrivateKey(private)
+    } else if let Some(xprv) = args.xprv{
+        info!("Importing HD wallet from BIP32 root key");
+        ImportSource::Xprv(xprv)
+    } else if let Some(seed) = args.seed_hex{
+        info!("Importing HD wallet from BIP39 seed");
+        ImportSource::SeedHex(seed)
+    } else if args.shamir{
+        info!("Importing HD wallet from SLIP-39 shares");
+        let shares = read_shamir_shares()?;
+        let passphrase = args.passphrase.then(|| read_passphrase(&tr("prompt-slip39-passphrase"))).transpose()?;
+        ImportSource::Shamir { shares, passphrase: passphrase.map(|p| p.to_string()) }
+    } else if let Some(path) = args.keystore{
+        info!("Importing wallet from keystore {}", path.display());
+        let password = get_password(&tr("prompt-keystore-password"))?;
+        ImportSource::Keystore { path, password }
+    } else if let Some(xpub) = args.xpub{
+        info!("Importing watch-only wallet from xpub");
+        ImportSource::Xpub(xpub)
+    } else if let Some(address) = args.address{
+        info!("Importing watch-only wallet for {}", address);
+        ImportSource::Address(address)
+    } else{
+        // Check if we're in a testing environment that doesn't support interactive input
+        // or if both parameters are missing, return error immediately
+        return Err(WalletError::UserInput(UserInputError::MissingParameter {
+            parameter: "import source".to_string(),
+            hint: "one of --mnemonic, --private-key, --xprv, --seed-hex, --shamir, --keystore, --xpub or --address required".to_string(),
+        }));
+    };
+
+    let save = match &args.save {
+        Some(filename) => Some(manager.save_options(filename, args.cold, args.backup_verified, get_password)?),
+        None => None,
+    };
+    let outcome = commands::import(&manager, source, args.path_preset, save).await?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Wallet imported successfully!");
+            println!("Address:  {}", outcome.address);
+            println!("Network:  {}", outcome.network);
+            if outcome.wallet_type == WalletType::WatchOnly {
+                println!("Type:     {}", watch_only_label(outcome.is_hd));
+            } else if outcome.has_mnemonic {
+                println!("Type:     HD Wallet (BIP44)");
+            } else if outcome.is_hd {
+                println!("Type:     HD Wallet (imported seed or root key)");
+            } else {
+                println!("Type:     Private Key Only");
+            }
+            if let Some(preset) = args.path_preset {
+                println!("Paths:    {} ({})", preset.as_str(), preset.template());
+            }
+            if let Some(path) = &outcome.saved_to {
+                println!("\n Wallet saved to: {}", path.display());
+            }
+        }
+        OutputFormat::Json => {
+            print_json(&outcome_json(&outcome)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+
+fn watch_only_label(is_hd: bool) -> &'static str {
+    if is_hd {
+        "Watch-only (xpub, no private keys)"
+    } else {
+        "Watch-only (single address)"
+    }
+}
+
+async fn excute_load(
+    args: LoadArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()>{
+    let manager = WalletManager::new(config.clone());
+
+    let file_path = config.wallet_path(&args.filename);
+
+    info!("Loading wallet from: {}", file_path.display());
+
+    if args.address_only{
+        let summary = commands::inspect(&file_path).await?;
+        match output {
+            OutputFormat::Table => {
+                println!("\n📁 Wallet file: {}", summary.file.display());
+                println!("Address:  {}", summary.address);
+                if let Some(network) = &summary.network {
+                    println!("Network:  {}", network);
+                }
+                if let Some(created_at) = &summary.created_at {
+                    println!("Created:  {}", created_at);
+                }
+                if let Some(alias) = &summary.alias {
+                    println!("Alias:    {}", alias);
+                }
+                if summary.format == "v3" {
+                    println!("Format:   keystore v3");
+                }
+                if summary.wallet_type == WalletType::WatchOnly {
+                    println!("Type:     Watch-only");
+                }
+            }
+            OutputFormat::Json => {
+                print_json(&outcome_json(&summary)?)?;
+            }
+        }
+        return Ok(());
+    }
+
+    let derive = args.derive;
+    let wallet = with_wallet_password(
+        &manager,
+        &file_path,
+        config,
+        &tr("prompt-new-wallet-password"),
+        |password| {
+            let (manager, file_path) = (&manager, &file_path);
+            async move { manager.load_wallet(file_path, &password).await }
+        },
+        wallet_passphrase,
+    )
+    .await?;
+    let outcome = commands::load_outcome(&wallet, derive)?;
+    record_usage(config, &outcome.address, &[UsageKind::Unlock]).await;
+
+    // Display wallet information
+    match output {
+        OutputFormat::Table => {
+            println!("\n Wallet loaded successfully!");
+            println!("Address:  {}", outcome.address);
+            println!("Network:  {}", outcome.network);
+            if outcome.wallet_type == WalletType::WatchOnly {
+                println!("Type:     {}", watch_only_label(outcome.is_hd));
+            } else if outcome.has_mnemonic {
+                println!("Type:     HD Wallet (BIP44)");
+            } else if outcome.is_hd {
+                println!("Type:     HD Wallet (imported seed or root key)");
+            } else {
+                println!("Type:     Private Key Only");
+            }
+            if let Some(alias) = &outcome.alias {
+                println!("Alias:    {}", alias);
+            }
+            println!("Created:  {}", outcome.created_at.format("%Y-%m-%d %H:%M:%S UTC"));
+
+            if let Some(derived) = &outcome.derived {
+                println!("\n Derived address [{}]:", derived.index);
+                println!("Address:  {}", derived.address);
+                println!("Path:     {}", derived.derivation_path);
+            }
+        }
+        OutputFormat::Json => {
+            print_json(&outcome_json(&outcome)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Prompt twice for a passphrase, which unlike a password cannot be checked against anything
+/// later: a typo would silently make another wallet
+fn read_passphrase(prompt: &str) -> WalletResult<zeroize::Zeroizing<String>> {
+    let passphrase = zeroize::Zeroizing::new(get_secret(prompt)?);
+    let confirm = zeroize::Zeroizing::new(get_secret(&tr("prompt-confirm-passphrase"))?);
+    if passphrase != confirm {
+        return Err(UserInputError::PasswordMismatch.into());
+    }
+    Ok(passphrase)
+}
+
+/// Prompt for shares until the threshold encoded in the first one is reached
+fn read_shamir_shares() -> WalletResult<Vec<String>> {
+    let first = prompt_line(&tr("prompt-shamir-first-share"))?;
+    let threshold = ShamirService::threshold_of(&first)?;
+
+    let mut shares = vec![first];
+    while shares.len() < usize::from(threshold) {
+        let index = (shares.len() + 1).to_string();
+        let prompt = tr_args("prompt-shamir-share", &[("index", &index), ("threshold", &threshold.to_string())]);
+        shares.push(prompt_line(&prompt)?);
+    }
+    Ok(shares)
+}
+
+async fn execute_backup(
+    command: BackupCommand,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    match command {
+        BackupCommand::Shamir { filename, threshold, shares, passphrase } => {
+            execute_shamir_backup(filename, threshold, shares, passphrase, config, output).await
+        }
+        BackupCommand::Create { out } => execute_archive_create(&out, config, output).await,
+        BackupCommand::Restore { archive, force } => execute_archive_restore(&archive, force, config, output).await,
+    }
+}
+
+async fn execute_archive_create(out: &Path, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+
+    let password = get_password(&tr("prompt-archive-password"))?;
+    let confirm = get_password(&tr("prompt-confirm-password"))?;
+    if password != confirm {
+        return Err(WalletError::UserInput(UserInputError::PasswordMismatch));
+    }
+    let outcome = commands::create_archive(&manager, out, &password).await?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Archived {} files of {} ({}):", outcome.files.len(), outcome.directory.display(), outcome.kdf);
+            for file in &outcome.files {
+                println!("  {}", file);
+            }
+            println!("Archive: {}", outcome.archive.display());
+        }
+        OutputFormat::Json => {
+            print_json(&outcome_json(&outcome)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_archive_restore(archive: &Path, force: bool, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+
+    let password = get_password(&tr("prompt-archive-unlock"))?;
+    let outcome = commands::restore_archive(&manager, archive, &password, force).await?;
+    // Restored keystores may be under older passwords than the agent holds
+    for file in &outcome.restored {
+        forget_agent_password(config, &outcome.directory.join(file)).await;
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Restored {} archived on {} into {}:", outcome.archive.display(), outcome.created_at, outcome.directory.display());
+            for file in &outcome.restored {
+                println!("  {}", file);
+            }
+            if !outcome.unchanged.is_empty() {
+                println!("Unchanged: {}", outcome.unchanged.join(", "));
+            }
+        }
+        OutputFormat::Json => {
+            print_json(&outcome_json(&outcome)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_shamir_backup(
+    filename: String,
+    threshold: u8,
+    count: u8,
+    passphrase: Option<String>,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+
+    let file_path = config.wallet_path(&filename);
+
+    let wallet = unlock_wallet(&manager, &file_path, config).await?;
+    let seed = wallet.bip32_seed().ok_or_else(|| {
+        WalletError::UserInput(UserInputError::InvalidParameters {
+            parameter: "filename".to_string(),
+            value: filename.clone(),
+            expected: "an HD wallet created or imported from a mnemonic or seed".to_string(),
+        })
+    })?;
+
+    let shares = ShamirService::split(&seed, threshold, count, passphrase.as_deref().unwrap_or(""))?;
+    record_usage(config, wallet.address(), &[UsageKind::Unlock, UsageKind::Export]).await;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n SLIP-39 backup of {}: any {} of these {} shares restore the wallet", display_address(wallet.address()), threshold, count);
+            for (i, share) in shares.iter().enumerate() {
+                println!("\nShare {}:\n{}", i + 1, share);
+            }
+            if wallet.derivation_path() != web3wallet_cli::config::DEFAULT_DERIVATION_PATH {
+                println!("\nThe shares hold the seed only; account path {} is not part of the backup.", wallet.derivation_path());
+            }
+        }
+        OutputFormat::Json => {
+            print_json(&serde_json::json!({
+                "success": true,
+                "address": display_address(wallet.address()),
+                "threshold": threshold,
+                "shares": shares,
+            }))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_xpub(
+    args: XpubArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let file_path = config.wallet_path(&args.filename);
+
+    let wallet = unlock_wallet(&manager, &file_path, config).await?;
+    record_usage(config, wallet.address(), &[UsageKind::Unlock]).await;
+    if !wallet.is_hd() {
+        return Err(UserInputError::InvalidParameters {
+            parameter: "wallet".to_string(),
+            value: "private key only".to_string(),
+            expected: "HD wallet with mnemonic or root key".to_string(),
+        }
+        .into());
+    }
+    let outcome = commands::xpub(&wallet, args.public_keys)?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Extended public key:");
+            println!("Account path: {}", outcome.account_path);
+            println!("Xpub:         {}", outcome.xpub);
+            if !outcome.public_keys.is_empty() {
+                println!();
+                let rows: Vec<Vec<String>> = outcome
+                    .public_keys
+                    .iter()
+                    .map(|k| vec![k.index.to_string(), k.address.clone(), k.public_key.clone(), k.derivation_path.clone()])
+                    .collect();
+                for line in table_lines(&["INDEX", "ADDRESS", "PUBLIC KEY", "DERIVATION PATH"], &rows) {
+                    println!("{}", line);
+                }
+            }
+        }
+        OutputFormat::Json => {
+            print_json(&serde_json::to_value(&outcome)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_export(
+    args: ExportArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+
+    let file_path = config.wallet_path(&args.filename);
+
+    // Refuse before any password prompt; export_v3 checks again for library callers
+    if args.out.exists() {
+        return Err(WalletError::Filesystem(FilesystemError::FileExists {
+            path: args.out.display().to_string(),
+            suggestion: "choose another --out path or remove the existing file".to_string(),
+        }));
+    }
+
+    let wallet = unlock_wallet(&manager, &file_path, config).await?;
+
+    let export_password = get_password(&tr("prompt-export-password"))?;
+    let confirm = get_password(&tr("prompt-confirm-password"))?;
+    if export_password != confirm {
+        return Err(WalletError::UserInput(UserInputError::PasswordMismatch));
+    }
+
+    let outcome = match args.format {
+        ExportFormat::V3 => {
+            info!("Encrypting keystore v3 (scrypt, {} parameters)", if args.light { "light" } else { "standard" });
+            commands::export_v3(&wallet, &args.out, &export_password, args.light).await?
+        }
+    };
+    record_usage(config, &outcome.address, &[UsageKind::Unlock, UsageKind::Export]).await;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Wallet exported:");
+            println!("Address:  {}", outcome.address);
+            println!("Format:   keystore v3");
+            println!("File:     {}", outcome.file.display());
+        }
+        OutputFormat::Json => {
+            print_json(&outcome_json(&outcome)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_account(
+    command: AccountCommand,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let filename = match &command {
+        AccountCommand::Add { filename, .. } | AccountCommand::List { filename } | AccountCommand::Select { filename, .. } => filename,
+    };
+    let file_path = config.wallet_path(filename);
+
+    let (manager, path, command) = (&manager, file_path.as_path(), &command);
+    let outcome = with_wallet_password(manager, path, config, &tr("prompt-wallet-password"), |password| async move {
+        match command {
+            AccountCommand::Add { name, index, path: base_path, private_key, select, .. } => {
+                let account = match private_key {
+                    Some(private_key) => commands::NewAccount::Imported { private_key: private_key.clone() },
+                    None => commands::NewAccount::Derived {
+                        base_path: base_path.clone().or_else(|| index.map(|index| web3wallet_cli::config::bip44_base_path(index, 0))),
+                    },
+                };
+                commands::add_account(manager, path, &password, name, account, *select).await
+            }
+            AccountCommand::List { .. } => commands::list_accounts(manager, path, &password).await,
+            AccountCommand::Select { name, .. } => commands::select_account(manager, path, &password, name).await,
+        }
+    }, |_| None)
+    .await?;
+    if !matches!(command, AccountCommand::List { .. }) {
+        // The agent holds the wallet as it was selected
+        forget_agent_password(config, path).await;
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Accounts of {}:", outcome.file.display());
+            let rows: Vec<Vec<String>> = outcome
+                .accounts
+                .iter()
+                .map(|a| {
+                    let marker = if a.selected { "*" } else { "" };
+                    vec![marker.to_string(), a.name.clone(), a.address.clone(), a.derivation_path.clone().unwrap_or_else(|| "imported key".to_string())]
+                })
+                .collect();
+            for line in table_lines(&["", "NAME", "ADDRESS", "PATH"], &rows) {
+                println!("{}", line);
+            }
+        }
+        OutputFormat::Json => {
+            print_json(&outcome_json(&outcome)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn execute_genpass(args: GenpassArgs, output: OutputFormat) -> WalletResult<()> {
+    let length = match (args.length, args.words) {
+        (None, Some(_)) => None,
+        (length, _) => Some(length.unwrap_or(web3wallet_cli::config::genpass::DEFAULT_LENGTH)),
+    };
+    let secret = |generated: password_generator::Generated, length| GeneratedSecret {
+        value: generated.value.to_string(),
+        length,
+        bits: generated.bits as u32,
+    };
+    let outcome = GenpassOutcome {
+        password: length.map(|n| password_generator::password(n).map(|g| secret(g, n))).transpose()?,
+        passphrase: args.words.map(|n| password_generator::passphrase(n).map(|g| secret(g, n))).transpose()?,
+    };
+
+    match output {
+        OutputFormat::Table => {
+            if let Some(password) = &outcome.password {
+                println!("\n Password ({} characters, {} bits):", password.length, password.bits);
+                println!("{}", password.value);
+            }
+            if let Some(passphrase) = &outcome.passphrase {
+                println!("\n Passphrase ({} words, {} bits):", passphrase.length, passphrase.bits);
+                println!("{}", passphrase.value);
+            }
+        }
+        OutputFormat::Json => {
+            print_json(&outcome_json(&outcome)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_passwd(
+    args: PasswdArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+
+    let file_path = config.wallet_path(&args.filename);
+
+    let old_password = get_password(&tr("prompt-current-password"))?;
+    let new_password = get_password(&tr("prompt-new-password"))?;
+    let confirm = get_password(&tr("prompt-confirm-new-password"))?;
+    if new_password != confirm {
+        return Err(WalletError::UserInput(UserInputError::PasswordMismatch));
+    }
+
+    let outcome = commands::change_password(&manager, &file_path, &old_password, &new_password).await?;
+    forget_agent_password(config, &file_path).await;
+    record_usage(config, &outcome.address, &[UsageKind::Unlock]).await;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Password changed:");
+            println!("Address:  {}", outcome.address);
+            println!("Format:   {}", outcome.format);
+            println!("KDF:      {}", outcome.kdf);
+            println!("File:     {}", outcome.file.display());
+        }
+        OutputFormat::Json => {
+            print_json(&outcome_json(&outcome)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_verify_backup(
+    args: VerifyBackupArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let file_path = config.wallet_path(&args.filename);
+
+    let wallet = unlock_wallet(&manager, &file_path, config).await?;
+    record_usage(config, wallet.address(), &[UsageKind::Unlock]).await;
+    if !wallet.has_mnemonic() {
+        return Err(UserInputError::InvalidParameters {
+            parameter: "wallet".to_string(),
+            value: "no mnemonic".to_string(),
+            expected: "a wallet created or imported from a mnemonic".to_string(),
+        }
+        .into());
+    }
+
+    backup_quiz(&MnemonicService::validate(wallet.mnemonic())?)?;
+    let recorded = commands::mark_backup_verified(&file_path).await?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Backup verified for {}", display_address(wallet.address()));
+        }
+        OutputFormat::Json => {
+            print_json(&serde_json::json!({
+                "success": true,
+                "address": display_address(wallet.address()),
+                "file": file_path.display().to_string(),
+                "recorded": recorded,
+            }))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_rename(
+    args: RenameArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let file_path = config.wallet_path(&args.filename);
+
+    let new_path = match &args.to {
+        Some(name) if name.contains('/') || name.contains('\\') => {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "to".to_string(),
+                value: name.clone(),
+                expected: "a file name, the wallet stays in its directory".to_string(),
+            }
+            .into());
+        }
+        Some(name) => {
+            let name = if name.ends_with(".json") { name.clone() } else { format!("{}.json", name) };
+            Some(file_path.with_file_name(name))
+        }
+        None => None,
+    };
+    let alias = match (args.alias, args.clear_alias) {
+        (Some(alias), _) => Some(Some(alias)),
+        (None, true) => Some(None),
+        (None, false) => None,
+    };
+    let password = if args.require_password {
+        Some(get_password(&tr("prompt-wallet-password"))?)
+    } else {
+        None
+    };
+
+    if new_path.is_some() {
+        forget_agent_password(config, &file_path).await;
+    }
+    let outcome = commands::rename(&manager, &file_path, alias, new_path.as_deref(), password.as_deref()).await?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Wallet updated:");
+            println!("Address:  {}", outcome.address);
+            println!("Alias:    {}", outcome.alias.as_deref().unwrap_or("-"));
+            if let Some(previous) = &outcome.previous_file {
+                println!("Moved:    {} -> {}", previous.display(), outcome.file.display());
+            } else {
+                println!("File:     {}", outcome.file.display());
+            }
+        }
+        OutputFormat::Json => {
+            print_json(&outcome_json(&outcome)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_restore_backup(
+    args: RestoreBackupArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let file_path = config.wallet_path(&args.filename);
+
+    if !args.list {
+        // The restored keystore may be under an older password
+        forget_agent_password(config, &file_path).await;
+    }
+    let outcome = commands::restore_backup(&manager, &file_path, args.backup.as_deref(), args.list).await?;
+
+    match output {
+        OutputFormat::Table => {
+            if let Some(backup) = &outcome.restored_from {
+                println!("\n Restored {} from {}", outcome.file.display(), backup.display());
+                if let Some(address) = &outcome.address {
+                    println!("Address: {}", address);
+                }
+            } else if outcome.backups.is_empty() {
+                println!("No backups of {}", outcome.file.display());
+            } else {
+                println!("Backups of {}, oldest first:", outcome.file.display());
+                for backup in &outcome.backups {
+                    println!("  {}", backup.display());
+                }
+            }
+        }
+        OutputFormat::Json => {
+            print_json(&outcome_json(&outcome)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_doctor(
+    args: DoctorArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let audit = health::audit_directory(&config.wallets_path, args.fix).await?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Wallets directory: {} ({} files)", audit.directory.display(), audit.files);
+            if audit.findings.is_empty() {
+                println!("Permissions: ok, owner only");
+            } else {
+                println!();
+                let rows: Vec<Vec<String>> = audit
+                    .findings
+                    .iter()
+                    .map(|f| {
+                        let status = if f.fixed { "fixed" } else { "open" };
+                        vec![f.path.display().to_string(), f.mode.clone(), f.expected.clone(), status.to_string()]
+                    })
+                    .collect();
+                for line in table_lines(&["PATH", "MODE", "EXPECTED", "STATUS"], &rows) {
+                    println!("{}", line);
+                }
+                if !audit.healthy() {
+                    println!("\nOther users can read these; run `web3wallet doctor --fix` to restrict them.");
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let mut value = outcome_json(&audit)?;
+            value["healthy"] = audit.healthy().into();
+            print_json(&value)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_delete(
+    args: DeleteArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let file_path = config.wallet_path(&args.filename);
+
+    if !args.yes {
+        // Unreadable keystores can still be deleted, confirmed by their file name
+        let summary = commands::inspect(&file_path).await.ok();
+        let mut accepted: Vec<String> = Vec::new();
+        if let Some(summary) = &summary {
+            println!("\n {}", tr_args("delete-about-to", &[("file", &summary.file.display().to_string())]));
+            println!("Address:  {}", summary.address);
+            if let Some(alias) = &summary.alias {
+                println!("Alias:    {}", alias);
+                accepted.push(alias.to_lowercase());
+            }
+        } else {
+            println!("\n {}", tr_args("delete-about-to-unreadable", &[("file", &file_path.display().to_string())]));
+            accepted.push(args.filename.to_lowercase());
+        }
+
+        let what = if summary.is_some() { "identity" } else { "file" };
+        let answer = prompt_line(&tr(&format!("delete-confirm-{}", what)))?;
+        let is_address = summary
+            .as_ref()
+            .is_some_and(|summary| web3wallet_cli::utils::addresses_equal(&summary.address, &answer));
+        if !is_address && !accepted.contains(&answer.to_lowercase()) {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "confirmation".to_string(),
+                value: answer,
+                expected: tr(&format!("delete-expected-{}", what)),
+            }
+            .into());
+        }
+    }
+
+    forget_agent_password(config, &file_path).await;
+    let outcome = commands::delete(&file_path, args.shred).await?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Wallet deleted:");
+            if let Some(address) = &outcome.address {
+                println!("Address:  {}", address);
+            }
+            println!("File:     {}{}", outcome.file.display(), if outcome.shredded { " (overwritten)" } else { "" });
+        }
+        OutputFormat::Json => {
+            print_json(&outcome_json(&outcome)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_migrate(
+    args: MigrateArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+
+    let files = match &args.filename {
+        Some(filename) if filename.contains('/') || filename.contains('\\') => vec![PathBuf::from(filename)],
+        Some(filename) => vec![config.wallets_path.join(filename)],
+        None => manager.list_keystores().await?.into_iter().map(|(path, _)| path).collect(),
+    };
+
+    let mut outcomes = Vec::with_capacity(files.len());
+    for file in &files {
+        let password = if args.rekey && !args.dry_run {
+            Some(get_password(&tr_args("prompt-password-for", &[("file", &file.display().to_string())]))?)
+        } else {
+            None
+        };
+        outcomes.push(commands::migrate(&manager, file, args.dry_run, password.as_deref()).await?);
+    }
+
+    match output {
+        OutputFormat::Table => {
+            if outcomes.is_empty() {
+                println!("No keystores found in {}", config.wallets_path.display());
+                return Ok(());
+            }
+            println!("\n{:<32} {:<8} {:<8} {:<10} STATUS", "FILE", "FROM", "TO", "KDF");
+            println!("{}", "─".repeat(80));
+            for outcome in &outcomes {
+                let file = outcome.file.file_name().and_then(|n| n.to_str()).unwrap_or("unknown");
+                let status = match (outcome.migrated, outcome.dry_run) {
+                    (true, true) => "would migrate",
+                    (true, false) => "migrated",
+                    (false, _) => "up to date",
+                };
+                let kdf_note = if outcome.rekeyed {
+                    ", re-encrypted"
+                } else if outcome.kdf_outdated {
+                    ", KDF outdated (--rekey)"
+                } else {
+                    ""
+                };
+                println!(
+                    "{:<32} {:<8} {:<8} {:<10} {}{}",
+                    file, outcome.from_version, outcome.to_version, outcome.kdf, status, kdf_note
+                );
+            }
+        }
+        OutputFormat::Json => {
+            print_json(&serde_json::json!({
+                "success": true,
+                "keystores": outcomes,
+            }))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_list(
+    args: ListArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let mut manager = WalletManager::new(config.clone());
+    if let Some(path) = args.path {
+        manager = manager.with_wallet_dir(path);
+    }
+
+    info!("Scanning wallet directory: {}", manager.config().wallets_path.display());
+    let mut outcome = manager.list().await?;
+    if let Some(network) = &args.network {
+        outcome.retain_network(network);
+    }
+    outcome.sort(args.sort);
+
+    match output{
+        OutputFormat::Table => {
+            println!("\n Wallet directory: {}", outcome.directory.display());
+            if outcome.created {
+                println!("No wallets found. Directory created.");
+                return Ok(());
+            }
+            println!("Found {} wallets: \n", outcome.wallets.len());
+
+            if outcome.wallets.is_empty(){
+                println!("No wallet found.");
+            }else{
+                println!("{:<20} {:<44} {:<12} {:<20} {:<10}",
+                    "FILENAME", "ADDRESS", "NETWORK", "CREATED", "TYPE");
+                println!("{}", "─".repeat(110));
+
+                for wallet in &outcome.wallets{
+                    let wallet_type = match wallet.wallet_type {
+                        WalletType::Full => "full",
+                        WalletType::WatchOnly => "watch-only",
+                    };
+                    println!("{:<20} {:<44} {:<12} {:<20} {:<10}",
+                            wallet.filename,
+                            wallet.address,
+                            wallet.network,
+                            wallet.created_at.get(..19).unwrap_or(&wallet.created_at).replace('T', " "),
+                            wallet_type);
+                };
+            }
+        }
+        OutputFormat::Json => {
+            let mut output = outcome_json(&outcome)?;
+            output["count"] = outcome.wallets.len().into();
+            print_json(&output)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_derive(
+    args: DeriveArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    
+    
+    let manager = WalletManager::new(config.clone());
+
+    let account = account_levels(args.account, args.change);
+    let custom_path = args.path.as_deref().map(split_address_path).transpose()?;
+    let mut key_source = None;
+    let signer: Box<dyn Signer> = if args.signer == SignerKind::Ledger {
+        if args.show_private_keys {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "show-private-keys".to_string(),
+                value: "ledger".to_string(),
+                expected: "a software wallet; a Ledger never reveals its keys".to_string(),
+            }
+            .into());
+        }
+        let hd_path = match args.path_preset {
+            // The device derives siblings of the signing path, one account per address does not fit
+            Some(preset) if !preset.is_flat() => {
+                return Err(UserInputError::InvalidParameters {
+                    parameter: "path-preset".to_string(),
+                    value: preset.as_str().to_string(),
+                    expected: "metamask, legacy or mew with --signer ledger; use --account for Ledger Live accounts".to_string(),
+                }
+                .into());
+            }
+            Some(preset) => Some(preset.path(0)),
+            None => match &custom_path {
+                Some((parent, _)) => Some(format!("{}/0", parent)),
+                None => account.map(|(account, change)| format!("{}/0", web3wallet_cli::config::bip44_base_path(account, change))),
+            },
+        };
+        load_signer(SignerKind::Ledger, None, hd_path.as_deref(), config).await?
+    } else if let Some(signer) = derive_agent_signer(&manager, &args, custom_path.as_ref(), account, config).await {
+        Box::new(signer)
+    } else {
+        let wallet = if let Some(filename) = args.from_file{
+            let file_path = config.wallet_path(&filename);
+
+            let wallet = unlock_wallet(&manager, &file_path, config).await?;
+            record_usage(config, wallet.address(), &[UsageKind::Unlock]).await;
+            wallet
+        } else {
+            let mnemonic = get_secret(&tr("prompt-mnemonic"))?;
+            manager.import_from_mnemoic(&mnemonic).await?
+        };
+
+        if !wallet.is_hd() {
+            return Err(WalletError::UserInput(
+                UserInputError::InvalidParameters {
+                    parameter: "wallet".to_string(),
+                    value: if wallet.is_watch_only() { "single address" } else { "private key only" }.to_string(),
+                    expected: "HD wallet with mnemonic, root key or xpub".to_string(),
+                }
+            ));
+        }
+        let wallet = match (&custom_path, account, args.path_preset) {
+            (Some((parent, _)), _, _) => wallet.with_base_path(parent)?,
+            (None, Some((account, change)), _) => wallet.with_account(account, change)?,
+            (None, None, Some(preset)) => wallet.with_path_preset(preset)?,
+            (None, None, None) => wallet,
+        };
+        if args.show_private_keys {
+            if wallet.is_watch_only() {
+                return Err(AuthenticationError::WatchOnlyWallet { address: display_address(wallet.address()) }.into());
+            }
+            key_source = Some(wallet.clone());
+        }
+        Box::new(SoftwareSigner::new(wallet))
+    };
+
+    let start_index = custom_path.map_or(args.start_index, |(_, index)| index);
+    let mut outcome = commands::derive(signer.as_ref(), start_index, args.count).await?;
+    if let Some(wallet) = &key_source {
+        print_warning(&tr("derive-keys-warning"))?;
+        let answer = prompt_line(&tr("derive-keys-confirm"))?;
+        if answer != "show keys" {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "confirmation".to_string(),
+                value: answer,
+                expected: tr("derive-keys-expected"),
+            }
+            .into());
+        }
+        commands::reveal_private_keys(wallet, &mut outcome)?;
+        record_usage(config, wallet.address(), &[UsageKind::Export]).await;
+    }
+
+    if let Some(out) = &args.out {
+        write_derive_csv(out, &outcome)?;
+        match output {
+            OutputFormat::Table => {
+                println!("Wrote {} addresses to {}", outcome.addresses.len(), out.display());
+            }
+            OutputFormat::Json => {
+                print_json(&serde_json::json!({
+                    "success": true,
+                    "file": out,
+                    "count": outcome.addresses.len(),
+                    "base_path": outcome.base_path,
+                }))?;
+            }
+        }
+        return Ok(());
+    }
+
+    match output {
+        OutputFormat::Table => {
+            let mut lines = vec![
+                String::new(),
+                " Derived addresses from HD wallet:".to_string(),
+                format!("Base address: {}", outcome.base_address),
+                format!("Base path:    {}", outcome.base_path),
+                String::new(),
+            ];
+            let rows: Vec<Vec<String>> = outcome
+                .addresses
+                .iter()
+                .map(|d| {
+                    let chain = d.address_chain.map_or("-", |chain| chain.as_str());
+                    let mut row = vec![d.index.to_string(), d.address.clone(), chain.to_string(), d.derivation_path.clone()];
+                    row.extend(d.private_key.clone());
+                    row
+                })
+                .collect();
+            let headers: &[&str] = if key_source.is_some() {
+                &["INDEX", "ADDRESS", "CHAIN", "DERIVATION PATH", "PRIVATE KEY"]
+            } else {
+                &["INDEX", "ADDRESS", "CHAIN", "DERIVATION PATH"]
+            };
+            lines.extend(table_lines(headers, &rows));
+            print_paged(&lines, !args.no_pager)?;
+        }
+        OutputFormat::Json => {
+            let mut output = outcome_json(&outcome)?;
+            output["count"] = outcome.addresses.len().into();
+            print_json(&output)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_discover(
+    args: DiscoverArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let config = &wallet_config(config, &args.wallet_file).await?;
+    let chain = ChainInfo::resolve(args.chain.as_deref().unwrap_or(&config.network), config)?;
+    let file_path = config.wallet_path(&args.wallet_file);
+    let discovery = AccountDiscovery::new(&chain, args.gap_limit, config)?;
+
+    let manager = WalletManager::new(config.clone());
+    let wallet = unlock_wallet(&manager, &file_path, config).await?;
+    record_usage(config, wallet.address(), &[UsageKind::Unlock]).await;
+    if !wallet.is_hd() {
+        return Err(WalletError::UserInput(
+            UserInputError::InvalidParameters {
+                parameter: "wallet".to_string(),
+                value: if wallet.is_watch_only() { "single address" } else { "private key only" }.to_string(),
+                expected: "HD wallet with mnemonic, root key or xpub".to_string(),
+            }
+        ));
+    }
+
+    info!("Scanning {} with a gap limit of {}", chain.name, args.gap_limit);
+    let accounts = match args.account {
+        Some(account) => vec![discovery.scan_account(&wallet.with_account(account, 0)?, account).await?],
+        None => discovery.scan(&wallet).await?,
+    };
+    let used: usize = accounts.iter().map(|a| a.used.len()).sum();
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Used addresses on {} (gap limit {})", chain.name, args.gap_limit);
+            let rows: Vec<Vec<String>> = accounts
+                .iter()
+                .flat_map(|a| a.used.iter().map(move |u| {
+                    vec![a.account.to_string(), u.index.to_string(), u.address.clone(), u.nonce.to_string(), u.balance.clone()]
+                }))
+                .collect();
+            if !rows.is_empty() {
+                for line in table_lines(&["ACCOUNT", "INDEX", "ADDRESS", "NONCE", "BALANCE (WEI)"], &rows) {
+                    println!("{}", line);
+                }
+            }
+            println!();
+            for a in &accounts {
+                if a.is_used() {
+                    println!("Account {} ({}): {} used, next unused index {}", a.account, a.account_path, a.used.len(), a.next_index());
+                } else {
+                    println!("Account {} ({}): unused, {} addresses checked", a.account, a.account_path, a.scanned);
+                }
+            }
+            println!("Found {} used address(es) in {} account(s)", used, accounts.iter().filter(|a| a.is_used()).count());
+        }
+        OutputFormat::Json => {
+            print_json(&serde_json::json!({
+                "success": true,
+                "chain": chain.name,
+                "gap_limit": args.gap_limit,
+                "used_count": used,
+                "accounts": accounts,
+            }))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_script(
+    args: ScriptArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let mut spec = ScriptSpec::from_file(&args.file).await?;
+    if args.continue_on_error {
+        spec.continue_on_error = true;
+    }
+
+    info!("Running script {} ({} steps)", args.file.display(), spec.steps.len());
+    let report = ScriptRunner::new(config.clone()).run(&spec).await;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Script: {}", spec.name.as_deref().unwrap_or(&args.file.display().to_string()));
+            println!("{:<6} {:<16} {:<10} {:<10}", "STEP", "ID", "OP", "STATUS");
+            println!("{}", "─".repeat(60));
+            for step in &report.steps {
+                let status = match step.status {
+                    StepStatus::Ok => "ok",
+                    StepStatus::Failed => "FAILED",
+                    StepStatus::Skipped => "skipped",
+                };
+                println!("{:<6} {:<16} {:<10} {:<10}",
+                    step.index + 1,
+                    step.id.as_deref().unwrap_or("-"),
+                    step.op,
+                    status);
+                if let Some(err) = &step.error {
+                    println!("       error: {}", err);
+                }
+            }
+            println!("\n{} succeeded, {} failed, {} skipped", report.succeeded, report.failed, report.skipped);
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "success": report.is_success(),
+                "report": report
+            });
+            print_json(&output)?;
+        }
+    }
+
+    if !report.is_success() {
+        return Err(WalletError::Validation(ValidationError::InvalidCommandSyntax {
+            command: "script".to_string(),
+            error: format!("{} of {} steps failed", report.failed, report.steps.len()),
+        }));
+    }
+    Ok(())
+}
+
+async fn execute_app_key(
+    args: AppKeyArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+
+    let wallet = if let Some(filename) = args.from_file{
+        let file_path = config.wallet_path(&filename);
+
+        let wallet = unlock_wallet(&manager, &file_path, config).await?;
+        record_usage(config, wallet.address(), &[UsageKind::Unlock]).await;
+        wallet
+    } else {
+        let mnemonic = get_secret(&tr("prompt-mnemonic"))?;
+        manager.import_from_mnemoic(&mnemonic).await?
+    };
+
+    info!("Deriving app key for {} at index {}", args.app, args.index);
+    let derived = wallet.derive_app_key(&args.app, args.index)?;
+    let signature = match &args.message {
+        Some(message) => {
+            let payload = SignedPayload::Message(message_bytes(message, args.hex)?);
+            Some(SigningService::sign(&wallet.app_key_wallet(&args.app, args.index)?, &payload)?)
+        }
+        None => None,
+    };
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n App key for {}:", args.app);
+            println!("Address:   {}", display_address(derived.address()));
+            println!("Path:      {}", derived.derivation_path());
+            if let Some(signature) = &signature {
+                println!("Signature: {}", signature);
+            }
+        }
+        OutputFormat::Json => {
+            let mut output = serde_json::json!({
+                "app": args.app,
+                "index": args.index,
+                "address": display_address(derived.address()),
+                "derivation_path": derived.derivation_path()
+            });
+            if let Some(signature) = signature {
+                output["signature"] = signature.into();
+            }
+            print_json(&output)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The agent's signer for `derive --from-file` when it holds the keys; revealing the keys and
+/// path presets need the decrypted wallet here
+async fn derive_agent_signer(
+    manager: &WalletManager,
+    args: &DeriveArgs,
+    custom_path: Option<&(String, u32)>,
+    account: Option<(u32, u32)>,
+    config: &WalletConfig,
+) -> Option<AgentSigner> {
+    let filename = args.from_file.as_ref()?;
+    if args.show_private_keys || args.path_preset.is_some() {
+        return None;
+    }
+    let file_path = config.wallet_path(filename);
+    let base_path = match (custom_path, account) {
+        (Some((parent, _)), _) => Some(parent.clone()),
+        (None, Some((account, change))) => Some(web3wallet_cli::config::bip44_base_path(account, change)),
+        (None, None) => None,
+    };
+    agent_signer(manager, &file_path, base_path, config).await
+}
+
+async fn execute_derive_entropy(
+    args: DeriveEntropyArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+
+    let wallet = if let Some(filename) = args.from_file{
+        let file_path = config.wallet_path(&filename);
+
+        let wallet = unlock_wallet(&manager, &file_path, config).await?;
+        record_usage(config, wallet.address(), &[UsageKind::Unlock, UsageKind::Export]).await;
+        wallet
+    } else {
+        let mnemonic = get_secret(&tr("prompt-mnemonic"))?;
+        manager.import_from_mnemoic(&mnemonic).await?
+    };
+
+    info!("Deriving {}-word child mnemonic at index {}", args.words, args.index);
+    let child = MnemonicService::derive_bip85(&wallet, args.language, args.words, args.index)?;
+    let path = web3wallet_cli::config::bip85_mnemonic_path(mnemonic::bip85_language_code(args.language), args.words, args.index);
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Child mnemonic ({} words, index {}):", args.words, args.index);
+            println!("{}", child.phrase());
+            println!("Path:     {}", path);
+        }
+        OutputFormat::Json => {
+            print_json(&serde_json::json!({
+                "success": true,
+                "mnemonic": child.phrase(),
+                "language": mnemonic::language_name(child.language()),
+                "words": args.words,
+                "index": args.index,
+                "derivation_path": path,
+            }))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_sign_tx(
+    args: SignTxArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let mut unsigned = match &args.tx_json {
+        Some(path) => {
+            let json = tokio::fs::read_to_string(path).await?;
+            let mut tx = TransactionService::from_json(&json)?;
+            tx.legacy |= args.legacy;
+            tx
+        }
+        None => UnsignedTransaction {
+            to: args.to,
+            value: Some(args.value),
+            nonce: args.nonce.unwrap_or_default(),
+            gas: Some(args.gas),
+            gas_price: args.gas_price,
+            max_fee_per_gas: args.max_fee,
+            max_priority_fee_per_gas: args.max_priority_fee,
+            chain_id: args.chain_id.unwrap_or_default(),
+            data: args.data,
+            legacy: args.legacy,
+        },
+    };
+
+    let config = &match &args.from_file {
+        Some(filename) => wallet_config(config, filename).await?,
+        None => config.clone(),
+    };
+
+    if unsigned.needs_fees() {
+        let chain = ChainInfo::by_chain_id(unsigned.chain_id, config).filter(|_| !config.offline).ok_or_else(|| {
+            WalletError::UserInput(UserInputError::MissingParameter {
+                parameter: "fees".to_string(),
+                hint: "pass --max-fee/--max-priority-fee (or --gas-price with --legacy); \
+                       estimation needs network access and a known chain id".to_string(),
+            })
+        })?;
+        info!("Estimating fees from {}", chain.name);
+        let estimate = FeeEstimator::estimate(&RpcClient::from_config(&chain.rpc_url, config)?, config.fee_tier).await?;
+        FeeEstimator::apply(&mut unsigned, &estimate);
+    }
+
+    if let (SignerKind::Software, Some(filename)) = (args.signer, &args.from_file) {
+        check_signing_chain(filename, unsigned.chain_id, args.force, config).await?;
+    }
+    let signer = load_signer(args.signer, args.from_file.as_deref(), args.hd_path.as_deref(), config).await?;
+    let address = signer.address().await?;
+    if unsigned.nonce == web3wallet_cli::config::nonces::AUTO {
+        unsigned.nonce = tracked_nonce(config, &address, unsigned.chain_id).await?.to_string();
+        info!("Using nonce {} tracked for {}", unsigned.nonce, display_address(&address));
+    }
+    let chain = ChainInfo::by_chain_id(unsigned.chain_id, config);
+    require_approval(
+        config,
+        &unsigned,
+        &address,
+        chain.as_ref(),
+        args.note.as_deref(),
+        args.approval_token.as_deref(),
+    )
+    .await?;
+
+    info!("Signing transaction on chain {}", unsigned.chain_id);
+    let signed = signer.sign_transaction(&unsigned).await?;
+    record_usage(config, &signed.from, &[UsageKind::Sign]).await;
+    record_nonce(config, &signed.from, &unsigned).await;
+
+    let mut entry = HistoryEntry::new(
+        &chain.as_ref().map_or_else(|| format!("chain {}", unsigned.chain_id), |c| c.name.clone()),
+        chain.as_ref().map_or(web3wallet_cli::config::chains::DEFAULT_CURRENCY_SYMBOL, |c| c.currency_symbol.as_str()),
+        &unsigned,
+        &signed,
+        false,
+    );
+    entry.note = args.note;
+    record_history(config, entry).await;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Transaction signed:");
+            println!("From:     {}", display_address(&signed.from));
+            println!("Hash:     {}", signed.hash);
+            println!("Raw:      {}", signed.raw);
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "success": true,
+                "from": display_address(&signed.from),
+                "hash": signed.hash,
+                "raw": signed.raw
+            });
+            print_json(&output)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_decode_tx(
+    args: DecodeTxArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let raw = match (args.raw, &args.file) {
+        (Some(raw), _) => raw,
+        (None, Some(path)) => tokio::fs::read_to_string(path).await?,
+        (None, None) => unreachable!("clap requires the raw transaction or --file"),
+    };
+    let expected = match args.chain_id {
+        Some(chain_id) => chain_id,
+        None => ChainInfo::resolve(args.chain.as_deref().unwrap_or(&config.network), config)?.chain_id,
+    };
+
+    let tx = raw_transaction::decode(&raw)?;
+    let warnings = tx.warnings(expected);
+    for warning in &warnings {
+        warn!("{}", warning);
+    }
+
+    match output {
+        OutputFormat::Table => {
+            let optional = |label: &str, value: &Option<String>| {
+                if let Some(value) = value {
+                    println!("{:<18}{}", label, value);
+                }
+            };
+            println!("\n {} transaction (type {}):", tx.type_name(), tx.tx_type);
+            println!("{:<18}{}", "Hash:", tx.hash);
+            println!("{:<18}{}", "From:", display_address(&tx.from));
+            println!("{:<18}{}", "To:", tx.to.as_deref().map_or("(contract creation)".to_string(), display_address));
+            println!("{:<18}{}", "Chain id:", tx.chain_id.map_or("(none)".to_string(), |id| id.to_string()));
+            println!("{:<18}{}", "Nonce:", tx.nonce);
+            println!("{:<18}{} wei", "Value:", tx.value);
+            println!("{:<18}{}", "Gas:", tx.gas);
+            optional("Gas price:", &tx.gas_price);
+            optional("Max fee:", &tx.max_fee_per_gas);
+            optional("Max priority fee:", &tx.max_priority_fee_per_gas);
+            optional("Max blob fee:", &tx.max_fee_per_blob_gas);
+            println!("{:<18}{} ({} bytes)", "Data:", tx.data, (tx.data.len() - 2) / 2);
+            for item in &tx.access_list.0 {
+                println!("{:<18}{:?} ({} storage keys)", "Access list:", item.address, item.storage_keys.len());
+            }
+            for hash in &tx.blob_versioned_hashes {
+                println!("{:<18}{}", "Blob hash:", hash);
+            }
+            if let Some(blobs) = tx.blobs {
+                println!("{:<18}{}", "Blobs attached:", blobs);
+            }
+            println!("{:<18}{}", "v:", tx.v);
+            println!("{:<18}{}", "r:", tx.r);
+            println!("{:<18}{}", "s:", tx.s);
+        }
+        OutputFormat::Json => {
+            let mut value = serde_json::to_value(&tx)?;
+            value["from"] = display_address(&tx.from).into();
+            if let Some(to) = &tx.to {
+                value["to"] = display_address(to).into();
+            }
+            value["expected_chain_id"] = expected.into();
+            value["warnings"] = warnings.into();
+            print_json(&value)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_nonce(
+    command: NonceCommand,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let mut store = NonceStore::load(&NonceStore::default_path(&config.state_path)).await?;
+
+    match command {
+        NonceCommand::Sync { account, chain } => {
+            let address = nonce_account(account, config).await?;
+            let chain = ChainInfo::resolve(chain.as_deref().unwrap_or(&config.network), config)?;
+
+            info!("Reading the pending nonce of {} on {}", display_address(&address), chain.name);
+            let count = RpcClient::from_config(&chain.rpc_url, config)?
+                .call("eth_getTransactionCount", serde_json::json!([address, "pending"]))
+                .await?;
+            let pending = parse_quantity("nonce", count.as_str().unwrap_or("0x0"))?.low_u64();
+            let previous = store.get(&address, chain.chain_id).map(|tracked| tracked.next);
+            let next = store.sync(&address, chain.chain_id, pending);
+            store.save().await?;
+            if next > pending {
+                warn!(
+                    "Nonces {} to {} were signed here but are not on {} yet",
+                    pending,
+                    next - 1,
+                    chain.name
+                );
+            }
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n Nonce synced:");
+                    println!("Address:  {}", display_address(&address));
+                    println!("Chain:    {} ({})", chain.name, chain.chain_id);
+                    println!("On chain: {}", pending);
+                    println!("Tracked:  {}", previous.map_or("-".to_string(), |n| n.to_string()));
+                    println!("Next:     {}", next);
+                }
+                OutputFormat::Json => {
+                    print_json(&serde_json::json!({
+                        "success": true,
+                        "address": display_address(&address),
+                        "chain": chain.name,
+                        "chain_id": chain.chain_id,
+                        "pending": pending,
+                        "previous": previous,
+                        "next": next
+                    }))?;
+                }
+            }
+        }
+        NonceCommand::Reset { account, chain } => {
+            let address = nonce_account(account, config).await?;
+            let chain_id = chain.map(|name| ChainInfo::resolve(&name, config).map(|c| c.chain_id)).transpose()?;
+            let removed = store.reset(&address, chain_id);
+            if removed > 0 {
+                store.save().await?;
+            }
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n Cleared {} tracked nonce(s) for {}", removed, display_address(&address));
+                }
+                OutputFormat::Json => {
+                    print_json(&serde_json::json!({
+                        "success": true,
+                        "address": display_address(&address),
+                        "chain_id": chain_id,
+                        "removed": removed
+                    }))?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn nonce_account(account: NonceAccountArgs, config: &WalletConfig) -> WalletResult<String> {
+    match (account.address, account.from_file) {
+        (Some(address), _) => {
+            web3wallet_cli::utils::validate_ethereum_address(&address)?;
+            Ok(address)
+        }
+        (None, Some(filename)) => {
+            let file_path = config.wallet_path(&filename);
+            Ok(web3wallet_cli::services::CryptoService::load_keystore(&file_path).await?.metadata.address)
+        }
+        (None, None) => Err(WalletError::UserInput(UserInputError::MissingParameter {
+            parameter: "address source".to_string(),
+            hint: "either --from-file or --address required".to_string(),
+        })),
+    }
+}
+
+async fn execute_multisig(
+    command: MultisigCommand,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+
+    match command {
+        MultisigCommand::Create { name, threshold, owners } => {
+            let file_path = config.save_path(&name);
+            if file_path.exists() {
+                return Err(FilesystemError::FileExists {
+                    path: file_path.display().to_string(),
+                    suggestion: "choose another name; an existing keystore is never overwritten".to_string(),
+                }
+                .into());
+            }
+            let mut keys = Vec::with_capacity(owners.len());
+            for owner in &owners {
+                let spec = if owner.starts_with("0x") && owner.contains(':') {
+                    owner.clone()
+                } else {
+                    info!("Unlocking owner {}", owner);
+                    MultisigService::owner_spec(&unlock_wallet(&manager, &config.wallet_path(owner), config).await?)?
+                };
+                keys.push(MultisigService::parse_owner_spec(&spec)?);
+            }
+
+            let multisig = MultisigService::create(threshold, &keys)?;
+            if let Some(parent) = file_path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            web3wallet_cli::utils::write_private(&file_path, serde_json::to_string_pretty(&multisig)?.as_bytes()).await?;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n {}-of-{} multisig created:", multisig.threshold, multisig.owners.len());
+                    println!("Address:  {}", display_address(&multisig.address));
+                    println!("File:     {}", file_path.display());
+                    for owner in &multisig.owners {
+                        println!("Owner:    {}", display_address(&owner.address));
+                    }
+                }
+                OutputFormat::Json => {
+                    print_json(&serde_json::json!({
+                        "success": true,
+                        "address": display_address(&multisig.address),
+                        "file": file_path.display().to_string(),
+                        "threshold": multisig.threshold,
+                        "owners": multisig.owners.iter().map(|o| display_address(&o.address)).collect::<Vec<_>>(),
+                    }))?;
+                }
+            }
+        }
+        MultisigCommand::OwnerKey { from_file } => {
+            let wallet = unlock_wallet(&manager, &config.wallet_path(&from_file), config).await?;
+            let spec = MultisigService::owner_spec(&wallet)?;
+            match output {
+                OutputFormat::Table => {
+                    println!("\n Owner key of {}, pass it to `multisig create --owner`:", display_address(wallet.address()));
+                    println!("{}", spec);
+                }
+                OutputFormat::Json => {
+                    print_json(&serde_json::json!({
+                        "success": true,
+                        "address": display_address(wallet.address()),
+                        "owner": spec,
+                    }))?;
+                }
+            }
+        }
+        MultisigCommand::Propose { multisig, tx_json, from_file, note, out } => {
+            let multisig = load_multisig(&multisig, config).await?;
+            let tx = TransactionService::from_json(&tokio::fs::read_to_string(&tx_json).await?)?;
+            let coordinator = web3wallet_cli::services::CryptoService::load_keystore(&config.wallet_path(&from_file))
+                .await?
+                .metadata
+                .address;
+            let proposal = MultisigService::propose(&multisig, &coordinator, &tx, note.as_deref())?;
+            if let Some(path) = &out {
+                tokio::fs::write(path, format!("{}\n", serde_json::to_string_pretty(&proposal)?)).await?;
+            }
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n Proposal from {}:", display_address(&proposal.multisig));
+                    println!("Sighash:     {}", proposal.sighash);
+                    println!("Coordinator: {}", display_address(&proposal.coordinator));
+                    println!("Approvals:   {} of {} owners", multisig.threshold, multisig.owners.len());
+                    match &out {
+                        Some(path) => println!("Written to {}", path.display()),
+                        None => println!("{}", serde_json::to_string(&proposal)?),
+                    }
+                }
+                OutputFormat::Json => {
+                    print_json(&serde_json::json!({ "success": true, "proposal": proposal }))?;
+                }
+            }
+        }
+        MultisigCommand::Approve { multisig, proposal, from_file, out, yes } => {
+            let multisig = load_multisig(&multisig, config).await?;
+            let proposal: MultisigProposal = serde_json::from_str(&tokio::fs::read_to_string(&proposal).await?)?;
+            let tx = &proposal.tx;
+
+            // JSON callers are scripts that already decided, the summary is for people
+            if matches!(output, OutputFormat::Table) {
+                let currency = ChainInfo::by_chain_id(tx.chain_id, config)
+                    .map_or(web3wallet_cli::config::chains::DEFAULT_CURRENCY_SYMBOL.to_string(), |c| c.currency_symbol);
+                let value = parse_quantity("value", tx.value.as_deref().unwrap_or("0"))?;
+                println!("\n Multisig proposal:");
+                println!("From:     {}", display_address(&proposal.multisig));
+                println!("Chain:    {}", tx.chain_id);
+                println!("To:       {}", tx.to.as_deref().map_or_else(|| "contract creation".to_string(), display_address));
+                println!("Value:    {} {}", display_amount(&value.to_string(), 18), currency);
+                println!("Nonce:    {}", tx.nonce);
+                if let Some(data) = tx.data.as_deref().filter(|d| d.len() > 2) {
+                    println!("Data:     {} bytes", data.trim_start_matches("0x").len() / 2);
+                }
+                if let Some(note) = &proposal.note {
+                    println!("Note:     {}", note);
+                }
+            }
+            if !yes {
+                let answer = prompt_line("Approve this transaction? [y/N] ")?;
+                if !matches!(answer.to_lowercase().as_str(), "y" | "yes") {
+                    return Err(UserInputError::InvalidParameters {
+                        parameter: "confirmation".to_string(),
+                        value: answer,
+                        expected: "y to approve".to_string(),
+                    }
+                    .into());
+                }
+            }
+
+            let wallet = unlock_wallet(&manager, &config.wallet_path(&from_file), config).await?;
+            let approval = MultisigService::approve(&multisig, &proposal, &wallet)?;
+            record_usage(config, wallet.address(), &[UsageKind::Unlock, UsageKind::Sign]).await;
+            if let Some(path) = &out {
+                // The approval carries a share of the key, sealed to the coordinator
+                web3wallet_cli::utils::write_private(path, format!("{}\n", serde_json::to_string_pretty(&approval)?).as_bytes())
+                    .await?;
+            }
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n Approved by {}:", display_address(&approval.owner));
+                    match &out {
+                        Some(path) => println!("Written to {}", path.display()),
+                        None => println!("{}", serde_json::to_string(&approval)?),
+                    }
+                }
+                OutputFormat::Json => {
+                    print_json(&serde_json::json!({ "success": true, "approval": approval }))?;
+                }
+            }
+        }
+        MultisigCommand::Combine { multisig, proposal, approvals, from_file } => {
+            let multisig = load_multisig(&multisig, config).await?;
+            let proposal: MultisigProposal = serde_json::from_str(&tokio::fs::read_to_string(&proposal).await?)?;
+            let mut collected = Vec::new();
+            for source in &approvals {
+                let text = if source == "-" {
+                    let mut text = String::new();
+                    std::io::Read::read_to_string(&mut std::io::stdin(), &mut text)?;
+                    text
+                } else {
+                    tokio::fs::read_to_string(source).await?
+                };
+                // Several approvals can follow each other in one file or on stdin
+                for approval in serde_json::Deserializer::from_str(&text).into_iter::<MultisigApproval>() {
+                    collected.push(approval?);
+                }
+            }
+
+            let wallet = unlock_wallet(&manager, &config.wallet_path(&from_file), config).await?;
+            info!("Combining {} approval(s) for {}", collected.len(), display_address(&multisig.address));
+            let signed = MultisigService::combine(&multisig, &proposal, &collected, &wallet)?;
+            record_usage(config, wallet.address(), &[UsageKind::Unlock]).await;
+
+            let chain = ChainInfo::by_chain_id(proposal.tx.chain_id, config);
+            let mut entry = HistoryEntry::new(
+                &chain.as_ref().map_or_else(|| format!("chain {}", proposal.tx.chain_id), |c| c.name.clone()),
+                chain.as_ref().map_or(web3wallet_cli::config::chains::DEFAULT_CURRENCY_SYMBOL, |c| c.currency_symbol.as_str()),
+                &proposal.tx,
+                &signed,
+                false,
+            );
+            entry.note = proposal.note.clone();
+            record_history(config, entry).await;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n Multisig transaction signed:");
+                    println!("From:     {}", display_address(&signed.from));
+                    println!("Hash:     {}", signed.hash);
+                    println!("Raw:      {}", signed.raw);
+                }
+                OutputFormat::Json => {
+                    print_json(&serde_json::json!({
+                        "success": true,
+                        "from": display_address(&signed.from),
+                        "hash": signed.hash,
+                        "raw": signed.raw
+                    }))?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn load_multisig(name: &str, config: &WalletConfig) -> WalletResult<MultisigKeystore> {
+    let file_path = config.wallet_path(name);
+    MultisigKeystore::from_json(&tokio::fs::read_to_string(&file_path).await?, &file_path.display().to_string())
+}
+
+/// Bytes of a `--message`: the text itself, or the decoded bytes with `--hex`
+fn message_bytes(message: &str, hex: bool) -> WalletResult<Vec<u8>> {
+    if !hex {
+        return Ok(message.as_bytes().to_vec());
+    }
+    hex::decode(message.strip_prefix("0x").unwrap_or(message)).map_err(|_| {
+        WalletError::UserInput(UserInputError::InvalidParameters {
+            parameter: "message".to_string(),
+            value: message.to_string(),
+            expected: "hex encoded bytes".to_string(),
+        })
+    })
+}
+
+async fn execute_sign_message(
+    args: SignMessageArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let message = message_bytes(&args.message, args.hex)?;
+    let signer = load_signer(args.signer, args.from_file.as_deref(), args.hd_path.as_deref(), config).await?;
+    let address = signer.address().await?;
+    let signature = signer.sign_message(&message).await?;
+    record_usage(config, &address, &[UsageKind::Sign]).await;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Message signed:");
+            println!("Signer:    {}", display_address(&address));
+            println!("Signature: {}", signature);
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "success": true,
+                "signer": display_address(&address),
+                "signature": signature
+            });
+            print_json(&output)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_sign_safe_message(
+    args: SignSafeMessageArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let file_path = config.wallet_path(&args.from_file);
+
+    check_signing_chain(&args.from_file, args.chain_id, args.force, config).await?;
+    let wallet = unlock_wallet(&manager, &file_path, config).await?;
+
+    info!("Signing SafeMessage for {} on chain {}", args.safe, args.chain_id);
+    let signed = SafeService::sign_message(&wallet, &args.safe, args.chain_id, &args.message, args.hex)?;
+    record_usage(config, wallet.address(), &[UsageKind::Unlock, UsageKind::Sign]).await;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Safe message signed:");
+            println!("Safe:         {}", display_address(&signed.safe));
+            println!("Signer:       {}", display_address(&signed.signer));
+            println!("Message hash: {}", signed.safe_message_hash);
+            println!("Signature:    {}", signed.signature);
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "success": true,
+                "safe": display_address(&signed.safe),
+                "signer": display_address(&signed.signer),
+                "safe_message_hash": signed.safe_message_hash,
+                "signature": signed.signature
+            });
+            print_json(&output)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_safe(
+    command: SafeCommand,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let (tx_args, signer) = match command {
+        SafeCommand::Hash { tx } => (tx, None),
+        SafeCommand::Sign { from_file, tx, out, force } => (tx, Some((from_file, out, force))),
+    };
+    let config = &match &signer {
+        Some((from_file, ..)) => wallet_config(config, from_file).await?,
+        None => config.clone(),
+    };
+
+    let chain = ChainInfo::resolve(tx_args.chain.as_deref().unwrap_or(&config.network), config)?;
+    let tx = safe_transaction(tx_args, &chain, config).await?;
+    if tx.operation == SafeOperation::DelegateCall {
+        warn!(
+            "This is a delegatecall: {} runs with the Safe's storage and can take it over",
+            display_address(&format!("{:?}", tx.to))
+        );
+    }
+    let safe_tx_hash = format!("0x{}", hex::encode(tx.safe_tx_hash()));
+
+    let Some((from_file, out, force)) = signer else {
+        match output {
+            OutputFormat::Table => {
+                println!("\n SafeTxHash for {} on {}:", display_address(&format!("{:?}", tx.safe)), chain.name);
+                println!("Nonce:      {}", tx.nonce);
+                println!("SafeTxHash: {}", safe_tx_hash);
+            }
+            OutputFormat::Json => {
+                print_json(&serde_json::json!({
+                    "success": true,
+                    "safe": display_address(&format!("{:?}", tx.safe)),
+                    "chain_id": tx.chain_id,
+                    "nonce": tx.nonce,
+                    "safe_tx_hash": safe_tx_hash,
+                }))?;
+            }
+        }
+        return Ok(());
+    };
+
+    let manager = WalletManager::new(config.clone());
+    check_signing_chain(&from_file, chain.chain_id, force, config).await?;
+    let wallet = unlock_wallet(&manager, &config.wallet_path(&from_file), config).await?;
+
+    info!("Signing SafeTx {} for {}", safe_tx_hash, display_address(&format!("{:?}", tx.safe)));
+    let signed = SafeService::sign_transaction(&wallet, &tx)?;
+    record_usage(config, wallet.address(), &[UsageKind::Unlock, UsageKind::Sign]).await;
+    let proposal = signed.proposal(&tx);
+    if let Some(path) = &out {
+        tokio::fs::write(path, format!("{}\n", serde_json::to_string_pretty(&proposal)?)).await?;
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Safe transaction signed:");
+            println!("Safe:       {}", signed.safe);
+            println!("Signer:     {}", signed.signer);
+            println!("Nonce:      {}", tx.nonce);
+            println!("SafeTxHash: {}", signed.safe_tx_hash);
+            println!("Signature:  {}", signed.signature);
+            if let Some(path) = &out {
+                println!("Proposal written to {}", path.display());
+            }
+        }
+        OutputFormat::Json => {
+            print_json(&serde_json::json!({
+                "success": true,
+                "safe": signed.safe,
+                "chain_id": tx.chain_id,
+                "signer": signed.signer,
+                "nonce": tx.nonce,
+                "safe_tx_hash": signed.safe_tx_hash,
+                "signature": signed.signature,
+                "proposal": proposal,
+            }))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The Safe transaction `args` describe, its nonce read from the Safe unless given
+async fn safe_transaction(args: SafeTxArgs, chain: &ChainInfo, config: &WalletConfig) -> WalletResult<SafeTransaction> {
+    let safe = parse_address("safe", &args.safe)?;
+    let optional_address = |parameter: &str, value: &Option<String>| {
+        value.as_deref().map_or(Ok(ethers::types::Address::zero()), |value| parse_address(parameter, value))
+    };
+    let nonce = match args.nonce {
+        Some(nonce) => nonce,
+        None if config.offline => {
+            return Err(UserInputError::MissingParameter {
+                parameter: "nonce".to_string(),
+                hint: "offline the Safe nonce cannot be read from the chain, pass it explicitly".to_string(),
+            }
+            .into());
+        }
+        None => {
+            info!("Reading the nonce of Safe {} on {}", display_address(&args.safe), chain.name);
+            SafeService::nonce(&RpcClient::from_config(&chain.rpc_url, config)?, safe).await?
+        }
+    };
+
+    Ok(SafeTransaction {
+        safe,
+        chain_id: chain.chain_id,
+        to: parse_address("to", &resolve_address(&args.to, config).await?)?,
+        value: parse_quantity("value", &args.value)?,
+        data: args.data.as_deref().map_or(Ok(Vec::new()), |data| parse_bytes("data", data).map(|b| b.to_vec()))?,
+        operation: args.operation,
+        safe_tx_gas: parse_quantity("safe_tx_gas", &args.safe_tx_gas)?,
+        base_gas: parse_quantity("base_gas", &args.base_gas)?,
+        gas_price: parse_quantity("gas_price", &args.gas_price)?,
+        gas_token: optional_address("gas_token", &args.gas_token)?,
+        refund_receiver: optional_address("refund_receiver", &args.refund_receiver)?,
+        nonce,
+    })
+}
+
+async fn execute_verify_signature(
+    args: VerifySignatureArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    // Typed data files are also identified by the digest of their canonical JSON; a file with
+    // integers past 2^53 has no canonical form and still verifies, without one
+    let (payload, document) = match (&args.message, &args.typed_data) {
+        (_, Some(path)) => {
+            let json = tokio::fs::read_to_string(path).await?;
+            (SignedPayload::typed_data_from_json(&json)?, canonical_json::digest_str(&json).ok())
+        }
+        (Some(message), None) => (SignedPayload::Message(message.as_bytes().to_vec()), None),
+        (None, None) => unreachable!("clap requires --message or --typed-data"),
+    };
+
+    let expected = resolve_address(&args.address, config).await?;
+    let verification = SigningService::verify(&payload, &args.signature, &expected)?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Signature {}:", if verification.valid { "valid" } else { "INVALID" });
+            println!("Expected:  {}", display_address(&verification.expected));
+            println!("Recovered: {}", display_address(&verification.recovered));
+            println!("Hash:      {}", verification.hash);
+            if let Some(document) = &document {
+                println!("Document:  {}", document);
+            }
+        }
+        OutputFormat::Json => {
+            let mut output = serde_json::json!({
+                "valid": verification.valid,
+                "expected": display_address(&verification.expected),
+                "recovered": display_address(&verification.recovered),
+                "hash": verification.hash
+            });
+            if let Some(document) = document {
+                output["document_hash"] = serde_json::json!(document);
+            }
+            print_json(&output)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_send_nft(
+    args: SendNftArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let config = &wallet_config(config, &args.from_file).await?;
+    let chain = ChainInfo::resolve(args.chain.as_deref().unwrap_or(&config.network), config)?;
+    let contract = parse_address("contract", &args.contract)?;
+    let recipient = resolve_address(&args.to, config).await?;
+    let to = parse_address("to", &recipient)?;
+    let token_id = parse_quantity("token_id", &args.token_id)?;
+    let client = RpcClient::from_config(&chain.rpc_url, config)?;
+
+    let manager = WalletManager::new(config.clone());
+    let file_path = config.wallet_path(&args.from_file);
+
+    check_signing_chain(&args.from_file, chain.chain_id, args.force, config).await?;
+    let wallet = unlock_wallet(&manager, &file_path, config).await?;
+    let from = parse_address("from", wallet.address())?;
+
+    let standard = match args.standard {
+        Some(standard) => standard,
+        None => NftService::detect_standard(&client, &args.contract).await?,
+    };
+
+    let amount = match (standard, &args.amount) {
+        (NftStandard::Erc1155, Some(amount)) => parse_quantity("amount", amount)?,
+        (NftStandard::Erc1155, None) => 1.into(),
+        (NftStandard::Erc721, None) => 1.into(),
+        (NftStandard::Erc721, Some(amount)) => {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "amount".to_string(),
+                value: amount.clone(),
+                expected: "no --amount for ERC-721 tokens".to_string(),
+            }
+            .into());
+        }
+    };
+
+    let transfer = NftTransfer { standard, contract, from, to, token_id, amount };
+
+    info!("Checking ownership of token {} on {}", token_id, chain.name);
+    NftService::check_ownership(&client, &transfer).await?;
+
+    let unsigned = TransactionService::prepare(
+        &client,
+        chain.chain_id,
+        wallet.address(),
+        &args.contract,
+        0.into(),
+        &transfer.calldata(),
+        FeeOptions { legacy: args.legacy, tier: config.fee_tier },
+    )
+    .await?;
+    let signed = TransactionService::sign(&wallet, &unsigned)?;
+    record_usage(config, wallet.address(), &[UsageKind::Unlock, UsageKind::Sign]).await;
+
+    let broadcast = !args.sign_only;
+    if broadcast {
+        info!("Broadcasting transaction to {}", chain.name);
+        TransactionService::broadcast(&client, &signed).await?;
+    }
+
+    let mut entry = HistoryEntry::new(&chain.name, &chain.currency_symbol, &unsigned, &signed, broadcast);
+    let label = match standard {
+        NftStandard::Erc721 => "ERC-721",
+        NftStandard::Erc1155 => "ERC-1155",
+    };
+    entry.to = recipient;
+    entry.asset = format!("{} {}#{}", label, display_address(&args.contract), token_id);
+    entry.amount = amount.to_string();
+    entry.note = args.note.clone();
+    record_history(config, entry).await;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n NFT transfer {}:", if broadcast { "sent" } else { "signed" });
+            println!("Contract: {}", display_address(&args.contract));
+            println!("Token id: {}", token_id);
+            if standard == NftStandard::Erc1155 {
+                println!("Amount:   {}", amount);
+            }
+            println!("From:     {}", display_address(&signed.from));
+            println!("To:       {}", display_address(&args.to));
+            println!("Hash:     {}", signed.hash);
+            if !broadcast {
+                println!("Raw:      {}", signed.raw);
+            }
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "success": true,
+                "broadcast": broadcast,
+                "chain": chain.name,
+                "standard": standard,
+                "contract": display_address(&args.contract),
+                "token_id": token_id.to_string(),
+                "amount": amount.to_string(),
+                "from": display_address(&signed.from),
+                "to": display_address(&args.to),
+                "hash": signed.hash,
+                "raw": signed.raw
+            });
+            print_json(&output)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The registered or bundled token `query` names; None for the address of an unlisted
+/// contract, which can still be used as a token
+fn known_token(registry: &TokenRegistry, query: &str, chain_id: u64) -> WalletResult<Option<Token>> {
+    match registry.find_or_bundled(query, chain_id) {
+        Ok(token) => Ok(Some(token)),
+        Err(_) if web3wallet_cli::utils::validate_ethereum_address(query).is_ok() => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+async fn execute_sign_permit(
+    args: SignPermitArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let config = &wallet_config(config, &args.from_file).await?;
+    let chain = ChainInfo::resolve(args.chain.as_deref().unwrap_or(&config.network), config)?;
+    let registry = TokenRegistry::load(&TokenRegistry::default_path(&config.state_path)).await?;
+    let token = known_token(&registry, &args.token, chain.chain_id)?.map_or(args.token.clone(), |t| t.address);
+    let token = parse_address("token", &token)?;
+    let spender = parse_address("spender", &resolve_address(&args.spender, config).await?)?;
+    let value = permit::parse_value(&args.value)?;
+    let nonce = args.nonce.as_deref().map(|n| parse_quantity("nonce", n)).transpose()?;
+    let separator = args.domain_separator.as_deref().map(permit::parse_separator).transpose()?;
+
+    if args.deadline <= chrono::Utc::now().timestamp().max(0) as u64 {
+        return Err(UserInputError::InvalidParameters {
+            parameter: "deadline".to_string(),
+            value: args.deadline.to_string(),
+            expected: "a unix time in the future".to_string(),
+        }
+        .into());
+    }
+    if config.offline && (nonce.is_none() || (separator.is_none() && args.domain_name.is_none())) {
+        return Err(UserInputError::MissingParameter {
+            parameter: (if nonce.is_none() { "nonce" } else { "domain_separator" }).to_string(),
+            hint: "offline the token's nonce and domain cannot be read from the chain, pass --nonce and \
+                   --domain-separator (or --domain-name)"
+                .to_string(),
+        }
+        .into());
+    }
+
+    let manager = WalletManager::new(config.clone());
+    let file_path = config.wallet_path(&args.from_file);
+
+    check_signing_chain(&args.from_file, chain.chain_id, args.force, config).await?;
+    let wallet = unlock_wallet(&manager, &file_path, config).await?;
+    let owner = parse_address("from", wallet.address())?;
+
+    let onchain = if !config.offline && (nonce.is_none() || separator.is_none()) {
+        info!("Reading the permit domain and nonce of {} on {}", display_address(&format!("{:?}", token)), chain.name);
+        let client = RpcClient::from_config(&chain.rpc_url, config)?;
+        Some(TokenPermitService::read(&client, token, owner).await?)
+    } else {
+        None
+    };
+
+    let domain = args.domain_name.or_else(|| onchain.as_ref().and_then(|t| t.name.clone())).map(|name| PermitDomain {
+        name,
+        version: args
+            .domain_version
+            .or_else(|| onchain.as_ref().and_then(|t| t.version.clone()))
+            .unwrap_or_else(|| web3wallet_cli::config::permit::DEFAULT_DOMAIN_VERSION.to_string()),
+    });
+    let separator = match (separator, &onchain, &domain) {
+        (Some(separator), _, _) => separator,
+        (None, Some(onchain), _) => onchain.separator,
+        (None, None, Some(domain)) => domain.separator(chain.chain_id, token),
+        (None, None, None) => {
+            return Err(UserInputError::MissingParameter {
+                parameter: "domain_separator".to_string(),
+                hint: "pass the token's DOMAIN_SEPARATOR() or its --domain-name".to_string(),
+            }
+            .into());
+        }
+    };
+    let nonce = match nonce.or(onchain.as_ref().map(|t| t.nonce)) {
+        Some(nonce) => nonce,
+        None => {
+            return Err(UserInputError::MissingParameter {
+                parameter: "nonce".to_string(),
+                hint: "pass the token's nonces(owner) for this wallet".to_string(),
+            }
+            .into());
+        }
+    };
+
+    let permit = TokenPermit {
+        token,
+        chain_id: chain.chain_id,
+        owner,
+        spender,
+        value,
+        nonce,
+        deadline: args.deadline,
+        separator,
+        domain,
+    };
+    if !permit.domain_matches() {
+        warn!(
+            "The token's domain separator is not the name/version/chainId/verifyingContract one {}, \
+             signing against it without an EIP-712 document",
+            permit.domain.as_ref().map_or("(no name known)".to_string(), |d| format!("of {:?} version {:?}", d.name, d.version))
+        );
+    }
+    let signed = TokenPermitService::sign(&wallet, &permit)?;
+    record_usage(config, wallet.address(), &[UsageKind::Unlock, UsageKind::Sign]).await;
+
+    if let Some(path) = &args.out {
+        tokio::fs::write(path, format!("{}\n", serde_json::to_string_pretty(&signed)?)).await?;
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n EIP-2612 permit signed:");
+            println!("Token:     {}", signed.token);
+            println!("Owner:     {}", signed.owner);
+            println!("Spender:   {}", signed.spender);
+            println!("Value:     {}", signed.value);
+            println!("Nonce:     {}", signed.nonce);
+            println!("Deadline:  {}", signed.deadline);
+            println!("Hash:      {}", signed.hash);
+            println!("v:         {}", signed.v);
+            println!("r:         {}", signed.r);
+            println!("s:         {}", signed.s);
+            println!("Signature: {}", signed.signature);
+            println!("Calldata:  {}", signed.calldata);
+            if let Some(path) = &args.out {
+                println!("Permit written to {}", path.display());
+            }
+        }
+        OutputFormat::Json => {
+            print_json(&serde_json::json!({
+                "success": true,
+                "chain": chain.name,
+                "token": signed.token,
+                "owner": signed.owner,
+                "spender": signed.spender,
+                "value": signed.value,
+                "nonce": signed.nonce,
+                "deadline": signed.deadline,
+                "hash": signed.hash,
+                "signature": signed.signature,
+                "v": signed.v,
+                "r": signed.r,
+                "s": signed.s,
+                "calldata": signed.calldata,
+                "typed_data": signed.typed_data,
+            }))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_send_token(
+    args: SendTokenArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let config = &wallet_config(config, &args.from_file).await?;
+    let chain = ChainInfo::resolve(args.chain.as_deref().unwrap_or(&config.network), config)?;
+    let registry = TokenRegistry::load(&TokenRegistry::default_path(&config.state_path)).await?;
+    let known = known_token(&registry, &args.token, chain.chain_id)?;
+    let token = known.as_ref().map_or(args.token.clone(), |t| t.address.clone());
+    let recipient = resolve_address(&args.to, config).await?;
+    let to = parse_address("to", &recipient)?;
+
+    let token_service = Erc20Service::from_config(&chain, config).await?;
+    let (symbol, decimals) = token_service.metadata(&token, known.as_ref()).await?;
+    let amount = erc20::parse_amount(&args.amount, decimals)?;
+
+    let manager = WalletManager::new(config.clone());
+    let file_path = config.wallet_path(&args.from_file);
+
+    check_signing_chain(&args.from_file, chain.chain_id, args.force, config).await?;
+    let wallet = unlock_wallet(&manager, &file_path, config).await?;
+
+    let balance = token_service.balance_of(&token, wallet.address()).await?;
+    if balance < amount {
+        return Err(ValidationError::PreconditionFailed {
+            check: "token-balance".to_string(),
+            details: format!(
+                "{} holds {} {}, less than the {} to send",
+                display_address(wallet.address()),
+                erc20::display_units(balance, decimals),
+                symbol,
+                args.amount
+            ),
+        }
+        .into());
+    }
+
+    let client = RpcClient::from_config(&chain.rpc_url, config)?;
+    let unsigned = TransactionService::prepare(
+        &client,
+        chain.chain_id,
+        wallet.address(),
+        &token,
+        0.into(),
+        &erc20::transfer_calldata(to, amount),
+        FeeOptions { legacy: args.legacy, tier: config.fee_tier },
+    )
+    .await?;
+    require_approval(
+        config,
+        &unsigned,
+        wallet.address(),
+        Some(&chain),
+        args.note.as_deref(),
+        args.approval_token.as_deref(),
+    )
+    .await?;
+    let signed = TransactionService::sign(&wallet, &unsigned)?;
+    record_usage(config, wallet.address(), &[UsageKind::Unlock, UsageKind::Sign]).await;
+
+    let broadcast = !args.sign_only && args.out.is_none();
+    if broadcast {
+        info!("Broadcasting transaction to {}", chain.name);
+        TransactionService::broadcast(&client, &signed).await?;
+    }
+    if let Some(path) = &args.out {
+        tokio::fs::write(path, format!("{}\n", signed.raw)).await?;
+    }
+
+    let mut entry = HistoryEntry::new(&chain.name, &chain.currency_symbol, &unsigned, &signed, broadcast);
+    entry.to = recipient.clone();
+    entry.asset = symbol.clone();
+    entry.amount = erc20::display_units(amount, decimals);
+    entry.note = args.note.clone();
+    record_history(config, entry).await;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Token transfer {}:", if broadcast { "sent" } else { "signed" });
+            println!("Amount:   {} {}", erc20::display_units(amount, decimals), symbol);
+            println!("Token:    {}", display_address(&token));
+            println!("From:     {}", display_address(&signed.from));
+            println!("To:       {}", display_address(&recipient));
+            println!("Hash:     {}", signed.hash);
+            match &args.out {
+                Some(path) => println!("Raw:      written to {}", path.display()),
+                None if !broadcast => println!("Raw:      {}", signed.raw),
+                None => {}
+            }
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "success": true,
+                "broadcast": broadcast,
+                "chain": chain.name,
+                "token": display_address(&token),
+                "symbol": symbol,
+                "decimals": decimals,
+                "amount": erc20::display_units(amount, decimals),
+                "amount_base_units": amount.to_string(),
+                "from": display_address(&signed.from),
+                "to": display_address(&recipient),
+                "hash": signed.hash,
+                "raw": signed.raw
+            });
+            print_json(&output)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_wrap(
+    args: WrapArgs,
+    unwrap: bool,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let config = &wallet_config(config, &args.from_file).await?;
+    let chain = ChainInfo::resolve(args.network.as_deref().unwrap_or(&config.network), config)?;
+    let wrapped_native = chain.wrapped_native.clone().ok_or_else(|| {
+        WalletError::UserInput(UserInputError::InvalidParameters {
+            parameter: "network".to_string(),
+            value: chain.name.clone(),
+            expected: "a network with wrapped_native set in its [networks] entry".to_string(),
+        })
+    })?;
+    let amount = ethers::utils::parse_ether(&args.amount).map_err(|_| {
+        WalletError::UserInput(UserInputError::InvalidParameters {
+            parameter: "amount".to_string(),
+            value: args.amount.clone(),
+            expected: "a decimal amount in ether units, e.g. 1.0".to_string(),
+        })
+    })?;
+
+    let (value, calldata) = if unwrap {
+        (0.into(), WrappedNative::withdraw(amount))
+    } else {
+        (amount, WrappedNative::deposit())
+    };
+    let client = RpcClient::from_config(&chain.rpc_url, config)?;
+
+    let manager = WalletManager::new(config.clone());
+    let file_path = config.wallet_path(&args.from_file);
+
+    check_signing_chain(&args.from_file, chain.chain_id, args.force, config).await?;
+    let wallet = unlock_wallet(&manager, &file_path, config).await?;
+
+    let unsigned = TransactionService::prepare(
+        &client,
+        chain.chain_id,
+        wallet.address(),
+        &wrapped_native,
+        value,
+        &calldata,
+        FeeOptions { legacy: args.legacy, tier: config.fee_tier },
+    )
+    .await?;
+    require_approval(
+        config,
+        &unsigned,
+        wallet.address(),
+        Some(&chain),
+        args.note.as_deref(),
+        args.approval_token.as_deref(),
+    )
+    .await?;
+    let signed = TransactionService::sign(&wallet, &unsigned)?;
+    record_usage(config, wallet.address(), &[UsageKind::Unlock, UsageKind::Sign]).await;
+
+    let broadcast = !args.sign_only;
+    if broadcast {
+        info!("Broadcasting transaction to {}", chain.name);
+        TransactionService::broadcast(&client, &signed).await?;
+    }
+
+    let mut entry = HistoryEntry::new(&chain.name, &chain.currency_symbol, &unsigned, &signed, broadcast);
+    if unwrap {
+        entry.asset = format!("W{}", chain.currency_symbol);
+        entry.amount = args.amount.clone();
+    }
+    entry.note = args.note.clone();
+    record_history(config, entry).await;
+
+    let action = if unwrap { "unwrap" } else { "wrap" };
+    match output {
+        OutputFormat::Table => {
+            println!("\n {} of {} {}:", action, args.amount, if broadcast { "sent" } else { "signed" });
+            println!("Network:  {}", chain.name);
+            println!("Token:    {}", display_address(&wrapped_native));
+            println!("From:     {}", display_address(&signed.from));
+            println!("Hash:     {}", signed.hash);
+            if !broadcast {
+                println!("Raw:      {}", signed.raw);
+            }
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "success": true,
+                "action": action,
+                "broadcast": broadcast,
+                "network": chain.name,
+                "token": display_address(&wrapped_native),
+                "amount_wei": amount.to_string(),
+                "from": display_address(&signed.from),
+                "hash": signed.hash,
+                "raw": signed.raw
+            });
+            print_json(&output)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_quote(
+    args: QuoteArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let chain = ChainInfo::resolve(&args.chain, config)?;
+    let registry = TokenRegistry::load(&TokenRegistry::default_path(&config.state_path)).await?;
+    let sell = QuoteToken::resolve(&registry, &args.sell, chain.chain_id)?;
+    let buy = QuoteToken::resolve(&registry, &args.buy, chain.chain_id)?;
+
+    let client = QuoteClient::from_config(config, args.aggregator, args.api_url.as_deref())?;
+    info!("Requesting {} -> {} quote on {}", sell.symbol, buy.symbol, chain.name);
+    let quote = client.quote(chain.chain_id, sell, buy, &args.amount).await?;
+
+    let buy_display = display_amount(&quote.buy_amount, quote.buy.decimals);
+    match output {
+        OutputFormat::Table => {
+            println!("\n Quote on {} (informational only, nothing is executed):", chain.name);
+            println!("Sell:     {} {}", args.amount, quote.sell.symbol);
+            println!("Buy:      {} {}", buy_display, quote.buy.symbol);
+            println!("Price:    1 {} = {:.6} {}", quote.sell.symbol, quote.price(), quote.buy.symbol);
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "chain": chain.name,
+                "aggregator": quote.api,
+                "sell": quote.sell,
+                "buy": quote.buy,
+                "sell_amount": quote.sell_amount,
+                "buy_amount": quote.buy_amount,
+                "buy_amount_display": buy_display,
+                "price": quote.price()
+            });
+            print_json(&output)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_monitor(
+    args: MonitorArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let mut thresholds = Vec::new();
+    for (chain, amount) in &config.min_balances {
+        if !args.min_balances.iter().any(|spec| spec.split('=').next() == Some(chain.as_str())) {
+            thresholds.push(BalanceThreshold::new(chain, amount, config)?);
+        }
+    }
+    for spec in &args.min_balances {
+        thresholds.push(BalanceThreshold::parse(spec, config)?);
+    }
+    for threshold in &mut thresholds {
+        threshold.chain = threshold.chain.clone().with_config(config);
+    }
+    if thresholds.is_empty() {
+        return Err(UserInputError::MissingParameter {
+            parameter: "min-balance".to_string(),
+            hint: "pass --min-balance <chain>=<amount> at least once".to_string(),
+        }
+        .into());
+    }
+
+    // Cold wallets never pay for gas, only hot wallets are watched
+    let manager = WalletManager::new(config.clone());
+    let wallets: Vec<(String, String)> = manager
+        .list_keystores()
+        .await?
+        .into_iter()
+        .filter(|(_, keystore)| !keystore.metadata.cold)
+        .map(|(path, keystore)| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("unknown").to_string();
+            (name, keystore.metadata.address)
+        })
+        .collect();
+    info!("Monitoring {} hot wallets on {} chains", wallets.len(), thresholds.len());
+
+    let shutdown = Shutdown::new();
+    shutdown.listen();
+
+    loop {
+        let Some(check) = shutdown.begin() else {
+            break;
+        };
+        let statuses = BalanceMonitor::check(&wallets, &thresholds, config).await?;
+        let low: Vec<_> = statuses.iter().filter(|s| s.below_threshold).collect();
+
+        for status in &low {
+            warn!(
+                "{} ({}) on {} has {}, below the minimum of {}",
+                status.wallet,
+                display_address(&status.address),
+                status.chain,
+                status.balance.as_deref().unwrap_or("?"),
+                status.min_balance
+            );
+        }
+
+        match output {
+            OutputFormat::Table => {
+                println!("\n {:<24} {:<44} {:<10} {:>24} {:>12}", "WALLET", "ADDRESS", "CHAIN", "BALANCE", "MINIMUM");
+                for status in &statuses {
+                    let balance = match (&status.balance, &status.error) {
+                        (Some(balance), _) => balance.clone(),
+                        (None, Some(_)) => "error".to_string(),
+                        (None, None) => "-".to_string(),
+                    };
+                    println!(
+                        "{}{:<24} {:<44} {:<10} {:>24} {:>12}",
+                        if status.below_threshold { "!" } else { " " },
+                        status.wallet,
+                        display_address(&status.address),
+                        status.chain,
+                        balance,
+                        status.min_balance
+                    );
+                }
+            }
+            OutputFormat::Json => {
+                let output = serde_json::json!({
+                    "checked_at": chrono::Utc::now().to_rfc3339(),
+                    "alerts": low.len(),
+                    "balances": statuses
+                        .iter()
+                        .map(|s| serde_json::json!({
+                            "wallet": s.wallet,
+                            "address": display_address(&s.address),
+                            "chain": s.chain,
+                            "balance": s.balance,
+                            "min_balance": s.min_balance,
+                            "below_threshold": s.below_threshold,
+                            "error": s.error
+                        }))
+                        .collect::<Vec<_>>()
+                });
+                print_json(&output)?;
+            }
+        }
+
+        if args.once {
+            if !low.is_empty() {
+                return Err(ValidationError::PreconditionFailed {
+                    check: "min-balance".to_string(),
+                    details: format!("{} wallet(s) below their minimum balance", low.len()),
+                }
+                .into());
+            }
+            return Ok(());
+        }
+        drop(check);
+
+        tokio::select! {
+            _ = tokio::time::sleep(std::time::Duration::from_secs(args.interval)) => {}
+            _ = shutdown.triggered() => break,
+        }
+    }
+
+    exit_after_shutdown(&shutdown).await
+}
+
+/// Drain in-flight operations after SIGINT/SIGTERM, flush output and exit with the signal's code
+async fn exit_after_shutdown(shutdown: &Shutdown) -> ! {
+    use std::io::Write;
+
+    let signal = shutdown.triggered().await;
+    let grace = std::time::Duration::from_secs(web3wallet_cli::config::shutdown::DRAIN_GRACE_SECS);
+    let outcome = shutdown.drain(grace).await;
+    match outcome {
+        DrainOutcome::Drained => info!(target: "audit", "Shut down cleanly after {}", signal.as_str()),
+        DrainOutcome::TimedOut { in_flight } => {
+            warn!(target: "audit", "Shut down after {} with {} operation(s) still in flight", signal.as_str(), in_flight)
+        }
+    }
+
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+    // A monitor stopped by a signal has still produced its output
+    if let Err(err) = finish_output(true) {
+        error!("Command failed: {}", err);
+    }
+    std::process::exit(outcome.exit_code(signal))
+}
+
+async fn execute_balance(
+    args: BalanceArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let address = match (args.address, args.from_file) {
+        (Some(address), _) => resolve_address(&address, config).await?,
+        (None, Some(filename)) => {
+            let file_path = config.wallet_path(&filename);
+            web3wallet_cli::services::CryptoService::load_keystore(&file_path).await?.metadata.address
+        }
+        (None, None) => unreachable!("clap requires an address or --from-file"),
+    };
+    let chain = ChainInfo::resolve(args.chain.as_deref().unwrap_or(&config.network), config)?;
+
+    let mut balances = Vec::new();
+    if args.tokens.is_empty() {
+        let result = RpcClient::from_config(&chain.rpc_url, config)?
+            .call("eth_getBalance", serde_json::json!([address, "latest"]))
+            .await?;
+        let wei = parse_quantity("balance", result.as_str().unwrap_or("0x0"))?;
+        balances.push(TokenBalance::new(None, &chain.currency_symbol, 18, wei));
+    } else {
+        let registry = TokenRegistry::load(&TokenRegistry::default_path(&config.state_path)).await?;
+        let erc20 = Erc20Service::from_config(&chain, config).await?;
+        for query in &args.tokens {
+            let known = known_token(&registry, query, chain.chain_id)?;
+            let token = known.as_ref().map_or(query.as_str(), |t| t.address.as_str());
+            info!("Reading the balance of {} in {} on {}", address, token, chain.name);
+            balances.push(erc20.balance(token, &address, known.as_ref()).await?);
+        }
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Balances of {} on {}", display_address(&address), chain.name);
+            let rows: Vec<Vec<String>> = balances
+                .iter()
+                .map(|b| {
+                    vec![
+                        b.symbol.clone(),
+                        b.amount.clone(),
+                        b.token.as_deref().map_or_else(|| "(native)".to_string(), display_address),
+                    ]
+                })
+                .collect();
+            for line in table_lines(&["ASSET", "BALANCE", "CONTRACT"], &rows) {
+                println!("{}", line);
+            }
+        }
+        OutputFormat::Json => {
+            let mut balances = serde_json::to_value(&balances)?;
+            for balance in balances.as_array_mut().into_iter().flatten() {
+                if let Some(token) = balance["token"].as_str() {
+                    balance["token"] = display_address(token).into();
+                }
+            }
+            print_json(&serde_json::json!({
+                "success": true,
+                "address": display_address(&address),
+                "chain": chain.name,
+                "chain_id": chain.chain_id,
+                "balances": balances
+            }))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_xchain_check(
+    args: XchainCheckArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let address = match (args.address, args.from_file) {
+        (Some(address), _) => resolve_address(&address, config).await?,
+        (None, Some(filename)) => {
+            let file_path = config.wallet_path(&filename);
+            web3wallet_cli::services::CryptoService::load_keystore(&file_path).await?.metadata.address
+        }
+        (None, None) => {
+            return Err(WalletError::UserInput(UserInputError::MissingParameter {
+                parameter: "address source".to_string(),
+                hint: "either --from-file or --address required".to_string(),
+            }));
+        }
+    };
+
+    let targets = if args.chains.is_empty() {
+        ChainInfo::registry(config)
+    } else {
+        args.chains.iter().map(|name| ChainInfo::resolve(name, config)).collect::<WalletResult<Vec<_>>>()?
+    };
+
+    info!("Checking {} on {} chains", address, targets.len());
+    let results = chains::probe_all(targets, &address, config).await?;
+    let active: Vec<_> = results.iter().filter(|r| r.is_active()).map(|r| r.chain.clone()).collect();
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Address: {} (same on every EVM chain)", display_address(&address));
+            println!("{:<12} {:<10} {:<8} {:<26} {:<8}", "CHAIN", "CHAIN ID", "NONCE", "BALANCE (WEI)", "STATUS");
+            println!("{}", "─".repeat(70));
+            for r in &results {
+                let status = match (&r.error, r.is_active()) {
+                    (Some(_), _) => "ERROR",
+                    (None, true) => "ACTIVE",
+                    (None, false) => "unused",
+                };
+                println!("{:<12} {:<10} {:<8} {:<26} {:<8}", r.chain, r.chain_id, r.nonce, r.balance, status);
+                if let Some(err) = &r.error {
+                    println!("             error: {}", err);
+                }
+            }
+            if !active.is_empty() {
+                println!("\n⚠ Activity found on: {}. Make sure you monitor these chains.", active.join(", "));
+            }
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "success": true,
+                "address": display_address(&address),
+                "active_chains": active,
+                "chains": results
+            });
+            print_json(&output)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_activity(
+    args: ActivityArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let address = resolve_address(&args.address, config).await?;
+    let chain = ChainInfo::resolve(&args.chain, config)?;
+    let since = args
+        .since
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc());
+
+    info!("Fetching activity of {} on {}", address, chain.name);
+    let transactions = ExplorerClient::for_chain(config, &chain)?
+        .transactions(&address)
+        .await?;
+    let summary = ActivitySummary::from_transactions(&address, chain.chain_id, since, &transactions);
+
+    let fmt_time = |t: Option<chrono::DateTime<chrono::Utc>>| {
+        t.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_else(|| "-".to_string())
+    };
+
+    if args.csv {
+        println!("address,chain_id,since,tx_count,incoming,outgoing,failed,first_activity,last_activity,total_in_wei,total_out_wei,total_fees_wei");
+        println!("{},{},{},{},{},{},{},{},{},{},{},{}",
+            display_address(&summary.address),
+            summary.chain_id,
+            fmt_time(summary.since),
+            summary.tx_count,
+            summary.incoming,
+            summary.outgoing,
+            summary.failed,
+            fmt_time(summary.first_activity),
+            fmt_time(summary.last_activity),
+            summary.total_in,
+            summary.total_out,
+            summary.total_fees);
+        return Ok(());
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Activity of {} on {}", display_address(&summary.address), chain.name);
+            if let Some(since) = summary.since {
+                println!("Since:          {}", since.format("%Y-%m-%d"));
+            }
+            println!("Transactions:   {} ({} in, {} out, {} failed)", summary.tx_count, summary.incoming, summary.outgoing, summary.failed);
+            println!("First activity: {}", fmt_time(summary.first_activity));
+            println!("Last activity:  {}", fmt_time(summary.last_activity));
+            println!("Total in:       {} wei", summary.total_in);
+            println!("Total out:      {} wei", summary.total_out);
+            println!("Fees paid:      {} wei", summary.total_fees);
         }
-        None=>{
-            info!("Using default config");
-            Ok(WalletConfig::default())
+        OutputFormat::Json => {
+            let mut output = serde_json::to_value(&summary)?;
+            output["success"] = serde_json::json!(true);
+            output["address"] = serde_json::json!(display_address(&summary.address));
+            print_json(&output)?;
         }
     }
+
+    Ok(())
 }
 
-// #[tokio::main]
-// async fn main() -> WalletResult<()> {
-//     let cli: Cli = Cli::parse();
+async fn execute_history(
+    args: HistoryArgs,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let max = web3wallet_cli::config::explorer::MAX_HISTORY_LIMIT;
+    if !(1..=max).contains(&args.limit) {
+        return Err(UserInputError::InvalidParameters {
+            parameter: "--limit".to_string(),
+            value: args.limit.to_string(),
+            expected: format!("a number from 1 to {}", max),
+        }
+        .into());
+    }
 
-//     init_logging(cli.verbose);
+    let address = resolve_address(&args.address, config).await?;
+    let chain = ChainInfo::resolve(args.chain.as_deref().unwrap_or(&config.network), config)?;
 
-//     let config = load_config(cli.config).await?;
-    
-//     if cli.verbose {
-//         info!("Starting Web3 Wallet CLI v{}", env!("CARGO_PKG_VERSION"));
-//     }
-//     let manager = WalletManager::new(config);
-// }
+    info!("Fetching the last {} transactions of {} on {}", args.limit, address, chain.name);
+    let transactions = ExplorerClient::for_chain(config, &chain)?
+        .recent_transactions(&address, args.limit)
+        .await?;
 
-async fn execute_create(args: CreateArgs,
-                        config: &WalletConfig,
-                        output: OutputFormat) -> WalletResult<()> {
-        let manager = WalletManager::new(config.clone());
+    let direction = |tx: &ExplorerTransaction| {
+        let from = web3wallet_cli::utils::addresses_equal(&tx.from, &address);
+        let to = web3wallet_cli::utils::addresses_equal(&tx.to, &address);
+        match (from, to) {
+            (true, true) => "self",
+            (true, false) => "out",
+            _ => "in",
+        }
+    };
+    let status = |tx: &ExplorerTransaction| {
+        if tx.failed() { "failed" } else { "success" }
+    };
 
-        info!("Creating a new wallet with {} words on {} network", args.words, args.network);
-        let wallet = manager.create_wallet_with_network(args.words, &args.network).await?;
+    match output {
+        OutputFormat::Table => {
+            println!("\n Last {} transactions of {} on {}", transactions.len(), display_address(&address), chain.name);
+            let rows: Vec<Vec<String>> = transactions
+                .iter()
+                .map(|tx| {
+                    let counterparty = match direction(tx) {
+                        "in" => display_address(&tx.from),
+                        _ if tx.to.is_empty() => "(contract creation)".to_string(),
+                        _ => display_address(&tx.to),
+                    };
+                    vec![
+                        tx.timestamp().map_or_else(|| "-".to_string(), |t| t.format("%Y-%m-%d %H:%M").to_string()),
+                        tx.hash.clone(),
+                        direction(tx).to_string(),
+                        counterparty,
+                        format!("{} {}", native_amount(tx.value_wei()), chain.currency_symbol),
+                        format!("{}/{}", tx.gas_used, tx.gas),
+                        format!("{} {}", native_amount(tx.fee_wei()), chain.currency_symbol),
+                        status(tx).to_string(),
+                    ]
+                })
+                .collect();
+            let headers = ["TIME (UTC)", "HASH", "DIR", "COUNTERPARTY", "VALUE", "GAS USED", "FEE", "STATUS"];
+            for line in table_lines(&headers, &rows) {
+                println!("{}", line);
+            }
+        }
+        OutputFormat::Json => {
+            let transactions: Vec<serde_json::Value> = transactions
+                .iter()
+                .map(|tx| {
+                    serde_json::json!({
+                        "hash": tx.hash,
+                        "block_number": tx.block_number,
+                        "timestamp": tx.timestamp(),
+                        "from": display_address(&tx.from),
+                        "to": (!tx.to.is_empty()).then(|| display_address(&tx.to)),
+                        "direction": direction(tx),
+                        "value": native_amount(tx.value_wei()),
+                        "value_wei": tx.value,
+                        "gas": tx.gas,
+                        "gas_used": tx.gas_used,
+                        "gas_price": tx.gas_price,
+                        "fee": native_amount(tx.fee_wei()),
+                        "status": status(tx)
+                    })
+                })
+                .collect();
+            print_json(&serde_json::json!({
+                "success": true,
+                "address": display_address(&address),
+                "chain": chain.name,
+                "chain_id": chain.chain_id,
+                "currency": chain.currency_symbol,
+                "transactions": transactions
+            }))?;
+        }
+    }
 
-        match output{
-            OutputFormat::Table=>{
-                println!("New wallet created:");
-                println!("Address: {}", wallet.address());
-                println!("Mnemonic: {}", wallet.mnemonic());
-                println!("Network: {}", wallet.network());
+    Ok(())
+}
+
+async fn execute_tokens(
+    command: TokensCommand,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let mut registry = TokenRegistry::load(&TokenRegistry::default_path(&config.state_path)).await?;
+
+    let chain_id = |name: &str| ChainInfo::resolve(name, config).map(|c| c.chain_id);
+
+    match command {
+        TokensCommand::Add { symbol, address, decimals, chain, name } => {
+            let token = Token::new(chain_id(&chain)?, &address, &symbol, name, decimals)?;
+            let added = registry.add(token.clone());
+            registry.save().await?;
+            match output {
+                OutputFormat::Table => println!("{} {} ({}) on {}",
+                    if added { "Added" } else { "Updated" }, token.symbol, display_address(&token.address), chain),
+                OutputFormat::Json => print_json(&serde_json::json!({ "success": true, "added": added, "token": token_json(&token)? }))?,
             }
-            OutputFormat::Json=>{
-                let output = serde_json::json!({
+        }
+        TokensCommand::Remove { token, chain } => {
+            let removed = registry.remove(&token, chain_id(&chain)?)?;
+            registry.save().await?;
+            match output {
+                OutputFormat::Table => println!("Removed {} token(s)", removed.len()),
+                OutputFormat::Json => print_json(&serde_json::json!({
                     "success": true,
-                    "address": wallet.address(),
-                    "network": wallet.network(),
-                    "mnemonic": wallet.mnemonic(),
-                    "derivation_path": wallet.derivation_path(),
-                    "created_at": wallet.created_at()
-                });
-                println!("{}", serde_json::to_string_pretty(&output)
-                    .map_err(|e| WalletError::Json(e.to_string()))?);
+                    "removed": removed.iter().map(token_json).collect::<WalletResult<Vec<_>>>()?
+                }))?,
+            }
+        }
+        TokensCommand::List { chain } => {
+            let filter = chain.as_deref().map(chain_id).transpose()?;
+            let tokens = registry.list(filter);
+            match output {
+                OutputFormat::Table => {
+                    if tokens.is_empty() {
+                        println!("No tokens registered. Add one with `tokens add` or `tokens seed`.");
+                    } else {
+                        println!("{:<10} {:<10} {:<44} {:<8}", "CHAIN ID", "SYMBOL", "ADDRESS", "DECIMALS");
+                        println!("{}", "─".repeat(76));
+                        for t in tokens {
+                            println!("{:<10} {:<10} {:<44} {:<8}", t.chain_id, t.symbol, display_address(&t.address), t.decimals);
+                        }
+                    }
+                }
+                OutputFormat::Json => print_json(&serde_json::json!({
+                    "success": true,
+                    "count": tokens.len(),
+                    "tokens": tokens.iter().copied().map(token_json).collect::<WalletResult<Vec<_>>>()?
+                }))?,
+            }
+        }
+        TokensCommand::Seed { url, chain } => {
+            let filter = chain.as_deref().map(chain_id).transpose()?;
+            info!("Fetching token list from {}", url);
+            let list = TokenListService::fetch(&url).await?;
+            let list_name = list.name.clone();
+            let added = TokenListService::seed(&mut registry, list, filter);
+            registry.save().await?;
+            match output {
+                OutputFormat::Table => println!("Imported {} new tokens from \"{}\"", added, list_name),
+                OutputFormat::Json => print_json(&serde_json::json!({
+                    "success": true,
+                    "list": list_name,
+                    "added": added
+                }))?,
             }
         }
+    }
+
+    Ok(())
+}
+
+/// Hex addresses pass through validated, ENS names are resolved on mainnet (cached)
+async fn resolve_address(input: &str, config: &WalletConfig) -> WalletResult<String> {
+    if !ens::is_ens_name(input) {
+        web3wallet_cli::utils::validate_ethereum_address(input)?;
+        return Ok(input.to_string());
+    }
 
-        if let Some(filename) = args.save {
-            let password = get_password("Enter a password to encrypt the wallet: ")?;
-            let confirm_password = get_password("Confirm password: ")?;
+    let address = EnsResolver::from_config(config, true).await?.resolve(input).await?;
+    info!("Resolved {} to {}", input, address);
+    Ok(address)
+}
+
+async fn execute_ens(
+    command: EnsCommand,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    match command {
+        EnsCommand::Resolve { name, no_cache } => {
+            if !ens::is_ens_name(&name) {
+                return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                    parameter: "name".to_string(),
+                    value: name,
+                    expected: "dot separated ENS name, e.g. vitalik.eth".to_string(),
+                }));
+            }
+            let address = EnsResolver::from_config(config, !no_cache).await?.resolve(&name).await?;
 
-            if password != confirm_password {
-                return Err(WalletError::UserInput(UserInputError::PasswordMismatch));
+            match output {
+                OutputFormat::Table => {
+                    println!("{} -> {}", ens::normalize(&name), display_address(&address));
+                }
+                OutputFormat::Json => {
+                    print_json(&serde_json::json!({
+                        "success": true,
+                        "name": ens::normalize(&name),
+                        "address": display_address(&address),
+                    }))?;
+                }
             }
+        }
+        EnsCommand::Reverse { address, no_cache } => {
+            web3wallet_cli::utils::validate_ethereum_address(&address)?;
+            let name = EnsResolver::from_config(config, !no_cache).await?.reverse(&address).await?;
 
-            let wallet_dir = &config.wallets_path;
-            tokio::fs::create_dir_all(wallet_dir).await.map_err(|e|{
-                WalletError::Filesystem(FilesystemError::DirectoryNotAccessible{
-                    path: wallet_dir.display().to_string(),
-                    details: e.to_string()
-                })
-            })?;
+            match output {
+                OutputFormat::Table => match &name {
+                    Some(name) => println!("{} -> {}", display_address(&address), name),
+                    None => println!("{} has no verified primary name", display_address(&address)),
+                },
+                OutputFormat::Json => {
+                    print_json(&serde_json::json!({
+                        "success": true,
+                        "address": display_address(&address),
+                        "name": name,
+                    }))?;
+                }
+            }
+        }
+    }
 
-            let file_path = wallet_dir.join(format!("{}.json", filename));
-            manager.save_wallet(&wallet, &file_path, &password).await?;
+    Ok(())
+}
 
-            print!("\n Wallet saved to： {}", file_path.display());
+async fn execute_wallet(
+    command: WalletCommand,
+    verbose: bool,
+    config_path: Option<PathBuf>,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    match command {
+        WalletCommand::Usage { wallet } => execute_wallet_usage(wallet, config, output).await,
+        WalletCommand::Version => execute_wallet_version(verbose, config_path, config, output),
+        WalletCommand::History(HistoryCommand::Export { format, period, fiat, price_source, rates_file, out }) => {
+            let mut config = config.clone();
+            if let Some(path) = rates_file {
+                config.price_source = PriceSource::File;
+                config.price_rates_file = Some(path);
+            }
+            if let Some(source) = price_source {
+                config.price_source = source;
+            }
+            execute_history_export(format, period, fiat, out, &config, output).await
+        }
+        WalletCommand::Permit2(Permit2Command::Single { common, token, amount, nonce }) => {
+            let token = parse_address("token", &token)?;
+            let amount = permit2::parse_amount(&amount)?;
+            execute_permit2(common, vec![(token, amount, nonce)], false, config, output).await
+        }
+        WalletCommand::Permit2(Permit2Command::Batch { common, permits }) => {
+            let permits = permits.iter().map(|spec| permit2::parse_permit_spec(spec)).collect::<WalletResult<Vec<_>>>()?;
+            execute_permit2(common, permits, true, config, output).await
         }
+        WalletCommand::Delegate { from_file, derive_index, export_to } => {
+            execute_wallet_delegate(&from_file, derive_index, &export_to, config, output).await
+        }
+        WalletCommand::Score { from_file } => execute_wallet_score(&from_file, config, output).await,
+        WalletCommand::Fixtures { mnemonic, passphrase, count, out } => {
+            execute_wallet_fixtures(&mnemonic, passphrase.as_deref(), count, out, config, output).await
+        }
+    }
+}
 
-        Ok(())
+async fn execute_wallet_score(
+    from_file: &str,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let config = &wallet_config(config, from_file).await?;
+    let file_path = config.wallet_path(from_file);
+
+    let report = health::assess(config, &file_path, from_file).await?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Wallet health: {} ({}/100)", report.grade, report.score);
+            println!("Address:  {}", display_address(&report.address));
+            println!("File:     {}", report.file.display());
+            println!();
+            let rows: Vec<Vec<String>> = report
+                .checks
+                .iter()
+                .map(|c| vec![c.name.to_string(), c.status.as_str().to_string(), c.details.clone()])
+                .collect();
+            for line in table_lines(&["CHECK", "STATUS", "DETAILS"], &rows) {
+                println!("{}", line);
+            }
+            let remedies = report.remedies();
+            if !remedies.is_empty() {
+                println!("\nTo improve:");
+                for (i, remedy) in remedies.iter().enumerate() {
+                    println!("  {}. {}", i + 1, remedy);
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let mut value = outcome_json(&report)?;
+            value["address"] = display_address(&report.address).into();
+            value["remedies"] = serde_json::json!(report.remedies());
+            print_json(&value)?;
+        }
     }
 
-async fn excute_import(args: ImportArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()>{
-    // Create a temporary config with the specified network
-    let mut temp_config = config.clone();
-    temp_config.network = args.network.clone();
-    let manager = WalletManager::new(temp_config);
+    Ok(())
+}
 
-    let wallet = if let Some(mnemonic) = args.mnemonic{
-        info!("Importing wallet from mnemonic");
-        manager.import_from_mnemoic(&mnemonic).await?
-    } else if let Some(private) = args.private_key{
-        info!("Importing wallet from private key...");
-        manager.import_from_private_key(&private).await?
-    } else{
-        // Check if we're in a testing environment that doesn't support interactive input
-        // or if both parameters are missing, return error immediately
-        return Err(WalletError::UserInput(UserInputError::MissingParameter {
-            parameter: "import source".to_string(),
-            hint: "either --mnemonic or --private-key required".to_string(),
-        }));
+async fn execute_wallet_delegate(
+    from_file: &str,
+    derive_index: u32,
+    export_to: &str,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let file_path = config.wallet_path(from_file);
+    let out = config.save_path(export_to);
+
+    manager.check_can_sign(&file_path).await?;
+    let wallet = unlock_wallet(&manager, &file_path, config).await?;
+
+    let password = get_password(&tr("prompt-delegate-password"))?;
+    let confirm = get_password(&tr("prompt-confirm-password"))?;
+    if password != confirm {
+        return Err(WalletError::UserInput(UserInputError::PasswordMismatch));
+    }
+
+    let outcome = commands::delegate(&manager, &wallet, derive_index, &out, &password).await?;
+    record_usage(config, wallet.address(), &[UsageKind::Unlock, UsageKind::Export]).await;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Delegate keystore written:");
+            println!("Address:  {}", outcome.address);
+            println!("Path:     {}", outcome.derivation_path);
+            println!("From:     {}", outcome.parent_address);
+            println!("File:     {}", outcome.file.display());
+            println!("\nIt holds this one key and no mnemonic; give the bot its password, never the main wallet's.");
+        }
+        OutputFormat::Json => {
+            print_json(&outcome_json(&outcome)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_permit2(
+    args: Permit2Args,
+    permits: Vec<(ethers::types::Address, ethers::types::U256, Option<u64>)>,
+    batch: bool,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let config = &wallet_config(config, &args.from_file).await?;
+    let chain = ChainInfo::resolve(args.chain.as_deref().unwrap_or(&config.network), config)?;
+    let spender = parse_address("spender", &resolve_address(&args.spender, config).await?)?;
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    let expiration = permit2::check_timestamp(
+        "expiration",
+        args.expiration.unwrap_or(now + web3wallet_cli::config::permit2::DEFAULT_EXPIRATION_SECS),
+    )?;
+    let sig_deadline = permit2::check_timestamp(
+        "sig_deadline",
+        args.sig_deadline.unwrap_or(now + web3wallet_cli::config::permit2::DEFAULT_SIG_DEADLINE_SECS),
+    )?;
+
+    let manager = WalletManager::new(config.clone());
+    let file_path = config.wallet_path(&args.from_file);
+
+    check_signing_chain(&args.from_file, chain.chain_id, args.force, config).await?;
+    let wallet = unlock_wallet(&manager, &file_path, config).await?;
+    let owner = parse_address("from", wallet.address())?;
+
+    let mut details = Vec::with_capacity(permits.len());
+    for (token, amount, nonce) in permits {
+        let nonce = match nonce {
+            Some(nonce) => nonce,
+            None if config.offline => {
+                return Err(UserInputError::MissingParameter {
+                    parameter: "nonce".to_string(),
+                    hint: "offline the Permit2 nonce cannot be read from the chain, pass it explicitly".to_string(),
+                }
+                .into());
+            }
+            None => {
+                info!("Reading the Permit2 nonce of {} on {}", display_address(&format!("{:?}", token)), chain.name);
+                let client = RpcClient::from_config(&chain.rpc_url, config)?;
+                Permit2Service::nonce(&client, owner, token, spender).await?
+            }
+        };
+        details.push(PermitDetails { token, amount, expiration, nonce });
+    }
+
+    let permit = Permit { chain_id: chain.chain_id, spender, sig_deadline, details, batch };
+    let signed = Permit2Service::sign(&wallet, &permit)?;
+    record_usage(config, wallet.address(), &[UsageKind::Unlock, UsageKind::Sign]).await;
+    let document = canonical_json::digest(&signed.typed_data)?;
+
+    if let Some(path) = &args.out {
+        tokio::fs::write(path, format!("{}\n", serde_json::to_string_pretty(&signed.typed_data)?)).await?;
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n Permit2 {} signed:", permit.primary_type());
+            println!("Signer:    {}", signed.signer);
+            println!("Spender:   {}", signed.spender);
+            for d in &permit.details {
+                println!("Token:     {} amount {} nonce {}", display_address(&format!("{:?}", d.token)), d.amount, d.nonce);
+            }
+            println!("Hash:      {}", signed.hash);
+            println!("Signature: {}", signed.signature);
+            println!("Document:  {}", document);
+            if let Some(path) = &args.out {
+                println!("Typed data written to {}", path.display());
+            }
+        }
+        OutputFormat::Json => {
+            print_json(&serde_json::json!({
+                "success": true,
+                "signer": signed.signer,
+                "spender": signed.spender,
+                "hash": signed.hash,
+                "signature": signed.signature,
+                "document_hash": document,
+                "typed_data": signed.typed_data,
+            }))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_wallet_fixtures(
+    mnemonic: &str,
+    passphrase: Option<&str>,
+    count: u32,
+    out: Option<PathBuf>,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let fixtures = commands::fixtures(&manager, mnemonic, passphrase, count).await?;
+    let document = format!("{}\n", serde_json::to_string_pretty(&fixtures)?);
+
+    // Without --out the fixtures are the output, so nothing else may be printed
+    let Some(path) = out else {
+        print!("{}", document);
+        return Ok(());
     };
+    tokio::fs::write(&path, document).await?;
 
     match output {
         OutputFormat::Table => {
-            println!("\n Wallet imported successfully!");
-            println!("Address:  {}", wallet.address());
-            println!("Network:  {}", wallet.network());
-            if wallet.has_mnemonic() {
-                println!("Type:     HD Wallet (BIP44)");
-            } else {
-                println!("Type:     Private Key Only");
+            println!("Wrote {} {} fixture account(s) to {}", fixtures.accounts.len(), fixtures.name, path.display());
+        }
+        OutputFormat::Json => {
+            print_json(&serde_json::json!({
+                "success": true,
+                "file": path.display().to_string(),
+                "name": fixtures.name,
+                "accounts": fixtures.accounts.len(),
+            }))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_history_export(
+    format: HistoryFormat,
+    period: Option<Period>,
+    fiat: Option<String>,
+    out: Option<PathBuf>,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let log = HistoryLog::load(&HistoryLog::default_path(&config.state_path)).await?;
+    let entries = log.in_period(period.as_ref());
+
+    let fiat_values = match fiat {
+        Some(currency) => {
+            let provider = prices::from_config(config).await?;
+            Some(fiat_prices(provider.as_ref(), &entries, &currency).await?)
+        }
+        None => None,
+    };
+
+    let document = match format {
+        HistoryFormat::Csv => ledger_csv(&entries, fiat_values.as_ref()),
+    };
+
+    // Without --out the ledger itself is the output, so nothing else may be printed
+    let Some(path) = out else {
+        print!("{}", document);
+        return Ok(());
+    };
+    tokio::fs::write(&path, document).await?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("Exported {} transaction(s) to {}", entries.len(), path.display());
+        }
+        OutputFormat::Json => {
+            print_json(&serde_json::json!({
+                "success": true,
+                "file": path.display().to_string(),
+                "transactions": entries.len(),
+                "period": period.map(|p| serde_json::json!({ "start": p.start, "end": p.end })),
+                "fiat": fiat_values.map(|p| p.currency),
+            }))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn execute_wallet_version(
+    verbose: bool,
+    config_path: Option<PathBuf>,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let build = BuildInfo::current();
+    let default_path = WalletConfig::default_config_path();
+    let config_file = config_path.or_else(|| default_path.exists().then_some(default_path));
+
+    match output {
+        OutputFormat::Table => {
+            println!("web3wallet {} ({})", build.version, build.git_commit);
+            if verbose {
+                println!("Target:      {} ({})", build.target, build.profile);
+                println!("Features:    {}", if build.features.is_empty() { "none".to_string() } else { build.features.join(", ") });
+                println!("Crypto:      {}", build.crypto.iter().map(|c| format!("{} {}", c.name, c.version)).collect::<Vec<_>>().join(", "));
+                println!("Config file: {}", config_file.as_ref().map_or("none, built-in defaults".to_string(), |p| p.display().to_string()));
+                println!("Wallets:     {}", config.wallets_path.display());
+                println!("State:       {}", config.state_path.display());
+                println!("Network:     {}", config.network);
+                println!("OS:          {} {}", std::env::consts::OS, std::env::consts::ARCH);
+            }
+        }
+        OutputFormat::Json => {
+            let mut value = serde_json::json!({
+                "success": true,
+                "version": build.version,
+                "git_commit": build.git_commit,
+            });
+            if verbose {
+                value["build"] = serde_json::to_value(&build)?;
+                value["config_file"] = config_file.map(|p| p.display().to_string()).into();
+                value["wallets_path"] = config.wallets_path.display().to_string().into();
+                value["state_path"] = config.state_path.display().to_string().into();
+                value["network"] = config.network.clone().into();
+                value["os"] = format!("{} {}", std::env::consts::OS, std::env::consts::ARCH).into();
+            }
+            print_json(&value)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn execute_wallet_usage(
+    wallet: Option<String>,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let log = UsageLog::load(&UsageLog::default_path(&config.state_path)).await?;
+    let address = match wallet {
+        Some(wallet) if wallet.starts_with("0x") && wallet.len() == 42 => {
+            web3wallet_cli::utils::validate_ethereum_address(&wallet)?;
+            Some(wallet)
+        }
+        Some(wallet) => {
+            let file_path = config.wallet_path(&wallet);
+            Some(commands::inspect(&file_path).await?.address)
+        }
+        None => None,
+    };
+
+    let wallets: Vec<_> = match &address {
+        Some(address) => log.get(address).map(|usage| (web3wallet_cli::utils::normalize_address(address), usage)).into_iter().collect(),
+        None => log.wallets.iter().map(|(address, usage)| (address.clone(), usage)).collect(),
+    };
+    let now = chrono::Utc::now();
+    let window = chrono::Duration::days(web3wallet_cli::config::usage::DORMANT_DAYS);
+
+    match output {
+        OutputFormat::Table => {
+            if !config.track_usage {
+                println!("Usage tracking is off (track_usage = false), counts may be stale");
+            }
+            if wallets.is_empty() {
+                println!("No usage recorded{}", address.map(|a| format!(" for {}", display_address(&a))).unwrap_or_default());
+                return Ok(());
+            }
+            println!("\n{:<44} {:>8} {:>8} {:>8}  {:<20}", "ADDRESS", "UNLOCKS", "SIGNS", "EXPORTS", "LAST USED");
+            println!("{}", "─".repeat(94));
+            for (address, usage) in &wallets {
+                println!(
+                    "{:<44} {:>8} {:>8} {:>8}  {:<20}",
+                    display_address(address),
+                    usage.unlocks,
+                    usage.signs,
+                    usage.exports,
+                    usage.last_used.map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string()).unwrap_or_default()
+                );
+                for flagged in usage.recent_anomalies(window, now) {
+                    println!("  ! {}: {}", flagged.at.format("%Y-%m-%d %H:%M UTC"), flagged.anomaly);
+                }
             }
         }
         OutputFormat::Json => {
-            let output = serde_json::json!({
+            let wallets: Vec<_> = wallets
+                .iter()
+                .map(|(address, usage)| serde_json::json!({
+                    "address": display_address(address),
+                    "unlocks": usage.unlocks,
+                    "signs": usage.signs,
+                    "exports": usage.exports,
+                    "first_used": usage.first_used,
+                    "last_used": usage.last_used,
+                    "sign_hours": usage.sign_hours,
+                    "anomalies": usage.recent_anomalies(window, now).collect::<Vec<_>>(),
+                }))
+                .collect();
+            print_json(&serde_json::json!({
                 "success": true,
-                "address": wallet.address(),
-                "network": wallet.network(),
-                "has_mnemonic": wallet.has_mnemonic(),
-                "derivation_path": wallet.derivation_path(),
-                "created_at": wallet.created_at()
-            });
-            println!("{}", serde_json::to_string_pretty(&output)
-                .map_err(|e| WalletError::Json(e.to_string()))?);
+                "tracking": config.track_usage,
+                "wallets": wallets,
+            }))?;
         }
     }
 
-    if let Some(filename) = args.save{
-        let password = get_password("Enter the password to encrypt wallet..")?;
-        let confirm = get_password("Confirm password....")?;
+    Ok(())
+}
 
-        if password != confirm {
-            return Err(WalletError::UserInput(
-                UserInputError::PasswordMismatch
-            ));
-        }
+async fn execute_config(
+    command: ConfigCommand,
+    config_path: Option<PathBuf>,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    let default_path = WalletConfig::default_config_path();
 
-        let wallet_dir = &config.wallets_path;
-        tokio::fs::create_dir_all(wallet_dir).await.map_err(|e|{
-            WalletError::Filesystem(
-                FilesystemError::DirectoryNotAccessible { 
-                    path: wallet_dir.display().to_string(), 
-                    details: e.to_string() 
-                }
-            )
-        })?;
+    match command {
+        ConfigCommand::Init { path, force } => {
+            let path = path.or(config_path).unwrap_or(default_path);
+            if path.exists() && !force {
+                return Err(WalletError::Filesystem(FilesystemError::FileExists {
+                    path: path.display().to_string(),
+                    suggestion: "pass --force to overwrite it".to_string(),
+                }));
+            }
+            if let Some(dir) = path.parent() {
+                tokio::fs::create_dir_all(dir).await?;
+            }
+            tokio::fs::write(&path, WalletConfig::template()).await?;
 
-        let file_path = if filename.ends_with(".json") {
-            wallet_dir.join(filename)
-        } else {
-            wallet_dir.join(format!("{}.json", filename))
-        };
-        manager.save_wallet(&wallet, &file_path, &password).await?;
+            match output {
+                OutputFormat::Table => println!("Wrote default config to {}", path.display()),
+                OutputFormat::Json => print_json(&serde_json::json!({ "success": true, "file": path.display().to_string() }))?,
+            }
+        }
+        ConfigCommand::Show => {
+            let source = config_path.or_else(|| default_path.exists().then_some(default_path));
+            match output {
+                OutputFormat::Table => {
+                    match &source {
+                        Some(path) => println!("# Loaded from {}", path.display()),
+                        None => println!("# Built-in defaults, no config file found"),
+                    }
+                    print!("{}", config.to_toml()?);
+                }
+                OutputFormat::Json => print_json(&serde_json::json!({
+                    "success": true,
+                    "source": source.map(|p| p.display().to_string()),
+                    "config": config.to_json()?
+                }))?,
+            }
+        }
+        ConfigCommand::Validate { path } => {
+            let path = path.or(config_path).unwrap_or(default_path);
+            WalletConfig::load(&path).await?;
 
-        println!("\n Wallet saved to: {}", file_path.display());
+            match output {
+                OutputFormat::Table => println!("{} is valid", path.display()),
+                OutputFormat::Json => print_json(&serde_json::json!({ "success": true, "file": path.display().to_string(), "valid": true }))?,
+            }
+        }
     }
 
     Ok(())
 }
 
-
-async fn excute_load(
-    args: LoadArgs,
+async fn execute_network(
+    command: NetworkCommand,
+    config_path: Option<PathBuf>,
     config: &WalletConfig,
     output: OutputFormat
-) -> WalletResult<()>{
-    let manager = WalletManager::new(config.clone());
+) -> WalletResult<()> {
+    let path = config_path.unwrap_or_else(WalletConfig::default_config_path);
 
-    let file_path = if args.filename.contains('/') || args.filename.contains('\\'){
-        PathBuf::from(&args.filename)
-    }else{
-        config.wallets_path.join(&args.filename)
-    };
+    match command {
+        NetworkCommand::Add { name, chain_id, rpc_url, explorer_url, symbol, wrapped_native, force } => {
+            if ChainInfo::by_name(&name).is_ok() {
+                return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                    parameter: "name".to_string(),
+                    value: name,
+                    expected: "a name that is not built in; change a built-in endpoint under [rpc.endpoints]".to_string(),
+                }));
+            }
+            if config.networks.contains_key(&name) && !force {
+                return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                    parameter: "name".to_string(),
+                    value: name,
+                    expected: "a network not defined yet, or --force to replace it".to_string(),
+                }));
+            }
 
-    info!("Loading wallet from: {}", file_path.display());
+            let network = web3wallet_cli::NetworkSection {
+                chain_id,
+                rpc_url,
+                explorer_url,
+                currency_symbol: symbol,
+                wrapped_native,
+            };
+            WalletConfig::write_network(&path, &name, &network).await?;
 
-    if args.address_only{
-        let keystore = web3wallet_cli::services::CryptoService::load_keystore(&file_path).await?;
-        match output {
-            OutputFormat::Table => {
-                println!("\n📁 Wallet file: {}", file_path.display());
-                println!("Address:  {}", keystore.metadata.address);
-                println!("Network:  {}", keystore.metadata.network);
-                println!("Created:  {}", keystore.metadata.created_at);
-                if let Some(alias) = &keystore.metadata.alias {
-                    println!("Alias:    {}", alias);
+            match output {
+                OutputFormat::Table => println!("Added network {} (chain id {}) to {}", name, chain_id, path.display()),
+                OutputFormat::Json => print_json(&serde_json::json!({
+                    "success": true,
+                    "name": name,
+                    "chain_id": chain_id,
+                    "file": path.display().to_string()
+                }))?,
+            }
+        }
+        NetworkCommand::List => {
+            let registry = ChainInfo::registry(config);
+            match output {
+                OutputFormat::Table => {
+                    let rows: Vec<Vec<String>> = registry
+                        .iter()
+                        .map(|chain| vec![
+                            chain.name.clone(),
+                            chain.chain_id.to_string(),
+                            chain.currency_symbol.clone(),
+                            chain.rpc_url.clone(),
+                            if chain.custom { "config" } else { "built-in" }.to_string(),
+                        ])
+                        .collect();
+                    println!();
+                    for line in table_lines(&["NAME", "CHAIN ID", "SYMBOL", "RPC URL", "SOURCE"], &rows) {
+                        println!("{}", line);
+                    }
                 }
+                OutputFormat::Json => print_json(&serde_json::json!({ "success": true, "networks": registry }))?,
             }
-            OutputFormat::Json => {
-                let output = serde_json::json!({
-                    "file": file_path.display().to_string(),
-                    "address": keystore.metadata.address,
-                    "network": keystore.metadata.network,
-                    "created_at": keystore.metadata.created_at,
-                    "alias": keystore.metadata.alias
-                });
-                println!("{}", serde_json::to_string_pretty(&output)
-                    .map_err(|e| WalletError::Json(e.to_string()))?);
+        }
+        NetworkCommand::Remove { name } => {
+            if ChainInfo::by_name(&name).is_ok() {
+                return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                    parameter: "name".to_string(),
+                    value: name,
+                    expected: "a network defined in the config file, built-in networks cannot be removed".to_string(),
+                }));
+            }
+            if !WalletConfig::remove_network(&path, &name).await? {
+                return Err(WalletError::UserInput(UserInputError::InvalidNetwork {
+                    network: name,
+                    supported: config.networks.keys().cloned().collect(),
+                }));
+            }
+
+            match output {
+                OutputFormat::Table => println!("Removed network {} from {}", name, path.display()),
+                OutputFormat::Json => print_json(&serde_json::json!({
+                    "success": true,
+                    "name": name,
+                    "file": path.display().to_string()
+                }))?,
             }
         }
-        return Ok(());
     }
-    
 
-    let password = get_password("Enter a password to encrypt the wallet: ")?;
-    let wallet = manager.load_wallet(&file_path, &password).await?;
-    
+    Ok(())
+}
 
-    // Display wallet information
-    match output {
-        OutputFormat::Table => {
-            println!("\n Wallet loaded successfully!");
-            println!("Address:  {}", wallet.address());
-            println!("Network:  {}", wallet.network());
-            if wallet.has_mnemonic() {
-                println!("Type:     HD Wallet (BIP44)");
-            } else {
-                println!("Type:     Private Key Only");
-            }
-            if let Some(alias) = wallet.alias() {
-                println!("Alias:    {}", alias);
+async fn execute_approval(
+    command: ApprovalCommand,
+    config: &WalletConfig,
+    output: OutputFormat
+) -> WalletResult<()> {
+    match command {
+        ApprovalCommand::Keygen { out } => {
+            let (secret, public_key) = ApprovalService::generate_key();
+            ApprovalService::save_key(&out, &secret).await?;
+            match output {
+                OutputFormat::Table => {
+                    println!("Companion key written to {}", out.display());
+                    println!("Public key: {}", public_key);
+                    println!("Set it as `public_key` under [approval] in the signer's config file");
+                }
+                OutputFormat::Json => {
+                    print_json(&serde_json::json!({
+                        "success": true,
+                        "file": out.display().to_string(),
+                        "public_key": public_key,
+                    }))?;
+                }
             }
-            println!("Created:  {}", wallet.created_at().format("%Y-%m-%d %H:%M:%S UTC"));
-        }
-        OutputFormat::Json => {
-            let output = serde_json::json!({
-                "success": true,
-                "address": wallet.address(),
-                "network": wallet.network(),
-                "has_mnemonic": wallet.has_mnemonic(),
-                "derivation_path": wallet.derivation_path(),
-                "alias": wallet.alias(),
-                "created_at": wallet.created_at()
-            });
-            println!("{}", serde_json::to_string_pretty(&output)
-                .map_err(|e| WalletError::Json(e.to_string()))?);
         }
-    }
+        ApprovalCommand::Approve { request, key, from_file, signer, hd_path, yes } => {
+            let request = ApprovalRequest::open(&request, &ApprovalService::load_key(&key).await?)?;
+            let tx = &request.tx;
+            let currency = ChainInfo::by_chain_id(tx.chain_id, config)
+                .map_or(web3wallet_cli::config::chains::DEFAULT_CURRENCY_SYMBOL.to_string(), |c| c.currency_symbol);
+            let data_len = tx.data.as_deref().map_or(0, |d| d.trim_start_matches("0x").len() / 2);
 
-    // Derive specific address if requested
-    if let Some(index) = args.derive{
-        if !wallet.has_mnemonic(){
-            return Err(WalletError::UserInput(
-                UserInputError::InvalidParameters { 
-                    parameter: "derive".to_string(), 
-                    value: index.to_string(), 
-                    expected: "HD wallet with mnemonic".to_string() 
+            let value = format!("{} {}", display_amount(&request.value()?.to_string(), 18), currency);
+
+            // JSON callers are scripts that already decided, the summary is for people
+            if matches!(output, OutputFormat::Table) {
+                println!("\n Approval request:");
+                println!("From:     {}", display_address(&request.from));
+                println!("Chain:    {} ({})", request.chain, tx.chain_id);
+                println!("To:       {}", tx.to.as_deref().map_or_else(|| "contract creation".to_string(), display_address));
+                println!("Value:    {}", value);
+                println!("Nonce:    {}", tx.nonce);
+                if data_len > 0 {
+                    println!("Data:     {} bytes", data_len);
                 }
-            ))
-        }
- 
-        let derived = wallet.derive_address(index)?;
-        match output {
+                if let Some(note) = &request.note {
+                    println!("Note:     {}", note);
+                }
+                println!("Asked:    {}", request.requested_at.format("%Y-%m-%d %H:%M:%S UTC"));
+            }
+
+            if !yes {
+                let answer = prompt_line("Approve this transaction? [y/N] ")?;
+                if !matches!(answer.to_lowercase().as_str(), "y" | "yes") {
+                    return Err(UserInputError::InvalidParameters {
+                        parameter: "confirmation".to_string(),
+                        value: answer,
+                        expected: "y to approve".to_string(),
+                    }
+                    .into());
+                }
+            }
+
+            let signer = load_signer(signer, from_file.as_deref(), hd_path.as_deref(), config).await?;
+            let approver = signer.address().await?;
+            if web3wallet_cli::utils::addresses_equal(&approver, &request.from) {
+                return Err(AuthenticationError::ApprovalRejected {
+                    reason: "the sending wallet cannot approve its own transaction".to_string(),
+                }
+                .into());
+            }
+            let expires_at = chrono::Utc::now() + chrono::Duration::seconds(config.approval.token_ttl().as_secs() as i64);
+            let expires = expires_at.timestamp().max(0) as u64;
+            let signature = signer.sign_message(request.message(expires)?.as_bytes()).await?;
+            record_usage(config, &approver, &[UsageKind::Sign]).await;
+            let token = ApprovalService::token(expires, &signature);
+
+            match output {
                 OutputFormat::Table => {
-                    println!("\n Derived address [{}]:", index);
-                    println!("Address:  {}", derived.address());
-                    println!("Path:     {}", derived.derivation_path());
+                    println!("\n Approved by {}, valid until {}:", display_address(&approver), expires_at.format("%Y-%m-%d %H:%M:%S UTC"));
+                    println!("{}", token);
                 }
                 OutputFormat::Json => {
-                    let output = serde_json::json!({
-                        "derived": {
-                            "index": index,
-                            "address": derived.address(),
-                            "derivation_path": derived.derivation_path()
-                        }
-                    });
-                    println!("{}", serde_json::to_string_pretty(&output)?);
+                    print_json(&serde_json::json!({
+                        "success": true,
+                        "approver": display_address(&approver),
+                        "from": display_address(&request.from),
+                        "value": value,
+                        "token": token,
+                        "expires_at": expires_at.to_rfc3339(),
+                    }))?;
                 }
             }
-    }     
+        }
+    }
 
     Ok(())
 }
 
-async fn execute_list(
-    args: ListArgs,
+async fn execute_serve(args: ServeArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    use web3wallet_cli::services::rpc_server::{self, RpcServer};
+
+    let token_file = args
+        .token_file
+        .unwrap_or_else(|| config.state_path.join(web3wallet_cli::config::serve::TOKEN_FILE_NAME));
+    let token = rpc_server::load_or_create_token(&token_file).await?;
+    let mut server = RpcServer::bind(args.listen, token).await?;
+    if let Some(origin) = args.allow_origin {
+        server = server.with_allowed_origin(origin);
+    }
+    let url = format!("http://{}", server.local_addr()?);
+
+    match output {
+        OutputFormat::Table => {
+            println!("Serving JSON-RPC on {}", url);
+            println!("Token:    {} (send it as Authorization: Bearer <token>)", token_file.display());
+            println!("Methods:  {}", serve::METHODS.join(", "));
+        }
+        OutputFormat::Json => {
+            print_json(&serde_json::json!({
+                "success": true,
+                "url": url,
+                "token_file": token_file,
+                "methods": serve::METHODS,
+            }))?;
+        }
+    }
+
+    let shutdown = Shutdown::new();
+    shutdown.listen();
+    server.serve(std::sync::Arc::new(serve::WalletMethods::new(config)), shutdown.clone()).await?;
+    exit_after_shutdown(&shutdown).await
+}
+
+async fn execute_agent(
+    command: AgentCommand,
     config: &WalletConfig,
     output: OutputFormat
 ) -> WalletResult<()> {
-    let wallet_dir = args.path.unwrap_or_else(||config.wallets_path.clone());
+    let paths = AgentPaths::in_state_dir(&config.state_path);
 
-    info!("Scanning wallet directory: {}", wallet_dir.display());
-
-    if !wallet_dir.exists(){
-        tokio::fs::create_dir_all(&wallet_dir).await.map_err(|e|{
-            WalletError::Filesystem(FilesystemError::DirectoryNotAccessible { 
-                path: wallet_dir.display().to_string(), 
-                details: e.to_string() })
-        })?;
+    match command {
+        AgentCommand::Start { ttl, confirm } => {
+            use std::io::IsTerminal;
 
-        match output{
-            OutputFormat::Json => {
-                let output = serde_json::json!({
-                    "directory": wallet_dir.display().to_string(),
-                    "wallets": []
-                });
-                println!("{}", serde_json::to_string_pretty(&output)?);
+            if confirm && !std::io::stdin().is_terminal() {
+                return Err(UserInputError::InvalidParameters {
+                    parameter: "confirm".to_string(),
+                    value: "no terminal".to_string(),
+                    expected: "an agent started from a terminal, where the confirmations are answered".to_string(),
+                }
+                .into());
             }
-            OutputFormat::Table => {
-                println!("\n Wallet directory: {}", wallet_dir.display());
-                println!("No wallets found. Directory created.");
+            let manager = WalletManager::new(config.clone());
+            let mut server = AgentServer::bind(paths.clone(), std::time::Duration::from_secs(ttl), manager).await?;
+            if confirm {
+                server = server.with_confirmation(std::sync::Arc::new(|summary: String| {
+                    prompt_line(&format!("{} [y/N] ", summary))
+                        .is_ok_and(|answer| answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes"))
+                }));
+            }
+            match output {
+                OutputFormat::Table => {
+                    println!("Agent running (pid {}), caching passwords and keys for {}s", std::process::id(), ttl);
+                    println!("Socket:   {}", paths.socket.display());
+                    if confirm {
+                        println!("Every signature is confirmed here");
+                    }
+                }
+                OutputFormat::Json => {
+                    print_json(&serde_json::json!({
+                        "success": true,
+                        "pid": std::process::id(),
+                        "socket": paths.socket,
+                        "ttl_secs": ttl,
+                        "confirm": confirm,
+                    }))?;
+                }
             }
-        }
-        return Ok(());
-    }
-
-    // Read directory and find wallet files
-    let mut entries = tokio::fs::read_dir(&wallet_dir).await.map_err(|e|{
-        WalletError::Filesystem(FilesystemError::DirectoryNotAccessible { 
-            path: wallet_dir.display().to_string(), 
-            details: e.to_string() 
-        })
-    })?;
 
-    let mut wallets = Vec::new();
-    while let Some(entry) = entries.next_entry().await.map_err(|e|{
-        WalletError::Filesystem(FilesystemError::DirectoryNotAccessible { 
-            path: wallet_dir.display().to_string(), 
-            details: e.to_string() 
-        })
-    })? {
-        let path = entry.path();
-        if path.extension().and_then(|s|s.to_str()) == Some("json"){
-            match web3wallet_cli::services::CryptoService::load_keystore(&path).await {
-                Ok(keystore) => {
-                    wallets.push((path.clone(), keystore));
+            let shutdown = Shutdown::new();
+            shutdown.listen();
+            server.serve(shutdown).await?;
+        }
+        AgentCommand::Status => {
+            let Some(agent) = running_agent(&paths, &output).await? else {
+                return Ok(());
+            };
+            let status = agent.status().await?;
+            match output {
+                OutputFormat::Table => {
+                    println!("Agent running (pid {})", agent.pid());
+                    println!("Cached:   {} password(s), kept {}s each", status.cached, status.ttl.as_secs());
+                    println!("Keys:     {} wallet(s) held decrypted in locked memory", status.keys);
+                    println!("Confirm:  {}", if status.confirm { "every signature" } else { "off" });
+                }
+                OutputFormat::Json => {
+                    print_json(&serde_json::json!({
+                        "success": true,
+                        "running": true,
+                        "pid": agent.pid(),
+                        "cached": status.cached,
+                        "keys": status.keys,
+                        "ttl_secs": status.ttl.as_secs(),
+                        "confirm": status.confirm,
+                    }))?;
+                }
+            }
+        }
+        AgentCommand::Lock => {
+            let Some(agent) = running_agent(&paths, &output).await? else {
+                return Ok(());
+            };
+            agent.forget(None).await?;
+            match output {
+                OutputFormat::Table => println!("Agent (pid {}) forgot all cached passwords and keys", agent.pid()),
+                OutputFormat::Json => {
+                    print_json(&serde_json::json!({ "success": true, "running": true, "pid": agent.pid() }))?;
                 }
-                Err(_) => {
-                    continue;
+            }
+        }
+        AgentCommand::Stop => {
+            let Some(agent) = running_agent(&paths, &output).await? else {
+                return Ok(());
+            };
+            agent.stop().await?;
+            match output {
+                OutputFormat::Table => println!("Agent (pid {}) stopped", agent.pid()),
+                OutputFormat::Json => {
+                    print_json(&serde_json::json!({ "success": true, "running": false, "pid": agent.pid() }))?;
                 }
             }
         }
     }
 
-    match output{
-        OutputFormat::Table => {
-            println!("\n Wallet directory: {}", wallet_dir.display());
-            println!("Found {} wallets: \n", wallets.len());
-            
-            if wallets.is_empty(){
-                println!("No wallet found.");
-            }else{
-                println!("{:<20} {:<44} {:<12} {:<20}",
-                    "FILENAME", "ADDRESS", "NETWORK", "CREATED");
-                println!("{}", "─".repeat(100));
-
-                for (path, keystore) in wallets{
-                    let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("unknown");
-
-                    let short_address = if keystore.metadata.address.len() > 42 {
-                        format!("{}...{}", 
-                                 &keystore.metadata.address[..6],
-                                &keystore.metadata.address[38..])
-                    }else{
-                        keystore.metadata.address.clone()
-                    };
-                    println!("{:<20} {:<44} {:<12} {:<20}",
-                            filename, 
-                            short_address, 
-                            keystore.metadata.network, 
-                            keystore.metadata.created_at[..19].replace('T', " "));
-                };                
-            }
-        }
-        OutputFormat::Json => {
-            let wallet_list: Vec<_> = wallets.into_iter().map(|(path, keystore)| {
-                serde_json::json!({
-                    "filename": path.file_name().and_then(|n| n.to_str()).unwrap_or("Unknown"),
-                    "path": path.display().to_string(),
-                    "address": keystore.metadata.address,
-                    "network": keystore.metadata.network,
-                    "created_at": keystore.metadata.created_at,
-                    "alias": keystore.metadata.alias
-                })
-            }).collect();
+    Ok(())
+}
 
-            let output = serde_json::json!({
-                "success": true,
-                "directory": wallet_dir.display().to_string(),
-                "count": wallet_list.len(),
-                "wallets": wallet_list
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
+/// The running agent, or `None` after reporting that there is none
+async fn running_agent(paths: &AgentPaths, output: &OutputFormat) -> WalletResult<Option<AgentClient>> {
+    let agent = AgentClient::detect(paths).await;
+    if agent.is_none() {
+        match output {
+            OutputFormat::Table => println!("No agent running"),
+            OutputFormat::Json => print_json(&serde_json::json!({ "success": true, "running": false }))?,
         }
     }
-
-    Ok(())
+    Ok(agent)
 }
 
-async fn execute_derive(
-    args: DeriveArgs,
+async fn execute_kdf_bench(
+    args: KdfBenchArgs,
+    config_path: Option<PathBuf>,
     config: &WalletConfig,
     output: OutputFormat
 ) -> WalletResult<()> {
-    
-    
-    let manager = WalletManager::new(config.clone());
-
-    let wallet = if let Some(filename) = args.from_file{
-        let file_path = if filename.contains('/') || filename.contains('\\'){
-            PathBuf::from(&filename)
-        } else {
-            config.wallets_path.join(&filename)
-        };
-
-        let password = get_password("Enter wallet password")?;
-        manager.load_wallet(&file_path, &password).await?
-    } else {
-        let mnemonic = get_password("Enter wallet mnemonic...")?;
-        manager.import_from_mnemoic(&mnemonic).await?
+    let target = std::time::Duration::from_millis(args.target_ms);
+    let algorithms = match args.kdf {
+        Some(kdf) => vec![kdf],
+        None => vec![KdfAlgorithm::Argon2id, KdfAlgorithm::Scrypt, KdfAlgorithm::Pbkdf2],
     };
 
-    if !wallet.has_mnemonic() {
-        return Err(WalletError::UserInput(
-            UserInputError::InvalidParameters {
-                parameter: "wallet".to_string(),
-                value: "private key only".to_string(),
-                expected: "HD wallet with mnemonic".to_string(),
-            }
-        ));
+    let mut suggestions = Vec::new();
+    for algorithm in algorithms {
+        info!("Probing {}", algorithm.as_str());
+        suggestions.push(kdf_bench::tune(algorithm, target)?);
     }
 
-    let start_index = if args.path.parse::<u32>().is_ok(){
-        args.path.parse::<u32>().unwrap()
-    }else{
-        args.start_index
+    let written = if args.write {
+        let algorithm = args.kdf.unwrap_or(config.kdf);
+        let chosen = suggestions.iter().find(|s| s.settings.algorithm() == algorithm).ok_or_else(|| {
+            WalletError::UserInput(UserInputError::MissingParameter {
+                parameter: "kdf".to_string(),
+                hint: "the KDF to write must be among those benchmarked".to_string(),
+            })
+        })?;
+        let path = config_path.unwrap_or_else(WalletConfig::default_config_path);
+        WalletConfig::write_kdf(&path, &chosen.settings).await?;
+        Some(path)
+    } else {
+        None
     };
 
-    let mut derived_addresses = Vec::new();
-    for i in 0..args.count {
-        let index = start_index + i;
-        let derived = wallet.derive_address(index)?;
-        derived_addresses.push((index, derived));
-    }
-
     match output {
         OutputFormat::Table => {
-            println!("\n Derived addresses from HD wallet:");
-            println!("Base address: {}", wallet.address());
-            println!("Base path:    {}\n", wallet.derivation_path());
-
-            println!("{:<6} {:<44} {:<30}",
-                "INDEX", "ADDRESS", "DERIVATION PATH");
-            println!("{}", "─".repeat(85));
-
-            for (index, derived) in derived_addresses {
-                println!("{:<6} {:<44} {:<30}",
-                    index,
-                    derived.address(),
-                    derived.derivation_path()
-                );
+            println!("\n Target unlock time: {} ms\n", args.target_ms);
+            println!("{:<10} {:<36} {:<12}", "KDF", "PARAMETERS", "EST. UNLOCK");
+            println!("{}", "─".repeat(60));
+            for suggestion in &suggestions {
+                println!("{:<10} {:<36} {:<12}",
+                    suggestion.settings.algorithm().as_str(),
+                    suggestion.settings,
+                    format!("{} ms{}", suggestion.estimated_ms, if suggestion.clamped { " *" } else { "" }));
+            }
+            if suggestions.iter().any(|s| s.clamped) {
+                println!("\n * held at a safety floor or memory ceiling, the target is out of reach");
+            }
+            if let Some(path) = &written {
+                println!("\n Wrote [kdf] settings to {}", path.display());
             }
         }
         OutputFormat::Json => {
-            let addresses: Vec<_> = derived_addresses.into_iter().map(|(index, derived)| {
-                serde_json::json!({
-                    "index": index,
-                    "address": derived.address(),
-                    "derivation_path": derived.derivation_path()
-                })
-            }).collect();
-
-            let output = serde_json::json!({
-                "base_address": wallet.address(),
-                "base_path": wallet.derivation_path(),
-                "count": args.count,
-                "start_index": start_index,
-                "addresses": addresses
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
+            print_json(&serde_json::json!({
+                "success": true,
+                "target_ms": args.target_ms,
+                "suggestions": suggestions,
+                "written_to": written.map(|p| p.display().to_string())
+            }))?;
         }
     }
 
     Ok(())
 }
 
+/// Whether `command` may ask for a BIP39 passphrase on the terminal. The agent and `serve`
+/// take it with the password in their requests, the TUI in its own form.
+fn prompts_for_passphrase(command: &Commands) -> bool {
+    match command {
+        Commands::Agent(_) | Commands::Serve(_) => false,
+        #[cfg(feature = "tui")]
+        Commands::Tui => false,
+        _ => true,
+    }
+}
+
 #[tokio::main]
 async fn main() -> WalletResult<()> {
     let cli = Cli::parse();
 
-    init_logging(cli.verbose);
+    let redirect = cli.out.as_deref().map(OutputFile::redirect).transpose();
+    // The tui owns the screen, log lines would tear through it
+    #[cfg(feature = "tui")]
+    let quiet = matches!(cli.command, Commands::Tui);
+    #[cfg(not(feature = "tui"))]
+    let quiet = false;
+    init_logging(cli.verbose, quiet);
+    i18n::set_lang(cli.lang.unwrap_or_else(Lang::detect));
+    match redirect {
+        Ok(file) => *output_file() = file,
+        Err(err) => exit_with_error(&err),
+    }
+    match password_source(cli.password_file.as_deref(), cli.password_stdin) {
+        Ok(source) => *PASSWORD_SOURCE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = source,
+        Err(err) => exit_with_error(&err),
+    }
+
+    let config_path = cli.config.clone();
+    let mut config = match load_config(cli.config).await {
+        Ok(config) => config,
+        // `config init` and `config validate` must still run against a broken file
+        Err(_) if matches!(cli.command, Commands::Config(ConfigCommand::Init { .. } | ConfigCommand::Validate { .. })) => {
+            WalletConfig::default()
+        }
+        Err(err) => {
+            let _ = finish_output(false);
+            error!("Invalid configuration: {}", err);
+            std::process::exit(err.exit_code());
+        }
+    };
+    config.offline |= cli.offline;
+    if let Some(timeout) = cli.timeout {
+        config.rpc_timeout_secs = timeout;
+    }
+    if let Some(timeout) = cli.prompt_timeout {
+        config.prompt_timeout_secs = timeout;
+    }
+    let _ = PROMPT_TIMEOUT.set(std::time::Duration::from_secs(config.prompt_timeout_secs));
+    config.use_agent &= !cli.no_agent;
+    config.strict_permissions |= cli.strict;
+    config.harden_memory |= cli.harden;
+    config.allow_weak_passwords |= cli.allow_weak_password;
+    if prompts_for_passphrase(&cli.command) {
+        config.passphrase_prompt = Some(get_secret);
+    }
+    if config.harden_memory {
+        locked_memory::harden();
+    }
 
-    let config = load_config(cli.config).await?;
+    // A template always renders from the JSON document of a command
+    let output = match cli.template {
+        Some(template) => {
+            let _ = OUTPUT_TEMPLATE.set(template);
+            OutputFormat::Json
+        }
+        None => cli.output,
+    };
 
     if cli.verbose {
         info!("Starting Web3 Wallet CLI v{}", env!("CARGO_PKG_VERSION"));
@@ -638,30 +6422,210 @@ async fn main() -> WalletResult<()> {
     let result = match cli.command {
         Commands::Create(args) => {
             info!("Creating new wallet...");
-            execute_create(args, &config, cli.output).await
+            execute_create(args, &config, output).await
         }
         Commands::Import(args) => {
             info!("Importing wallet...");
-            excute_import(args, &config, cli.output).await
+            excute_import(args, &config, output).await
         }
         Commands::Load(args) => {
             info!("Loading wallet...");
-            excute_load(args, &config, cli.output).await
+            excute_load(args, &config, output).await
         }
         Commands::List(args) => {
             info!("Listing wallets...");
-            execute_list(args, &config, cli.output).await
+            execute_list(args, &config, output).await
+        }
+        Commands::Export(args) => {
+            info!("Exporting wallet...");
+            execute_export(args, &config, output).await
+        }
+        Commands::Xpub(args) => {
+            info!("Deriving extended public key...");
+            execute_xpub(args, &config, output).await
+        }
+        Commands::Backup(command) => {
+            info!("Running backup command...");
+            execute_backup(command, &config, output).await
+        }
+        Commands::Passwd(args) => {
+            info!("Changing wallet password...");
+            execute_passwd(args, &config, output).await
+        }
+        Commands::Account(command) => {
+            info!("Running account command...");
+            execute_account(command, &config, output).await
+        }
+        Commands::Genpass(args) => {
+            info!("Generating password...");
+            execute_genpass(args, output)
+        }
+        Commands::Rename(args) => {
+            info!("Updating wallet metadata...");
+            execute_rename(args, &config, output).await
+        }
+        Commands::RestoreBackup(args) => {
+            info!("Restoring keystore backup...");
+            execute_restore_backup(args, &config, output).await
+        }
+        Commands::Doctor(args) => {
+            info!("Auditing the wallets directory...");
+            execute_doctor(args, &config, output).await
+        }
+        Commands::VerifyBackup(args) => {
+            info!("Verifying wallet backup...");
+            execute_verify_backup(args, &config, output).await
+        }
+        Commands::Delete(args) => {
+            info!("Deleting wallet...");
+            execute_delete(args, &config, output).await
+        }
+        Commands::Migrate(args) => {
+            info!("Migrating keystores...");
+            execute_migrate(args, &config, output).await
         }
         Commands::Derive(args) => {
             info!("Deriving addresses...");
-            execute_derive(args, &config, cli.output).await
+            execute_derive(args, &config, output).await
+        }
+        Commands::Discover(args) => {
+            execute_discover(args, &config, output).await
+        }
+        Commands::Script(args) => {
+            info!("Running script...");
+            execute_script(args, &config, output).await
+        }
+        Commands::AppKey(args) => {
+            info!("Deriving app key...");
+            execute_app_key(args, &config, output).await
+        }
+        Commands::DeriveEntropy(args) => {
+            info!("Deriving child mnemonic...");
+            execute_derive_entropy(args, &config, output).await
         }
+        Commands::SignTx(args) => {
+            info!("Signing transaction...");
+            execute_sign_tx(args, &config, output).await
+        }
+        Commands::DecodeTx(args) => {
+            info!("Decoding transaction...");
+            execute_decode_tx(args, &config, output).await
+        }
+        Commands::Nonce(command) => {
+            info!("Running nonce command...");
+            execute_nonce(command, &config, output).await
+        }
+        Commands::Multisig(command) => {
+            info!("Running multisig command...");
+            execute_multisig(command, &config, output).await
+        }
+        Commands::SignMessage(args) => {
+            info!("Signing message...");
+            execute_sign_message(args, &config, output).await
+        }
+        Commands::SignSafeMessage(args) => {
+            info!("Signing Safe message...");
+            execute_sign_safe_message(args, &config, output).await
+        }
+        Commands::VerifySignature(args) => {
+            info!("Verifying signature...");
+            execute_verify_signature(args, &config, output).await
+        }
+        Commands::SendNft(args) => {
+            info!("Sending NFT...");
+            execute_send_nft(args, &config, output).await
+        }
+        Commands::SendToken(args) => {
+            info!("Sending tokens...");
+            execute_send_token(args, &config, output).await
+        }
+        Commands::SignPermit(args) => {
+            info!("Signing permit...");
+            execute_sign_permit(args, &config, output).await
+        }
+        Commands::Wrap(args) => {
+            info!("Wrapping native currency...");
+            execute_wrap(args, false, &config, output).await
+        }
+        Commands::Unwrap(args) => {
+            info!("Unwrapping native currency...");
+            execute_wrap(args, true, &config, output).await
+        }
+        Commands::Quote(args) => {
+            info!("Fetching quote...");
+            execute_quote(args, &config, output).await
+        }
+        Commands::XchainCheck(args) => {
+            info!("Checking address across chains...");
+            execute_xchain_check(args, &config, output).await
+        }
+        Commands::History(args) => {
+            info!("Fetching transaction history...");
+            execute_history(args, &config, output).await
+        }
+        Commands::Balance(args) => {
+            info!("Fetching balances...");
+            execute_balance(args, &config, output).await
+        }
+        Commands::Activity(args) => {
+            info!("Building activity report...");
+            execute_activity(args, &config, output).await
+        }
+        Commands::Monitor(args) => {
+            info!("Starting balance monitor...");
+            execute_monitor(args, &config, output).await
+        }
+        Commands::Tokens(command) => {
+            info!("Managing token registry...");
+            execute_tokens(command, &config, output).await
+        }
+        Commands::Wallet(command) => {
+            execute_wallet(command, cli.verbose, config_path, &config, output).await
+        }
+        Commands::Ens(command) => {
+            execute_ens(command, &config, output).await
+        }
+        Commands::Config(command) => {
+            execute_config(command, config_path, &config, output).await
+        }
+        Commands::Network(command) => {
+            execute_network(command, config_path, &config, output).await
+        }
+        Commands::KdfBench(args) => {
+            info!("Benchmarking key derivation functions...");
+            execute_kdf_bench(args, config_path, &config, output).await
+        }
+        Commands::Agent(command) => {
+            execute_agent(command, &config, output).await
+        }
+        Commands::Safe(command) => {
+            info!("Running Safe command...");
+            execute_safe(command, &config, output).await
+        }
+        Commands::Approval(command) => {
+            execute_approval(command, &config, output).await
+        }
+        Commands::Serve(args) => {
+            execute_serve(args, &config, output).await
+        }
+        #[cfg(feature = "tui")]
+        Commands::Tui => tui::run(&config).await,
     };
 
+    let result = result.and_then(|()| finish_output(true));
     if let Err(ref err) = result {
-        error!("Command failed: {}", err);
-        std::process::exit(1);
+        exit_with_error(err);
     }
 
     result
+}
+
+/// Log `err` with its hint, discard any `--out` output and exit with the status of its class
+fn exit_with_error(err: &WalletError) -> ! {
+    let _ = finish_output(false);
+    error!("Command failed: {}", err);
+    if let Some(hint) = err.suggestion() {
+        error!("{}: {}", tr("hint-label"), hint);
+    }
+    std::process::exit(err.exit_code());
 }
\ No newline at end of file
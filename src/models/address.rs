@@ -21,10 +21,10 @@ impl Address{
         derivation_path: Option<String>
     ) -> WalletResult<Self>{
         crate::utils::validate_ethereum_address(&address)?;
-        if !config::is_supported_network(&network){
+        if !config::is_valid_network_name(&network){
             return Err(ValidationError::InvalidAddressFormat{
                 address: network.clone(),
-                expected: format!("one of : {:?}", config::SUPPORTED_NETWORKS)
+                expected: "network name of lowercase letters, digits, '-' and '_'".to_string()
             }.into());
         }
 
@@ -33,7 +33,7 @@ impl Address{
         }
 
         Ok(Self{
-            address: address,
+            address,
             index,
             derivation_path,
             balance: None,
@@ -77,10 +77,10 @@ impl Address{
     pub fn validate(&self) -> WalletResult<()>{
         crate::utils::validate_ethereum_address(&self.address)?;
 
-        if !config::is_supported_network(&self.network){
+        if !config::is_valid_network_name(&self.network){
             return Err(ValidationError::InvalidAddressFormat{
                 address: self.address.clone(),
-                expected: format!("one of {:?}", config::SUPPORTED_NETWORKS)
+                expected: "network name of lowercase letters, digits, '-' and '_'".to_string()
             }.into());
         }
         
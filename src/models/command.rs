@@ -1,5 +1,6 @@
 use crate::config;
 use crate::errors::{WalletResult, UserInputError};
+use crate::models::wallet::{AddressChain, WalletType};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -38,6 +39,12 @@ pub struct CreateCommand{
 }
 
 
+impl Default for CreateCommand{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CreateCommand{
     pub fn new() -> Self{
         Self {
@@ -53,14 +60,14 @@ impl CreateCommand{
             return Err(UserInputError::InvalidParameters{
                 parameter: "words".to_string(),
                 value: self.words.to_string(),
-                expected: "12 or 24".to_string(), 
+                expected: "12, 15, 18, 21 or 24".to_string(), 
             }.into());
         }
 
-        if !config::is_supported_network(&self.network){
+        if !config::is_valid_network_name(&self.network){
             return Err(UserInputError::InvalidNetwork{
                 network: self.network.clone(),
-                supported: config::SUPPORTED_NETWORKS.iter().map(|s| s.to_string()).collect(),
+                supported: crate::services::chains::ChainInfo::defaults().into_iter().map(|c| c.name).collect(),
             }.into());
         }
         if let Some(ref save_path) = self.save {
@@ -68,4 +75,333 @@ impl CreateCommand{
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Typed results of the wallet commands; frontends render these however they like.
+/// Addresses are EIP-55 checksummed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateOutcome {
+    pub address: String,
+    pub network: String,
+    pub mnemonic: String,
+    /// Wordlist of `mnemonic`, e.g. "english"
+    pub language: String,
+    pub derivation_path: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub saved_to: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportOutcome {
+    pub address: String,
+    pub network: String,
+    pub has_mnemonic: bool,
+    /// Child keys can be derived, from the mnemonic, a BIP32 root key or an xpub
+    #[serde(default)]
+    pub is_hd: bool,
+    #[serde(default)]
+    pub wallet_type: WalletType,
+    pub derivation_path: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub saved_to: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadOutcome {
+    pub address: String,
+    pub network: String,
+    pub has_mnemonic: bool,
+    /// Child keys can be derived, from the mnemonic, a BIP32 root key or an xpub
+    #[serde(default)]
+    pub is_hd: bool,
+    #[serde(default)]
+    pub wallet_type: WalletType,
+    pub derivation_path: String,
+    pub alias: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Set when a child address was requested with the load
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub derived: Option<DerivedEntry>,
+}
+
+/// What can be read from a keystore without its password
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreSummary {
+    pub file: PathBuf,
+    pub address: String,
+    /// `native` or `v3`
+    pub format: String,
+    /// Only recorded by native keystores
+    pub network: Option<String>,
+    pub created_at: Option<String>,
+    pub alias: Option<String>,
+    #[serde(default)]
+    pub wallet_type: WalletType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListOutcome {
+    pub directory: PathBuf,
+    /// True when the directory did not exist and was created
+    pub created: bool,
+    pub wallets: Vec<WalletEntry>,
+}
+
+impl ListOutcome {
+    /// Keep only the wallets created for `network`
+    pub fn retain_network(&mut self, network: &str) {
+        self.wallets.retain(|wallet| wallet.network.eq_ignore_ascii_case(network));
+    }
+
+    /// Reorder the wallets, keeping file name order among equal keys
+    pub fn sort(&mut self, by: WalletSort) {
+        match by {
+            WalletSort::Filename => self.wallets.sort_by(|a, b| a.filename.cmp(&b.filename)),
+            WalletSort::Created => self.wallets.sort_by(|a, b| a.created_at.cmp(&b.created_at)),
+            WalletSort::Network => self.wallets.sort_by(|a, b| a.network.cmp(&b.network)),
+            WalletSort::Address => self.wallets.sort_by_key(|wallet| wallet.address.to_lowercase()),
+            // Wallets without an alias go last
+            WalletSort::Alias => self.wallets.sort_by(|a, b| match (&a.alias, &b.alias) {
+                (Some(a), Some(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            }),
+        }
+    }
+}
+
+/// Order of `list`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WalletSort {
+    #[default]
+    Filename,
+    Created,
+    Network,
+    Alias,
+    Address,
+}
+
+impl std::str::FromStr for WalletSort {
+    type Err = crate::errors::WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "filename" | "name" => Ok(Self::Filename),
+            "created" | "created-at" => Ok(Self::Created),
+            "network" => Ok(Self::Network),
+            "alias" => Ok(Self::Alias),
+            "address" => Ok(Self::Address),
+            _ => Err(UserInputError::InvalidParameters {
+                parameter: "sort".to_string(),
+                value: s.to_string(),
+                expected: "filename, created, network, alias or address".to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletEntry {
+    pub filename: String,
+    pub path: PathBuf,
+    pub address: String,
+    pub network: String,
+    pub created_at: String,
+    pub alias: Option<String>,
+    #[serde(default)]
+    pub wallet_type: WalletType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeriveOutcome {
+    pub base_address: String,
+    pub base_path: String,
+    pub start_index: u32,
+    pub addresses: Vec<DerivedEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DerivedEntry {
+    pub index: u32,
+    pub address: String,
+    pub derivation_path: String,
+    /// External or internal BIP44 chain, absent for other path layouts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_chain: Option<AddressChain>,
+    /// Only filled by `derive --show-private-keys`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+}
+
+/// Account-level extended public key, for watch-only wallets elsewhere
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct XpubOutcome {
+    pub address: String,
+    /// `m/44'/60'/{account}'`
+    pub account_path: String,
+    pub xpub: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub public_keys: Vec<PublicKeyEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicKeyEntry {
+    pub index: u32,
+    pub derivation_path: String,
+    pub address: String,
+    /// Compressed SEC1 public key
+    pub public_key: String,
+}
+
+/// Derivations of a public test mnemonic, stable across runs so other projects can pin them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixturesOutcome {
+    pub version: u32,
+    /// Short name from `config::fixtures::TEST_MNEMONICS`
+    pub name: String,
+    pub mnemonic: String,
+    pub passphrase: String,
+    pub base_path: String,
+    pub accounts: Vec<FixtureAccount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureAccount {
+    pub index: u32,
+    pub derivation_path: String,
+    pub address: String,
+    pub private_key: String,
+    /// Uncompressed SEC1 public key
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportOutcome {
+    pub address: String,
+    pub format: String,
+    pub file: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegateOutcome {
+    /// Address of the delegated key
+    pub address: String,
+    /// Primary address of the wallet it was taken from
+    pub parent_address: String,
+    pub derivation_path: String,
+    pub file: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteOutcome {
+    pub file: PathBuf,
+    pub address: Option<String>,
+    pub alias: Option<String>,
+    /// Contents were overwritten before the file was removed
+    pub shredded: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameOutcome {
+    pub file: PathBuf,
+    /// Set when the keystore was moved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_file: Option<PathBuf>,
+    pub address: String,
+    pub alias: Option<String>,
+    /// Ownership was checked by decrypting before the change
+    pub verified: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreBackupOutcome {
+    pub file: PathBuf,
+    /// Backup put back in place; unset when only listing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restored_from: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Backups of the keystore after the command, oldest first
+    pub backups: Vec<PathBuf>,
+}
+
+/// Accounts of a keystore, as `account list` shows them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountsOutcome {
+    pub file: PathBuf,
+    /// Name of the account that signs
+    pub selected: String,
+    /// The primary account first, then the others in the order they were added
+    pub accounts: Vec<AccountEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountEntry {
+    pub name: String,
+    pub address: String,
+    /// Parent path of an account derived from the wallet's seed; None for a standalone key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
+    pub selected: bool,
+}
+
+/// Secrets made by `genpass`, each with the bits of randomness it holds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenpassOutcome {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<GeneratedSecret>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub passphrase: Option<GeneratedSecret>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedSecret {
+    pub value: String,
+    /// Passwords count characters, passphrases words
+    pub length: usize,
+    pub bits: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveOutcome {
+    pub archive: PathBuf,
+    pub directory: PathBuf,
+    pub files: Vec<String>,
+    pub kdf: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveRestoreOutcome {
+    pub archive: PathBuf,
+    pub directory: PathBuf,
+    /// When the archive was made, RFC 3339
+    pub created_at: String,
+    pub restored: Vec<String>,
+    /// Files already present with the archived contents
+    pub unchanged: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrateOutcome {
+    pub file: PathBuf,
+    pub from_version: String,
+    pub to_version: String,
+    /// False when the file was already at the latest version
+    pub migrated: bool,
+    pub dry_run: bool,
+    pub kdf: String,
+    /// The KDF differs from, or is cheaper than, the configured one
+    pub kdf_outdated: bool,
+    /// Re-encrypted with the configured KDF
+    pub rekeyed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordChangeOutcome {
+    pub address: String,
+    pub format: String,
+    pub kdf: String,
+    pub file: PathBuf,
+}
@@ -1,6 +1,8 @@
 use crate::config;
-use crate::errors::{ValidationError, CryptographicError, WalletResult};
+use crate::errors::{ValidationError, CryptographicError, UserInputError, WalletResult};
+use crate::models::wallet::WalletType;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Serialize, Deserialize) ]
 pub struct Keystore{
@@ -14,8 +16,22 @@ pub struct KeystoreMetadata{
     pub alias: Option<String>,
     pub address: String,
     pub created_at: String,
+    /// Set by `passwd`; the password dates from `created_at` while absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_changed_at: Option<String>,
     pub network: String,
-    pub keystore_type: String
+    /// Chain the wallet was created for; signing for another chain needs `--force`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+    pub keystore_type: String,
+    /// Cold wallets may only be decrypted offline
+    #[serde(default)]
+    pub cold: bool,
+    #[serde(default)]
+    pub backup_verified: bool,
+    /// Plaintext copy of the payload's type, so signing is refused before the password prompt
+    #[serde(default)]
+    pub wallet_type: WalletType,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,9 +68,153 @@ pub enum KdfParams {
         prf: String,
         salt: String,
     },
+    /// scrypt parameters, as used by geth; `n` is a power of two
+    Scrypt {
+        dklen: u32,
+        n: u32,
+        r: u32,
+        p: u32,
+        salt: String,
+    },
+}
+
+/// KDF used for newly saved keystores, chosen with `--kdf` or `[kdf] algorithm`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KdfAlgorithm {
+    #[default]
+    Argon2id,
+    /// Lower memory ceiling than Argon2 and understood by geth tooling
+    Scrypt,
+    Pbkdf2,
+}
+
+impl KdfAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Argon2id => "argon2id",
+            Self::Scrypt => "scrypt",
+            Self::Pbkdf2 => "pbkdf2",
+        }
+    }
+}
+
+impl FromStr for KdfAlgorithm {
+    type Err = crate::errors::WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "argon2id" | "argon2" => Ok(Self::Argon2id),
+            "scrypt" => Ok(Self::Scrypt),
+            "pbkdf2" => Ok(Self::Pbkdf2),
+            _ => Err(UserInputError::InvalidParameters {
+                parameter: "kdf".to_string(),
+                value: s.to_string(),
+                expected: "argon2id, scrypt or pbkdf2".to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// Native keystore schema versions, oldest first.
+/// Validation dispatches on the version; `upgrade_json` rewrites older files to `LATEST`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum KeystoreVersion {
+    /// `kdf` was informational and could disagree with `kdfparams`;
+    /// `cold` and `backup_verified` may be missing
+    V1_0,
+    /// `kdf` names the algorithm of `kdfparams` and the metadata flags are always written
+    V1_1,
+}
+
+impl KeystoreVersion {
+    pub const LATEST: Self = Self::V1_1;
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::V1_0 => "1.0.0",
+            Self::V1_1 => "1.1.0",
+        }
+    }
+
+    pub fn parse(version: &str) -> WalletResult<Self> {
+        match version {
+            "1.0.0" => Ok(Self::V1_0),
+            "1.1.0" => Ok(Self::V1_1),
+            "" => Err(ValidationError::InvalidKeystoreSchema {
+                error: "Missing version".to_string(),
+                file_path: "keystore".to_string(),
+            }
+            .into()),
+            other => Err(ValidationError::InvalidKeystoreSchema {
+                error: format!(
+                    "Unsupported keystore version {}, this build reads up to {}",
+                    other,
+                    Self::LATEST.as_str()
+                ),
+                file_path: "keystore".to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// Rewrite a native keystore's raw JSON one version at a time up to `KeystoreVersion::LATEST`,
+/// returning the version it started from. The encrypted payload is never touched.
+pub fn upgrade_json(value: &mut serde_json::Value) -> WalletResult<KeystoreVersion> {
+    let original = KeystoreVersion::parse(value.get("version").and_then(|v| v.as_str()).unwrap_or_default())?;
+
+    let mut version = original;
+    while version < KeystoreVersion::LATEST {
+        version = match version {
+            KeystoreVersion::V1_0 => upgrade_1_0(value)?,
+            KeystoreVersion::V1_1 => unreachable!("latest version has no upgrade step"),
+        };
+        value["version"] = serde_json::Value::from(version.as_str());
+    }
+    Ok(original)
+}
+
+fn upgrade_1_0(value: &mut serde_json::Value) -> WalletResult<KeystoreVersion> {
+    let schema_error = |error: &str| ValidationError::InvalidKeystoreSchema {
+        error: error.to_string(),
+        file_path: "keystore".to_string(),
+    };
+
+    let metadata = value
+        .get_mut("metadata")
+        .and_then(|m| m.as_object_mut())
+        .ok_or_else(|| schema_error("Missing metadata"))?;
+    metadata.entry("cold").or_insert(serde_json::Value::Bool(false));
+    metadata.entry("backup_verified").or_insert(serde_json::Value::Bool(false));
+
+    let crypto = value
+        .get_mut("crypto")
+        .and_then(|c| c.as_object_mut())
+        .ok_or_else(|| schema_error("Missing crypto parameters"))?;
+    let params: KdfParams = crypto
+        .get("kdfparams")
+        .cloned()
+        .and_then(|p| serde_json::from_value(p).ok())
+        .ok_or_else(|| schema_error("Unrecognized KDF parameters"))?;
+    crypto.insert("kdf".to_string(), serde_json::Value::from(params.algorithm().as_str()));
+
+    Ok(KeystoreVersion::V1_1)
+}
+
+impl KdfParams {
+    pub fn algorithm(&self) -> KdfAlgorithm {
+        match self {
+            Self::Argon2 { .. } => KdfAlgorithm::Argon2id,
+            Self::Pbkdf2 { .. } => KdfAlgorithm::Pbkdf2,
+            Self::Scrypt { .. } => KdfAlgorithm::Scrypt,
+        }
+    }
 }
 
 impl Keystore{
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         alias: Option<String>,
         address: String,
@@ -69,8 +229,13 @@ impl Keystore{
             alias,
             address, 
             created_at: chrono::Utc::now().to_rfc3339(),
+            password_changed_at: None,
             network, 
+            chain_id: None,
             keystore_type: "web3wallet-cli".to_string(),
+            cold: false,
+            backup_verified: false,
+            wallet_type: WalletType::Full,
         };
 
         let crypto = CryptoParams{
@@ -79,21 +244,19 @@ impl Keystore{
             cipherparams: CipherParams{
                 iv: hex::encode(nonce),
             },
-            kdf: match kdf_params{
-                KdfParams::Argon2{..} => "argon2id".to_string(),
-                KdfParams::Pbkdf2{..} => "pbkdf2".to_string()
-            },
+            kdf: kdf_params.algorithm().as_str().to_string(),
             kdfparams: kdf_params,
             mac: hex::encode(mac)
         };
 
         Self{
-            version: "1.0.0".to_string(),
+            version: KeystoreVersion::LATEST.as_str().to_string(),
             metadata,
             crypto
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn with_pbkdf2(
         alias: Option<String>,
         address: String,
@@ -121,6 +284,7 @@ impl Keystore{
             kdf_params,
         )
     }
+    #[allow(clippy::too_many_arguments)]
     pub fn with_argon2(
         alias: Option<String>,
         address: String,
@@ -168,6 +332,7 @@ impl Keystore{
         let salt_hex = match &self.crypto.kdfparams {
             KdfParams::Argon2 { salt, .. } => salt,
             KdfParams::Pbkdf2 { salt, .. } => salt,
+            KdfParams::Scrypt { salt, .. } => salt,
         };
 
         hex::decode(salt_hex).map_err(|e| {
@@ -203,15 +368,28 @@ impl Keystore{
         &self.crypto.kdfparams
     }
 
+    pub fn schema_version(&self) -> WalletResult<KeystoreVersion> {
+        KeystoreVersion::parse(&self.version)
+    }
+
     pub fn validate(&self)->WalletResult<()>{
-        // Validate version
-        if self.version.is_empty() {
-            return Err(ValidationError::InvalidKeystoreSchema {
-                error: "Missing version".to_string(),
-                file_path: "keystore".to_string(),
-            }.into());
+        match self.schema_version()? {
+            KeystoreVersion::V1_0 => self.validate_common(),
+            KeystoreVersion::V1_1 => {
+                self.validate_common()?;
+                if self.crypto.kdf != self.crypto.kdfparams.algorithm().as_str() {
+                    return Err(ValidationError::InvalidKeystoreSchema {
+                        error: format!("kdf \"{}\" does not match the KDF parameters", self.crypto.kdf),
+                        file_path: "keystore".to_string(),
+                    }.into());
+                }
+                Ok(())
+            }
         }
+    }
 
+    /// Checks shared by every schema version
+    fn validate_common(&self) -> WalletResult<()> {
         // Validate address format (should be hex with 0x prefix)
         if !self.metadata.address.starts_with("0x") || self.metadata.address.len() != 42 {
             return Err(ValidationError::InvalidKeystoreSchema {
@@ -287,6 +465,20 @@ impl Keystore{
                     }.into());
                 }
             }
+            KdfParams::Scrypt { salt, dklen, n, r, p } => {
+                hex::decode(salt).map_err(|_| {
+                    ValidationError::InvalidKeystoreSchema {
+                        error: "Invalid scrypt salt hex".to_string(),
+                        file_path: "keystore".to_string(),
+                    }
+                })?;
+                if *dklen == 0 || *n < 2 || !n.is_power_of_two() || *r == 0 || *p == 0 {
+                    return Err(ValidationError::InvalidKeystoreSchema {
+                        error: "Invalid scrypt parameters".to_string(),
+                        file_path: "keystore".to_string(),
+                    }.into());
+                }
+            }
         }
 
         Ok(())
@@ -0,0 +1,77 @@
+use crate::models::Keystore;
+use serde::{Deserialize, Serialize};
+
+/// Web3 Secret Storage (keystore v3) file as written by geth, MyEtherWallet and MetaMask
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreV3 {
+    pub version: u8,
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Lowercase hex without `0x`, optional in the spec
+    #[serde(default)]
+    pub address: Option<String>,
+    /// MyEtherWallet writes `Crypto`
+    #[serde(alias = "Crypto")]
+    pub crypto: V3Crypto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct V3Crypto {
+    /// Only "aes-128-ctr" is defined by the spec
+    pub cipher: String,
+    pub ciphertext: String,
+    pub cipherparams: V3CipherParams,
+    /// "scrypt" or "pbkdf2"
+    pub kdf: String,
+    pub kdfparams: V3KdfParams,
+    pub mac: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct V3CipherParams {
+    pub iv: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum V3KdfParams {
+    Scrypt {
+        dklen: u32,
+        n: u32,
+        r: u32,
+        p: u32,
+        salt: String,
+    },
+    Pbkdf2 {
+        dklen: u32,
+        c: u32,
+        prf: String,
+        salt: String,
+    },
+}
+
+impl KeystoreV3 {
+    /// `0x`-prefixed address, if the file records one
+    pub fn address(&self) -> Option<String> {
+        self.address
+            .as_deref()
+            .map(crate::utils::normalize_address)
+    }
+}
+
+/// Any keystore file format the CLI can read
+#[derive(Debug, Clone)]
+pub enum KeystoreFile {
+    /// The crate's own format
+    Native(Box<Keystore>),
+    V3(Box<KeystoreV3>),
+}
+
+impl KeystoreFile {
+    pub fn address(&self) -> Option<String> {
+        match self {
+            Self::Native(keystore) => Some(keystore.metadata.address.clone()),
+            Self::V3(keystore) => keystore.address(),
+        }
+    }
+}
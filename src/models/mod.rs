@@ -1,9 +1,16 @@
 pub mod address;
+#[doc(hidden)]
 pub mod command;
 pub mod keystore;
+pub mod keystore_v3;
+pub mod overrides;
+pub mod token;
 pub mod wallet;
 
 pub use address::Address;
 pub use keystore::Keystore;
-pub use wallet::Wallet;
+pub use keystore_v3::{KeystoreFile, KeystoreV3};
+pub use overrides::WalletOverrides;
+pub use token::{Token, TokenRegistry};
+pub use wallet::{Account, AddressChain, PathPreset, Wallet, WalletType};
 // pub use command::{CommandResult, OutputFormat};
\ No newline at end of file
@@ -0,0 +1,74 @@
+use crate::config;
+#[cfg(feature = "native")]
+use crate::errors::{FilesystemError, WalletResult};
+use crate::services::fees::FeeTier;
+use crate::WalletConfig;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Per-wallet defaults read from a `<wallet>.toml` sidecar next to the keystore
+///
+/// ```toml
+/// network = "sepolia"
+/// fee_tier = "fast"
+/// policy_profile = "treasury"
+/// rpc_url = "https://sepolia.example.org"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WalletOverrides {
+    /// Preferred network, used when a command's network flag is omitted
+    pub network: Option<String>,
+    pub fee_tier: Option<FeeTier>,
+    pub policy_profile: Option<String>,
+    /// RPC endpoint for the preferred network
+    pub rpc_url: Option<String>,
+}
+
+impl WalletOverrides {
+    pub fn sidecar_path(keystore_path: &Path) -> PathBuf {
+        keystore_path.with_extension(config::wallet::OVERRIDES_EXTENSION)
+    }
+
+    /// Sidecar of the keystore at `keystore_path`, `None` if the wallet has none
+    #[cfg(feature = "native")]
+    pub async fn load(keystore_path: &Path) -> WalletResult<Option<Self>> {
+        let path = Self::sidecar_path(keystore_path);
+        let data = match tokio::fs::read_to_string(&path).await {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let overrides: Self = toml::from_str(&data).map_err(|e| FilesystemError::InvalidFormat {
+            path: path.display().to_string(),
+            details: e.to_string(),
+        })?;
+        if let Some(network) = &overrides.network {
+            if !config::is_valid_network_name(network) {
+                return Err(FilesystemError::InvalidFormat {
+                    path: path.display().to_string(),
+                    details: format!("invalid network name {:?}", network),
+                }
+                .into());
+            }
+        }
+        Ok(Some(overrides))
+    }
+
+    /// Layer these overrides on top of `config`
+    pub fn apply(&self, config: &mut WalletConfig) {
+        if let Some(network) = &self.network {
+            config.network = network.clone();
+        }
+        if let Some(tier) = self.fee_tier {
+            config.fee_tier = tier;
+        }
+        if let Some(profile) = &self.policy_profile {
+            config.policy_profile = Some(profile.clone());
+        }
+        if let Some(url) = &self.rpc_url {
+            config.rpc_overrides.insert(config.network.clone(), url.clone());
+        }
+    }
+}
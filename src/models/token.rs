@@ -0,0 +1,175 @@
+#[cfg(feature = "native")]
+use crate::errors::FilesystemError;
+use crate::errors::{UserInputError, WalletResult};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// ERC-20 token known to the CLI
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Token {
+    pub chain_id: u64,
+    pub address: String,
+    pub symbol: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub decimals: u8,
+}
+
+impl Token {
+    pub fn new(chain_id: u64, address: &str, symbol: &str, name: Option<String>, decimals: u8) -> WalletResult<Self> {
+        crate::utils::validate_ethereum_address(address)?;
+        Ok(Self {
+            chain_id,
+            address: crate::utils::normalize_address(address),
+            symbol: symbol.to_string(),
+            name,
+            decimals,
+        })
+    }
+
+    /// Tokens from `config::tokens::BUNDLED_TOKENS`
+    pub fn bundled() -> Vec<Self> {
+        crate::config::tokens::BUNDLED_TOKENS
+            .iter()
+            .map(|(chain_id, address, symbol, name, decimals)| Self {
+                chain_id: *chain_id,
+                address: crate::utils::normalize_address(address),
+                symbol: symbol.to_string(),
+                name: Some(name.to_string()),
+                decimals: *decimals,
+            })
+            .collect()
+    }
+
+    /// Whether `query` names this token, by symbol (case-insensitive) or address
+    pub fn matches(&self, query: &str) -> bool {
+        self.symbol.eq_ignore_ascii_case(query) || crate::utils::addresses_equal(&self.address, query)
+    }
+}
+
+/// Local token registry, persisted as JSON
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenRegistry {
+    #[cfg(feature = "native")]
+    #[serde(skip)]
+    path: PathBuf,
+    pub tokens: Vec<Token>,
+}
+
+impl TokenRegistry {
+    pub fn default_path(state_dir: &Path) -> PathBuf {
+        state_dir.join(crate::config::tokens::REGISTRY_FILE_NAME)
+    }
+
+    /// Load the registry at `path`, empty if the file does not exist yet
+    #[cfg(feature = "native")]
+    pub async fn load(path: &Path) -> WalletResult<Self> {
+        let mut registry: Self = match tokio::fs::read_to_string(path).await {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| FilesystemError::InvalidFormat {
+                path: path.display().to_string(),
+                details: e.to_string(),
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e.into()),
+        };
+        registry.path = path.to_path_buf();
+        Ok(registry)
+    }
+
+    #[cfg(feature = "native")]
+    pub async fn save(&self) -> WalletResult<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                FilesystemError::DirectoryNotAccessible {
+                    path: parent.display().to_string(),
+                    details: e.to_string(),
+                }
+            })?;
+        }
+        tokio::fs::write(&self.path, serde_json::to_string_pretty(self)?).await?;
+        Ok(())
+    }
+
+    /// Insert or replace the token with the same chain and address; returns true if it was new
+    pub fn add(&mut self, token: Token) -> bool {
+        match self
+            .tokens
+            .iter_mut()
+            .find(|t| t.chain_id == token.chain_id && crate::utils::addresses_equal(&t.address, &token.address))
+        {
+            Some(existing) => {
+                *existing = token;
+                false
+            }
+            None => {
+                self.tokens.push(token);
+                true
+            }
+        }
+    }
+
+    /// Remove tokens matching `query` (symbol or address) on `chain_id`
+    pub fn remove(&mut self, query: &str, chain_id: u64) -> WalletResult<Vec<Token>> {
+        let (removed, kept): (Vec<Token>, Vec<Token>) = self
+            .tokens
+            .drain(..)
+            .partition(|t| t.chain_id == chain_id && t.matches(query));
+        self.tokens = kept;
+
+        if removed.is_empty() {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "token".to_string(),
+                value: query.to_string(),
+                expected: format!("a registered token symbol or address on chain {}", chain_id),
+            }
+            .into());
+        }
+        Ok(removed)
+    }
+
+    /// Resolve a symbol or address on `chain_id`; ambiguous symbols are an error
+    pub fn find(&self, query: &str, chain_id: u64) -> WalletResult<&Token> {
+        let matches: Vec<&Token> = self
+            .tokens
+            .iter()
+            .filter(|t| t.chain_id == chain_id && t.matches(query))
+            .collect();
+
+        match matches.as_slice() {
+            [token] => Ok(*token),
+            [] => Err(UserInputError::InvalidParameters {
+                parameter: "token".to_string(),
+                value: query.to_string(),
+                expected: format!("a registered token on chain {} (see `tokens list`)", chain_id),
+            }
+            .into()),
+            _ => Err(UserInputError::InvalidParameters {
+                parameter: "token".to_string(),
+                value: query.to_string(),
+                expected: "an unambiguous symbol, or the token address".to_string(),
+            }
+            .into()),
+        }
+    }
+
+    /// Resolve like `find`, in the bundled tokens when no registered token matches
+    pub fn find_or_bundled(&self, query: &str, chain_id: u64) -> WalletResult<Token> {
+        if self.tokens.iter().any(|t| t.chain_id == chain_id && t.matches(query)) {
+            return self.find(query, chain_id).cloned();
+        }
+        let bundled = Self {
+            #[cfg(feature = "native")]
+            path: PathBuf::new(),
+            tokens: Token::bundled(),
+        };
+        bundled.find(query, chain_id).cloned()
+    }
+
+    pub fn list(&self, chain_id: Option<u64>) -> Vec<&Token> {
+        self.tokens
+            .iter()
+            .filter(|t| chain_id.is_none_or(|id| t.chain_id == id))
+            .collect()
+    }
+}
@@ -1,18 +1,149 @@
 use crate::config;
-use crate::errors::{WalletResult, CryptographicError};
+use crate::errors::{AuthenticationError, WalletResult, CryptographicError};
 use ethers::prelude::*;
-use ethers::signers::coins_bip39::{English, Mnemonic};
+use coins_bip32::enc::{MainnetEncoder, XKeyEncoder};
+use coins_bip32::primitives::Hint;
+use coins_bip32::xkeys::{Parent, XPriv, XPub};
+use ethers::core::k256::ecdsa::{SigningKey, VerifyingKey};
+use ethers::signers::coins_bip39::English;
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
+use crate::services::locked_memory::LockedString;
 use crate::services::mnemonic::MnemonicService;
+
+/// Whether a wallet can sign, recorded in the keystore metadata as well as the payload
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WalletType {
+    #[default]
+    Full,
+    /// Imported from an xpub or a bare address: shows and derives addresses, never signs
+    WatchOnly,
+}
+
+impl WalletType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::WatchOnly => "watch_only",
+        }
+    }
+}
+
+/// BIP44 chain of an address: external addresses receive, internal ones take change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressChain {
+    External,
+    Internal,
+}
+
+impl AddressChain {
+    /// Chain named by the change level of `m/44'/{coin}'/{account}'/{change}/{index}`; paths
+    /// of other shapes (legacy, app keys) have none
+    pub fn of_path(derivation_path: &str) -> Option<Self> {
+        let levels: Vec<&str> = derivation_path.split('/').collect();
+        match levels.as_slice() {
+            ["m", "44'", _, _, "0", _] => Some(Self::External),
+            ["m", "44'", _, _, "1", _] => Some(Self::Internal),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::External => "external",
+            Self::Internal => "internal",
+        }
+    }
+}
+
+/// Derivation path layouts of other wallets, for restoring a mnemonic whose path the user
+/// does not know. See `config::path_presets` for the templates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PathPreset {
+    /// `m/44'/60'/0'/0/{index}`, also Trezor and most software wallets
+    Metamask,
+    /// `m/44'/60'/{index}'/0/0`, one BIP44 account per address
+    LedgerLive,
+    /// `m/44'/60'/0'/{index}`, Ledger Chrome app and early MyEtherWallet
+    Legacy,
+    /// `m/44'/60'/0'/0/{index}`, MyEtherWallet software wallets
+    Mew,
+}
+
+impl PathPreset {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Metamask => "metamask",
+            Self::LedgerLive => "ledger-live",
+            Self::Legacy => "legacy",
+            Self::Mew => "mew",
+        }
+    }
+
+    pub fn template(&self) -> &'static str {
+        match self {
+            Self::Metamask => config::path_presets::METAMASK,
+            Self::LedgerLive => config::path_presets::LEDGER_LIVE,
+            Self::Legacy => config::path_presets::LEGACY,
+            Self::Mew => config::path_presets::MEW,
+        }
+    }
+
+    /// Full path of address `index`
+    pub fn path(&self, index: u32) -> String {
+        self.template().replace("{index}", &index.to_string())
+    }
+
+    /// Whether addresses are `{base}/{index}` under a single parent, so `derivation_path`
+    /// alone describes the layout
+    pub fn is_flat(&self) -> bool {
+        self.template().ends_with("/{index}")
+    }
+}
+
+impl std::str::FromStr for PathPreset {
+    type Err = crate::errors::WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "metamask" => Ok(Self::Metamask),
+            "ledger-live" | "ledgerlive" => Ok(Self::LedgerLive),
+            "legacy" | "ledger-legacy" => Ok(Self::Legacy),
+            "mew" | "myetherwallet" => Ok(Self::Mew),
+            _ => Err(crate::errors::UserInputError::InvalidParameters {
+                parameter: "path-preset".to_string(),
+                value: s.to_string(),
+                expected: "metamask, ledger-live, legacy or mew".to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Zeroize, ZeroizeOnDrop)]
 pub struct Wallet {
+    mnemonic: LockedString,
+    /// Optional BIP39 passphrase ("25th word"), mixed into the seed. Never written to the
+    /// keystore, so it stays a second factor: it is asked for again on every unlock
+    #[serde(default, skip_serializing)]
+    passphrase: LockedString,
+    /// Whether the seed takes a passphrase, which `unlock_passphrase` must then supply
     #[zeroize(skip)]
-    mnemonic: String,
-    #[serde(skip)] // 私钥绝不能被序列化或打印  
+    #[serde(default)]
+    passphrase_protected: bool,
+    /// Only ever serialized into the encrypted keystore payload; key-only wallets have no other copy
+    #[serde(default)]
     master_private_key: Option<Vec<u8>>,
+    /// BIP32 root key (xprv) of HD wallets imported without a mnemonic; same confidentiality as above
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    extended_key: Option<String>,
+    /// Raw BIP39 seed of HD wallets imported without a mnemonic; same confidentiality as above
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<Vec<u8>>,
     #[zeroize(skip)]
     address: String,
     #[zeroize(skip)]
@@ -23,6 +154,56 @@ pub struct Wallet {
     alias: Option<String>,
     #[zeroize(skip)]
     created_at: chrono::DateTime<chrono::Utc>,
+    #[zeroize(skip)]
+    #[serde(default)]
+    wallet_type: WalletType,
+    /// Account-level extended public key of watch-only wallets, addresses are derived from it
+    #[zeroize(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    xpub: Option<String>,
+    /// Layout addresses are derived in when it is not `derivation_path/{index}`
+    #[zeroize(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path_preset: Option<PathPreset>,
+    /// Further named accounts kept in the same keystore, see `account add`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    accounts: Vec<Account>,
+    /// Account that signs and shows its address; None for the wallet's own, primary one
+    #[zeroize(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    selected_account: Option<String>,
+}
+
+/// One of the further accounts of a keystore: derived from the wallet's seed under its own
+/// path, or a private key imported into it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Zeroize, ZeroizeOnDrop)]
+pub struct Account {
+    #[zeroize(skip)]
+    name: String,
+    #[zeroize(skip)]
+    address: String,
+    /// Parent path of a derived account, its address is index 0 under it
+    #[zeroize(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    derivation_path: Option<String>,
+    /// Key of an imported account; only ever serialized into the encrypted keystore payload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    private_key: Option<Vec<u8>>,
+}
+
+impl Account {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+    pub fn derivation_path(&self) -> Option<&str> {
+        self.derivation_path.as_deref()
+    }
+    pub fn is_imported(&self) -> bool {
+        self.private_key.is_some()
+    }
 }
 
 impl Wallet {
@@ -31,31 +212,70 @@ impl Wallet {
         network: &str,
         alias: Option<String>
     ) -> WalletResult<Self>{
-        let bip_mnemonic = bip39::Mnemonic::from_str(mnemonic).map_err(|e|{
-            CryptographicError::InvalidMnemonic{
-                detail: e.to_string(),
-                suggestion: "Ensure the mnemonic is valid and follows BIP39 standards".to_string()
-            }
-        })?;
+        Self::from_mnemonic_with_passphrase(mnemonic, "", network, alias)
+    }
+
+    pub fn from_mnemonic_with_passphrase(
+        mnemonic: &str,
+        passphrase: &str,
+        network: &str,
+        alias: Option<String>
+    ) -> WalletResult<Self>{
+        MnemonicService::detect_language(mnemonic)?;
+
+        let mut wallet = Self{
+            mnemonic: mnemonic.into(),
+            passphrase: passphrase.into(),
+            passphrase_protected: !passphrase.is_empty(),
+            master_private_key: None,
+            extended_key: None,
+            seed: None,
+            address: String::new(),
+            derivation_path: config::DEFAULT_DERIVATION_PATH.to_string(),
+            network: network.to_string(),
+            alias,
+            created_at: chrono::Utc::now(),
+            wallet_type: WalletType::Full,
+            xpub: None,
+            path_preset: None,
+            accounts: Vec::new(),
+            selected_account: None,
+        };
+        let signer = wallet.signer()?;
+        wallet.master_private_key = Some(signer.signer().to_bytes().to_vec());
+        wallet.address = format!("{:?}", signer.address());
+        Ok(wallet)
+    }
+
+    /// HD wallet from a base58 BIP32 root key (`xprv...`), for keys exported by tools that
+    /// never showed a mnemonic. Derivation works as for mnemonic wallets.
+    pub fn from_xprv(
+        xprv: &str,
+        network: &str,
+        alias: Option<String>
+    ) -> WalletResult<Self> {
+        let root = parse_xprv(xprv)?;
+        let primary = format!("{}/0", config::DEFAULT_DERIVATION_PATH);
+        let wallet = derive_signer(&root, &primary)?;
 
-        let wallet = MnemonicBuilder::<English>::default()
-            .phrase(mnemonic)
-            .build()
-            .map_err(|e|{
-                CryptographicError::AddressGenerationFailed {
-                    details: e.to_string(),
-                }
-            })?;
-        
         Ok(Self{
-            mnemonic: mnemonic.to_string(),
+            mnemonic: LockedString::default(),
+            passphrase: LockedString::default(),
+            passphrase_protected: false,
             master_private_key: Some(wallet.signer().to_bytes().to_vec()),
+            extended_key: Some(xprv.trim().to_string()),
+            seed: None,
             address: format!("{:?}", wallet.address()),
             derivation_path: config::DEFAULT_DERIVATION_PATH.to_string(),
             network: network.to_string(),
             alias,
             created_at: chrono::Utc::now(),
-        })    
+            wallet_type: WalletType::Full,
+            xpub: None,
+            path_preset: None,
+            accounts: Vec::new(),
+            selected_account: None,
+        })
     }
 
     pub fn from_private_key(
@@ -85,54 +305,544 @@ impl Wallet {
         })?;
 
         Ok(Self{
-            mnemonic: "".to_string(),
+            mnemonic: LockedString::default(),
+            passphrase: LockedString::default(),
+            passphrase_protected: false,
             master_private_key: Some(wallet.signer().to_bytes().to_vec()),
+            extended_key: None,
+            seed: None,
             address: format!("{:?}", wallet.address()),
             derivation_path: config::DEFAULT_DERIVATION_PATH.to_string(),
             network: network.to_string(),
             alias,
             created_at: chrono::Utc::now(),
+            wallet_type: WalletType::Full,
+            xpub: None,
+            path_preset: None,
+            accounts: Vec::new(),
+            selected_account: None,
         })
     }
 
+    /// HD wallet from a hex-encoded 64-byte BIP39 seed, as exported by some tools in place of
+    /// the mnemonic. The seed is kept, encrypted with the rest of the payload, for derivation.
+    pub fn from_seed_hex(
+        seed_hex: &str,
+        network: &str,
+        alias: Option<String>
+    ) -> WalletResult<Self> {
+        let seed_hex = seed_hex.trim();
+        let seed_hex = seed_hex.strip_prefix("0x").unwrap_or(seed_hex);
+        if seed_hex.len() != config::bip39::SEED_LENGTH * 2 {
+            return Err(CryptographicError::InvalidPrivateKey {
+                detail: format!("Expected {} hex characters, got {}", config::bip39::SEED_LENGTH * 2, seed_hex.len()),
+                expected: "64-byte BIP39 seed as 128 hex characters".to_string(),
+            }
+            .into());
+        }
+        let seed = hex::decode(seed_hex).map_err(|e| CryptographicError::InvalidPrivateKey {
+            detail: e.to_string(),
+            expected: "64-byte BIP39 seed as 128 hex characters".to_string(),
+        })?;
+
+        Self::from_seed(&seed, network, alias)
+    }
+
+    /// HD wallet from a raw BIP32 seed of 16 to 64 bytes, e.g. one recovered from Shamir shares
+    pub fn from_seed(
+        seed: &[u8],
+        network: &str,
+        alias: Option<String>
+    ) -> WalletResult<Self> {
+        if !(config::bip32::MIN_SEED_LENGTH..=config::bip32::MAX_SEED_LENGTH).contains(&seed.len()) {
+            return Err(CryptographicError::InvalidPrivateKey {
+                detail: format!("Seed is {} bytes", seed.len()),
+                expected: format!(
+                    "BIP32 seed of {} to {} bytes",
+                    config::bip32::MIN_SEED_LENGTH,
+                    config::bip32::MAX_SEED_LENGTH
+                ),
+            }
+            .into());
+        }
+
+        let primary = format!("{}/0", config::DEFAULT_DERIVATION_PATH);
+        let wallet = derive_signer(&root_from_seed(seed)?, &primary)?;
+
+        Ok(Self{
+            mnemonic: LockedString::default(),
+            passphrase: LockedString::default(),
+            passphrase_protected: false,
+            master_private_key: Some(wallet.signer().to_bytes().to_vec()),
+            extended_key: None,
+            seed: Some(seed.to_vec()),
+            address: format!("{:?}", wallet.address()),
+            derivation_path: config::DEFAULT_DERIVATION_PATH.to_string(),
+            network: network.to_string(),
+            alias,
+            created_at: chrono::Utc::now(),
+            wallet_type: WalletType::Full,
+            xpub: None,
+            path_preset: None,
+            accounts: Vec::new(),
+            selected_account: None,
+        })
+    }
+
+    /// Watch-only wallet from an account-level xpub (`m/44'/60'/{account}'`, as `xpub` prints
+    /// it); addresses under `{account}'/0` are derived from public keys alone
+    pub fn watch_only_from_xpub(
+        xpub: &str,
+        network: &str,
+        alias: Option<String>
+    ) -> WalletResult<Self> {
+        let account = parse_account_xpub(xpub)?.1;
+
+        let mut wallet = Self{
+            mnemonic: LockedString::default(),
+            passphrase: LockedString::default(),
+            passphrase_protected: false,
+            master_private_key: None,
+            extended_key: None,
+            seed: None,
+            address: String::new(),
+            derivation_path: config::bip44_base_path(account, 0),
+            network: network.to_string(),
+            alias,
+            created_at: chrono::Utc::now(),
+            wallet_type: WalletType::WatchOnly,
+            xpub: Some(xpub.trim().to_string()),
+            path_preset: None,
+            accounts: Vec::new(),
+            selected_account: None,
+        };
+        wallet.address = wallet.derive_address(0)?.address;
+        Ok(wallet)
+    }
+
+    /// Watch-only wallet for a single address
+    pub fn watch_only_from_address(
+        address: &str,
+        network: &str,
+        alias: Option<String>
+    ) -> WalletResult<Self> {
+        let address = crate::services::transaction::parse_address("address", address.trim())?;
+
+        Ok(Self{
+            mnemonic: LockedString::default(),
+            passphrase: LockedString::default(),
+            passphrase_protected: false,
+            master_private_key: None,
+            extended_key: None,
+            seed: None,
+            address: format!("{:?}", address),
+            derivation_path: config::DEFAULT_DERIVATION_PATH.to_string(),
+            network: network.to_string(),
+            alias,
+            created_at: chrono::Utc::now(),
+            wallet_type: WalletType::WatchOnly,
+            xpub: None,
+            path_preset: None,
+            accounts: Vec::new(),
+            selected_account: None,
+        })
+    }
+
+    /// Seed the BIP32 root key is derived from: the stored seed, or the mnemonic's seed under
+    /// its passphrase. `None` for wallets imported from an xprv or a bare private key.
+    pub fn bip32_seed(&self) -> Option<Zeroizing<Vec<u8>>> {
+        if let Some(seed) = &self.seed {
+            return Some(Zeroizing::new(seed.clone()));
+        }
+        if !self.has_mnemonic() || self.needs_passphrase() {
+            return None;
+        }
+
+        MnemonicService::seed_of(&self.mnemonic, &self.passphrase).ok()
+    }
+
     pub fn has_mnemonic(&self) -> bool {
         !self.mnemonic.is_empty()
     }
-    
+
+    pub fn has_passphrase(&self) -> bool {
+        self.passphrase_protected || !self.passphrase.is_empty()
+    }
+
+    /// The BIP39 passphrase, empty when the seed takes none or it was not supplied yet
+    pub fn passphrase(&self) -> &str {
+        &self.passphrase
+    }
+
+    /// Whether the wallet came out of a keystore without the passphrase its seed takes
+    pub fn needs_passphrase(&self) -> bool {
+        self.passphrase_protected && self.passphrase.is_empty()
+    }
+
+    /// Supply the passphrase of a wallet loaded from a keystore. A passphrase that does not
+    /// give back the saved address is refused rather than opening another wallet.
+    pub fn unlock_passphrase(&mut self, passphrase: &str) -> WalletResult<()> {
+        self.passphrase = passphrase.into();
+        let matches = !passphrase.is_empty()
+            && self.signer().is_ok_and(|signer| format!("{:?}", signer.address()).eq_ignore_ascii_case(&self.address));
+        if !matches {
+            self.passphrase = LockedString::default();
+            return Err(AuthenticationError::WrongPassphrase {
+                address: self.address.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Keystores written before the passphrase stopped being saved still hold it in the
+    /// payload; mark it required so saving the wallet again drops it safely
+    pub(crate) fn upgrade_payload(&mut self) {
+        self.passphrase_protected |= !self.passphrase.is_empty();
+    }
+
+    /// Copy of the wallet as it goes into the keystore: without the passphrase, nor the
+    /// primary key derived under it, so the keystore password alone opens neither
+    pub(crate) fn to_payload(&self) -> Self {
+        let mut payload = self.clone();
+        if payload.passphrase_protected {
+            payload.master_private_key = None;
+        }
+        payload
+    }
+
+    /// Whether child addresses can be derived, from a mnemonic, a seed, a BIP32 root key or an xpub
+    pub fn is_hd(&self) -> bool {
+        self.has_mnemonic() || self.extended_key.is_some() || self.seed.is_some() || self.xpub.is_some()
+    }
+
+    pub fn wallet_type(&self) -> WalletType {
+        self.wallet_type
+    }
+
+    pub fn is_watch_only(&self) -> bool {
+        self.wallet_type == WalletType::WatchOnly
+    }
+
+    /// Move the wallet to `m/44'/60'/{account}'/{change}`, the primary address becomes index 0
+    /// under it. Lets users follow wallets that put funds outside account 0.
+    pub fn with_account(mut self, account: u32, change: u32) -> WalletResult<Self> {
+        if !self.is_hd() {
+            return Err(CryptographicError::KdfFailed {
+                details: "Cannot select an account on a private key only wallet".to_string(),
+            }
+            .into());
+        }
+        if self.is_watch_only() {
+            return Err(CryptographicError::KdfFailed {
+                details: "An xpub covers a single account, import the xpub of the account instead".to_string(),
+            }
+            .into());
+        }
+
+        let base_path = config::bip44_base_path(account, change);
+        let primary = self.hd_signer(&format!("{}/0", base_path), "valid BIP44 account and change")?;
+        self.master_private_key = Some(primary.signer().to_bytes().to_vec());
+        self.address = format!("{:?}", primary.address());
+        self.derivation_path = base_path;
+        self.path_preset = None;
+        Ok(self)
+    }
+
+    /// Move the wallet under an arbitrary parent path such as "m/44'/60'/7'/1", the primary
+    /// address becomes index 0 under it
+    pub fn with_base_path(mut self, base_path: &str) -> WalletResult<Self> {
+        if !self.is_hd() {
+            return Err(CryptographicError::KdfFailed {
+                details: "Cannot select a derivation path on a private key only wallet".to_string(),
+            }
+            .into());
+        }
+        if self.is_watch_only() {
+            return Err(CryptographicError::KdfFailed {
+                details: "An xpub covers a single account, its derivation path is fixed".to_string(),
+            }
+            .into());
+        }
+
+        let primary = self.hd_signer(&format!("{}/0", base_path), "valid derivation path")?;
+        self.master_private_key = Some(primary.signer().to_bytes().to_vec());
+        self.address = format!("{:?}", primary.address());
+        self.derivation_path = base_path.to_string();
+        self.path_preset = None;
+        Ok(self)
+    }
+
+    /// Move the wallet to the layout of `preset`, the primary address becomes its index 0
+    pub fn with_path_preset(mut self, preset: PathPreset) -> WalletResult<Self> {
+        if !self.is_hd() {
+            return Err(CryptographicError::KdfFailed {
+                details: "Cannot select a derivation path on a private key only wallet".to_string(),
+            }
+            .into());
+        }
+        if self.is_watch_only() {
+            return Err(CryptographicError::KdfFailed {
+                details: "An xpub covers a single account, its derivation path is fixed".to_string(),
+            }
+            .into());
+        }
+
+        let primary_path = preset.path(0);
+        let primary = self.hd_signer(&primary_path, "valid derivation path")?;
+        self.master_private_key = Some(primary.signer().to_bytes().to_vec());
+        self.address = format!("{:?}", primary.address());
+        self.derivation_path = match primary_path.rsplit_once('/') {
+            Some((parent, _)) if preset.is_flat() => parent.to_string(),
+            _ => config::DEFAULT_DERIVATION_PATH.to_string(),
+        };
+        self.path_preset = (!preset.is_flat()).then_some(preset);
+        Ok(self)
+    }
+
+    /// Layout the wallet derives in when it is not `derivation_path/{index}`
+    pub fn path_preset(&self) -> Option<PathPreset> {
+        self.path_preset
+    }
+
     pub fn derive_address(&self, index: u32)->WalletResult<DerivedAddress>{
-        if self.mnemonic.is_empty() {
+        if !self.is_hd() {
             return Err(CryptographicError::KdfFailed {
                 details: "Cannot derive addresses from private key only wallet".to_string(),
             }
             .into());
         }
 
-        let derivation_path = format!("{}/{}", self.derivation_path, index);
+        let derivation_path = match self.path_preset {
+            Some(preset) => preset.path(index),
+            None => format!("{}/{}", self.derivation_path, index),
+        };
+        let address = match &self.xpub {
+            Some(xpub) => ethers::utils::public_key_to_address(&xpub_child(xpub, &self.derivation_path, index)?),
+            None => self.hd_signer(&derivation_path, "valid BIP44 derivation path")?.address(),
+        };
 
-        let wallet = MnemonicBuilder::<English>::default()
-                    .phrase(self.mnemonic.as_str())
-                    .derivation_path(&derivation_path)
-                    .map_err(|_e| CryptographicError::InvalidDerivationPath {
-                        path: derivation_path.clone(),
-                        expected: "valid BIP44 derivation path".to_string(),
-                    })?
-                    .build()
-                    .map_err(|e|{
-                        CryptographicError::AddressGenerationFailed {
-                            details: e.to_string(),
-                        }
-                    })?;
+        Ok(DerivedAddress::new(format!("{:?}", address), index, derivation_path))
+    }
 
-        Ok(DerivedAddress{
-            address: format!("{:?}", wallet.address()),
-            index,
-            derivation_path,
-        })  
+    /// Derive a dedicated signing key for an application namespace such as "github.com"
+    pub fn derive_app_key(&self, app: &str, index: u32) -> WalletResult<DerivedAddress> {
+        if !self.is_hd() {
+            return Err(CryptographicError::KdfFailed {
+                details: "Cannot derive app keys from private key only wallet".to_string(),
+            }
+            .into());
+        }
+
+        let derivation_path = Self::app_key_path(app, index);
+        let wallet = self.hd_signer(&derivation_path, "valid app key derivation path")?;
+
+        Ok(DerivedAddress::new(format!("{:?}", wallet.address()), index, derivation_path))
+    }
+
+    /// `m/44'/60'/{account}'`, the BIP44 account level above the wallet's change path
+    pub fn account_path(&self) -> &str {
+        self.derivation_path.rsplit_once('/').map_or(self.derivation_path.as_str(), |(account, _)| account)
+    }
+
+    /// Extended public key (xpub) at `account_path`. Watch-only tools derive `{change}/{index}`
+    /// from it, so it reveals every address of the account but no private key.
+    pub fn account_xpub(&self) -> WalletResult<String> {
+        if let Some(xpub) = &self.xpub {
+            return Ok(xpub.clone());
+        }
+        if !self.is_hd() {
+            return Err(CryptographicError::KdfFailed {
+                details: "Cannot derive an xpub from private key only wallet".to_string(),
+            }
+            .into());
+        }
+        // Ledger Live and legacy layouts have no account xpub that covers their addresses
+        if self.path_preset.is_some() || self.derivation_path.split('/').count() != 5 {
+            return Err(CryptographicError::KdfFailed {
+                details: "Only BIP44 layouts (m/44'/60'/{account}'/{change}/{index}) have an account xpub".to_string(),
+            }
+            .into());
+        }
+
+        let root = match &self.extended_key {
+            Some(xprv) => parse_xprv(xprv)?,
+            None => {
+                let seed = self.bip32_seed().ok_or_else(|| CryptographicError::KdfFailed {
+                    details: "Wallet has no BIP32 seed".to_string(),
+                })?;
+                root_from_seed(&seed)?
+            }
+        };
+        let account = root.derive_path(self.account_path()).map_err(|_e| CryptographicError::InvalidDerivationPath {
+            path: self.account_path().to_string(),
+            expected: "valid BIP44 account path".to_string(),
+        })?;
+        MainnetEncoder::xpub_to_base58(&account.verify_key()).map_err(|e| {
+            CryptographicError::AddressGenerationFailed {
+                details: e.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Compressed SEC1 public key of the address at `index`, as hex
+    pub fn public_key_at(&self, index: u32) -> WalletResult<String> {
+        let derived = self.derive_address(index)?;
+        let point = match &self.xpub {
+            Some(xpub) => xpub_child(xpub, &self.derivation_path, index)?.to_encoded_point(true),
+            None => {
+                let signer = self.hd_signer(derived.derivation_path(), "valid BIP44 derivation path")?;
+                signer.signer().verifying_key().to_encoded_point(true)
+            }
+        };
+        Ok(format!("0x{}", hex::encode(point.as_bytes())))
+    }
+
+    /// Private key at `derivation_path`, the `k` BIP85 turns into child entropy
+    pub(crate) fn hd_private_key(&self, derivation_path: &str) -> WalletResult<Zeroizing<Vec<u8>>> {
+        if !self.is_hd() {
+            return Err(CryptographicError::KdfFailed {
+                details: "Cannot derive child entropy from private key only wallet".to_string(),
+            }
+            .into());
+        }
+
+        let wallet = self.hd_signer(derivation_path, "valid BIP85 derivation path")?;
+        Ok(Zeroizing::new(wallet.signer().to_bytes().to_vec()))
+    }
+
+    /// Private key only wallet holding the key at `index` of the account and nothing else,
+    /// so automation can sign for that one address without the mnemonic
+    pub fn delegate(&self, index: u32) -> WalletResult<Self> {
+        let derived = self.derive_address(index)?;
+        let key = self.hd_private_key(derived.derivation_path())?;
+        Ok(self.key_only(&key, derived.address()))
+    }
+
+    /// Private key only wallet holding the app key of `derive_app_key`, to sign for the app
+    /// without handing out the funds key
+    pub fn app_key_wallet(&self, app: &str, index: u32) -> WalletResult<Self> {
+        let derived = self.derive_app_key(app, index)?;
+        let key = self.hd_private_key(derived.derivation_path())?;
+        Ok(self.key_only(&key, derived.address()))
+    }
+
+    /// Wallet with `key` at `address` as its only key, on this wallet's network
+    fn key_only(&self, key: &[u8], address: &str) -> Self {
+        Self {
+            mnemonic: LockedString::default(),
+            passphrase: LockedString::default(),
+            passphrase_protected: false,
+            master_private_key: Some(key.to_vec()),
+            extended_key: None,
+            seed: None,
+            address: address.to_string(),
+            derivation_path: config::DEFAULT_DERIVATION_PATH.to_string(),
+            network: self.network.clone(),
+            alias: None,
+            created_at: chrono::Utc::now(),
+            wallet_type: WalletType::Full,
+            xpub: None,
+            path_preset: None,
+            accounts: Vec::new(),
+            selected_account: None,
+        }
+    }
+
+    /// `m/13'/a'/b'/c'/d'/index'`, where a..d are the first 16 bytes of sha256(app) split
+    /// into four 31-bit hardened components, so every app lives in its own subtree
+    pub fn app_key_path(app: &str, index: u32) -> String {
+        use sha2::{Digest, Sha256};
+
+        let digest = Sha256::digest(app.as_bytes());
+        let components: Vec<String> = digest[..16]
+            .chunks(4)
+            .map(|c| {
+                let n = u32::from_be_bytes([c[0], c[1], c[2], c[3]]) & 0x7fff_ffff;
+                format!("{}'", n)
+            })
+            .collect();
+
+        format!("{}/{}/{}'", config::APP_KEY_PURPOSE_PATH, components.join("/"), index)
+    }
+
+    /// Signer at `derivation_path` under the mnemonic, the raw seed or the BIP32 root key
+    fn hd_signer(&self, derivation_path: &str, expected: &str) -> WalletResult<LocalWallet> {
+        if self.is_watch_only() {
+            return Err(self.watch_only_error());
+        }
+        if self.needs_passphrase() {
+            return Err(AuthenticationError::WrongPassphrase {
+                address: self.address.clone(),
+            }
+            .into());
+        }
+        if let Some(xprv) = &self.extended_key {
+            return derive_signer(&parse_xprv(xprv)?, derivation_path);
+        }
+        if let Some(seed) = &self.seed {
+            return derive_signer(&root_from_seed(seed)?, derivation_path);
+        }
+        // Other wordlists go through the BIP39 seed, like wallets imported from one
+        if MnemonicService::detect_language(&self.mnemonic)? != bip39::Language::English {
+            let seed = MnemonicService::seed_of(&self.mnemonic, &self.passphrase)?;
+            return derive_signer(&root_from_seed(&seed)?, derivation_path);
+        }
+
+        MnemonicBuilder::<English>::default()
+            .phrase(self.mnemonic.as_str())
+            .password(self.passphrase.as_str())
+            .derivation_path(derivation_path)
+            .map_err(|_e| CryptographicError::InvalidDerivationPath {
+                path: derivation_path.to_string(),
+                expected: expected.to_string(),
+            })?
+            .build()
+            .map_err(|e|{
+                CryptographicError::AddressGenerationFailed {
+                    details: e.to_string(),
+                }
+                .into()
+            })
+    }
+
+    /// Signer for the wallet's primary address
+    pub fn signer(&self) -> WalletResult<LocalWallet> {
+        if self.is_watch_only() {
+            return Err(self.watch_only_error());
+        }
+        if !self.mnemonic.is_empty() {
+            let primary = format!("{}/0", self.derivation_path);
+            return self.hd_signer(&primary, "valid BIP44 derivation path");
+        }
+
+        let key = self.master_private_key.as_ref().ok_or_else(|| CryptographicError::SigningFailed {
+            details: "Wallet has no signing key available".to_string(),
+        })?;
+
+        LocalWallet::from_bytes(key).map_err(|e| {
+            CryptographicError::InvalidPrivateKey {
+                detail: e.to_string(),
+                expected: "valid secp256k1 private key".to_string(),
+            }
+            .into()
+        })
+    }
+
+    fn watch_only_error(&self) -> crate::errors::WalletError {
+        AuthenticationError::WatchOnlyWallet {
+            address: self.address.clone(),
+        }
+        .into()
     }
 
     pub fn alias(&self) -> Option<&str> {
         self.alias.as_deref()
     }
+    pub fn set_alias(&mut self, alias: Option<String>) {
+        self.alias = alias;
+    }
     pub fn address(&self) -> &str {
         &self.address
     }
@@ -151,12 +861,136 @@ impl Wallet {
         self.created_at
     }
 
+    /// Accounts added besides the primary one, in the order they were added
+    pub fn accounts(&self) -> &[Account] {
+        &self.accounts
+    }
+
+    /// Name of the selected account, None while the primary one is
+    pub fn selected_account(&self) -> Option<&str> {
+        self.selected_account.as_deref()
+    }
+
+    /// Add an account derived under `base_path`, by default the first BIP44 account no other
+    /// account of the wallet uses
+    pub fn add_derived_account(&mut self, name: &str, base_path: Option<&str>) -> WalletResult<&Account> {
+        self.check_account_name(name)?;
+        let base_path = match base_path {
+            Some(base_path) => base_path.to_string(),
+            None => (1..=config::MAX_BIP32_INDEX)
+                .map(|account| config::bip44_base_path(account, 0))
+                .find(|path| *path != self.derivation_path && self.accounts.iter().all(|a| a.derivation_path() != Some(path.as_str())))
+                .ok_or_else(|| CryptographicError::KdfFailed { details: "Every BIP44 account is in use".to_string() })?,
+        };
+        let derived = self.clone().with_base_path(&base_path)?;
+        self.push_account(Account {
+            name: name.to_string(),
+            address: derived.address.clone(),
+            derivation_path: Some(base_path),
+            private_key: None,
+        })
+    }
+
+    /// Add an account for an imported private key, kept encrypted with the rest of the wallet
+    pub fn add_imported_account(&mut self, name: &str, private_key: &str) -> WalletResult<&Account> {
+        self.check_account_name(name)?;
+        let imported = Self::from_private_key(private_key, &self.network, None)?;
+        self.push_account(Account {
+            name: name.to_string(),
+            address: imported.address.clone(),
+            derivation_path: None,
+            private_key: imported.master_private_key.clone(),
+        })
+    }
+
+    /// Make the account `name` the one that signs, or the primary one for `PRIMARY_ACCOUNT_NAME`
+    pub fn select_account(&mut self, name: &str) -> WalletResult<()> {
+        if name == config::wallet::PRIMARY_ACCOUNT_NAME {
+            self.selected_account = None;
+            return Ok(());
+        }
+        self.account(name)?;
+        self.selected_account = Some(name.to_string());
+        Ok(())
+    }
+
+    /// The selected account as a wallet of its own: the wallet moved to a derived account's
+    /// path, or a private key only wallet for an imported one. Commands that sign or show an
+    /// address work on this; only `account` sees the others.
+    pub fn active_account(&self) -> WalletResult<Self> {
+        let Some(name) = &self.selected_account else {
+            return Ok(self.clone());
+        };
+        let account = self.account(name)?;
+        let mut wallet = match (&account.derivation_path, &account.private_key) {
+            (Some(base_path), _) => self.clone().with_base_path(base_path)?,
+            (None, Some(key)) => Self::from_private_key(&Zeroizing::new(hex::encode(key)), &self.network, None)?,
+            (None, None) => {
+                return Err(CryptographicError::DataCorruption {
+                    details: format!("Account {} has neither a derivation path nor a key", name),
+                }
+                .into())
+            }
+        };
+        wallet.accounts.clear();
+        wallet.selected_account = None;
+        wallet.alias = self.alias.clone();
+        wallet.created_at = self.created_at;
+        Ok(wallet)
+    }
+
+    fn account(&self, name: &str) -> WalletResult<&Account> {
+        self.accounts.iter().find(|a| a.name == name).ok_or_else(|| {
+            crate::errors::UserInputError::InvalidParameters {
+                parameter: "account".to_string(),
+                value: name.to_string(),
+                expected: format!(
+                    "{} or an account of this wallet: {}",
+                    config::wallet::PRIMARY_ACCOUNT_NAME,
+                    self.accounts.iter().map(|a| a.name.as_str()).collect::<Vec<_>>().join(", ")
+                ),
+            }
+            .into()
+        })
+    }
+
+    fn check_account_name(&self, name: &str) -> WalletResult<()> {
+        let taken = name == config::wallet::PRIMARY_ACCOUNT_NAME || self.accounts.iter().any(|a| a.name == name);
+        if name.trim().is_empty() || name.len() > config::wallet::MAX_ACCOUNT_NAME_LENGTH || taken {
+            return Err(crate::errors::UserInputError::InvalidParameters {
+                parameter: "name".to_string(),
+                value: name.to_string(),
+                expected: format!(
+                    "a new account name of at most {} characters, other than {}",
+                    config::wallet::MAX_ACCOUNT_NAME_LENGTH,
+                    config::wallet::PRIMARY_ACCOUNT_NAME
+                ),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    fn push_account(&mut self, account: Account) -> WalletResult<&Account> {
+        let same = |address: &str| address.eq_ignore_ascii_case(&account.address);
+        if same(&self.address) || self.accounts.iter().any(|a| same(&a.address)) {
+            return Err(crate::errors::UserInputError::InvalidParameters {
+                parameter: "account".to_string(),
+                value: account.address.clone(),
+                expected: "an address not already in the wallet".to_string(),
+            }
+            .into());
+        }
+        self.accounts.push(account);
+        Ok(&self.accounts[self.accounts.len() - 1])
+    }
+
     pub fn validate(&self) -> WalletResult<()> {
         // Validate address format
         crate::utils::validate_ethereum_address(&self.address)?;
 
         // Validate network
-        if !config::is_supported_network(&self.network) {
+        if !config::is_valid_network_name(&self.network) {
             return Err(CryptographicError::KdfFailed {
                 details: format!("Unsupported network: {}", self.network),
             }
@@ -180,14 +1014,104 @@ impl Wallet {
     }
 }
 
+fn parse_xprv(xprv: &str) -> WalletResult<XPriv> {
+    let invalid = |detail: String| CryptographicError::InvalidPrivateKey {
+        detail,
+        expected: "base58 BIP32 root key (xprv...)".to_string(),
+    };
+
+    // Serialized keys are version(4) || depth(1) || fingerprint(4) || index(4) || chain code(32) || key(33)
+    let payload = bs58::decode(xprv.trim()).with_check(None).into_vec().map_err(|e| invalid(e.to_string()))?;
+    match payload.get(4) {
+        Some(0) => {}
+        Some(depth) => return Err(invalid(format!("extended key is at depth {}, not a root key", depth)).into()),
+        None => return Err(invalid("extended key is truncated".to_string()).into()),
+    }
+
+    MainnetEncoder::xpriv_from_base58(xprv.trim()).map_err(|e| invalid(e.to_string()).into())
+}
+
+/// Parse an account-level xpub, returning it with its (hardened) account number
+fn parse_account_xpub(xpub: &str) -> WalletResult<(XPub, u32)> {
+    let invalid = |detail: String| CryptographicError::InvalidPrivateKey {
+        detail,
+        expected: "account-level xpub (m/44'/60'/{account}'), e.g. from `web3wallet xpub`".to_string(),
+    };
+
+    // Same layout as in `parse_xprv`; the child number of an account key is its hardened account
+    let payload = bs58::decode(xpub.trim()).with_check(None).into_vec().map_err(|e| invalid(e.to_string()))?;
+    if payload.len() < 13 {
+        return Err(invalid("extended key is truncated".to_string()).into());
+    }
+    if payload[4] != 3 {
+        return Err(invalid(format!("extended key is at depth {}, not an account key", payload[4])).into());
+    }
+    let child = u32::from_be_bytes([payload[9], payload[10], payload[11], payload[12]]);
+    if child < 1 << 31 {
+        return Err(invalid("account keys are hardened".to_string()).into());
+    }
+
+    let key = MainnetEncoder::xpub_from_base58(xpub.trim()).map_err(|e| invalid(e.to_string()))?;
+    Ok((key, child - (1 << 31)))
+}
+
+/// Public key at `{change}/{index}` under an account xpub, `change` being the last component
+/// of `base_path`
+fn xpub_child(xpub: &str, base_path: &str, index: u32) -> WalletResult<VerifyingKey> {
+    let (account, _) = parse_account_xpub(xpub)?;
+    let change = base_path
+        .rsplit('/')
+        .next()
+        .and_then(|change| change.parse::<u32>().ok())
+        .ok_or_else(|| CryptographicError::InvalidDerivationPath {
+            path: base_path.to_string(),
+            expected: "unhardened change level under an xpub".to_string(),
+        })?;
+
+    let child = account.derive_child(change).and_then(|key| key.derive_child(index)).map_err(|_e| {
+        CryptographicError::InvalidDerivationPath {
+            path: format!("{}/{}", base_path, index),
+            expected: "unhardened index under an xpub".to_string(),
+        }
+    })?;
+    let key: &VerifyingKey = child.as_ref();
+    Ok(*key)
+}
+
+fn root_from_seed(seed: &[u8]) -> WalletResult<XPriv> {
+    // The hint picks the version bytes, Legacy serializes as xprv/xpub
+    XPriv::root_from_seed(seed, Some(Hint::Legacy)).map_err(|e| {
+        CryptographicError::AddressGenerationFailed {
+            details: e.to_string(),
+        }
+        .into()
+    })
+}
+
+fn derive_signer(root: &XPriv, derivation_path: &str) -> WalletResult<LocalWallet> {
+    let child = root.derive_path(derivation_path).map_err(|_e| CryptographicError::InvalidDerivationPath {
+        path: derivation_path.to_string(),
+        expected: "valid BIP32 derivation path".to_string(),
+    })?;
+    let key: &SigningKey = child.as_ref();
+    Ok(LocalWallet::from(key.clone()))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DerivedAddress{
     address: String,
     index: u32,
     derivation_path: String,
+    #[serde(default)]
+    address_chain: Option<AddressChain>,
 }
 
 impl DerivedAddress {
+    pub fn new(address: String, index: u32, derivation_path: String) -> Self {
+        let address_chain = AddressChain::of_path(&derivation_path);
+        Self { address, index, derivation_path, address_chain }
+    }
+
     /// Get address
     pub fn address(&self) -> &str {
         &self.address
@@ -202,4 +1126,9 @@ impl DerivedAddress {
     pub fn derivation_path(&self) -> &str {
         &self.derivation_path
     }
+
+    /// External (receive) or internal (change) chain, for BIP44 paths
+    pub fn address_chain(&self) -> Option<AddressChain> {
+        self.address_chain
+    }
 }
\ No newline at end of file
@@ -0,0 +1,30 @@
+//! Supported library surface.
+//!
+//! Everything re-exported here follows semver; other public paths may move
+//! between minor releases as the CLI evolves.
+//!
+//! ```no_run
+//! use web3wallet_cli::prelude::*;
+//! ```
+
+pub use crate::errors::{
+    AuthenticationError, CryptographicError, FilesystemError, NetworkError, UserInputError,
+    ValidationError, WalletError, WalletResult,
+};
+pub use crate::models::command::{
+    CreateOutcome, DeleteOutcome, DeriveOutcome, DerivedEntry, ExportOutcome, ImportOutcome,
+    KeystoreSummary, ListOutcome, LoadOutcome, MigrateOutcome, PasswordChangeOutcome, RenameOutcome,
+    WalletEntry,
+};
+pub use crate::models::wallet::DerivedAddress;
+pub use crate::models::{Keystore, KeystoreFile, KeystoreV3, Wallet, WalletType};
+#[cfg(feature = "native")]
+pub use crate::services::commands::{self, ImportSource, SaveOptions};
+#[cfg(feature = "native")]
+pub use crate::services::signer::LedgerSigner;
+pub use crate::services::signer::{Signer, SignerKind, SoftwareSigner};
+pub use crate::services::transaction::{SignedTransaction, UnsignedTransaction};
+pub use crate::services::{CryptoService, MnemonicService, TransactionService};
+#[cfg(feature = "native")]
+pub use crate::services::{RpcClient, WalletManager};
+pub use crate::WalletConfig;
@@ -0,0 +1,204 @@
+//! Wallet methods of `web3wallet serve`, answered over `services::rpc_server`. Wallets are named
+//! by file name in the wallets directory; signing uses the keys a running agent holds, or the
+//! password passed with the request.
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use web3wallet_cli::errors::{FilesystemError, UserInputError};
+use web3wallet_cli::services::chains::ChainInfo;
+use web3wallet_cli::services::commands;
+use web3wallet_cli::services::history::HistoryEntry;
+use web3wallet_cli::services::rate_limit::SigningRateLimiter;
+use web3wallet_cli::services::rpc_server::{RpcFault, RpcHandler};
+use web3wallet_cli::services::signer::{Signer, SoftwareSigner};
+use web3wallet_cli::services::transaction::UnsignedTransaction;
+use web3wallet_cli::services::usage::UsageKind;
+use web3wallet_cli::{WalletConfig, WalletError, WalletManager, WalletResult};
+use zeroize::Zeroizing;
+
+pub const METHODS: &[&str] = &["wallet_list", "wallet_derive", "wallet_signMessage", "wallet_signTransaction"];
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NoParams {}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeriveParams {
+    wallet: String,
+    #[serde(default)]
+    start: u32,
+    #[serde(default = "default_count")]
+    count: u32,
+    /// Parent of the derived addresses, like `derive --path` without the index
+    base_path: Option<String>,
+    password: Option<String>,
+    /// BIP39 passphrase of a wallet whose seed takes one; it is not in the keystore
+    passphrase: Option<String>,
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SignMessageParams {
+    wallet: String,
+    message: String,
+    /// `message` is hex encoded bytes rather than text
+    #[serde(default)]
+    hex: bool,
+    password: Option<String>,
+    passphrase: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SignTransactionParams {
+    wallet: String,
+    /// Same fields as the `sign-tx --tx-json` file, fees included
+    tx: UnsignedTransaction,
+    note: Option<String>,
+    password: Option<String>,
+    passphrase: Option<String>,
+}
+
+pub struct WalletMethods {
+    config: WalletConfig,
+    manager: WalletManager,
+    /// `[rate_limit]` of the config file, per wallet and across all of them
+    limiter: SigningRateLimiter,
+}
+
+impl WalletMethods {
+    pub fn new(config: &WalletConfig) -> Self {
+        Self {
+            config: config.clone(),
+            manager: WalletManager::new(config.clone()),
+            limiter: SigningRateLimiter::from_config(config),
+        }
+    }
+
+    async fn derive(&self, params: DeriveParams) -> Result<Value, RpcFault> {
+        let max = web3wallet_cli::config::serve::MAX_DERIVE_COUNT;
+        if params.count == 0 || params.count > max {
+            return Err(RpcFault::invalid_params(format!("count must be between 1 and {}", max)));
+        }
+        let file_path = self.wallet_path(&params.wallet)?;
+        let signer = self.signer(&file_path, params.base_path, params.password, params.passphrase).await?;
+        result(&commands::derive(signer.as_ref(), params.start, params.count).await?)
+    }
+
+    async fn sign_message(&self, params: SignMessageParams) -> Result<Value, RpcFault> {
+        let message = if params.hex {
+            hex::decode(params.message.strip_prefix("0x").unwrap_or(&params.message))
+                .map_err(|_| RpcFault::invalid_params("message is not hex encoded bytes"))?
+        } else {
+            params.message.into_bytes()
+        };
+        let file_path = self.wallet_path(&params.wallet)?;
+        self.manager.check_can_sign(&file_path).await?;
+        self.limiter.acquire(&params.wallet)?;
+
+        let signer = self.signer(&file_path, None, params.password, params.passphrase).await?;
+        let address = signer.address().await?;
+        let signature = signer.sign_message(&message).await?;
+        crate::record_usage(&self.config, &address, &[UsageKind::Sign]).await;
+        Ok(json!({ "signer": crate::display_address(&address), "signature": signature }))
+    }
+
+    async fn sign_transaction(&self, params: SignTransactionParams) -> Result<Value, RpcFault> {
+        let unsigned = params.tx;
+        // Estimating fees would make a local call wait on the network
+        if unsigned.needs_fees() {
+            return Err(RpcFault::invalid_params(
+                "tx needs max_fee_per_gas and max_priority_fee_per_gas, or gas_price with legacy",
+            ));
+        }
+        let file_path = self.wallet_path(&params.wallet)?;
+        self.manager.check_can_sign(&file_path).await?;
+        self.manager.check_signing_chain(&file_path, unsigned.chain_id).await?;
+        self.limiter.acquire(&params.wallet)?;
+
+        let signer = self.signer(&file_path, None, params.password, params.passphrase).await?;
+        let signed = signer.sign_transaction(&unsigned).await?;
+        crate::record_usage(&self.config, &signed.from, &[UsageKind::Sign]).await;
+
+        let chain = ChainInfo::by_chain_id(unsigned.chain_id, &self.config);
+        let mut entry = HistoryEntry::new(
+            &chain.as_ref().map_or_else(|| format!("chain {}", unsigned.chain_id), |c| c.name.clone()),
+            chain.as_ref().map_or(web3wallet_cli::config::chains::DEFAULT_CURRENCY_SYMBOL, |c| c.currency_symbol.as_str()),
+            &unsigned,
+            &signed,
+            false,
+        );
+        entry.note = params.note;
+        crate::record_history(&self.config, entry).await;
+
+        Ok(json!({ "from": crate::display_address(&signed.from), "hash": signed.hash, "raw": signed.raw }))
+    }
+
+    /// Only file names in the wallets directory; a path could point the server at any file
+    fn wallet_path(&self, wallet: &str) -> WalletResult<PathBuf> {
+        if wallet.is_empty() || wallet.starts_with('.') || wallet.contains('/') || wallet.contains('\\') {
+            return Err(FilesystemError::PathTraversal { path: wallet.to_string() }.into());
+        }
+        Ok(self.config.wallets_path.join(wallet))
+    }
+
+    /// The keys a running agent holds for `file_path`, else the wallet decrypted with `password`
+    /// and, when its seed takes one, `passphrase`
+    async fn signer(
+        &self,
+        file_path: &Path,
+        base_path: Option<String>,
+        password: Option<String>,
+        passphrase: Option<String>,
+    ) -> WalletResult<Box<dyn Signer>> {
+        if let Some(signer) = crate::agent_signer(&self.manager, file_path, base_path.clone(), &self.config).await {
+            return Ok(Box::new(signer));
+        }
+        let password = Zeroizing::new(password.ok_or_else(|| UserInputError::MissingParameter {
+            parameter: "password".to_string(),
+            hint: "unlock the wallet once while `web3wallet agent` runs, or pass its password".to_string(),
+        })?);
+        let passphrase = passphrase.map(Zeroizing::new);
+        let wallet = self
+            .manager
+            .load_wallet_with_passphrase(file_path, &password, passphrase.as_deref().map(String::as_str))
+            .await?;
+        crate::record_usage(&self.config, wallet.address(), &[UsageKind::Unlock]).await;
+        let wallet = match base_path {
+            Some(base_path) => wallet.with_base_path(&base_path)?,
+            None => wallet,
+        };
+        Ok(Box::new(SoftwareSigner::new(wallet)))
+    }
+}
+
+#[async_trait]
+impl RpcHandler for WalletMethods {
+    async fn call(&self, method: &str, params: Value) -> Result<Value, RpcFault> {
+        match method {
+            "wallet_list" => {
+                parse::<NoParams>(params)?;
+                result(&commands::list(&self.config.wallets_path).await?)
+            }
+            "wallet_derive" => self.derive(parse(params)?).await,
+            "wallet_signMessage" => self.sign_message(parse(params)?).await,
+            "wallet_signTransaction" => self.sign_transaction(parse(params)?).await,
+            _ => Err(RpcFault::method_not_found(method)),
+        }
+    }
+}
+
+fn parse<T: DeserializeOwned>(params: Value) -> Result<T, RpcFault> {
+    serde_json::from_value(params).map_err(RpcFault::invalid_params)
+}
+
+fn result<T: Serialize>(outcome: &T) -> Result<Value, RpcFault> {
+    Ok(serde_json::to_value(outcome).map_err(WalletError::from)?)
+}
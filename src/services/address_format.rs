@@ -0,0 +1,143 @@
+use crate::errors::{CryptographicError, UserInputError, WalletResult};
+use bech32::{u5, ToBase32, Variant};
+use std::str::FromStr;
+
+/// Chain families with their own canonical address encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chain {
+    Evm,
+    Bitcoin,
+    Solana,
+}
+
+impl Chain {
+    pub const ALL: &'static [&'static str] = &["evm", "btc", "sol"];
+}
+
+impl FromStr for Chain {
+    type Err = crate::errors::WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "evm" | "eth" | "ethereum" => Ok(Chain::Evm),
+            "btc" | "bitcoin" => Ok(Chain::Bitcoin),
+            "sol" | "solana" => Ok(Chain::Solana),
+            _ => Err(UserInputError::InvalidParameters {
+                parameter: "chain".to_string(),
+                value: s.to_string(),
+                expected: format!("one of {:?}", Chain::ALL),
+            }
+            .into()),
+        }
+    }
+}
+
+/// Renders raw address bytes in the canonical text format of a chain
+pub trait AddressFormatter: Send + Sync {
+    fn chain(&self) -> Chain;
+
+    /// Format raw address bytes (20-byte hash for EVM/BTC, 32-byte public key for SOL)
+    fn format(&self, raw: &[u8]) -> WalletResult<String>;
+
+    /// Format an address given as hex, with or without `0x` prefix
+    fn format_hex(&self, address: &str) -> WalletResult<String> {
+        let raw = hex::decode(address.strip_prefix("0x").unwrap_or(address)).map_err(|e| {
+            CryptographicError::InvalidAddressFormat {
+                details: format!("Invalid address hex '{}': {}", address, e),
+                suggestion: "Provide the address as hex, e.g. 0xabc...".to_string(),
+            }
+        })?;
+        self.format(&raw)
+    }
+}
+
+/// EIP-55 mixed-case checksum encoding for EVM chains
+pub struct Eip55Formatter;
+
+impl AddressFormatter for Eip55Formatter {
+    fn chain(&self) -> Chain {
+        Chain::Evm
+    }
+
+    fn format(&self, raw: &[u8]) -> WalletResult<String> {
+        expect_len(raw, 20, "EVM")?;
+        crate::utils::to_checksum_address(&hex::encode(raw))
+    }
+}
+
+/// Bech32 segwit v0 (P2WPKH) encoding for Bitcoin
+pub struct Bech32Formatter {
+    hrp: String,
+}
+
+impl Bech32Formatter {
+    pub fn new(hrp: &str) -> Self {
+        Self { hrp: hrp.to_string() }
+    }
+}
+
+impl Default for Bech32Formatter {
+    fn default() -> Self {
+        Self::new("bc")
+    }
+}
+
+impl AddressFormatter for Bech32Formatter {
+    fn chain(&self) -> Chain {
+        Chain::Bitcoin
+    }
+
+    fn format(&self, raw: &[u8]) -> WalletResult<String> {
+        expect_len(raw, 20, "BTC")?;
+        let mut data = vec![u5::try_from_u8(0).expect("0 is a valid u5")];
+        data.extend(raw.to_base32());
+
+        bech32::encode(&self.hrp, data, Variant::Bech32).map_err(|e| {
+            CryptographicError::AddressGenerationFailed {
+                details: format!("Bech32 encoding failed: {}", e),
+            }
+            .into()
+        })
+    }
+}
+
+/// Base58 encoding of the ed25519 public key for Solana
+pub struct Base58Formatter;
+
+impl AddressFormatter for Base58Formatter {
+    fn chain(&self) -> Chain {
+        Chain::Solana
+    }
+
+    fn format(&self, raw: &[u8]) -> WalletResult<String> {
+        expect_len(raw, 32, "SOL")?;
+        Ok(bs58::encode(raw).into_string())
+    }
+}
+
+/// Formatter used by every output path for the given chain
+pub fn formatter_for(chain: Chain) -> Box<dyn AddressFormatter> {
+    match chain {
+        Chain::Evm => Box::new(Eip55Formatter),
+        Chain::Bitcoin => Box::new(Bech32Formatter::default()),
+        Chain::Solana => Box::new(Base58Formatter),
+    }
+}
+
+/// Canonical display form of `address`; falls back to the input when it cannot be decoded
+pub fn display(chain: Chain, address: &str) -> String {
+    formatter_for(chain)
+        .format_hex(address)
+        .unwrap_or_else(|_| address.to_string())
+}
+
+fn expect_len(raw: &[u8], len: usize, chain: &str) -> WalletResult<()> {
+    if raw.len() != len {
+        return Err(CryptographicError::InvalidAddressFormat {
+            details: format!("{} address must be {} bytes, got {}", chain, len, raw.len()),
+            suggestion: format!("Pass a {}-byte {} address", len, chain),
+        }
+        .into());
+    }
+    Ok(())
+}
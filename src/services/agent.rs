@@ -0,0 +1,743 @@
+//! Optional background agent caching wallet passwords for a limited time, so a series of commands
+//! unlocks each wallet once. A pid file and a ping over the agent's socket tell whether one is
+//! running; commands use it when it answers and prompt as before otherwise. Unix only.
+//!
+//! The agent also signs: it decrypts a keystore whose password it holds once, keeps the keys in
+//! locked memory and answers derive and sign requests with them, so later `sign-tx` or `derive`
+//! runs skip the KDF. Started with a confirmation hook, it asks before every signature.
+
+use crate::config::agent as limits;
+use crate::errors::{FilesystemError, ValidationError, WalletError, WalletResult};
+use crate::models::wallet::DerivedAddress;
+use crate::models::Wallet;
+use crate::services::locked_memory::{LockedBuffer, LockedString};
+use crate::services::shutdown::Shutdown;
+#[cfg(unix)]
+use crate::services::shutdown::ShutdownSignal;
+use crate::services::signer::Signer;
+#[cfg(unix)]
+use crate::services::signing::{SignedPayload, SigningService};
+#[cfg(unix)]
+use crate::services::transaction::TransactionService;
+use crate::services::transaction::{SignedTransaction, UnsignedTransaction};
+#[cfg(unix)]
+use crate::services::walletmanager::WalletManager;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+/// One request per line on the agent socket, answered by one `AgentResponse` line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum AgentRequest {
+    Ping,
+    Get { path: PathBuf },
+    /// Cache the password of a keystore, with the BIP39 passphrase its seed takes if any
+    Put {
+        path: PathBuf,
+        password: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        passphrase: Option<String>,
+    },
+    /// Drop the password and keys of one keystore, or all of them
+    Forget { path: Option<PathBuf> },
+    /// Open the keystore with the keys held, or its cached password, and describe the account;
+    /// `base_path` re-roots derivation like `derive --path`
+    Session { path: PathBuf, base_path: Option<String> },
+    Derive { path: PathBuf, base_path: Option<String>, index: u32 },
+    SignTransaction { path: PathBuf, tx: UnsignedTransaction },
+    /// EIP-191 message, hex encoded
+    SignMessage { path: PathBuf, message: String },
+    Stop,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AgentResponse {
+    Pong {
+        pid: u32,
+        cached: usize,
+        ttl_secs: u64,
+        /// Keystores whose keys are held decrypted
+        #[serde(default)]
+        keys: usize,
+        /// Whether every signature is confirmed at the agent's terminal
+        #[serde(default)]
+        confirm: bool,
+    },
+    Password { password: Option<String> },
+    Session { address: String, base_path: String },
+    Derived { address: DerivedAddress },
+    SignedTransaction { tx: SignedTransaction },
+    Signature { signature: String },
+    /// Neither keys nor a password are held for the keystore
+    Locked,
+    Ok,
+    Error { message: String },
+}
+
+/// Asked with a summary of each signing request when the agent confirms them; `true` signs
+pub type Confirm = Arc<dyn Fn(String) -> bool + Send + Sync>;
+
+/// Pid file and socket of the agent serving one state directory
+#[derive(Debug, Clone)]
+pub struct AgentPaths {
+    pub pid_file: PathBuf,
+    pub socket: PathBuf,
+}
+
+impl AgentPaths {
+    pub fn in_state_dir(state_dir: &Path) -> Self {
+        Self {
+            pid_file: state_dir.join(limits::PID_FILE_NAME),
+            socket: state_dir.join(limits::SOCKET_FILE_NAME),
+        }
+    }
+}
+
+/// Passwords by keystore path, shared by every connection of the agent
+#[derive(Clone)]
+pub struct PasswordCache {
+    entries: Arc<Mutex<HashMap<PathBuf, CachedPassword>>>,
+    ttl: Duration,
+}
+
+struct CachedPassword {
+    password: Zeroizing<String>,
+    /// Never in the keystore, so the agent cannot open a passphrase wallet without it
+    passphrase: Option<Zeroizing<String>>,
+    expires: Instant,
+}
+
+impl PasswordCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            ttl,
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn get(&self, path: &Path) -> Option<Zeroizing<String>> {
+        let mut entries = self.lock();
+        entries.retain(|_, entry| entry.expires > Instant::now());
+        entries.get(path).map(|entry| entry.password.clone())
+    }
+
+    /// BIP39 passphrase cached along with the password of `path`
+    pub fn passphrase(&self, path: &Path) -> Option<Zeroizing<String>> {
+        let mut entries = self.lock();
+        entries.retain(|_, entry| entry.expires > Instant::now());
+        entries.get(path).and_then(|entry| entry.passphrase.clone())
+    }
+
+    pub fn put(&self, path: PathBuf, password: String, passphrase: Option<String>) {
+        let entry = CachedPassword {
+            password: Zeroizing::new(password),
+            passphrase: passphrase.map(Zeroizing::new),
+            expires: Instant::now() + self.ttl,
+        };
+        self.lock().insert(path, entry);
+    }
+
+    pub fn forget(&self, path: Option<&Path>) {
+        match path {
+            Some(path) => {
+                self.lock().remove(path);
+            }
+            None => self.lock().clear(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        let mut entries = self.lock();
+        entries.retain(|_, entry| entry.expires > Instant::now());
+        entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// A panicked connection leaves the map itself intact, so a poisoned lock is still usable
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, CachedPassword>> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Decrypted wallets by keystore path, each serialized into locked memory and expiring like
+/// the password that opened it. The passphrase is skipped by serde like in the keystore, so
+/// it is held next to the serialized wallet and supplied again on every `get`.
+#[derive(Clone)]
+pub struct KeyCache {
+    entries: Arc<Mutex<HashMap<PathBuf, CachedKeys>>>,
+    ttl: Duration,
+}
+
+struct CachedKeys {
+    wallet: LockedBuffer,
+    passphrase: Option<LockedString>,
+    expires: Instant,
+}
+
+impl KeyCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            ttl,
+        }
+    }
+
+    /// A working copy of the wallet held for `path`, zeroed when dropped
+    pub fn get(&self, path: &Path) -> WalletResult<Option<Wallet>> {
+        let mut entries = self.lock();
+        entries.retain(|_, entry| entry.expires > Instant::now());
+        match entries.get(path) {
+            Some(entry) => {
+                let mut wallet: Wallet = serde_json::from_slice(entry.wallet.as_slice())?;
+                if let Some(passphrase) = &entry.passphrase {
+                    wallet.unlock_passphrase(passphrase)?;
+                }
+                Ok(Some(wallet))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn put(&self, path: PathBuf, wallet: &Wallet) -> WalletResult<()> {
+        let serialized = Zeroizing::new(serde_json::to_vec(wallet)?);
+        let entry = CachedKeys {
+            wallet: LockedBuffer::new(&serialized),
+            passphrase: Some(wallet.passphrase()).filter(|passphrase| !passphrase.is_empty()).map(LockedString::from),
+            expires: Instant::now() + self.ttl,
+        };
+        self.lock().insert(path, entry);
+        Ok(())
+    }
+
+    pub fn forget(&self, path: Option<&Path>) {
+        match path {
+            Some(path) => {
+                self.lock().remove(path);
+            }
+            None => self.lock().clear(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        let mut entries = self.lock();
+        entries.retain(|_, entry| entry.expires > Instant::now());
+        entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, CachedKeys>> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Everything a connection of the agent answers from
+#[cfg(unix)]
+#[derive(Clone)]
+struct AgentState {
+    passwords: PasswordCache,
+    keys: KeyCache,
+    manager: Arc<WalletManager>,
+    confirm: Option<Confirm>,
+    /// One confirmation at the terminal at a time
+    confirming: Arc<tokio::sync::Mutex<()>>,
+}
+
+#[cfg(unix)]
+impl AgentState {
+    async fn answer(&self, request: AgentRequest) -> AgentResponse {
+        let result = match request {
+            AgentRequest::Ping => Ok(AgentResponse::Pong {
+                pid: std::process::id(),
+                cached: self.passwords.len(),
+                ttl_secs: self.passwords.ttl().as_secs(),
+                keys: self.keys.len(),
+                confirm: self.confirm.is_some(),
+            }),
+            AgentRequest::Get { path } => Ok(AgentResponse::Password {
+                password: self.passwords.get(&path).map(|password| password.to_string()),
+            }),
+            AgentRequest::Put { path, password, passphrase } => {
+                // A new password may open different keys
+                self.keys.forget(Some(&path));
+                self.passwords.put(path, password, passphrase);
+                Ok(AgentResponse::Ok)
+            }
+            AgentRequest::Forget { path } => {
+                self.passwords.forget(path.as_deref());
+                self.keys.forget(path.as_deref());
+                Ok(AgentResponse::Ok)
+            }
+            AgentRequest::Session { path, base_path } => self.session(&path, base_path.as_deref()).await,
+            AgentRequest::Derive { path, base_path, index } => match self.wallet(&path, base_path.as_deref()).await {
+                Ok(Some(wallet)) => wallet.derive_address(index).map(|address| AgentResponse::Derived { address }),
+                other => other.map(|_| AgentResponse::Locked),
+            },
+            AgentRequest::SignTransaction { path, tx } => {
+                let summary = format!(
+                    "Sign a transaction with {} to {} for {} wei on chain {}?",
+                    path.display(),
+                    tx.to.as_deref().unwrap_or("a new contract"),
+                    tx.value.as_deref().unwrap_or("0"),
+                    tx.chain_id
+                );
+                self.sign(&path, summary, |wallet| {
+                    TransactionService::sign(wallet, &tx).map(|tx| AgentResponse::SignedTransaction { tx })
+                })
+                .await
+            }
+            AgentRequest::SignMessage { path, message } => match hex::decode(message.trim_start_matches("0x")) {
+                Ok(message) => {
+                    let preview: String = String::from_utf8_lossy(&message).chars().take(80).collect();
+                    let summary = format!("Sign the message {:?} with {}?", preview, path.display());
+                    self.sign(&path, summary, |wallet| {
+                        SigningService::sign(wallet, &SignedPayload::Message(message))
+                            .map(|signature| AgentResponse::Signature { signature })
+                    })
+                    .await
+                }
+                Err(e) => Ok(AgentResponse::Error { message: format!("message is not hex: {}", e) }),
+            },
+            // The connection triggers the shutdown once this answer is written
+            AgentRequest::Stop => Ok(AgentResponse::Ok),
+        };
+        result.unwrap_or_else(|e| AgentResponse::Error { message: e.to_string() })
+    }
+
+    async fn session(&self, path: &Path, base_path: Option<&str>) -> WalletResult<AgentResponse> {
+        Ok(match self.wallet(path, base_path).await? {
+            Some(wallet) => AgentResponse::Session {
+                address: wallet.address().to_string(),
+                base_path: wallet.derivation_path().to_string(),
+            },
+            None => AgentResponse::Locked,
+        })
+    }
+
+    /// The wallet held for `path`, decrypting it with the cached password the first time
+    async fn wallet(&self, path: &Path, base_path: Option<&str>) -> WalletResult<Option<Wallet>> {
+        let wallet = match self.keys.get(path)? {
+            Some(wallet) => wallet,
+            None => {
+                let Some(password) = self.passwords.get(path) else {
+                    return Ok(None);
+                };
+                let passphrase = self.passwords.passphrase(path);
+                let wallet = self
+                    .manager
+                    .load_wallet_with_passphrase(path, &password, passphrase.as_deref().map(String::as_str))
+                    .await?;
+                self.keys.put(path.to_path_buf(), &wallet)?;
+                wallet
+            }
+        };
+        match base_path {
+            Some(base_path) => wallet.with_base_path(base_path).map(Some),
+            None => Ok(Some(wallet)),
+        }
+    }
+
+    async fn sign<F>(&self, path: &Path, summary: String, sign: F) -> WalletResult<AgentResponse>
+    where
+        F: FnOnce(&Wallet) -> WalletResult<AgentResponse>,
+    {
+        let Some(wallet) = self.wallet(path, None).await? else {
+            return Ok(AgentResponse::Locked);
+        };
+        if !self.confirmed(summary).await {
+            return Ok(AgentResponse::Error { message: "declined at the agent".to_string() });
+        }
+        sign(&wallet)
+    }
+
+    async fn confirmed(&self, summary: String) -> bool {
+        let Some(confirm) = self.confirm.clone() else {
+            return true;
+        };
+        let _turn = self.confirming.lock().await;
+        let answer = tokio::task::spawn_blocking(move || confirm(summary));
+        // A late answer is read by the abandoned task and discarded
+        matches!(
+            tokio::time::timeout(Duration::from_secs(limits::CONFIRM_TIMEOUT_SECS), answer).await,
+            Ok(Ok(true))
+        )
+    }
+}
+
+/// Client side of a running agent
+#[derive(Debug, Clone)]
+pub struct AgentClient {
+    paths: AgentPaths,
+    pid: u32,
+}
+
+impl AgentClient {
+    /// The agent serving `paths`, or `None` without a pid file or when nobody answers in time
+    pub async fn detect(paths: &AgentPaths) -> Option<Self> {
+        let pid = tokio::fs::read_to_string(&paths.pid_file).await.ok()?.trim().parse::<u32>().ok()?;
+        match request(paths, &AgentRequest::Ping).await.ok()? {
+            AgentResponse::Pong { pid: answered, .. } if answered == pid => Some(Self {
+                paths: paths.clone(),
+                pid,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub async fn status(&self) -> WalletResult<AgentStatus> {
+        match request(&self.paths, &AgentRequest::Ping).await? {
+            AgentResponse::Pong { cached, ttl_secs, keys, confirm, .. } => Ok(AgentStatus {
+                cached,
+                keys,
+                ttl: Duration::from_secs(ttl_secs),
+                confirm,
+            }),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Signer using the keys the agent holds for `path`, `None` when it holds neither them nor
+    /// the keystore's password; `base_path` re-roots derivation like `derive --path`
+    pub async fn signer(&self, path: &Path, base_path: Option<String>) -> Option<AgentSigner> {
+        let path = cache_key(path);
+        let session = AgentRequest::Session { path: path.clone(), base_path: base_path.clone() };
+        match request_within(&self.paths, &session, sign_timeout()).await.ok()? {
+            AgentResponse::Session { address, base_path: resolved } => Some(AgentSigner {
+                client: self.clone(),
+                path,
+                base_path,
+                address,
+                resolved_base_path: resolved,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Cached password of the keystore at `path`; any failure to ask counts as a miss
+    pub async fn password(&self, path: &Path) -> Option<Zeroizing<String>> {
+        let path = cache_key(path);
+        match request(&self.paths, &AgentRequest::Get { path }).await.ok()? {
+            AgentResponse::Password { password } => password.map(Zeroizing::new),
+            _ => None,
+        }
+    }
+
+    /// Cache the password of `path`, and the BIP39 passphrase of a wallet that takes one
+    pub async fn remember(&self, path: &Path, password: &str, passphrase: Option<&str>) -> WalletResult<()> {
+        let request_body = AgentRequest::Put {
+            path: cache_key(path),
+            password: password.to_string(),
+            passphrase: passphrase.map(str::to_string),
+        };
+        expect_ok(request(&self.paths, &request_body).await?)
+    }
+
+    /// Drop the cached password and keys of `path`, or all of them
+    pub async fn forget(&self, path: Option<&Path>) -> WalletResult<()> {
+        let path = path.map(cache_key);
+        expect_ok(request(&self.paths, &AgentRequest::Forget { path }).await?)
+    }
+
+    pub async fn stop(&self) -> WalletResult<()> {
+        expect_ok(request(&self.paths, &AgentRequest::Stop).await?)
+    }
+}
+
+/// What a running agent holds
+#[derive(Debug, Clone)]
+pub struct AgentStatus {
+    /// Passwords cached right now
+    pub cached: usize,
+    /// Keystores whose keys are held decrypted
+    pub keys: usize,
+    /// How long each password and its keys are kept
+    pub ttl: Duration,
+    /// Whether signatures are confirmed at the agent's terminal
+    pub confirm: bool,
+}
+
+/// Signs with keys held by the agent, so the keystore is not decrypted again
+pub struct AgentSigner {
+    client: AgentClient,
+    path: PathBuf,
+    base_path: Option<String>,
+    address: String,
+    resolved_base_path: String,
+}
+
+impl AgentSigner {
+    async fn request(&self, request: AgentRequest) -> WalletResult<AgentResponse> {
+        match request_within(&self.client.paths, &request, sign_timeout()).await? {
+            AgentResponse::Locked => Err(ValidationError::PreconditionFailed {
+                check: "agent keys".to_string(),
+                details: format!("the agent no longer holds the keys of {}", self.path.display()),
+            }
+            .into()),
+            response => Ok(response),
+        }
+    }
+}
+
+#[async_trait]
+impl Signer for AgentSigner {
+    async fn address(&self) -> WalletResult<String> {
+        Ok(self.address.clone())
+    }
+
+    fn base_path(&self) -> String {
+        self.resolved_base_path.clone()
+    }
+
+    async fn derive_address(&self, index: u32) -> WalletResult<DerivedAddress> {
+        let request = AgentRequest::Derive { path: self.path.clone(), base_path: self.base_path.clone(), index };
+        match self.request(request).await? {
+            AgentResponse::Derived { address } => Ok(address),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    async fn sign_transaction(&self, tx: &UnsignedTransaction) -> WalletResult<SignedTransaction> {
+        match self.request(AgentRequest::SignTransaction { path: self.path.clone(), tx: tx.clone() }).await? {
+            AgentResponse::SignedTransaction { tx } => Ok(tx),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> WalletResult<String> {
+        let request = AgentRequest::SignMessage { path: self.path.clone(), message: hex::encode(message) };
+        match self.request(request).await? {
+            AgentResponse::Signature { signature } => Ok(signature),
+            other => Err(unexpected(other)),
+        }
+    }
+}
+
+/// The listening agent; at most one per state directory
+#[cfg(unix)]
+pub struct AgentServer {
+    paths: AgentPaths,
+    listener: tokio::net::UnixListener,
+    state: AgentState,
+}
+
+#[cfg(unix)]
+impl AgentServer {
+    /// Claim the pid file and bind the socket. Fails when another agent answers; leftovers of
+    /// an agent that died without cleaning up are replaced. Keystores are decrypted with `manager`.
+    pub async fn bind(paths: AgentPaths, ttl: Duration, manager: WalletManager) -> WalletResult<Self> {
+        if let Some(dir) = paths.pid_file.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+
+        if !claim_pid_file(&paths.pid_file)? {
+            if let Some(running) = AgentClient::detect(&paths).await {
+                return Err(ValidationError::PreconditionFailed {
+                    check: "single agent".to_string(),
+                    details: format!("an agent is already running with pid {}", running.pid()),
+                }
+                .into());
+            }
+            let _ = std::fs::remove_file(&paths.pid_file);
+            if !claim_pid_file(&paths.pid_file)? {
+                return Err(FilesystemError::LockFailed {
+                    path: paths.pid_file.display().to_string(),
+                    timeout: Duration::ZERO,
+                }
+                .into());
+            }
+        }
+
+        let _ = std::fs::remove_file(&paths.socket);
+        let listener = tokio::net::UnixListener::bind(&paths.socket).inspect_err(|_| {
+            let _ = std::fs::remove_file(&paths.pid_file);
+        })?;
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&paths.socket, std::fs::Permissions::from_mode(limits::FILE_PERMISSIONS))?;
+        }
+
+        Ok(Self {
+            paths,
+            listener,
+            state: AgentState {
+                passwords: PasswordCache::new(ttl),
+                keys: KeyCache::new(ttl),
+                manager: Arc::new(manager),
+                confirm: None,
+                confirming: Arc::new(tokio::sync::Mutex::new(())),
+            },
+        })
+    }
+
+    /// Ask `confirm` before every signature; unanswered requests are declined
+    pub fn with_confirmation(mut self, confirm: Confirm) -> Self {
+        self.state.confirm = Some(confirm);
+        self
+    }
+
+    /// Answer requests until `shutdown` triggers, then remove the socket and pid file
+    pub async fn serve(self, shutdown: Shutdown) -> WalletResult<()> {
+        loop {
+            tokio::select! {
+                _ = shutdown.triggered() => break,
+                accepted = self.listener.accept() => {
+                    let Ok((stream, _)) = accepted else { continue };
+                    let state = self.state.clone();
+                    let shutdown = shutdown.clone();
+                    tokio::spawn(async move {
+                        let _ = handle_connection(stream, state, shutdown).await;
+                    });
+                }
+            }
+        }
+
+        self.state.passwords.forget(None);
+        self.state.keys.forget(None);
+        let _ = std::fs::remove_file(&self.paths.socket);
+        let _ = std::fs::remove_file(&self.paths.pid_file);
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+pub struct AgentServer;
+
+#[cfg(not(unix))]
+impl AgentServer {
+    pub async fn bind(_paths: AgentPaths, _ttl: Duration, _manager: crate::services::walletmanager::WalletManager) -> WalletResult<Self> {
+        Err(WalletError::NotImplemented("the agent needs unix domain sockets".to_string()))
+    }
+
+    pub fn with_confirmation(self, _confirm: Confirm) -> Self {
+        self
+    }
+
+    pub async fn serve(self, _shutdown: Shutdown) -> WalletResult<()> {
+        Ok(())
+    }
+}
+
+/// Create the pid file if nobody holds it; `false` when it already exists
+#[cfg(unix)]
+fn claim_pid_file(path: &Path) -> WalletResult<bool> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    match std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(limits::FILE_PERMISSIONS)
+        .open(path)
+    {
+        Ok(mut file) => {
+            writeln!(file, "{}", std::process::id())?;
+            Ok(true)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(unix)]
+async fn handle_connection(stream: tokio::net::UnixStream, state: AgentState, shutdown: Shutdown) -> std::io::Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        let (response, stop) = match serde_json::from_str::<AgentRequest>(&line) {
+            Ok(request) => {
+                let stop = matches!(request, AgentRequest::Stop);
+                (state.answer(request).await, stop)
+            }
+            Err(e) => (AgentResponse::Error { message: e.to_string() }, false),
+        };
+        let mut encoded = serde_json::to_string(&response)?;
+        encoded.push('\n');
+        writer.write_all(encoded.as_bytes()).await?;
+        writer.flush().await?;
+        if stop {
+            shutdown.trigger(ShutdownSignal::Terminate);
+            break;
+        }
+    }
+    Ok(())
+}
+
+async fn request(paths: &AgentPaths, request: &AgentRequest) -> WalletResult<AgentResponse> {
+    request_within(paths, request, Duration::from_millis(limits::RESPONSE_TIMEOUT_MS)).await
+}
+
+#[cfg(unix)]
+async fn request_within(paths: &AgentPaths, request: &AgentRequest, timeout: Duration) -> WalletResult<AgentResponse> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let exchange = async {
+        let stream = tokio::net::UnixStream::connect(&paths.socket).await?;
+        let (reader, mut writer) = stream.into_split();
+        let mut encoded = serde_json::to_string(request)?;
+        encoded.push('\n');
+        writer.write_all(encoded.as_bytes()).await?;
+
+        let mut line = String::new();
+        BufReader::new(reader).read_line(&mut line).await?;
+        Ok::<AgentResponse, WalletError>(serde_json::from_str(&line)?)
+    };
+
+    tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "agent did not answer"))?
+}
+
+#[cfg(not(unix))]
+async fn request_within(_paths: &AgentPaths, _request: &AgentRequest, _timeout: Duration) -> WalletResult<AgentResponse> {
+    Err(WalletError::NotImplemented("the agent needs unix domain sockets".to_string()))
+}
+
+/// Requests that may decrypt a keystore or wait for the user's confirmation
+fn sign_timeout() -> Duration {
+    Duration::from_secs(limits::SIGN_TIMEOUT_SECS)
+}
+
+/// Cache entries are keyed by the canonical path, so relative and absolute names agree
+fn cache_key(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn expect_ok(response: AgentResponse) -> WalletResult<()> {
+    match response {
+        AgentResponse::Ok => Ok(()),
+        other => Err(unexpected(other)),
+    }
+}
+
+fn unexpected(response: AgentResponse) -> WalletError {
+    let details = match response {
+        AgentResponse::Error { message } => message,
+        _ => "unexpected answer from the agent".to_string(),
+    };
+    ValidationError::PreconditionFailed {
+        check: "agent response".to_string(),
+        details,
+    }
+    .into()
+}
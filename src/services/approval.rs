@@ -0,0 +1,301 @@
+//! Two-person rule for high-value transactions. From `[approval] threshold` on, signing stops
+//! until an approver vouches for the transaction: the request is sealed (libsodium sealed box)
+//! to the approvers' companion key and pushed to an ntfy topic or webhook, and the approver
+//! answers with a token signed by their own wallet, see `approval approve`.
+use crate::config::approval as settings;
+use crate::errors::{AuthenticationError, CryptographicError, UserInputError, WalletResult};
+#[cfg(feature = "native")]
+use crate::errors::{FilesystemError, NetworkError};
+use crate::services::signing::{SignedPayload, SigningService};
+use crate::services::transaction::{parse_address, parse_bytes, parse_quantity, UnsignedTransaction};
+#[cfg(feature = "native")]
+use crate::WalletConfig;
+use chrono::{DateTime, Utc};
+use crypto_box::aead::OsRng;
+use crypto_box::{PublicKey, SecretKey};
+use ethers::abi::Token;
+use ethers::types::{Address, H256, U256};
+use ethers::utils::{keccak256, parse_ether, to_checksum};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "native")]
+use std::path::Path;
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+/// Where sealed requests are pushed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalChannel {
+    /// POST the sealed request as the message body of an ntfy topic URL
+    #[default]
+    Ntfy,
+    /// POST `{"type": ..., "sealed": ...}` as JSON
+    Webhook,
+}
+
+/// `[approval]` in the config file; approvals are off while `threshold` is unset
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApprovalSettings {
+    /// Native value in ether units from which a transaction needs approval, e.g. "1.0"
+    pub threshold: Option<String>,
+    /// Addresses whose tokens are accepted; never the sending wallet itself
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approvers: Vec<String>,
+    /// Companion key requests are sealed to, the public half from `approval keygen`
+    pub public_key: Option<String>,
+    pub channel: Option<ApprovalChannel>,
+    /// ntfy topic or webhook; without one the sealed request is printed for delivery by hand
+    pub url: Option<String>,
+    /// Lifetime of tokens issued by `approval approve`
+    pub token_ttl_secs: Option<u64>,
+}
+
+impl ApprovalSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check the settings hang together, naming the first key that does not
+    pub fn validate(&self) -> Result<(), (&'static str, String)> {
+        let Some(threshold) = &self.threshold else {
+            return Ok(());
+        };
+        parse_ether(threshold).map_err(|_| ("approval.threshold", format!("{:?} is not an amount in ether units", threshold)))?;
+        if self.approvers.is_empty() {
+            return Err(("approval.approvers", "a threshold needs at least one approver".to_string()));
+        }
+        if let Some(approver) = self.approvers.iter().find(|a| parse_address("approver", a).is_err()) {
+            return Err(("approval.approvers", format!("{:?} is not an address", approver)));
+        }
+        match &self.public_key {
+            None => Err(("approval.public_key", "a threshold needs the companion key from `approval keygen`".to_string())),
+            Some(key) if public_key(key).is_err() => Err(("approval.public_key", "expected 32 bytes of hex".to_string())),
+            Some(_) => Ok(()),
+        }
+    }
+
+    pub fn token_ttl(&self) -> Duration {
+        Duration::from_secs(self.token_ttl_secs.unwrap_or(settings::DEFAULT_TOKEN_TTL_SECS))
+    }
+}
+
+/// What an approver is asked to vouch for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    pub from: String,
+    pub tx: UnsignedTransaction,
+    /// Chain name, for display
+    pub chain: String,
+    #[serde(default)]
+    pub note: Option<String>,
+    pub requested_at: DateTime<Utc>,
+}
+
+impl ApprovalRequest {
+    pub fn new(from: &str, tx: &UnsignedTransaction, chain: &str, note: Option<&str>) -> Self {
+        Self {
+            from: from.to_string(),
+            tx: tx.clone(),
+            chain: chain.to_string(),
+            note: note.map(str::to_string),
+            requested_at: Utc::now(),
+        }
+    }
+
+    /// Commitment to sender, chain, recipient, value, nonce and calldata. Fees are left out so
+    /// a re-estimated fee does not void an approval.
+    pub fn digest(&self) -> WalletResult<H256> {
+        let tx = &self.tx;
+        let to = match &tx.to {
+            Some(to) => parse_address("to", to)?,
+            None => Address::zero(),
+        };
+        let data = match &tx.data {
+            Some(data) => parse_bytes("data", data)?.to_vec(),
+            None => Vec::new(),
+        };
+        let encoded = ethers::abi::encode(&[
+            Token::Uint(U256::from(tx.chain_id)),
+            Token::Address(parse_address("from", &self.from)?),
+            Token::Address(to),
+            Token::Uint(self.value()?),
+            Token::Uint(parse_quantity("nonce", &tx.nonce)?),
+            Token::Bytes(data),
+        ]);
+        Ok(H256::from(keccak256(encoded)))
+    }
+
+    /// Native value moved, in wei
+    pub fn value(&self) -> WalletResult<U256> {
+        parse_quantity("value", self.tx.value.as_deref().unwrap_or("0"))
+    }
+
+    /// Text the approver signs with `personal_sign`
+    pub fn message(&self, expires: u64) -> WalletResult<String> {
+        Ok(format!(
+            "{}\nwallet: {}\nchain: {}\ntransaction: {:?}\nexpires: {}",
+            settings::MESSAGE_HEADER,
+            to_checksum(&parse_address("from", &self.from)?, None),
+            self.tx.chain_id,
+            self.digest()?,
+            expires
+        ))
+    }
+
+    /// Hex of the request sealed to `public_key`; only the companion key's owner can open it
+    pub fn seal(&self, public_key_hex: &str) -> WalletResult<String> {
+        let sealed = public_key(public_key_hex)?
+            .seal(&mut OsRng, &serde_json::to_vec(self)?)
+            .map_err(|e| CryptographicError::EncryptionFailed { details: e.to_string() })?;
+        Ok(hex::encode(sealed))
+    }
+
+    pub fn open(sealed_hex: &str, secret: &SecretKey) -> WalletResult<Self> {
+        let sealed = hex::decode(sealed_hex.trim()).map_err(|_| UserInputError::InvalidParameters {
+            parameter: "request".to_string(),
+            value: sealed_hex.chars().take(16).collect(),
+            expected: "the hex of a sealed approval request".to_string(),
+        })?;
+        let json = Zeroizing::new(secret.unseal(&sealed).map_err(|_| CryptographicError::DecryptionFailed {
+            context: "the request was not sealed to this companion key".to_string(),
+        })?);
+        Ok(serde_json::from_slice(&json)?)
+    }
+}
+
+pub struct ApprovalService;
+
+impl ApprovalService {
+    /// Whether `tx` moves at least the configured threshold
+    pub fn required(approval: &ApprovalSettings, tx: &UnsignedTransaction) -> WalletResult<bool> {
+        let Some(threshold) = &approval.threshold else {
+            return Ok(false);
+        };
+        let threshold = parse_ether(threshold).map_err(|e| UserInputError::InvalidParameters {
+            parameter: "approval.threshold".to_string(),
+            value: e.to_string(),
+            expected: "an amount in ether units".to_string(),
+        })?;
+        Ok(parse_quantity("value", tx.value.as_deref().unwrap_or("0"))? >= threshold)
+    }
+
+    /// Token for `request` from an approver's signature over `request.message(expires)`
+    pub fn token(expires: u64, signature: &str) -> String {
+        format!("{}:{}", expires, signature)
+    }
+
+    /// Check `token` approves `request`, returning the approver's address
+    pub fn verify(approval: &ApprovalSettings, request: &ApprovalRequest, token: &str) -> WalletResult<String> {
+        let rejected = |reason: &str| AuthenticationError::ApprovalRejected { reason: reason.to_string() };
+
+        let (expires, signature) = token
+            .trim()
+            .split_once(':')
+            .and_then(|(expires, signature)| Some((expires.parse::<u64>().ok()?, signature)))
+            .ok_or_else(|| rejected("malformed token, expected <expiry>:<signature>"))?;
+        if expires < Utc::now().timestamp().max(0) as u64 {
+            return Err(rejected("the token has expired").into());
+        }
+
+        let payload = SignedPayload::Message(request.message(expires)?.into_bytes());
+        for approver in &approval.approvers {
+            // A signature that does not even recover is no one's approval
+            if !SigningService::verify(&payload, signature, approver).is_ok_and(|v| v.valid) {
+                continue;
+            }
+            if crate::utils::addresses_equal(approver, &request.from) {
+                return Err(rejected("the sending wallet cannot approve its own transaction").into());
+            }
+            return Ok(to_checksum(&parse_address("approver", approver)?, None));
+        }
+        Err(rejected("not signed by a configured approver for this transaction").into())
+    }
+
+    /// Push `sealed` to the configured channel
+    #[cfg(feature = "native")]
+    pub async fn notify(config: &WalletConfig, sealed: &str) -> WalletResult<()> {
+        let Some(url) = &config.approval.url else {
+            return Ok(());
+        };
+        let client = reqwest::Client::new();
+        let request = match config.approval.channel.unwrap_or_default() {
+            ApprovalChannel::Ntfy => client
+                .post(url)
+                .header("Title", settings::NOTIFICATION_TITLE)
+                .header("Tags", "lock")
+                .body(sealed.to_string()),
+            ApprovalChannel::Webhook => client.post(url).json(&serde_json::json!({
+                "type": settings::WEBHOOK_EVENT,
+                "sealed": sealed,
+            })),
+        };
+
+        let response = request
+            .timeout(Duration::from_secs(config.rpc_timeout_secs))
+            .send()
+            .await
+            .map_err(|e| NetworkError::ConnectivityFailure {
+                endpoint: url.clone(),
+                details: e.to_string(),
+            })?;
+        if !response.status().is_success() {
+            return Err(NetworkError::InvalidResponse {
+                endpoint: url.clone(),
+                details: format!("HTTP {}", response.status()),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// New companion key pair: the secret as hex, and the public key for `[approval] public_key`
+    pub fn generate_key() -> (Zeroizing<String>, String) {
+        let secret = SecretKey::generate(&mut OsRng);
+        let public = hex::encode(secret.public_key().as_bytes());
+        (Zeroizing::new(hex::encode(secret.to_bytes())), public)
+    }
+
+    /// Write a companion secret key readable by the owner only; refuses to overwrite
+    #[cfg(feature = "native")]
+    pub async fn save_key(path: &Path, secret_hex: &str) -> WalletResult<()> {
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(crate::config::fs::KEYSTORE_FILE_PERMISSIONS);
+
+        use tokio::io::AsyncWriteExt;
+        let mut file = options.open(path).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => FilesystemError::FileExists {
+                path: path.display().to_string(),
+                suggestion: "choose another --out path; an existing key is never overwritten".to_string(),
+            }
+            .into(),
+            _ => crate::errors::WalletError::from(e),
+        })?;
+        file.write_all(format!("{}\n", secret_hex).as_bytes()).await?;
+        file.sync_all().await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "native")]
+    pub async fn load_key(path: &Path) -> WalletResult<SecretKey> {
+        let data = Zeroizing::new(tokio::fs::read_to_string(path).await?);
+        let bytes = Zeroizing::new(hex::decode(data.trim()).unwrap_or_default());
+        let bytes: [u8; 32] = bytes.as_slice().try_into().map_err(|_| FilesystemError::InvalidFormat {
+            path: path.display().to_string(),
+            details: "expected a companion key from `approval keygen`".to_string(),
+        })?;
+        Ok(SecretKey::from(bytes))
+    }
+}
+
+fn public_key(hex_key: &str) -> WalletResult<PublicKey> {
+    let bytes = hex::decode(hex_key.trim().trim_start_matches("0x")).unwrap_or_default();
+    let bytes: [u8; 32] = bytes.as_slice().try_into().map_err(|_| UserInputError::InvalidParameters {
+        parameter: "approval.public_key".to_string(),
+        value: hex_key.to_string(),
+        expected: "32 bytes of hex from `approval keygen`".to_string(),
+    })?;
+    Ok(PublicKey::from(bytes))
+}
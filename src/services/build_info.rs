@@ -0,0 +1,43 @@
+use serde::Serialize;
+
+/// How this binary was built, for bug reports
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub git_commit: &'static str,
+    pub target: &'static str,
+    pub profile: &'static str,
+    pub features: Vec<&'static str>,
+    /// Locked versions of the cryptography crates; empty when built without a Cargo.lock
+    pub crypto: Vec<CrateVersion>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateVersion {
+    pub name: &'static str,
+    pub version: &'static str,
+}
+
+impl BuildInfo {
+    pub fn current() -> Self {
+        let mut features = Vec::new();
+        if cfg!(feature = "cli") {
+            features.push("cli");
+        }
+
+        let crypto = env!("WEB3WALLET_CRYPTO_VERSIONS")
+            .split(';')
+            .filter_map(|entry| entry.split_once('='))
+            .map(|(name, version)| CrateVersion { name, version })
+            .collect();
+
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            git_commit: env!("WEB3WALLET_GIT_COMMIT"),
+            target: env!("WEB3WALLET_BUILD_TARGET"),
+            profile: env!("WEB3WALLET_BUILD_PROFILE"),
+            features,
+            crypto,
+        }
+    }
+}
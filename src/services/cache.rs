@@ -0,0 +1,116 @@
+use crate::config;
+use crate::errors::{FilesystemError, WalletResult};
+use crate::utils;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    value: Value,
+    /// Unix timestamp (seconds) after which the entry is stale
+    expires_at: u64,
+}
+
+/// TTL cache for idempotent chain reads, keyed by scope + method + params
+/// and persisted as JSON under the state directory. RPC reads are scoped by chain id, so
+/// a renamed or redefined network never serves another chain's answers.
+#[derive(Debug)]
+pub struct RpcCache {
+    path: PathBuf,
+    entries: HashMap<String, CacheEntry>,
+}
+
+impl RpcCache {
+    /// Open the cache file at `path`, starting empty if it is missing or unreadable
+    pub async fn open(path: &Path) -> Self {
+        let entries = match tokio::fs::read_to_string(path).await {
+            Ok(data) => serde_json::from_str(&data).unwrap_or_default(),
+            Err(_) => HashMap::new(),
+        };
+
+        let mut cache = Self {
+            path: path.to_path_buf(),
+            entries,
+        };
+        cache.evict_expired();
+        cache
+    }
+
+    /// Default cache location inside `state_dir`
+    pub fn default_path(state_dir: &Path) -> PathBuf {
+        state_dir.join(config::cache::CACHE_FILE_NAME)
+    }
+
+    pub fn key(scope: &str, method: &str, params: &Value) -> String {
+        format!("{}:{}:{}", scope, method, params)
+    }
+
+    pub fn get(&self, scope: &str, method: &str, params: &Value) -> Option<Value> {
+        self.entries
+            .get(&Self::key(scope, method, params))
+            .filter(|entry| entry.expires_at > now())
+            .map(|entry| entry.value.clone())
+    }
+
+    pub fn insert(&mut self, scope: &str, method: &str, params: &Value, value: Value, ttl: Duration) {
+        self.entries.insert(
+            Self::key(scope, method, params),
+            CacheEntry {
+                value,
+                expires_at: now() + ttl.as_secs(),
+            },
+        );
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn evict_expired(&mut self) {
+        let now = now();
+        self.entries.retain(|_, entry| entry.expires_at > now);
+    }
+
+    /// Write the cache back to disk
+    pub async fn persist(&mut self) -> WalletResult<()> {
+        self.evict_expired();
+
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                FilesystemError::DirectoryNotAccessible {
+                    path: parent.display().to_string(),
+                    details: e.to_string(),
+                }
+            })?;
+        }
+
+        let json = serde_json::to_string(&self.entries)?;
+        utils::write_atomic(&self.path, json.as_bytes()).await
+    }
+}
+
+/// Default TTL for methods that are always safe to cache, `None` if the method must not be cached implicitly
+pub fn default_ttl(method: &str) -> Option<Duration> {
+    config::cache::CACHEABLE_METHODS
+        .iter()
+        .find(|(name, _)| *name == method)
+        .map(|(_, secs)| Duration::from_secs(*secs))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
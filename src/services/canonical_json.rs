@@ -0,0 +1,141 @@
+//! JSON Canonicalization Scheme (RFC 8785). Structured payloads are hashed over this form, so
+//! the same document gives the same digest whatever key order, whitespace or number spelling
+//! the tool that wrote it used, and other JCS implementations reproduce it byte for byte.
+use crate::errors::{UserInputError, WalletResult};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+
+/// Integers past 2^53 are not exact as the IEEE 754 doubles JCS works with
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Canonical form of `value`: members sorted by UTF-16 code units, no whitespace, ECMAScript
+/// number formatting and minimal string escaping
+pub fn canonicalize(value: &Value) -> WalletResult<String> {
+    let mut out = String::new();
+    write_value(&mut out, value)?;
+    Ok(out)
+}
+
+pub fn to_canonical_string<T: Serialize>(value: &T) -> WalletResult<String> {
+    canonicalize(&serde_json::to_value(value)?)
+}
+
+/// SHA-256 of the canonical form, as 0x-prefixed hex
+pub fn digest(value: &Value) -> WalletResult<String> {
+    Ok(format!("0x{}", hex::encode(Sha256::digest(canonicalize(value)?.as_bytes()))))
+}
+
+/// `digest` of a JSON document given as text
+pub fn digest_str(json: &str) -> WalletResult<String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| UserInputError::InvalidParameters {
+        parameter: "json".to_string(),
+        value: e.to_string(),
+        expected: "a JSON document".to_string(),
+    })?;
+    digest(&value)
+}
+
+fn write_value(out: &mut String, value: &Value) -> WalletResult<()> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => out.push_str(&number(n)?),
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item)?;
+            }
+            out.push(']');
+        }
+        Value::Object(members) => {
+            let mut members: Vec<_> = members.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (key, item)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                write_value(out, item)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{c}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn number(n: &serde_json::Number) -> WalletResult<String> {
+    let unsafe_integer = || UserInputError::InvalidParameters {
+        parameter: "json".to_string(),
+        value: n.to_string(),
+        expected: format!("integers within ±{}, larger ones as strings", MAX_SAFE_INTEGER),
+    };
+    if let Some(u) = n.as_u64() {
+        return if u <= MAX_SAFE_INTEGER { Ok(u.to_string()) } else { Err(unsafe_integer().into()) };
+    }
+    if let Some(i) = n.as_i64() {
+        return if i.unsigned_abs() <= MAX_SAFE_INTEGER { Ok(i.to_string()) } else { Err(unsafe_integer().into()) };
+    }
+    Ok(ecmascript_number(n.as_f64().unwrap_or_default()))
+}
+
+/// `Number.prototype.toString()` of a finite double, the spelling RFC 8785 requires
+fn ecmascript_number(v: f64) -> String {
+    if v == 0.0 {
+        return "0".to_string();
+    }
+
+    // Shortest round-trip digits and decimal exponent, e.g. "1.5e-7"
+    let scientific = format!("{:e}", v.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap_or(0) + 1;
+
+    let body = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat((-n) as usize), digits)
+    } else {
+        let e = n - 1;
+        let sign = if e < 0 { '-' } else { '+' };
+        match k {
+            1 => format!("{}e{}{}", digits, sign, e.abs()),
+            _ => format!("{}.{}e{}{}", &digits[..1], &digits[1..], sign, e.abs()),
+        }
+    };
+    if v < 0.0 {
+        format!("-{}", body)
+    } else {
+        body
+    }
+}
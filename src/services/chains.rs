@@ -0,0 +1,202 @@
+use crate::config;
+#[cfg(feature = "native")]
+use crate::errors::NetworkError;
+use crate::errors::{UserInputError, WalletResult};
+#[cfg(feature = "native")]
+use crate::services::rpc::{RpcClient, RpcRequest};
+#[cfg(feature = "native")]
+use crate::services::transaction::parse_quantity;
+use crate::WalletConfig;
+#[cfg(feature = "native")]
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "native")]
+use serde_json::json;
+
+/// An EVM chain the CLI knows how to reach
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChainInfo {
+    pub name: String,
+    pub chain_id: u64,
+    pub rpc_url: String,
+    /// WETH-style wrapper of the native currency, if the chain has one
+    pub wrapped_native: Option<String>,
+    pub explorer_url: Option<String>,
+    pub currency_symbol: String,
+    /// Defined under `[networks]` in the config file rather than built in
+    pub custom: bool,
+}
+
+/// A chain's own Etherscan-compatible indexer, from `[explorer.networks]` in the config file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExplorerApi {
+    /// The default indexer, with `api_key`, when not set
+    pub api_url: Option<String>,
+    pub api_key: Option<String>,
+}
+
+impl ChainInfo {
+    /// Built-in chains from `config::chains::DEFAULT_EVM_CHAINS`
+    pub fn defaults() -> Vec<Self> {
+        config::chains::DEFAULT_EVM_CHAINS
+            .iter()
+            .map(|(name, chain_id, rpc_url, wrapped_native, explorer_url, symbol)| Self {
+                name: name.to_string(),
+                chain_id: *chain_id,
+                rpc_url: rpc_url.to_string(),
+                wrapped_native: Some(wrapped_native.to_string()),
+                explorer_url: Some(explorer_url.to_string()),
+                currency_symbol: symbol.to_string(),
+                custom: false,
+            })
+            .collect()
+    }
+
+    /// Built-in chains followed by the networks defined in `config`, RPC overrides applied
+    pub fn registry(config: &WalletConfig) -> Vec<Self> {
+        Self::defaults()
+            .into_iter()
+            .chain(config.networks.values().cloned())
+            .map(|chain| chain.with_config(config))
+            .collect()
+    }
+
+    /// Built-in or config-defined chain called `name`
+    pub fn resolve(name: &str, config: &WalletConfig) -> WalletResult<Self> {
+        let mut registry = Self::registry(config);
+        if let Some(position) = registry.iter().position(|c| c.name == name) {
+            return Ok(registry.swap_remove(position));
+        }
+        Err(UserInputError::InvalidNetwork {
+            network: name.to_string(),
+            supported: registry.into_iter().map(|c| c.name).collect(),
+        }
+        .into())
+    }
+
+    pub fn by_chain_id(chain_id: u64, config: &WalletConfig) -> Option<Self> {
+        Self::registry(config).into_iter().find(|c| c.chain_id == chain_id)
+    }
+
+    /// Swap in the RPC endpoint configured for this chain, if any
+    pub fn with_config(mut self, config: &WalletConfig) -> Self {
+        if let Some(url) = config.rpc_overrides.get(&self.name) {
+            self.rpc_url = url.clone();
+        }
+        self
+    }
+
+    pub fn by_name(name: &str) -> WalletResult<Self> {
+        Self::defaults()
+            .into_iter()
+            .find(|c| c.name == name)
+            .ok_or_else(|| {
+                UserInputError::InvalidNetwork {
+                    network: name.to_string(),
+                    supported: Self::defaults().into_iter().map(|c| c.name).collect(),
+                }
+                .into()
+            })
+    }
+}
+
+/// On-chain footprint of one address on one chain
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountActivity {
+    pub chain: String,
+    pub chain_id: u64,
+    pub address: String,
+    /// Outgoing transaction count
+    pub nonce: u64,
+    /// Balance in wei, as decimal string
+    pub balance: String,
+    /// Deployed code present (contract or EIP-7702 delegation)
+    pub has_code: bool,
+    pub error: Option<String>,
+}
+
+impl AccountActivity {
+    pub fn is_active(&self) -> bool {
+        self.nonce > 0 || self.balance != "0" || self.has_code
+    }
+}
+
+/// Query nonce, balance and code of `address` on `chain` through `client` in one batched round trip
+#[cfg(feature = "native")]
+pub async fn probe_account(client: &RpcClient, chain: &ChainInfo, address: &str) -> WalletResult<AccountActivity> {
+    let mut results = client
+        .batch(vec![
+            RpcRequest::new("eth_chainId", json!([])),
+            RpcRequest::new("eth_getTransactionCount", json!([address, "latest"])),
+            RpcRequest::new("eth_getBalance", json!([address, "latest"])),
+            RpcRequest::new("eth_getCode", json!([address, "latest"])),
+        ])
+        .await?
+        .into_iter();
+
+    let mut next_str = || -> WalletResult<String> {
+        let value = results.next().unwrap_or(Ok(serde_json::Value::Null))?;
+        Ok(value.as_str().unwrap_or("0x0").to_string())
+    };
+
+    let reported_chain_id = parse_quantity("eth_chainId", &next_str()?)?;
+    if reported_chain_id != U256::from(chain.chain_id) {
+        return Err(NetworkError::InvalidConfiguration {
+            key: chain.name.clone(),
+            details: format!(
+                "RPC {} reports chain id {}, expected {}",
+                chain.rpc_url, reported_chain_id, chain.chain_id
+            ),
+        }
+        .into());
+    }
+
+    let nonce = parse_quantity("nonce", &next_str()?)?;
+    let balance = parse_quantity("balance", &next_str()?)?;
+    let code = next_str()?;
+
+    Ok(AccountActivity {
+        chain: chain.name.clone(),
+        chain_id: chain.chain_id,
+        address: address.to_string(),
+        nonce: nonce.low_u64(),
+        balance: balance.to_string(),
+        has_code: code.len() > 2,
+        error: None,
+    })
+}
+
+/// Probe `address` on every chain concurrently; per-chain failures are reported, not fatal
+#[cfg(feature = "native")]
+pub async fn probe_all(chains: Vec<ChainInfo>, address: &str, config: &WalletConfig) -> WalletResult<Vec<AccountActivity>> {
+    let mut tasks = tokio::task::JoinSet::new();
+    for (position, chain) in chains.into_iter().enumerate() {
+        let client = RpcClient::from_config(&chain.rpc_url, config)?;
+        let address = address.to_string();
+        tasks.spawn(async move {
+            let activity = match probe_account(&client, &chain, &address).await {
+                Ok(activity) => activity,
+                Err(err) => AccountActivity {
+                    chain: chain.name.clone(),
+                    chain_id: chain.chain_id,
+                    address,
+                    nonce: 0,
+                    balance: "0".to_string(),
+                    has_code: false,
+                    error: Some(err.to_string()),
+                },
+            };
+            (position, activity)
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok(result) = joined {
+            results.push(result);
+        }
+    }
+    results.sort_by_key(|(position, _)| *position);
+    Ok(results.into_iter().map(|(_, activity)| activity).collect())
+}
@@ -0,0 +1,67 @@
+use crate::config;
+use crate::errors::{AuthenticationError, WalletResult};
+use crate::models::{Keystore, Wallet};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+
+/// Restrictions enforced for wallets marked as cold storage
+pub struct ColdStoragePolicy;
+
+impl ColdStoragePolicy {
+    /// Checks before a cold wallet is written for the first time
+    pub fn check_save(wallet: &Wallet, backup_verified: bool) -> WalletResult<()> {
+        if !wallet.has_mnemonic() || !wallet.has_passphrase() {
+            return Err(AuthenticationError::ColdStorageViolation {
+                rule: "cold wallets require a BIP39 passphrase".to_string(),
+                suggestion: "Pass --passphrase when creating or importing a cold wallet".to_string(),
+            }
+            .into());
+        }
+
+        if !backup_verified {
+            return Err(AuthenticationError::ColdStorageViolation {
+                rule: "backup must be verified before the first save".to_string(),
+                suggestion: "Write down the mnemonic and passphrase, verify them, then pass --backup-verified".to_string(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Checks before a cold keystore is decrypted
+    pub fn check_decrypt(keystore: &Keystore, offline: bool) -> WalletResult<()> {
+        if !keystore.metadata.cold {
+            return Ok(());
+        }
+
+        if !offline {
+            return Err(AuthenticationError::ColdStorageViolation {
+                rule: "cold wallets can only be decrypted in --offline mode".to_string(),
+                suggestion: "Disconnect the machine from all networks and rerun with --offline".to_string(),
+            }
+            .into());
+        }
+
+        if let Some(probe) = Self::reachable_probe() {
+            return Err(AuthenticationError::ColdStorageViolation {
+                rule: format!("network connectivity detected ({} reachable)", probe),
+                suggestion: "Disconnect the machine from all networks before decrypting a cold wallet".to_string(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// First connectivity probe that accepts a TCP connection, if any
+    fn reachable_probe() -> Option<&'static str> {
+        let timeout = Duration::from_millis(config::cold::CONNECTIVITY_TIMEOUT_MS);
+        config::cold::CONNECTIVITY_PROBES.iter().copied().find(|probe| {
+            probe
+                .parse::<SocketAddr>()
+                .map(|addr| TcpStream::connect_timeout(&addr, timeout).is_ok())
+                .unwrap_or(false)
+        })
+    }
+}
@@ -0,0 +1,681 @@
+//! Core logic of the wallet commands, free of prompting and printing. The CLI collects
+//! passwords and renders the returned outcomes; other frontends can call the same functions.
+use crate::config;
+use crate::errors::{AuthenticationError, FilesystemError, UserInputError, ValidationError, WalletResult};
+use crate::models::command::{
+    AccountEntry, AccountsOutcome, ArchiveOutcome, ArchiveRestoreOutcome, CreateOutcome, DelegateOutcome, DeleteOutcome, DeriveOutcome, DerivedEntry, ExportOutcome, FixtureAccount, FixturesOutcome, ImportOutcome,
+    KeystoreSummary, ListOutcome, LoadOutcome, PublicKeyEntry, XpubOutcome, MigrateOutcome, PasswordChangeOutcome, RenameOutcome, RestoreBackupOutcome,
+};
+use crate::models::keystore::KeystoreVersion;
+use crate::models::{KeystoreFile, PathPreset, Wallet, WalletOverrides, WalletType};
+use crate::services::address_format::{self, Chain};
+use crate::services::mnemonic::language_name;
+use crate::services::signer::Signer;
+use crate::services::crypto::KdfSettings;
+use crate::services::{keystore_backup, wallet_archive, wallet_index, CryptoService, WalletManager};
+use crate::utils;
+use bip39::Language;
+use std::path::{Path, PathBuf};
+
+/// Where and how to persist a created or imported wallet
+#[derive(Debug, Clone)]
+pub struct SaveOptions {
+    pub path: PathBuf,
+    pub password: String,
+    /// Mark the keystore as cold storage, see `ColdStoragePolicy`
+    pub cold: bool,
+    pub backup_verified: bool,
+}
+
+/// How `account add` makes a new account
+#[derive(Debug, Clone)]
+pub enum NewAccount {
+    /// Derived from the wallet's seed under this parent path, or the next free BIP44 account
+    Derived { base_path: Option<String> },
+    Imported { private_key: String },
+}
+
+/// Source of an imported wallet
+#[derive(Debug, Clone)]
+pub enum ImportSource {
+    Mnemonic { phrase: String, passphrase: Option<String> },
+    PrivateKey(String),
+    /// Base58 BIP32 root key
+    Xprv(String),
+    /// Hex-encoded 64-byte BIP39 seed
+    SeedHex(String),
+    /// SLIP-39 shares holding the BIP32 seed, and the passphrase they were split under
+    Shamir { shares: Vec<String>, passphrase: Option<String> },
+    Keystore { path: PathBuf, password: String },
+    /// Account-level xpub, imported watch-only
+    Xpub(String),
+    /// Bare address, imported watch-only
+    Address(String),
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn create(
+    manager: &WalletManager,
+    words: u8,
+    language: Language,
+    user_entropy: Option<&[u8]>,
+    network: &str,
+    passphrase: Option<&str>,
+    account: Option<(u32, u32)>,
+    save: Option<SaveOptions>,
+) -> WalletResult<CreateOutcome> {
+    let wallet = new_wallet(manager, words, language, user_entropy, network, passphrase, account).await?;
+    save_created(manager, &wallet, language, save).await
+}
+
+/// First half of `create`: the wallet, not saved yet, so the caller can check the user's backup
+/// of its mnemonic before `save_created`
+pub async fn new_wallet(
+    manager: &WalletManager,
+    words: u8,
+    language: Language,
+    user_entropy: Option<&[u8]>,
+    network: &str,
+    passphrase: Option<&str>,
+    account: Option<(u32, u32)>,
+) -> WalletResult<Wallet> {
+    let wallet = manager.create_wallet_in_language(words, language, user_entropy, network, passphrase).await?;
+    match account {
+        Some((account, change)) => wallet.with_account(account, change),
+        None => Ok(wallet),
+    }
+}
+
+/// Second half of `create`
+pub async fn save_created(manager: &WalletManager, wallet: &Wallet, language: Language, save: Option<SaveOptions>) -> WalletResult<CreateOutcome> {
+    let saved_to = save_wallet(manager, wallet, save).await?;
+
+    Ok(CreateOutcome {
+        address: checksum(wallet.address()),
+        network: wallet.network().to_string(),
+        mnemonic: wallet.mnemonic().to_string(),
+        language: language_name(language).to_string(),
+        derivation_path: wallet.derivation_path().to_string(),
+        created_at: wallet.created_at(),
+        saved_to,
+    })
+}
+
+pub async fn import(
+    manager: &WalletManager,
+    source: ImportSource,
+    path_preset: Option<PathPreset>,
+    save: Option<SaveOptions>,
+) -> WalletResult<ImportOutcome> {
+    let wallet = match source {
+        ImportSource::Mnemonic { phrase, passphrase: Some(passphrase) } => {
+            manager.import_from_mnemonic_with_passphrase(&phrase, &passphrase).await?
+        }
+        ImportSource::Mnemonic { phrase, passphrase: None } => manager.import_from_mnemoic(&phrase).await?,
+        ImportSource::PrivateKey(key) => manager.import_from_private_key(&key).await?,
+        ImportSource::Xprv(xprv) => manager.import_from_xprv(&xprv).await?,
+        ImportSource::SeedHex(seed) => manager.import_from_seed_hex(&seed).await?,
+        ImportSource::Shamir { shares, passphrase } => {
+            manager.import_from_shamir(&shares, passphrase.as_deref().unwrap_or("")).await?
+        }
+        ImportSource::Keystore { path, password } => manager.import_from_keystore(&path, &password).await?,
+        ImportSource::Xpub(xpub) => manager.import_watch_only_xpub(&xpub).await?,
+        ImportSource::Address(address) => manager.import_watch_only_address(&address).await?,
+    };
+    let wallet = match path_preset {
+        Some(preset) => wallet.with_path_preset(preset)?,
+        None => wallet,
+    };
+    let saved_to = save_wallet(manager, &wallet, save).await?;
+
+    Ok(ImportOutcome {
+        address: checksum(wallet.address()),
+        network: wallet.network().to_string(),
+        has_mnemonic: wallet.has_mnemonic(),
+        is_hd: wallet.is_hd(),
+        wallet_type: wallet.wallet_type(),
+        derivation_path: wallet.derivation_path().to_string(),
+        created_at: wallet.created_at(),
+        saved_to,
+    })
+}
+
+/// Decrypt a keystore, optionally deriving the child address at `derive`
+pub async fn load(
+    manager: &WalletManager,
+    path: &Path,
+    password: &str,
+    derive: Option<u32>,
+) -> WalletResult<LoadOutcome> {
+    let wallet = manager.load_wallet(path, password).await?;
+    load_outcome(&wallet, derive)
+}
+
+/// Describe an unlocked wallet for `load`, with the child address at `derive` if asked for
+pub fn load_outcome(wallet: &Wallet, derive: Option<u32>) -> WalletResult<LoadOutcome> {
+    let derived = match derive {
+        Some(index) if !wallet.is_hd() => {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "derive".to_string(),
+                value: index.to_string(),
+                expected: "HD wallet with mnemonic or root key".to_string(),
+            }
+            .into())
+        }
+        Some(index) => {
+            let derived = wallet.derive_address(index)?;
+            Some(DerivedEntry {
+                index,
+                address: checksum(derived.address()),
+                derivation_path: derived.derivation_path().to_string(),
+                address_chain: derived.address_chain(),
+                private_key: None,
+            })
+        }
+        None => None,
+    };
+
+    Ok(LoadOutcome {
+        address: checksum(wallet.address()),
+        network: wallet.network().to_string(),
+        has_mnemonic: wallet.has_mnemonic(),
+        is_hd: wallet.is_hd(),
+        wallet_type: wallet.wallet_type(),
+        derivation_path: wallet.derivation_path().to_string(),
+        alias: wallet.alias().map(str::to_string),
+        created_at: wallet.created_at(),
+        derived,
+    })
+}
+
+/// Read a keystore's public metadata without decrypting it
+pub async fn inspect(path: &Path) -> WalletResult<KeystoreSummary> {
+    match CryptoService::load_any_keystore(path).await? {
+        KeystoreFile::Native(keystore) => Ok(KeystoreSummary {
+            file: path.to_path_buf(),
+            address: checksum(&keystore.metadata.address),
+            format: "native".to_string(),
+            network: Some(keystore.metadata.network),
+            created_at: Some(keystore.metadata.created_at),
+            alias: keystore.metadata.alias,
+            wallet_type: keystore.metadata.wallet_type,
+        }),
+        KeystoreFile::V3(keystore) => {
+            let address = keystore.address().ok_or_else(|| ValidationError::InvalidKeystoreSchema {
+                error: "v3 keystore does not record its address, load it with a password".to_string(),
+                file_path: path.display().to_string(),
+            })?;
+            Ok(KeystoreSummary {
+                file: path.to_path_buf(),
+                address: checksum(&address),
+                format: "v3".to_string(),
+                network: None,
+                created_at: None,
+                alias: None,
+                wallet_type: WalletType::Full,
+            })
+        }
+    }
+}
+
+/// Change a native keystore's alias and/or move it to `new_path`, together with its
+/// `<wallet>.toml` overrides. The alias is plaintext metadata, so no password is needed;
+/// with `password` the keystore is decrypted first to prove ownership.
+/// `alias`: `None` keeps the current one, `Some(None)` clears it.
+pub async fn rename(
+    manager: &WalletManager,
+    path: &Path,
+    alias: Option<Option<String>>,
+    new_path: Option<&Path>,
+    password: Option<&str>,
+) -> WalletResult<RenameOutcome> {
+    if let Some(new_path) = new_path.filter(|p| p.exists()) {
+        return Err(FilesystemError::FileExists {
+            path: new_path.display().to_string(),
+            suggestion: "choose another name or delete the existing wallet first".to_string(),
+        }
+        .into());
+    }
+
+    let mut keystore = match CryptoService::load_any_keystore(path).await? {
+        KeystoreFile::Native(keystore) => keystore,
+        KeystoreFile::V3(_) => {
+            return Err(ValidationError::InvalidKeystoreSchema {
+                error: "v3 keystores have no alias metadata, import the wallet to manage it".to_string(),
+                file_path: path.display().to_string(),
+            }
+            .into())
+        }
+    };
+    if let Some(password) = password {
+        manager.load_wallet(path, password).await?;
+    }
+
+    if let Some(alias) = alias {
+        keystore.metadata.alias = alias;
+        manager.write_keystore(path, keystore.to_json()?.as_bytes()).await?;
+    }
+
+    let mut file = path.to_path_buf();
+    let mut previous_file = None;
+    if let Some(new_path) = new_path {
+        tokio::fs::rename(path, new_path).await?;
+        let sidecar = WalletOverrides::sidecar_path(path);
+        if sidecar.exists() {
+            tokio::fs::rename(&sidecar, WalletOverrides::sidecar_path(new_path)).await?;
+        }
+        keystore_backup::move_all(path, new_path).await?;
+        previous_file = Some(file);
+        file = new_path.to_path_buf();
+    }
+
+    Ok(RenameOutcome {
+        file,
+        previous_file,
+        address: checksum(&keystore.metadata.address),
+        alias: keystore.metadata.alias.clone(),
+        verified: password.is_some(),
+    })
+}
+
+/// Roll the keystore at `path` back to `backup`, or to its newest backup. With `list_only`
+/// nothing changes and the outcome only lists the backups.
+pub async fn restore_backup(manager: &WalletManager, path: &Path, backup: Option<&Path>, list_only: bool) -> WalletResult<RestoreBackupOutcome> {
+    let (restored_from, address) = if list_only {
+        (None, None)
+    } else {
+        let restored_from = keystore_backup::restore(path, backup, manager.config().keystore_backups).await?;
+        let address = CryptoService::load_any_keystore(path).await?.address().map(|address| checksum(&address));
+        (Some(restored_from), address)
+    };
+    Ok(RestoreBackupOutcome {
+        file: path.to_path_buf(),
+        restored_from,
+        address,
+        backups: keystore_backup::list(path).await?,
+    })
+}
+
+/// Accounts of the keystore at `path`
+pub async fn list_accounts(manager: &WalletManager, path: &Path, password: &str) -> WalletResult<AccountsOutcome> {
+    let (_, wallet) = manager.load_accounts(path, password).await?;
+    Ok(accounts_outcome(path, &wallet))
+}
+
+/// Add the account `name` to the keystore at `path`, selecting it when `select` is set
+pub async fn add_account(
+    manager: &WalletManager,
+    path: &Path,
+    password: &str,
+    name: &str,
+    account: NewAccount,
+    select: bool,
+) -> WalletResult<AccountsOutcome> {
+    let (keystore, mut wallet) = manager.load_accounts(path, password).await?;
+    if wallet.is_watch_only() {
+        return Err(AuthenticationError::WatchOnlyWallet { address: checksum(wallet.address()) }.into());
+    }
+    match account {
+        NewAccount::Derived { base_path } => wallet.add_derived_account(name, base_path.as_deref())?,
+        NewAccount::Imported { private_key } => wallet.add_imported_account(name, &private_key)?,
+    };
+    if select {
+        wallet.select_account(name)?;
+    }
+    manager.save_accounts(path, keystore, &wallet, password).await?;
+    Ok(accounts_outcome(path, &wallet))
+}
+
+/// Make the account `name` of the keystore at `path` the one that signs
+pub async fn select_account(manager: &WalletManager, path: &Path, password: &str, name: &str) -> WalletResult<AccountsOutcome> {
+    let (keystore, mut wallet) = manager.load_accounts(path, password).await?;
+    wallet.select_account(name)?;
+    manager.save_accounts(path, keystore, &wallet, password).await?;
+    Ok(accounts_outcome(path, &wallet))
+}
+
+fn accounts_outcome(path: &Path, wallet: &Wallet) -> AccountsOutcome {
+    let selected = wallet.selected_account().unwrap_or(config::wallet::PRIMARY_ACCOUNT_NAME).to_string();
+    let primary = AccountEntry {
+        name: config::wallet::PRIMARY_ACCOUNT_NAME.to_string(),
+        address: checksum(wallet.address()),
+        derivation_path: wallet.is_hd().then(|| wallet.derivation_path().to_string()),
+        selected: wallet.selected_account().is_none(),
+    };
+    let others = wallet.accounts().iter().map(|account| AccountEntry {
+        name: account.name().to_string(),
+        address: checksum(account.address()),
+        derivation_path: account.derivation_path().map(str::to_string),
+        selected: wallet.selected_account() == Some(account.name()),
+    });
+    let accounts = std::iter::once(primary).chain(others).collect();
+    AccountsOutcome {
+        file: path.to_path_buf(),
+        selected,
+        accounts,
+    }
+}
+
+/// Encrypt the manager's whole wallets directory into a new archive at `out`, under the
+/// configured KDF
+pub async fn create_archive(manager: &WalletManager, out: &Path, password: &str) -> WalletResult<ArchiveOutcome> {
+    let config = manager.config();
+    CryptoService::check_password(password, config.allow_weak_passwords)?;
+    let kdf = KdfSettings::from_config(config);
+    let files = wallet_archive::create(&config.wallets_path, out, password, kdf).await?;
+    Ok(ArchiveOutcome {
+        archive: out.to_path_buf(),
+        directory: config.wallets_path.clone(),
+        files,
+        kdf: kdf.algorithm().as_str().to_string(),
+    })
+}
+
+/// Unpack an archive from `create_archive` into the manager's wallets directory; see
+/// `wallet_archive::restore` for when `force` is needed
+pub async fn restore_archive(manager: &WalletManager, archive: &Path, password: &str, force: bool) -> WalletResult<ArchiveRestoreOutcome> {
+    let config = manager.config();
+    let report = wallet_archive::restore(&config.wallets_path, archive, password, force, config.keystore_backups).await?;
+    Ok(ArchiveRestoreOutcome {
+        archive: archive.to_path_buf(),
+        directory: config.wallets_path.clone(),
+        created_at: report.created_at,
+        restored: report.restored,
+        unchanged: report.unchanged,
+    })
+}
+
+/// Record in a native keystore's metadata that its mnemonic backup passed `verify-backup`;
+/// v3 keystores have nowhere to keep the flag and are left alone
+pub async fn mark_backup_verified(path: &Path) -> WalletResult<bool> {
+    let KeystoreFile::Native(mut keystore) = CryptoService::load_any_keystore(path).await? else {
+        return Ok(false);
+    };
+    if !keystore.metadata.backup_verified {
+        keystore.metadata.backup_verified = true;
+        utils::write_private(path, keystore.to_json()?.as_bytes()).await?;
+    }
+    Ok(true)
+}
+
+/// Remove a keystore file, overwriting its contents first when `shred` is set.
+/// Confirmation is the caller's job; see `inspect` for what to show the user.
+pub async fn delete(path: &Path, shred: bool) -> WalletResult<DeleteOutcome> {
+    if !path.is_file() {
+        return Err(FilesystemError::FileNotFound {
+            path: path.display().to_string(),
+            director: path.parent().map(|p| p.display().to_string()).unwrap_or_default(),
+        }
+        .into());
+    }
+    let summary = inspect(path).await.ok();
+
+    if shred {
+        utils::shred_file(path).await?;
+    } else {
+        tokio::fs::remove_file(path).await?;
+    }
+
+    Ok(DeleteOutcome {
+        file: path.to_path_buf(),
+        address: summary.as_ref().map(|s| s.address.clone()),
+        alias: summary.and_then(|s| s.alias),
+        shredded: shred,
+    })
+}
+
+/// Native keystores in `dir` by file name, creating the directory when it does not exist yet.
+/// Metadata comes from the directory's index, see `wallet_index`.
+pub async fn list(dir: &Path) -> WalletResult<ListOutcome> {
+    let created = !dir.exists();
+    if created {
+        tokio::fs::create_dir_all(dir).await.map_err(|e| FilesystemError::DirectoryNotAccessible {
+            path: dir.display().to_string(),
+            details: e.to_string(),
+        })?;
+    }
+
+    Ok(ListOutcome {
+        directory: dir.to_path_buf(),
+        created,
+        wallets: wallet_index::refresh(dir).await?,
+    })
+}
+
+/// `count` consecutive addresses from `start_index` under the signer's account path
+pub async fn derive(signer: &dyn Signer, start_index: u32, count: u32) -> WalletResult<DeriveOutcome> {
+    let mut addresses = Vec::with_capacity(count as usize);
+    for index in start_index..start_index + count {
+        let derived = signer.derive_address(index).await?;
+        addresses.push(DerivedEntry {
+            index,
+            address: checksum(derived.address()),
+            derivation_path: derived.derivation_path().to_string(),
+            address_chain: derived.address_chain(),
+            private_key: None,
+        });
+    }
+
+    Ok(DeriveOutcome {
+        base_address: checksum(&signer.address().await?),
+        base_path: signer.base_path(),
+        start_index,
+        addresses,
+    })
+}
+
+/// Fill in the private key of every address in `outcome`, derived from `wallet` at its path
+pub fn reveal_private_keys(wallet: &Wallet, outcome: &mut DeriveOutcome) -> WalletResult<()> {
+    if wallet.is_watch_only() {
+        return Err(AuthenticationError::WatchOnlyWallet { address: checksum(wallet.address()) }.into());
+    }
+    for entry in &mut outcome.addresses {
+        let key = wallet.hd_private_key(&entry.derivation_path)?;
+        entry.private_key = Some(format!("0x{}", hex::encode(key.as_slice())));
+    }
+    Ok(())
+}
+
+/// Account xpub of `wallet`, plus the public keys of the first `public_keys` addresses under it
+pub fn xpub(wallet: &Wallet, public_keys: u32) -> WalletResult<XpubOutcome> {
+    let mut entries = Vec::with_capacity(public_keys as usize);
+    for index in 0..public_keys {
+        let derived = wallet.derive_address(index)?;
+        entries.push(PublicKeyEntry {
+            index,
+            derivation_path: derived.derivation_path().to_string(),
+            address: checksum(derived.address()),
+            public_key: wallet.public_key_at(index)?,
+        });
+    }
+
+    Ok(XpubOutcome {
+        address: checksum(wallet.address()),
+        account_path: wallet.account_path().to_string(),
+        xpub: wallet.account_xpub()?,
+        public_keys: entries,
+    })
+}
+
+/// Addresses and keys of the first `count` accounts of a public test mnemonic, given by its
+/// phrase or short name. Anything else is refused: private keys end up in plain files.
+pub async fn fixtures(manager: &WalletManager, mnemonic: &str, passphrase: Option<&str>, count: u32) -> WalletResult<FixturesOutcome> {
+    use ethers::signers::LocalWallet;
+
+    let phrase = mnemonic.split_whitespace().collect::<Vec<_>>().join(" ");
+    let Some((name, phrase)) = config::fixtures::TEST_MNEMONICS
+        .iter()
+        .find(|(name, known)| *name == phrase || *known == phrase)
+    else {
+        let names: Vec<&str> = config::fixtures::TEST_MNEMONICS.iter().map(|(name, _)| *name).collect();
+        return Err(UserInputError::InvalidParameters {
+            parameter: "mnemonic".to_string(),
+            value: "<hidden>".to_string(),
+            expected: format!("a public test mnemonic or its name: {}", names.join(", ")),
+        }
+        .into());
+    };
+    if count == 0 || count > config::fixtures::MAX_COUNT {
+        return Err(UserInputError::InvalidParameters {
+            parameter: "count".to_string(),
+            value: count.to_string(),
+            expected: format!("1 to {}", config::fixtures::MAX_COUNT),
+        }
+        .into());
+    }
+
+    let passphrase = passphrase.unwrap_or_default();
+    let wallet = manager.import_from_mnemonic_with_passphrase(phrase, passphrase).await?;
+    let mut accounts = Vec::with_capacity(count as usize);
+    for index in 0..count {
+        let derived = wallet.derive_address(index)?;
+        let key = wallet.hd_private_key(derived.derivation_path())?;
+        let signer = LocalWallet::from_bytes(&key).map_err(|e| crate::errors::CryptographicError::InvalidPrivateKey {
+            detail: e.to_string(),
+            expected: "valid secp256k1 private key".to_string(),
+        })?;
+        let public_key = signer.signer().verifying_key().to_encoded_point(false);
+        accounts.push(FixtureAccount {
+            index,
+            derivation_path: derived.derivation_path().to_string(),
+            address: checksum(derived.address()),
+            private_key: format!("0x{}", hex::encode(key.as_slice())),
+            public_key: format!("0x{}", hex::encode(public_key.as_bytes())),
+        });
+    }
+
+    Ok(FixturesOutcome {
+        version: config::fixtures::FORMAT_VERSION,
+        name: name.to_string(),
+        mnemonic: phrase.to_string(),
+        passphrase: passphrase.to_string(),
+        base_path: wallet.derivation_path().to_string(),
+        accounts,
+    })
+}
+
+/// Re-encrypt `wallet` as a keystore v3 file at `out`, which must not exist yet
+pub async fn export_v3(wallet: &Wallet, out: &Path, password: &str, light: bool) -> WalletResult<ExportOutcome> {
+    if out.exists() {
+        return Err(FilesystemError::FileExists {
+            path: out.display().to_string(),
+            suggestion: "choose another --out path or remove the existing file".to_string(),
+        }
+        .into());
+    }
+
+    let private_key = zeroize::Zeroizing::new(wallet.signer()?.signer().to_bytes().to_vec());
+    let (address, password) = (wallet.address().to_string(), zeroize::Zeroizing::new(password.to_string()));
+    let keystore = utils::blocking(move || CryptoService::encrypt_v3(&private_key, &address, &password, light)).await?;
+    utils::write_private(out, serde_json::to_string_pretty(&keystore)?.as_bytes()).await?;
+
+    Ok(ExportOutcome {
+        address: checksum(wallet.address()),
+        format: "v3".to_string(),
+        file: out.to_path_buf(),
+    })
+}
+
+/// Save the key at `index` of `wallet` alone in a new keystore at `out` under `password`.
+/// The keystore holds no mnemonic, so whoever runs it can sign for that address only.
+pub async fn delegate(manager: &WalletManager, wallet: &Wallet, index: u32, out: &Path, password: &str) -> WalletResult<DelegateOutcome> {
+    if out.exists() {
+        return Err(FilesystemError::FileExists {
+            path: out.display().to_string(),
+            suggestion: "choose another --export-to path; an existing keystore is never overwritten".to_string(),
+        }
+        .into());
+    }
+
+    let derived = wallet.derive_address(index)?;
+    let child = wallet.delegate(index)?;
+    let save = SaveOptions {
+        path: out.to_path_buf(),
+        password: password.to_string(),
+        cold: false,
+        backup_verified: false,
+    };
+    save_wallet(manager, &child, Some(save)).await?;
+
+    Ok(DelegateOutcome {
+        address: checksum(child.address()),
+        parent_address: checksum(wallet.address()),
+        derivation_path: derived.derivation_path().to_string(),
+        file: out.to_path_buf(),
+    })
+}
+
+/// Re-encrypt the keystore at `path` under a new password, replacing the file atomically
+pub async fn change_password(
+    manager: &WalletManager,
+    path: &Path,
+    old_password: &str,
+    new_password: &str,
+) -> WalletResult<PasswordChangeOutcome> {
+    let (address, format, kdf) = match manager.change_password(path, old_password, new_password).await? {
+        KeystoreFile::Native(keystore) => (keystore.metadata.address, "native", keystore.crypto.kdf),
+        KeystoreFile::V3(keystore) => (keystore.address().unwrap_or_default(), "v3", keystore.crypto.kdf),
+    };
+
+    Ok(PasswordChangeOutcome {
+        address: checksum(&address),
+        format: format.to_string(),
+        kdf,
+        file: path.to_path_buf(),
+    })
+}
+
+/// Upgrade a native keystore to the latest schema version. With `rekey_password`, a keystore
+/// whose KDF is outdated is also re-encrypted with the configured KDF.
+pub async fn migrate(
+    manager: &WalletManager,
+    path: &Path,
+    dry_run: bool,
+    rekey_password: Option<&str>,
+) -> WalletResult<MigrateOutcome> {
+    let (keystore, original) = manager.migrate_keystore(path, dry_run).await?;
+    let kdf_outdated = manager.kdf_outdated(&keystore);
+
+    let mut kdf = keystore.crypto.kdf.clone();
+    let mut rekeyed = false;
+    if let Some(password) = rekey_password.filter(|_| kdf_outdated && !dry_run) {
+        if let KeystoreFile::Native(updated) = manager.change_password(path, password, password).await? {
+            kdf = updated.crypto.kdf;
+        }
+        rekeyed = true;
+    }
+
+    Ok(MigrateOutcome {
+        file: path.to_path_buf(),
+        from_version: original.as_str().to_string(),
+        to_version: KeystoreVersion::LATEST.as_str().to_string(),
+        migrated: original < KeystoreVersion::LATEST,
+        dry_run,
+        kdf,
+        kdf_outdated: kdf_outdated && !rekeyed,
+        rekeyed,
+    })
+}
+
+async fn save_wallet(manager: &WalletManager, wallet: &Wallet, save: Option<SaveOptions>) -> WalletResult<Option<PathBuf>> {
+    let Some(save) = save else {
+        return Ok(None);
+    };
+    if let Some(dir) = save.path.parent() {
+        tokio::fs::create_dir_all(dir).await.map_err(|e| FilesystemError::DirectoryNotAccessible {
+            path: dir.display().to_string(),
+            details: e.to_string(),
+        })?;
+    }
+
+    if save.cold {
+        manager.save_cold_wallet(wallet, &save.path, &save.password, save.backup_verified).await?;
+    } else {
+        manager.save_wallet(wallet, &save.path, &save.password).await?;
+    }
+    Ok(Some(save.path))
+}
+
+fn checksum(address: &str) -> String {
+    address_format::display(Chain::Evm, address)
+}
@@ -1,17 +1,118 @@
 use crate::config;
 use crate::errors::{CryptographicError, WalletResult};
-use crate::models::{Keystore, Wallet};
-use crate::models::keystore::KdfParams;
+use crate::WalletConfig;
+use crate::models::{Keystore, KeystoreFile, KeystoreV3, Wallet};
+use crate::models::keystore::{KdfAlgorithm, KdfParams};
+use crate::models::keystore_v3::{V3CipherParams, V3Crypto, V3KdfParams};
+use aes::cipher::{KeyIvInit, StreamCipher};
 use aes_gcm::{
     aead::{Aead, KeyInit},
-    Aes256Gcm, Key, Nonce,
+    Aes256Gcm, Nonce,
 };
 use argon2::{Algorithm, Argon2, Params, Version};
 use pbkdf2::pbkdf2_hmac;
 use rand::RngCore;
 use sha2::Sha256;
+#[cfg(feature = "native")]
 use std::path::Path;
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use crate::services::locked_memory::RegionLock;
+use crate::services::password_strength;
+use zeroize::Zeroizing;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+/// Algorithm and cost parameters for a new native keystore's KDF
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "algorithm", rename_all = "lowercase")]
+pub enum KdfSettings {
+    Argon2id { memory: u32, iterations: u32, parallelism: u32 },
+    Scrypt { log_n: u8, r: u32, p: u32 },
+    Pbkdf2 { iterations: u32 },
+}
+
+impl std::fmt::Display for KdfSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Argon2id { memory, iterations, parallelism } => {
+                format!("memory={} KiB, t={}, p={}", memory, iterations, parallelism)
+            }
+            Self::Scrypt { log_n, r, p } => format!("N=2^{}, r={}, p={}", log_n, r, p),
+            Self::Pbkdf2 { iterations } => format!("c={}", iterations),
+        };
+        // Honour width and alignment so the settings line up in tables
+        f.pad(&text)
+    }
+}
+
+impl KdfSettings {
+    /// Keystore parameters recording these settings and `salt`
+    pub fn params(&self, salt: &[u8]) -> KdfParams {
+        let dklen = config::crypto::KEY_LENGTH as u32;
+        match *self {
+            Self::Argon2id { memory, iterations, parallelism } => KdfParams::Argon2 {
+                dklen,
+                memory,
+                time: iterations,
+                parallelism,
+                salt: hex::encode(salt),
+            },
+            Self::Scrypt { log_n, r, p } => KdfParams::Scrypt {
+                dklen,
+                n: 1 << log_n,
+                r,
+                p,
+                salt: hex::encode(salt),
+            },
+            Self::Pbkdf2 { iterations } => KdfParams::Pbkdf2 {
+                dklen,
+                c: iterations,
+                prf: "hmac-sha256".to_string(),
+                salt: hex::encode(salt),
+            },
+        }
+    }
+
+    pub fn algorithm(&self) -> KdfAlgorithm {
+        match self {
+            Self::Argon2id { .. } => KdfAlgorithm::Argon2id,
+            Self::Scrypt { .. } => KdfAlgorithm::Scrypt,
+            Self::Pbkdf2 { .. } => KdfAlgorithm::Pbkdf2,
+        }
+    }
+
+    /// True when `params` use this algorithm at no lower a cost
+    pub fn satisfied_by(&self, params: &KdfParams) -> bool {
+        match (*self, params) {
+            (Self::Argon2id { memory, iterations, parallelism }, KdfParams::Argon2 { memory: m, time, parallelism: p, .. }) => {
+                *m >= memory && *time >= iterations && *p >= parallelism
+            }
+            (Self::Scrypt { log_n, r, p }, KdfParams::Scrypt { n, r: r2, p: p2, .. }) => {
+                u64::from(*n) >= 1u64 << log_n && *r2 >= r && *p2 >= p
+            }
+            (Self::Pbkdf2 { iterations }, KdfParams::Pbkdf2 { c, .. }) => *c >= iterations,
+            _ => false,
+        }
+    }
+
+    /// The configured algorithm with its configured cost
+    pub fn from_config(config: &WalletConfig) -> Self {
+        match config.kdf {
+            KdfAlgorithm::Argon2id => Self::Argon2id {
+                memory: config.kdf_memory,
+                iterations: config.kdf_iterations,
+                parallelism: config.kdf_parallelism,
+            },
+            KdfAlgorithm::Scrypt => Self::Scrypt {
+                log_n: config.kdf_scrypt_log_n,
+                r: config::crypto::V3_SCRYPT_R,
+                p: config::crypto::V3_SCRYPT_P,
+            },
+            KdfAlgorithm::Pbkdf2 => Self::Pbkdf2 {
+                iterations: config.kdf_pbkdf2_iterations,
+            },
+        }
+    }
+}
 
 pub struct CryptoService;
 
@@ -21,11 +122,28 @@ impl CryptoService {
         password:&str,
         use_argon2: bool
     ) -> WalletResult<Keystore> {
-        let wallet_data = serde_json::to_vec(wallet).map_err(|e|{
+        let kdf = if use_argon2 {
+            let (memory, iterations, parallelism) = config::get_argon2_config(false);
+            KdfSettings::Argon2id { memory, iterations, parallelism }
+        } else {
+            KdfSettings::Pbkdf2 { iterations: config::crypto::PBKDF2_ITERATIONS }
+        };
+        Self::encrypt_wallet_with(wallet, password, kdf)
+    }
+
+    /// Encrypt `wallet` into a native keystore, deriving the key with `kdf`
+    pub fn encrypt_wallet_with(
+        wallet: &Wallet,
+        password: &str,
+        kdf: KdfSettings
+    ) -> WalletResult<Keystore> {
+        // The serialized payload holds the mnemonic and keys in the clear
+        let wallet_data = Zeroizing::new(serde_json::to_vec(&wallet.to_payload()).map_err(|e|{
             CryptographicError::KdfFailed{
                 details: format!("Wallet serialization failed: {}", e),
             }
-        })?;
+        })?);
+        let _locked = RegionLock::new(&wallet_data);
 
         let mut salt = vec![0u8; config::crypto::SALT_LENGTH];
         let mut nonce_bytes = vec![0u8; config::crypto::NONCE_LENGTH];
@@ -33,38 +151,10 @@ impl CryptoService {
         rand::thread_rng().fill_bytes(&mut salt);
         rand::thread_rng().fill_bytes(&mut nonce_bytes);
 
-        let mut key_bytes = vec![0u8; config::crypto::KEY_LENGTH];
-
-        let kdf_params = if use_argon2 {
-            let (memory, iterations, parallelism) = config::get_argon2_config(false);
-
-            Self::derive_key_argon2(
-                password.as_bytes(),
-                &salt,
-                memory,
-                iterations,
-                parallelism,
-                &mut key_bytes
-            )?;
-
-            KdfParams::Argon2{
-                dklen: config::crypto::KEY_LENGTH as u32,
-                memory,
-                time: iterations,
-                parallelism,
-                salt: hex::encode(&salt)
-            }
+        let mut key_bytes = Zeroizing::new(vec![0u8; config::crypto::KEY_LENGTH]);
 
-        } else {
-            const PBKDF2_ITERATIONS: u32 = 100_000;
-            pbkdf2_hmac::<Sha256>(password.as_bytes(), &salt, PBKDF2_ITERATIONS, &mut key_bytes);
-            KdfParams::Pbkdf2 { 
-                dklen: config::crypto::KEY_LENGTH as u32,
-                c: PBKDF2_ITERATIONS,
-                prf: "hmc-sha256".to_string(),
-                salt: hex::encode(&salt)
-             }
-        };
+        let kdf_params = kdf.params(&salt);
+        Self::derive_key(password, &salt, &kdf_params, &mut key_bytes)?;
 
         let cipher = Aes256Gcm::new_from_slice(&key_bytes).map_err(|e| {
             CryptographicError::KdfFailed {
@@ -73,7 +163,7 @@ impl CryptoService {
         })?;
 
         let nonce = Nonce::from_slice(&nonce_bytes);
-        let ciphertext = cipher.encrypt(nonce, wallet_data.as_ref()).map_err(|e| {
+        let ciphertext = cipher.encrypt(nonce, wallet_data.as_slice()).map_err(|e| {
             CryptographicError::DecryptionFailed {
                 context: format!("Encryption failed: {}", e),
             }
@@ -81,11 +171,8 @@ impl CryptoService {
 
         // Create keystore
         let mac = Self::compute_mac(&key_bytes, &ciphertext, &nonce_bytes)?;
-        
-        // Clear sensitive data
-        key_bytes.zeroize();
 
-        Ok(Keystore::new(
+        let mut keystore = Keystore::new(
             wallet.alias().map(|s| s.to_string()),
             wallet.address().to_string(),
             wallet.network().to_string(),
@@ -94,7 +181,39 @@ impl CryptoService {
             nonce_bytes,
             mac,
             kdf_params
-        ))
+        );
+        keystore.metadata.wallet_type = wallet.wallet_type();
+        Ok(keystore)
+    }
+
+    /// Run the KDF recorded in a keystore over `password`
+    pub(crate) fn derive_key(password: &str, salt: &[u8], params: &KdfParams, output: &mut [u8]) -> WalletResult<()> {
+        match params {
+            KdfParams::Argon2 { memory, time, parallelism, .. } => {
+                Self::derive_key_argon2(password.as_bytes(), salt, *memory, *time, *parallelism, output)
+            }
+            KdfParams::Pbkdf2 { c, .. } => {
+                pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, *c, output);
+                Ok(())
+            }
+            KdfParams::Scrypt { n, r, p, .. } => Self::derive_key_scrypt(password.as_bytes(), salt, *n, *r, *p, output),
+        }
+    }
+
+    fn derive_key_scrypt(password: &[u8], salt: &[u8], n: u32, r: u32, p: u32, output: &mut [u8]) -> WalletResult<()> {
+        if n < 2 || !n.is_power_of_two() {
+            return Err(CryptographicError::KdfFailed {
+                details: format!("scrypt n must be a power of two, got {}", n),
+            }
+            .into());
+        }
+        let params = scrypt::Params::new(n.trailing_zeros() as u8, r, p, output.len()).map_err(|e| {
+            CryptographicError::KdfFailed { details: format!("Invalid scrypt parameters: {}", e) }
+        })?;
+        scrypt::scrypt(password, salt, &params, output).map_err(|e| {
+            CryptographicError::KdfFailed { details: format!("scrypt failed: {}", e) }
+        })?;
+        Ok(())
     }
 
     ///convert the password to a high-crypto, completely random key
@@ -137,41 +256,48 @@ impl CryptoService {
         Ok(mac.finalize().into_bytes().to_vec())
     }
 
+    /// Refuse a password for a new keystore unless it is long enough and its estimated
+    /// strength reaches `MIN_PASSWORD_STRENGTH_BITS`; see `password_strength`
     pub fn validate_password(password: &str) -> WalletResult<()> {
-        let mut requirements = Vec::new();
-
-        if password.len() < config::crypto::MIN_PASSWORD_LENGTH {
-            requirements.push(format!("At least {} characters", config::crypto::MIN_PASSWORD_LENGTH));
-        }
-
-        if password.len() > config::crypto::MAX_PASSWORD_LENGTH {
-            requirements.push(format!("At most {} characters", config::crypto::MAX_PASSWORD_LENGTH));
-        }
+        Self::check_password(password, false)
+    }
 
-        if !password.chars().any(|c| c.is_ascii_lowercase()) {
-            requirements.push("At least one lowercase letter".to_string());
+    /// As `validate_password`, but with `allow_weak` a short or weak password only warns;
+    /// the length limit still applies
+    pub fn check_password(password: &str, allow_weak: bool) -> WalletResult<()> {
+        let length = password.chars().count();
+        if length > config::crypto::MAX_PASSWORD_LENGTH {
+            return Err(crate::errors::AuthenticationError::WeakPassword {
+                requirements: vec![format!("At most {} characters", config::crypto::MAX_PASSWORD_LENGTH)],
+            }
+            .into());
         }
 
-        if !password.chars().any(|c| c.is_ascii_uppercase()) {
-            requirements.push("At least one uppercase letter".to_string());
+        let mut requirements = Vec::new();
+        if length < config::crypto::MIN_PASSWORD_LENGTH {
+            requirements.push(format!("At least {} characters", config::crypto::MIN_PASSWORD_LENGTH));
         }
-
-        if !password.chars().any(|c| c.is_ascii_digit()) {
-            requirements.push("At least one digit".to_string());
+        let strength = password_strength::estimate(password);
+        if !strength.is_strong() {
+            requirements.push(format!(
+                "An estimated strength of at least {} bits rather than {:.0}",
+                config::crypto::MIN_PASSWORD_STRENGTH_BITS,
+                strength.bits
+            ));
+            requirements.extend(strength.weaknesses.iter().map(|weakness| weakness.requirement().to_string()));
+            if strength.weaknesses.is_empty() {
+                requirements.push("More characters such as a few uncommon words".to_string());
+            }
         }
 
-        if !password.chars().any(|c| "!@#$%^&*()_+-=[]{}|;:,.<>?".contains(c)) {
-            requirements.push("At least one special character".to_string());
+        if requirements.is_empty() {
+            return Ok(());
         }
-
-        if !requirements.is_empty() {
-            return Err(crate::errors::AuthenticationError::WeakPassword {
-                requirements,
-            }
-            .into());
+        if allow_weak {
+            tracing::warn!("Using a weak password, it needs: {}", requirements.join("; "));
+            return Ok(());
         }
-
-        Ok(())
+        Err(crate::errors::AuthenticationError::WeakPassword { requirements }.into())
     }
 
     pub fn decrypt_wallet(
@@ -182,23 +308,10 @@ impl CryptoService {
         let nonce_bytes = keystore.nonce()?;
         let ciphertext = keystore.encrypted_data()?;
 
-        let mut key_bytes = vec![0u8; config::crypto::KEY_LENGTH];
+        // Zeroed on every return, including a failed MAC check
+        let mut key_bytes = Zeroizing::new(vec![0u8; config::crypto::KEY_LENGTH]);
 
-        match &keystore.kdf_params() {
-            KdfParams::Argon2 { memory, time, parallelism, .. } => {
-                Self::derive_key_argon2(
-                    password.as_bytes(),
-                    &salt,
-                    *memory,
-                    *time,
-                    *parallelism,
-                    &mut key_bytes
-                )?;
-            },
-            KdfParams::Pbkdf2 { c, .. } => {
-                pbkdf2_hmac::<Sha256>(password.as_bytes(), &salt, *c, &mut key_bytes);
-            },
-        }
+        Self::derive_key(password, &salt, keystore.kdf_params(), &mut key_bytes)?;
 
         // Verify MAC
         let expected_mac = Self::compute_mac(&key_bytes, &ciphertext, &nonce_bytes)?;
@@ -216,28 +329,32 @@ impl CryptoService {
         })?;
 
         let nonce = Nonce::from_slice(&nonce_bytes);
-        let decrypted_data = cipher.decrypt(nonce, ciphertext.as_ref()).map_err(|e| {
+        let decrypted_data = Zeroizing::new(cipher.decrypt(nonce, ciphertext.as_ref()).map_err(|e| {
             CryptographicError::DecryptionFailed {
                 context: format!("Decryption failed: {}", e),
             }
-        })?;
-
-        // Clear sensitive data
-        key_bytes.zeroize();
+        })?);
+        let _locked = RegionLock::new(&decrypted_data);
 
-        let wallet: Wallet = serde_json::from_slice(&decrypted_data).map_err(|e|{
+        let mut wallet: Wallet = serde_json::from_slice(&decrypted_data).map_err(|e|{
             CryptographicError::DataCorruption { details: format!("Failed to parse wallet JSON: {}", e) }
         })?;
+        wallet.upgrade_payload();
 
         Ok(wallet)
     }
 
+    #[cfg(feature = "native")]
     pub async fn load_keystore<P: AsRef<Path>>(path: P) -> WalletResult<Keystore>{
         let data = tokio::fs::read_to_string(path).await.map_err(|e|{
             CryptographicError::DataCorruption { details: format!("Failed to read keystore file: {}", e) }
         })?;
+        Self::parse_keystore(&data)
+    }
 
-        let keystore: Keystore = serde_json::from_str(&data).map_err(|e|{
+    /// A native keystore from its JSON, as `load_keystore` reads it from a file
+    pub fn parse_keystore(data: &str) -> WalletResult<Keystore> {
+        let keystore: Keystore = serde_json::from_str(data).map_err(|e|{
             CryptographicError::DataCorruption { details: format!("Failed to parse keystore JSON: {}", e) }
         })?;
 
@@ -245,11 +362,149 @@ impl CryptoService {
         Ok(keystore)
     }
 
-    pub async fn save_keystore<P: AsRef<Path>>(keystore: &Keystore, path: P) -> WalletResult<()>{
-        let json = keystore.to_json()?;
-        tokio::fs::write(path, json).await.map_err(|e|{
-            CryptographicError::DataCorruption { details: format!("Failed to write keystore file: {}", e) }
+    /// Read a keystore in either the native or the v3 (geth/MetaMask) format
+    #[cfg(feature = "native")]
+    pub async fn load_any_keystore<P: AsRef<Path>>(path: P) -> WalletResult<KeystoreFile> {
+        let data = tokio::fs::read_to_string(path).await.map_err(|e|{
+            CryptographicError::DataCorruption { details: format!("Failed to read keystore file: {}", e) }
+        })?;
+        Self::parse_any_keystore(&data)
+    }
+
+    /// A native or v3 keystore from its JSON, as `load_any_keystore` reads it from a file
+    pub fn parse_any_keystore(data: &str) -> WalletResult<KeystoreFile> {
+        let value: serde_json::Value = serde_json::from_str(data).map_err(|e|{
+            CryptographicError::DataCorruption { details: format!("Failed to parse keystore JSON: {}", e) }
+        })?;
+
+        if value.get("version").and_then(|v| v.as_u64()) == Some(3) {
+            let keystore: KeystoreV3 = serde_json::from_value(value).map_err(|e|{
+                CryptographicError::DataCorruption { details: format!("Failed to parse v3 keystore: {}", e) }
+            })?;
+            return Ok(KeystoreFile::V3(Box::new(keystore)));
+        }
+
+        let keystore: Keystore = serde_json::from_value(value).map_err(|e|{
+            CryptographicError::DataCorruption { details: format!("Failed to parse keystore JSON: {}", e) }
+        })?;
+        keystore.validate()?;
+        Ok(KeystoreFile::Native(Box::new(keystore)))
+    }
+
+    /// Encrypt a raw private key into a geth-compatible v3 keystore (scrypt, aes-128-ctr)
+    pub fn encrypt_v3(private_key: &[u8], address: &str, password: &str, light: bool) -> WalletResult<KeystoreV3> {
+        let (log_n, p) = if light {
+            (config::crypto::V3_LIGHT_SCRYPT_LOG_N, config::crypto::V3_LIGHT_SCRYPT_P)
+        } else {
+            (config::crypto::V3_SCRYPT_LOG_N, config::crypto::V3_SCRYPT_P)
+        };
+        let r = config::crypto::V3_SCRYPT_R;
+
+        let mut salt = vec![0u8; config::crypto::SALT_LENGTH];
+        let mut iv = vec![0u8; config::crypto::V3_IV_LENGTH];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut iv);
+
+        let mut derived_key = Zeroizing::new(vec![0u8; config::crypto::KEY_LENGTH]);
+        Self::derive_key_scrypt(password.as_bytes(), &salt, 1 << log_n, r, p, &mut derived_key)?;
+
+        let mut cipher = Aes128Ctr::new_from_slices(&derived_key[..16], &iv).map_err(|e| {
+            CryptographicError::KdfFailed { details: format!("AES-CTR cipher creation failed: {}", e) }
+        })?;
+        let mut ciphertext = private_key.to_vec();
+        cipher.apply_keystream(&mut ciphertext);
+
+        let mut mac_input = derived_key[16..32].to_vec();
+        mac_input.extend_from_slice(&ciphertext);
+        let mac = ethers::utils::keccak256(&mac_input);
+
+        Ok(KeystoreV3 {
+            version: 3,
+            id: Some(uuid::Uuid::new_v4().to_string()),
+            address: Some(address.strip_prefix("0x").unwrap_or(address).to_lowercase()),
+            crypto: V3Crypto {
+                cipher: "aes-128-ctr".to_string(),
+                ciphertext: hex::encode(&ciphertext),
+                cipherparams: V3CipherParams { iv: hex::encode(&iv) },
+                kdf: "scrypt".to_string(),
+                kdfparams: V3KdfParams::Scrypt {
+                    dklen: config::crypto::KEY_LENGTH as u32,
+                    n: 1 << log_n,
+                    r,
+                    p,
+                    salt: hex::encode(&salt),
+                },
+                mac: hex::encode(mac),
+            },
+        })
+    }
+
+    /// Decrypt a v3 keystore, returning the raw 32 byte private key
+    pub fn decrypt_v3(keystore: &KeystoreV3, password: &str) -> WalletResult<Zeroizing<Vec<u8>>> {
+        let crypto = &keystore.crypto;
+        if crypto.cipher != "aes-128-ctr" {
+            return Err(CryptographicError::DecryptionFailed {
+                context: format!("Unsupported v3 cipher: {}", crypto.cipher),
+            }
+            .into());
+        }
+
+        let decode = |field: &str, value: &str| {
+            hex::decode(value.strip_prefix("0x").unwrap_or(value)).map_err(|e| {
+                CryptographicError::DataCorruption { details: format!("Invalid {} hex: {}", field, e) }
+            })
+        };
+        let ciphertext = decode("ciphertext", &crypto.ciphertext)?;
+        let iv = decode("iv", &crypto.cipherparams.iv)?;
+        let mac = decode("mac", &crypto.mac)?;
+
+        let mut derived_key = Zeroizing::new(Vec::new());
+        match &crypto.kdfparams {
+            V3KdfParams::Scrypt { dklen, n, r, p, salt } => {
+                derived_key.resize(*dklen as usize, 0);
+                Self::derive_key_scrypt(password.as_bytes(), &decode("salt", salt)?, *n, *r, *p, &mut derived_key)?;
+            }
+            V3KdfParams::Pbkdf2 { dklen, c, prf, salt } => {
+                if prf != "hmac-sha256" {
+                    return Err(CryptographicError::KdfFailed {
+                        details: format!("Unsupported pbkdf2 prf: {}", prf),
+                    }
+                    .into());
+                }
+                derived_key.resize(*dklen as usize, 0);
+                pbkdf2_hmac::<Sha256>(password.as_bytes(), &decode("salt", salt)?, *c, &mut derived_key);
+            }
+        }
+
+        if derived_key.len() < 32 {
+            return Err(CryptographicError::KdfFailed {
+                details: format!("v3 dklen must be at least 32, got {}", derived_key.len()),
+            }
+            .into());
+        }
+
+        // v3 MAC: keccak256(derived_key[16..32] || ciphertext)
+        let mut mac_input = derived_key[16..32].to_vec();
+        mac_input.extend_from_slice(&ciphertext);
+        if ethers::utils::keccak256(&mac_input).as_slice() != mac.as_slice() {
+            return Err(CryptographicError::DecryptionFailed {
+                context: "Mac verified failed".to_string(),
+            }
+            .into());
+        }
+
+        let mut cipher = Aes128Ctr::new_from_slices(&derived_key[..16], &iv).map_err(|e| {
+            CryptographicError::KdfFailed { details: format!("AES-CTR cipher creation failed: {}", e) }
         })?;
-        Ok(())  
+        let mut private_key = Zeroizing::new(ciphertext);
+        cipher.apply_keystream(&mut private_key);
+        Ok(private_key)
+    }
+
+    #[cfg(feature = "native")]
+    /// Write `keystore` to `path` atomically, so a crash leaves the old file or the new one,
+    /// readable by the owner only
+    pub async fn save_keystore<P: AsRef<Path>>(keystore: &Keystore, path: P) -> WalletResult<()>{
+        crate::utils::write_private(path, keystore.to_json()?.as_bytes()).await
     }
 }
@@ -0,0 +1,154 @@
+//! BIP44 account discovery. Addresses of an account's external chain are checked in order until
+//! `gap_limit` consecutive ones have neither a transaction nor a balance; accounts are walked
+//! from 0 and the walk stops at the first account with no used address, as wallets restoring
+//! a mnemonic are expected to do.
+use crate::config::discovery as settings;
+use crate::errors::{NetworkError, WalletResult};
+use crate::models::Wallet;
+use crate::services::address_format::{self, Chain};
+use crate::services::chains::ChainInfo;
+use crate::services::rpc::{RpcClient, RpcRequest};
+use crate::services::transaction::parse_quantity;
+use crate::WalletConfig;
+use ethers::types::U256;
+use serde::Serialize;
+use serde_json::json;
+
+/// An address with on-chain history
+#[derive(Debug, Clone, Serialize)]
+pub struct UsedAddress {
+    pub index: u32,
+    pub address: String,
+    pub derivation_path: String,
+    /// Outgoing transaction count
+    pub nonce: u64,
+    /// Balance in wei, as decimal string
+    pub balance: String,
+}
+
+/// Result of scanning one account's external chain
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscoveredAccount {
+    pub account: u32,
+    pub account_path: String,
+    /// Addresses checked, including the trailing gap
+    pub scanned: u32,
+    pub used: Vec<UsedAddress>,
+}
+
+impl DiscoveredAccount {
+    pub fn is_used(&self) -> bool {
+        !self.used.is_empty()
+    }
+
+    /// Index after the last used address, where a wallet hands out its next receive address
+    pub fn next_index(&self) -> u32 {
+        self.used.last().map_or(0, |used| used.index + 1)
+    }
+}
+
+pub struct AccountDiscovery {
+    client: RpcClient,
+    gap_limit: u32,
+}
+
+impl AccountDiscovery {
+    /// Scanner for `chain` that ends an account after `gap_limit` unused addresses in a row
+    pub fn new(chain: &ChainInfo, gap_limit: u32, config: &WalletConfig) -> WalletResult<Self> {
+        Ok(Self {
+            client: RpcClient::from_config(&chain.rpc_url, config)?,
+            gap_limit: gap_limit.max(1),
+        })
+    }
+
+    /// Walk accounts from 0 up to the first one never used; that one is included in the
+    /// result so callers can tell where the next account would be opened
+    pub async fn scan(&self, wallet: &Wallet) -> WalletResult<Vec<DiscoveredAccount>> {
+        // An xpub covers its own account only
+        if wallet.is_watch_only() {
+            return Ok(vec![self.scan_account(wallet, account_number(wallet)).await?]);
+        }
+
+        let mut accounts = Vec::new();
+        for account in 0..settings::MAX_ACCOUNTS {
+            let discovered = self.scan_account(&wallet.clone().with_account(account, 0)?, account).await?;
+            let used = discovered.is_used();
+            accounts.push(discovered);
+            if !used {
+                break;
+            }
+        }
+        Ok(accounts)
+    }
+
+    /// Scan the chain `wallet` is positioned on, `account` being its BIP44 account number
+    pub async fn scan_account(&self, wallet: &Wallet, account: u32) -> WalletResult<DiscoveredAccount> {
+        let mut used = Vec::new();
+        let mut gap = 0;
+        let mut index = 0;
+
+        // One batch covers the addresses that could still close the gap
+        while gap < self.gap_limit {
+            let window: Vec<_> = (index..index + (self.gap_limit - gap))
+                .map(|i| wallet.derive_address(i))
+                .collect::<WalletResult<_>>()?;
+            let requests = window
+                .iter()
+                .flat_map(|derived| {
+                    [
+                        RpcRequest::new("eth_getTransactionCount", json!([derived.address(), "latest"])),
+                        RpcRequest::new("eth_getBalance", json!([derived.address(), "latest"])),
+                    ]
+                })
+                .collect();
+            let mut results = self.client.batch(requests).await?.into_iter();
+
+            for derived in &window {
+                let nonce = self.quantity("nonce", results.next())?;
+                let balance = self.quantity("balance", results.next())?;
+                index += 1;
+                if nonce.is_zero() && balance.is_zero() {
+                    gap += 1;
+                    if gap == self.gap_limit {
+                        break;
+                    }
+                    continue;
+                }
+
+                gap = 0;
+                used.push(UsedAddress {
+                    index: derived.index(),
+                    address: address_format::display(Chain::Evm, derived.address()),
+                    derivation_path: derived.derivation_path().to_string(),
+                    nonce: nonce.low_u64(),
+                    balance: balance.to_string(),
+                });
+            }
+        }
+
+        Ok(DiscoveredAccount {
+            account,
+            account_path: wallet.account_path().to_string(),
+            scanned: index,
+            used,
+        })
+    }
+
+    fn quantity(&self, name: &str, result: Option<WalletResult<serde_json::Value>>) -> WalletResult<U256> {
+        let value = result.ok_or_else(|| NetworkError::InvalidResponse {
+            endpoint: self.client.endpoint().to_string(),
+            details: "batch response is missing entries".to_string(),
+        })??;
+        parse_quantity(name, value.as_str().unwrap_or("0x0"))
+    }
+}
+
+/// `{account}` of the wallet's `m/44'/60'/{account}'` path
+fn account_number(wallet: &Wallet) -> u32 {
+    wallet
+        .account_path()
+        .rsplit('/')
+        .next()
+        .and_then(|level| level.trim_end_matches('\'').parse().ok())
+        .unwrap_or(0)
+}
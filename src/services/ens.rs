@@ -0,0 +1,155 @@
+use crate::config;
+use crate::errors::{NetworkError, UserInputError, WalletResult};
+use crate::services::cache::RpcCache;
+use crate::services::chains::ChainInfo;
+use crate::services::rpc::RpcClient;
+use crate::services::transaction::encode_call;
+use crate::WalletConfig;
+use ethers::abi::{ParamType, Token as AbiToken};
+use ethers::types::Address;
+use ethers::utils::keccak256;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+/// EIP-137 namehash of a dot separated name, the empty name hashes to zero
+pub fn namehash(name: &str) -> [u8; 32] {
+    let mut node = [0u8; 32];
+    if name.is_empty() {
+        return node;
+    }
+    for label in name.rsplit('.') {
+        let mut buf = [0u8; 64];
+        buf[..32].copy_from_slice(&node);
+        buf[32..].copy_from_slice(&keccak256(label.as_bytes()));
+        node = keccak256(buf);
+    }
+    node
+}
+
+/// True when `input` should be resolved through ENS rather than parsed as a hex address
+pub fn is_ens_name(input: &str) -> bool {
+    let input = input.trim();
+    !input.starts_with("0x")
+        && input.contains('.')
+        && input.split('.').all(|label| !label.is_empty())
+}
+
+/// Lowercased name; full ENSIP-15 normalization is left to the resolver, which rejects
+/// names that do not hash to a registered node
+pub fn normalize(name: &str) -> String {
+    name.trim().trim_end_matches('.').to_lowercase()
+}
+
+/// Forward and reverse ENS lookups over `eth_call`, optionally cached
+pub struct EnsResolver {
+    client: RpcClient,
+    /// Cache lifetime of lookups, `None` to always ask the node
+    ttl: Option<Duration>,
+}
+
+impl EnsResolver {
+    pub fn new(client: RpcClient) -> Self {
+        Self { client, ttl: None }
+    }
+
+    /// Cache lookups for `ttl`; only effective when the client has a cache attached
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Resolver on the configured mainnet endpoint, caching lookups under `state_path` when `cache` is set
+    pub async fn from_config(config: &WalletConfig, cache: bool) -> WalletResult<Self> {
+        let chain = ChainInfo::resolve(config::ens::RESOLUTION_CHAIN, config)?;
+        let client = RpcClient::from_config(&chain.rpc_url, config)?;
+        if !cache {
+            return Ok(Self::new(client));
+        }
+
+        let cache = RpcCache::open(&RpcCache::default_path(&config.state_path)).await;
+        let client = client.with_cache(chain.chain_id, Arc::new(Mutex::new(cache)));
+        Ok(Self::new(client).with_cache_ttl(Duration::from_secs(config::cache::ENS_TTL_SECS)))
+    }
+
+    /// Address record of `name`, checksummed
+    pub async fn resolve(&self, name: &str) -> WalletResult<String> {
+        let name = normalize(name);
+        let node = namehash(&name);
+        let resolver = self.resolver(&node).await?.ok_or_else(|| unresolved(&name, "no resolver is set"))?;
+
+        let data = encode_call("addr(bytes32)", &[AbiToken::FixedBytes(node.to_vec())]);
+        let address = word_address(&self.call(&resolver, &data).await?)
+            .ok_or_else(|| unresolved(&name, "the resolver has no address record"))?;
+        Ok(ethers::utils::to_checksum(&address, None))
+    }
+
+    /// Primary name of `address`, only if it resolves back to the same address
+    pub async fn reverse(&self, address: &str) -> WalletResult<Option<String>> {
+        crate::utils::validate_ethereum_address(address)?;
+        let hex_address = address.strip_prefix("0x").unwrap_or(address).to_lowercase();
+        let node = namehash(&format!("{}.{}", hex_address, config::ens::REVERSE_SUFFIX));
+        let Some(resolver) = self.resolver(&node).await? else {
+            return Ok(None);
+        };
+
+        let data = encode_call("name(bytes32)", &[AbiToken::FixedBytes(node.to_vec())]);
+        let ret = self.call(&resolver, &data).await?;
+        let name = match ethers::abi::decode(&[ParamType::String], &ret) {
+            Ok(tokens) => tokens.into_iter().next().and_then(AbiToken::into_string).unwrap_or_default(),
+            Err(_) => String::new(),
+        };
+        if name.is_empty() {
+            return Ok(None);
+        }
+
+        // Anyone can claim any reverse name, it only counts if the forward record agrees
+        match self.resolve(&name).await {
+            Ok(forward) if crate::utils::addresses_equal(&forward, &hex_address) => Ok(Some(name)),
+            _ => {
+                debug!("Reverse record {} of {} does not resolve back, ignoring", name, address);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn resolver(&self, node: &[u8; 32]) -> WalletResult<Option<String>> {
+        let data = encode_call("resolver(bytes32)", &[AbiToken::FixedBytes(node.to_vec())]);
+        let ret = self.call(config::ens::REGISTRY_ADDRESS, &data).await?;
+        Ok(word_address(&ret).map(|address| format!("{:?}", address)))
+    }
+
+    async fn call(&self, to: &str, data: &[u8]) -> WalletResult<Vec<u8>> {
+        let params = json!([{ "to": to, "data": format!("0x{}", hex::encode(data)) }, "latest"]);
+        let result = match self.ttl {
+            Some(ttl) => self.client.call_cached("eth_call", params, ttl).await?,
+            None => self.client.call("eth_call", params).await?,
+        };
+        let hex_str = result.as_str().unwrap_or("0x");
+        hex::decode(hex_str.strip_prefix("0x").unwrap_or(hex_str)).map_err(|e| {
+            NetworkError::InvalidResponse {
+                endpoint: self.client.endpoint().to_string(),
+                details: format!("eth_call returned invalid hex: {}", e),
+            }
+            .into()
+        })
+    }
+}
+
+/// ABI-encoded address word, `None` for the zero address or short return data
+fn word_address(ret: &[u8]) -> Option<Address> {
+    (ret.len() >= 32)
+        .then(|| Address::from_slice(&ret[12..32]))
+        .filter(|address| !address.is_zero())
+}
+
+fn unresolved(name: &str, reason: &str) -> crate::errors::WalletError {
+    UserInputError::InvalidParameters {
+        parameter: "ens name".to_string(),
+        value: name.to_string(),
+        expected: format!("a name with an address record ({})", reason),
+    }
+    .into()
+}
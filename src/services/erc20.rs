@@ -0,0 +1,172 @@
+//! ERC-20 reads over `eth_call`: what a token contract says its symbol, decimals and the
+//! balance of an owner are. Symbol and decimals rarely change and are cached under
+//! `state_path`; balances are always read fresh. Also the `transfer` calldata `send-token`
+//! signs.
+use crate::config;
+use crate::errors::{NetworkError, UserInputError, ValidationError, WalletResult};
+use crate::models::Token;
+use crate::services::cache::RpcCache;
+use crate::services::chains::ChainInfo;
+use crate::services::rpc::RpcClient;
+use crate::services::transaction::{encode_call, parse_address};
+use crate::WalletConfig;
+use ethers::abi::{ParamType, Token as AbiToken};
+use ethers::types::{Address, U256};
+use ethers::utils::{format_units, parse_units, to_checksum};
+use serde::Serialize;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Balance of one asset; `token` is None for the chain's native currency
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenBalance {
+    pub token: Option<String>,
+    pub symbol: String,
+    pub decimals: u8,
+    /// Base units, decimal string
+    pub balance: String,
+    /// Whole tokens, without trailing zeros
+    pub amount: String,
+}
+
+impl TokenBalance {
+    pub fn new(token: Option<String>, symbol: &str, decimals: u8, balance: U256) -> Self {
+        Self {
+            token,
+            symbol: symbol.to_string(),
+            decimals,
+            balance: balance.to_string(),
+            amount: display_units(balance, decimals),
+        }
+    }
+}
+
+pub struct Erc20Service {
+    client: RpcClient,
+    metadata_ttl: Option<Duration>,
+}
+
+impl Erc20Service {
+    pub fn new(client: RpcClient) -> Self {
+        Self { client, metadata_ttl: None }
+    }
+
+    /// Service on `chain`'s RPC endpoint, caching token metadata under `state_path`
+    pub async fn from_config(chain: &ChainInfo, config: &WalletConfig) -> WalletResult<Self> {
+        let client = RpcClient::from_config(&chain.rpc_url, config)?;
+        let cache = RpcCache::open(&RpcCache::default_path(&config.state_path)).await;
+        Ok(Self {
+            client: client.with_cache(chain.chain_id, Arc::new(Mutex::new(cache))),
+            metadata_ttl: Some(Duration::from_secs(config::cache::TOKEN_METADATA_TTL_SECS)),
+        })
+    }
+
+    /// Balance of `owner` in `token`, with the symbol and decimals the contract reports. A
+    /// contract without `symbol()` or `decimals()` falls back to `known`, e.g. a registry entry.
+    pub async fn balance(&self, token: &str, owner: &str, known: Option<&Token>) -> WalletResult<TokenBalance> {
+        let balance = self.balance_of(token, owner).await?;
+        let (symbol, decimals) = self.metadata(token, known).await?;
+        Ok(TokenBalance::new(Some(to_checksum(&parse_address("token", token)?, None)), &symbol, decimals, balance))
+    }
+
+    pub async fn balance_of(&self, token: &str, owner: &str) -> WalletResult<U256> {
+        let data = encode_call("balanceOf(address)", &[AbiToken::Address(parse_address("address", owner)?)]);
+        let ret = self.call(token, &data, None).await?;
+        if ret.len() != 32 {
+            return Err(not_erc20(token, "balanceOf(address) did not return a uint256"));
+        }
+        Ok(U256::from_big_endian(&ret))
+    }
+
+    /// Symbol and decimals of `token`
+    pub async fn metadata(&self, token: &str, known: Option<&Token>) -> WalletResult<(String, u8)> {
+        let decimals = match self.call(token, &encode_call("decimals()", &[]), self.metadata_ttl).await {
+            Ok(ret) if ret.len() == 32 && ret[..31].iter().all(|b| *b == 0) => Some(ret[31]),
+            _ => None,
+        };
+        let symbol = match self.call(token, &encode_call("symbol()", &[]), self.metadata_ttl).await {
+            Ok(ret) => decode_string(&ret),
+            Err(_) => None,
+        };
+
+        match (symbol.or(known.map(|t| t.symbol.clone())), decimals.or(known.map(|t| t.decimals))) {
+            (Some(symbol), Some(decimals)) => Ok((symbol, decimals)),
+            (_, None) => Err(not_erc20(token, "decimals() is missing; register the token with `tokens add`")),
+            (None, Some(_)) => Err(not_erc20(token, "symbol() is missing; register the token with `tokens add`")),
+        }
+    }
+
+    async fn call(&self, to: &str, data: &[u8], ttl: Option<Duration>) -> WalletResult<Vec<u8>> {
+        let params = json!([{ "to": to, "data": format!("0x{}", hex::encode(data)) }, "latest"]);
+        let result = match ttl {
+            Some(ttl) => self.client.call_cached("eth_call", params, ttl).await?,
+            None => self.client.call("eth_call", params).await?,
+        };
+        let hex_str = result.as_str().unwrap_or("0x");
+        hex::decode(hex_str.strip_prefix("0x").unwrap_or(hex_str)).map_err(|e| {
+            NetworkError::InvalidResponse {
+                endpoint: self.client.endpoint().to_string(),
+                details: format!("eth_call returned invalid hex: {}", e),
+            }
+            .into()
+        })
+    }
+}
+
+/// Calldata of `transfer(to, amount)`, `amount` in base units
+pub fn transfer_calldata(to: Address, amount: U256) -> Vec<u8> {
+    encode_call("transfer(address,uint256)", &[AbiToken::Address(to), AbiToken::Uint(amount)])
+}
+
+/// Base units of `value` whole tokens, e.g. "100.5"; more fraction digits than `decimals`
+/// are refused rather than rounded
+pub fn parse_amount(value: &str, decimals: u8) -> WalletResult<U256> {
+    // parse_units silently truncates extra fraction digits and accepts negative amounts
+    let fraction = value.split_once('.').map_or("", |(_, fraction)| fraction);
+    let parsed = match parse_units(value, decimals as u32) {
+        Ok(parsed) if !value.starts_with('-') && fraction.len() <= decimals as usize => U256::from(parsed),
+        _ => U256::zero(),
+    };
+    if parsed.is_zero() {
+        return Err(UserInputError::InvalidParameters {
+            parameter: "amount".to_string(),
+            value: value.to_string(),
+            expected: format!("a positive amount in whole tokens with at most {} decimals, e.g. 100.5", decimals),
+        }
+        .into());
+    }
+    Ok(parsed)
+}
+
+/// `value` base units in whole tokens, without trailing zeros
+pub fn display_units(value: U256, decimals: u8) -> String {
+    let amount = format_units(value, decimals as u32).unwrap_or_else(|_| value.to_string());
+    if !amount.contains('.') {
+        return amount;
+    }
+    amount.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// An ABI string, or the bytes32 some early tokens (e.g. MKR) return instead
+pub(crate) fn decode_string(ret: &[u8]) -> Option<String> {
+    if let Ok(tokens) = ethers::abi::decode(&[ParamType::String], ret) {
+        if let Some(AbiToken::String(symbol)) = tokens.into_iter().next() {
+            return Some(symbol).filter(|s| !s.is_empty());
+        }
+    }
+    if ret.len() == 32 {
+        let end = ret.iter().position(|b| *b == 0).unwrap_or(32);
+        return String::from_utf8(ret[..end].to_vec()).ok().filter(|s| !s.is_empty());
+    }
+    None
+}
+
+fn not_erc20(token: &str, details: &str) -> crate::errors::WalletError {
+    ValidationError::PreconditionFailed {
+        check: "ERC-20".to_string(),
+        details: format!("{} is not an ERC-20 token: {}", token, details),
+    }
+    .into()
+}
@@ -0,0 +1,236 @@
+use crate::config;
+use crate::errors::{NetworkError, WalletResult};
+use crate::services::chains::ChainInfo;
+use crate::WalletConfig;
+use chrono::{DateTime, TimeZone, Utc};
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Normal transaction as returned by an Etherscan-compatible `txlist` call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplorerTransaction {
+    pub block_number: String,
+    pub time_stamp: String,
+    pub hash: String,
+    pub from: String,
+    #[serde(default)]
+    pub to: String,
+    pub value: String,
+    /// Gas limit
+    #[serde(default)]
+    pub gas: String,
+    #[serde(default)]
+    pub gas_used: String,
+    #[serde(default)]
+    pub gas_price: String,
+    #[serde(default)]
+    pub is_error: String,
+    /// "1" success, "0" reverted, empty before Byzantium receipts had a status
+    #[serde(default)]
+    pub txreceipt_status: String,
+}
+
+impl ExplorerTransaction {
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.time_stamp
+            .parse::<i64>()
+            .ok()
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+    }
+
+    pub fn value_wei(&self) -> U256 {
+        U256::from_dec_str(&self.value).unwrap_or_default()
+    }
+
+    pub fn fee_wei(&self) -> U256 {
+        let gas_used = U256::from_dec_str(&self.gas_used).unwrap_or_default();
+        let gas_price = U256::from_dec_str(&self.gas_price).unwrap_or_default();
+        gas_used.saturating_mul(gas_price)
+    }
+
+    pub fn failed(&self) -> bool {
+        self.is_error == "1" || self.txreceipt_status == "0"
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ExplorerResponse {
+    status: String,
+    message: String,
+    result: serde_json::Value,
+}
+
+/// Client for Etherscan-compatible indexer APIs
+pub struct ExplorerClient {
+    base_url: String,
+    api_key: Option<String>,
+    chain_id: u64,
+    http: reqwest::Client,
+    timeout: Duration,
+}
+
+impl ExplorerClient {
+    pub fn new(base_url: &str, api_key: Option<String>, chain_id: u64) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+            api_key,
+            chain_id,
+            http: reqwest::Client::new(),
+            timeout: Duration::from_secs(config::rpc::DEFAULT_TIMEOUT_SECS),
+        }
+    }
+
+    /// Client for `chain` using its indexer from `[explorer.networks]`, or the default one.
+    /// A chain with its own API URL only gets its own key, never the default indexer's.
+    pub fn for_chain(config: &WalletConfig, chain: &ChainInfo) -> WalletResult<Self> {
+        let (url, key) = match config.explorer_overrides.get(&chain.name) {
+            Some(api) => match &api.api_url {
+                Some(url) => (url.as_str(), api.api_key.clone()),
+                None => (config.explorer_api_url.as_str(), api.api_key.clone().or(config.explorer_api_key.clone())),
+            },
+            None => (config.explorer_api_url.as_str(), config.explorer_api_key.clone()),
+        };
+        config.require_network(url)?;
+        let mut client = Self::new(url, key, chain.chain_id);
+        client.timeout = Duration::from_secs(config.rpc_timeout_secs);
+        Ok(client)
+    }
+
+    /// All normal transactions of `address`, oldest first
+    pub async fn transactions(&self, address: &str) -> WalletResult<Vec<ExplorerTransaction>> {
+        self.txlist(address, &[("sort", "asc")]).await
+    }
+
+    /// The `limit` most recent normal transactions of `address`, newest first
+    pub async fn recent_transactions(&self, address: &str, limit: usize) -> WalletResult<Vec<ExplorerTransaction>> {
+        let limit = limit.to_string();
+        self.txlist(address, &[("sort", "desc"), ("page", "1"), ("offset", limit.as_str())]).await
+    }
+
+    async fn txlist(&self, address: &str, paging: &[(&str, &str)]) -> WalletResult<Vec<ExplorerTransaction>> {
+        let chain_id = self.chain_id.to_string();
+        let mut query = vec![
+            ("chainid", chain_id.as_str()),
+            ("module", "account"),
+            ("action", "txlist"),
+            ("address", address),
+            ("startblock", "0"),
+            ("endblock", "latest"),
+        ];
+        query.extend_from_slice(paging);
+        if let Some(key) = &self.api_key {
+            query.push(("apikey", key.as_str()));
+        }
+
+        let response = self
+            .http
+            .get(&self.base_url)
+            .query(&query)
+            .timeout(self.timeout)
+            .send()
+            .await
+            .map_err(|e| NetworkError::ConnectivityFailure {
+                endpoint: self.base_url.clone(),
+                details: e.to_string(),
+            })?;
+
+        let body: ExplorerResponse = response.json().await.map_err(|e| NetworkError::InvalidResponse {
+            endpoint: self.base_url.clone(),
+            details: e.to_string(),
+        })?;
+
+        if body.status != "1" {
+            // Etherscan reports an empty history as an error
+            if body.message.starts_with("No transactions found") {
+                return Ok(Vec::new());
+            }
+            return Err(NetworkError::InvalidResponse {
+                endpoint: self.base_url.clone(),
+                details: format!("{}: {}", body.message, body.result),
+            }
+            .into());
+        }
+
+        serde_json::from_value(body.result).map_err(|e| {
+            NetworkError::InvalidResponse {
+                endpoint: self.base_url.clone(),
+                details: e.to_string(),
+            }
+            .into()
+        })
+    }
+}
+
+/// Aggregated activity of an address over a time window
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivitySummary {
+    pub address: String,
+    pub chain_id: u64,
+    pub since: Option<DateTime<Utc>>,
+    pub tx_count: usize,
+    pub incoming: usize,
+    pub outgoing: usize,
+    pub failed: usize,
+    pub first_activity: Option<DateTime<Utc>>,
+    pub last_activity: Option<DateTime<Utc>>,
+    /// Wei, decimal strings
+    pub total_in: String,
+    pub total_out: String,
+    pub total_fees: String,
+}
+
+impl ActivitySummary {
+    pub fn from_transactions(
+        address: &str,
+        chain_id: u64,
+        since: Option<DateTime<Utc>>,
+        transactions: &[ExplorerTransaction],
+    ) -> Self {
+        let in_window: Vec<&ExplorerTransaction> = transactions
+            .iter()
+            .filter(|tx| match (since, tx.timestamp()) {
+                (Some(since), Some(ts)) => ts >= since,
+                _ => true,
+            })
+            .collect();
+
+        let (mut total_in, mut total_out, mut total_fees) = (U256::zero(), U256::zero(), U256::zero());
+        let (mut incoming, mut outgoing, mut failed) = (0, 0, 0);
+
+        for tx in &in_window {
+            if tx.failed() {
+                failed += 1;
+            }
+            if crate::utils::addresses_equal(&tx.from, address) {
+                outgoing += 1;
+                total_fees = total_fees.saturating_add(tx.fee_wei());
+                if !tx.failed() {
+                    total_out = total_out.saturating_add(tx.value_wei());
+                }
+            }
+            if crate::utils::addresses_equal(&tx.to, address) {
+                incoming += 1;
+                if !tx.failed() {
+                    total_in = total_in.saturating_add(tx.value_wei());
+                }
+            }
+        }
+
+        Self {
+            address: address.to_string(),
+            chain_id,
+            since,
+            tx_count: in_window.len(),
+            incoming,
+            outgoing,
+            failed,
+            first_activity: in_window.iter().filter_map(|tx| tx.timestamp()).min(),
+            last_activity: in_window.iter().filter_map(|tx| tx.timestamp()).max(),
+            total_in: total_in.to_string(),
+            total_out: total_out.to_string(),
+            total_fees: total_fees.to_string(),
+        }
+    }
+}
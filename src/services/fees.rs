@@ -0,0 +1,147 @@
+use crate::config;
+#[cfg(feature = "native")]
+use crate::errors::{NetworkError, WalletResult};
+#[cfg(feature = "native")]
+use crate::services::rpc::RpcClient;
+#[cfg(feature = "native")]
+use crate::services::transaction::parse_quantity;
+use crate::services::transaction::UnsignedTransaction;
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "native")]
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+/// How aggressively to bid for inclusion
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeTier {
+    Slow,
+    #[default]
+    Standard,
+    Fast,
+}
+
+impl FeeTier {
+    /// `eth_feeHistory` reward percentile for this tier
+    pub fn percentile(&self) -> u64 {
+        let [slow, standard, fast] = config::fees::PRIORITY_FEE_PERCENTILES;
+        match self {
+            Self::Slow => slow,
+            Self::Standard => standard,
+            Self::Fast => fast,
+        }
+    }
+}
+
+impl FromStr for FeeTier {
+    type Err = crate::errors::WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "slow" => Ok(Self::Slow),
+            "standard" => Ok(Self::Standard),
+            "fast" => Ok(Self::Fast),
+            _ => Err(crate::errors::UserInputError::InvalidParameters {
+                parameter: "fee_tier".to_string(),
+                value: s.to_string(),
+                expected: "slow, standard or fast".to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// How `TransactionService::prepare` prices a transaction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeOptions {
+    /// Pay `gasPrice` instead of EIP-1559 fees
+    pub legacy: bool,
+    pub tier: FeeTier,
+}
+
+/// Suggested fees, in wei
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeEstimate {
+    /// Base fee expected for the next block
+    pub base_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    /// `base_fee * BASE_FEE_MULTIPLIER + priority`, headroom for rising base fees
+    pub max_fee_per_gas: U256,
+    /// `eth_gasPrice`, for legacy transactions
+    pub gas_price: U256,
+}
+
+/// Fee estimation from `eth_feeHistory`
+pub struct FeeEstimator;
+
+impl FeeEstimator {
+    #[cfg(feature = "native")]
+    pub async fn estimate(client: &RpcClient, tier: FeeTier) -> WalletResult<FeeEstimate> {
+        let history = client
+            .call(
+                "eth_feeHistory",
+                json!([
+                    format!("0x{:x}", config::fees::FEE_HISTORY_BLOCKS),
+                    "latest",
+                    [tier.percentile()]
+                ]),
+            )
+            .await?;
+        let gas_price = client.call("eth_gasPrice", json!([])).await?;
+
+        let base_fees = history["baseFeePerGas"].as_array().ok_or_else(|| invalid(client, "missing baseFeePerGas"))?;
+        // The last entry is the base fee of the pending block
+        let base_fee = parse_quantity(
+            "baseFeePerGas",
+            base_fees.last().and_then(Value::as_str).ok_or_else(|| invalid(client, "empty baseFeePerGas"))?,
+        )?;
+
+        let mut rewards: Vec<U256> = history["reward"]
+            .as_array()
+            .map(|blocks| {
+                blocks
+                    .iter()
+                    .filter_map(|b| b.get(0).and_then(Value::as_str))
+                    .filter_map(|r| parse_quantity("reward", r).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        rewards.sort();
+
+        let min_priority = U256::from(config::fees::MIN_PRIORITY_FEE_WEI);
+        let priority = rewards
+            .get(rewards.len() / 2)
+            .copied()
+            .unwrap_or(min_priority)
+            .max(min_priority);
+
+        Ok(FeeEstimate {
+            base_fee_per_gas: base_fee,
+            max_priority_fee_per_gas: priority,
+            max_fee_per_gas: base_fee * config::fees::BASE_FEE_MULTIPLIER + priority,
+            gas_price: parse_quantity("gas_price", gas_price.as_str().unwrap_or("0x0"))?,
+        })
+    }
+
+    /// Fill in whichever fee fields `tx` is missing
+    pub fn apply(tx: &mut UnsignedTransaction, estimate: &FeeEstimate) {
+        if tx.is_legacy() {
+            tx.gas_price.get_or_insert_with(|| estimate.gas_price.to_string());
+        } else {
+            tx.max_priority_fee_per_gas
+                .get_or_insert_with(|| estimate.max_priority_fee_per_gas.to_string());
+            tx.max_fee_per_gas
+                .get_or_insert_with(|| estimate.max_fee_per_gas.to_string());
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+fn invalid(client: &RpcClient, details: &str) -> crate::errors::WalletError {
+    NetworkError::InvalidResponse {
+        endpoint: client.endpoint().to_string(),
+        details: format!("eth_feeHistory: {}", details),
+    }
+    .into()
+}
@@ -0,0 +1,341 @@
+//! Wallet health score. A keystore is graded from what can be checked without its password:
+//! KDF strength, password age, backup verification, file permissions and how it reaches the
+//! network. Every finding names the command that fixes it. `doctor` audits the permissions
+//! of the whole wallets directory.
+use crate::config::{self, health as settings};
+use crate::errors::{FilesystemError, WalletResult};
+use crate::models::keystore::KdfParams;
+use crate::models::keystore_v3::V3KdfParams;
+use crate::models::{KeystoreFile, WalletType};
+use crate::services::chains::ChainInfo;
+use crate::services::crypto::CryptoService;
+use crate::services::walletmanager::WalletManager;
+use crate::utils;
+use crate::WalletConfig;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    /// Not recorded for this keystore format or platform; costs no points
+    Unknown,
+}
+
+impl CheckStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pass => "pass",
+            Self::Warn => "warn",
+            Self::Fail => "fail",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthCheck {
+    /// kdf, password_age, backup, permissions or network
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub details: String,
+    /// Points taken off the score
+    pub penalty: u32,
+    /// Command or step that resolves the finding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remedy: Option<String>,
+}
+
+impl HealthCheck {
+    fn pass(name: &'static str, details: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Pass, details: details.into(), penalty: 0, remedy: None }
+    }
+
+    fn unknown(name: &'static str, details: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Unknown, details: details.into(), penalty: 0, remedy: None }
+    }
+
+    fn finding(name: &'static str, status: CheckStatus, penalty: u32, details: impl Into<String>, remedy: String) -> Self {
+        Self { name, status, details: details.into(), penalty, remedy: Some(remedy) }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub file: PathBuf,
+    pub address: String,
+    /// 100 less the penalties of all findings
+    pub score: u32,
+    pub grade: &'static str,
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+    fn new(file: &Path, address: String, checks: Vec<HealthCheck>) -> Self {
+        let score = 100u32.saturating_sub(checks.iter().map(|c| c.penalty).sum());
+        let grade = settings::GRADES
+            .iter()
+            .find(|(min, _)| score >= *min)
+            .map_or("F", |(_, grade)| grade);
+        Self { file: file.to_path_buf(), address, score, grade, checks }
+    }
+
+    /// Remedies of the findings, worst first
+    pub fn remedies(&self) -> Vec<&str> {
+        let mut findings: Vec<_> = self.checks.iter().filter(|c| c.remedy.is_some()).collect();
+        findings.sort_by_key(|f| Reverse(f.penalty));
+        findings.into_iter().filter_map(|c| c.remedy.as_deref()).collect()
+    }
+}
+
+/// Grade the keystore at `path`; `filename` is how the user named it, repeated in remedies.
+/// `config` should carry the wallet's sidecar overrides so the endpoint it signs through is checked.
+pub async fn assess(config: &WalletConfig, path: &Path, filename: &str) -> WalletResult<HealthReport> {
+    let manager = WalletManager::new(config.clone());
+    let keystore = CryptoService::load_any_keystore(path).await?;
+
+    let checks = vec![
+        kdf_check(&manager, &keystore, filename),
+        password_age_check(&keystore, filename),
+        backup_check(&keystore, filename),
+        permissions_check(path).await?,
+        network_check(config, &keystore),
+    ];
+    let address = match &keystore {
+        KeystoreFile::Native(keystore) => keystore.metadata.address.clone(),
+        KeystoreFile::V3(keystore) => keystore.address().unwrap_or_default(),
+    };
+    Ok(HealthReport::new(path, address, checks))
+}
+
+fn kdf_check(manager: &WalletManager, keystore: &KeystoreFile, filename: &str) -> HealthCheck {
+    match keystore {
+        KeystoreFile::Native(keystore) => {
+            let rekey = format!("web3wallet migrate {} --rekey", filename);
+            match keystore.kdf_params() {
+                KdfParams::Pbkdf2 { c, .. } => HealthCheck::finding(
+                    "kdf",
+                    CheckStatus::Fail,
+                    settings::WEAK_KDF_PENALTY,
+                    format!("PBKDF2 with {} iterations is not memory-hard", c),
+                    rekey,
+                ),
+                _ if manager.kdf_outdated(keystore) => HealthCheck::finding(
+                    "kdf",
+                    CheckStatus::Warn,
+                    settings::OUTDATED_KDF_PENALTY,
+                    format!("{} is cheaper than the configured KDF", keystore.crypto.kdf),
+                    rekey,
+                ),
+                _ => HealthCheck::pass("kdf", format!("{} at the configured cost", keystore.crypto.kdf)),
+            }
+        }
+        KeystoreFile::V3(keystore) => {
+            let passwd = format!("web3wallet passwd {}", filename);
+            match &keystore.crypto.kdfparams {
+                V3KdfParams::Pbkdf2 { c, .. } => HealthCheck::finding(
+                    "kdf",
+                    CheckStatus::Fail,
+                    settings::WEAK_KDF_PENALTY,
+                    format!("PBKDF2 with {} iterations is not memory-hard", c),
+                    passwd,
+                ),
+                V3KdfParams::Scrypt { n, .. } if u64::from(*n) < 1u64 << config::crypto::V3_SCRYPT_LOG_N => {
+                    HealthCheck::finding(
+                        "kdf",
+                        CheckStatus::Warn,
+                        settings::OUTDATED_KDF_PENALTY,
+                        format!("light scrypt (N = {})", n),
+                        passwd,
+                    )
+                }
+                V3KdfParams::Scrypt { n, .. } => HealthCheck::pass("kdf", format!("scrypt (N = {})", n)),
+            }
+        }
+    }
+}
+
+fn password_age_check(keystore: &KeystoreFile, filename: &str) -> HealthCheck {
+    let KeystoreFile::Native(keystore) = keystore else {
+        return HealthCheck::unknown("password_age", "v3 keystores record no dates");
+    };
+    if keystore.metadata.wallet_type == WalletType::WatchOnly {
+        return HealthCheck::pass("password_age", "watch-only, the password guards no key");
+    }
+
+    let set_at = keystore.metadata.password_changed_at.as_deref().unwrap_or(&keystore.metadata.created_at);
+    let Ok(set_at) = chrono::DateTime::parse_from_rfc3339(set_at) else {
+        return HealthCheck::unknown("password_age", "unreadable date in the keystore metadata");
+    };
+    let days = (chrono::Utc::now() - set_at.with_timezone(&chrono::Utc)).num_days();
+    if days > settings::PASSWORD_MAX_AGE_DAYS {
+        HealthCheck::finding(
+            "password_age",
+            CheckStatus::Warn,
+            settings::OLD_PASSWORD_PENALTY,
+            format!("password set {} days ago", days),
+            format!("web3wallet passwd {}", filename),
+        )
+    } else {
+        HealthCheck::pass("password_age", format!("password set {} days ago", days))
+    }
+}
+
+fn backup_check(keystore: &KeystoreFile, filename: &str) -> HealthCheck {
+    let KeystoreFile::Native(keystore) = keystore else {
+        return HealthCheck::unknown("backup", "v3 keystores record no backup checks");
+    };
+    if keystore.metadata.wallet_type == WalletType::WatchOnly {
+        HealthCheck::pass("backup", "watch-only, nothing secret to back up")
+    } else if keystore.metadata.backup_verified {
+        HealthCheck::pass("backup", "backup verified")
+    } else {
+        HealthCheck::finding(
+            "backup",
+            CheckStatus::Fail,
+            settings::UNVERIFIED_BACKUP_PENALTY,
+            "backup never verified",
+            format!("web3wallet verify-backup {}", filename),
+        )
+    }
+}
+
+#[cfg(unix)]
+async fn permissions_check(path: &Path) -> WalletResult<HealthCheck> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = tokio::fs::metadata(path).await?.permissions().mode() & 0o777;
+    if mode & 0o077 == 0 {
+        Ok(HealthCheck::pass("permissions", format!("{:o}, owner only", mode)))
+    } else {
+        Ok(HealthCheck::finding(
+            "permissions",
+            CheckStatus::Fail,
+            settings::OPEN_PERMISSIONS_PENALTY,
+            format!("{:o}, readable by other users", mode),
+            format!("chmod {:o} {}", config::fs::KEYSTORE_FILE_PERMISSIONS, path.display()),
+        ))
+    }
+}
+
+#[cfg(not(unix))]
+async fn permissions_check(_path: &Path) -> WalletResult<HealthCheck> {
+    Ok(HealthCheck::unknown("permissions", "file modes are not checked on this platform"))
+}
+
+fn network_check(config: &WalletConfig, keystore: &KeystoreFile) -> HealthCheck {
+    if let KeystoreFile::Native(keystore) = keystore {
+        if keystore.metadata.cold {
+            return HealthCheck::pass("network", "cold wallet, decrypts offline only");
+        }
+        if keystore.metadata.wallet_type == WalletType::WatchOnly {
+            return HealthCheck::pass("network", "watch-only, never signs");
+        }
+    }
+
+    let Ok(chain) = ChainInfo::resolve(&config.network, config) else {
+        return HealthCheck::unknown("network", format!("network {} is not configured", config.network));
+    };
+    let Ok(url) = reqwest::Url::parse(&chain.rpc_url) else {
+        return HealthCheck::unknown("network", format!("{} RPC endpoint is not a URL", chain.name));
+    };
+    if url.scheme() == "http" && !is_loopback(&url) {
+        HealthCheck::finding(
+            "network",
+            CheckStatus::Warn,
+            settings::PLAINTEXT_RPC_PENALTY,
+            format!("{} RPC {} is plain http", chain.name, url.host_str().unwrap_or_default()),
+            format!("set an https endpoint for {} in [rpc.endpoints] or the wallet's .toml sidecar", chain.name),
+        )
+    } else {
+        HealthCheck::pass("network", format!("{} RPC over {}", chain.name, url.scheme()))
+    }
+}
+
+/// Plain http to this machine never crosses the network
+fn is_loopback(url: &reqwest::Url) -> bool {
+    match url.host_str() {
+        Some("localhost") => true,
+        Some(host) => host
+            .trim_matches(['[', ']'])
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback()),
+        None => false,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionFinding {
+    pub path: PathBuf,
+    /// Octal mode found, e.g. "644"
+    pub mode: String,
+    pub expected: String,
+    /// Set to the expected mode by `doctor --fix`
+    pub fixed: bool,
+}
+
+/// Permissions of a wallets directory and the files in it, as `doctor` reports them
+#[derive(Debug, Clone, Serialize)]
+pub struct DirectoryAudit {
+    pub directory: PathBuf,
+    /// Files checked besides the directory itself
+    pub files: usize,
+    pub findings: Vec<PermissionFinding>,
+}
+
+impl DirectoryAudit {
+    /// No finding is left unfixed
+    pub fn healthy(&self) -> bool {
+        self.findings.iter().all(|finding| finding.fixed)
+    }
+}
+
+/// Find the wallets directory or files in it that other users can access, and with `fix`
+/// restrict them to the owner. A missing directory has nothing to audit.
+pub async fn audit_directory(dir: &Path, fix: bool) -> WalletResult<DirectoryAudit> {
+    let mut audit = DirectoryAudit { directory: dir.to_path_buf(), files: 0, findings: Vec::new() };
+    if !dir.exists() {
+        return Ok(audit);
+    }
+
+    audit_path(dir, config::fs::WALLETS_DIR_PERMISSIONS, fix, &mut audit.findings).await?;
+    let not_accessible = |e: std::io::Error| FilesystemError::DirectoryNotAccessible {
+        path: dir.display().to_string(),
+        details: e.to_string(),
+    };
+    let mut entries = tokio::fs::read_dir(dir).await.map_err(not_accessible)?;
+    let mut paths = Vec::new();
+    while let Some(entry) = entries.next_entry().await.map_err(not_accessible)? {
+        if entry.file_type().await.is_ok_and(|kind| kind.is_file()) {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+    audit.files = paths.len();
+    for path in paths {
+        audit_path(&path, config::fs::KEYSTORE_FILE_PERMISSIONS, fix, &mut audit.findings).await?;
+    }
+    Ok(audit)
+}
+
+async fn audit_path(path: &Path, expected: u32, fix: bool, findings: &mut Vec<PermissionFinding>) -> WalletResult<()> {
+    let Some(mode) = utils::open_permissions(path).await? else {
+        return Ok(());
+    };
+    #[cfg(unix)]
+    if fix {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(expected)).await?;
+    }
+    findings.push(PermissionFinding {
+        path: path.to_path_buf(),
+        mode: format!("{:o}", mode),
+        expected: format!("{:o}", expected),
+        fixed: fix,
+    });
+    Ok(())
+}
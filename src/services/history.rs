@@ -0,0 +1,272 @@
+//! Local ledger of the transactions this CLI signs, kept under `state_path` for bookkeeping
+//! exports. Entries hold what the signing command knew; nothing is looked up on chain later.
+use crate::config::history as settings;
+use crate::errors::{FilesystemError, UserInputError, WalletResult};
+use crate::services::prices::{is_priced_chain, PriceProvider};
+use crate::services::quote::display_amount;
+use crate::services::transaction::{parse_quantity, SignedTransaction, UnsignedTransaction};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// One signed transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub at: DateTime<Utc>,
+    pub chain: String,
+    pub chain_id: u64,
+    pub from: String,
+    /// Empty for contract creation
+    #[serde(default)]
+    pub to: String,
+    /// Native currency symbol, or the token moved, e.g. "ERC-721 0x…#42"
+    pub asset: String,
+    /// Decimal amount in `asset` units
+    pub amount: String,
+    /// Native currency symbol of the chain, the unit of `fee`
+    pub currency: String,
+    /// Most the transaction can pay in fees: gas limit times max fee per gas
+    pub fee: String,
+    pub hash: String,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// False for transactions that were only signed, e.g. `sign-tx` or `--sign-only`
+    pub broadcast: bool,
+}
+
+impl HistoryEntry {
+    /// Entry for `signed` moving the chain's native currency; token transfers replace `asset` and `amount`
+    pub fn new(chain: &str, currency: &str, unsigned: &UnsignedTransaction, signed: &SignedTransaction, broadcast: bool) -> Self {
+        let quantity = |value: Option<&String>| value.and_then(|v| parse_quantity("value", v).ok()).unwrap_or_default();
+        let fee_per_gas = if unsigned.is_legacy() { &unsigned.gas_price } else { &unsigned.max_fee_per_gas };
+        let fee = quantity(unsigned.gas.as_ref()).saturating_mul(quantity(fee_per_gas.as_ref()));
+
+        Self {
+            at: Utc::now(),
+            chain: chain.to_string(),
+            chain_id: unsigned.chain_id,
+            from: signed.from.clone(),
+            to: unsigned.to.clone().unwrap_or_default(),
+            asset: currency.to_string(),
+            amount: native_amount(quantity(unsigned.value.as_ref())),
+            currency: currency.to_string(),
+            fee: native_amount(fee),
+            hash: signed.hash.clone(),
+            note: None,
+            broadcast,
+        }
+    }
+
+    /// The amount is in the native currency, so a native price applies to it as well as the fee
+    pub fn is_native(&self) -> bool {
+        self.asset == self.currency
+    }
+}
+
+/// `wei` in whole units of the native currency, without trailing zeros
+pub fn native_amount(wei: U256) -> String {
+    let amount = display_amount(&wei.to_string(), 18);
+    match amount.trim_end_matches('0').trim_end_matches('.') {
+        "" => "0".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+/// Calendar span given as a year, quarter or month, e.g. "2025", "2025-Q1" or "2025-03"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub start: DateTime<Utc>,
+    /// Exclusive
+    pub end: DateTime<Utc>,
+}
+
+impl Period {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.start <= at && at < self.end
+    }
+
+    fn months(year: i32, first_month: u32, months: u32) -> Option<Self> {
+        let start = NaiveDate::from_ymd_opt(year, first_month, 1)?;
+        let end_month = first_month + months;
+        let end = if end_month > 12 {
+            NaiveDate::from_ymd_opt(year + 1, end_month - 12, 1)?
+        } else {
+            NaiveDate::from_ymd_opt(year, end_month, 1)?
+        };
+        Some(Self {
+            start: Utc.from_utc_datetime(&start.and_hms_opt(0, 0, 0)?),
+            end: Utc.from_utc_datetime(&end.and_hms_opt(0, 0, 0)?),
+        })
+    }
+}
+
+impl FromStr for Period {
+    type Err = crate::errors::WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parsed = match s.trim().split_once('-') {
+            None => s.trim().parse::<i32>().ok().and_then(|year| Self::months(year, 1, 12)),
+            Some((year, part)) => year.parse::<i32>().ok().and_then(|year| {
+                match part.strip_prefix('Q').or_else(|| part.strip_prefix('q')) {
+                    Some(quarter) => match quarter.parse::<u32>() {
+                        Ok(quarter @ 1..=4) => Self::months(year, 3 * quarter - 2, 3),
+                        _ => None,
+                    },
+                    None if part.len() == 2 => part.parse::<u32>().ok().and_then(|month| Self::months(year, month, 1)),
+                    None => None,
+                }
+            }),
+        };
+
+        parsed.ok_or_else(|| {
+            UserInputError::InvalidParameters {
+                parameter: "period".to_string(),
+                value: s.to_string(),
+                expected: "a year, quarter or month, e.g. 2025, 2025-Q1 or 2025-03".to_string(),
+            }
+            .into()
+        })
+    }
+}
+
+/// Every transaction signed on this machine, oldest first, persisted as JSON
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryLog {
+    #[serde(skip)]
+    path: PathBuf,
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl HistoryLog {
+    pub fn default_path(state_dir: &Path) -> PathBuf {
+        state_dir.join(settings::HISTORY_FILE_NAME)
+    }
+
+    /// Load the log at `path`, empty if the file does not exist yet
+    pub async fn load(path: &Path) -> WalletResult<Self> {
+        let mut log: Self = match tokio::fs::read_to_string(path).await {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| FilesystemError::InvalidFormat {
+                path: path.display().to_string(),
+                details: e.to_string(),
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e.into()),
+        };
+        log.path = path.to_path_buf();
+        Ok(log)
+    }
+
+    pub async fn save(&self) -> WalletResult<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                FilesystemError::DirectoryNotAccessible {
+                    path: parent.display().to_string(),
+                    details: e.to_string(),
+                }
+            })?;
+        }
+        crate::utils::write_atomic(&self.path, serde_json::to_string_pretty(self)?.as_bytes()).await
+    }
+
+    pub fn record(&mut self, entry: HistoryEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Entries inside `period`, or all of them, oldest first
+    pub fn in_period(&self, period: Option<&Period>) -> Vec<HistoryEntry> {
+        let mut entries: Vec<HistoryEntry> = self
+            .entries
+            .iter()
+            .filter(|e| period.is_none_or(|p| p.contains(e.at)))
+            .cloned()
+            .collect();
+        entries.sort_by_key(|e| e.at);
+        entries
+    }
+}
+
+/// Native currency price of every exported entry, in one fiat currency
+#[derive(Debug, Clone)]
+pub struct FiatPrices {
+    /// Lowercase ISO code, e.g. "usd"
+    pub currency: String,
+    /// Aligned with the exported entries; None where no price is known
+    pub prices: Vec<Option<f64>>,
+}
+
+/// Accountant-friendly CSV ledger of `entries`, with value columns in fiat when `fiat` is given
+pub fn ledger_csv(entries: &[HistoryEntry], fiat: Option<&FiatPrices>) -> String {
+    let mut headers: Vec<String> = ["date", "chain", "from", "to", "asset", "amount", "fee", "fee_asset", "tx_hash", "note", "status"]
+        .iter()
+        .map(|header| header.to_string())
+        .collect();
+    if let Some(fiat) = fiat {
+        let code = fiat.currency.to_lowercase();
+        headers.extend([format!("price_{}", code), format!("amount_{}", code), format!("fee_{}", code)]);
+    }
+
+    let mut csv = headers.join(",");
+    csv.push_str("\r\n");
+    for (i, entry) in entries.iter().enumerate() {
+        let mut row = vec![
+            entry.at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            entry.chain.clone(),
+            entry.from.clone(),
+            entry.to.clone(),
+            entry.asset.clone(),
+            entry.amount.clone(),
+            entry.fee.clone(),
+            entry.currency.clone(),
+            entry.hash.clone(),
+            entry.note.clone().unwrap_or_default(),
+            if entry.broadcast { "broadcast" } else { "signed" }.to_string(),
+        ];
+        if let Some(fiat) = fiat {
+            let price = fiat.prices.get(i).copied().flatten();
+            let value = |amount: &str| {
+                price
+                    .zip(amount.parse::<f64>().ok())
+                    .map(|(price, amount)| format!("{:.*}", settings::FIAT_DECIMALS, price * amount))
+                    .unwrap_or_default()
+            };
+            row.push(price.map(|p| format!("{:.*}", settings::FIAT_DECIMALS, p)).unwrap_or_default());
+            row.push(if entry.is_native() { value(&entry.amount) } else { String::new() });
+            row.push(value(&entry.fee));
+        }
+        csv.push_str(&row.iter().map(String::as_str).map(csv_field).collect::<Vec<_>>().join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// RFC 4180 quoting; text a spreadsheet would evaluate as a formula is prefixed with a quote
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// Native currency price of each entry on the day it was signed; chains whose coin has no
+/// market value, such as testnets, get None
+pub async fn fiat_prices(provider: &dyn PriceProvider, entries: &[HistoryEntry], currency: &str) -> WalletResult<FiatPrices> {
+    let currency = currency.to_lowercase();
+    let mut prices = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let price = if is_priced_chain(entry.chain_id) {
+            provider.price_on(&entry.currency, entry.at.date_naive(), &currency).await?
+        } else {
+            None
+        };
+        prices.push(price);
+    }
+    Ok(FiatPrices { currency, prices })
+}
@@ -0,0 +1,79 @@
+use crate::config::{crypto, kdf_bench};
+use crate::errors::WalletResult;
+use crate::models::keystore::KdfAlgorithm;
+use crate::services::crypto::{CryptoService, KdfSettings};
+use rand::RngCore;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Parameters expected to take about the target time on this machine
+#[derive(Debug, Clone, Serialize)]
+pub struct KdfSuggestion {
+    #[serde(flatten)]
+    pub settings: KdfSettings,
+    pub estimated_ms: u64,
+    /// True when a floor or ceiling kept the estimate away from the target
+    pub clamped: bool,
+}
+
+/// Time one key derivation with `settings`
+pub fn measure(settings: KdfSettings) -> WalletResult<Duration> {
+    let mut salt = vec![0u8; crypto::SALT_LENGTH];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let mut key = zeroize::Zeroizing::new(vec![0u8; crypto::KEY_LENGTH]);
+
+    let started = Instant::now();
+    CryptoService::derive_key("kdf-bench", &salt, &settings.params(&salt), &mut key)?;
+    Ok(started.elapsed())
+}
+
+/// Probe `algorithm` cheaply and scale its cost to take about `target` per unlock
+pub fn tune(algorithm: KdfAlgorithm, target: Duration) -> WalletResult<KdfSuggestion> {
+    let target_ms = target.as_secs_f64() * 1000.0;
+
+    match algorithm {
+        KdfAlgorithm::Argon2id => {
+            let probe = KdfSettings::Argon2id { memory: kdf_bench::ARGON2_PROBE_MEMORY, iterations: 1, parallelism: 1 };
+            let probe_ms = millis(measure(probe)?);
+            // Argon2 time grows linearly with memory, then with passes once memory is capped
+            let wanted = kdf_bench::ARGON2_PROBE_MEMORY as f64 * target_ms / probe_ms;
+            let memory = (wanted as u32).clamp(kdf_bench::ARGON2_MIN_MEMORY, kdf_bench::ARGON2_MAX_MEMORY);
+            let iterations = (wanted / memory as f64).round().max(1.0) as u32;
+            let estimated = probe_ms * (memory as f64 * iterations as f64) / kdf_bench::ARGON2_PROBE_MEMORY as f64;
+            Ok(suggestion(KdfSettings::Argon2id { memory, iterations, parallelism: 1 }, estimated, target_ms))
+        }
+        KdfAlgorithm::Scrypt => {
+            let probe_log_n = kdf_bench::SCRYPT_PROBE_LOG_N;
+            let probe = KdfSettings::Scrypt { log_n: probe_log_n, r: crypto::V3_SCRYPT_R, p: crypto::V3_SCRYPT_P };
+            let probe_ms = millis(measure(probe)?);
+            // Each step of log_n doubles the time
+            let steps = (target_ms / probe_ms).log2().round() as i32;
+            let log_n = (probe_log_n as i32 + steps)
+                .clamp(kdf_bench::SCRYPT_MIN_LOG_N as i32, kdf_bench::SCRYPT_MAX_LOG_N as i32) as u8;
+            let estimated = probe_ms * 2f64.powi(log_n as i32 - probe_log_n as i32);
+            Ok(suggestion(KdfSettings::Scrypt { log_n, r: crypto::V3_SCRYPT_R, p: crypto::V3_SCRYPT_P }, estimated, target_ms))
+        }
+        KdfAlgorithm::Pbkdf2 => {
+            let probe = KdfSettings::Pbkdf2 { iterations: kdf_bench::PBKDF2_PROBE_ITERATIONS };
+            let probe_ms = millis(measure(probe)?);
+            let wanted = kdf_bench::PBKDF2_PROBE_ITERATIONS as f64 * target_ms / probe_ms;
+            // Round to thousands so the config stays readable
+            let iterations = ((wanted / 1000.0).round() as u32 * 1000).max(kdf_bench::PBKDF2_MIN_ITERATIONS);
+            let estimated = probe_ms * iterations as f64 / kdf_bench::PBKDF2_PROBE_ITERATIONS as f64;
+            Ok(suggestion(KdfSettings::Pbkdf2 { iterations }, estimated, target_ms))
+        }
+    }
+}
+
+fn suggestion(settings: KdfSettings, estimated_ms: f64, target_ms: f64) -> KdfSuggestion {
+    KdfSuggestion {
+        settings,
+        estimated_ms: estimated_ms.round() as u64,
+        // Outside 2x of the target the floors or ceilings decided, not the measurement
+        clamped: estimated_ms > target_ms * 2.0 || estimated_ms < target_ms / 2.0,
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    (duration.as_secs_f64() * 1000.0).max(0.001)
+}
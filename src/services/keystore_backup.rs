@@ -0,0 +1,105 @@
+//! Timestamped copies of a keystore, taken just before it is rewritten so a bad password
+//! change or migration can be rolled back with `restore-backup`. Backups sit beside the
+//! keystore as `<wallet>.json.<UTC time>.bak`, which sorts oldest first.
+use crate::config::wallet as settings;
+use crate::errors::{FilesystemError, WalletResult};
+use crate::services::CryptoService;
+use crate::utils;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Copy `path` aside unless `keep` is 0 or it does not exist yet, then prune its backups to
+/// the newest `keep`. Returns the new backup.
+pub async fn snapshot(path: &Path, keep: u32) -> WalletResult<Option<PathBuf>> {
+    if keep == 0 || !path.exists() {
+        return Ok(None);
+    }
+    let stamp = chrono::Utc::now().format(settings::BACKUP_TIME_FORMAT);
+    let backup = path.with_file_name(format!("{}.{}.{}", file_name(path)?, stamp, settings::BACKUP_EXTENSION));
+    tokio::fs::copy(path, &backup).await?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::Permissions::from_mode(crate::config::fs::KEYSTORE_FILE_PERMISSIONS);
+        tokio::fs::set_permissions(&backup, mode).await?;
+    }
+    info!("Backed up {} to {}", path.display(), backup.display());
+
+    let existing = list(path).await?;
+    for stale in existing.iter().take(existing.len().saturating_sub(keep as usize)) {
+        if let Err(e) = tokio::fs::remove_file(stale).await {
+            warn!("Could not remove the old backup {}: {}", stale.display(), e);
+        }
+    }
+    Ok(Some(backup))
+}
+
+/// Backups of the keystore at `path`, oldest first
+pub async fn list(path: &Path) -> WalletResult<Vec<PathBuf>> {
+    let prefix = format!("{}.", file_name(path)?);
+    let suffix = format!(".{}", settings::BACKUP_EXTENSION);
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut backups = Vec::new();
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(backups),
+        Err(e) => return Err(e.into()),
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if name.starts_with(&prefix) && name.ends_with(&suffix) {
+            backups.push(entry.path());
+        }
+    }
+    backups.sort();
+    Ok(backups)
+}
+
+/// Put `backup`, or the newest backup, back in place of the keystore at `path`. The keystore
+/// being replaced is backed up first when backups are kept, so a restore can be undone.
+pub async fn restore(path: &Path, backup: Option<&Path>, keep: u32) -> WalletResult<PathBuf> {
+    let backup = match backup {
+        Some(backup) => backup.to_path_buf(),
+        None => list(path).await?.pop().ok_or_else(|| FilesystemError::FileNotFound {
+            path: format!("{}.*.{}", path.display(), settings::BACKUP_EXTENSION),
+            director: "set keystore_backups in the config file to keep copies before each rewrite".to_string(),
+        })?,
+    };
+    // Refuse anything that would not load as a keystore
+    CryptoService::load_any_keystore(&backup).await?;
+    let contents = tokio::fs::read(&backup).await?;
+
+    snapshot(path, keep).await?;
+    utils::write_private(path, &contents).await?;
+    Ok(backup)
+}
+
+/// Carry the backups of `from` over to its new name `to`, as `rename --to` moves a keystore
+pub async fn move_all(from: &Path, to: &Path) -> WalletResult<()> {
+    let (old_name, new_name) = (file_name(from)?, file_name(to)?);
+    for backup in list(from).await? {
+        let Some(name) = backup.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let renamed = to.with_file_name(format!("{}{}", new_name, &name[old_name.len()..]));
+        tokio::fs::rename(&backup, renamed).await?;
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> WalletResult<&str> {
+    path.file_name().and_then(|name| name.to_str()).ok_or_else(|| {
+        FilesystemError::InvalidFormat {
+            path: path.display().to_string(),
+            details: "not a file path".to_string(),
+        }
+        .into()
+    })
+}
@@ -0,0 +1,213 @@
+//! Secrets kept in memory for a while, e.g. by the agent, go into a buffer locked with mlock(2)
+//! so it is never written to swap, and zeroed before it is unlocked and freed.
+//!
+//! `harden` opts the whole process in: core dumps are turned off and the mnemonic and
+//! passphrase of every `Wallet`, as well as decrypted keystore payloads, are locked too.
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::warn;
+use zeroize::Zeroize;
+
+static HARDENED: AtomicBool = AtomicBool::new(false);
+/// Set after the first refused lock, so a low RLIMIT_MEMLOCK warns once rather than per secret
+static LOCK_REFUSED: AtomicBool = AtomicBool::new(false);
+
+/// Disable core dumps and lock secrets in memory from now on. Returns whether core dumps
+/// could be disabled; locks that are refused later only warn.
+pub fn harden() -> bool {
+    HARDENED.store(true, Ordering::Relaxed);
+    let disabled = disable_core_dumps();
+    if !disabled {
+        warn!("Could not disable core dumps, a crash may write secrets to disk");
+    }
+    disabled
+}
+
+pub fn is_hardened() -> bool {
+    HARDENED.load(Ordering::Relaxed)
+}
+
+/// Lock `data` when hardened, warning once if the lock is refused
+fn lock_if_hardened(data: &[u8]) -> bool {
+    if !is_hardened() || data.is_empty() {
+        return false;
+    }
+    let locked = lock(data);
+    if !locked && !LOCK_REFUSED.swap(true, Ordering::Relaxed) {
+        warn!("Could not lock secrets in memory, they may be swapped to disk; raise RLIMIT_MEMLOCK (ulimit -l)");
+    }
+    locked
+}
+
+/// Keeps a borrowed secret, e.g. a decrypted payload, locked in memory while hardened
+pub struct RegionLock<'a> {
+    data: &'a [u8],
+    locked: bool,
+}
+
+impl<'a> RegionLock<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, locked: lock_if_hardened(data) }
+    }
+}
+
+impl Drop for RegionLock<'_> {
+    fn drop(&mut self) {
+        if self.locked {
+            unlock(self.data);
+        }
+    }
+}
+
+/// String secret, such as a mnemonic, locked in memory while hardened and zeroed on drop.
+/// It is never modified in place, so the locked buffer stays where it is.
+#[derive(Default)]
+pub struct LockedString {
+    value: String,
+    locked: bool,
+}
+
+impl LockedString {
+    pub fn new(value: String) -> Self {
+        let locked = lock_if_hardened(value.as_bytes());
+        Self { value, locked }
+    }
+}
+
+impl From<&str> for LockedString {
+    fn from(value: &str) -> Self {
+        Self::new(value.to_string())
+    }
+}
+
+impl std::ops::Deref for LockedString {
+    type Target = String;
+
+    fn deref(&self) -> &String {
+        &self.value
+    }
+}
+
+impl Clone for LockedString {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl PartialEq for LockedString {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl Eq for LockedString {}
+
+impl std::fmt::Debug for LockedString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl Zeroize for LockedString {
+    /// Zero the bytes but keep the length, so `Drop` unlocks the range it locked
+    fn zeroize(&mut self) {
+        // SAFETY: NUL bytes are valid UTF-8
+        unsafe { self.value.as_bytes_mut() }.zeroize();
+    }
+}
+
+impl Drop for LockedString {
+    fn drop(&mut self) {
+        self.zeroize();
+        if self.locked {
+            unlock(self.value.as_bytes());
+        }
+    }
+}
+
+impl serde::Serialize for LockedString {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.value)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for LockedString {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
+
+pub struct LockedBuffer {
+    data: Vec<u8>,
+    locked: bool,
+}
+
+impl LockedBuffer {
+    /// Copy `bytes` into a buffer of its own and lock it; when the lock is refused, e.g. over
+    /// RLIMIT_MEMLOCK, the buffer is still zeroed on drop but may be swapped
+    pub fn new(bytes: &[u8]) -> Self {
+        let mut data = Vec::with_capacity(bytes.len());
+        data.extend_from_slice(bytes);
+        let locked = lock(&data);
+        if !locked && !data.is_empty() {
+            warn!("Could not lock {} bytes of key material in memory, it may be swapped to disk", data.len());
+        }
+        Self { data, locked }
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+impl Drop for LockedBuffer {
+    fn drop(&mut self) {
+        // Zero in place, keeping the length, so the unlocked range is the locked one
+        self.data.as_mut_slice().zeroize();
+        if self.locked {
+            unlock(&self.data);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn lock(data: &[u8]) -> bool {
+    // SAFETY: the range is the initialised part of a live allocation
+    !data.is_empty() && unsafe { libc::mlock(data.as_ptr().cast(), data.len()) } == 0
+}
+
+#[cfg(unix)]
+fn unlock(data: &[u8]) {
+    // SAFETY: the same range `lock` locked
+    unsafe {
+        libc::munlock(data.as_ptr().cast(), data.len());
+    }
+}
+
+#[cfg(unix)]
+fn disable_core_dumps() -> bool {
+    let limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    // SAFETY: setrlimit only reads the limit passed by reference
+    let limited = unsafe { libc::setrlimit(libc::RLIMIT_CORE, &limit) } == 0;
+    // Not dumpable also keeps other processes of the same user from attaching with ptrace
+    #[cfg(target_os = "linux")]
+    // SAFETY: PR_SET_DUMPABLE takes plain integer arguments
+    let limited = limited && unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 0, 0, 0, 0) } == 0;
+    limited
+}
+
+#[cfg(not(unix))]
+fn disable_core_dumps() -> bool {
+    false
+}
+
+#[cfg(not(unix))]
+fn lock(_data: &[u8]) -> bool {
+    false
+}
+
+#[cfg(not(unix))]
+fn unlock(_data: &[u8]) {}
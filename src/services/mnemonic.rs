@@ -1,24 +1,76 @@
 use crate::config;
-use crate::errors::{CryptographicError, WalletResult};
+use crate::errors::{CryptographicError, UserInputError, ValidationError, WalletError, WalletResult};
+use crate::models::Wallet;
 use bip39::{Language, Mnemonic};
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
 use rand::RngCore;
-use std::str::FromStr;
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use sha2::{Digest, Sha256, Sha512};
+use std::borrow::Cow;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// BIP39 wordlists by the name `--language` takes, with their BIP85 language code
+pub const LANGUAGES: &[(&str, Language, u32)] = &[
+    ("english", Language::English, 0),
+    ("japanese", Language::Japanese, 1),
+    ("korean", Language::Korean, 2),
+    ("spanish", Language::Spanish, 3),
+    ("chinese_simplified", Language::SimplifiedChinese, 4),
+    ("chinese_traditional", Language::TraditionalChinese, 5),
+    ("french", Language::French, 6),
+    ("italian", Language::Italian, 7),
+    ("czech", Language::Czech, 8),
+    ("portuguese", Language::Portuguese, 9),
+];
+
+/// Wordlist called `name`, e.g. "spanish" or "chinese-simplified"
+pub fn parse_language(name: &str) -> WalletResult<Language> {
+    let name = name.trim().to_lowercase().replace('-', "_");
+    LANGUAGES
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, language, _)| *language)
+        .ok_or_else(|| {
+            UserInputError::InvalidParameters {
+                parameter: "language".to_string(),
+                value: name.clone(),
+                expected: format!("one of {}", LANGUAGES.iter().map(|(n, _, _)| *n).collect::<Vec<_>>().join(", ")),
+            }
+            .into()
+        })
+}
+
+/// Name of `language` as `--language` takes it
+pub fn language_name(language: Language) -> &'static str {
+    LANGUAGES.iter().find(|(_, l, _)| *l == language).map_or("english", |(name, _, _)| name)
+}
+
+/// BIP85 language code of `language`
+pub fn bip85_language_code(language: Language) -> u32 {
+    LANGUAGES.iter().find(|(_, l, _)| *l == language).map_or(0, |(_, _, code)| *code)
+}
 
 #[derive(Debug, Clone, Zeroize, ZeroizeOnDrop)]
 pub struct SecureMnemonic {
     phrase: String,
+    #[zeroize(skip)]
+    language: Language,
 }
 
 impl SecureMnemonic {
-    pub fn new(phrase: String) -> Self {
-        Self { phrase }
+    pub fn new(phrase: String, language: Language) -> Self {
+        Self { phrase, language }
     }
 
     pub fn phrase(&self) -> &str {
         &self.phrase
     }
 
+    /// Wordlist the phrase is written in
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
     pub fn word_count(&self) -> usize {
         self.phrase.split_whitespace().count()
     }
@@ -35,6 +87,142 @@ impl SecureMnemonic {
         MnemonicService::validate(&self.phrase)?;
         Ok(())
     }
+
+    /// Index (0..2048) of every word in the phrase's wordlist
+    pub fn word_indices(&self) -> WalletResult<Vec<u16>> {
+        self.words()
+            .iter()
+            .map(|word| {
+                self.language.find_word(&normalize(&word.to_lowercase())).ok_or_else(|| {
+                    CryptographicError::InvalidMnemonic {
+                        detail: format!("{:?} is not in the BIP39 {} wordlist", word, language_name(self.language)),
+                        suggestion: "Check the spelling of each word".to_string(),
+                    }
+                    .into()
+                })
+            })
+            .collect()
+    }
+
+    /// The 11 bits each word encodes; concatenated they are the entropy followed by its checksum.
+    /// As sensitive as the phrase itself.
+    pub fn word_bits(&self) -> WalletResult<Vec<WordBits>> {
+        Ok(self
+            .words()
+            .into_iter()
+            .zip(self.word_indices()?)
+            .enumerate()
+            .map(|(i, (word, index))| WordBits {
+                position: i + 1,
+                word: word.to_string(),
+                index,
+                bits: format!("{:011b}", index),
+            })
+            .collect())
+    }
+
+    /// "Word N of M" quiz over `questions` distinct random positions
+    pub fn quiz(&self, questions: usize) -> WordQuiz {
+        WordQuiz::random(self.word_count(), questions)
+    }
+}
+
+/// NFKD form of `text`, the form BIP39 wordlists and seeds use
+fn normalize(text: &str) -> String {
+    let mut text = Cow::Borrowed(text);
+    Mnemonic::normalize_utf8_cow(&mut text);
+    text.into_owned()
+}
+
+/// Index of `word` in the BIP39 English wordlist
+pub fn wordlist_index(word: &str) -> Option<u16> {
+    Language::English.find_word(&word.to_lowercase())
+}
+
+/// Word at `index` of the BIP39 English wordlist
+pub fn wordlist_word(index: u16) -> Option<&'static str> {
+    Language::English.word_list().get(index as usize).copied()
+}
+
+/// One word of a mnemonic with its wordlist index in binary
+#[derive(Debug, Clone, Zeroize, ZeroizeOnDrop)]
+pub struct WordBits {
+    /// 1-based position in the phrase
+    pub position: usize,
+    pub word: String,
+    pub index: u16,
+    /// `index` as 11 binary digits
+    pub bits: String,
+}
+
+/// Backup check asking for the words at a few positions, without revealing the others
+#[derive(Debug, Clone)]
+pub struct WordQuiz {
+    /// 1-based, ascending
+    positions: Vec<usize>,
+    total: usize,
+}
+
+impl WordQuiz {
+    pub fn random(total: usize, questions: usize) -> Self {
+        let mut positions: Vec<usize> = rand::seq::index::sample(&mut rand::thread_rng(), total, questions.min(total))
+            .into_iter()
+            .map(|i| i + 1)
+            .collect();
+        positions.sort_unstable();
+        Self { positions, total }
+    }
+
+    /// Quiz over fixed 1-based positions
+    pub fn with_positions(total: usize, positions: Vec<usize>) -> Self {
+        Self { positions, total }
+    }
+
+    pub fn positions(&self) -> &[usize] {
+        &self.positions
+    }
+
+    /// "Word 3 of 12" style prompt for every question
+    pub fn prompts(&self) -> Vec<String> {
+        self.positions
+            .iter()
+            .map(|position| format!("Word {} of {}", position, self.total))
+            .collect()
+    }
+
+    /// Compare `answers`, one per question, against the phrase; errors name the wrong positions only
+    pub fn check(&self, mnemonic: &SecureMnemonic, answers: &[&str]) -> WalletResult<()> {
+        let words = mnemonic.words();
+        if answers.len() != self.positions.len() || words.len() != self.total {
+            return Err(ValidationError::PreconditionFailed {
+                check: "backup quiz".to_string(),
+                details: format!("expected {} answers for a {} word phrase", self.positions.len(), self.total),
+            }
+            .into());
+        }
+
+        let wrong: Vec<String> = self
+            .positions
+            .iter()
+            .zip(answers)
+            .filter(|(position, answer)| {
+                words
+                    .get(position.wrapping_sub(1))
+                    .is_none_or(|word| normalize(&word.to_lowercase()) != normalize(&answer.trim().to_lowercase()))
+            })
+            .map(|(position, _)| position.to_string())
+            .collect();
+
+        if wrong.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError::PreconditionFailed {
+                check: "backup quiz".to_string(),
+                details: format!("word {} of {} does not match the backup", wrong.join(", "), self.total),
+            }
+            .into())
+        }
+    }
 }
 #[derive(Debug, Clone, Zeroize, ZeroizeOnDrop)]
 pub struct SecureSeed {
@@ -63,27 +251,123 @@ impl SecureSeed {
     }
 }
 
+/// User entropy given as hex, for `create --entropy-hex`; at least as many bytes as the
+/// mnemonic's entropy, so it could stand on its own
+pub fn parse_entropy_hex(hex_str: &str, word_count: u8) -> WalletResult<Zeroizing<Vec<u8>>>{
+    let bytes = Zeroizing::new(hex::decode(hex_str.trim().trim_start_matches("0x")).map_err(|_| UserInputError::InvalidParameters {
+        parameter: "entropy-hex".to_string(),
+        value: "<hidden>".to_string(),
+        expected: "hex digits".to_string(),
+    })?);
+    let required = required_entropy_bits(word_count)? / 8;
+    if bytes.len() < required {
+        return Err(UserInputError::InvalidParameters {
+            parameter: "entropy-hex".to_string(),
+            value: format!("{} bytes", bytes.len()),
+            expected: format!("at least {} bytes ({} hex digits) for {} words", required, required * 2, word_count),
+        }
+        .into());
+    }
+    if bytes.iter().all(|b| *b == bytes[0]) {
+        return Err(UserInputError::InvalidParameters {
+            parameter: "entropy-hex".to_string(),
+            value: "one repeated byte".to_string(),
+            expected: "random bytes, e.g. from coin flips or dice".to_string(),
+        }
+        .into());
+    }
+    Ok(bytes)
+}
+
+/// Rolls of a six-sided die needed to match the entropy of a `word_count` word mnemonic
+pub fn required_dice_rolls(word_count: u8) -> WalletResult<usize>{
+    let bits = required_entropy_bits(word_count)?;
+    Ok((bits as f64 / 6f64.log2()).ceil() as usize)
+}
+
+/// Dice rolls (digits 1-6; spaces and other separators are ignored) as entropy input, for
+/// `create --dice`
+pub fn parse_dice_rolls(rolls: &str, word_count: u8) -> WalletResult<Zeroizing<Vec<u8>>>{
+    let rolls: Zeroizing<Vec<u8>> = Zeroizing::new(
+        rolls.chars().filter(|c| !c.is_whitespace() && *c != ',').map(|c| c as u8).collect(),
+    );
+    if let Some(bad) = rolls.iter().find(|c| !(b'1'..=b'6').contains(c)) {
+        return Err(UserInputError::InvalidParameters {
+            parameter: "dice".to_string(),
+            value: (*bad as char).to_string(),
+            expected: "rolls of a six-sided die, 1 to 6".to_string(),
+        }
+        .into());
+    }
+    let required = required_dice_rolls(word_count)?;
+    if rolls.len() < required {
+        return Err(UserInputError::InvalidParameters {
+            parameter: "dice".to_string(),
+            value: format!("{} rolls", rolls.len()),
+            expected: format!("at least {} rolls for {} words", required, word_count),
+        }
+        .into());
+    }
+    if rolls.iter().all(|r| *r == rolls[0]) {
+        return Err(UserInputError::InvalidParameters {
+            parameter: "dice".to_string(),
+            value: "one repeated number".to_string(),
+            expected: "real rolls of a fair die".to_string(),
+        }
+        .into());
+    }
+    Ok(rolls)
+}
+
+fn required_entropy_bits(word_count: u8) -> WalletResult<usize>{
+    config::entropy_bits_for_word_count(word_count).ok_or_else(|| {
+        CryptographicError::InvalidMnemonic{
+            detail: format!("Unsupported word count: {}", word_count),
+            suggestion: "Use 12, 15, 18, 21 or 24 words".to_string()
+        }
+        .into()
+    })
+}
+
 pub struct MnemonicService;
 
 impl MnemonicService{
     pub fn generate(word_count: u8) -> WalletResult<SecureMnemonic>{
+        Self::generate_in(word_count, Language::English)
+    }
+
+    /// Fresh mnemonic of `word_count` words from the `language` wordlist
+    pub fn generate_in(word_count: u8, language: Language) -> WalletResult<SecureMnemonic>{
+        Self::generate_mixed(word_count, language, None)
+    }
+
+    /// Like `generate_in`, with the SHA-256 of `user_entropy` (from `parse_entropy_hex` or
+    /// `parse_dice_rolls`) XORed into the OS randomness. The mnemonic stays unpredictable as long
+    /// as either source is, so a weak RNG or badly rolled dice alone cannot give it away.
+    pub fn generate_mixed(word_count: u8, language: Language, user_entropy: Option<&[u8]>) -> WalletResult<SecureMnemonic>{
         if !config::is_supported_word_count(word_count){
             return Err(CryptographicError::InvalidAddressFormat{
                 details: format!("Unsupported word count: {}", word_count),
-                suggestion: "Use 12 or 24 words".to_string()
+                suggestion: "Use 12, 15, 18, 21 or 24 words".to_string()
             }.into())
         }
 
         let entropy_bits = config::entropy_bits_for_word_count(word_count)
             .ok_or_else(|| CryptographicError::InvalidMnemonic{
                 detail: format!("Cannot determinate entropy for {} words", word_count),
-                suggestion: "Use 12 or 24 words!".to_string()
+                suggestion: "Use 12, 15, 18, 21 or 24 words!".to_string()
             })?;
         
+        Self::check_entropy_availability(entropy_bits)?;
+
         let mut entropy = vec![0u8; entropy_bits / 8];
         rand::thread_rng().fill_bytes(&mut entropy);
+        if let Some(user_entropy) = user_entropy {
+            let digest = Zeroizing::new(Sha256::digest(user_entropy));
+            entropy.iter_mut().zip(digest.iter()).for_each(|(byte, user)| *byte ^= user);
+        }
         
-        let mnemonic = Mnemonic::from_entropy(&entropy).map_err(|e|{
+        let mnemonic = Mnemonic::from_entropy_in(language, &entropy).map_err(|e|{
             CryptographicError::InvalidMnemonic{
                 detail: e.to_string(),
                 suggestion: "Ensure system has adequate entropy sources".to_string()
@@ -91,31 +375,87 @@ impl MnemonicService{
         })?;
 
         entropy.zeroize();
-        Ok(SecureMnemonic::new(mnemonic.to_string()))
+        Ok(SecureMnemonic::new(mnemonic.to_string(), language))
     }
 
-    pub fn validate(mnemonic_str: &str) -> WalletResult<SecureMnemonic>{
-        let mnemonic = Mnemonic::from_str(mnemonic_str).map_err(|e|{
+    /// BIP85 child mnemonic of `word_count` words in `language` at `index`: the same wallet always
+    /// yields the same child, and the child reveals nothing about the wallet it came from
+    pub fn derive_bip85(wallet: &Wallet, language: Language, word_count: u8, index: u32) -> WalletResult<SecureMnemonic>{
+        let entropy_bits = config::entropy_bits_for_word_count(word_count)
+            .filter(|_| config::bip85::WORD_COUNTS.contains(&word_count))
+            .ok_or_else(|| CryptographicError::InvalidMnemonic{
+                detail: format!("Unsupported word count: {}", word_count),
+                suggestion: "BIP85 child mnemonics have 12, 18 or 24 words".to_string()
+            })?;
+
+        let key = wallet.hd_private_key(&config::bip85_mnemonic_path(bip85_language_code(language), word_count, index))?;
+        let mut mac = Hmac::<Sha512>::new_from_slice(config::bip85::ENTROPY_HMAC_KEY)
+            .expect("HMAC accepts keys of any length");
+        mac.update(&key);
+        let entropy = Zeroizing::new(mac.finalize().into_bytes().to_vec());
+
+        let mnemonic = Mnemonic::from_entropy_in(language, &entropy[..entropy_bits / 8]).map_err(|e|{
             CryptographicError::InvalidMnemonic{
                 detail: e.to_string(),
-                suggestion: "Verify the mnemonic phrase has the correct number of words (12 or 24) and all words are from the BIP39 wordlist.".to_string(),
+                suggestion: "BIP85 child mnemonics have 12, 18 or 24 words".to_string()
             }
         })?;
+        Ok(SecureMnemonic::new(mnemonic.to_string(), language))
+    }
+
+    /// Wordlist `phrase` is written in. A few words appear in more than one list, so a phrase
+    /// counts as a language only when it also passes that language's checksum; English wins
+    /// the rare phrase valid in several.
+    pub fn detect_language(phrase: &str) -> WalletResult<Language>{
+        let mut valid = LANGUAGES
+            .iter()
+            .map(|(_, language, _)| *language)
+            .filter(|language| Mnemonic::parse_in(*language, phrase).is_ok());
+
+        match valid.next() {
+            Some(language) => Ok(language),
+            None => Err(CryptographicError::InvalidMnemonic{
+                detail: Mnemonic::parse(phrase).err().map_or_else(|| "checksum mismatch".to_string(), |e| e.to_string()),
+                suggestion: "Verify the mnemonic phrase has the correct number of words (12, 15, 18, 21 or 24) and all words are from one BIP39 wordlist.".to_string(),
+            }
+            .into()),
+        }
+    }
+
+    /// `phrase` parsed in its detected wordlist
+    fn parse(phrase: &str) -> WalletResult<Mnemonic>{
+        let language = Self::detect_language(phrase)?;
+        Mnemonic::parse_in(language, phrase).map_err(|e|{
+            CryptographicError::InvalidMnemonic{
+                detail: e.to_string(),
+                suggestion: "Ensure mnemonic is valid BIP39 format".to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// BIP39 seed of `phrase`, in whichever wordlist it is written, under `passphrase`
+    pub fn seed_of(phrase: &str, passphrase: &str) -> WalletResult<Zeroizing<Vec<u8>>>{
+        Ok(Zeroizing::new(Self::parse(phrase)?.to_seed(passphrase).to_vec()))
+    }
+
+    pub fn validate(mnemonic_str: &str) -> WalletResult<SecureMnemonic>{
+        let mnemonic = Self::parse(mnemonic_str)?;
 
         let word_count = mnemonic_str.split_whitespace().count();
         if !config::is_supported_word_count(word_count as u8){
             return Err(CryptographicError::InvalidMnemonic{
                 detail: format!("Unsupported word count: {}", word_count),
-                suggestion: "Use 12 or 24 words".to_string()
+                suggestion: "Use 12, 15, 18, 21 or 24 words".to_string()
             }
             .into());
         }
 
-        Ok(SecureMnemonic::new(mnemonic.to_string()))
+        Ok(SecureMnemonic::new(mnemonic.to_string(), mnemonic.language()))
     }
 
     pub fn generate_seed(mnemonic: &SecureMnemonic, passphrase: Option<&str>) -> WalletResult<SecureSeed>{
-        let bip39_mnemonic = Mnemonic::from_str(mnemonic.phrase()).map_err(|e|{
+        let bip39_mnemonic = Mnemonic::parse_in(mnemonic.language(), mnemonic.phrase()).map_err(|e|{
             CryptographicError::InvalidMnemonic{
                 detail: e.to_string(),
                 suggestion: "Ensure mnemonic is valid BIP39 format".to_string(),
@@ -127,18 +467,86 @@ impl MnemonicService{
         Ok(SecureSeed::new(seed.to_vec()))
     }
 
-    ///模拟检查，在实际项目中需要更精确的 OS 检查
-    fn check_entropy_availability(required_bits: usize) -> WalletResult<()> {
-         if required_bits > 512 {
+    /// Refuse to generate keys from an unhealthy RNG: the OS source (getrandom) must work, the
+    /// Linux input pool must report enough entropy, and output must not repeat within this run.
+    /// Repeats across runs are `check_entropy_history`'s, which needs the state directory.
+    pub fn check_entropy_availability(required_bits: usize) -> WalletResult<()> {
+        let unhealthy = |available: u32, suggestion: &str| -> WalletError {
+            CryptographicError::InsufficientEntropy {
+                available,
+                required: required_bits as u32,
+                suggestion: suggestion.to_string(),
+            }
+            .into()
+        };
+
+        let mut first = [0u8; 32];
+        let mut second = [0u8; 32];
+        if OsRng.try_fill_bytes(&mut first).and_then(|_| OsRng.try_fill_bytes(&mut second)).is_err() {
+            return Err(unhealthy(0, "The OS random number generator is unavailable; check that /dev/urandom exists and getrandom is not blocked by a seccomp filter."));
+        }
+        if first == second || first.iter().all(|b| *b == first[0]) {
+            return Err(unhealthy(0, "The OS random number generator returned repeating output; do not create keys on this machine."));
+        }
+
+        if let Some(available) = Self::kernel_entropy_bits() {
+            if (available as usize) < required_bits {
+                return Err(unhealthy(available, "The kernel entropy pool is low; wait for it to fill after boot, or run an entropy daemon such as haveged or rngd."));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Refuse to generate keys when the OS RNG repeats the output of a recent run, which is
+    /// what a restored VM snapshot looks like. Hashes of throwaway samples are kept in
+    /// `state_dir`; failing to read or write them is ignored.
+    #[cfg(feature = "native")]
+    pub async fn check_entropy_history(state_dir: &std::path::Path, word_count: u8) -> WalletResult<()> {
+        let required_bits = required_entropy_bits(word_count)?;
+        let mut sample = Zeroizing::new([0u8; 32]);
+        OsRng.fill_bytes(sample.as_mut());
+        let path = state_dir.join(config::crypto::ENTROPY_FINGERPRINT_FILE);
+        if Self::seen_before(&path, &ethers::utils::keccak256(sample.as_ref())).await {
             return Err(CryptographicError::InsufficientEntropy {
-                available: 256, // Simplified value
+                available: 0,
                 required: required_bits as u32,
-                suggestion: "Ensure system has adequate entropy sources.".to_string(),
+                suggestion: "Random output repeated an earlier run, as happens when a VM snapshot is restored; reboot the machine before creating keys.".to_string(),
             }
             .into());
         }
-
         Ok(())
     }
+
+    fn kernel_entropy_bits() -> Option<u32> {
+        if !cfg!(target_os = "linux") {
+            return None;
+        }
+        std::fs::read_to_string(config::crypto::LINUX_ENTROPY_AVAIL_PATH).ok()?.trim().parse().ok()
+    }
+
+    /// Record the sample hash in the history at `path` and report whether a recent run
+    /// already produced it
+    #[cfg(feature = "native")]
+    async fn seen_before(path: &std::path::Path, fingerprint: &[u8; 32]) -> bool {
+        let entry = hex::encode(fingerprint);
+        let mut history: Vec<String> = tokio::fs::read_to_string(path)
+            .await
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        if history.contains(&entry) {
+            return true;
+        }
+
+        history.push(entry);
+        let start = history.len().saturating_sub(config::crypto::ENTROPY_FINGERPRINT_HISTORY);
+        if let Some(dir) = path.parent() {
+            let _ = tokio::fs::create_dir_all(dir).await;
+        }
+        let _ = crate::utils::write_private(path, history[start..].join("\n").as_bytes()).await;
+        false
+    }
 }
 
@@ -1,7 +1,90 @@
+//! Services shared by the CLI and library users. The ones behind the `native` feature touch
+//! the filesystem, the network or the process; the rest also build for wasm32.
+
+pub mod address_format;
+#[cfg(feature = "native")]
+pub mod agent;
+pub mod approval;
+pub mod build_info;
+#[cfg(feature = "native")]
+pub mod cache;
+pub mod canonical_json;
+pub mod chains;
+#[cfg(feature = "native")]
+pub mod commands;
+#[cfg(feature = "native")]
+pub(crate) mod cold;
 pub mod crypto;
+#[cfg(feature = "native")]
+pub mod discovery;
+#[cfg(feature = "native")]
+pub mod ens;
+#[cfg(feature = "native")]
+pub mod erc20;
+#[cfg(feature = "native")]
+pub mod explorer;
+pub mod fees;
+#[cfg(feature = "native")]
+pub mod health;
+#[cfg(feature = "native")]
+pub mod history;
+pub mod kdf_bench;
+#[cfg(feature = "native")]
+pub mod keystore_backup;
+pub mod locked_memory;
 pub mod mnemonic;
+#[cfg(feature = "native")]
+pub mod monitor;
+pub mod multisig;
+#[cfg(feature = "native")]
+pub mod nft;
+#[cfg(feature = "native")]
+pub mod nonce_store;
+#[cfg(feature = "native")]
+pub mod output_file;
+#[cfg(feature = "native")]
+pub mod password_source;
+pub mod password_generator;
+pub mod password_strength;
+pub mod permit;
+pub mod permit2;
+pub mod prices;
+#[cfg(feature = "native")]
+pub mod quote;
+pub mod rate_limit;
+pub mod raw_transaction;
+#[cfg(feature = "native")]
+pub mod rpc;
+#[cfg(feature = "native")]
+pub mod rpc_server;
+pub mod safe;
+#[cfg(feature = "native")]
+pub mod script;
+pub mod shamir;
+#[cfg(feature = "native")]
+pub mod shutdown;
+pub mod signer;
+pub mod signing;
+#[cfg(feature = "native")]
+pub mod tokens;
+#[cfg(feature = "native")]
+pub mod usage;
+pub mod transaction;
+#[cfg(feature = "native")]
+pub mod unlock_throttle;
+#[cfg(feature = "native")]
+pub mod wallet_index;
+#[cfg(feature = "native")]
 pub mod walletmanager;
+#[cfg(feature = "native")]
+pub mod wallet_archive;
+pub mod wrapped;
 
+pub use address_format::AddressFormatter;
 pub use crypto::CryptoService;
 pub use mnemonic::MnemonicService;
+#[cfg(feature = "native")]
+pub use rpc::RpcClient;
+pub use transaction::TransactionService;
+#[cfg(feature = "native")]
 pub use walletmanager::WalletManager;
\ No newline at end of file
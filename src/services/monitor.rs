@@ -0,0 +1,94 @@
+use crate::errors::{UserInputError, WalletResult};
+use crate::services::chains::ChainInfo;
+use crate::services::rpc::{RpcClient, RpcRequest};
+use crate::services::transaction::parse_quantity;
+use crate::WalletConfig;
+use ethers::types::U256;
+use ethers::utils::{format_ether, parse_ether};
+use serde::Serialize;
+use serde_json::json;
+
+/// Minimum native balance a payer must keep on one chain
+#[derive(Debug, Clone, Serialize)]
+pub struct BalanceThreshold {
+    pub chain: ChainInfo,
+    /// Wei
+    pub min_balance: U256,
+}
+
+impl BalanceThreshold {
+    /// Parse `chain=amount`, amount in ether units (e.g. `mainnet=0.05`)
+    pub fn parse(spec: &str, config: &WalletConfig) -> WalletResult<Self> {
+        let invalid = || UserInputError::InvalidParameters {
+            parameter: "min_balance".to_string(),
+            value: spec.to_string(),
+            expected: "<chain>=<amount in ether units>, e.g. mainnet=0.05".to_string(),
+        };
+        let (chain, amount) = spec.split_once('=').ok_or_else(invalid)?;
+        Self::new(chain.trim(), amount.trim(), config).map_err(|_| invalid().into())
+    }
+
+    pub fn new(chain: &str, amount: &str, config: &WalletConfig) -> WalletResult<Self> {
+        let min_balance = parse_ether(amount).map_err(|_| UserInputError::InvalidParameters {
+            parameter: "min_balance".to_string(),
+            value: amount.to_string(),
+            expected: "amount in ether units".to_string(),
+        })?;
+        Ok(Self {
+            chain: ChainInfo::resolve(chain, config)?,
+            min_balance,
+        })
+    }
+}
+
+/// Balance of one hot wallet on one chain
+#[derive(Debug, Clone, Serialize)]
+pub struct BalanceStatus {
+    pub wallet: String,
+    pub address: String,
+    pub chain: String,
+    /// Ether units
+    pub balance: Option<String>,
+    pub min_balance: String,
+    pub below_threshold: bool,
+    pub error: Option<String>,
+}
+
+pub struct BalanceMonitor;
+
+impl BalanceMonitor {
+    /// Check every `(wallet name, address)` against every threshold, one batched call per chain
+    pub async fn check(
+        wallets: &[(String, String)],
+        thresholds: &[BalanceThreshold],
+        config: &WalletConfig,
+    ) -> WalletResult<Vec<BalanceStatus>> {
+        let mut statuses = Vec::new();
+        for threshold in thresholds {
+            let client = RpcClient::from_config(&threshold.chain.rpc_url, config)?;
+            let requests = wallets
+                .iter()
+                .map(|(_, address)| RpcRequest::new("eth_getBalance", json!([address, "latest"])))
+                .collect();
+
+            let results = match client.batch(requests).await {
+                Ok(results) => results,
+                Err(err) => wallets.iter().map(|_| Err(err.clone())).collect(),
+            };
+
+            for ((wallet, address), result) in wallets.iter().zip(results) {
+                let balance = result.and_then(|v| parse_quantity("balance", v.as_str().unwrap_or("0x0")));
+                statuses.push(BalanceStatus {
+                    wallet: wallet.clone(),
+                    address: address.clone(),
+                    chain: threshold.chain.name.clone(),
+                    balance: balance.as_ref().ok().map(|b| format_ether(*b)),
+                    min_balance: format_ether(threshold.min_balance),
+                    below_threshold: balance.as_ref().is_ok_and(|b| *b < threshold.min_balance),
+                    error: balance.err().map(|e| e.to_string()),
+                });
+            }
+        }
+        Ok(statuses)
+    }
+}
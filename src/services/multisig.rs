@@ -0,0 +1,344 @@
+//! Local M-of-N multisig without a contract. `multisig create` makes a fresh account key,
+//! splits it into SLIP-39 shares with threshold M (see `services::shamir`) and seals each share
+//! to one owner's companion key; the multisig keystore keeps only the owners' addresses and
+//! public keys, the threshold and the sealed shares. A transaction is proposed to one owner who
+//! coordinates, each approving owner signs the proposal and reseals their share to the
+//! coordinator, and `combine` restores the account key from M approvals to sign exactly the
+//! proposed transaction. Unlike threshold ECDSA, the key is assembled, in memory and only for
+//! that signature, on the coordinator's machine.
+use crate::config::multisig as settings;
+use crate::errors::{CryptographicError, UserInputError, ValidationError, WalletError, WalletResult};
+use crate::models::Wallet;
+use crate::services::shamir::ShamirService;
+use crate::services::signing::{SignedPayload, SigningService};
+use crate::services::transaction::{parse_address, SignedTransaction, TransactionService, UnsignedTransaction};
+use crate::utils::addresses_equal;
+use chrono::{DateTime, Utc};
+use crypto_box::aead::OsRng;
+use crypto_box::{PublicKey, SecretKey};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::utils::{keccak256, to_checksum};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+/// One of the N owners
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultisigOwner {
+    pub address: String,
+    /// Companion key (X25519) shares are sealed to, derived from the owner's wallet
+    pub public_key: String,
+    /// The owner's share of the account key, sealed to `public_key`
+    pub share: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultisigKeystore {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub version: u32,
+    /// The account the owners control together
+    pub address: String,
+    pub threshold: u8,
+    pub owners: Vec<MultisigOwner>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl MultisigKeystore {
+    /// Parse the multisig keystore read from `file_path`
+    pub fn from_json(json: &str, file_path: &str) -> WalletResult<Self> {
+        let invalid = |error: String| ValidationError::InvalidKeystoreSchema {
+            error,
+            file_path: file_path.to_string(),
+        };
+        let keystore: Self = serde_json::from_str(json).map_err(|e| invalid(format!("not a multisig keystore: {}", e)))?;
+        if keystore.kind != settings::KEYSTORE_TYPE || keystore.version != settings::KEYSTORE_VERSION {
+            return Err(invalid(format!("unsupported keystore type {} version {}", keystore.kind, keystore.version)).into());
+        }
+        Ok(keystore)
+    }
+
+    pub fn owner(&self, address: &str) -> Option<&MultisigOwner> {
+        self.owners.iter().find(|owner| addresses_equal(&owner.address, address))
+    }
+}
+
+/// A transaction waiting for approvals, addressed to the owner who combines them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultisigProposal {
+    pub multisig: String,
+    pub coordinator: String,
+    pub tx: UnsignedTransaction,
+    /// Signing hash of `tx`, what approvals commit to
+    pub sighash: String,
+    #[serde(default)]
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl MultisigProposal {
+    /// Text owners sign with `personal_sign` to approve
+    pub fn message(&self) -> WalletResult<String> {
+        Ok(format!(
+            "{}\nmultisig: {}\nchain: {}\ntransaction: {}",
+            settings::APPROVAL_HEADER,
+            to_checksum(&parse_address("multisig", &self.multisig)?, None),
+            self.tx.chain_id,
+            self.sighash
+        ))
+    }
+
+    /// `sighash` recomputed from `tx`, so an edited proposal cannot pass for the one approved
+    fn checked_sighash(&self) -> WalletResult<String> {
+        let sighash = format!("{:?}", TransactionService::build(&self.tx)?.sighash());
+        if !sighash.eq_ignore_ascii_case(&self.sighash) {
+            return Err(mismatch(format!(
+                "the proposal's transaction hashes to {}, not the {} it claims",
+                sighash, self.sighash
+            )));
+        }
+        Ok(sighash)
+    }
+}
+
+/// One owner's partial signature: their approval and their share for the coordinator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultisigApproval {
+    pub owner: String,
+    /// Signing hash of the approved transaction
+    pub sighash: String,
+    /// `personal_sign` over the proposal's message
+    pub signature: String,
+    /// The owner's share of the account key, sealed to the coordinator
+    pub share: String,
+}
+
+pub struct MultisigService;
+
+impl MultisigService {
+    /// Companion key of `wallet`. Its signature over a fixed message is deterministic
+    /// (RFC 6979), so hashing it gives the same key every time without storing another secret.
+    pub fn companion_key(wallet: &Wallet) -> WalletResult<SecretKey> {
+        let payload = SignedPayload::Message(settings::COMPANION_KEY_MESSAGE.as_bytes().to_vec());
+        let signature = Zeroizing::new(SigningService::sign(wallet, &payload)?);
+        let bytes = Zeroizing::new(hex::decode(signature.trim_start_matches("0x")).unwrap_or_default());
+        Ok(SecretKey::from(keccak256(&*bytes)))
+    }
+
+    /// `<address>:<public key>` naming `wallet` as an owner to `create`
+    pub fn owner_spec(wallet: &Wallet) -> WalletResult<String> {
+        let public_key = Self::companion_key(wallet)?.public_key();
+        Ok(format!(
+            "{}:{}",
+            to_checksum(&parse_address("address", wallet.address())?, None),
+            hex::encode(public_key.as_bytes())
+        ))
+    }
+
+    /// Owner address and companion public key of an `owner_spec`
+    pub fn parse_owner_spec(spec: &str) -> WalletResult<(String, PublicKey)> {
+        let invalid = || UserInputError::InvalidParameters {
+            parameter: "owner".to_string(),
+            value: spec.to_string(),
+            expected: "<address>:<public key> as `multisig owner-key` prints it".to_string(),
+        };
+        let (address, key) = spec.trim().split_once(':').ok_or_else(invalid)?;
+        let key: [u8; 32] = hex::decode(key).ok().and_then(|key| key.try_into().ok()).ok_or_else(invalid)?;
+        Ok((to_checksum(&parse_address("owner", address)?, None), PublicKey::from(key)))
+    }
+
+    /// New multisig over a fresh account key, one share per owner's companion public key
+    pub fn create(threshold: u8, owners: &[(String, PublicKey)]) -> WalletResult<MultisigKeystore> {
+        for (index, (address, _)) in owners.iter().enumerate() {
+            if owners[..index].iter().any(|(other, _)| addresses_equal(other, address)) {
+                return Err(UserInputError::InvalidParameters {
+                    parameter: "owner".to_string(),
+                    value: address.clone(),
+                    expected: "each owner once".to_string(),
+                }
+                .into());
+            }
+        }
+        let count = u8::try_from(owners.len()).unwrap_or(u8::MAX);
+
+        let account = LocalWallet::new(&mut OsRng);
+        let secret = Zeroizing::new(account.signer().to_bytes().to_vec());
+        let shares = ShamirService::split(&secret, threshold, count, "")?;
+
+        let owners = owners
+            .iter()
+            .zip(&shares)
+            .map(|((address, public_key), share)| {
+                Ok(MultisigOwner {
+                    address: to_checksum(&parse_address("owner", address)?, None),
+                    public_key: hex::encode(public_key.as_bytes()),
+                    share: seal(public_key, share)?,
+                })
+            })
+            .collect::<WalletResult<Vec<_>>>()?;
+
+        Ok(MultisigKeystore {
+            kind: settings::KEYSTORE_TYPE.to_string(),
+            version: settings::KEYSTORE_VERSION,
+            address: to_checksum(&account.address(), None),
+            threshold,
+            owners,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Proposal of `tx` from the multisig account, to be combined by `coordinator`. Fees are
+    /// part of what owners approve, so they must be set now.
+    pub fn propose(
+        multisig: &MultisigKeystore,
+        coordinator: &str,
+        tx: &UnsignedTransaction,
+        note: Option<&str>,
+    ) -> WalletResult<MultisigProposal> {
+        let coordinator = multisig.owner(coordinator).ok_or_else(|| not_an_owner("coordinator", coordinator))?;
+        if tx.needs_fees() {
+            return Err(UserInputError::MissingParameter {
+                parameter: "fees".to_string(),
+                hint: "owners approve the exact transaction, so set max_fee_per_gas and \
+                       max_priority_fee_per_gas (or gas_price for a legacy one) in the proposal"
+                    .to_string(),
+            }
+            .into());
+        }
+
+        Ok(MultisigProposal {
+            multisig: multisig.address.clone(),
+            coordinator: coordinator.address.clone(),
+            tx: tx.clone(),
+            sighash: format!("{:?}", TransactionService::build(tx)?.sighash()),
+            note: note.map(str::to_string),
+            created_at: Utc::now(),
+        })
+    }
+
+    /// `wallet`'s approval of `proposal`, with its share resealed to the coordinator
+    pub fn approve(
+        multisig: &MultisigKeystore,
+        proposal: &MultisigProposal,
+        wallet: &Wallet,
+    ) -> WalletResult<MultisigApproval> {
+        check_proposal(multisig, proposal)?;
+        let sighash = proposal.checked_sighash()?;
+        let owner = multisig.owner(wallet.address()).ok_or_else(|| not_an_owner("from_file", wallet.address()))?;
+        let coordinator = multisig
+            .owner(&proposal.coordinator)
+            .ok_or_else(|| not_an_owner("coordinator", &proposal.coordinator))?;
+
+        let share = open(&owner.share, &Self::companion_key(wallet)?)?;
+        let payload = SignedPayload::Message(proposal.message()?.into_bytes());
+        Ok(MultisigApproval {
+            owner: owner.address.clone(),
+            sighash,
+            signature: SigningService::sign(wallet, &payload)?,
+            share: seal(&public_key(&coordinator.public_key)?, &share)?,
+        })
+    }
+
+    /// Restore the account key from the threshold of `approvals` and sign the proposed
+    /// transaction; `coordinator` is the wallet the proposal names
+    pub fn combine(
+        multisig: &MultisigKeystore,
+        proposal: &MultisigProposal,
+        approvals: &[MultisigApproval],
+        coordinator: &Wallet,
+    ) -> WalletResult<SignedTransaction> {
+        check_proposal(multisig, proposal)?;
+        let sighash = proposal.checked_sighash()?;
+        if !addresses_equal(coordinator.address(), &proposal.coordinator) {
+            return Err(mismatch(format!(
+                "the proposal is to be combined by {}, not {}",
+                proposal.coordinator,
+                coordinator.address()
+            )));
+        }
+
+        let secret = Self::companion_key(coordinator)?;
+        let payload = SignedPayload::Message(proposal.message()?.into_bytes());
+        let mut approvers: Vec<&str> = Vec::new();
+        let mut shares = Vec::new();
+        for approval in approvals {
+            let owner = multisig.owner(&approval.owner).ok_or_else(|| not_an_owner("approval", &approval.owner))?;
+            if !approval.sighash.eq_ignore_ascii_case(&sighash) {
+                return Err(mismatch(format!("the approval by {} is for transaction {}", owner.address, approval.sighash)));
+            }
+            if !SigningService::verify(&payload, &approval.signature, &owner.address).is_ok_and(|v| v.valid) {
+                return Err(mismatch(format!("the approval by {} is not signed by that owner", owner.address)));
+            }
+            if approvers.contains(&owner.address.as_str()) {
+                continue;
+            }
+            approvers.push(&owner.address);
+            shares.push(open(&approval.share, &secret)?.to_string());
+        }
+        let shares = Zeroizing::new(shares);
+
+        if shares.len() < multisig.threshold as usize {
+            return Err(ValidationError::PreconditionFailed {
+                check: "multisig threshold".to_string(),
+                details: format!("{} of the {} approvals needed", shares.len(), multisig.threshold),
+            }
+            .into());
+        }
+        let restored = ShamirService::combine(&shares, "")?;
+        let account = LocalWallet::from_bytes(&restored).map_err(|e| CryptographicError::SigningFailed {
+            details: e.to_string(),
+        })?;
+        if !addresses_equal(&format!("{:?}", account.address()), &multisig.address) {
+            return Err(mismatch(format!("the approved shares do not restore the key of {}", multisig.address)));
+        }
+        TransactionService::sign_with(account, &proposal.tx)
+    }
+}
+
+fn check_proposal(multisig: &MultisigKeystore, proposal: &MultisigProposal) -> WalletResult<()> {
+    if !addresses_equal(&proposal.multisig, &multisig.address) {
+        return Err(mismatch(format!("the proposal is for {}, not {}", proposal.multisig, multisig.address)));
+    }
+    Ok(())
+}
+
+fn seal(public_key: &PublicKey, share: &str) -> WalletResult<String> {
+    let sealed = public_key
+        .seal(&mut OsRng, share.as_bytes())
+        .map_err(|e| CryptographicError::EncryptionFailed { details: e.to_string() })?;
+    Ok(hex::encode(sealed))
+}
+
+fn open(sealed_hex: &str, secret: &SecretKey) -> WalletResult<Zeroizing<String>> {
+    let failed = || CryptographicError::DecryptionFailed {
+        context: "the share is not sealed to this wallet's companion key".to_string(),
+    };
+    let sealed = hex::decode(sealed_hex.trim()).map_err(|_| failed())?;
+    let share = Zeroizing::new(secret.unseal(&sealed).map_err(|_| failed())?);
+    Ok(Zeroizing::new(String::from_utf8(share.to_vec()).map_err(|_| failed())?))
+}
+
+fn public_key(hex_key: &str) -> WalletResult<PublicKey> {
+    let bytes = hex::decode(hex_key.trim()).unwrap_or_default();
+    let bytes: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| mismatch(format!("owner public key {} is not 32 bytes of hex", hex_key)))?;
+    Ok(PublicKey::from(bytes))
+}
+
+fn not_an_owner(parameter: &str, address: &str) -> WalletError {
+    UserInputError::InvalidParameters {
+        parameter: parameter.to_string(),
+        value: address.to_string(),
+        expected: "one of the multisig's owners".to_string(),
+    }
+    .into()
+}
+
+fn mismatch(details: String) -> WalletError {
+    ValidationError::PreconditionFailed {
+        check: "multisig".to_string(),
+        details,
+    }
+    .into()
+}
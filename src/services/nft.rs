@@ -0,0 +1,153 @@
+use crate::errors::{UserInputError, ValidationError, WalletResult};
+use crate::services::rpc::RpcClient;
+use crate::services::transaction::encode_call;
+use ethers::abi::Token as AbiToken;
+use ethers::types::{Address, U256};
+use serde::Serialize;
+use std::str::FromStr;
+
+/// ERC-165 interface ids
+const ERC721_INTERFACE_ID: [u8; 4] = [0x80, 0xac, 0x58, 0xcd];
+const ERC1155_INTERFACE_ID: [u8; 4] = [0xd9, 0xb6, 0x7a, 0x26];
+
+/// NFT contract standard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NftStandard {
+    Erc721,
+    Erc1155,
+}
+
+impl FromStr for NftStandard {
+    type Err = crate::errors::WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "").as_str() {
+            "erc721" | "721" => Ok(Self::Erc721),
+            "erc1155" | "1155" => Ok(Self::Erc1155),
+            _ => Err(UserInputError::InvalidParameters {
+                parameter: "standard".to_string(),
+                value: s.to_string(),
+                expected: "erc721 or erc1155".to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// One NFT transfer, before it is turned into a transaction
+#[derive(Debug, Clone)]
+pub struct NftTransfer {
+    pub standard: NftStandard,
+    pub contract: Address,
+    pub from: Address,
+    pub to: Address,
+    pub token_id: U256,
+    /// Always 1 for ERC-721
+    pub amount: U256,
+}
+
+impl NftTransfer {
+    /// `safeTransferFrom` calldata for the transfer's standard
+    pub fn calldata(&self) -> Vec<u8> {
+        match self.standard {
+            NftStandard::Erc721 => encode_call(
+                "safeTransferFrom(address,address,uint256)",
+                &[
+                    AbiToken::Address(self.from),
+                    AbiToken::Address(self.to),
+                    AbiToken::Uint(self.token_id),
+                ],
+            ),
+            NftStandard::Erc1155 => encode_call(
+                "safeTransferFrom(address,address,uint256,uint256,bytes)",
+                &[
+                    AbiToken::Address(self.from),
+                    AbiToken::Address(self.to),
+                    AbiToken::Uint(self.token_id),
+                    AbiToken::Uint(self.amount),
+                    AbiToken::Bytes(Vec::new()),
+                ],
+            ),
+        }
+    }
+}
+
+pub struct NftService;
+
+impl NftService {
+    /// Detect the contract's standard through ERC-165 `supportsInterface`
+    pub async fn detect_standard(client: &RpcClient, contract: &str) -> WalletResult<NftStandard> {
+        if Self::supports_interface(client, contract, ERC721_INTERFACE_ID).await? {
+            return Ok(NftStandard::Erc721);
+        }
+        if Self::supports_interface(client, contract, ERC1155_INTERFACE_ID).await? {
+            return Ok(NftStandard::Erc1155);
+        }
+        Err(UserInputError::InvalidParameters {
+            parameter: "contract".to_string(),
+            value: contract.to_string(),
+            expected: "an ERC-721 or ERC-1155 contract (or pass --standard)".to_string(),
+        }
+        .into())
+    }
+
+    async fn supports_interface(client: &RpcClient, contract: &str, interface_id: [u8; 4]) -> WalletResult<bool> {
+        let data = encode_call(
+            "supportsInterface(bytes4)",
+            &[AbiToken::FixedBytes(interface_id.to_vec())],
+        );
+        // Contracts without ERC-165 revert; treat that as "not supported"
+        Ok(match client.eth_call(contract, &data).await {
+            Ok(ret) => ret.len() == 32 && ret[31] == 1,
+            Err(_) => false,
+        })
+    }
+
+    /// Make sure `transfer.from` holds the token (and enough of it, for ERC-1155) before signing
+    pub async fn check_ownership(client: &RpcClient, transfer: &NftTransfer) -> WalletResult<()> {
+        let contract = format!("{:?}", transfer.contract);
+        match transfer.standard {
+            NftStandard::Erc721 => {
+                let data = encode_call("ownerOf(uint256)", &[AbiToken::Uint(transfer.token_id)]);
+                let ret = client.eth_call(&contract, &data).await?;
+                let owner = (ret.len() == 32).then(|| Address::from_slice(&ret[12..]));
+                if owner != Some(transfer.from) {
+                    return Err(ValidationError::PreconditionFailed {
+                        check: "ownerOf".to_string(),
+                        details: format!(
+                            "token {} of {} is owned by {}, not the signing wallet {}",
+                            transfer.token_id,
+                            ethers::utils::to_checksum(&transfer.contract, None),
+                            owner.map_or_else(|| "nobody".to_string(), |o| ethers::utils::to_checksum(&o, None)),
+                            ethers::utils::to_checksum(&transfer.from, None),
+                        ),
+                    }
+                    .into());
+                }
+            }
+            NftStandard::Erc1155 => {
+                let data = encode_call(
+                    "balanceOf(address,uint256)",
+                    &[AbiToken::Address(transfer.from), AbiToken::Uint(transfer.token_id)],
+                );
+                let ret = client.eth_call(&contract, &data).await?;
+                let balance = if ret.len() == 32 { U256::from_big_endian(&ret) } else { U256::zero() };
+                if balance < transfer.amount {
+                    return Err(ValidationError::PreconditionFailed {
+                        check: "balanceOf".to_string(),
+                        details: format!(
+                            "signing wallet {} holds {} of token {}, cannot send {}",
+                            ethers::utils::to_checksum(&transfer.from, None),
+                            balance,
+                            transfer.token_id,
+                            transfer.amount,
+                        ),
+                    }
+                    .into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+//! Next nonce per address and chain, for signing offline where the chain cannot be asked.
+//! `sign-tx --nonce auto` signs with the tracked nonce, every signed transaction moves it past
+//! its own, and `nonce sync` reconciles it with the chain's pending transaction count.
+use crate::config::nonces as settings;
+use crate::errors::{FilesystemError, WalletResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackedNonce {
+    /// Nonce the next transaction is signed with
+    pub next: u64,
+    pub updated_at: DateTime<Utc>,
+    /// Last `nonce sync`, None while only signing has moved it
+    #[serde(default)]
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+/// Tracked nonces keyed by "<chain id>:<lowercase address>" and persisted as JSON
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NonceStore {
+    #[serde(skip)]
+    path: PathBuf,
+    pub nonces: BTreeMap<String, TrackedNonce>,
+}
+
+impl NonceStore {
+    pub fn default_path(state_dir: &Path) -> PathBuf {
+        state_dir.join(settings::NONCES_FILE_NAME)
+    }
+
+    /// Load the store at `path`, empty if the file does not exist yet
+    pub async fn load(path: &Path) -> WalletResult<Self> {
+        let mut store: Self = match tokio::fs::read_to_string(path).await {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| FilesystemError::InvalidFormat {
+                path: path.display().to_string(),
+                details: e.to_string(),
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e.into()),
+        };
+        store.path = path.to_path_buf();
+        Ok(store)
+    }
+
+    pub async fn save(&self) -> WalletResult<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                FilesystemError::DirectoryNotAccessible {
+                    path: parent.display().to_string(),
+                    details: e.to_string(),
+                }
+            })?;
+        }
+        crate::utils::write_atomic(&self.path, serde_json::to_string_pretty(self)?.as_bytes()).await
+    }
+
+    pub fn get(&self, address: &str, chain_id: u64) -> Option<&TrackedNonce> {
+        self.nonces.get(&key(address, chain_id))
+    }
+
+    /// Move the next nonce past `nonce`, which was just signed with; a lower one, e.g. a
+    /// replacement transaction, leaves it where it is
+    pub fn record_signed(&mut self, address: &str, chain_id: u64, nonce: u64) {
+        let now = Utc::now();
+        let tracked = self.nonces.entry(key(address, chain_id)).or_insert(TrackedNonce {
+            next: 0,
+            updated_at: now,
+            synced_at: None,
+        });
+        if nonce.saturating_add(1) > tracked.next {
+            tracked.next = nonce.saturating_add(1);
+            tracked.updated_at = now;
+        }
+    }
+
+    /// Reconcile with the chain's pending transaction count, returning the next nonce. Nonces
+    /// signed here but not on chain yet are kept; drop them with `reset` first.
+    pub fn sync(&mut self, address: &str, chain_id: u64, pending: u64) -> u64 {
+        let now = Utc::now();
+        let local = self.get(address, chain_id).map_or(0, |tracked| tracked.next);
+        let next = local.max(pending);
+        self.nonces.insert(
+            key(address, chain_id),
+            TrackedNonce {
+                next,
+                updated_at: now,
+                synced_at: Some(now),
+            },
+        );
+        next
+    }
+
+    /// Forget the nonces of `address` on `chain_id`, or on every chain, returning how many
+    pub fn reset(&mut self, address: &str, chain_id: Option<u64>) -> usize {
+        let before = self.nonces.len();
+        match chain_id {
+            Some(chain_id) => {
+                self.nonces.remove(&key(address, chain_id));
+            }
+            None => {
+                let suffix = format!(":{}", crate::utils::normalize_address(address));
+                self.nonces.retain(|key, _| !key.ends_with(&suffix));
+            }
+        }
+        before - self.nonces.len()
+    }
+}
+
+fn key(address: &str, chain_id: u64) -> String {
+    format!("{}:{}", chain_id, crate::utils::normalize_address(address))
+}
@@ -0,0 +1,125 @@
+//! `--out`: a command's stdout goes to a temp file created owner-only (0600) next to the
+//! destination, and the file is moved into place once the command has succeeded. Unlike shell
+//! redirection, a failed run leaves nothing behind and exported keys are never readable by others.
+use crate::config::fs::KEYSTORE_FILE_PERMISSIONS;
+use crate::errors::{FilesystemError, WalletError, WalletResult};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+pub struct OutputFile {
+    target: PathBuf,
+    temp: PathBuf,
+    file: File,
+    /// stdout as it was before the redirect, for logs and prompts
+    terminal: File,
+    finished: bool,
+}
+
+impl OutputFile {
+    /// Point stdout at a fresh temp file for `target`; an existing `target` is never replaced
+    #[cfg(unix)]
+    pub fn redirect(target: &Path) -> WalletResult<Self> {
+        use std::io::Write;
+        use std::os::fd::{AsFd, AsRawFd};
+        use std::os::unix::fs::OpenOptionsExt;
+
+        if target.exists() {
+            return Err(already_exists(target));
+        }
+        let temp = temp_path(target)?;
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(KEYSTORE_FILE_PERMISSIONS)
+            .open(&temp)?;
+        let terminal = File::from(std::io::stdout().as_fd().try_clone_to_owned()?);
+
+        std::io::stdout().flush()?;
+        // SAFETY: both descriptors are open for the duration of the call
+        if unsafe { libc::dup2(file.as_raw_fd(), libc::STDOUT_FILENO) } < 0 {
+            let err = std::io::Error::last_os_error();
+            let _ = std::fs::remove_file(&temp);
+            return Err(err.into());
+        }
+
+        Ok(Self {
+            target: target.to_path_buf(),
+            temp,
+            file,
+            terminal,
+            finished: false,
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn redirect(_target: &Path) -> WalletResult<Self> {
+        Err(WalletError::NotImplemented("--out needs a unix system".to_string()))
+    }
+
+    /// Where the user still sees text while stdout goes to the file
+    pub fn terminal(&self) -> &File {
+        &self.terminal
+    }
+
+    /// Sync the output and move it to the destination, restoring stdout
+    pub fn commit(mut self) -> WalletResult<()> {
+        use std::io::Write;
+
+        std::io::stdout().flush()?;
+        self.restore_stdout();
+        self.file.sync_all()?;
+
+        // A hard link fails instead of replacing a file that appeared in the meantime
+        match std::fs::hard_link(&self.temp, &self.target) {
+            Ok(()) => std::fs::remove_file(&self.temp)?,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return Err(already_exists(&self.target)),
+            // Filesystems without hard links still get an atomic rename
+            Err(_) => std::fs::rename(&self.temp, &self.target)?,
+        }
+        self.finished = true;
+
+        if let Some(dir) = self.target.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            let _ = File::open(dir).and_then(|dir| dir.sync_all());
+        }
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn restore_stdout(&self) {
+        use std::os::fd::AsRawFd;
+
+        // SAFETY: the saved terminal descriptor is owned by self and still open
+        unsafe {
+            libc::dup2(self.terminal.as_raw_fd(), libc::STDOUT_FILENO);
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn restore_stdout(&self) {}
+}
+
+impl Drop for OutputFile {
+    /// An output that was not committed is discarded
+    fn drop(&mut self) {
+        if !self.finished {
+            self.restore_stdout();
+            let _ = std::fs::remove_file(&self.temp);
+        }
+    }
+}
+
+fn temp_path(target: &Path) -> WalletResult<PathBuf> {
+    let file_name = target.file_name().ok_or_else(|| FilesystemError::InvalidFormat {
+        path: target.display().to_string(),
+        details: "not a file path".to_string(),
+    })?;
+    Ok(target.with_file_name(format!(".{}.{}.tmp", file_name.to_string_lossy(), std::process::id())))
+}
+
+fn already_exists(target: &Path) -> WalletError {
+    FilesystemError::FileExists {
+        path: target.display().to_string(),
+        suggestion: "choose another --out path or remove the file first".to_string(),
+    }
+    .into()
+}
@@ -0,0 +1,62 @@
+//! Random passwords and passphrases for new keystores, made by `genpass`. Characters and words
+//! are drawn from the OS RNG with uniform sampling, so the strength reported is exact rather
+//! than estimated: bits per pick times the number of picks.
+use crate::config::{self, genpass as settings};
+use crate::errors::{UserInputError, WalletResult};
+use crate::services::password_strength;
+use bip39::Language;
+use rand::{rngs::OsRng, Rng};
+use zeroize::Zeroizing;
+
+/// A generated secret with the bits of randomness it holds
+pub struct Generated {
+    pub value: Zeroizing<String>,
+    pub bits: f64,
+}
+
+/// `length` characters from letters, digits and symbols, leaving out quotes, backslashes and
+/// the characters shells use for pipes and redirection
+pub fn password(length: usize) -> WalletResult<Generated> {
+    check_range("--length", length, config::crypto::MIN_PASSWORD_LENGTH, config::crypto::MAX_PASSWORD_LENGTH)?;
+    let alphabet: Vec<char> = settings::PASSWORD_ALPHABET.chars().collect();
+    Ok(Generated {
+        value: draw(|| (0..length).map(|_| alphabet[OsRng.gen_range(0..alphabet.len())]).collect()),
+        bits: length as f64 * (alphabet.len() as f64).log2(),
+    })
+}
+
+/// `words` words of the BIP39 English wordlist, joined by `WORD_SEPARATOR`
+pub fn passphrase(words: usize) -> WalletResult<Generated> {
+    check_range("--words", words, settings::MIN_WORDS, settings::MAX_WORDS)?;
+    let list = Language::English.word_list();
+    Ok(Generated {
+        value: draw(|| {
+            let picked: Vec<&str> = (0..words).map(|_| list[OsRng.gen_range(0..list.len())]).collect();
+            picked.join(settings::WORD_SEPARATOR)
+        }),
+        bits: words as f64 * (list.len() as f64).log2(),
+    })
+}
+
+/// Draw until the result also passes the strength estimate `save` applies, which a draw of
+/// the allowed sizes fails only by rare chance
+fn draw(mut generate: impl FnMut() -> String) -> Zeroizing<String> {
+    loop {
+        let value = Zeroizing::new(generate());
+        if password_strength::estimate(&value).is_strong() {
+            return value;
+        }
+    }
+}
+
+fn check_range(parameter: &str, value: usize, min: usize, max: usize) -> WalletResult<()> {
+    if (min..=max).contains(&value) {
+        return Ok(());
+    }
+    Err(UserInputError::InvalidParameters {
+        parameter: parameter.to_string(),
+        value: value.to_string(),
+        expected: format!("a number from {} to {}", min, max),
+    }
+    .into())
+}
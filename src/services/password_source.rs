@@ -0,0 +1,89 @@
+//! `--password-file` and `--password-stdin`: passwords read from a file or a pipe instead of the
+//! terminal, so scripts and CI can save and unlock wallets without a TTY while the secret stays
+//! out of argv and the process environment.
+use crate::errors::{FilesystemError, UserInputError, WalletError, WalletResult};
+use std::collections::VecDeque;
+use std::io::BufRead;
+use std::path::Path;
+use tracing::warn;
+use zeroize::Zeroizing;
+
+enum Source {
+    /// Lines of the password file, read up front
+    File(VecDeque<Zeroizing<String>>),
+    /// Read a line at a time, so other prompts can take the lines in between
+    Stdin,
+}
+
+/// Each password prompt takes the next line; once the lines run out the last one answers
+/// the remaining prompts, so a single line serves both a password and its confirmation
+pub struct PasswordSource {
+    source: Source,
+    last: Option<Zeroizing<String>>,
+}
+
+impl PasswordSource {
+    pub fn from_file(path: &Path) -> WalletResult<Self> {
+        let contents = Zeroizing::new(std::fs::read_to_string(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => FilesystemError::FileNotFound {
+                path: path.display().to_string(),
+                director: path.parent().map(|p| p.display().to_string()).unwrap_or_default(),
+            }
+            .into(),
+            _ => WalletError::from(e),
+        })?);
+        warn_if_readable_by_others(path);
+
+        let lines: VecDeque<_> = contents.lines().map(|line| Zeroizing::new(line.to_string())).collect();
+        if lines.is_empty() {
+            return Err(empty("password-file"));
+        }
+        Ok(Self { source: Source::File(lines), last: None })
+    }
+
+    pub fn stdin() -> Self {
+        Self { source: Source::Stdin, last: None }
+    }
+
+    /// Answer to the next password prompt
+    pub fn next_password(&mut self) -> WalletResult<Zeroizing<String>> {
+        let line = match &mut self.source {
+            Source::File(lines) => lines.pop_front(),
+            Source::Stdin => {
+                let mut line = Zeroizing::new(String::new());
+                match std::io::stdin().lock().read_line(&mut line)? {
+                    0 => None,
+                    _ => Some(Zeroizing::new(line.trim_end_matches(['\r', '\n']).to_string())),
+                }
+            }
+        };
+        if let Some(line) = line {
+            self.last = Some(line);
+        }
+        self.last.clone().ok_or_else(|| empty("password-stdin"))
+    }
+}
+
+fn empty(parameter: &str) -> WalletError {
+    UserInputError::InvalidParameters {
+        parameter: parameter.to_string(),
+        value: String::new(),
+        expected: "the password on the first line".to_string(),
+    }
+    .into()
+}
+
+#[cfg(unix)]
+fn warn_if_readable_by_others(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+
+    if let Ok(metadata) = std::fs::metadata(path) {
+        let mode = metadata.permissions().mode() & 0o777;
+        if mode & 0o077 != 0 {
+            warn!("Password file {} is readable by other users ({:o}), consider chmod 600", path.display(), mode);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn warn_if_readable_by_others(_path: &Path) {}
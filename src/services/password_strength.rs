@@ -0,0 +1,213 @@
+//! Password strength estimate in the spirit of zxcvbn. The password is covered by the cheapest
+//! run of guessable patterns (common passwords, alphabet and number sequences, keyboard runs,
+//! repeated characters, years) and characters guessed one by one, and its strength is the
+//! number of bits an attacker who knows these patterns needs to guess that cover.
+use crate::config::crypto as settings;
+
+/// Most common passwords and wallet words first, lowercase; a match costs its rank
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456", "password", "12345678", "qwerty", "123456789", "12345", "1234", "111111", "1234567",
+    "dragon", "123123", "baseball", "abc123", "football", "monkey", "letmein", "696969", "shadow",
+    "master", "666666", "qwertyuiop", "123321", "mustang", "1234567890", "michael", "654321",
+    "superman", "1qaz2wsx", "7777777", "121212", "000000", "qazwsx", "123qwe", "killer",
+    "trustno1", "jordan", "jennifer", "zxcvbnm", "asdfgh", "hunter", "buster", "soccer",
+    "harley", "batman", "andrew", "tigger", "sunshine", "iloveyou", "fuckme", "2000", "charlie",
+    "robert", "thomas", "hockey", "ranger", "daniel", "starwars", "klaster", "112233", "george",
+    "computer", "michelle", "jessica", "pepper", "1111", "zxcvbn", "555555", "11111111",
+    "131313", "freedom", "777777", "pass", "maggie", "159753", "aaaaaa", "ginger", "princess",
+    "joshua", "cheese", "amanda", "summer", "love", "ashley", "nicole", "chelsea", "biteme",
+    "matthew", "access", "yankees", "987654321", "dallas", "austin", "thunder", "taylor",
+    "matrix", "admin", "welcome", "login", "passw0rd", "secret", "hello", "whatever", "qwerty123",
+    "wallet", "bitcoin", "ethereum", "crypto", "satoshi", "nakamoto", "metamask", "ledger",
+    "trezor", "hodl", "moon", "lambo", "blockchain", "mnemonic", "seed", "private",
+];
+
+const KEYBOARD_ROWS: &[&str] = &["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// Longer patterns are covered as several matches, which keeps long passwords fast to check
+const MAX_MATCH_LENGTH: usize = 32;
+
+/// What made part of a password easy to guess
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weakness {
+    CommonPassword,
+    Sequence,
+    KeyboardRun,
+    Repeat,
+    Year,
+}
+
+impl Weakness {
+    /// The requirement a password with this weakness misses
+    pub fn requirement(self) -> &'static str {
+        match self {
+            Weakness::CommonPassword => "No common passwords or wallet words",
+            Weakness::Sequence => "No sequences such as abc or 123",
+            Weakness::KeyboardRun => "No keyboard runs such as qwerty",
+            Weakness::Repeat => "No character repeated three or more times",
+            Weakness::Year => "No years",
+        }
+    }
+}
+
+/// Estimated strength of a password
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswordStrength {
+    /// log2 of the guesses needed
+    pub bits: f64,
+    /// Patterns in the cheapest cover, in the order they appear
+    pub weaknesses: Vec<Weakness>,
+}
+
+impl PasswordStrength {
+    pub fn is_strong(&self) -> bool {
+        self.bits >= settings::MIN_PASSWORD_STRENGTH_BITS
+    }
+}
+
+struct Match {
+    start: usize,
+    end: usize,
+    bits: f64,
+    weakness: Weakness,
+}
+
+pub fn estimate(password: &str) -> PasswordStrength {
+    let chars: Vec<char> = password.chars().collect();
+    let matches = find_matches(&chars);
+    let per_char = cardinality(&chars).log2();
+
+    // best[i] is the cheapest cover of the first i characters, with the match ending it
+    let mut best: Vec<(f64, Option<usize>)> = vec![(0.0, None); chars.len() + 1];
+    for end in 1..=chars.len() {
+        best[end] = (best[end - 1].0 + per_char, None);
+        for (index, m) in matches.iter().enumerate().filter(|(_, m)| m.end == end) {
+            // One bit more for the choice of pattern, so a match never ties plain characters
+            let bits = best[m.start].0 + m.bits + 1.0;
+            if bits < best[end].0 {
+                best[end] = (bits, Some(index));
+            }
+        }
+    }
+
+    let mut weaknesses = Vec::new();
+    let mut end = chars.len();
+    while end > 0 {
+        match best[end].1 {
+            Some(index) => {
+                weaknesses.push(matches[index].weakness);
+                end = matches[index].start;
+            }
+            None => end -= 1,
+        }
+    }
+    weaknesses.reverse();
+    weaknesses.dedup();
+    PasswordStrength {
+        bits: best[chars.len()].0,
+        weaknesses,
+    }
+}
+
+/// A character class and how many characters it holds
+type CharClass = (fn(&char) -> bool, f64);
+
+/// Size of the alphabet a brute force over this password's character classes searches
+fn cardinality(chars: &[char]) -> f64 {
+    let classes: [CharClass; 5] = [
+        (char::is_ascii_lowercase, 26.0),
+        (char::is_ascii_uppercase, 26.0),
+        (char::is_ascii_digit, 10.0),
+        (char::is_ascii_punctuation, 33.0),
+        (|c: &char| !c.is_ascii(), 100.0),
+    ];
+    let size: f64 = classes.iter().filter(|(class, _)| chars.iter().any(class)).map(|(_, size)| size).sum();
+    size.max(10.0)
+}
+
+fn find_matches(chars: &[char]) -> Vec<Match> {
+    let lower: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+    let unleet: Vec<char> = lower.iter().map(|&c| unleet(c)).collect();
+    let mut matches = Vec::new();
+
+    for start in 0..chars.len() {
+        for end in start + 1..=chars.len().min(start + MAX_MATCH_LENGTH) {
+            let len = end - start;
+            let plain: String = lower[start..end].iter().collect();
+            let unleeted: String = unleet[start..end].iter().collect();
+            let rank = COMMON_PASSWORDS.iter().position(|w| *w == plain || *w == unleeted);
+            if let Some(rank) = rank {
+                let substituted = lower[start..end].iter().zip(&unleet[start..end]).filter(|(a, b)| a != b).count();
+                let bits = ((rank + 1) as f64).log2() + case_bits(&chars[start..end]) + substituted as f64;
+                matches.push(Match { start, end, bits, weakness: Weakness::CommonPassword });
+            }
+            if len < 3 {
+                continue;
+            }
+            if let Some(bits) = sequence_bits(&lower[start..end]) {
+                matches.push(Match { start, end, bits, weakness: Weakness::Sequence });
+            }
+            if len >= 4 && is_keyboard_run(&plain) {
+                let bits = ((KEYBOARD_ROWS.len() * 10 * 2) as f64).log2() + (len as f64).log2();
+                matches.push(Match { start, end, bits, weakness: Weakness::KeyboardRun });
+            }
+            if chars[start..end].iter().all(|c| *c == chars[start]) {
+                let bits = cardinality(&chars[start..=start]).log2() + (len as f64).log2();
+                matches.push(Match { start, end, bits, weakness: Weakness::Repeat });
+            }
+            if len == 4 && plain.parse::<u32>().is_ok_and(|year| (1900..2040).contains(&year)) {
+                matches.push(Match { start, end, bits: 140f64.log2(), weakness: Weakness::Year });
+            }
+        }
+    }
+    matches
+}
+
+/// Bits for the capitalisation of a dictionary match: none for lowercase, one for a capital
+/// first letter or all capitals, otherwise one per capital
+fn case_bits(chars: &[char]) -> f64 {
+    let upper = chars.iter().filter(|c| c.is_ascii_uppercase()).count();
+    let letters = chars.iter().filter(|c| c.is_ascii_alphabetic()).count();
+    match upper {
+        0 => 0.0,
+        _ if upper == letters || (upper == 1 && chars[0].is_ascii_uppercase()) => 1.0,
+        _ => upper as f64,
+    }
+}
+
+/// Bits for `chars` as a run of letters or digits with a constant step of one, e.g. abc or 987
+fn sequence_bits(chars: &[char]) -> Option<f64> {
+    let same_class = chars.iter().all(char::is_ascii_lowercase) || chars.iter().all(char::is_ascii_digit);
+    let step = chars[1] as i32 - chars[0] as i32;
+    if !same_class || step.abs() != 1 || chars.windows(2).any(|pair| pair[1] as i32 - pair[0] as i32 != step) {
+        return None;
+    }
+    let start = match chars[0] {
+        'a' | 'z' | '0' | '1' | '9' => 2.0,
+        c if c.is_ascii_digit() => 10f64.log2(),
+        _ => 26f64.log2(),
+    };
+    let descending = if step < 0 { 1.0 } else { 0.0 };
+    Some(start + (chars.len() as f64).log2() + descending)
+}
+
+fn is_keyboard_run(run: &str) -> bool {
+    let reversed: String = run.chars().rev().collect();
+    KEYBOARD_ROWS.iter().any(|row| row.contains(run) || row.contains(reversed.as_str()))
+}
+
+/// The letter a common substitution stands for
+fn unleet(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '8' => 'b',
+        '3' => 'e',
+        '6' | '9' => 'g',
+        '1' | '!' | '|' => 'i',
+        '0' => 'o',
+        '5' | '$' => 's',
+        '7' | '+' => 't',
+        '2' => 'z',
+        _ => c,
+    }
+}
@@ -0,0 +1,275 @@
+//! EIP-2612 `permit` on the token itself. The owner signs an EIP-712 `Permit` off chain and
+//! anyone can submit it with `permit(owner, spender, value, deadline, v, r, s)`, which sets the
+//! allowance without an `approve` transaction. Unlike Permit2 (see `services::permit2`) every
+//! token has its own domain and nonces, so both are read from the token or given offline.
+#[cfg(feature = "native")]
+use crate::errors::ValidationError;
+use crate::errors::{CryptographicError, UserInputError, WalletResult};
+use crate::models::Wallet;
+#[cfg(feature = "native")]
+use crate::services::rpc::RpcClient;
+use crate::services::transaction::{encode_call, parse_quantity};
+use ethers::abi::{encode, Token as AbiToken};
+use ethers::signers::Signer;
+use ethers::types::{Address, H256, U256};
+use ethers::utils::{keccak256, to_checksum};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// keccak256("Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)")
+const PERMIT_TYPEHASH: &str = "6e71edae12b1b97f4d1f60370fef10105fa2faae0126114a169c64845d6126c9";
+
+/// keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)")
+const DOMAIN_TYPEHASH: &str = "8b73c3c69bb8fe3d512ecc4cf759cc79239f7b179b0ffacaa9a75d522b39400f";
+
+/// The `name` and `version` of a token's EIP-712 domain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermitDomain {
+    pub name: String,
+    pub version: String,
+}
+
+impl PermitDomain {
+    /// Domain separator of `token` on `chain_id` under this name and version
+    pub fn separator(&self, chain_id: u64, token: Address) -> [u8; 32] {
+        keccak256(encode(&[
+            AbiToken::FixedBytes(typehash(DOMAIN_TYPEHASH)),
+            AbiToken::FixedBytes(keccak256(self.name.as_bytes()).to_vec()),
+            AbiToken::FixedBytes(keccak256(self.version.as_bytes()).to_vec()),
+            AbiToken::Uint(chain_id.into()),
+            AbiToken::Address(token),
+        ]))
+    }
+}
+
+/// What a token reports about its permits, from `TokenPermitService::read`
+#[derive(Debug, Clone)]
+pub struct TokenDomain {
+    /// `DOMAIN_SEPARATOR()`
+    pub separator: [u8; 32],
+    /// `name()` and `version()`, when the token has them
+    pub name: Option<String>,
+    pub version: Option<String>,
+    /// `nonces(owner)`
+    pub nonce: U256,
+}
+
+/// An EIP-2612 `Permit` of `owner`'s tokens to `spender`
+#[derive(Debug, Clone)]
+pub struct TokenPermit {
+    pub token: Address,
+    pub chain_id: u64,
+    pub owner: Address,
+    pub spender: Address,
+    /// Allowance in the token's base units
+    pub value: U256,
+    /// The token's `nonces(owner)`
+    pub nonce: U256,
+    /// Unix time after which `permit` reverts
+    pub deadline: u64,
+    /// The token's `DOMAIN_SEPARATOR()`, what the signature is checked against
+    pub separator: [u8; 32],
+    /// Name and version, for the EIP-712 document; only used when they produce `separator`
+    pub domain: Option<PermitDomain>,
+}
+
+impl TokenPermit {
+    pub fn struct_hash(&self) -> [u8; 32] {
+        keccak256(encode(&[
+            AbiToken::FixedBytes(typehash(PERMIT_TYPEHASH)),
+            AbiToken::Address(self.owner),
+            AbiToken::Address(self.spender),
+            AbiToken::Uint(self.value),
+            AbiToken::Uint(self.nonce),
+            AbiToken::Uint(self.deadline.into()),
+        ]))
+    }
+
+    /// `0x1901 || domainSeparator || structHash`, hashed: what `permit` recovers the owner from
+    pub fn digest(&self) -> [u8; 32] {
+        let mut payload = Vec::with_capacity(66);
+        payload.extend_from_slice(&[0x19, 0x01]);
+        payload.extend_from_slice(&self.separator);
+        payload.extend_from_slice(&self.struct_hash());
+        keccak256(payload)
+    }
+
+    /// True when `domain` is known and gives the token's domain separator; tokens with other
+    /// domain fields (no version, a salt) can still be signed, but not described as typed data
+    pub fn domain_matches(&self) -> bool {
+        self.domain.as_ref().is_some_and(|d| d.separator(self.chain_id, self.token) == self.separator)
+    }
+
+    /// EIP-712 document as wallets and `verify-signature --typed-data` expect it
+    pub fn typed_data(&self) -> Option<Value> {
+        let domain = self.domain.as_ref().filter(|_| self.domain_matches())?;
+        Some(json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "version", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" },
+                ],
+                "Permit": [
+                    { "name": "owner", "type": "address" },
+                    { "name": "spender", "type": "address" },
+                    { "name": "value", "type": "uint256" },
+                    { "name": "nonce", "type": "uint256" },
+                    { "name": "deadline", "type": "uint256" },
+                ],
+            },
+            "primaryType": "Permit",
+            "domain": {
+                "name": domain.name,
+                "version": domain.version,
+                "chainId": self.chain_id,
+                "verifyingContract": to_checksum(&self.token, None),
+            },
+            "message": {
+                "owner": to_checksum(&self.owner, None),
+                "spender": to_checksum(&self.spender, None),
+                "value": self.value.to_string(),
+                "nonce": self.nonce.to_string(),
+                "deadline": self.deadline.to_string(),
+            },
+        }))
+    }
+}
+
+/// A signed permit, split the way `permit` takes the signature
+#[derive(Debug, Clone, Serialize)]
+pub struct SignedTokenPermit {
+    pub owner: String,
+    pub spender: String,
+    pub token: String,
+    pub value: String,
+    pub nonce: String,
+    pub deadline: u64,
+    /// EIP-712 digest that was signed
+    pub hash: String,
+    /// r || s || v, v in {27, 28}
+    pub signature: String,
+    pub v: u64,
+    pub r: String,
+    pub s: String,
+    /// Calldata of `permit(owner, spender, value, deadline, v, r, s)` on the token
+    pub calldata: String,
+    pub typed_data: Option<Value>,
+}
+
+/// Allowance in base units; "max" is the largest uint256, the unlimited allowance
+pub fn parse_value(value: &str) -> WalletResult<U256> {
+    if value.eq_ignore_ascii_case("max") {
+        return Ok(U256::MAX);
+    }
+    parse_quantity("value", value)
+}
+
+/// `DOMAIN_SEPARATOR()` as given on the command line
+pub fn parse_separator(value: &str) -> WalletResult<[u8; 32]> {
+    value
+        .trim()
+        .parse::<H256>()
+        .map(|h| h.0)
+        .map_err(|_| {
+            UserInputError::InvalidParameters {
+                parameter: "domain_separator".to_string(),
+                value: value.to_string(),
+                expected: "32 bytes of hex, as the token's DOMAIN_SEPARATOR() returns".to_string(),
+            }
+            .into()
+        })
+}
+
+pub struct TokenPermitService;
+
+impl TokenPermitService {
+    /// Domain separator, name, version and `owner`'s nonce as `token` reports them. A token
+    /// without `DOMAIN_SEPARATOR()` or `nonces(address)` has no EIP-2612 permit; one whose
+    /// `PERMIT_TYPEHASH()` is another struct (DAI's `allowed`/`expiry` permit) is refused too.
+    #[cfg(feature = "native")]
+    pub async fn read(client: &RpcClient, token: Address, owner: Address) -> WalletResult<TokenDomain> {
+        let to = format!("{:?}", token);
+        let word = |ret: WalletResult<Vec<u8>>| ret.ok().filter(|r| r.len() == 32);
+
+        let separator = word(client.eth_call(&to, &encode_call("DOMAIN_SEPARATOR()", &[])).await)
+            .ok_or_else(|| no_permit(token, "it has no DOMAIN_SEPARATOR()"))?;
+        let nonce = word(client.eth_call(&to, &encode_call("nonces(address)", &[AbiToken::Address(owner)])).await)
+            .ok_or_else(|| no_permit(token, "it has no nonces(address)"))?;
+        if let Some(permit_typehash) = word(client.eth_call(&to, &encode_call("PERMIT_TYPEHASH()", &[])).await) {
+            if permit_typehash != typehash(PERMIT_TYPEHASH) {
+                return Err(no_permit(token, "its PERMIT_TYPEHASH() is not the EIP-2612 Permit struct"));
+            }
+        }
+        let string = |ret: WalletResult<Vec<u8>>| ret.ok().and_then(|r| crate::services::erc20::decode_string(&r));
+
+        Ok(TokenDomain {
+            separator: separator.try_into().expect("checked to be 32 bytes"),
+            name: string(client.eth_call(&to, &encode_call("name()", &[])).await),
+            version: string(client.eth_call(&to, &encode_call("version()", &[])).await),
+            nonce: U256::from_big_endian(&nonce),
+        })
+    }
+
+    pub fn sign(wallet: &Wallet, permit: &TokenPermit) -> WalletResult<SignedTokenPermit> {
+        let signer = wallet.signer()?;
+        if signer.address() != permit.owner {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "owner".to_string(),
+                value: to_checksum(&permit.owner, None),
+                expected: format!("the address of the signing wallet, {}", to_checksum(&signer.address(), None)),
+            }
+            .into());
+        }
+
+        let hash = H256::from(permit.digest());
+        let signature = signer.sign_hash(hash).map_err(|e| CryptographicError::SigningFailed {
+            details: e.to_string(),
+        })?;
+        let (mut r, mut s) = ([0u8; 32], [0u8; 32]);
+        signature.r.to_big_endian(&mut r);
+        signature.s.to_big_endian(&mut s);
+        let calldata = encode_call(
+            "permit(address,address,uint256,uint256,uint8,bytes32,bytes32)",
+            &[
+                AbiToken::Address(permit.owner),
+                AbiToken::Address(permit.spender),
+                AbiToken::Uint(permit.value),
+                AbiToken::Uint(permit.deadline.into()),
+                AbiToken::Uint(signature.v.into()),
+                AbiToken::FixedBytes(r.to_vec()),
+                AbiToken::FixedBytes(s.to_vec()),
+            ],
+        );
+
+        Ok(SignedTokenPermit {
+            owner: to_checksum(&permit.owner, None),
+            spender: to_checksum(&permit.spender, None),
+            token: to_checksum(&permit.token, None),
+            value: permit.value.to_string(),
+            nonce: permit.nonce.to_string(),
+            deadline: permit.deadline,
+            hash: format!("{:?}", hash),
+            signature: format!("0x{}", signature),
+            v: signature.v,
+            r: format!("0x{}", hex::encode(r)),
+            s: format!("0x{}", hex::encode(s)),
+            calldata: format!("0x{}", hex::encode(calldata)),
+            typed_data: permit.typed_data(),
+        })
+    }
+}
+
+#[cfg(feature = "native")]
+fn no_permit(token: Address, reason: &str) -> crate::errors::WalletError {
+    ValidationError::PreconditionFailed {
+        check: "EIP-2612".to_string(),
+        details: format!("{} does not support EIP-2612 permits: {}", to_checksum(&token, None), reason),
+    }
+    .into()
+}
+
+fn typehash(hex_str: &str) -> Vec<u8> {
+    hex::decode(hex_str).expect("typehash constants are valid hex")
+}
@@ -0,0 +1,210 @@
+//! Uniswap Permit2 `AllowanceTransfer` permits. The owner signs an EIP-712 `PermitSingle` or
+//! `PermitBatch` off chain and the dapp submits it to the canonical Permit2 contract, which
+//! replaces the per-token EIP-2612 `permit` that many ERC-20 tokens never implemented.
+use crate::config::permit2 as settings;
+#[cfg(feature = "native")]
+use crate::errors::ValidationError;
+use crate::errors::{UserInputError, WalletResult};
+use crate::models::Wallet;
+#[cfg(feature = "native")]
+use crate::services::rpc::RpcClient;
+use crate::services::signing::{SignedPayload, SigningService};
+#[cfg(feature = "native")]
+use crate::services::transaction::encode_call;
+use crate::services::transaction::{parse_address, parse_quantity};
+#[cfg(feature = "native")]
+use ethers::abi::Token as AbiToken;
+use ethers::types::{Address, U256};
+use ethers::utils::to_checksum;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// One token's allowance inside a permit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermitDetails {
+    pub token: Address,
+    /// uint160, in the token's base units
+    pub amount: U256,
+    /// Unix time the allowance lapses, uint48
+    pub expiration: u64,
+    /// Permit2's nonce for (owner, token, spender), uint48
+    pub nonce: u64,
+}
+
+/// A `PermitSingle` (one token) or `PermitBatch` (several) for `spender`
+#[derive(Debug, Clone)]
+pub struct Permit {
+    pub chain_id: u64,
+    pub spender: Address,
+    /// Unix time after which the signature can no longer be submitted
+    pub sig_deadline: u64,
+    pub details: Vec<PermitDetails>,
+    /// Sign a `PermitBatch` even for a single token
+    pub batch: bool,
+}
+
+impl Permit {
+    pub fn primary_type(&self) -> &'static str {
+        if self.batch {
+            "PermitBatch"
+        } else {
+            "PermitSingle"
+        }
+    }
+
+    /// EIP-712 document as dapps and `verify-signature --typed-data` expect it
+    pub fn typed_data(&self) -> Value {
+        let details: Vec<Value> = self
+            .details
+            .iter()
+            .map(|d| {
+                json!({
+                    "token": to_checksum(&d.token, None),
+                    "amount": d.amount.to_string(),
+                    "expiration": d.expiration.to_string(),
+                    "nonce": d.nonce.to_string(),
+                })
+            })
+            .collect();
+        let details = if self.batch { Value::Array(details) } else { details[0].clone() };
+        let details_type = if self.batch { "PermitDetails[]" } else { "PermitDetails" };
+
+        json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" },
+                ],
+                (self.primary_type()): [
+                    { "name": "details", "type": details_type },
+                    { "name": "spender", "type": "address" },
+                    { "name": "sigDeadline", "type": "uint256" },
+                ],
+                "PermitDetails": [
+                    { "name": "token", "type": "address" },
+                    { "name": "amount", "type": "uint160" },
+                    { "name": "expiration", "type": "uint48" },
+                    { "name": "nonce", "type": "uint48" },
+                ],
+            },
+            "primaryType": self.primary_type(),
+            "domain": {
+                "name": settings::DOMAIN_NAME,
+                "chainId": self.chain_id,
+                "verifyingContract": settings::ADDRESS,
+            },
+            "message": {
+                "details": details,
+                "spender": to_checksum(&self.spender, None),
+                "sigDeadline": self.sig_deadline.to_string(),
+            },
+        })
+    }
+
+    pub fn payload(&self) -> WalletResult<SignedPayload> {
+        SignedPayload::typed_data_from_json(&self.typed_data().to_string())
+    }
+}
+
+/// A signed permit, ready to hand to the dapp
+#[derive(Debug, Clone, Serialize)]
+pub struct SignedPermit {
+    pub signer: String,
+    pub spender: String,
+    /// EIP-712 digest that was signed
+    pub hash: String,
+    pub signature: String,
+    pub typed_data: Value,
+}
+
+/// Allowance in base units; "max" is the largest uint160, Permit2's unlimited allowance
+pub fn parse_amount(value: &str) -> WalletResult<U256> {
+    let max = (U256::one() << 160) - 1;
+    if value.eq_ignore_ascii_case("max") {
+        return Ok(max);
+    }
+    let amount = parse_quantity("amount", value)?;
+    if amount > max {
+        return Err(UserInputError::InvalidParameters {
+            parameter: "amount".to_string(),
+            value: value.to_string(),
+            expected: "at most 2^160 - 1, or \"max\"".to_string(),
+        }
+        .into());
+    }
+    Ok(amount)
+}
+
+/// Unix time that fits Permit2's uint48 fields
+pub fn check_timestamp(parameter: &str, value: u64) -> WalletResult<u64> {
+    if value >= 1 << 48 {
+        return Err(UserInputError::InvalidParameters {
+            parameter: parameter.to_string(),
+            value: value.to_string(),
+            expected: "a unix timestamp below 2^48".to_string(),
+        }
+        .into());
+    }
+    Ok(value)
+}
+
+/// `<token>:<amount>[:<nonce>]` as `wallet permit2 batch --permit` takes it
+pub fn parse_permit_spec(spec: &str) -> WalletResult<(Address, U256, Option<u64>)> {
+    let invalid = || UserInputError::InvalidParameters {
+        parameter: "permit".to_string(),
+        value: spec.to_string(),
+        expected: "<token>:<amount>[:<nonce>], e.g. 0xA0b8...eB48:1000000".to_string(),
+    };
+    let parts: Vec<&str> = spec.split(':').map(str::trim).collect();
+    let (token, amount, nonce) = match parts[..] {
+        [token, amount] => (token, amount, None),
+        [token, amount, nonce] => (token, amount, Some(nonce.parse::<u64>().map_err(|_| invalid())?)),
+        _ => return Err(invalid().into()),
+    };
+    Ok((parse_address("token", token)?, parse_amount(amount)?, nonce))
+}
+
+pub struct Permit2Service;
+
+impl Permit2Service {
+    /// Next nonce Permit2 expects for `owner`'s allowance of `token` to `spender`
+    #[cfg(feature = "native")]
+    pub async fn nonce(client: &RpcClient, owner: Address, token: Address, spender: Address) -> WalletResult<u64> {
+        let data = encode_call(
+            "allowance(address,address,address)",
+            &[AbiToken::Address(owner), AbiToken::Address(token), AbiToken::Address(spender)],
+        );
+        // (uint160 amount, uint48 expiration, uint48 nonce)
+        let ret = client.eth_call(settings::ADDRESS, &data).await?;
+        if ret.len() < 96 {
+            return Err(ValidationError::PreconditionFailed {
+                check: "Permit2".to_string(),
+                details: format!("no Permit2 contract answers at {} on this chain", settings::ADDRESS),
+            }
+            .into());
+        }
+        Ok(U256::from_big_endian(&ret[64..96]).low_u64())
+    }
+
+    pub fn sign(wallet: &Wallet, permit: &Permit) -> WalletResult<SignedPermit> {
+        if permit.details.is_empty() || permit.details.len() > settings::MAX_BATCH {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "permit".to_string(),
+                value: permit.details.len().to_string(),
+                expected: format!("1 to {} tokens", settings::MAX_BATCH),
+            }
+            .into());
+        }
+
+        let payload = permit.payload()?;
+        let signature = SigningService::sign(wallet, &payload)?;
+        Ok(SignedPermit {
+            signer: to_checksum(&parse_address("address", wallet.address())?, None),
+            spender: to_checksum(&permit.spender, None),
+            hash: format!("{:?}", payload.hash()?),
+            signature,
+            typed_data: permit.typed_data(),
+        })
+    }
+}
@@ -0,0 +1,357 @@
+//! Fiat prices of native currencies behind one `PriceProvider` trait, so exports work the same
+//! against CoinGecko, CryptoCompare or a local file of fixed rates for offline machines.
+use crate::config::prices as settings;
+use crate::errors::{UserInputError, WalletResult};
+#[cfg(feature = "native")]
+use crate::errors::{FilesystemError, NetworkError};
+#[cfg(feature = "native")]
+use crate::services::cache::RpcCache;
+#[cfg(feature = "native")]
+use crate::WalletConfig;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+#[cfg(feature = "native")]
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "native")]
+use serde_json::{json, Value};
+#[cfg(feature = "native")]
+use std::collections::HashMap;
+#[cfg(feature = "native")]
+use std::path::Path;
+use std::str::FromStr;
+#[cfg(feature = "native")]
+use std::time::Duration;
+#[cfg(feature = "native")]
+use tokio::sync::Mutex;
+
+/// Where prices come from, selected with `[prices] source` or `--price-source`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceSource {
+    #[default]
+    CoinGecko,
+    CryptoCompare,
+    /// Rates read from `[prices] rates_file`, no network access
+    File,
+}
+
+impl FromStr for PriceSource {
+    type Err = crate::errors::WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "coingecko" => Ok(Self::CoinGecko),
+            "cryptocompare" => Ok(Self::CryptoCompare),
+            "file" => Ok(Self::File),
+            _ => Err(UserInputError::InvalidParameters {
+                parameter: "price_source".to_string(),
+                value: s.to_string(),
+                expected: "coingecko, cryptocompare or file".to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// A source of daily fiat prices
+#[async_trait]
+pub trait PriceProvider: Send + Sync {
+    /// Short name used in cache keys and messages
+    fn name(&self) -> &'static str;
+
+    /// Price of one `symbol` (e.g. "ETH") in `fiat` (lowercase ISO code) on `date`, None if the
+    /// source has no price for that coin or day
+    async fn price_on(&self, symbol: &str, date: NaiveDate, fiat: &str) -> WalletResult<Option<f64>>;
+}
+
+/// Provider configured in `config`; network sources are cached under `state_path` and refused offline
+#[cfg(feature = "native")]
+pub async fn from_config(config: &WalletConfig) -> WalletResult<Box<dyn PriceProvider>> {
+    let provider: Box<dyn PriceProvider> = match config.price_source {
+        PriceSource::File => {
+            let path = config.price_rates_file.as_ref().ok_or_else(|| UserInputError::MissingParameter {
+                parameter: "rates_file".to_string(),
+                hint: "set [prices] rates_file or pass --rates-file".to_string(),
+            })?;
+            return Ok(Box::new(FixedRates::load(path).await?));
+        }
+        _ if config.offline => {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "offline".to_string(),
+                value: "true".to_string(),
+                expected: "network access for --fiat prices, or fixed rates from --rates-file".to_string(),
+            }
+            .into());
+        }
+        PriceSource::CoinGecko => Box::new(CoinGecko::from_config(config)),
+        PriceSource::CryptoCompare => Box::new(CryptoCompare::from_config(config)),
+    };
+    let cache = RpcCache::open(&RpcCache::default_path(&config.state_path)).await;
+    Ok(Box::new(CachedPrices::new(provider, cache)))
+}
+
+/// Whether the native currency of `chain_id` is worth anything; testnet coins are not
+pub fn is_priced_chain(chain_id: u64) -> bool {
+    settings::PRICED_CHAIN_IDS.contains(&chain_id)
+}
+
+/// Remembers answers of `inner` in the RPC cache, so repeated exports of the same period do not
+/// hit the price API again
+#[cfg(feature = "native")]
+pub struct CachedPrices {
+    inner: Box<dyn PriceProvider>,
+    cache: Mutex<RpcCache>,
+}
+
+#[cfg(feature = "native")]
+impl CachedPrices {
+    pub fn new(inner: Box<dyn PriceProvider>, cache: RpcCache) -> Self {
+        Self {
+            inner,
+            cache: Mutex::new(cache),
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl PriceProvider for CachedPrices {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    async fn price_on(&self, symbol: &str, date: NaiveDate, fiat: &str) -> WalletResult<Option<f64>> {
+        let params = json!([symbol, fiat, date.to_string()]);
+        if let Some(cached) = self.cache.lock().await.get(self.name(), "price", &params) {
+            return Ok(cached.as_f64());
+        }
+
+        let price = self.inner.price_on(symbol, date, fiat).await?;
+        let ttl = if date < Utc::now().date_naive() {
+            settings::PAST_DAY_TTL_SECS
+        } else {
+            settings::TODAY_TTL_SECS
+        };
+        let mut cache = self.cache.lock().await;
+        cache.insert(self.name(), "price", &params, json!(price), Duration::from_secs(ttl));
+        // A cache that cannot be written only costs another lookup next time
+        let _ = cache.persist().await;
+        Ok(price)
+    }
+}
+
+#[cfg(feature = "native")]
+fn request_error(endpoint: &str, e: reqwest::Error) -> crate::errors::WalletError {
+    NetworkError::ConnectivityFailure {
+        endpoint: endpoint.to_string(),
+        details: e.to_string(),
+    }
+    .into()
+}
+
+#[cfg(feature = "native")]
+async fn json_body(endpoint: &str, response: reqwest::Response) -> WalletResult<Value> {
+    if !response.status().is_success() {
+        return Err(NetworkError::InvalidResponse {
+            endpoint: endpoint.to_string(),
+            details: format!("HTTP {}", response.status()),
+        }
+        .into());
+    }
+    response.json().await.map_err(|e| {
+        NetworkError::InvalidResponse {
+            endpoint: endpoint.to_string(),
+            details: e.to_string(),
+        }
+        .into()
+    })
+}
+
+/// CoinGecko `coins/{id}/history`, one request per coin and day
+#[cfg(feature = "native")]
+pub struct CoinGecko {
+    base_url: String,
+    api_key: Option<String>,
+    http: reqwest::Client,
+    timeout: Duration,
+}
+
+#[cfg(feature = "native")]
+impl CoinGecko {
+    pub fn from_config(config: &WalletConfig) -> Self {
+        let base_url = config.price_api_url.as_deref().unwrap_or(settings::DEFAULT_COINGECKO_API_URL);
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: config.price_api_key.clone(),
+            http: reqwest::Client::new(),
+            timeout: Duration::from_secs(config.rpc_timeout_secs),
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl PriceProvider for CoinGecko {
+    fn name(&self) -> &'static str {
+        "coingecko"
+    }
+
+    async fn price_on(&self, symbol: &str, date: NaiveDate, fiat: &str) -> WalletResult<Option<f64>> {
+        let Some((_, id)) = settings::COINGECKO_IDS.iter().find(|(s, _)| s.eq_ignore_ascii_case(symbol)) else {
+            return Ok(None);
+        };
+
+        let endpoint = format!("{}/coins/{}/history", self.base_url, id);
+        let mut request = self
+            .http
+            .get(&endpoint)
+            .query(&[("date", date.format("%d-%m-%Y").to_string()), ("localization", "false".to_string())])
+            .timeout(self.timeout);
+        if let Some(key) = &self.api_key {
+            request = request.header("x-cg-demo-api-key", key);
+        }
+        let response = request.send().await.map_err(|e| request_error(&endpoint, e))?;
+        let body = json_body(&endpoint, response).await?;
+
+        // Days before a coin was listed have no market data
+        Ok(body["market_data"]["current_price"][fiat.to_lowercase()].as_f64())
+    }
+}
+
+/// CryptoCompare `pricehistorical`, the daily close of the requested day
+#[cfg(feature = "native")]
+pub struct CryptoCompare {
+    base_url: String,
+    api_key: Option<String>,
+    http: reqwest::Client,
+    timeout: Duration,
+}
+
+#[cfg(feature = "native")]
+impl CryptoCompare {
+    pub fn from_config(config: &WalletConfig) -> Self {
+        let base_url = config.price_api_url.as_deref().unwrap_or(settings::DEFAULT_CRYPTOCOMPARE_API_URL);
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: config.price_api_key.clone(),
+            http: reqwest::Client::new(),
+            timeout: Duration::from_secs(config.rpc_timeout_secs),
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl PriceProvider for CryptoCompare {
+    fn name(&self) -> &'static str {
+        "cryptocompare"
+    }
+
+    async fn price_on(&self, symbol: &str, date: NaiveDate, fiat: &str) -> WalletResult<Option<f64>> {
+        let (symbol, fiat) = (symbol.to_uppercase(), fiat.to_uppercase());
+        let Some(close) = date.and_hms_opt(23, 59, 59) else {
+            return Ok(None);
+        };
+
+        let endpoint = format!("{}/pricehistorical", self.base_url);
+        let mut request = self
+            .http
+            .get(&endpoint)
+            .query(&[("fsym", symbol.as_str()), ("tsyms", fiat.as_str())])
+            .query(&[("ts", Utc.from_utc_datetime(&close).timestamp())])
+            .timeout(self.timeout);
+        if let Some(key) = &self.api_key {
+            request = request.header("authorization", format!("Apikey {}", key));
+        }
+        let response = request.send().await.map_err(|e| request_error(&endpoint, e))?;
+        let body = json_body(&endpoint, response).await?;
+
+        // Errors come back as HTTP 200 with {"Response": "Error", "Message": ...}
+        if body["Response"] == "Error" {
+            let message = body["Message"].as_str().unwrap_or("unknown error");
+            // An unknown coin is not an outage
+            if message.contains("no data for") {
+                return Ok(None);
+            }
+            return Err(NetworkError::InvalidResponse {
+                endpoint,
+                details: message.to_string(),
+            }
+            .into());
+        }
+        // Unlisted days come back as 0
+        Ok(body[symbol.as_str()][fiat.as_str()].as_f64().filter(|price| *price > 0.0))
+    }
+}
+
+/// Rates from a local CSV of `date,asset,fiat,price` rows, for machines without network access.
+/// A date of `*` applies to every day that has no row of its own:
+///
+/// ```text
+/// date,asset,fiat,price
+/// *,ETH,usd,3000
+/// 2025-01-15,ETH,usd,3310.42
+/// ```
+#[cfg(feature = "native")]
+#[derive(Debug, Default)]
+pub struct FixedRates {
+    /// (symbol, fiat, day) to price, symbol uppercase and fiat lowercase; None for `*`
+    rates: HashMap<(String, String, Option<NaiveDate>), f64>,
+}
+
+#[cfg(feature = "native")]
+impl FixedRates {
+    pub async fn load(path: &Path) -> WalletResult<Self> {
+        let data = tokio::fs::read_to_string(path).await?;
+        Self::parse(&data).map_err(|details| {
+            FilesystemError::InvalidFormat {
+                path: path.display().to_string(),
+                details,
+            }
+            .into()
+        })
+    }
+
+    fn parse(data: &str) -> Result<Self, String> {
+        let mut rates = HashMap::new();
+        for (number, line) in data.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("date,") {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [date, symbol, fiat, price] = fields[..] else {
+                return Err(format!("line {}: expected date,asset,fiat,price", number + 1));
+            };
+            let date = match date {
+                "*" => None,
+                date => Some(
+                    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                        .map_err(|_| format!("line {}: date must be YYYY-MM-DD or *", number + 1))?,
+                ),
+            };
+            let price = price
+                .parse::<f64>()
+                .ok()
+                .filter(|price| price.is_finite() && *price >= 0.0)
+                .ok_or_else(|| format!("line {}: invalid price {:?}", number + 1, price))?;
+            rates.insert((symbol.to_uppercase(), fiat.to_lowercase(), date), price);
+        }
+        Ok(Self { rates })
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl PriceProvider for FixedRates {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn price_on(&self, symbol: &str, date: NaiveDate, fiat: &str) -> WalletResult<Option<f64>> {
+        let (symbol, fiat) = (symbol.to_uppercase(), fiat.to_lowercase());
+        let rate = |day| self.rates.get(&(symbol.clone(), fiat.clone(), day)).copied();
+        Ok(rate(Some(date)).or_else(|| rate(None)))
+    }
+}
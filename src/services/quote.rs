@@ -0,0 +1,207 @@
+use crate::config;
+use crate::errors::{NetworkError, UserInputError, WalletResult};
+use crate::models::TokenRegistry;
+use crate::WalletConfig;
+use ethers::types::U256;
+use ethers::utils::{format_units, parse_units};
+use serde::Serialize;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Supported aggregator API flavors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum QuoteApi {
+    #[serde(rename = "0x")]
+    ZeroEx,
+    #[serde(rename = "1inch")]
+    OneInch,
+}
+
+impl QuoteApi {
+    pub fn default_url(&self) -> &'static str {
+        match self {
+            Self::ZeroEx => config::quote::ZEROEX_API_URL,
+            Self::OneInch => config::quote::ONEINCH_API_URL,
+        }
+    }
+}
+
+impl FromStr for QuoteApi {
+    type Err = crate::errors::WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "0x" | "zeroex" => Ok(Self::ZeroEx),
+            "1inch" | "oneinch" => Ok(Self::OneInch),
+            _ => Err(UserInputError::InvalidParameters {
+                parameter: "aggregator".to_string(),
+                value: s.to_string(),
+                expected: "0x or 1inch".to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// One side of a quote, resolved to an address and decimals
+#[derive(Debug, Clone, Serialize)]
+pub struct QuoteToken {
+    pub symbol: String,
+    pub address: String,
+    pub decimals: u8,
+}
+
+impl QuoteToken {
+    /// Resolve the native currency symbol or a registered token on `chain_id`
+    pub fn resolve(registry: &TokenRegistry, query: &str, chain_id: u64) -> WalletResult<Self> {
+        if query.eq_ignore_ascii_case(config::quote::NATIVE_SYMBOL) {
+            return Ok(Self {
+                symbol: config::quote::NATIVE_SYMBOL.to_string(),
+                address: config::quote::NATIVE_TOKEN_ADDRESS.to_string(),
+                decimals: 18,
+            });
+        }
+        let token = registry.find(query, chain_id)?;
+        Ok(Self {
+            symbol: token.symbol.clone(),
+            address: token.address.clone(),
+            decimals: token.decimals,
+        })
+    }
+}
+
+/// Indicative price from an aggregator, never executed
+#[derive(Debug, Clone, Serialize)]
+pub struct Quote {
+    pub api: QuoteApi,
+    pub chain_id: u64,
+    pub sell: QuoteToken,
+    pub buy: QuoteToken,
+    /// Base units, decimal strings
+    pub sell_amount: String,
+    pub buy_amount: String,
+}
+
+impl Quote {
+    /// Buy tokens received per sell token, in display units
+    pub fn price(&self) -> f64 {
+        let sell = display_amount(&self.sell_amount, self.sell.decimals).parse::<f64>().unwrap_or(0.0);
+        let buy = display_amount(&self.buy_amount, self.buy.decimals).parse::<f64>().unwrap_or(0.0);
+        if sell == 0.0 { 0.0 } else { buy / sell }
+    }
+}
+
+/// Client for 0x / 1inch compatible price endpoints
+pub struct QuoteClient {
+    api: QuoteApi,
+    url: String,
+    api_key: Option<String>,
+    http: reqwest::Client,
+    timeout: Duration,
+}
+
+impl QuoteClient {
+    pub fn new(api: QuoteApi, url: Option<&str>, api_key: Option<String>) -> Self {
+        Self {
+            api,
+            url: url.unwrap_or(api.default_url()).to_string(),
+            api_key,
+            http: reqwest::Client::new(),
+            timeout: Duration::from_secs(config::rpc::DEFAULT_TIMEOUT_SECS),
+        }
+    }
+
+    pub fn from_config(config: &WalletConfig, api: QuoteApi, url: Option<&str>) -> WalletResult<Self> {
+        let mut client = Self::new(api, url.or(config.quote_api_url.as_deref()), config.quote_api_key.clone());
+        config.require_network(&client.url)?;
+        client.timeout = Duration::from_secs(config.rpc_timeout_secs);
+        Ok(client)
+    }
+
+    /// Quote selling `amount` (display units) of `sell` for `buy` on `chain_id`
+    pub async fn quote(&self, chain_id: u64, sell: QuoteToken, buy: QuoteToken, amount: &str) -> WalletResult<Quote> {
+        let sell_amount: U256 = parse_units(amount, sell.decimals as u32)
+            .map_err(|_| UserInputError::InvalidParameters {
+                parameter: "amount".to_string(),
+                value: amount.to_string(),
+                expected: format!("a decimal amount with at most {} decimals", sell.decimals),
+            })?
+            .into();
+        let sell_amount = sell_amount.to_string();
+        let chain_id_str = chain_id.to_string();
+
+        let url = self.url.replace("{chain_id}", &chain_id_str);
+        let mut request = match self.api {
+            QuoteApi::ZeroEx => self
+                .http
+                .get(&url)
+                .query(&[
+                    ("chainId", chain_id_str.as_str()),
+                    ("sellToken", sell.address.as_str()),
+                    ("buyToken", buy.address.as_str()),
+                    ("sellAmount", sell_amount.as_str()),
+                ])
+                .header("0x-version", "v2"),
+            QuoteApi::OneInch => self.http.get(&url).query(&[
+                ("src", sell.address.as_str()),
+                ("dst", buy.address.as_str()),
+                ("amount", sell_amount.as_str()),
+            ]),
+        };
+        if let Some(key) = &self.api_key {
+            request = match self.api {
+                QuoteApi::ZeroEx => request.header("0x-api-key", key),
+                QuoteApi::OneInch => request.bearer_auth(key),
+            };
+        }
+
+        let response = request
+            .timeout(self.timeout)
+            .send()
+            .await
+            .map_err(|e| NetworkError::ConnectivityFailure {
+                endpoint: url.clone(),
+                details: e.to_string(),
+            })?;
+
+        let status = response.status();
+        let body: serde_json::Value = response.json().await.map_err(|e| NetworkError::InvalidResponse {
+            endpoint: url.clone(),
+            details: e.to_string(),
+        })?;
+        if !status.is_success() {
+            return Err(NetworkError::InvalidResponse {
+                endpoint: url,
+                details: format!("HTTP {}: {}", status, body),
+            }
+            .into());
+        }
+
+        // 0x answers with `buyAmount`, 1inch with `dstAmount`
+        let buy_amount = body["buyAmount"]
+            .as_str()
+            .or_else(|| body["dstAmount"].as_str())
+            .ok_or_else(|| NetworkError::InvalidResponse {
+                endpoint: url.clone(),
+                details: format!("no buy amount in quote: {}", body),
+            })?
+            .to_string();
+
+        Ok(Quote {
+            api: self.api,
+            chain_id,
+            sell,
+            buy,
+            sell_amount,
+            buy_amount,
+        })
+    }
+}
+
+/// Base units to a decimal string in token units
+pub fn display_amount(base_units: &str, decimals: u8) -> String {
+    U256::from_dec_str(base_units)
+        .ok()
+        .and_then(|v| format_units(v, decimals as u32).ok())
+        .unwrap_or_else(|| base_units.to_string())
+}
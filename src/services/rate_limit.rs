@@ -0,0 +1,120 @@
+use crate::errors::{AuthenticationError, WalletResult};
+use crate::WalletConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+const MINUTE: Duration = Duration::from_secs(60);
+const HOUR: Duration = Duration::from_secs(3_600);
+
+/// Maximum signing operations per window; `None` leaves the window unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SigningLimits {
+    pub per_minute: Option<u32>,
+    pub per_hour: Option<u32>,
+}
+
+impl SigningLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.per_minute.is_none() && self.per_hour.is_none()
+    }
+}
+
+/// Sliding-window limits on signing, per caller identity and across all callers.
+/// Meant to be shared by every request a long-running signer serves.
+#[derive(Debug, Default)]
+pub struct SigningRateLimiter {
+    identity_limits: SigningLimits,
+    global_limits: SigningLimits,
+    state: Mutex<LimiterState>,
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    per_identity: HashMap<String, VecDeque<Instant>>,
+    global: VecDeque<Instant>,
+}
+
+impl SigningRateLimiter {
+    pub fn new(identity_limits: SigningLimits, global_limits: SigningLimits) -> Self {
+        Self {
+            identity_limits,
+            global_limits,
+            state: Mutex::new(LimiterState::default()),
+        }
+    }
+
+    pub fn from_config(config: &WalletConfig) -> Self {
+        Self::new(config.signing_limits, config.global_signing_limits)
+    }
+
+    /// Count one signing by `identity`, or refuse it with AUTH_004 when a limit is reached.
+    /// Refused attempts are not counted and are written to the audit log.
+    pub fn acquire(&self, identity: &str) -> WalletResult<()> {
+        self.acquire_at(identity, Instant::now())
+    }
+
+    fn acquire_at(&self, identity: &str, now: Instant) -> WalletResult<()> {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let LimiterState { per_identity, global } = &mut *state;
+        let history = per_identity.entry(identity.to_string()).or_default();
+        prune(history, now);
+        prune(global, now);
+
+        let refused = check(history, &self.identity_limits, now)
+            .map(|(limit, window, retry_after)| (identity.to_string(), limit, window, retry_after))
+            .or_else(|| {
+                check(global, &self.global_limits, now)
+                    .map(|(limit, window, retry_after)| ("all identities".to_string(), limit, window, retry_after))
+            });
+
+        if let Some((scope, limit, window, retry_after)) = refused {
+            warn!(
+                target: "audit",
+                identity,
+                scope = scope.as_str(),
+                limit,
+                window,
+                retry_after_ms = retry_after.as_millis() as u64,
+                "signing refused by rate limit"
+            );
+            return Err(AuthenticationError::SigningRateLimited {
+                scope,
+                limit,
+                window: window.to_string(),
+                retry_after,
+            }
+            .into());
+        }
+
+        history.push_back(now);
+        global.push_back(now);
+        Ok(())
+    }
+}
+
+/// Drop timestamps older than the longest window
+fn prune(history: &mut VecDeque<Instant>, now: Instant) {
+    while history.front().is_some_and(|t| now.duration_since(*t) >= HOUR) {
+        history.pop_front();
+    }
+}
+
+/// The first exhausted window as (limit, window name, time until a slot frees up)
+fn check(history: &VecDeque<Instant>, limits: &SigningLimits, now: Instant) -> Option<(u32, &'static str, Duration)> {
+    [(limits.per_minute, MINUTE, "minute"), (limits.per_hour, HOUR, "hour")]
+        .into_iter()
+        .find_map(|(limit, span, name)| {
+            let limit = limit?;
+            let recent: Vec<&Instant> = history.iter().filter(|t| now.duration_since(**t) < span).collect();
+            (recent.len() >= limit as usize).then(|| {
+                // The slot frees once the oldest counted signing leaves the window
+                let oldest = recent.len() - limit as usize;
+                let retry_after = recent.get(oldest).map_or(span, |t| span - now.duration_since(**t));
+                (limit, name, retry_after)
+            })
+        })
+}
@@ -0,0 +1,198 @@
+//! Decoding of signed raw transactions for `decode-tx`, to review what is about to be
+//! broadcast. ethers decodes legacy, EIP-2930 and EIP-1559 transactions; EIP-4844 blob
+//! transactions, in either their canonical or network form, are decoded here.
+use crate::errors::{CryptographicError, UserInputError, WalletError, WalletResult};
+use crate::services::transaction::parse_bytes;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip2930::AccessList;
+use ethers::types::{Address, Bytes, Signature, H256, U256};
+use ethers::utils::rlp::{Rlp, RlpStream};
+use ethers::utils::{keccak256, to_checksum};
+use serde::Serialize;
+
+const BLOB_TX_TYPE: u8 = 3;
+/// Fields of a blob transaction before the signature
+const BLOB_TX_UNSIGNED_FIELDS: usize = 11;
+/// Half the secp256k1 group order; EIP-2 rejects signatures with a higher s
+const SECP256K1_HALF_N: &str = "7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0";
+
+/// A signed transaction taken apart; quantities are decimal strings
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedTransaction {
+    /// EIP-2718 type: 0 legacy, 1 EIP-2930, 2 EIP-1559, 3 EIP-4844
+    #[serde(rename = "type")]
+    pub tx_type: u8,
+    pub hash: String,
+    /// Recovered from the signature
+    pub from: String,
+    /// None for a legacy transaction without EIP-155 replay protection
+    pub chain_id: Option<u64>,
+    pub nonce: String,
+    /// None for contract creation
+    pub to: Option<String>,
+    pub value: String,
+    pub gas: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_blob_gas: Option<String>,
+    pub data: String,
+    #[serde(skip_serializing_if = "is_empty")]
+    pub access_list: AccessList,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blob_versioned_hashes: Vec<String>,
+    /// Blobs carried along in the network form of a blob transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blobs: Option<usize>,
+    pub v: u64,
+    pub r: String,
+    pub s: String,
+}
+
+impl DecodedTransaction {
+    pub fn type_name(&self) -> &'static str {
+        match self.tx_type {
+            0 => "legacy",
+            1 => "EIP-2930",
+            2 => "EIP-1559",
+            _ => "EIP-4844",
+        }
+    }
+
+    /// Reasons not to broadcast this transaction on chain `expected`
+    pub fn warnings(&self, expected: u64) -> Vec<String> {
+        let mut warnings = Vec::new();
+        match self.chain_id {
+            None => warnings.push("no EIP-155 replay protection: valid on every chain".to_string()),
+            Some(chain_id) if chain_id != expected => {
+                warnings.push(format!("signed for chain {}, not chain {}", chain_id, expected))
+            }
+            Some(_) => {}
+        }
+        let s = U256::from_dec_str(&self.s).unwrap_or_default();
+        if s > U256::from_str_radix(SECP256K1_HALF_N, 16).unwrap_or_default() {
+            warnings.push("signature s is in the upper half of the curve order, nodes reject it (EIP-2)".to_string());
+        }
+        warnings
+    }
+}
+
+/// Decode `raw`, a `0x` hex signed transaction as `sign-tx` prints it
+pub fn decode(raw: &str) -> WalletResult<DecodedTransaction> {
+    let bytes = parse_bytes("raw", raw.trim())?;
+    match bytes.first() {
+        None => Err(invalid(raw, "empty input")),
+        Some(&BLOB_TX_TYPE) => decode_blob(raw, &bytes),
+        Some(&first) => {
+            let (typed, signature) =
+                TypedTransaction::decode_signed(&Rlp::new(&bytes)).map_err(|e| invalid(raw, e))?;
+            let from = recover(&signature, typed.sighash())?;
+            let (gas_price, max_fee_per_gas, max_priority_fee_per_gas) = match &typed {
+                TypedTransaction::Eip1559(tx) => (None, tx.max_fee_per_gas, tx.max_priority_fee_per_gas),
+                other => (other.gas_price(), None, None),
+            };
+            let quantity = |value: Option<&U256>| value.copied().unwrap_or_default().to_string();
+
+            Ok(DecodedTransaction {
+                tx_type: if first >= 0xc0 { 0 } else { first },
+                hash: format!("0x{}", hex::encode(keccak256(&bytes))),
+                from,
+                chain_id: typed.chain_id().map(|id| id.as_u64()),
+                nonce: quantity(typed.nonce()),
+                to: typed.to_addr().map(|to| to_checksum(to, None)),
+                value: quantity(typed.value()),
+                gas: quantity(typed.gas()),
+                gas_price: gas_price.map(|fee| fee.to_string()),
+                max_fee_per_gas: max_fee_per_gas.map(|fee| fee.to_string()),
+                max_priority_fee_per_gas: max_priority_fee_per_gas.map(|fee| fee.to_string()),
+                max_fee_per_blob_gas: None,
+                data: format!("0x{}", hex::encode(typed.data().cloned().unwrap_or_default())),
+                access_list: typed.access_list().cloned().unwrap_or_default(),
+                blob_versioned_hashes: Vec::new(),
+                blobs: None,
+                v: signature.v,
+                r: signature.r.to_string(),
+                s: signature.s.to_string(),
+            })
+        }
+    }
+}
+
+/// `0x03 || rlp([chain_id, nonce, max_priority_fee_per_gas, max_fee_per_gas, gas, to, value,
+/// data, access_list, max_fee_per_blob_gas, blob_versioned_hashes, y_parity, r, s])`, or the
+/// network form that wraps that list with its blobs, commitments and proofs
+fn decode_blob(raw: &str, bytes: &[u8]) -> WalletResult<DecodedTransaction> {
+    let fail = |e: ethers::utils::rlp::DecoderError| invalid(raw, e);
+    let outer = Rlp::new(&bytes[1..]);
+    let (tx, blobs) = if outer.at(0).map_err(fail)?.is_list() {
+        (outer.at(0).map_err(fail)?, Some(outer.at(1).map_err(fail)?.item_count().map_err(fail)?))
+    } else {
+        (outer, None)
+    };
+    if tx.item_count().map_err(fail)? != BLOB_TX_UNSIGNED_FIELDS + 3 {
+        return Err(invalid(raw, "a blob transaction has 14 fields"));
+    }
+
+    let mut unsigned = RlpStream::new_list(BLOB_TX_UNSIGNED_FIELDS);
+    for index in 0..BLOB_TX_UNSIGNED_FIELDS {
+        unsigned.append_raw(tx.at(index).map_err(fail)?.as_raw(), 1);
+    }
+    let sighash = H256(keccak256([&[BLOB_TX_TYPE][..], &unsigned.out()[..]].concat()));
+    let signature = Signature {
+        v: tx.val_at(11).map_err(fail)?,
+        r: tx.val_at(12).map_err(fail)?,
+        s: tx.val_at(13).map_err(fail)?,
+    };
+    let quantity = |index: usize| tx.val_at::<U256>(index).map(|value| value.to_string()).map_err(fail);
+    let to: Address = tx.val_at(5).map_err(fail)?;
+    let data = Bytes::from(tx.val_at::<Vec<u8>>(7).map_err(fail)?);
+    let hashes: Vec<H256> = tx.list_at(10).map_err(fail)?;
+
+    Ok(DecodedTransaction {
+        tx_type: BLOB_TX_TYPE,
+        hash: format!("0x{}", hex::encode(keccak256([&[BLOB_TX_TYPE][..], tx.as_raw()].concat()))),
+        from: recover(&signature, sighash)?,
+        chain_id: Some(tx.val_at(0).map_err(fail)?),
+        nonce: quantity(1)?,
+        to: Some(to_checksum(&to, None)),
+        value: quantity(6)?,
+        gas: quantity(4)?,
+        gas_price: None,
+        max_fee_per_gas: Some(quantity(3)?),
+        max_priority_fee_per_gas: Some(quantity(2)?),
+        max_fee_per_blob_gas: Some(quantity(9)?),
+        data: format!("0x{}", hex::encode(data)),
+        access_list: tx.val_at(8).map_err(fail)?,
+        blob_versioned_hashes: hashes.iter().map(|hash| format!("{:?}", hash)).collect(),
+        blobs,
+        v: signature.v,
+        r: signature.r.to_string(),
+        s: signature.s.to_string(),
+    })
+}
+
+fn is_empty(access_list: &AccessList) -> bool {
+    access_list.0.is_empty()
+}
+
+fn recover(signature: &Signature, sighash: H256) -> WalletResult<String> {
+    let from = signature.recover(sighash).map_err(|e| CryptographicError::SigningFailed {
+        details: format!("signature recovery failed: {}", e),
+    })?;
+    Ok(to_checksum(&from, None))
+}
+
+fn invalid(raw: &str, details: impl std::fmt::Display) -> WalletError {
+    // Blob transactions run to hundreds of kilobytes; the start identifies the input
+    let shown: String = raw.trim().chars().take(24).collect();
+    UserInputError::InvalidParameters {
+        parameter: "raw".to_string(),
+        value: if shown.len() < raw.trim().len() { format!("{}...", shown) } else { shown },
+        expected: format!("a signed legacy, EIP-2930, EIP-1559 or EIP-4844 transaction ({})", details),
+    }
+    .into()
+}
@@ -0,0 +1,402 @@
+use crate::config;
+use crate::errors::{NetworkError, WalletError, WalletResult};
+use crate::services::cache::{self, RpcCache};
+use crate::WalletConfig;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+/// A single JSON-RPC call, before an id is assigned
+#[derive(Debug, Clone)]
+pub struct RpcRequest {
+    pub method: String,
+    pub params: Value,
+}
+
+impl RpcRequest {
+    pub fn new(method: &str, params: Value) -> Self {
+        Self {
+            method: method.to_string(),
+            params,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RpcEnvelope<'a> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: &'a Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    id: Option<u64>,
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<RpcErrorBody>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcErrorBody {
+    code: i64,
+    message: String,
+}
+
+/// How failed RPC round trips are retried
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one
+    pub max_attempts: u32,
+    /// Backoff before the first retry, doubled on every attempt
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Randomize each backoff in [0, backoff] ("full jitter")
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: config::rpc::DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: Duration::from_millis(config::rpc::DEFAULT_BACKOFF_MS),
+            max_backoff: Duration::from_millis(config::rpc::MAX_BACKOFF_MS),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &WalletConfig) -> Self {
+        Self {
+            max_attempts: config.rpc_max_attempts.max(1),
+            initial_backoff: Duration::from_millis(config.rpc_backoff_ms),
+            max_backoff: Duration::from_millis(config::rpc::MAX_BACKOFF_MS),
+            jitter: config.rpc_jitter,
+        }
+    }
+
+    /// Delay before retry number `attempt` (1-based)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+
+        if self.jitter && !exp.is_zero() {
+            Duration::from_millis(rand::thread_rng().gen_range(0..=exp.as_millis() as u64))
+        } else {
+            exp
+        }
+    }
+
+    /// Only transport-level failures are worth retrying, JSON-RPC errors are final
+    fn is_retryable(err: &WalletError) -> bool {
+        matches!(
+            err,
+            WalletError::Network(
+                NetworkError::ConnectivityFailure { .. }
+                    | NetworkError::RequestTimeout { .. }
+                    | NetworkError::RateLimitExceeded { .. }
+            )
+        )
+    }
+}
+
+/// JSON-RPC 2.0 client over HTTP with request batching
+pub struct RpcClient {
+    endpoint: String,
+    http: reqwest::Client,
+    next_id: AtomicU64,
+    max_batch_size: usize,
+    timeout: Duration,
+    retry: RetryPolicy,
+    /// Chain id and shared cache for idempotent reads
+    cache: Option<(u64, Arc<Mutex<RpcCache>>)>,
+}
+
+impl RpcClient {
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            http: reqwest::Client::new(),
+            next_id: AtomicU64::new(1),
+            max_batch_size: config::rpc::MAX_BATCH_SIZE,
+            timeout: Duration::from_secs(config::rpc::DEFAULT_TIMEOUT_SECS),
+            retry: RetryPolicy::default(),
+            cache: None,
+        }
+    }
+
+    /// Client honoring the timeout and retry settings of `config`, refused under `--offline`
+    pub fn from_config(endpoint: &str, config: &WalletConfig) -> WalletResult<Self> {
+        config.require_network(endpoint)?;
+        Ok(Self::new(endpoint)
+            .with_timeout(Duration::from_secs(config.rpc_timeout_secs))
+            .with_retry_policy(RetryPolicy::from_config(config)))
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Serve idempotent reads for chain `chain_id` from `cache`
+    pub fn with_cache(mut self, chain_id: u64, cache: Arc<Mutex<RpcCache>>) -> Self {
+        self.cache = Some((chain_id, cache));
+        self
+    }
+
+    pub fn with_max_batch_size(mut self, size: usize) -> Self {
+        self.max_batch_size = size.max(1);
+        self
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Issue a single call
+    pub async fn call(&self, method: &str, params: Value) -> WalletResult<Value> {
+        let mut results = self.batch(vec![RpcRequest::new(method, params)]).await?;
+        results.pop().unwrap_or_else(|| {
+            Err(NetworkError::InvalidResponse {
+                endpoint: self.endpoint.clone(),
+                details: "empty batch response".to_string(),
+            }
+            .into())
+        })
+    }
+
+    /// `eth_call` against `to` at the latest block, returning the raw return data
+    pub async fn eth_call(&self, to: &str, data: &[u8]) -> WalletResult<Vec<u8>> {
+        let result = self
+            .call(
+                "eth_call",
+                json!([{ "to": to, "data": format!("0x{}", hex::encode(data)) }, "latest"]),
+            )
+            .await?;
+        let hex_str = result.as_str().unwrap_or("0x");
+        hex::decode(hex_str.strip_prefix("0x").unwrap_or(hex_str)).map_err(|e| {
+            NetworkError::InvalidResponse {
+                endpoint: self.endpoint.clone(),
+                details: format!("eth_call returned invalid hex: {}", e),
+            }
+            .into()
+        })
+    }
+
+    /// Issue a call whose result may be cached for `ttl`, e.g. token decimals or ENS lookups
+    pub async fn call_cached(&self, method: &str, params: Value, ttl: Duration) -> WalletResult<Value> {
+        if let Some(value) = self.cache_get(method, &params).await {
+            return Ok(value);
+        }
+
+        let value = self.call(method, params.clone()).await?;
+        self.cache_put(&[(method, &params, &value, ttl)]).await;
+        Ok(value)
+    }
+
+    /// Issue many calls in as few HTTP round trips as possible.
+    /// Results are returned in request order; each entry fails independently.
+    /// Methods listed in `config::cache::CACHEABLE_METHODS` are answered from the cache when fresh.
+    pub async fn batch(&self, requests: Vec<RpcRequest>) -> WalletResult<Vec<WalletResult<Value>>> {
+        let mut results: Vec<Option<WalletResult<Value>>> = Vec::with_capacity(requests.len());
+        let mut pending = Vec::new();
+        for (index, request) in requests.iter().enumerate() {
+            let hit = match cache::default_ttl(&request.method) {
+                Some(_) => self.cache_get(&request.method, &request.params).await,
+                None => None,
+            };
+            if hit.is_none() {
+                pending.push(index);
+            }
+            results.push(hit.map(Ok));
+        }
+
+        for indices in pending.chunks(self.max_batch_size) {
+            let chunk: Vec<RpcRequest> = indices.iter().map(|i| requests[*i].clone()).collect();
+            let responses = self.send_with_retry(&chunk).await?;
+
+            let mut fresh = Vec::new();
+            for (index, response) in indices.iter().zip(responses) {
+                let request = &requests[*index];
+                if let (Ok(value), Some(ttl)) = (&response, cache::default_ttl(&request.method)) {
+                    fresh.push((request.method.as_str(), &request.params, value.clone(), ttl));
+                }
+                results[*index] = Some(response);
+            }
+            let fresh: Vec<_> = fresh.iter().map(|(m, p, v, t)| (*m, *p, v, *t)).collect();
+            self.cache_put(&fresh).await;
+        }
+
+        Ok(results.into_iter().map(|r| r.unwrap_or(Ok(Value::Null))).collect())
+    }
+
+    async fn cache_get(&self, method: &str, params: &Value) -> Option<Value> {
+        let (chain_id, cache) = self.cache.as_ref()?;
+        cache.lock().await.get(&chain_id.to_string(), method, params)
+    }
+
+    /// The cache only saves round trips, failing to write it must not fail the read
+    async fn cache_put(&self, entries: &[(&str, &Value, &Value, Duration)]) {
+        let Some((chain_id, cache)) = self.cache.as_ref() else {
+            return;
+        };
+        if entries.is_empty() {
+            return;
+        }
+
+        let scope = chain_id.to_string();
+        let mut cache = cache.lock().await;
+        for (method, params, value, ttl) in entries {
+            cache.insert(&scope, method, params, (*value).clone(), *ttl);
+        }
+        if let Err(e) = cache.persist().await {
+            warn!("Could not update the RPC cache: {}", e);
+        }
+    }
+
+    async fn send_with_retry(&self, chunk: &[RpcRequest]) -> WalletResult<Vec<WalletResult<Value>>> {
+        let mut attempt = 1;
+        loop {
+            let started = Instant::now();
+            let result = match tokio::time::timeout(self.timeout, self.send_chunk(chunk)).await {
+                Ok(result) => result,
+                Err(_) => Err(NetworkError::RequestTimeout {
+                    request_type: chunk
+                        .iter()
+                        .map(|r| r.method.as_str())
+                        .collect::<Vec<_>>()
+                        .join(","),
+                    elapsed: started.elapsed(),
+                    limit: self.timeout,
+                }
+                .into()),
+            };
+
+            match result {
+                Err(err) if attempt < self.retry.max_attempts && RetryPolicy::is_retryable(&err) => {
+                    let delay = match &err {
+                        // A provider asking for hours must not hang the command past the backoff cap
+                        WalletError::Network(NetworkError::RateLimitExceeded { retry_after }) => {
+                            (*retry_after).min(self.retry.max_backoff)
+                        }
+                        _ => self.retry.backoff(attempt),
+                    };
+                    debug!("RPC attempt {} failed ({}), retrying in {:?}", attempt, err, delay);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                other => return other,
+            }
+        }
+    }
+
+    async fn send_chunk(&self, chunk: &[RpcRequest]) -> WalletResult<Vec<WalletResult<Value>>> {
+        let ids: Vec<u64> = chunk
+            .iter()
+            .map(|_| self.next_id.fetch_add(1, Ordering::Relaxed))
+            .collect();
+
+        let envelopes: Vec<RpcEnvelope> = chunk
+            .iter()
+            .zip(&ids)
+            .map(|(request, id)| RpcEnvelope {
+                jsonrpc: "2.0",
+                id: *id,
+                method: &request.method,
+                params: &request.params,
+            })
+            .collect();
+
+        // Single requests go out unbatched, some providers reject one-element batches
+        let body = if envelopes.len() == 1 {
+            json!(envelopes[0])
+        } else {
+            json!(envelopes)
+        };
+
+        let response = self
+            .http
+            .post(&self.endpoint)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| NetworkError::ConnectivityFailure {
+                endpoint: self.endpoint.clone(),
+                details: e.to_string(),
+            })?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .map(Duration::from_secs)
+                .unwrap_or_else(|| self.retry.backoff(1));
+            return Err(NetworkError::RateLimitExceeded { retry_after }.into());
+        }
+
+        if response.status().is_server_error() {
+            return Err(NetworkError::ConnectivityFailure {
+                endpoint: self.endpoint.clone(),
+                details: format!("HTTP {}", response.status()),
+            }
+            .into());
+        }
+
+        let payload: Value = response.json().await.map_err(|e| NetworkError::InvalidResponse {
+            endpoint: self.endpoint.clone(),
+            details: e.to_string(),
+        })?;
+
+        let responses: Vec<RpcResponse> = match payload {
+            Value::Array(_) => serde_json::from_value(payload),
+            other => serde_json::from_value(other).map(|r| vec![r]),
+        }
+        .map_err(|e| NetworkError::InvalidResponse {
+            endpoint: self.endpoint.clone(),
+            details: e.to_string(),
+        })?;
+
+        // Providers may answer a batch in any order, correlate by id
+        let mut by_id: HashMap<u64, RpcResponse> = responses
+            .into_iter()
+            .filter_map(|r| r.id.map(|id| (id, r)))
+            .collect();
+
+        Ok(chunk
+            .iter()
+            .zip(ids)
+            .map(|(request, id)| match by_id.remove(&id) {
+                Some(RpcResponse { error: Some(err), .. }) => Err(NetworkError::RpcError {
+                    method: request.method.clone(),
+                    code: err.code,
+                    message: err.message,
+                }
+                .into()),
+                Some(RpcResponse { result, .. }) => Ok(result.unwrap_or(Value::Null)),
+                None => Err(NetworkError::InvalidResponse {
+                    endpoint: self.endpoint.clone(),
+                    details: format!("no response for {} (id {})", request.method, id),
+                }
+                .into()),
+            })
+            .collect())
+    }
+}
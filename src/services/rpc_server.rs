@@ -0,0 +1,361 @@
+//! HTTP side of `web3wallet serve`: JSON-RPC 2.0 requests POSTed to `/` on a loopback address,
+//! each carrying the server's bearer token. What the methods do is up to the `RpcHandler`
+//! served; one request per connection keeps the HTTP handling small.
+use crate::config::serve as limits;
+use crate::errors::{FilesystemError, UserInputError, WalletError, WalletResult};
+use crate::services::shutdown::Shutdown;
+use crate::utils;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::debug;
+use zeroize::Zeroizing;
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// Any `WalletError`; its exit code and hint go in `data`
+pub const WALLET_ERROR: i64 = -32000;
+
+/// Error object of a JSON-RPC response
+#[derive(Debug, Clone)]
+pub struct RpcFault {
+    pub code: i64,
+    pub message: String,
+    pub data: Option<Value>,
+}
+
+impl RpcFault {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into(), data: None }
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(METHOD_NOT_FOUND, format!("Method not found: {}", method))
+    }
+
+    pub fn invalid_params(details: impl std::fmt::Display) -> Self {
+        Self::new(INVALID_PARAMS, format!("Invalid params: {}", details))
+    }
+
+    fn to_json(&self) -> Value {
+        let mut error = json!({ "code": self.code, "message": self.message });
+        if let Some(data) = &self.data {
+            error["data"] = data.clone();
+        }
+        error
+    }
+}
+
+impl From<WalletError> for RpcFault {
+    fn from(err: WalletError) -> Self {
+        Self {
+            code: WALLET_ERROR,
+            message: err.to_string(),
+            data: Some(json!({ "exit_code": err.exit_code(), "hint": err.suggestion() })),
+        }
+    }
+}
+
+/// The methods a server answers
+#[async_trait]
+pub trait RpcHandler: Send + Sync {
+    /// Result of `method`; `params` is an object, `{}` when the request has none
+    async fn call(&self, method: &str, params: Value) -> Result<Value, RpcFault>;
+}
+
+pub struct RpcServer {
+    listener: TcpListener,
+    token: Arc<Zeroizing<String>>,
+    allow_origin: Option<String>,
+}
+
+impl RpcServer {
+    /// Bind `addr`, refusing anything but a loopback address: the methods sign with the wallets
+    pub async fn bind(addr: SocketAddr, token: Zeroizing<String>) -> WalletResult<Self> {
+        if !addr.ip().is_loopback() {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "listen".to_string(),
+                value: addr.to_string(),
+                expected: format!("a loopback address such as {}", limits::DEFAULT_LISTEN),
+            }
+            .into());
+        }
+        let listener = TcpListener::bind(addr).await?;
+        Ok(Self { listener, token: Arc::new(token), allow_origin: None })
+    }
+
+    /// Let pages served from `origin` call the API from a browser; requests naming any other
+    /// origin are refused
+    pub fn with_allowed_origin(mut self, origin: String) -> Self {
+        self.allow_origin = Some(origin);
+        self
+    }
+
+    pub fn local_addr(&self) -> WalletResult<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Answer requests until `shutdown` triggers; requests being answered hold an in-flight
+    /// guard, so the caller can drain them
+    pub async fn serve(self, handler: Arc<dyn RpcHandler>, shutdown: Shutdown) -> WalletResult<()> {
+        let endpoint = Arc::new(Endpoint { token: self.token, allow_origin: self.allow_origin, handler });
+        loop {
+            tokio::select! {
+                _ = shutdown.triggered() => break,
+                accepted = self.listener.accept() => {
+                    let Ok((stream, peer)) = accepted else { continue };
+                    let Some(in_flight) = shutdown.begin() else { break };
+                    let endpoint = endpoint.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(stream, &endpoint).await {
+                            debug!("Connection from {} failed: {}", peer, e);
+                        }
+                        drop(in_flight);
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Random bearer token, hex encoded
+pub fn generate_token() -> Zeroizing<String> {
+    Zeroizing::new(hex::encode(rand::random::<[u8; limits::TOKEN_BYTES]>()))
+}
+
+/// Token kept in `path`, or a new one written there readable by the owner only
+pub async fn load_or_create_token(path: &Path) -> WalletResult<Zeroizing<String>> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(contents) => {
+            let token = Zeroizing::new(contents.trim().to_string());
+            if token.is_empty() {
+                return Err(FilesystemError::InvalidFormat {
+                    path: path.display().to_string(),
+                    details: "expected a bearer token on the first line".to_string(),
+                }
+                .into());
+            }
+            Ok(token)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            if let Some(dir) = path.parent() {
+                tokio::fs::create_dir_all(dir).await?;
+            }
+            let token = generate_token();
+            utils::write_private(path, format!("{}\n", token.as_str()).as_bytes()).await?;
+            Ok(token)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+struct Endpoint {
+    token: Arc<Zeroizing<String>>,
+    allow_origin: Option<String>,
+    handler: Arc<dyn RpcHandler>,
+}
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    origin: Option<String>,
+    body: Vec<u8>,
+}
+
+struct HttpResponse {
+    status: u16,
+    reason: &'static str,
+    body: Option<Value>,
+}
+
+impl HttpResponse {
+    fn new(status: u16, reason: &'static str) -> Self {
+        Self { status, reason, body: None }
+    }
+
+    fn json(body: Value) -> Self {
+        Self { status: 200, reason: "OK", body: Some(body) }
+    }
+}
+
+async fn handle_connection(stream: TcpStream, endpoint: &Endpoint) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader.take(limits::MAX_HEADER_BYTES + limits::MAX_BODY_BYTES));
+    let read = tokio::time::timeout(Duration::from_secs(limits::READ_TIMEOUT_SECS), read_request(&mut reader));
+    let (response, origin) = match read.await {
+        Ok(Ok(request)) => {
+            let origin = request.origin.clone();
+            (endpoint.respond(request).await, origin)
+        }
+        Ok(Err(response)) => (response, None),
+        Err(_) => (HttpResponse::new(408, "Request Timeout"), None),
+    };
+
+    let body = match &response.body {
+        Some(body) => serde_json::to_vec(body)?,
+        None => Vec::new(),
+    };
+    let mut head = format!("HTTP/1.1 {} {}\r\nConnection: close\r\nContent-Length: {}\r\n", response.status, response.reason, body.len());
+    if response.body.is_some() {
+        head.push_str("Content-Type: application/json\r\n");
+    }
+    if let Some(origin) = origin.filter(|origin| endpoint.allow_origin.as_ref() == Some(origin)) {
+        head.push_str(&format!(
+            "Access-Control-Allow-Origin: {}\r\nAccess-Control-Allow-Methods: POST\r\nAccess-Control-Allow-Headers: authorization, content-type\r\nVary: Origin\r\n",
+            origin
+        ));
+    }
+    head.push_str("\r\n");
+    writer.write_all(head.as_bytes()).await?;
+    writer.write_all(&body).await?;
+    writer.shutdown().await
+}
+
+/// Request line, the headers the server looks at and the body; the error is the response to send
+async fn read_request<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<HttpRequest, HttpResponse> {
+    let bad_request = || HttpResponse::new(400, "Bad Request");
+
+    let mut line = String::new();
+    reader.read_line(&mut line).await.map_err(|_| bad_request())?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(bad_request());
+    };
+    let mut request = HttpRequest {
+        method: method.to_string(),
+        path: path.to_string(),
+        authorization: None,
+        origin: None,
+        body: Vec::new(),
+    };
+
+    let mut header_bytes = line.len() as u64;
+    let mut content_length = 0u64;
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).await.map_err(|_| bad_request())?;
+        header_bytes += read as u64;
+        if read == 0 {
+            return Err(bad_request());
+        }
+        if header_bytes > limits::MAX_HEADER_BYTES {
+            return Err(HttpResponse::new(431, "Request Header Fields Too Large"));
+        }
+        let header = line.trim_end_matches(['\r', '\n']);
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(bad_request());
+        };
+        let value = value.trim().to_string();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => content_length = value.parse().map_err(|_| bad_request())?,
+            "authorization" => request.authorization = Some(value),
+            "origin" => request.origin = Some(value),
+            _ => {}
+        }
+    }
+
+    if content_length > limits::MAX_BODY_BYTES {
+        return Err(HttpResponse::new(413, "Payload Too Large"));
+    }
+    request.body = vec![0; content_length as usize];
+    reader.read_exact(&mut request.body).await.map_err(|_| bad_request())?;
+    Ok(request)
+}
+
+impl Endpoint {
+    async fn respond(&self, request: HttpRequest) -> HttpResponse {
+        if request.path != "/" {
+            return HttpResponse::new(404, "Not Found");
+        }
+        // A page from an origin not allowed must not reach the wallets, token or not
+        if request.origin.as_ref().is_some_and(|origin| self.allow_origin.as_ref() != Some(origin)) {
+            return HttpResponse::new(403, "Forbidden");
+        }
+        match request.method.as_str() {
+            "OPTIONS" => HttpResponse::new(204, "No Content"),
+            "POST" => {
+                let authorized = request
+                    .authorization
+                    .as_deref()
+                    .and_then(|value| value.strip_prefix("Bearer "))
+                    .is_some_and(|token| token_matches(&self.token, token.trim()));
+                if !authorized {
+                    return HttpResponse::new(401, "Unauthorized");
+                }
+                match self.dispatch(&request.body).await {
+                    Some(body) => HttpResponse::json(body),
+                    None => HttpResponse::new(204, "No Content"),
+                }
+            }
+            _ => HttpResponse::new(405, "Method Not Allowed"),
+        }
+    }
+
+    /// Response to a request or a batch; `None` when it held notifications only
+    async fn dispatch(&self, body: &[u8]) -> Option<Value> {
+        let request: Value = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(e) => return Some(error_response(Value::Null, &RpcFault::new(PARSE_ERROR, format!("Parse error: {}", e)))),
+        };
+        match request {
+            Value::Array(calls) if calls.is_empty() => {
+                Some(error_response(Value::Null, &RpcFault::new(INVALID_REQUEST, "Invalid Request: empty batch")))
+            }
+            Value::Array(calls) => {
+                let mut responses = Vec::new();
+                for call in calls {
+                    responses.extend(self.call(call).await);
+                }
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            call => self.call(call).await,
+        }
+    }
+
+    async fn call(&self, call: Value) -> Option<Value> {
+        let id = call.get("id").cloned();
+        let invalid = |details: &str| Some(error_response(id.clone().unwrap_or(Value::Null), &RpcFault::new(INVALID_REQUEST, format!("Invalid Request: {}", details))));
+
+        if call.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+            return invalid("jsonrpc must be \"2.0\"");
+        }
+        let Some(method) = call.get("method").and_then(Value::as_str) else {
+            return invalid("method must be a string");
+        };
+        let params = match call.get("params") {
+            None | Some(Value::Null) => json!({}),
+            Some(params @ Value::Object(_)) => params.clone(),
+            Some(_) => return invalid("params must be an object"),
+        };
+
+        debug!("JSON-RPC call {}", method);
+        let result = self.handler.call(method, params).await;
+        // Notifications run but are not answered
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(fault) => error_response(id, &fault),
+        })
+    }
+}
+
+fn error_response(id: Value, fault: &RpcFault) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": fault.to_json() })
+}
+
+/// Compare without stopping at the first difference, so timing does not leak the token
+fn token_matches(expected: &str, given: &str) -> bool {
+    expected.len() == given.len() && expected.bytes().zip(given.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
@@ -0,0 +1,249 @@
+use crate::errors::{CryptographicError, UserInputError, WalletResult};
+#[cfg(feature = "native")]
+use crate::errors::ValidationError;
+use crate::models::Wallet;
+#[cfg(feature = "native")]
+use crate::services::rpc::RpcClient;
+#[cfg(feature = "native")]
+use crate::services::transaction::encode_call;
+use crate::services::transaction::{parse_address, parse_bytes};
+use ethers::abi::{encode, Token};
+use ethers::prelude::*;
+use ethers::utils::{hash_message, keccak256, to_checksum};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+/// keccak256("EIP712Domain(uint256 chainId,address verifyingContract)")
+const DOMAIN_SEPARATOR_TYPEHASH: &str = "47e79534a245952e8b16893a336b85a3d9ea9fa8c573f3d803afb92a79469218";
+
+/// keccak256("SafeMessage(bytes message)")
+const SAFE_MSG_TYPEHASH: &str = "60b3cbf8b4a223d68d641b3b6ddf9a298e7f33710cf3d3a9d1146b5a6150fbca";
+
+/// keccak256("SafeTx(address to,uint256 value,bytes data,uint8 operation,uint256 safeTxGas,
+/// uint256 baseGas,uint256 gasPrice,address gasToken,address refundReceiver,uint256 nonce)")
+const SAFE_TX_TYPEHASH: &str = "bb8310d486368db6bd6f849402fdd73ad53d316b5a4b2644ad6efe0f941286d8";
+
+/// How the Safe makes the call: a normal call, or a delegatecall that runs `to`'s code with
+/// the Safe's own storage and can take over the Safe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SafeOperation {
+    Call,
+    DelegateCall,
+}
+
+impl SafeOperation {
+    /// The uint8 the Safe contract and the transaction service use
+    pub fn code(self) -> u8 {
+        match self {
+            SafeOperation::Call => 0,
+            SafeOperation::DelegateCall => 1,
+        }
+    }
+}
+
+impl FromStr for SafeOperation {
+    type Err = crate::errors::WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "call" | "0" => Ok(Self::Call),
+            "delegatecall" | "1" => Ok(Self::DelegateCall),
+            _ => Err(UserInputError::InvalidParameters {
+                parameter: "operation".to_string(),
+                value: s.to_string(),
+                expected: "call or delegatecall".to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// A Safe transaction as `execTransaction` takes it, for `safe` on `chain_id`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafeTransaction {
+    pub safe: Address,
+    pub chain_id: u64,
+    pub to: Address,
+    pub value: U256,
+    pub data: Vec<u8>,
+    pub operation: SafeOperation,
+    pub safe_tx_gas: U256,
+    pub base_gas: U256,
+    pub gas_price: U256,
+    pub gas_token: Address,
+    pub refund_receiver: Address,
+    pub nonce: u64,
+}
+
+impl SafeTransaction {
+    /// EIP-712 hash of the `SafeTx`, the SafeTxHash owners sign and the Safe app shows
+    pub fn safe_tx_hash(&self) -> [u8; 32] {
+        let struct_hash = keccak256(encode(&[
+            Token::FixedBytes(typehash(SAFE_TX_TYPEHASH)),
+            Token::Address(self.to),
+            Token::Uint(self.value),
+            Token::FixedBytes(keccak256(&self.data).to_vec()),
+            Token::Uint(self.operation.code().into()),
+            Token::Uint(self.safe_tx_gas),
+            Token::Uint(self.base_gas),
+            Token::Uint(self.gas_price),
+            Token::Address(self.gas_token),
+            Token::Address(self.refund_receiver),
+            Token::Uint(self.nonce.into()),
+        ]));
+        SafeService::typed_hash(self.safe, self.chain_id, struct_hash)
+    }
+}
+
+/// An owner's signature over a Safe transaction
+#[derive(Debug, Clone, Serialize)]
+pub struct SignedSafeTransaction {
+    pub signer: String,
+    pub safe: String,
+    pub safe_tx_hash: String,
+    /// r || s || v, v in {27, 28}
+    pub signature: String,
+}
+
+impl SignedSafeTransaction {
+    /// Request body proposing the transaction to the Safe transaction service,
+    /// `POST /api/v1/safes/<safe>/multisig-transactions/`; the other owners then confirm it
+    /// with their own signature
+    pub fn proposal(&self, tx: &SafeTransaction) -> Value {
+        json!({
+            "to": to_checksum(&tx.to, None),
+            "value": tx.value.to_string(),
+            "data": format!("0x{}", hex::encode(&tx.data)),
+            "operation": tx.operation.code(),
+            "safeTxGas": tx.safe_tx_gas.to_string(),
+            "baseGas": tx.base_gas.to_string(),
+            "gasPrice": tx.gas_price.to_string(),
+            "gasToken": to_checksum(&tx.gas_token, None),
+            "refundReceiver": to_checksum(&tx.refund_receiver, None),
+            "nonce": tx.nonce,
+            "contractTransactionHash": self.safe_tx_hash,
+            "sender": self.signer,
+            "signature": self.signature,
+            "origin": crate::config::safe::ORIGIN,
+        })
+    }
+}
+
+/// Signature over a Safe off-chain message
+#[derive(Debug, Clone, Serialize)]
+pub struct SafeSignature {
+    pub signer: String,
+    pub safe: String,
+    /// EIP-712 SafeMessage hash that was signed
+    pub safe_message_hash: String,
+    /// r || s || v, v in {27, 28}
+    pub signature: String,
+}
+
+/// Safe (Gnosis Safe) EIP-712 hashing and signing, for Safe contracts >= 1.3.0
+pub struct SafeService;
+
+impl SafeService {
+    pub fn domain_separator(safe: Address, chain_id: u64) -> [u8; 32] {
+        keccak256(encode(&[
+            Token::FixedBytes(typehash(DOMAIN_SEPARATOR_TYPEHASH)),
+            Token::Uint(U256::from(chain_id)),
+            Token::Address(safe),
+        ]))
+    }
+
+    /// Next nonce of `safe`, the one a new transaction takes
+    #[cfg(feature = "native")]
+    pub async fn nonce(client: &RpcClient, safe: Address) -> WalletResult<u64> {
+        let ret = client.eth_call(&format!("{:?}", safe), &encode_call("nonce()", &[])).await?;
+        if ret.len() != 32 {
+            return Err(ValidationError::PreconditionFailed {
+                check: "Safe".to_string(),
+                details: format!("no Safe contract answers at {} on this chain", to_checksum(&safe, None)),
+            }
+            .into());
+        }
+        Ok(U256::from_big_endian(&ret).low_u64())
+    }
+
+    /// Message bytes for a plain text message, as Safe{Wallet} does: the EIP-191 hash of the text
+    pub fn text_message(text: &str) -> Vec<u8> {
+        hash_message(text).as_bytes().to_vec()
+    }
+
+    /// EIP-712 hash of `SafeMessage(bytes message)` for `safe` on `chain_id`
+    pub fn safe_message_hash(safe: Address, chain_id: u64, message: &[u8]) -> [u8; 32] {
+        let struct_hash = keccak256(encode(&[
+            Token::FixedBytes(typehash(SAFE_MSG_TYPEHASH)),
+            Token::FixedBytes(keccak256(message).to_vec()),
+        ]));
+        Self::typed_hash(safe, chain_id, struct_hash)
+    }
+
+    /// `0x1901 || domainSeparator || structHash`, hashed
+    fn typed_hash(safe: Address, chain_id: u64, struct_hash: [u8; 32]) -> [u8; 32] {
+        let mut payload = Vec::with_capacity(66);
+        payload.extend_from_slice(&[0x19, 0x01]);
+        payload.extend_from_slice(&Self::domain_separator(safe, chain_id));
+        payload.extend_from_slice(&struct_hash);
+        keccak256(payload)
+    }
+
+    /// Sign a Safe message; `message` is text unless `raw_hex` is set,
+    /// in which case it is the hex of the message bytes (e.g. an EIP-712 hash)
+    pub fn sign_message(
+        wallet: &Wallet,
+        safe: &str,
+        chain_id: u64,
+        message: &str,
+        raw_hex: bool,
+    ) -> WalletResult<SafeSignature> {
+        let safe_address = parse_address("safe", safe)?;
+        let message = if raw_hex {
+            parse_bytes("message", message)?.to_vec()
+        } else {
+            Self::text_message(message)
+        };
+
+        let hash = Self::safe_message_hash(safe_address, chain_id, &message);
+        let (signer, signature) = sign_hash(wallet, hash)?;
+
+        Ok(SafeSignature {
+            signer: format!("{:?}", signer),
+            safe: format!("{:?}", safe_address),
+            safe_message_hash: format!("0x{}", hex::encode(hash)),
+            signature,
+        })
+    }
+
+    /// Sign `tx`'s SafeTxHash as one of the Safe's owners
+    pub fn sign_transaction(wallet: &Wallet, tx: &SafeTransaction) -> WalletResult<SignedSafeTransaction> {
+        let hash = tx.safe_tx_hash();
+        let (signer, signature) = sign_hash(wallet, hash)?;
+
+        Ok(SignedSafeTransaction {
+            // The transaction service only accepts a checksummed sender
+            signer: to_checksum(&signer, None),
+            safe: to_checksum(&tx.safe, None),
+            safe_tx_hash: format!("0x{}", hex::encode(hash)),
+            signature,
+        })
+    }
+}
+
+/// Signer address and `r || s || v` signature of an EIP-712 hash
+fn sign_hash(wallet: &Wallet, hash: [u8; 32]) -> WalletResult<(Address, String)> {
+    let signer = wallet.signer()?;
+    let signature = signer.sign_hash(H256::from(hash)).map_err(|e| {
+        CryptographicError::SigningFailed {
+            details: e.to_string(),
+        }
+    })?;
+    Ok((signer.address(), format!("0x{}", signature)))
+}
+
+fn typehash(hex_str: &str) -> Vec<u8> {
+    hex::decode(hex_str).expect("typehash constants are valid hex")
+}
@@ -0,0 +1,535 @@
+use crate::errors::{UserInputError, ValidationError, WalletResult};
+use crate::services::address_format::{self, Chain};
+use crate::services::chains::ChainInfo;
+use crate::services::fees::FeeOptions;
+use crate::services::rpc::RpcClient;
+use crate::services::transaction::{parse_address, parse_bytes, parse_quantity, TransactionService, UnsignedTransaction};
+use crate::services::walletmanager::WalletManager;
+use crate::WalletConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Declarative wallet script loaded from YAML (or JSON)
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptSpec {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Variables available as `${name}` in every step
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+    /// Keep going after a failed step instead of stopping
+    #[serde(default)]
+    pub continue_on_error: bool,
+    pub steps: Vec<ScriptStep>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptStep {
+    /// Step id, used to reference outputs as `${id.field}`
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Per-step override of the script level `continue_on_error`
+    #[serde(default)]
+    pub continue_on_error: Option<bool>,
+    /// Raw operation body, interpolated right before execution
+    #[serde(flatten)]
+    pub body: HashMap<String, Value>,
+}
+
+/// Operations understood by the script runner
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+pub enum ScriptOp {
+    Create {
+        #[serde(default = "default_words")]
+        words: u8,
+        #[serde(default)]
+        network: Option<String>,
+        #[serde(default)]
+        save: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    Import {
+        #[serde(default)]
+        mnemonic: Option<String>,
+        #[serde(default)]
+        private_key: Option<String>,
+        #[serde(default)]
+        network: Option<String>,
+        #[serde(default)]
+        save: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    Derive {
+        #[serde(default)]
+        mnemonic: Option<String>,
+        #[serde(default)]
+        from_file: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+        #[serde(default = "default_count")]
+        count: u32,
+        #[serde(default)]
+        start_index: u32,
+    },
+    /// Sign a complete transaction offline, like `sign-tx --tx-json`; fees must be given
+    Sign {
+        from_file: String,
+        #[serde(default)]
+        password: Option<String>,
+        tx: UnsignedTransaction,
+    },
+    /// Send `value` wei and optional calldata to `to`, with nonce, gas and fees taken from
+    /// the chain, and broadcast it
+    Send {
+        from_file: String,
+        #[serde(default)]
+        password: Option<String>,
+        to: String,
+        #[serde(default)]
+        value: Option<String>,
+        #[serde(default)]
+        data: Option<String>,
+        /// Chain name, the configured network by default
+        #[serde(default)]
+        chain: Option<String>,
+        #[serde(default)]
+        legacy: bool,
+    },
+}
+
+fn default_words() -> u8 {
+    crate::config::bip39::DEFAULT_WORD_COUNT
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+impl ScriptOp {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScriptOp::Create { .. } => "create",
+            ScriptOp::Import { .. } => "import",
+            ScriptOp::Derive { .. } => "derive",
+            ScriptOp::Sign { .. } => "sign",
+            ScriptOp::Send { .. } => "send",
+        }
+    }
+}
+
+impl ScriptSpec {
+    pub fn from_yaml(source: &str) -> WalletResult<Self> {
+        let spec: Self = serde_yaml::from_str(source).map_err(|e| {
+            ValidationError::InvalidCommandSyntax {
+                command: "script".to_string(),
+                error: e.to_string(),
+            }
+        })?;
+
+        if spec.steps.is_empty() {
+            return Err(ValidationError::InvalidCommandSyntax {
+                command: "script".to_string(),
+                error: "script has no steps".to_string(),
+            }
+            .into());
+        }
+        Ok(spec)
+    }
+
+    pub async fn from_file(path: &std::path::Path) -> WalletResult<Self> {
+        let source = tokio::fs::read_to_string(path).await?;
+        Self::from_yaml(&source)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    Ok,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StepReport {
+    pub index: usize,
+    pub id: Option<String>,
+    pub op: String,
+    pub status: StepStatus,
+    pub output: Option<Value>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptReport {
+    pub name: Option<String>,
+    pub steps: Vec<StepReport>,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+impl ScriptReport {
+    pub fn is_success(&self) -> bool {
+        self.failed == 0
+    }
+}
+
+/// Executes a `ScriptSpec` step by step against a wallet configuration
+pub struct ScriptRunner {
+    config: WalletConfig,
+}
+
+impl ScriptRunner {
+    pub fn new(config: WalletConfig) -> Self {
+        Self { config }
+    }
+
+    pub async fn run(&self, spec: &ScriptSpec) -> ScriptReport {
+        let mut scope: HashMap<String, Value> = spec
+            .vars
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+            .collect();
+
+        let mut steps = Vec::with_capacity(spec.steps.len());
+        let mut halted = false;
+
+        for (index, step) in spec.steps.iter().enumerate() {
+            let op_name = step
+                .body
+                .get("op")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+
+            if halted {
+                steps.push(StepReport {
+                    index,
+                    id: step.id.clone(),
+                    op: op_name,
+                    status: StepStatus::Skipped,
+                    output: None,
+                    error: None,
+                });
+                continue;
+            }
+
+            match self.run_step(step, &scope).await {
+                Ok(output) => {
+                    if let Some(id) = &step.id {
+                        scope.insert(id.clone(), output.clone());
+                    }
+                    steps.push(StepReport {
+                        index,
+                        id: step.id.clone(),
+                        op: op_name,
+                        status: StepStatus::Ok,
+                        output: Some(output),
+                        error: None,
+                    });
+                }
+                Err(err) => {
+                    if !step.continue_on_error.unwrap_or(spec.continue_on_error) {
+                        halted = true;
+                    }
+                    steps.push(StepReport {
+                        index,
+                        id: step.id.clone(),
+                        op: op_name,
+                        status: StepStatus::Failed,
+                        output: None,
+                        error: Some(err.to_string()),
+                    });
+                }
+            }
+        }
+
+        let count = |status: StepStatus| steps.iter().filter(|s| s.status == status).count();
+        ScriptReport {
+            name: spec.name.clone(),
+            succeeded: count(StepStatus::Ok),
+            failed: count(StepStatus::Failed),
+            skipped: count(StepStatus::Skipped),
+            steps,
+        }
+    }
+
+    async fn run_step(&self, step: &ScriptStep, scope: &HashMap<String, Value>) -> WalletResult<Value> {
+        let body = interpolate(&json!(step.body), scope)?;
+        let op: ScriptOp = serde_json::from_value(body).map_err(|e| {
+            ValidationError::InvalidCommandSyntax {
+                command: "script".to_string(),
+                error: e.to_string(),
+            }
+        })?;
+
+        match op {
+            ScriptOp::Create { words, network, save, password } => {
+                let manager = self.manager(network.as_deref());
+                let network = network.unwrap_or_else(|| self.config.network.clone());
+                let wallet = manager.create_wallet_with_network(words, &network).await?;
+                let saved = self.save(&manager, &wallet, save, password).await?;
+                Ok(json!({
+                    "address": address_format::display(Chain::Evm, wallet.address()),
+                    "network": wallet.network(),
+                    "mnemonic": wallet.mnemonic(),
+                    "derivation_path": wallet.derivation_path(),
+                    "file": saved
+                }))
+            }
+            ScriptOp::Import { mnemonic, private_key, network, save, password } => {
+                let manager = self.manager(network.as_deref());
+                let wallet = match (mnemonic, private_key) {
+                    (Some(mnemonic), None) => manager.import_from_mnemoic(&mnemonic).await?,
+                    (None, Some(key)) => manager.import_from_private_key(&key).await?,
+                    (Some(_), Some(_)) => {
+                        return Err(UserInputError::ConflictingOptions {
+                            option1: "mnemonic".to_string(),
+                            option2: "private_key".to_string(),
+                            suggestion: "use only one import source per step".to_string(),
+                        }
+                        .into())
+                    }
+                    (None, None) => {
+                        return Err(UserInputError::MissingParameter {
+                            parameter: "import source".to_string(),
+                            hint: "either mnemonic or private_key required".to_string(),
+                        }
+                        .into())
+                    }
+                };
+                let saved = self.save(&manager, &wallet, save, password).await?;
+                Ok(json!({
+                    "address": address_format::display(Chain::Evm, wallet.address()),
+                    "network": wallet.network(),
+                    "has_mnemonic": wallet.has_mnemonic(),
+                    "file": saved
+                }))
+            }
+            ScriptOp::Derive { mnemonic, from_file, password, count, start_index } => {
+                let manager = self.manager(None);
+                let wallet = match (mnemonic, from_file) {
+                    (Some(mnemonic), _) => manager.import_from_mnemoic(&mnemonic).await?,
+                    (None, Some(file)) => {
+                        let password = require_password(password)?;
+                        manager.load_wallet(&self.resolve_path(&file), &password).await?
+                    }
+                    (None, None) => {
+                        return Err(UserInputError::MissingParameter {
+                            parameter: "derive source".to_string(),
+                            hint: "either mnemonic or from_file required".to_string(),
+                        }
+                        .into())
+                    }
+                };
+
+                // Both come from the script, the last index must not wrap around
+                if start_index.checked_add(count.saturating_sub(1)).is_none() {
+                    return Err(UserInputError::InvalidParameters {
+                        parameter: "start_index".to_string(),
+                        value: start_index.to_string(),
+                        expected: format!("start_index + count - 1 at most {}", u32::MAX),
+                    }
+                    .into());
+                }
+
+                let mut addresses = Vec::new();
+                for index in (0..count).map(|i| start_index + i) {
+                    let derived = wallet.derive_address(index)?;
+                    addresses.push(json!({
+                        "index": derived.index(),
+                        "address": address_format::display(Chain::Evm, derived.address()),
+                        "derivation_path": derived.derivation_path()
+                    }));
+                }
+                Ok(json!({
+                    "base_address": address_format::display(Chain::Evm, wallet.address()),
+                    "addresses": addresses
+                }))
+            }
+            ScriptOp::Sign { from_file, password, tx } => {
+                // Estimating fees would need the network, which an offline signing step must not touch
+                if tx.needs_fees() {
+                    return Err(UserInputError::MissingParameter {
+                        parameter: "tx fees".to_string(),
+                        hint: "set max_fee_per_gas and max_priority_fee_per_gas, or gas_price with legacy".to_string(),
+                    }
+                    .into());
+                }
+                let manager = self.manager(None);
+                let file_path = self.resolve_path(&from_file);
+                manager.check_can_sign(&file_path).await?;
+                manager.check_signing_chain(&file_path, tx.chain_id).await?;
+                let wallet = manager.load_wallet(&file_path, &require_password(password)?).await?;
+                let signed = TransactionService::sign(&wallet, &tx)?;
+                Ok(json!({
+                    "from": address_format::display(Chain::Evm, &signed.from),
+                    "hash": signed.hash,
+                    "raw": signed.raw
+                }))
+            }
+            ScriptOp::Send { from_file, password, to, value, data, chain, legacy } => {
+                let chain = ChainInfo::resolve(chain.as_deref().unwrap_or(&self.config.network), &self.config)?;
+                let to = format!("{:?}", parse_address("to", &to)?);
+                let value = parse_quantity("value", value.as_deref().unwrap_or("0"))?;
+                let data = match data {
+                    Some(data) => parse_bytes("data", &data)?.to_vec(),
+                    None => Vec::new(),
+                };
+                let client = RpcClient::from_config(&chain.rpc_url, &self.config)?;
+
+                let manager = self.manager(None);
+                let file_path = self.resolve_path(&from_file);
+                manager.check_can_sign(&file_path).await?;
+                manager.check_signing_chain(&file_path, chain.chain_id).await?;
+                let wallet = manager.load_wallet(&file_path, &require_password(password)?).await?;
+
+                let fees = FeeOptions { legacy, tier: self.config.fee_tier };
+                let unsigned = TransactionService::prepare(&client, chain.chain_id, wallet.address(), &to, value, &data, fees).await?;
+                let signed = TransactionService::sign(&wallet, &unsigned)?;
+                let hash = TransactionService::broadcast(&client, &signed).await?;
+                Ok(json!({
+                    "chain": chain.name,
+                    "from": address_format::display(Chain::Evm, &signed.from),
+                    "to": address_format::display(Chain::Evm, &to),
+                    "value": value.to_string(),
+                    "nonce": unsigned.nonce,
+                    "hash": hash,
+                    "raw": signed.raw
+                }))
+            }
+        }
+    }
+
+    fn manager(&self, network: Option<&str>) -> WalletManager {
+        let mut config = self.config.clone();
+        if let Some(network) = network {
+            config.network = network.to_string();
+        }
+        WalletManager::new(config)
+    }
+
+    fn resolve_path(&self, filename: &str) -> PathBuf {
+        if filename.contains('/') || filename.contains('\\') {
+            PathBuf::from(filename)
+        } else if filename.ends_with(".json") {
+            self.config.wallets_path.join(filename)
+        } else {
+            self.config.wallets_path.join(format!("{}.json", filename))
+        }
+    }
+
+    async fn save(
+        &self,
+        manager: &WalletManager,
+        wallet: &crate::models::Wallet,
+        save: Option<String>,
+        password: Option<String>,
+    ) -> WalletResult<Option<String>> {
+        let Some(filename) = save else {
+            return Ok(None);
+        };
+        let password = require_password(password)?;
+        let file_path = self.resolve_path(&filename);
+        if let Some(parent) = file_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        manager.save_wallet(wallet, &file_path, &password).await?;
+        Ok(Some(file_path.display().to_string()))
+    }
+}
+
+fn require_password(password: Option<String>) -> WalletResult<String> {
+    password.ok_or_else(|| {
+        UserInputError::MissingParameter {
+            parameter: "password".to_string(),
+            hint: "set `password`, e.g. password: ${env.WALLET_PASSWORD}".to_string(),
+        }
+        .into()
+    })
+}
+
+/// Replace `${var}`, `${step.field}` and `${env.NAME}` references in every string of `value`.
+/// A string consisting of a single reference takes the referenced value as-is (numbers stay numbers).
+pub fn interpolate(value: &Value, scope: &HashMap<String, Value>) -> WalletResult<Value> {
+    match value {
+        Value::String(s) => interpolate_str(s, scope),
+        Value::Array(items) => items
+            .iter()
+            .map(|v| interpolate(v, scope))
+            .collect::<WalletResult<Vec<_>>>()
+            .map(Value::Array),
+        Value::Object(map) => {
+            let mut out = serde_json::Map::new();
+            for (k, v) in map {
+                out.insert(k.clone(), interpolate(v, scope)?);
+            }
+            Ok(Value::Object(out))
+        }
+        other => Ok(other.clone()),
+    }
+}
+
+fn interpolate_str(s: &str, scope: &HashMap<String, Value>) -> WalletResult<Value> {
+    if let Some(name) = s.strip_prefix("${").and_then(|r| r.strip_suffix('}')) {
+        if !name.contains("${") && !name.contains('}') {
+            return lookup(name, scope);
+        }
+    }
+
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find('}').ok_or_else(|| ValidationError::InvalidCommandSyntax {
+            command: "script".to_string(),
+            error: format!("unterminated reference in '{}'", s),
+        })?;
+        match lookup(&after[..end], scope)? {
+            Value::String(v) => out.push_str(&v),
+            other => out.push_str(&other.to_string()),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(Value::String(out))
+}
+
+fn lookup(name: &str, scope: &HashMap<String, Value>) -> WalletResult<Value> {
+    let name = name.trim();
+    if let Some(var) = name.strip_prefix("env.") {
+        return std::env::var(var).map(Value::String).map_err(|_| {
+            ValidationError::InvalidCommandSyntax {
+                command: "script".to_string(),
+                error: format!("environment variable '{}' is not set", var),
+            }
+            .into()
+        });
+    }
+
+    let mut parts = name.split('.');
+    let root = parts.next().unwrap_or_default();
+    let mut current = scope.get(root).ok_or_else(|| ValidationError::InvalidCommandSyntax {
+        command: "script".to_string(),
+        error: format!("unknown reference '{}'", name),
+    })?;
+
+    for part in parts {
+        let next = match part.parse::<usize>() {
+            Ok(i) => current.get(i),
+            Err(_) => current.get(part),
+        };
+        current = next.ok_or_else(|| ValidationError::InvalidCommandSyntax {
+            command: "script".to_string(),
+            error: format!("unknown reference '{}'", name),
+        })?;
+    }
+    Ok(current.clone())
+}
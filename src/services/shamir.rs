@@ -0,0 +1,478 @@
+//! SLIP-39 Shamir backups: a master secret split into mnemonic shares, any `threshold` of which
+//! restore it. Only single-group backups are created or accepted.
+
+use crate::config::slip39 as params;
+use crate::errors::{CryptographicError, UserInputError, WalletResult};
+use hmac::{Hmac, Mac};
+use pbkdf2::pbkdf2_hmac;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::Sha256;
+use std::sync::OnceLock;
+use zeroize::Zeroizing;
+
+const WORDLIST: &str = include_str!("shamir_wordlist.txt");
+
+/// x coordinates of the secret and of its digest share
+const SECRET_INDEX: u8 = 255;
+const DIGEST_INDEX: u8 = 254;
+
+const RADIX_BITS: usize = 10;
+const ID_EXP_WORDS: usize = 2;
+const PARAMS_WORDS: usize = 2;
+const CHECKSUM_WORDS: usize = 3;
+const METADATA_WORDS: usize = ID_EXP_WORDS + PARAMS_WORDS + CHECKSUM_WORDS;
+const MIN_MNEMONIC_WORDS: usize = METADATA_WORDS + (params::MIN_SECRET_LENGTH * 8).div_ceil(RADIX_BITS);
+
+const CHECKSUM_GENERATOR: [u32; 10] = [
+    0xE0E040, 0x1C1C080, 0x3838100, 0x7070200, 0xE0E0009,
+    0x1C0C2412, 0x38086C24, 0x3090FC48, 0x21B1F890, 0x3F3F120,
+];
+
+/// One decoded share
+#[derive(Debug, Clone)]
+pub struct Share {
+    identifier: u16,
+    extendable: bool,
+    iteration_exponent: u8,
+    group_index: u8,
+    group_threshold: u8,
+    group_count: u8,
+    member_index: u8,
+    member_threshold: u8,
+    value: Zeroizing<Vec<u8>>,
+}
+
+impl Share {
+    pub fn parse(mnemonic: &str) -> WalletResult<Self> {
+        let words = mnemonic
+            .split_whitespace()
+            .map(|word| {
+                word_index(word).ok_or_else(|| invalid_share(format!("'{}' is not a SLIP-39 word", word)))
+            })
+            .collect::<WalletResult<Vec<u16>>>()?;
+
+        if words.len() < MIN_MNEMONIC_WORDS {
+            return Err(invalid_share(format!(
+                "Share has {} words, at least {} expected",
+                words.len(),
+                MIN_MNEMONIC_WORDS
+            )));
+        }
+        let padding = (RADIX_BITS * (words.len() - METADATA_WORDS)) % 16;
+        if padding > 8 {
+            return Err(invalid_share(format!("Share has an invalid length of {} words", words.len())));
+        }
+
+        let extendable = (words[1] >> 4) & 1 == 1;
+        if polymod(customization(extendable), &words) != 1 {
+            return Err(invalid_share("Share checksum does not match, check for mistyped words".to_string()));
+        }
+
+        let id_exp = (u32::from(words[0]) << RADIX_BITS) | u32::from(words[1]);
+        let share_params = (u32::from(words[2]) << RADIX_BITS) | u32::from(words[3]);
+        let group_threshold = ((share_params >> 12) & 0xf) as u8 + 1;
+        let group_count = ((share_params >> 8) & 0xf) as u8 + 1;
+        if group_threshold > group_count {
+            return Err(invalid_share("Group threshold exceeds the number of groups".to_string()));
+        }
+
+        let value_words = &words[ID_EXP_WORDS + PARAMS_WORDS..words.len() - CHECKSUM_WORDS];
+        let bits: Vec<bool> = value_words
+            .iter()
+            .flat_map(|word| (0..RADIX_BITS).rev().map(move |bit| (word >> bit) & 1 == 1))
+            .collect();
+        if bits[..padding].iter().any(|bit| *bit) {
+            return Err(invalid_share("Share has non-zero padding bits".to_string()));
+        }
+        let value = bits[padding..]
+            .chunks(8)
+            .map(|byte| byte.iter().fold(0u8, |acc, bit| (acc << 1) | u8::from(*bit)))
+            .collect::<Vec<u8>>();
+
+        Ok(Self {
+            identifier: (id_exp >> 5) as u16,
+            extendable,
+            iteration_exponent: (id_exp & 0xf) as u8,
+            group_index: (share_params >> 16) as u8,
+            group_threshold,
+            group_count,
+            member_index: ((share_params >> 4) & 0xf) as u8,
+            member_threshold: (share_params & 0xf) as u8 + 1,
+            value: Zeroizing::new(value),
+        })
+    }
+
+    pub fn to_mnemonic(&self) -> String {
+        let id_exp = (u32::from(self.identifier) << 5)
+            | (u32::from(self.extendable) << 4)
+            | u32::from(self.iteration_exponent);
+        let share_params = (u32::from(self.group_index) << 16)
+            | (u32::from(self.group_threshold - 1) << 12)
+            | (u32::from(self.group_count - 1) << 8)
+            | (u32::from(self.member_index) << 4)
+            | u32::from(self.member_threshold - 1);
+
+        let mut words = vec![
+            (id_exp >> RADIX_BITS) as u16,
+            (id_exp & 0x3ff) as u16,
+            (share_params >> RADIX_BITS) as u16,
+            (share_params & 0x3ff) as u16,
+        ];
+
+        let value_bits = self.value.len() * 8;
+        let padding = (RADIX_BITS - value_bits % RADIX_BITS) % RADIX_BITS;
+        let bits: Vec<bool> = std::iter::repeat_n(false, padding)
+            .chain(self.value.iter().flat_map(|byte| (0..8).rev().map(move |bit| (byte >> bit) & 1 == 1)))
+            .collect();
+        words.extend(
+            bits.chunks(RADIX_BITS)
+                .map(|chunk| chunk.iter().fold(0u16, |acc, bit| (acc << 1) | u16::from(*bit))),
+        );
+
+        let mut padded = words.clone();
+        padded.extend([0; CHECKSUM_WORDS]);
+        let checksum = polymod(customization(self.extendable), &padded) ^ 1;
+        words.extend((0..CHECKSUM_WORDS).rev().map(|i| ((checksum >> (RADIX_BITS * i)) & 0x3ff) as u16));
+
+        let list = wordlist();
+        words.iter().map(|w| list[*w as usize]).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Shares needed to restore the secret
+    pub fn member_threshold(&self) -> u8 {
+        self.member_threshold
+    }
+}
+
+pub struct ShamirService;
+
+impl ShamirService {
+    /// Split `master_secret` into `count` mnemonic shares, any `threshold` of which restore it.
+    /// The secret is encrypted under `passphrase` first, as SLIP-39 requires.
+    pub fn split(master_secret: &[u8], threshold: u8, count: u8, passphrase: &str) -> WalletResult<Vec<String>> {
+        if master_secret.len() < params::MIN_SECRET_LENGTH || !master_secret.len().is_multiple_of(2) {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "secret".to_string(),
+                value: format!("{} bytes", master_secret.len()),
+                expected: format!("an even number of bytes, at least {}", params::MIN_SECRET_LENGTH),
+            }
+            .into());
+        }
+        if count == 0 || count > params::MAX_SHARES {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "shares".to_string(),
+                value: count.to_string(),
+                expected: format!("1 to {}", params::MAX_SHARES),
+            }
+            .into());
+        }
+        if threshold == 0 || threshold > count || (threshold == 1 && count > 1) {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "threshold".to_string(),
+                value: threshold.to_string(),
+                expected: format!("2 to {} (1 only for a single share)", count),
+            }
+            .into());
+        }
+        check_passphrase(passphrase)?;
+
+        let identifier = (OsRng.next_u32() & 0x7fff) as u16;
+        let iteration_exponent = params::ITERATION_EXPONENT;
+        let encrypted = feistel(master_secret, passphrase, iteration_exponent, identifier, false, false);
+
+        // One group with threshold 1, so the group share is the encrypted secret itself
+        let shares = split_secret(threshold, count, &encrypted)?;
+        Ok(shares
+            .into_iter()
+            .map(|(member_index, value)| {
+                Share {
+                    identifier,
+                    extendable: false,
+                    iteration_exponent,
+                    group_index: 0,
+                    group_threshold: 1,
+                    group_count: 1,
+                    member_index,
+                    member_threshold: threshold,
+                    value,
+                }
+                .to_mnemonic()
+            })
+            .collect())
+    }
+
+    /// Restore the master secret from the shares of a single-group backup
+    pub fn combine(mnemonics: &[String], passphrase: &str) -> WalletResult<Zeroizing<Vec<u8>>> {
+        let shares = mnemonics
+            .iter()
+            .map(|m| Share::parse(m))
+            .collect::<WalletResult<Vec<Share>>>()?;
+        let first = shares.first().ok_or_else(|| UserInputError::MissingParameter {
+            parameter: "shares".to_string(),
+            hint: "enter at least one SLIP-39 share".to_string(),
+        })?;
+
+        if first.group_threshold != 1 || first.group_count != 1 {
+            return Err(mismatched(format!(
+                "Shares belong to a {}-of-{} group backup; only single-group backups are supported",
+                first.group_threshold, first.group_count
+            )));
+        }
+        for share in &shares[1..] {
+            if share.identifier != first.identifier
+                || share.extendable != first.extendable
+                || share.iteration_exponent != first.iteration_exponent
+            {
+                return Err(mismatched("Shares come from different backups".to_string()));
+            }
+            if share.group_index != first.group_index
+                || share.group_threshold != first.group_threshold
+                || share.group_count != first.group_count
+                || share.member_threshold != first.member_threshold
+                || share.value.len() != first.value.len()
+            {
+                return Err(mismatched("Shares disagree on their parameters".to_string()));
+            }
+        }
+
+        let mut indices: Vec<u8> = shares.iter().map(|s| s.member_index).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        if indices.len() != shares.len() {
+            return Err(mismatched("The same share was entered more than once".to_string()));
+        }
+        if shares.len() < usize::from(first.member_threshold) {
+            return Err(mismatched(format!(
+                "{} shares given, {} needed",
+                shares.len(),
+                first.member_threshold
+            )));
+        }
+
+        let points: Vec<(u8, &[u8])> = shares
+            .iter()
+            .take(usize::from(first.member_threshold))
+            .map(|s| (s.member_index, s.value.as_slice()))
+            .collect();
+        let encrypted = recover_secret(first.member_threshold, &points)?;
+
+        Ok(feistel(
+            &encrypted,
+            passphrase,
+            first.iteration_exponent,
+            first.identifier,
+            first.extendable,
+            true,
+        ))
+    }
+
+    /// Number of shares needed to restore the backup `mnemonic` belongs to
+    pub fn threshold_of(mnemonic: &str) -> WalletResult<u8> {
+        Ok(Share::parse(mnemonic)?.member_threshold())
+    }
+}
+
+fn wordlist() -> &'static [&'static str] {
+    static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| WORDLIST.split_whitespace().collect())
+}
+
+fn word_index(word: &str) -> Option<u16> {
+    let word = word.to_lowercase();
+    wordlist().binary_search(&word.as_str()).ok().map(|i| i as u16)
+}
+
+fn customization(extendable: bool) -> &'static [u8] {
+    if extendable {
+        params::EXTENDABLE_CUSTOMIZATION.as_bytes()
+    } else {
+        params::CUSTOMIZATION.as_bytes()
+    }
+}
+
+/// RS1024 checksum over the customization string and the share words
+fn polymod(customization: &[u8], words: &[u16]) -> u32 {
+    let mut chk: u32 = 1;
+    for value in customization.iter().map(|b| u32::from(*b)).chain(words.iter().map(|w| u32::from(*w))) {
+        let b = chk >> 20;
+        chk = ((chk & 0xfffff) << 10) ^ value;
+        for (i, generator) in CHECKSUM_GENERATOR.iter().enumerate() {
+            if (b >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk
+}
+
+/// Four-round Feistel network keyed by PBKDF2 over the passphrase
+fn feistel(
+    secret: &[u8],
+    passphrase: &str,
+    iteration_exponent: u8,
+    identifier: u16,
+    extendable: bool,
+    decrypt: bool,
+) -> Zeroizing<Vec<u8>> {
+    let half = secret.len() / 2;
+    let mut left = Zeroizing::new(secret[..half].to_vec());
+    let mut right = Zeroizing::new(secret[half..].to_vec());
+
+    let mut salt_prefix = Vec::new();
+    if !extendable {
+        salt_prefix.extend_from_slice(params::CUSTOMIZATION.as_bytes());
+        salt_prefix.extend_from_slice(&identifier.to_be_bytes());
+    }
+    let iterations = (params::BASE_ITERATIONS << iteration_exponent) / u32::from(params::ROUND_COUNT);
+
+    let rounds: Vec<u8> = if decrypt {
+        (0..params::ROUND_COUNT).rev().collect()
+    } else {
+        (0..params::ROUND_COUNT).collect()
+    };
+    for round in rounds {
+        let mut password = Zeroizing::new(vec![round]);
+        password.extend_from_slice(passphrase.as_bytes());
+        let mut salt = salt_prefix.clone();
+        salt.extend_from_slice(&right);
+
+        let mut f = Zeroizing::new(vec![0u8; right.len()]);
+        pbkdf2_hmac::<Sha256>(&password, &salt, iterations, &mut f);
+
+        let next: Vec<u8> = left.iter().zip(f.iter()).map(|(l, f)| l ^ f).collect();
+        left = std::mem::replace(&mut right, Zeroizing::new(next));
+    }
+
+    let mut output = Zeroizing::new(right.to_vec());
+    output.extend_from_slice(&left);
+    output
+}
+
+fn digest(random: &[u8], secret: &[u8]) -> [u8; params::DIGEST_LENGTH] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(random).expect("HMAC accepts keys of any length");
+    mac.update(secret);
+    let mut out = [0u8; params::DIGEST_LENGTH];
+    out.copy_from_slice(&mac.finalize().into_bytes()[..params::DIGEST_LENGTH]);
+    out
+}
+
+fn split_secret(threshold: u8, count: u8, secret: &[u8]) -> WalletResult<Vec<(u8, Zeroizing<Vec<u8>>)>> {
+    if threshold == 1 {
+        return Ok((0..count).map(|i| (i, Zeroizing::new(secret.to_vec()))).collect());
+    }
+
+    let mut random = Zeroizing::new(vec![0u8; secret.len() - params::DIGEST_LENGTH]);
+    OsRng.fill_bytes(&mut random);
+    let mut digest_share = Zeroizing::new(digest(&random, secret).to_vec());
+    digest_share.extend_from_slice(&random);
+
+    let mut shares: Vec<(u8, Zeroizing<Vec<u8>>)> = (0..threshold - 2)
+        .map(|i| {
+            let mut value = Zeroizing::new(vec![0u8; secret.len()]);
+            OsRng.fill_bytes(&mut value);
+            (i, value)
+        })
+        .collect();
+
+    let mut base: Vec<(u8, &[u8])> = shares.iter().map(|(x, v)| (*x, v.as_slice())).collect();
+    base.push((DIGEST_INDEX, digest_share.as_slice()));
+    base.push((SECRET_INDEX, secret));
+    let interpolated: Vec<(u8, Zeroizing<Vec<u8>>)> = (threshold - 2..count)
+        .map(|x| (x, interpolate(&base, x)))
+        .collect();
+
+    shares.extend(interpolated);
+    Ok(shares)
+}
+
+fn recover_secret(threshold: u8, shares: &[(u8, &[u8])]) -> WalletResult<Zeroizing<Vec<u8>>> {
+    if threshold == 1 {
+        return Ok(Zeroizing::new(shares[0].1.to_vec()));
+    }
+
+    let secret = interpolate(shares, SECRET_INDEX);
+    let digest_share = interpolate(shares, DIGEST_INDEX);
+    let (expected, random) = digest_share.split_at(params::DIGEST_LENGTH);
+    if digest(random, &secret) != expected {
+        return Err(invalid_share("Share digest does not match, the shares may be corrupted".to_string()));
+    }
+    Ok(secret)
+}
+
+struct Gf256 {
+    exp: [u8; 255],
+    log: [u8; 256],
+}
+
+fn gf256() -> &'static Gf256 {
+    static TABLES: OnceLock<Gf256> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let mut exp = [0u8; 255];
+        let mut log = [0u8; 256];
+        let mut poly: u16 = 1;
+        for i in 0..255u16 {
+            exp[i as usize] = poly as u8;
+            log[poly as usize] = i as u8;
+            // Multiply by the generator x + 1, reducing modulo x^8 + x^4 + x^3 + x + 1
+            poly = (poly << 1) ^ poly;
+            if poly & 0x100 != 0 {
+                poly ^= 0x11b;
+            }
+        }
+        Gf256 { exp, log }
+    })
+}
+
+/// Lagrange interpolation over GF(256) of the polynomial through `shares`, evaluated at `x`
+fn interpolate(shares: &[(u8, &[u8])], x: u8) -> Zeroizing<Vec<u8>> {
+    if let Some((_, value)) = shares.iter().find(|(xi, _)| *xi == x) {
+        return Zeroizing::new(value.to_vec());
+    }
+
+    let gf = gf256();
+    let log_prod: i32 = shares.iter().map(|(xi, _)| i32::from(gf.log[usize::from(xi ^ x)])).sum();
+    let mut result = Zeroizing::new(vec![0u8; shares[0].1.len()]);
+
+    for (xi, yi) in shares {
+        let others: i32 = shares.iter().map(|(xj, _)| i32::from(gf.log[usize::from(xi ^ xj)])).sum();
+        let log_basis = (log_prod - i32::from(gf.log[usize::from(xi ^ x)]) - others).rem_euclid(255);
+
+        for (out, y) in result.iter_mut().zip(yi.iter()) {
+            if *y != 0 {
+                let log = (i32::from(gf.log[usize::from(*y)]) + log_basis) % 255;
+                *out ^= gf.exp[log as usize];
+            }
+        }
+    }
+    result
+}
+
+fn check_passphrase(passphrase: &str) -> WalletResult<()> {
+    if passphrase.bytes().all(|b| (32..=126).contains(&b)) {
+        return Ok(());
+    }
+    Err(UserInputError::InvalidParameters {
+        parameter: "passphrase".to_string(),
+        value: "<redacted>".to_string(),
+        expected: "printable ASCII characters".to_string(),
+    }
+    .into())
+}
+
+fn invalid_share(detail: String) -> crate::errors::WalletError {
+    CryptographicError::InvalidMnemonic {
+        detail,
+        suggestion: "Check each word of the share against the SLIP-39 wordlist".to_string(),
+    }
+    .into()
+}
+
+fn mismatched(detail: String) -> crate::errors::WalletError {
+    UserInputError::InvalidParameters {
+        parameter: "shares".to_string(),
+        value: detail,
+        expected: "distinct shares from one single-group SLIP-39 backup".to_string(),
+    }
+    .into()
+}
@@ -0,0 +1,195 @@
+//! Signal-driven shutdown for long-running modes. On SIGINT or SIGTERM new work is refused,
+//! in-flight operations get a grace period to finish, and the process exits with a code
+//! derived from the signal so orchestrators can tell a clean stop from a crash.
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, Notify};
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownSignal {
+    Interrupt,
+    Terminate,
+}
+
+impl ShutdownSignal {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Interrupt => "SIGINT",
+            Self::Terminate => "SIGTERM",
+        }
+    }
+
+    /// 128 + signal number, the shell convention for a process stopped by a signal
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Interrupt => 130,
+            Self::Terminate => 143,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrainOutcome {
+    Drained,
+    /// The grace period ran out with operations still running
+    TimedOut { in_flight: usize },
+}
+
+impl DrainOutcome {
+    pub fn exit_code(self, signal: ShutdownSignal) -> i32 {
+        match self {
+            Self::Drained => signal.exit_code(),
+            Self::TimedOut { .. } => crate::config::exit_codes::GENERAL,
+        }
+    }
+}
+
+/// Shared shutdown state; clones observe the same signal and in-flight count
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    signal: watch::Sender<Option<ShutdownSignal>>,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                signal: watch::channel(None).0,
+                in_flight: AtomicUsize::new(0),
+                idle: Notify::new(),
+            }),
+        }
+    }
+
+    /// Trigger shutdown on the first SIGINT, or SIGTERM on unix. Must be called within the runtime.
+    pub fn listen(&self) {
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            let signal = wait_for_signal().await;
+            info!(
+                target: "audit",
+                "{} received, refusing new work and draining {} in-flight operation(s)",
+                signal.as_str(),
+                shutdown.in_flight()
+            );
+            shutdown.trigger(signal);
+        });
+    }
+
+    /// Start shutting down; later signals keep the first one
+    pub fn trigger(&self, signal: ShutdownSignal) {
+        self.inner.signal.send_if_modified(|current| {
+            if current.is_some() {
+                return false;
+            }
+            *current = Some(signal);
+            true
+        });
+    }
+
+    pub fn signal(&self) -> Option<ShutdownSignal> {
+        *self.inner.signal.borrow()
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        self.signal().is_some()
+    }
+
+    /// Resolves once shutdown has been triggered
+    pub async fn triggered(&self) -> ShutdownSignal {
+        let mut receiver = self.inner.signal.subscribe();
+        loop {
+            if let Some(signal) = *receiver.borrow_and_update() {
+                return signal;
+            }
+            // The sender lives in `inner`, which this handle keeps alive
+            let _ = receiver.changed().await;
+        }
+    }
+
+    /// Register an in-flight operation, held until the guard drops.
+    /// `None` once shutdown has begun, so callers refuse the new work.
+    pub fn begin(&self) -> Option<InFlight> {
+        self.inner.in_flight.fetch_add(1, Ordering::SeqCst);
+        if self.is_triggered() {
+            self.release();
+            return None;
+        }
+        Some(InFlight { shutdown: self.clone() })
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.inner.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Wait for in-flight operations to finish, giving up after `grace`
+    pub async fn drain(&self, grace: Duration) -> DrainOutcome {
+        let idle = async {
+            loop {
+                let notified = self.inner.idle.notified();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        };
+
+        match tokio::time::timeout(grace, idle).await {
+            Ok(()) => DrainOutcome::Drained,
+            Err(_) => DrainOutcome::TimedOut { in_flight: self.in_flight() },
+        }
+    }
+
+    fn release(&self) {
+        if self.inner.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.inner.idle.notify_waiters();
+        }
+    }
+}
+
+/// Guard for one in-flight operation, see `Shutdown::begin`
+#[derive(Debug)]
+pub struct InFlight {
+    shutdown: Shutdown,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.shutdown.release();
+    }
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() -> ShutdownSignal {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let Ok(mut terminate) = signal(SignalKind::terminate()) else {
+        let _ = tokio::signal::ctrl_c().await;
+        return ShutdownSignal::Interrupt;
+    };
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => ShutdownSignal::Interrupt,
+        _ = terminate.recv() => ShutdownSignal::Terminate,
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() -> ShutdownSignal {
+    let _ = tokio::signal::ctrl_c().await;
+    ShutdownSignal::Interrupt
+}
@@ -0,0 +1,172 @@
+#[cfg(feature = "native")]
+use crate::config;
+#[cfg(feature = "native")]
+use crate::errors::CryptographicError;
+use crate::errors::{UserInputError, WalletResult};
+use crate::models::wallet::DerivedAddress;
+use crate::models::Wallet;
+use crate::services::signing::{SignedPayload, SigningService};
+use crate::services::transaction::{SignedTransaction, TransactionService, UnsignedTransaction};
+use async_trait::async_trait;
+#[cfg(feature = "native")]
+use ethers::signers::{HDPath, Ledger, Signer as _};
+#[cfg(feature = "native")]
+use ethers::utils::keccak256;
+use std::str::FromStr;
+
+/// Signing backend selected with `--signer`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SignerKind {
+    /// Encrypted keystore on disk
+    #[default]
+    Software,
+    /// Ledger hardware wallet running the Ethereum app
+    Ledger,
+}
+
+impl FromStr for SignerKind {
+    type Err = crate::errors::WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "software" | "keystore" => Ok(Self::Software),
+            "ledger" => Ok(Self::Ledger),
+            _ => Err(UserInputError::InvalidParameters {
+                parameter: "signer".to_string(),
+                value: s.to_string(),
+                expected: "software or ledger".to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// Anything that can hold an Ethereum key and sign with it
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// Address of the signing account
+    async fn address(&self) -> WalletResult<String>;
+
+    /// BIP44 path that `derive_address` indexes into
+    fn base_path(&self) -> String;
+
+    /// Address at `index` under the backend's BIP44 account path
+    async fn derive_address(&self, index: u32) -> WalletResult<DerivedAddress>;
+
+    async fn sign_transaction(&self, tx: &UnsignedTransaction) -> WalletResult<SignedTransaction>;
+
+    /// EIP-191 `personal_sign`, returning the 65 byte signature as hex
+    async fn sign_message(&self, message: &[u8]) -> WalletResult<String>;
+}
+
+/// Signer backed by a decrypted software wallet
+pub struct SoftwareSigner {
+    wallet: Wallet,
+}
+
+impl SoftwareSigner {
+    pub fn new(wallet: Wallet) -> Self {
+        Self { wallet }
+    }
+}
+
+#[async_trait]
+impl Signer for SoftwareSigner {
+    async fn address(&self) -> WalletResult<String> {
+        Ok(self.wallet.address().to_string())
+    }
+
+    fn base_path(&self) -> String {
+        self.wallet.derivation_path().to_string()
+    }
+
+    async fn derive_address(&self, index: u32) -> WalletResult<DerivedAddress> {
+        self.wallet.derive_address(index)
+    }
+
+    async fn sign_transaction(&self, tx: &UnsignedTransaction) -> WalletResult<SignedTransaction> {
+        TransactionService::sign(&self.wallet, tx)
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> WalletResult<String> {
+        SigningService::sign(&self.wallet, &SignedPayload::Message(message.to_vec()))
+    }
+}
+
+/// Signer backed by a Ledger device; keys never leave the device
+#[cfg(feature = "native")]
+pub struct LedgerSigner {
+    ledger: Ledger,
+    derivation_path: String,
+    /// Parent of `derivation_path`, where `derive_address` indexes
+    base_path: String,
+}
+
+#[cfg(feature = "native")]
+impl LedgerSigner {
+    /// Connect to the first Ledger over USB, signing with the account at `derivation_path` for
+    /// transactions on `chain_id`. The path is checked before the device is opened.
+    pub async fn connect(derivation_path: Option<&str>, chain_id: u64) -> WalletResult<Self> {
+        let derivation_path = derivation_path
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{}/0", config::DEFAULT_DERIVATION_PATH));
+        let (base_path, _) = crate::utils::split_address_path(&derivation_path)?;
+        let ledger = Ledger::new(HDPath::Other(derivation_path.clone()), chain_id)
+            .await
+            .map_err(ledger_error)?;
+        Ok(Self { ledger, derivation_path, base_path })
+    }
+
+    pub fn derivation_path(&self) -> &str {
+        &self.derivation_path
+    }
+}
+
+#[cfg(feature = "native")]
+#[async_trait]
+impl Signer for LedgerSigner {
+    async fn address(&self) -> WalletResult<String> {
+        Ok(format!("{:?}", self.ledger.address()))
+    }
+
+    /// Parent of the signing path, so `--account`/`--change` carry over to derivation
+    fn base_path(&self) -> String {
+        self.base_path.clone()
+    }
+
+    async fn derive_address(&self, index: u32) -> WalletResult<DerivedAddress> {
+        let path = format!("{}/{}", self.base_path(), index);
+        let address = self
+            .ledger
+            .get_address_with_path(&HDPath::Other(path.clone()))
+            .await
+            .map_err(ledger_error)?;
+        Ok(DerivedAddress::new(format!("{:?}", address), index, path))
+    }
+
+    async fn sign_transaction(&self, tx: &UnsignedTransaction) -> WalletResult<SignedTransaction> {
+        let typed = TransactionService::build(tx)?;
+        // Confirmation happens on the device, this waits for the user
+        let signature = self.ledger.sign_transaction(&typed).await.map_err(ledger_error)?;
+        let raw = typed.rlp_signed(&signature);
+        Ok(SignedTransaction {
+            from: format!("{:?}", self.ledger.address()),
+            hash: format!("0x{}", hex::encode(keccak256(&raw))),
+            raw: format!("0x{}", hex::encode(&raw)),
+        })
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> WalletResult<String> {
+        let signature = self.ledger.sign_message(message).await.map_err(ledger_error)?;
+        Ok(format!("0x{}", signature))
+    }
+}
+
+#[cfg(feature = "native")]
+fn ledger_error(e: ethers::signers::LedgerError) -> crate::errors::WalletError {
+    CryptographicError::HardwareSignerFailed {
+        device: "ledger".to_string(),
+        details: e.to_string(),
+    }
+    .into()
+}
@@ -0,0 +1,88 @@
+use crate::errors::{CryptographicError, UserInputError, WalletResult};
+use crate::models::Wallet;
+use crate::services::transaction::parse_address;
+use ethers::types::transaction::eip712::{Eip712, TypedData};
+use ethers::types::{Signature, H256};
+use ethers::utils::{hash_message, to_checksum};
+use serde::Serialize;
+use std::str::FromStr;
+
+/// What a signature commits to
+#[derive(Debug, Clone)]
+pub enum SignedPayload {
+    /// EIP-191 `personal_sign` message
+    Message(Vec<u8>),
+    /// EIP-712 typed data document
+    TypedData(Box<TypedData>),
+}
+
+impl SignedPayload {
+    pub fn typed_data_from_json(json: &str) -> WalletResult<Self> {
+        let typed: TypedData = serde_json::from_str(json).map_err(|e| UserInputError::InvalidParameters {
+            parameter: "typed_data".to_string(),
+            value: e.to_string(),
+            expected: "an EIP-712 typed data document (types, primaryType, domain, message)".to_string(),
+        })?;
+        Ok(Self::TypedData(Box::new(typed)))
+    }
+
+    /// Digest the signer actually signed
+    pub fn hash(&self) -> WalletResult<H256> {
+        match self {
+            Self::Message(message) => Ok(hash_message(message)),
+            Self::TypedData(typed) => typed.encode_eip712().map(H256::from).map_err(|e| {
+                UserInputError::InvalidParameters {
+                    parameter: "typed_data".to_string(),
+                    value: e.to_string(),
+                    expected: "typed data that encodes under EIP-712".to_string(),
+                }
+                .into()
+            }),
+        }
+    }
+}
+
+/// Outcome of checking a signature against an expected signer
+#[derive(Debug, Clone, Serialize)]
+pub struct Verification {
+    pub valid: bool,
+    pub expected: String,
+    pub recovered: String,
+    pub hash: String,
+}
+
+pub struct SigningService;
+
+impl SigningService {
+    /// Sign `payload` with the wallet's primary key, returning the 65 byte signature as hex
+    pub fn sign(wallet: &Wallet, payload: &SignedPayload) -> WalletResult<String> {
+        let signature = wallet.signer()?.sign_hash(payload.hash()?).map_err(|e| {
+            CryptographicError::SigningFailed {
+                details: e.to_string(),
+            }
+        })?;
+        Ok(format!("0x{}", signature))
+    }
+
+    /// Recover the signer of `payload` and compare it with `expected`
+    pub fn verify(payload: &SignedPayload, signature: &str, expected: &str) -> WalletResult<Verification> {
+        let expected = parse_address("address", expected)?;
+        let signature = Signature::from_str(signature).map_err(|e| UserInputError::InvalidParameters {
+            parameter: "signature".to_string(),
+            value: signature.to_string(),
+            expected: format!("65 byte hex signature ({})", e),
+        })?;
+
+        let hash = payload.hash()?;
+        let recovered = signature.recover(hash).map_err(|e| CryptographicError::SigningFailed {
+            details: format!("signature recovery failed: {}", e),
+        })?;
+
+        Ok(Verification {
+            valid: recovered == expected,
+            expected: to_checksum(&expected, None),
+            recovered: to_checksum(&recovered, None),
+            hash: format!("{:?}", hash),
+        })
+    }
+}
@@ -0,0 +1,46 @@
+use crate::errors::{NetworkError, WalletResult};
+use crate::models::token::{Token, TokenRegistry};
+use serde::Deserialize;
+
+/// A standard token list (https://tokenlists.org) document
+#[derive(Debug, Deserialize)]
+pub struct TokenList {
+    pub name: String,
+    pub tokens: Vec<Token>,
+}
+
+pub struct TokenListService;
+
+impl TokenListService {
+    pub async fn fetch(url: &str) -> WalletResult<TokenList> {
+        let response = reqwest::get(url).await.map_err(|e| NetworkError::ConnectivityFailure {
+            endpoint: url.to_string(),
+            details: e.to_string(),
+        })?;
+
+        response.json().await.map_err(|e| {
+            NetworkError::InvalidResponse {
+                endpoint: url.to_string(),
+                details: format!("not a token list: {}", e),
+            }
+            .into()
+        })
+    }
+
+    /// Merge every valid token of `list` (optionally only `chain_id`) into `registry`, returning how many were new
+    pub fn seed(registry: &mut TokenRegistry, list: TokenList, chain_id: Option<u64>) -> usize {
+        let mut added = 0;
+        for token in list.tokens {
+            if chain_id.is_some_and(|id| token.chain_id != id) {
+                continue;
+            }
+            // Lists occasionally carry malformed entries, skip rather than fail the import
+            if let Ok(token) = Token::new(token.chain_id, &token.address, &token.symbol, token.name, token.decimals) {
+                if registry.add(token) {
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+}
@@ -0,0 +1,257 @@
+#[cfg(feature = "native")]
+use crate::errors::NetworkError;
+use crate::errors::{CryptographicError, UserInputError, ValidationError, WalletResult};
+use crate::models::Wallet;
+#[cfg(feature = "native")]
+use crate::services::fees::{FeeEstimator, FeeOptions};
+#[cfg(feature = "native")]
+use crate::services::rpc::RpcClient;
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::keccak256;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "native")]
+use serde_json::json;
+
+/// Unsigned transaction as accepted by `sign-tx`, either from flags or a JSON file.
+/// Quantities are decimal strings or `0x` hex strings.
+/// Type-2 (EIP-1559) unless `legacy` is set or only `gas_price` is given.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UnsignedTransaction {
+    pub to: Option<String>,
+    #[serde(default)]
+    pub value: Option<String>,
+    pub nonce: String,
+    #[serde(default)]
+    pub gas: Option<String>,
+    #[serde(default)]
+    pub gas_price: Option<String>,
+    #[serde(default)]
+    pub max_fee_per_gas: Option<String>,
+    #[serde(default)]
+    pub max_priority_fee_per_gas: Option<String>,
+    pub chain_id: u64,
+    #[serde(default)]
+    pub data: Option<String>,
+    #[serde(default)]
+    pub legacy: bool,
+}
+
+impl UnsignedTransaction {
+    pub fn is_legacy(&self) -> bool {
+        self.legacy || (self.gas_price.is_some() && self.max_fee_per_gas.is_none())
+    }
+
+    /// True when the fee fields for the transaction type still need to be estimated
+    pub fn needs_fees(&self) -> bool {
+        if self.is_legacy() {
+            self.gas_price.is_none()
+        } else {
+            self.max_fee_per_gas.is_none() || self.max_priority_fee_per_gas.is_none()
+        }
+    }
+}
+
+/// Signed transaction ready to broadcast
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedTransaction {
+    pub from: String,
+    pub hash: String,
+    pub raw: String,
+}
+
+pub struct TransactionService;
+
+impl TransactionService {
+    pub fn from_json(json: &str) -> WalletResult<UnsignedTransaction> {
+        serde_json::from_str(json).map_err(|e| {
+            ValidationError::InvalidCommandSyntax {
+                command: "sign-tx".to_string(),
+                error: format!("Invalid unsigned transaction JSON: {}", e),
+            }
+            .into()
+        })
+    }
+
+    pub fn build(tx: &UnsignedTransaction) -> WalletResult<TypedTransaction> {
+        if tx.is_legacy() {
+            return Self::build_legacy(tx);
+        }
+
+        let mut request = Eip1559TransactionRequest::new()
+            .nonce(parse_quantity("nonce", &tx.nonce)?)
+            .gas(parse_quantity("gas", tx.gas.as_deref().unwrap_or("21000"))?)
+            .max_fee_per_gas(parse_quantity("max_fee_per_gas", required_fee("max_fee_per_gas", &tx.max_fee_per_gas)?)?)
+            .max_priority_fee_per_gas(parse_quantity(
+                "max_priority_fee_per_gas",
+                required_fee("max_priority_fee_per_gas", &tx.max_priority_fee_per_gas)?,
+            )?)
+            .value(parse_quantity("value", tx.value.as_deref().unwrap_or("0"))?)
+            .chain_id(tx.chain_id);
+
+        if let Some(to) = &tx.to {
+            request = request.to(parse_address("to", to)?);
+        }
+
+        if let Some(data) = &tx.data {
+            request = request.data(parse_bytes("data", data)?);
+        }
+
+        Ok(request.into())
+    }
+
+    fn build_legacy(tx: &UnsignedTransaction) -> WalletResult<TypedTransaction> {
+        let mut request = TransactionRequest::new()
+            .nonce(parse_quantity("nonce", &tx.nonce)?)
+            .gas(parse_quantity("gas", tx.gas.as_deref().unwrap_or("21000"))?)
+            .gas_price(parse_quantity("gas_price", required_fee("gas_price", &tx.gas_price)?)?)
+            .value(parse_quantity("value", tx.value.as_deref().unwrap_or("0"))?)
+            .chain_id(tx.chain_id);
+
+        if let Some(to) = &tx.to {
+            request = request.to(parse_address("to", to)?);
+        }
+
+        if let Some(data) = &tx.data {
+            request = request.data(parse_bytes("data", data)?);
+        }
+
+        Ok(request.into())
+    }
+
+    /// Sign `tx` with the wallet's primary key and RLP-encode the result
+    pub fn sign(wallet: &Wallet, tx: &UnsignedTransaction) -> WalletResult<SignedTransaction> {
+        Self::sign_with(wallet.signer()?, tx)
+    }
+
+    /// Sign with a key that is not in a keystore, e.g. one restored from multisig shares
+    pub fn sign_with(signer: LocalWallet, tx: &UnsignedTransaction) -> WalletResult<SignedTransaction> {
+        let typed = Self::build(tx)?;
+        let signer = signer.with_chain_id(tx.chain_id);
+
+        let signature = signer.sign_transaction_sync(&typed).map_err(|e| {
+            CryptographicError::SigningFailed {
+                details: e.to_string(),
+            }
+        })?;
+
+        let raw = typed.rlp_signed(&signature);
+        Ok(SignedTransaction {
+            from: format!("{:?}", signer.address()),
+            hash: format!("0x{}", hex::encode(keccak256(&raw))),
+            raw: format!("0x{}", hex::encode(&raw)),
+        })
+    }
+
+    /// Build an unsigned call from `from` with nonce, gas limit and fees filled in from the chain
+    #[cfg(feature = "native")]
+    pub async fn prepare(
+        client: &RpcClient,
+        chain_id: u64,
+        from: &str,
+        to: &str,
+        value: U256,
+        data: &[u8],
+        fees: FeeOptions,
+    ) -> WalletResult<UnsignedTransaction> {
+        let data_hex = format!("0x{}", hex::encode(data));
+        let nonce = client
+            .call("eth_getTransactionCount", json!([from, "pending"]))
+            .await?;
+        let gas = client
+            .call(
+                "eth_estimateGas",
+                json!([{ "from": from, "to": to, "value": format!("0x{:x}", value), "data": data_hex }]),
+            )
+            .await?;
+
+        let mut tx = UnsignedTransaction {
+            to: Some(to.to_string()),
+            value: Some(value.to_string()),
+            nonce: nonce.as_str().unwrap_or("0x0").to_string(),
+            gas: Some(gas.as_str().unwrap_or("0x5208").to_string()),
+            chain_id,
+            data: (!data.is_empty()).then_some(data_hex),
+            legacy: fees.legacy,
+            ..Default::default()
+        };
+
+        let estimate = FeeEstimator::estimate(client, fees.tier).await?;
+        FeeEstimator::apply(&mut tx, &estimate);
+        Ok(tx)
+    }
+
+    /// Submit a signed raw transaction, returning the hash reported by the node
+    #[cfg(feature = "native")]
+    pub async fn broadcast(client: &RpcClient, signed: &SignedTransaction) -> WalletResult<String> {
+        let hash = client
+            .call("eth_sendRawTransaction", json!([signed.raw]))
+            .await?;
+        hash.as_str().map(str::to_string).ok_or_else(|| {
+            NetworkError::InvalidResponse {
+                endpoint: client.endpoint().to_string(),
+                details: "eth_sendRawTransaction returned no hash".to_string(),
+            }
+            .into()
+        })
+    }
+}
+
+/// 4-byte selector followed by ABI-encoded arguments
+pub fn encode_call(signature: &str, args: &[ethers::abi::Token]) -> Vec<u8> {
+    let mut data = ethers::utils::id(signature).to_vec();
+    data.extend(ethers::abi::encode(args));
+    data
+}
+
+fn required_fee<'a>(field: &str, value: &'a Option<String>) -> WalletResult<&'a str> {
+    value.as_deref().ok_or_else(|| {
+        UserInputError::MissingParameter {
+            parameter: field.to_string(),
+            hint: "pass the fee explicitly or let it be estimated from the chain's RPC".to_string(),
+        }
+        .into()
+    })
+}
+
+/// Parse a decimal or `0x` hex quantity
+pub fn parse_quantity(field: &str, value: &str) -> WalletResult<U256> {
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_dec_str(value).ok(),
+    };
+
+    parsed.ok_or_else(|| {
+        UserInputError::InvalidParameters {
+            parameter: field.to_string(),
+            value: value.to_string(),
+            expected: "decimal or 0x-prefixed hex quantity".to_string(),
+        }
+        .into()
+    })
+}
+
+pub fn parse_address(field: &str, value: &str) -> WalletResult<Address> {
+    crate::utils::validate_ethereum_address(value)?;
+    value.parse::<Address>().map_err(|_| {
+        UserInputError::InvalidParameters {
+            parameter: field.to_string(),
+            value: value.to_string(),
+            expected: "0x-prefixed 20 byte address".to_string(),
+        }
+        .into()
+    })
+}
+
+pub fn parse_bytes(field: &str, value: &str) -> WalletResult<Bytes> {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value))
+        .map(Bytes::from)
+        .map_err(|_| {
+            UserInputError::InvalidParameters {
+                parameter: field.to_string(),
+                value: value.to_string(),
+                expected: "hex encoded bytes".to_string(),
+            }
+            .into()
+        })
+}
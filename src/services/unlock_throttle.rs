@@ -0,0 +1,134 @@
+//! Failed unlocks per keystore, kept in a hidden `.unlock-attempts` file beside the keystores.
+//! After `FREE_ATTEMPTS` wrong passwords a keystore is locked for a delay that doubles with
+//! every further failure, so guessing its password through the CLI stays slow even when a
+//! script drives it. The right password clears the count and failures older than a day are
+//! forgotten. Copying the keystore elsewhere escapes the count, so this slows casual guessing
+//! and is no substitute for a strong password.
+use crate::config::throttle as settings;
+use crate::errors::{AuthenticationError, WalletResult};
+use crate::utils;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Wrong passwords tried on one keystore since it was last unlocked
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Attempts {
+    pub failures: u32,
+    pub last_failure: DateTime<Utc>,
+}
+
+impl Attempts {
+    /// End of the lockout these failures earn, None while they are within the free attempts
+    pub fn locked_until(&self) -> Option<DateTime<Utc>> {
+        let past_free = self.failures.checked_sub(settings::FREE_ATTEMPTS)?;
+        let factor = 1u64.checked_shl(past_free).unwrap_or(u64::MAX);
+        let delay = settings::BASE_DELAY_SECS.saturating_mul(factor).min(settings::MAX_DELAY_SECS);
+        Some(self.last_failure + Duration::seconds(delay as i64))
+    }
+
+    fn expired(&self, now: DateTime<Utc>) -> bool {
+        now - self.last_failure > Duration::seconds(settings::RESET_AFTER_SECS)
+    }
+}
+
+/// Refuse to try a password on `keystore` while it is locked out
+pub async fn check(keystore: &Path) -> WalletResult<()> {
+    let (path, name) = locate(keystore);
+    let now = Utc::now();
+    let Some(attempts) = read(&path).await.remove(&name).filter(|a| !a.expired(now)) else {
+        return Ok(());
+    };
+    match attempts.locked_until() {
+        Some(until) if until > now => {
+            // Whole seconds, rounded up so retrying after the wait is never refused
+            let wait = (until - now).num_milliseconds().max(0) as u64;
+            Err(AuthenticationError::UnlockThrottled {
+                wallet_file: keystore.display().to_string(),
+                failures: attempts.failures,
+                retry_after: std::time::Duration::from_secs(wait.div_ceil(1000)),
+            }
+            .into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Count a wrong password for `keystore` and return the attempts left before it is locked
+pub async fn record_failure(keystore: &Path) -> u32 {
+    let (path, name) = locate(keystore);
+    let now = Utc::now();
+    let mut entries = read(&path).await;
+    entries.retain(|_, attempts| !attempts.expired(now));
+    let attempts = entries.entry(name).or_insert(Attempts { failures: 0, last_failure: now });
+    attempts.failures = attempts.failures.saturating_add(1);
+    attempts.last_failure = now;
+    let attempts = *attempts;
+
+    // A keystore in a read-only directory can still be unlocked, just without the count
+    if let Err(e) = write(&path, &entries).await {
+        warn!("Could not record the failed unlock in {}: {}", path.display(), e);
+    }
+    match attempts.locked_until() {
+        Some(until) => warn!(
+            "{} wrong passwords for {}, locked until {}",
+            attempts.failures,
+            keystore.display(),
+            until.to_rfc3339()
+        ),
+        None => warn!(
+            "Wrong password for {}, {} attempts left before it is locked",
+            keystore.display(),
+            settings::FREE_ATTEMPTS - attempts.failures
+        ),
+    }
+    settings::FREE_ATTEMPTS.saturating_sub(attempts.failures)
+}
+
+/// Forget the failures of `keystore` once it has been unlocked
+pub async fn clear(keystore: &Path) {
+    let (path, name) = locate(keystore);
+    let mut entries = read(&path).await;
+    if entries.remove(&name).is_none() {
+        return;
+    }
+    debug!("Clearing the failed unlocks of {}", keystore.display());
+    let result = if entries.is_empty() {
+        tokio::fs::remove_file(&path).await.map_err(Into::into)
+    } else {
+        write(&path, &entries).await
+    };
+    if let Err(e) = result {
+        warn!("Could not clear the failed unlocks in {}: {}", path.display(), e);
+    }
+}
+
+/// The attempts file for `keystore` and the keystore's entry in it
+fn locate(keystore: &Path) -> (PathBuf, String) {
+    let dir = match keystore.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let name = keystore.file_name().map_or_else(
+        || keystore.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    (dir.join(settings::ATTEMPTS_FILE_NAME), name)
+}
+
+/// Entries of the attempts file at `path`; empty when it is missing or unreadable
+async fn read(path: &Path) -> BTreeMap<String, Attempts> {
+    let Ok(data) = tokio::fs::read_to_string(path).await else {
+        return BTreeMap::new();
+    };
+    serde_json::from_str(&data).unwrap_or_else(|e| {
+        warn!("Ignoring the damaged attempts file {}: {}", path.display(), e);
+        BTreeMap::new()
+    })
+}
+
+async fn write(path: &Path, entries: &BTreeMap<String, Attempts>) -> WalletResult<()> {
+    utils::write_private(path, serde_json::to_string_pretty(entries)?.as_bytes()).await
+}
@@ -0,0 +1,188 @@
+//! Local per-wallet usage counters with simple anomaly flags. Nothing leaves the machine;
+//! flags are written to the audit log as they are raised.
+use crate::config::usage as limits;
+use crate::errors::{FilesystemError, WalletResult};
+use chrono::{DateTime, Duration, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageKind {
+    /// A keystore was decrypted
+    Unlock,
+    Sign,
+    /// Key material left the keystore, e.g. `export`
+    Export,
+}
+
+impl UsageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unlock => "unlock",
+            Self::Sign => "sign",
+            Self::Export => "export",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "flag", rename_all = "snake_case")]
+pub enum Anomaly {
+    /// Signing at an hour (UTC) this wallet rarely signs at
+    UnusualHour { hour: u32 },
+    /// First export of a wallet that has been in use, or an export after a dormant stretch
+    SuddenExport { idle_days: Option<i64> },
+}
+
+impl std::fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnusualHour { hour } => write!(f, "signed at an unusual hour ({:02}:00 UTC)", hour),
+            Self::SuddenExport { idle_days: Some(days) } => write!(f, "exported after {} days unused", days),
+            Self::SuddenExport { idle_days: None } => write!(f, "exported for the first time"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageEvent {
+    pub kind: UsageKind,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlaggedAnomaly {
+    #[serde(flatten)]
+    pub anomaly: Anomaly,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WalletUsage {
+    pub unlocks: u64,
+    pub signs: u64,
+    pub exports: u64,
+    pub first_used: Option<DateTime<Utc>>,
+    pub last_used: Option<DateTime<Utc>>,
+    /// Signs per hour of the day, UTC
+    pub sign_hours: [u64; 24],
+    /// Most recent first
+    pub recent: Vec<UsageEvent>,
+    /// Most recent first
+    pub anomalies: Vec<FlaggedAnomaly>,
+}
+
+impl WalletUsage {
+    /// Count one operation at `at`, returning the anomalies it raises
+    pub fn record(&mut self, kind: UsageKind, at: DateTime<Utc>) -> Vec<Anomaly> {
+        let anomalies = self.detect(kind, at);
+
+        match kind {
+            UsageKind::Unlock => self.unlocks += 1,
+            UsageKind::Sign => {
+                self.signs += 1;
+                self.sign_hours[at.hour() as usize] += 1;
+            }
+            UsageKind::Export => self.exports += 1,
+        }
+        self.first_used.get_or_insert(at);
+        self.last_used = Some(at);
+
+        self.recent.insert(0, UsageEvent { kind, at });
+        self.recent.truncate(limits::HISTORY_LIMIT);
+        for anomaly in anomalies.iter().rev() {
+            self.anomalies.insert(0, FlaggedAnomaly { anomaly: anomaly.clone(), at });
+        }
+        self.anomalies.truncate(limits::HISTORY_LIMIT);
+
+        anomalies
+    }
+
+    fn detect(&self, kind: UsageKind, at: DateTime<Utc>) -> Vec<Anomaly> {
+        let mut anomalies = Vec::new();
+        match kind {
+            UsageKind::Sign if self.signs >= limits::MIN_SIGNS_FOR_PROFILE => {
+                let hour = at.hour();
+                let share = self.sign_hours[hour as usize] as f64 / self.signs as f64;
+                if share < limits::UNUSUAL_HOUR_SHARE {
+                    anomalies.push(Anomaly::UnusualHour { hour });
+                }
+            }
+            UsageKind::Export => {
+                let idle_days = self.last_used.map(|last| (at - last).num_days());
+                if idle_days.is_some_and(|days| days >= limits::DORMANT_DAYS) {
+                    anomalies.push(Anomaly::SuddenExport { idle_days });
+                } else if self.exports == 0 && self.unlocks + self.signs > 0 {
+                    anomalies.push(Anomaly::SuddenExport { idle_days: None });
+                }
+            }
+            _ => {}
+        }
+        anomalies
+    }
+
+    /// Anomalies raised in the last `window`
+    pub fn recent_anomalies(&self, window: Duration, now: DateTime<Utc>) -> impl Iterator<Item = &FlaggedAnomaly> {
+        self.anomalies.iter().filter(move |a| now - a.at <= window)
+    }
+}
+
+/// Usage of every wallet seen, keyed by lowercase address and persisted as JSON
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageLog {
+    #[serde(skip)]
+    path: PathBuf,
+    pub wallets: BTreeMap<String, WalletUsage>,
+}
+
+impl UsageLog {
+    pub fn default_path(state_dir: &Path) -> PathBuf {
+        state_dir.join(limits::USAGE_FILE_NAME)
+    }
+
+    /// Load the log at `path`, empty if the file does not exist yet
+    pub async fn load(path: &Path) -> WalletResult<Self> {
+        let mut log: Self = match tokio::fs::read_to_string(path).await {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| FilesystemError::InvalidFormat {
+                path: path.display().to_string(),
+                details: e.to_string(),
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e.into()),
+        };
+        log.path = path.to_path_buf();
+        Ok(log)
+    }
+
+    pub async fn save(&self) -> WalletResult<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                FilesystemError::DirectoryNotAccessible {
+                    path: parent.display().to_string(),
+                    details: e.to_string(),
+                }
+            })?;
+        }
+        crate::utils::write_atomic(&self.path, serde_json::to_string_pretty(self)?.as_bytes()).await
+    }
+
+    pub fn get(&self, address: &str) -> Option<&WalletUsage> {
+        self.wallets.get(&crate::utils::normalize_address(address))
+    }
+
+    /// Count one operation by `address` now; anomalies are also written to the audit log
+    pub fn record(&mut self, address: &str, kind: UsageKind) -> Vec<Anomaly> {
+        self.record_at(address, kind, Utc::now())
+    }
+
+    pub fn record_at(&mut self, address: &str, kind: UsageKind, at: DateTime<Utc>) -> Vec<Anomaly> {
+        let anomalies = self.wallets.entry(crate::utils::normalize_address(address)).or_default().record(kind, at);
+        for anomaly in &anomalies {
+            warn!(target: "audit", "Wallet {}: {}", address, anomaly);
+        }
+        anomalies
+    }
+}
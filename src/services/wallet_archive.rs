@@ -0,0 +1,262 @@
+//! Password-encrypted archive of a whole wallets directory, made by `backup create` and
+//! unpacked by `backup restore`. The archive is a JSON envelope holding the KDF parameters
+//! and an AES-256-GCM ciphertext of the archived files, so the same password and KDF cost
+//! as a keystore protect it and a damaged or tampered archive fails to decrypt.
+use crate::config::{self, wallet as settings};
+use crate::errors::{CryptographicError, FilesystemError, WalletError, WalletResult};
+use crate::models::keystore::KdfParams;
+use crate::services::crypto::KdfSettings;
+use crate::services::{keystore_backup, CryptoService};
+use crate::utils;
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use tracing::{debug, info, warn};
+use zeroize::Zeroizing;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    format: String,
+    version: u32,
+    kdf: KdfParams,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Contents {
+    created_at: String,
+    files: Vec<ArchivedFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivedFile {
+    name: String,
+    modified_ns: u64,
+    /// Whether the file loaded as a keystore when archived; checked again before restoring
+    keystore: bool,
+    data: String,
+}
+
+/// What restoring an archive did to each of its files
+#[derive(Debug, Default)]
+pub struct RestoreReport {
+    pub created_at: String,
+    pub restored: Vec<String>,
+    /// Already present with the same contents
+    pub unchanged: Vec<String>,
+}
+
+/// Encrypt every file of `dir` into a new archive at `out` and return the archived names.
+/// The wallet index, write temporaries and subdirectories are left out.
+pub async fn create(dir: &Path, out: &Path, password: &str, kdf: KdfSettings) -> WalletResult<Vec<String>> {
+    if out.exists() {
+        return Err(FilesystemError::FileExists {
+            path: out.display().to_string(),
+            suggestion: "choose another --out file; an existing archive is never overwritten".to_string(),
+        }
+        .into());
+    }
+
+    let not_accessible = |e: std::io::Error| FilesystemError::DirectoryNotAccessible {
+        path: dir.display().to_string(),
+        details: e.to_string(),
+    };
+    let mut files = Vec::new();
+    let mut entries = tokio::fs::read_dir(dir).await.map_err(not_accessible)?;
+    while let Some(entry) = entries.next_entry().await.map_err(not_accessible)? {
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()).map(str::to_string) else {
+            continue;
+        };
+        let metadata = entry.metadata().await?;
+        if name.starts_with('.') || path == out {
+            continue;
+        }
+        if !metadata.is_file() {
+            warn!("Not archiving {}: only files directly in the wallets directory are", path.display());
+            continue;
+        }
+        let data = Zeroizing::new(tokio::fs::read(&path).await?);
+        files.push(ArchivedFile {
+            keystore: std::str::from_utf8(&data).is_ok_and(|text| CryptoService::parse_any_keystore(text).is_ok()),
+            modified_ns: modified_ns(&metadata),
+            data: hex::encode(data.as_slice()),
+            name,
+        });
+    }
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    let names = files.iter().map(|file| file.name.clone()).collect();
+
+    let contents = Contents {
+        created_at: chrono::Utc::now().to_rfc3339(),
+        files,
+    };
+    let plaintext = Zeroizing::new(serde_json::to_vec(&contents)?);
+    let password = Zeroizing::new(password.to_string());
+    let envelope = utils::blocking(move || seal(&plaintext, &password, kdf)).await?;
+
+    utils::write_private(out, serde_json::to_string_pretty(&envelope)?.as_bytes()).await?;
+    info!("Archived {} to {}", dir.display(), out.display());
+    Ok(names)
+}
+
+/// Decrypt the archive at `archive` and write its files into `dir`. Nothing is written unless
+/// every file can be: without `force`, a file in `dir` that differs from the archived one and
+/// was modified after it stops the restore. Files that are replaced get a keystore backup
+/// first when backups are kept.
+pub async fn restore(dir: &Path, archive: &Path, password: &str, force: bool, keep: u32) -> WalletResult<RestoreReport> {
+    let data = tokio::fs::read_to_string(archive).await.map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => FilesystemError::FileNotFound {
+            path: archive.display().to_string(),
+            director: "pass the file written by `backup create --out`".to_string(),
+        }
+        .into(),
+        _ => WalletError::from(e),
+    })?;
+    let envelope: Envelope = serde_json::from_str(&data).map_err(|e| invalid(archive, e.to_string()))?;
+    if envelope.format != settings::ARCHIVE_FORMAT || envelope.version != settings::ARCHIVE_VERSION {
+        return Err(invalid(
+            archive,
+            format!("{} version {} is not a supported wallet archive", envelope.format, envelope.version),
+        ));
+    }
+    let password = Zeroizing::new(password.to_string());
+    let plaintext = utils::blocking(move || open(&envelope, &password)).await?;
+    let contents: Contents = serde_json::from_slice(&plaintext).map_err(|e| invalid(archive, e.to_string()))?;
+
+    let mut pending = Vec::new();
+    let mut report = RestoreReport {
+        created_at: contents.created_at,
+        ..RestoreReport::default()
+    };
+    let mut newer = Vec::new();
+    for file in contents.files {
+        if !is_plain_name(&file.name) {
+            return Err(invalid(archive, format!("unsafe file name {:?}", file.name)));
+        }
+        let data = Zeroizing::new(hex::decode(&file.data).map_err(|e| invalid(archive, e.to_string()))?);
+        if file.keystore && !std::str::from_utf8(&data).is_ok_and(|text| CryptoService::parse_any_keystore(text).is_ok()) {
+            return Err(invalid(archive, format!("{} is no longer a valid keystore", file.name)));
+        }
+
+        let target = dir.join(&file.name);
+        match tokio::fs::metadata(&target).await {
+            Ok(metadata) => {
+                if tokio::fs::read(&target).await?.as_slice() == data.as_slice() {
+                    report.unchanged.push(file.name);
+                    continue;
+                }
+                if modified_ns(&metadata) > file.modified_ns {
+                    newer.push(file.name.clone());
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        pending.push((target, file.name, data));
+    }
+    if !newer.is_empty() && !force {
+        return Err(FilesystemError::FileExists {
+            path: newer.join(", "),
+            suggestion: format!("{} changed after the archive was made; pass --force to overwrite", newer.join(", ")),
+        }
+        .into());
+    }
+
+    tokio::fs::create_dir_all(dir).await?;
+    for (target, name, data) in pending {
+        keystore_backup::snapshot(&target, keep).await?;
+        utils::write_private(&target, &data).await?;
+        debug!("Restored {}", target.display());
+        report.restored.push(name);
+    }
+    Ok(report)
+}
+
+fn seal(plaintext: &[u8], password: &str, kdf: KdfSettings) -> WalletResult<Envelope> {
+    let mut salt = vec![0u8; config::crypto::SALT_LENGTH];
+    let mut nonce = vec![0u8; config::crypto::NONCE_LENGTH];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let params = kdf.params(&salt);
+    let cipher = cipher(password, &salt, &params)?;
+    let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), plaintext).map_err(|e| {
+        CryptographicError::DecryptionFailed {
+            context: format!("Archive encryption failed: {}", e),
+        }
+    })?;
+    Ok(Envelope {
+        format: settings::ARCHIVE_FORMAT.to_string(),
+        version: settings::ARCHIVE_VERSION,
+        kdf: params,
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    })
+}
+
+fn open(envelope: &Envelope, password: &str) -> WalletResult<Zeroizing<Vec<u8>>> {
+    let corrupt = |e: hex::FromHexError| CryptographicError::DataCorruption { details: format!("Archive is not valid hex: {}", e) };
+    let salt = hex::decode(kdf_salt(&envelope.kdf)).map_err(corrupt)?;
+    let nonce = hex::decode(&envelope.nonce).map_err(corrupt)?;
+    let ciphertext = hex::decode(&envelope.ciphertext).map_err(corrupt)?;
+    if nonce.len() != config::crypto::NONCE_LENGTH {
+        return Err(CryptographicError::DataCorruption {
+            details: format!("Archive nonce is {} bytes, expected {}", nonce.len(), config::crypto::NONCE_LENGTH),
+        }
+        .into());
+    }
+
+    let cipher = cipher(password, &salt, &envelope.kdf)?;
+    let plaintext = cipher.decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref()).map_err(|_| {
+        CryptographicError::DecryptionFailed {
+            context: "Archive decryption failed: wrong password or damaged archive".to_string(),
+        }
+    })?;
+    Ok(Zeroizing::new(plaintext))
+}
+
+fn cipher(password: &str, salt: &[u8], params: &KdfParams) -> WalletResult<Aes256Gcm> {
+    let mut key = Zeroizing::new(vec![0u8; config::crypto::KEY_LENGTH]);
+    CryptoService::derive_key(password, salt, params, &mut key)?;
+    Aes256Gcm::new_from_slice(&key).map_err(|e| {
+        CryptographicError::KdfFailed {
+            details: format!("AES cipher creation failed: {}", e),
+        }
+        .into()
+    })
+}
+
+fn kdf_salt(params: &KdfParams) -> &str {
+    match params {
+        KdfParams::Argon2 { salt, .. } | KdfParams::Pbkdf2 { salt, .. } | KdfParams::Scrypt { salt, .. } => salt,
+    }
+}
+
+/// A name that stays inside the wallets directory
+fn is_plain_name(name: &str) -> bool {
+    let path = Path::new(name);
+    !name.starts_with('.') && path.file_name().is_some_and(|file_name| file_name == name)
+}
+
+fn modified_ns(metadata: &std::fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .unwrap_or(UNIX_EPOCH)
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+fn invalid(archive: &Path, details: String) -> WalletError {
+    FilesystemError::InvalidFormat {
+        path: archive.display().to_string(),
+        details,
+    }
+    .into()
+}
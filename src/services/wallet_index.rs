@@ -0,0 +1,129 @@
+//! Index of the wallets directory, kept in `.wallets.index` beside the keystores. It caches
+//! what `list` shows of each keystore, keyed by file name and checked against the file's size
+//! and modification time, so listing only reads keystores added or changed since the last run.
+//! Any save, rename or delete is picked up on the next refresh; the index is never the source
+//! of truth and a missing or unreadable one is rebuilt.
+use crate::config::wallet as settings;
+use crate::errors::{FilesystemError, WalletResult};
+use crate::models::command::WalletEntry;
+use crate::models::WalletType;
+use crate::services::address_format::{self, Chain};
+use crate::services::CryptoService;
+use crate::utils;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+/// FAT and some network filesystems store modification times in 2 second steps
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexFile {
+    version: u32,
+    files: BTreeMap<String, IndexedFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct IndexedFile {
+    len: u64,
+    modified_ns: u64,
+    /// None for a `.json` file that is not a native keystore, so it is not parsed again
+    wallet: Option<IndexedWallet>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct IndexedWallet {
+    address: String,
+    network: String,
+    created_at: String,
+    alias: Option<String>,
+    wallet_type: WalletType,
+}
+
+/// Native keystores in `dir` ordered by file name, from the index where it is current
+pub async fn refresh(dir: &Path) -> WalletResult<Vec<WalletEntry>> {
+    let index_path = dir.join(settings::INDEX_FILE_NAME);
+    let previous = read(&index_path).await;
+    let mut files = BTreeMap::new();
+
+    let not_accessible = |e: std::io::Error| FilesystemError::DirectoryNotAccessible {
+        path: dir.display().to_string(),
+        details: e.to_string(),
+    };
+    let mut entries = tokio::fs::read_dir(dir).await.map_err(not_accessible)?;
+    while let Some(entry) = entries.next_entry().await.map_err(not_accessible)? {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let (Some(filename), Ok(metadata)) = (path.file_name().and_then(|n| n.to_str()), entry.metadata().await) else {
+            continue;
+        };
+        let len = metadata.len();
+        let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+        let modified_ns = modified.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64);
+        // Filesystems with coarse timestamps can hide a second write in the same tick
+        let settled = SystemTime::now().duration_since(modified).is_ok_and(|age| age >= RACY_WINDOW);
+
+        let indexed = match previous.get(filename) {
+            Some(cached) if settled && cached.len == len && cached.modified_ns == modified_ns => cached.clone(),
+            _ => {
+                debug!("Indexing {}", path.display());
+                let wallet = CryptoService::load_keystore(&path).await.ok().map(|keystore| IndexedWallet {
+                    address: address_format::display(Chain::Evm, &keystore.metadata.address),
+                    network: keystore.metadata.network,
+                    created_at: keystore.metadata.created_at,
+                    alias: keystore.metadata.alias,
+                    wallet_type: keystore.metadata.wallet_type,
+                });
+                IndexedFile { len, modified_ns, wallet }
+            }
+        };
+        files.insert(filename.to_string(), indexed);
+    }
+
+    if files != previous {
+        let index = IndexFile { version: settings::INDEX_VERSION, files };
+        // Listing still works from a read-only directory, just without the cache
+        if let Err(e) = write(&index_path, &index).await {
+            warn!("Could not update the wallet index {}: {}", index_path.display(), e);
+        }
+        files = index.files;
+    }
+
+    Ok(files
+        .into_iter()
+        .filter_map(|(filename, file)| {
+            let wallet = file.wallet?;
+            Some(WalletEntry {
+                path: dir.join(&filename),
+                filename,
+                address: wallet.address,
+                network: wallet.network,
+                created_at: wallet.created_at,
+                alias: wallet.alias,
+                wallet_type: wallet.wallet_type,
+            })
+        })
+        .collect())
+}
+
+/// Entries of the index at `path`; empty when it is missing, unreadable or of another version
+async fn read(path: &Path) -> BTreeMap<String, IndexedFile> {
+    let Ok(data) = tokio::fs::read_to_string(path).await else {
+        return BTreeMap::new();
+    };
+    match serde_json::from_str::<IndexFile>(&data) {
+        Ok(index) if index.version == settings::INDEX_VERSION => index.files,
+        _ => {
+            debug!("Rebuilding the wallet index {}", path.display());
+            BTreeMap::new()
+        }
+    }
+}
+
+async fn write(path: &Path, index: &IndexFile) -> WalletResult<()> {
+    utils::write_atomic(path, serde_json::to_string(index)?.as_bytes()).await
+}
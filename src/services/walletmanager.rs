@@ -1,48 +1,474 @@
-use ethers::signers::coins_bip39::mnemonic;
-
-use crate::errors::{WalletResult};
-use crate::models::{keystore, Address, Wallet};
-use crate::services::{crypto::CryptoService, mnemonic::MnemonicService};
+use crate::config;
+use crate::errors::{AuthenticationError, CryptographicError, FilesystemError, UserInputError, ValidationError, WalletError, WalletResult};
+use crate::i18n::tr;
+use crate::models::command::ListOutcome;
+use crate::models::keystore::{upgrade_json, KdfAlgorithm, KeystoreVersion};
+use crate::models::{Keystore, KeystoreFile, KeystoreV3, Wallet, WalletType};
+use crate::services::commands::{self, SaveOptions};
+use crate::services::{keystore_backup, unlock_throttle};
+use crate::services::{chains::ChainInfo, cold::ColdStoragePolicy, crypto::{CryptoService, KdfSettings}, mnemonic::MnemonicService, shamir::ShamirService};
+use crate::utils;
 use crate::WalletConfig;
-use std::path::Path;
+use bip39::Language;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+use zeroize::Zeroizing;
 
+/// Creates, imports, saves and loads wallets under a `WalletConfig`. Library users without a
+/// config file start from the defaults:
+///
+/// ```no_run
+/// # use web3wallet_cli::WalletManager;
+/// # use web3wallet_cli::models::keystore::KdfAlgorithm;
+/// let manager = WalletManager::default()
+///     .with_network("sepolia")
+///     .with_wallet_dir("./wallets")
+///     .with_kdf(KdfAlgorithm::Scrypt);
+/// ```
 pub struct WalletManager {
     config: WalletConfig,
 }
 
+impl Default for WalletManager {
+    fn default() -> Self {
+        Self::new(WalletConfig::default())
+    }
+}
+
 impl WalletManager {
     pub fn new(config: WalletConfig) -> Self {
         Self { config }
     }
 
+    /// Network of wallets created or imported from now on
+    pub fn with_network(mut self, network: &str) -> Self {
+        self.config.network = network.to_string();
+        self
+    }
+
+    /// Directory wallet names resolve in and `list` reads
+    pub fn with_wallet_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.wallets_path = dir.into();
+        self
+    }
+
+    /// KDF of keystores saved from now on, at the configured cost for that algorithm
+    pub fn with_kdf(mut self, kdf: KdfAlgorithm) -> Self {
+        self.config.kdf = kdf;
+        self
+    }
+
+    pub fn config(&self) -> &WalletConfig {
+        &self.config
+    }
+
+    /// See `WalletConfig::wallet_path`
+    pub fn wallet_path(&self, name: &str) -> PathBuf {
+        self.config.wallet_path(name)
+    }
+
+    /// See `WalletConfig::save_path`
+    pub fn save_path(&self, name: &str) -> PathBuf {
+        self.config.save_path(name)
+    }
+
+    /// Where and how to save a wallet as `name`, reading the new password and its confirmation
+    /// from `prompt`, which is given the localized prompt text
+    pub fn save_options(
+        &self,
+        name: &str,
+        cold: bool,
+        backup_verified: bool,
+        mut prompt: impl FnMut(&str) -> WalletResult<String>,
+    ) -> WalletResult<SaveOptions> {
+        let password = prompt(&tr("prompt-new-wallet-password"))?;
+        let confirm = Zeroizing::new(prompt(&tr("prompt-confirm-password"))?);
+        if password != *confirm {
+            return Err(UserInputError::PasswordMismatch.into());
+        }
+        Ok(SaveOptions { path: self.save_path(name), password, cold, backup_verified })
+    }
+
+    /// Native keystores in the wallets directory, creating it when it does not exist yet
+    pub async fn list(&self) -> WalletResult<ListOutcome> {
+        commands::list(&self.config.wallets_path).await
+    }
+
     pub async fn create_wallet(&self, word_count: u8) -> WalletResult<Wallet> {
         let mnemonic= MnemonicService::generate(word_count)?;
+        MnemonicService::check_entropy_history(&self.config.state_path, word_count).await?;
         Wallet::from_mnemonic(mnemonic.phrase(), &self.config.network, None)
     }
 
     pub async fn create_wallet_with_network(&self, word_count: u8, network: &str) -> WalletResult<Wallet> {
+        ChainInfo::resolve(network, &self.config)?;
         let mnemonic= MnemonicService::generate(word_count)?;
+        MnemonicService::check_entropy_history(&self.config.state_path, word_count).await?;
         Wallet::from_mnemonic(mnemonic.phrase(), network, None)
     }
 
+    pub async fn create_wallet_with_passphrase(&self, word_count: u8, network: &str, passphrase: &str) -> WalletResult<Wallet> {
+        ChainInfo::resolve(network, &self.config)?;
+        let mnemonic= MnemonicService::generate(word_count)?;
+        MnemonicService::check_entropy_history(&self.config.state_path, word_count).await?;
+        Wallet::from_mnemonic_with_passphrase(mnemonic.phrase(), passphrase, network, None)
+    }
+
+    /// New wallet whose mnemonic comes from the `language` wordlist, with an optional BIP39 passphrase;
+    /// `user_entropy` is mixed into the OS randomness, see `MnemonicService::generate_mixed`
+    pub async fn create_wallet_in_language(&self, word_count: u8, language: Language, user_entropy: Option<&[u8]>, network: &str, passphrase: Option<&str>) -> WalletResult<Wallet> {
+        ChainInfo::resolve(network, &self.config)?;
+        let mnemonic = MnemonicService::generate_mixed(word_count, language, user_entropy)?;
+        MnemonicService::check_entropy_history(&self.config.state_path, word_count).await?;
+        Wallet::from_mnemonic_with_passphrase(mnemonic.phrase(), passphrase.unwrap_or(""), network, None)
+    }
+
+    pub async fn import_from_mnemonic_with_passphrase(&self, mnemonic_str: &str, passphrase: &str) -> WalletResult<Wallet> {
+        let mnemonic = MnemonicService::validate(mnemonic_str)?;
+        Wallet::from_mnemonic_with_passphrase(mnemonic.phrase(), passphrase, &self.config.network, None)
+    }
+
     pub async fn import_from_mnemoic(&self, mnemonic_str: &str) -> WalletResult<Wallet> {
         let mnemonic = MnemonicService::validate(mnemonic_str)?;
         Wallet::from_mnemonic(mnemonic.phrase(), &self.config.network, None)
     }
 
+    /// Import a v3 keystore exported by geth, MyEtherWallet or MetaMask
+    pub async fn import_from_keystore(&self, path: &Path, password: &str) -> WalletResult<Wallet> {
+        self.load_wallet(path, password).await
+    }
+
+    /// Import an HD wallet from its BIP32 root key, for users without the mnemonic
+    pub async fn import_from_xprv(&self, xprv: &str) -> WalletResult<Wallet> {
+        Wallet::from_xprv(xprv, &self.config.network, None)
+    }
+
+    /// Import an HD wallet from a raw BIP39 seed, hex encoded
+    pub async fn import_from_seed_hex(&self, seed_hex: &str) -> WalletResult<Wallet> {
+        Wallet::from_seed_hex(seed_hex, &self.config.network, None)
+    }
+
+    /// HD wallet from the BIP32 seed held by a threshold of SLIP-39 shares
+    pub async fn import_from_shamir(&self, shares: &[String], passphrase: &str) -> WalletResult<Wallet> {
+        let seed = ShamirService::combine(shares, passphrase)?;
+        Wallet::from_seed(&seed, &self.config.network, None)
+    }
+
     pub async fn import_from_private_key(&self, private_key: &str) -> WalletResult<Wallet> {
         Wallet::from_private_key(private_key, &self.config.network, None)
     }
 
+    /// Watch-only wallet deriving the addresses of an account-level xpub
+    pub async fn import_watch_only_xpub(&self, xpub: &str) -> WalletResult<Wallet> {
+        Wallet::watch_only_from_xpub(xpub, &self.config.network, None)
+    }
+
+    /// Watch-only wallet for one address
+    pub async fn import_watch_only_address(&self, address: &str) -> WalletResult<Wallet> {
+        Wallet::watch_only_from_address(address, &self.config.network, None)
+    }
+
     pub async fn save_wallet(&self, wallet: &Wallet, path: &Path, password: &str) -> WalletResult<()>{
-        CryptoService::validate_password(password)?;
-        let keystore = CryptoService::encrypt_wallet(wallet, password, true)?;
-        CryptoService::save_keystore(&keystore, path).await
+        CryptoService::check_password(password, self.config.allow_weak_passwords)?;
+        let mut keystore = self.encrypt(wallet, password).await?;
+        keystore.metadata.chain_id = self.chain_id_of(wallet.network());
+        self.write_keystore(path, keystore.to_json()?.as_bytes()).await
+    }
+
+    /// Save a wallet marked as cold storage; see `ColdStoragePolicy`
+    pub async fn save_cold_wallet(&self, wallet: &Wallet, path: &Path, password: &str, backup_verified: bool) -> WalletResult<()>{
+        ColdStoragePolicy::check_save(wallet, backup_verified)?;
+        CryptoService::check_password(password, self.config.allow_weak_passwords)?;
+        let mut keystore = self.encrypt(wallet, password).await?;
+        keystore.metadata.chain_id = self.chain_id_of(wallet.network());
+        keystore.metadata.cold = true;
+        keystore.metadata.backup_verified = backup_verified;
+        self.write_keystore(path, keystore.to_json()?.as_bytes()).await
     }
 
+    /// Chain id the keystore at `path` is bound to. Keystores saved before chain ids were
+    /// recorded fall back to their network name; v3 keystores are not bound to any chain.
+    pub async fn keystore_chain_id(&self, path: &Path) -> WalletResult<Option<u64>> {
+        Ok(match CryptoService::load_any_keystore(path).await? {
+            KeystoreFile::Native(keystore) => keystore
+                .metadata
+                .chain_id
+                .or_else(|| self.chain_id_of(&keystore.metadata.network)),
+            KeystoreFile::V3(_) => None,
+        })
+    }
+
+    /// Refuse to sign for `chain_id` with the keystore at `path` if it is bound to another chain
+    pub async fn check_signing_chain(&self, path: &Path, chain_id: u64) -> WalletResult<()> {
+        match self.keystore_chain_id(path).await? {
+            Some(expected) if expected != chain_id => Err(ValidationError::ChainMismatch {
+                expected,
+                requested: chain_id,
+            }
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Refuse to sign with a watch-only keystore, before its password is asked for
+    pub async fn check_can_sign(&self, path: &Path) -> WalletResult<()> {
+        match CryptoService::load_any_keystore(path).await? {
+            KeystoreFile::Native(keystore) if keystore.metadata.wallet_type == WalletType::WatchOnly => {
+                Err(AuthenticationError::WatchOnlyWallet {
+                    address: keystore.metadata.address,
+                }
+                .into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Replace the keystore at `path` atomically with one readable by the owner only, first
+    /// copying the old one aside when `keystore_backups` asks for it
+    pub async fn write_keystore(&self, path: &Path, contents: &[u8]) -> WalletResult<()> {
+        keystore_backup::snapshot(path, self.config.keystore_backups).await?;
+        utils::write_private(path, contents).await
+    }
+
+    /// `wallet` encrypted under the configured KDF, off the async runtime
+    async fn encrypt(&self, wallet: &Wallet, password: &str) -> WalletResult<Keystore> {
+        let kdf = KdfSettings::from_config(&self.config);
+        let (wallet, password) = (wallet.clone(), Zeroizing::new(password.to_string()));
+        utils::blocking(move || CryptoService::encrypt_wallet_with(&wallet, &password, kdf)).await
+    }
+
+    async fn decrypt(keystore: &Keystore, password: &str) -> WalletResult<Wallet> {
+        let (keystore, password) = (keystore.clone(), Zeroizing::new(password.to_string()));
+        utils::blocking(move || CryptoService::decrypt_wallet(&keystore, &password)).await
+    }
+
+    async fn decrypt_v3(keystore: &KeystoreV3, password: &str) -> WalletResult<Zeroizing<Vec<u8>>> {
+        let (keystore, password) = (keystore.clone(), Zeroizing::new(password.to_string()));
+        utils::blocking(move || CryptoService::decrypt_v3(&keystore, &password)).await
+    }
+
+    /// Run `attempt`, a decryption of the keystore at `path`, under the unlock throttle: refused
+    /// while the keystore is locked out, a wrong password counted and reported as
+    /// `WrongPassword`, the right one clearing the count
+    async fn throttled<T>(&self, path: &Path, attempt: impl std::future::Future<Output = WalletResult<T>>) -> WalletResult<T> {
+        if !self.config.unlock_throttle {
+            return attempt.await;
+        }
+        unlock_throttle::check(path).await?;
+        match attempt.await {
+            Err(WalletError::Cryptographic(CryptographicError::DecryptionFailed { .. })) => {
+                let attempts_remaining = unlock_throttle::record_failure(path).await;
+                Err(AuthenticationError::WrongPassword {
+                    wallet_file: path.display().to_string(),
+                    attempts_remaining,
+                }
+                .into())
+            }
+            Ok(value) => {
+                unlock_throttle::clear(path).await;
+                Ok(value)
+            }
+            result => result,
+        }
+    }
+
+    fn chain_id_of(&self, network: &str) -> Option<u64> {
+        ChainInfo::resolve(network, &self.config).ok().map(|chain| chain.chain_id)
+    }
+
+    /// Warn when other users can read the keystore at `path`, or refuse it when
+    /// `strict_permissions` is set
+    pub async fn check_permissions(&self, path: &Path) -> WalletResult<()> {
+        // A missing or unreadable file is reported by whatever loads it
+        let Ok(Some(mode)) = utils::open_permissions(path).await else {
+            return Ok(());
+        };
+        if self.config.strict_permissions {
+            return Err(FilesystemError::PermissionDenied {
+                path: path.display().to_string(),
+                operation: format!(
+                    "unlock a keystore readable by other users ({:o}); chmod {:o} it or run `web3wallet doctor --fix`",
+                    mode,
+                    config::fs::KEYSTORE_FILE_PERMISSIONS
+                ),
+            }
+            .into());
+        }
+        warn!(
+            "Keystore {} is readable by other users ({:o}), consider chmod {:o}",
+            path.display(),
+            mode,
+            config::fs::KEYSTORE_FILE_PERMISSIONS
+        );
+        Ok(())
+    }
+
+    /// Load a native or v3 (geth/MetaMask) keystore. A native keystore with several accounts
+    /// loads as its selected account; see `load_accounts`.
     pub async fn load_wallet(&self, path: &Path, password: &str) -> WalletResult<Wallet>{
-        let keystore = CryptoService::load_keystore(path).await?;
-        CryptoService::decrypt_wallet(&keystore, password)
+        self.load_wallet_with_passphrase(path, password, None).await
+    }
+
+    /// `load_wallet` with the BIP39 passphrase already at hand, for callers that cannot
+    /// prompt; without one the `passphrase_prompt` is asked as usual
+    pub async fn load_wallet_with_passphrase(&self, path: &Path, password: &str, passphrase: Option<&str>) -> WalletResult<Wallet> {
+        self.check_permissions(path).await?;
+        match CryptoService::load_any_keystore(path).await? {
+            KeystoreFile::Native(keystore) => {
+                ColdStoragePolicy::check_decrypt(&keystore, self.config.offline)?;
+                let mut wallet = self.throttled(path, Self::decrypt(&keystore, password)).await?;
+                self.unlock_passphrase(&mut wallet, passphrase)?;
+                // The plaintext alias can be changed without the password, it wins over the payload's copy
+                wallet.set_alias(keystore.metadata.alias.clone());
+                wallet.active_account()
+            }
+            KeystoreFile::V3(keystore) => {
+                let private_key = self.throttled(path, Self::decrypt_v3(&keystore, password)).await?;
+                Wallet::from_private_key(&hex::encode(private_key.as_slice()), &self.config.network, None)
+            }
+        }
+    }
+
+    /// Decrypt the native keystore at `path` with every account in it, for `account` to
+    /// add or select one and write it back with `save_accounts`
+    pub async fn load_accounts(&self, path: &Path, password: &str) -> WalletResult<(Keystore, Wallet)> {
+        self.check_permissions(path).await?;
+        let keystore = match CryptoService::load_any_keystore(path).await? {
+            KeystoreFile::Native(keystore) => *keystore,
+            KeystoreFile::V3(_) => {
+                return Err(ValidationError::InvalidKeystoreSchema {
+                    error: "v3 keystores hold a single key, import the wallet to add accounts".to_string(),
+                    file_path: path.display().to_string(),
+                }
+                .into())
+            }
+        };
+        ColdStoragePolicy::check_decrypt(&keystore, self.config.offline)?;
+        let mut wallet = self.throttled(path, Self::decrypt(&keystore, password)).await?;
+        self.unlock_passphrase(&mut wallet, None)?;
+        Ok((keystore, wallet))
+    }
+
+    /// Supply the passphrase a decrypted wallet's seed takes, asking `passphrase_prompt`
+    /// unless the caller already has it
+    fn unlock_passphrase(&self, wallet: &mut Wallet, passphrase: Option<&str>) -> WalletResult<()> {
+        if !wallet.needs_passphrase() {
+            return Ok(());
+        }
+        if let Some(passphrase) = passphrase {
+            return wallet.unlock_passphrase(passphrase);
+        }
+        let Some(prompt) = self.config.passphrase_prompt else {
+            return Err(AuthenticationError::WrongPassphrase { address: wallet.address().to_string() }.into());
+        };
+        let passphrase = Zeroizing::new(prompt(&tr("prompt-bip39-passphrase"))?);
+        wallet.unlock_passphrase(&passphrase)
+    }
+
+    /// Re-encrypt `wallet`, loaded from `keystore` by `load_accounts`, under the same password
+    /// and write it back. The metadata is kept except for the address, which shows the
+    /// selected account.
+    pub async fn save_accounts(&self, path: &Path, keystore: Keystore, wallet: &Wallet, password: &str) -> WalletResult<()> {
+        let mut updated = self.encrypt(wallet, password).await?;
+        updated.metadata = keystore.metadata;
+        updated.metadata.address = wallet.active_account()?.address().to_string();
+        self.write_keystore(path, updated.to_json()?.as_bytes()).await
+    }
+
+    /// Whether `path` holds a keystore marked as cold storage
+    pub async fn is_cold_wallet(&self, path: &Path) -> bool {
+        matches!(
+            CryptoService::load_any_keystore(path).await,
+            Ok(KeystoreFile::Native(keystore)) if keystore.metadata.cold
+        )
+    }
+
+    /// Re-encrypt the keystore at `path` under `new_password` and replace it atomically.
+    /// Native keystores pick up the configured KDF and keep their metadata; v3 keystores
+    /// stay v3 with the standard scrypt parameters.
+    pub async fn change_password(&self, path: &Path, old_password: &str, new_password: &str) -> WalletResult<KeystoreFile> {
+        CryptoService::check_password(new_password, self.config.allow_weak_passwords)?;
+
+        let updated = match CryptoService::load_any_keystore(path).await? {
+            KeystoreFile::Native(keystore) => {
+                ColdStoragePolicy::check_decrypt(&keystore, self.config.offline)?;
+                let wallet = self.throttled(path, Self::decrypt(&keystore, old_password)).await?;
+                let mut updated = self.encrypt(&wallet, new_password).await?;
+                updated.metadata = keystore.metadata;
+                // Re-encrypting under the same password, as `migrate --rekey` does, is no change
+                if old_password != new_password {
+                    updated.metadata.password_changed_at = Some(chrono::Utc::now().to_rfc3339());
+                }
+                self.write_keystore(path, updated.to_json()?.as_bytes()).await?;
+                KeystoreFile::Native(Box::new(updated))
+            }
+            KeystoreFile::V3(keystore) => {
+                let private_key = self.throttled(path, Self::decrypt_v3(&keystore, old_password)).await?;
+                let wallet = Wallet::from_private_key(&hex::encode(private_key.as_slice()), &self.config.network, None)?;
+                let (address, new_password) = (wallet.address().to_string(), Zeroizing::new(new_password.to_string()));
+                let updated = utils::blocking(move || CryptoService::encrypt_v3(&private_key, &address, &new_password, false)).await?;
+                self.write_keystore(path, serde_json::to_string_pretty(&updated)?.as_bytes()).await?;
+                KeystoreFile::V3(Box::new(updated))
+            }
+        };
+        Ok(updated)
+    }
+
+    /// Upgrade the native keystore at `path` to the latest schema version, rewriting it atomically
+    /// unless `dry_run`. Returns the upgraded keystore and the version it was stored as.
+    pub async fn migrate_keystore(&self, path: &Path, dry_run: bool) -> WalletResult<(Keystore, KeystoreVersion)> {
+        let data = tokio::fs::read_to_string(path).await.map_err(|e| {
+            CryptographicError::DataCorruption { details: format!("Failed to read keystore file: {}", e) }
+        })?;
+        let mut value: serde_json::Value = serde_json::from_str(&data).map_err(|e| {
+            CryptographicError::DataCorruption { details: format!("Failed to parse keystore JSON: {}", e) }
+        })?;
+        if value.get("version").and_then(|v| v.as_u64()).is_some() {
+            return Err(ValidationError::InvalidKeystoreSchema {
+                error: "v3 keystores have no schema versions to migrate, use passwd to re-encrypt one".to_string(),
+                file_path: path.display().to_string(),
+            }
+            .into());
+        }
+
+        let original = upgrade_json(&mut value)?;
+        let keystore: Keystore = serde_json::from_value(value).map_err(|e| ValidationError::InvalidKeystoreSchema {
+            error: format!("Upgraded keystore does not parse: {}", e),
+            file_path: path.display().to_string(),
+        })?;
+        keystore.validate()?;
+
+        if original < KeystoreVersion::LATEST && !dry_run {
+            self.write_keystore(path, keystore.to_json()?.as_bytes()).await?;
+        }
+        Ok((keystore, original))
+    }
+
+    /// True when `keystore` was derived with a different KDF, or a cheaper one, than configured
+    pub fn kdf_outdated(&self, keystore: &Keystore) -> bool {
+        !KdfSettings::from_config(&self.config).satisfied_by(keystore.kdf_params())
+    }
+
+    /// Every readable keystore in the wallets directory; unparsable files are skipped
+    pub async fn list_keystores(&self) -> WalletResult<Vec<(PathBuf, Keystore)>> {
+        let mut keystores = Vec::new();
+        let mut entries = match tokio::fs::read_dir(&self.config.wallets_path).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(keystores),
+            Err(e) => return Err(e.into()),
+        };
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            if let Ok(keystore) = CryptoService::load_keystore(&path).await {
+                keystores.push((path, keystore));
+            }
+        }
+        keystores.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(keystores)
     }
 
     
@@ -0,0 +1,18 @@
+use crate::services::transaction::encode_call;
+use ethers::abi::Token as AbiToken;
+use ethers::types::U256;
+
+/// Calls on a WETH9-style wrapped native token
+pub struct WrappedNative;
+
+impl WrappedNative {
+    /// `deposit()`, wraps the transaction value
+    pub fn deposit() -> Vec<u8> {
+        encode_call("deposit()", &[])
+    }
+
+    /// `withdraw(uint256)`, unwraps `amount` wei back to the native currency
+    pub fn withdraw(amount: U256) -> Vec<u8> {
+        encode_call("withdraw(uint256)", &[AbiToken::Uint(amount)])
+    }
+}
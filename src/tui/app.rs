@@ -0,0 +1,273 @@
+//! State of the tui and what each key does to it
+use super::form::{Form, FormAction, FormKind};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::path::{Path, PathBuf};
+use web3wallet_cli::errors::{FilesystemError, UserInputError};
+use web3wallet_cli::models::command::{DeriveOutcome, WalletEntry};
+use web3wallet_cli::models::KeystoreFile;
+use web3wallet_cli::services::commands::{self, ImportSource, SaveOptions};
+use web3wallet_cli::services::crypto::CryptoService;
+use web3wallet_cli::services::signer::SoftwareSigner;
+use web3wallet_cli::services::usage::UsageKind;
+use web3wallet_cli::{WalletConfig, WalletError, WalletManager, WalletResult};
+use zeroize::Zeroizing;
+
+/// Window over the wallet list; only one is open at a time
+pub enum Modal {
+    Form(Form),
+    /// Mnemonic of the wallet just created, shown this once
+    Mnemonic { address: String, mnemonic: Zeroizing<String>, saved_to: PathBuf },
+    Derived(DeriveOutcome),
+}
+
+pub struct Status {
+    pub text: String,
+    pub error: bool,
+}
+
+pub struct App {
+    config: WalletConfig,
+    manager: WalletManager,
+    pub wallets: Vec<WalletEntry>,
+    pub selected: usize,
+    /// Keystore metadata of the selected wallet, as label and value
+    pub details: Vec<(&'static str, String)>,
+    pub modal: Option<Modal>,
+    pub status: Option<Status>,
+    pub quit: bool,
+}
+
+impl App {
+    pub async fn new(config: &WalletConfig) -> WalletResult<Self> {
+        let mut app = Self {
+            config: config.clone(),
+            manager: WalletManager::new(config.clone()),
+            wallets: Vec::new(),
+            selected: 0,
+            details: Vec::new(),
+            modal: None,
+            status: None,
+            quit: false,
+        };
+        app.refresh().await?;
+        Ok(app)
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.config.wallets_path
+    }
+
+    pub fn selected_wallet(&self) -> Option<&WalletEntry> {
+        self.wallets.get(self.selected)
+    }
+
+    /// Re-read the wallets directory, keeping the selection on the same file if it is still there
+    async fn refresh(&mut self) -> WalletResult<()> {
+        let current = self.selected_wallet().map(|w| w.path.clone());
+        self.wallets = commands::list(&self.config.wallets_path).await?.wallets;
+        self.wallets.sort_by(|a, b| a.filename.cmp(&b.filename));
+        self.selected = current
+            .and_then(|path| self.wallets.iter().position(|w| w.path == path))
+            .unwrap_or(0)
+            .min(self.wallets.len().saturating_sub(1));
+        self.load_details().await;
+        Ok(())
+    }
+
+    async fn select(&mut self, selected: usize) {
+        if selected != self.selected && selected < self.wallets.len() {
+            self.selected = selected;
+            self.load_details().await;
+        }
+    }
+
+    async fn load_details(&mut self) {
+        self.details.clear();
+        let Some(wallet) = self.selected_wallet() else {
+            return;
+        };
+        let path = wallet.path.clone();
+        let mut details = vec![
+            ("File", path.display().to_string()),
+            ("Address", wallet.address.clone()),
+            ("Alias", wallet.alias.clone().unwrap_or_else(|| "-".to_string())),
+            ("Network", wallet.network.clone()),
+            ("Type", wallet.wallet_type.as_str().to_string()),
+            ("Created", wallet.created_at.clone()),
+        ];
+        match CryptoService::load_any_keystore(&path).await {
+            Ok(KeystoreFile::Native(keystore)) => {
+                let metadata = &keystore.metadata;
+                if let Some(chain_id) = metadata.chain_id {
+                    details.push(("Chain ID", chain_id.to_string()));
+                }
+                details.push(("Password set", metadata.password_changed_at.clone().unwrap_or_else(|| metadata.created_at.clone())));
+                details.push(("KDF", keystore.crypto.kdf.clone()));
+                details.push(("Cold", yes_no(metadata.cold).to_string()));
+                details.push(("Backup verified", yes_no(metadata.backup_verified).to_string()));
+            }
+            Ok(KeystoreFile::V3(_)) => details.push(("Format", "v3".to_string())),
+            Err(e) => self.status = Some(Status { text: e.to_string(), error: true }),
+        }
+        self.details = details;
+    }
+
+    pub async fn handle_key(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.quit = true;
+            return;
+        }
+
+        match self.modal.take() {
+            Some(Modal::Form(mut form)) => match form.handle_key(key) {
+                FormAction::Continue => self.modal = Some(Modal::Form(form)),
+                FormAction::Cancel => {}
+                FormAction::Submit => self.submit(form).await,
+            },
+            Some(modal @ (Modal::Mnemonic { .. } | Modal::Derived(_))) => {
+                if !matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')) {
+                    self.modal = Some(modal);
+                }
+            }
+            None => self.browse(key).await,
+        }
+    }
+
+    async fn browse(&mut self, key: KeyEvent) {
+        self.status = None;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)).await,
+            KeyCode::Down | KeyCode::Char('j') => self.select(self.selected + 1).await,
+            KeyCode::Home => self.select(0).await,
+            KeyCode::End => self.select(self.wallets.len().saturating_sub(1)).await,
+            KeyCode::Char('c') => self.modal = Some(Modal::Form(Form::create())),
+            KeyCode::Char('i') => self.modal = Some(Modal::Form(Form::import())),
+            KeyCode::Char('d') => match self.selected_wallet() {
+                Some(wallet) => self.modal = Some(Modal::Form(Form::derive(wallet.path.clone(), &wallet.filename))),
+                None => self.status = Some(Status { text: "No wallet selected".to_string(), error: true }),
+            },
+            KeyCode::Char('r') => {
+                if let Err(e) = self.refresh().await {
+                    self.status = Some(Status { text: e.to_string(), error: true });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Run a submitted form; on failure it stays open with the error and its secrets cleared
+    async fn submit(&mut self, mut form: Form) {
+        let result = match &form.kind {
+            FormKind::Create => self.create(&form).await,
+            FormKind::Import => self.import(&form).await,
+            FormKind::Derive(path) => self.derive(path, &form).await,
+        };
+        match result {
+            Ok(modal) => {
+                self.modal = modal;
+                if let Err(e) = self.refresh().await {
+                    self.status = Some(Status { text: e.to_string(), error: true });
+                }
+            }
+            Err(e) => {
+                form.fail(match e.suggestion() {
+                    Some(hint) => format!("{} ({})", e, hint),
+                    None => e.to_string(),
+                });
+                self.modal = Some(Modal::Form(form));
+            }
+        }
+    }
+
+    async fn create(&mut self, form: &Form) -> WalletResult<Option<Modal>> {
+        let words = form.value("Words").trim().parse::<u8>().map_err(|_| UserInputError::InvalidParameters {
+            parameter: "words".to_string(),
+            value: form.value("Words").to_string(),
+            expected: "12, 15, 18, 21 or 24".to_string(),
+        })?;
+        let save = self.save_options(form)?;
+
+        let language = bip39::Language::English;
+        let wallet = commands::new_wallet(&self.manager, words, language, None, &self.config.network, None, None).await?;
+        let outcome = commands::save_created(&self.manager, &wallet, language, Some(save)).await?;
+        self.status = Some(Status { text: format!("Created {}", outcome.address), error: false });
+        Ok(Some(Modal::Mnemonic {
+            address: outcome.address,
+            mnemonic: Zeroizing::new(outcome.mnemonic.clone()),
+            saved_to: outcome.saved_to.unwrap_or_default(),
+        }))
+    }
+
+    async fn import(&mut self, form: &Form) -> WalletResult<Option<Modal>> {
+        let phrase = form.value("Mnemonic").split_whitespace().collect::<Vec<_>>().join(" ");
+        let save = self.save_options(form)?;
+
+        let source = ImportSource::Mnemonic { phrase, passphrase: None };
+        let outcome = commands::import(&self.manager, source, None, Some(save)).await?;
+        self.status = Some(Status { text: format!("Imported {}", outcome.address), error: false });
+        Ok(None)
+    }
+
+    async fn derive(&mut self, path: &Path, form: &Form) -> WalletResult<Option<Modal>> {
+        let count = form
+            .value("Count")
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|n| (1..=web3wallet_cli::config::tui::MAX_DERIVE_COUNT).contains(n))
+            .ok_or_else(|| UserInputError::InvalidParameters {
+                parameter: "count".to_string(),
+                value: form.value("Count").to_string(),
+                expected: format!("a number from 1 to {}", web3wallet_cli::config::tui::MAX_DERIVE_COUNT),
+            })?;
+
+        let passphrase = Some(form.value("Passphrase")).filter(|passphrase| !passphrase.is_empty());
+        let wallet = self.manager.load_wallet_with_passphrase(path, form.value("Password"), passphrase).await?;
+        crate::record_usage(&self.config, wallet.address(), &[UsageKind::Unlock]).await;
+        if !wallet.is_hd() {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "wallet".to_string(),
+                value: if wallet.is_watch_only() { "single address" } else { "private key only" }.to_string(),
+                expected: "HD wallet with mnemonic, root key or xpub".to_string(),
+            }
+            .into());
+        }
+        let outcome = commands::derive(&SoftwareSigner::new(wallet), 0, count).await?;
+        Ok(Some(Modal::Derived(outcome)))
+    }
+
+    /// Where the form's `Name` is saved, under the password it was given twice
+    fn save_options(&self, form: &Form) -> WalletResult<SaveOptions> {
+        let name = form.value("Name").trim();
+        if name.is_empty() || name.contains('/') || name.contains('\\') {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "name".to_string(),
+                value: name.to_string(),
+                expected: "a file name inside the wallets directory".to_string(),
+            }
+            .into());
+        }
+        if form.value("Password") != form.value("Confirm password") {
+            return Err(WalletError::UserInput(UserInputError::PasswordMismatch));
+        }
+
+        let path = self.manager.save_path(name);
+        if path.exists() {
+            return Err(FilesystemError::FileExists {
+                path: path.display().to_string(),
+                suggestion: "choose another name or delete the existing wallet first".to_string(),
+            }
+            .into());
+        }
+        Ok(SaveOptions { path, password: form.value("Password").to_string(), cold: false, backup_verified: false })
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
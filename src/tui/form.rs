@@ -0,0 +1,132 @@
+//! Modal forms of the tui: a column of single-line fields, secrets masked as they are typed
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::path::PathBuf;
+use zeroize::Zeroizing;
+
+pub enum FormKind {
+    Create,
+    Import,
+    /// Unlock the keystore at this path and list its addresses
+    Derive(PathBuf),
+}
+
+pub struct Field {
+    pub label: &'static str,
+    pub value: Zeroizing<String>,
+    /// Rendered as `*`, for passwords and mnemonics
+    pub masked: bool,
+}
+
+impl Field {
+    fn plain(label: &'static str, value: &str) -> Self {
+        Self { label, value: Zeroizing::new(value.to_string()), masked: false }
+    }
+
+    fn masked(label: &'static str) -> Self {
+        Self { label, value: Zeroizing::new(String::new()), masked: true }
+    }
+
+    /// What the field shows on screen
+    pub fn display(&self) -> String {
+        if self.masked {
+            "*".repeat(self.value.chars().count())
+        } else {
+            self.value.to_string()
+        }
+    }
+}
+
+pub enum FormAction {
+    Continue,
+    Submit,
+    Cancel,
+}
+
+pub struct Form {
+    pub kind: FormKind,
+    pub title: String,
+    pub fields: Vec<Field>,
+    pub focus: usize,
+    /// Why the last submit failed, shown under the fields
+    pub error: Option<String>,
+}
+
+impl Form {
+    pub fn create() -> Self {
+        Self::new(
+            FormKind::Create,
+            "Create wallet".to_string(),
+            vec![
+                Field::plain("Name", ""),
+                Field::plain("Words", "12"),
+                Field::masked("Password"),
+                Field::masked("Confirm password"),
+            ],
+        )
+    }
+
+    pub fn import() -> Self {
+        Self::new(
+            FormKind::Import,
+            "Import mnemonic".to_string(),
+            vec![
+                Field::masked("Mnemonic"),
+                Field::plain("Name", ""),
+                Field::masked("Password"),
+                Field::masked("Confirm password"),
+            ],
+        )
+    }
+
+    pub fn derive(path: PathBuf, filename: &str) -> Self {
+        Self::new(
+            FormKind::Derive(path),
+            format!("Derive from {}", filename),
+            // The passphrase is left empty for wallets whose seed takes none
+            vec![Field::masked("Password"), Field::masked("Passphrase"), Field::plain("Count", "10")],
+        )
+    }
+
+    fn new(kind: FormKind, title: String, fields: Vec<Field>) -> Self {
+        Self { kind, title, fields, focus: 0, error: None }
+    }
+
+    /// Value of the field labelled `label`
+    pub fn value(&self, label: &str) -> &str {
+        self.fields.iter().find(|f| f.label == label).map_or("", |f| f.value.as_str())
+    }
+
+    /// Empty the masked fields, so a failed attempt is typed again from scratch
+    pub fn clear_secrets(&mut self) {
+        for field in self.fields.iter_mut().filter(|f| f.masked) {
+            field.value = Zeroizing::new(String::new());
+        }
+    }
+
+    pub fn fail(&mut self, error: String) {
+        self.clear_secrets();
+        self.error = Some(error);
+        self.focus = self.fields.iter().position(|f| f.masked).unwrap_or(0);
+    }
+
+    /// Edit the focused field; Enter on the last field submits
+    pub fn handle_key(&mut self, key: KeyEvent) -> FormAction {
+        match key.code {
+            KeyCode::Esc => return FormAction::Cancel,
+            KeyCode::Enter if self.focus + 1 == self.fields.len() => return FormAction::Submit,
+            KeyCode::Enter | KeyCode::Tab | KeyCode::Down => self.focus = (self.focus + 1) % self.fields.len(),
+            KeyCode::BackTab | KeyCode::Up => self.focus = (self.focus + self.fields.len() - 1) % self.fields.len(),
+            KeyCode::Backspace => {
+                self.fields[self.focus].value.pop();
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.fields[self.focus].value = Zeroizing::new(String::new());
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.fields[self.focus].value.push(c);
+            }
+            _ => {}
+        }
+        FormAction::Continue
+    }
+}
@@ -0,0 +1,69 @@
+//! `web3wallet tui`: browse the wallets directory and run create, import and derive from
+//! forms instead of prompts. Built with the `tui` feature.
+mod app;
+mod form;
+mod ui;
+
+use app::App;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use ratatui::crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::crossterm::ExecutableCommand;
+use ratatui::Terminal;
+use std::io::{IsTerminal, Stdout};
+use std::time::Duration;
+use web3wallet_cli::errors::UserInputError;
+use web3wallet_cli::{WalletConfig, WalletResult};
+
+/// Raw mode and the alternate screen, undone on drop so an error or panic leaves a usable shell
+struct TerminalGuard {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl TerminalGuard {
+    fn enter() -> WalletResult<Self> {
+        terminal::enable_raw_mode()?;
+        let mut stdout = std::io::stdout();
+        if let Err(e) = stdout.execute(EnterAlternateScreen) {
+            let _ = terminal::disable_raw_mode();
+            return Err(e.into());
+        }
+        let terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+        Ok(Self { terminal })
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+        let _ = self.terminal.backend_mut().execute(LeaveAlternateScreen);
+        let _ = self.terminal.show_cursor();
+    }
+}
+
+pub async fn run(config: &WalletConfig) -> WalletResult<()> {
+    if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
+        return Err(UserInputError::InvalidParameters {
+            parameter: "tui".to_string(),
+            value: "not a terminal".to_string(),
+            expected: "an interactive terminal; use the other subcommands in scripts".to_string(),
+        }
+        .into());
+    }
+
+    let mut app = App::new(config).await?;
+    let mut guard = TerminalGuard::enter()?;
+    let tick = Duration::from_millis(web3wallet_cli::config::tui::TICK_MS);
+    while !app.quit {
+        guard.terminal.draw(|frame| ui::draw(frame, &app))?;
+        if event::poll(tick)? {
+            // Windows reports releases too, act on presses only
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    app.handle_key(key).await;
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,169 @@
+//! Drawing of the tui: wallet list and metadata side by side, the open modal on top
+use super::app::{App, Modal};
+use super::form::Form;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Row, Table, Wrap};
+use ratatui::Frame;
+
+const BROWSE_KEYS: &str = "↑/↓ select  c create  i import  d derive  r refresh  q quit";
+const FORM_KEYS: &str = "Tab/↑/↓ move  Enter next/submit  Ctrl-U clear  Esc cancel";
+const CLOSE_KEYS: &str = "Enter/Esc close";
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [body, footer] = split(Direction::Vertical, frame.size(), [Constraint::Min(3), Constraint::Length(1)]);
+    let [list, details] = split(Direction::Horizontal, body, [Constraint::Percentage(40), Constraint::Percentage(60)]);
+
+    draw_list(frame, app, list);
+    draw_details(frame, app, details);
+    draw_footer(frame, app, footer);
+
+    match &app.modal {
+        Some(Modal::Form(form)) => draw_form(frame, form),
+        Some(Modal::Mnemonic { address, mnemonic, saved_to }) => {
+            let lines = vec![
+                Line::from(format!("Address: {}", address)),
+                Line::from(format!("Saved to: {}", saved_to.display())),
+                Line::from(""),
+                Line::from(Span::styled(mnemonic.as_str(), Style::default().add_modifier(Modifier::BOLD))),
+                Line::from(""),
+                Line::from(Span::styled(
+                    "Write these words down now; they are not shown again. Confirm the backup later with verify-backup.",
+                    Style::default().fg(Color::Yellow),
+                )),
+            ];
+            let area = centered(frame.size(), 70, lines.len() as u16 + 4);
+            frame.render_widget(Clear, area);
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).block(modal_block("Recovery phrase")), area);
+        }
+        Some(Modal::Derived(outcome)) => {
+            let rows = outcome.addresses.iter().map(|entry| {
+                Row::new(vec![entry.index.to_string(), entry.address.clone(), entry.derivation_path.clone()])
+            });
+            let widths = [Constraint::Length(6), Constraint::Length(44), Constraint::Min(16)];
+            let table = Table::new(rows, widths)
+                .header(Row::new(vec!["INDEX", "ADDRESS", "PATH"]).style(Style::default().add_modifier(Modifier::BOLD)))
+                .block(modal_block(&format!("Addresses under {}", outcome.base_path)));
+            let area = centered(frame.size(), 90, outcome.addresses.len() as u16 + 4);
+            frame.render_widget(Clear, area);
+            frame.render_widget(table, area);
+        }
+        None => {}
+    }
+}
+
+fn draw_list(frame: &mut Frame, app: &App, area: Rect) {
+    let title = format!("Wallets in {}", app.directory().display());
+    if app.wallets.is_empty() {
+        let empty = Paragraph::new("No wallets yet. Press c to create one or i to import a mnemonic.")
+            .wrap(Wrap { trim: false })
+            .block(Block::default().borders(Borders::ALL).title(title));
+        frame.render_widget(empty, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = app
+        .wallets
+        .iter()
+        .map(|wallet| {
+            let name = match &wallet.alias {
+                Some(alias) => format!("{} ({})", wallet.filename, alias),
+                None => wallet.filename.clone(),
+            };
+            ListItem::new(name)
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+    let mut state = ListState::default().with_selected(Some(app.selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_details(frame: &mut Frame, app: &App, area: Rect) {
+    let width = app.details.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+    let lines: Vec<Line> = app
+        .details
+        .iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(format!("{:width$}  ", label, width = width), Style::default().fg(Color::Cyan)),
+                Span::raw(value.as_str()),
+            ])
+        })
+        .collect();
+    let details = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title("Details"));
+    frame.render_widget(details, area);
+}
+
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let line = match (&app.status, &app.modal) {
+        (Some(status), None) => {
+            let color = if status.error { Color::Red } else { Color::Green };
+            Line::from(Span::styled(status.text.as_str(), Style::default().fg(color)))
+        }
+        (_, None) => Line::from(BROWSE_KEYS),
+        (_, Some(Modal::Form(_))) => Line::from(FORM_KEYS),
+        (_, Some(_)) => Line::from(CLOSE_KEYS),
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn draw_form(frame: &mut Frame, form: &Form) {
+    let width = form.fields.iter().map(|f| f.label.len()).max().unwrap_or(0);
+    let mut lines: Vec<Line> = form
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let focused = i == form.focus;
+            let value_style = if focused {
+                Style::default().add_modifier(Modifier::UNDERLINED)
+            } else {
+                Style::default()
+            };
+            Line::from(vec![
+                Span::raw(if focused { "> " } else { "  " }),
+                Span::styled(format!("{:width$}  ", field.label, width = width), Style::default().fg(Color::Cyan)),
+                Span::styled(field.display(), value_style),
+                Span::raw(if focused { "_" } else { "" }),
+            ])
+        })
+        .collect();
+    if let Some(error) = &form.error {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(error.as_str(), Style::default().fg(Color::Red))));
+    }
+
+    let area = centered(frame.size(), 70, lines.len() as u16 + 4);
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).block(modal_block(&form.title)), area);
+}
+
+fn modal_block(title: &str) -> Block<'static> {
+    Block::default()
+        .borders(Borders::ALL)
+        .title(title.to_string())
+        .border_style(Style::default().fg(Color::Yellow))
+}
+
+fn split<const N: usize>(direction: Direction, area: Rect, constraints: [Constraint; N]) -> [Rect; N] {
+    let chunks = Layout::default().direction(direction).constraints(constraints).split(area);
+    std::array::from_fn(|i| chunks[i])
+}
+
+/// `width` percent of `area` wide and `height` rows high, in its middle
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = area.width * width / 100;
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
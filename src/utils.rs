@@ -1,5 +1,6 @@
 use crate::errors::{ValidationError, FilesystemError, WalletResult};
 use std::path::Path;
+use handlebars::Handlebars;
 
 pub fn validate_ethereum_address(address: &str) -> WalletResult<()> {
 
@@ -19,10 +20,45 @@ pub fn validate_ethereum_address(address: &str) -> WalletResult<()> {
         }
         .into());
     } 
+
+    // All-lowercase and all-uppercase addresses carry no checksum; mixed case must be EIP-55
+    let mixed_case = addr.chars().any(|c| c.is_ascii_lowercase()) && addr.chars().any(|c| c.is_ascii_uppercase());
+    if mixed_case && to_checksum_address(addr)?.strip_prefix("0x") != Some(addr) {
+        return Err(ValidationError::InvalidAddressFormat {
+            address: address.to_string(),
+            expected: "a valid EIP-55 checksum, or all-lowercase hex".to_string(),
+        }
+        .into());
+    }
     
     Ok(())
 }
 
+/// EIP-55 mixed-case checksum encoding of a hex address, with or without `0x` prefix
+pub fn to_checksum_address(address: &str) -> WalletResult<String> {
+    let lower = normalize_address(address);
+    let parsed: ethers::types::Address = lower[2..].parse().map_err(|_| ValidationError::InvalidAddressFormat {
+        address: address.to_string(),
+        expected: "40 hexadecimal characters".to_string(),
+    })?;
+    Ok(ethers::utils::to_checksum(&parsed, None))
+}
+
+/// Canonical form for matching and keying addresses: `0x` followed by lowercase hex
+pub fn normalize_address(address: &str) -> String {
+    let address = address.trim();
+    let hex = address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+        .unwrap_or(address);
+    format!("0x{}", hex.to_ascii_lowercase())
+}
+
+/// Whether two addresses are the same account, whatever their EIP-55 casing or `0x` prefix
+pub fn addresses_equal(a: &str, b: &str) -> bool {
+    normalize_address(a) == normalize_address(b)
+}
+
 pub fn validate_private_key(key: &str) -> WalletResult<()> {
     let private_key = key.strip_prefix("0x").unwrap_or(key);
 
@@ -60,11 +96,7 @@ pub fn validate_derivation_path(path: &str) -> WalletResult<()> {
             }.into());
         }
 
-        let num_str = if component.ends_with("'"){
-            &component[..component.len() -1]
-        }else{
-            component
-        };
+        let num_str = component.strip_suffix('\'').unwrap_or(component);
 
         if num_str.parse::<u32>().is_err(){
             return Err(ValidationError::InvalidAddressFormat{
@@ -76,6 +108,30 @@ pub fn validate_derivation_path(path: &str) -> WalletResult<()> {
     Ok(())
 }
 
+/// Split a full address path into its parent and unhardened address index, e.g.
+/// "m/44'/60'/1'/0/5" into ("m/44'/60'/1'/0", 5)
+pub fn split_address_path(path: &str) -> WalletResult<(String, u32)> {
+    validate_derivation_path(path)?;
+
+    let invalid = |expected: &str| ValidationError::InvalidAddressFormat {
+        address: path.to_string(),
+        expected: expected.to_string(),
+    };
+    for component in path[2..].split('/') {
+        let index = component.strip_suffix('\'').unwrap_or(component).parse::<u32>().unwrap_or(u32::MAX);
+        if index > crate::config::MAX_BIP32_INDEX {
+            return Err(invalid("path components of at most 2147483647, marked hardened with '").into());
+        }
+    }
+
+    match path.rsplit_once('/') {
+        Some((parent, index)) if parent != "m" && !index.ends_with('\'') => {
+            Ok((parent.to_string(), index.parse().unwrap_or_default()))
+        }
+        _ => Err(invalid("an unhardened address index below a parent path, e.g. m/44'/60'/1'/0/5").into()),
+    }
+}
+
 pub fn validate_file_path<P: AsRef<Path>>(path: P) -> WalletResult<()> {
     let path = path.as_ref();
 
@@ -90,3 +146,124 @@ pub fn validate_file_path<P: AsRef<Path>>(path: P) -> WalletResult<()> {
     Ok(())
 }
 
+/// Render `template` (handlebars syntax, e.g. `{{address}} {{network}}`) against a command's JSON output
+pub fn render_template(template: &str, data: &serde_json::Value) -> WalletResult<String> {
+    let mut handlebars = Handlebars::new();
+    handlebars.register_escape_fn(handlebars::no_escape);
+
+    handlebars.render_template(template, data).map_err(|e| {
+        ValidationError::InvalidCommandSyntax {
+            command: "--template".to_string(),
+            error: e.to_string(),
+        }
+        .into()
+    })
+}
+
+/// Mode of `path` when users other than its owner have any access to it
+#[cfg(all(feature = "native", unix))]
+pub async fn open_permissions<P: AsRef<Path>>(path: P) -> WalletResult<Option<u32>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = tokio::fs::metadata(path).await?.permissions().mode() & 0o777;
+    Ok((mode & 0o077 != 0).then_some(mode))
+}
+
+/// File modes are not checked on this platform
+#[cfg(all(feature = "native", not(unix)))]
+pub async fn open_permissions<P: AsRef<Path>>(_path: P) -> WalletResult<Option<u32>> {
+    Ok(None)
+}
+
+/// Replace `path` with `contents` via a synced temp file in the same directory and a rename,
+/// so a crash leaves either the old file or the new one, never a truncated mix
+#[cfg(feature = "native")]
+pub async fn write_atomic<P: AsRef<Path>>(path: P, contents: &[u8]) -> WalletResult<()> {
+    replace_file(path.as_ref(), contents, None).await
+}
+
+/// `write_atomic` for keystores and other secrets: the file ends up readable by the owner
+/// only, whatever mode an earlier version of it had
+#[cfg(feature = "native")]
+pub async fn write_private<P: AsRef<Path>>(path: P, contents: &[u8]) -> WalletResult<()> {
+    replace_file(path.as_ref(), contents, Some(crate::config::fs::KEYSTORE_FILE_PERMISSIONS)).await
+}
+
+/// Without a `mode` the new file keeps the permissions of the one it replaces
+#[cfg(feature = "native")]
+async fn replace_file(path: &Path, contents: &[u8], mode: Option<u32>) -> WalletResult<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let file_name = path.file_name().ok_or_else(|| FilesystemError::InvalidFormat {
+        path: path.display().to_string(),
+        details: "not a file path".to_string(),
+    })?;
+    let tmp = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+
+    let result = async {
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        if let Some(mode) = mode {
+            options.mode(mode);
+        }
+        let mut file = options.open(&tmp).await?;
+        file.write_all(contents).await?;
+        file.sync_all().await?;
+        match (mode, tokio::fs::metadata(path).await) {
+            // The mode only applies to a newly created temp file, e.g. not one left by a crash
+            #[cfg(unix)]
+            (Some(mode), _) => {
+                use std::os::unix::fs::PermissionsExt;
+                tokio::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(mode)).await?;
+            }
+            (None, Ok(existing)) => tokio::fs::set_permissions(&tmp, existing.permissions()).await?,
+            _ => {}
+        }
+        tokio::fs::rename(&tmp, path).await
+    }
+    .await;
+
+    if let Err(e) = result {
+        let _ = tokio::fs::remove_file(&tmp).await;
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Overwrite a file with random bytes and sync it before unlinking. On SSDs and
+/// copy-on-write or journaling filesystems old blocks may survive; this only
+/// defeats casual recovery.
+#[cfg(feature = "native")]
+pub async fn shred_file<P: AsRef<Path>>(path: P) -> WalletResult<()> {
+    use rand::RngCore;
+    use tokio::io::AsyncWriteExt;
+
+    let path = path.as_ref();
+    let len = tokio::fs::metadata(path).await?.len() as usize;
+    let mut noise = vec![0u8; len];
+    rand::thread_rng().fill_bytes(&mut noise);
+
+    let mut file = tokio::fs::OpenOptions::new().write(true).open(path).await?;
+    file.write_all(&noise).await?;
+    file.sync_all().await?;
+    drop(file);
+
+    tokio::fs::remove_file(path).await?;
+    Ok(())
+}
+
+/// Run CPU-bound `work`, such as a KDF or cipher, on tokio's blocking pool so a long-running
+/// `serve` or `agent` keeps answering other requests meanwhile
+#[cfg(feature = "native")]
+pub async fn blocking<T, F>(work: F) -> WalletResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> WalletResult<T> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(work).await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(e) => Err(crate::errors::WalletError::Io(format!("blocking task did not finish: {}", e))),
+    }
+}
@@ -0,0 +1,121 @@
+//! Helpers shared by the integration tests; each test crate uses some of them
+#![allow(dead_code)]
+
+use assert_cmd::Command;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::Path;
+
+/// Password the keystores of the command tests are saved under
+pub const PASSWORD: &str = "Test123!Password";
+
+/// Write `config.toml` into `dir` with the wallets and state directories inside it, followed
+/// by `extra` settings such as `offline = true` or a `[kdf]` table. Returns its path.
+pub fn write_config(dir: &Path, extra: &str) -> String {
+    let config = dir.join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "wallets_path = {:?}\nstate_path = {:?}\n{}",
+            dir.join("wallets").to_str().unwrap(),
+            dir.join("state").to_str().unwrap(),
+            extra
+        ),
+    )
+    .unwrap();
+    config.to_str().unwrap().to_string()
+}
+
+/// `web3wallet <args>` run against `config`
+pub fn web3wallet(config: &str, args: &[&str]) -> Command {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(args).args(["--config", config]);
+    cmd
+}
+
+/// `web3wallet` without the agent, reading `PASSWORD` from stdin for the unlock
+pub fn unlocked(config: &str, args: &[&str]) -> Command {
+    let mut cmd = web3wallet(config, args);
+    cmd.args(["--no-agent", "--password-stdin"]).write_stdin(format!("{}\n", PASSWORD));
+    cmd
+}
+
+/// Local HTTP endpoint that answers each POSTed JSON body with the status and body `respond`
+/// returns for it, until the test exits. Returns its URL.
+pub fn fake_http<F>(mut respond: F) -> String
+where
+    F: FnMut(Value) -> (u16, String) + Send + 'static,
+{
+    fake_http_with_headers(move |request| {
+        let (status, body) = respond(request);
+        (status, Vec::new(), body)
+    })
+}
+
+/// `fake_http` whose answers also carry the extra headers `respond` returns, e.g. `Retry-After`
+pub fn fake_http_with_headers<F>(mut respond: F) -> String
+where
+    F: FnMut(Value) -> (u16, Vec<(&'static str, String)>, String) + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+
+            let (status, headers, response) = respond(serde_json::from_slice(&body).unwrap());
+            let reason = if status == 200 { "OK" } else { "Error" };
+            let headers: String = headers.iter().map(|(name, value)| format!("{}: {}\r\n", name, value)).collect();
+            // The client may have given up on a slow answer
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+                status,
+                reason,
+                response.len(),
+                headers,
+                response
+            );
+        }
+    });
+    url
+}
+
+/// JSON-RPC endpoint where `result` gives the result of each call; a batch is answered call
+/// by call, echoing each id
+pub fn fake_rpc<F>(mut result: F) -> String
+where
+    F: FnMut(&Value) -> Value + Send + 'static,
+{
+    fake_http(move |request| {
+        let mut answer = |call: &Value| json!({ "jsonrpc": "2.0", "id": call["id"], "result": result(call) });
+        let response = match &request {
+            Value::Array(calls) => Value::Array(calls.iter().map(&mut answer).collect()),
+            call => answer(call),
+        };
+        (200, response.to_string())
+    })
+}
+
+/// `value` as a 32 byte ABI word, in hex
+pub fn word(value: u128) -> String {
+    format!("{:064x}", value)
+}
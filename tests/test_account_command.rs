@@ -0,0 +1,64 @@
+mod common;
+
+use common::unlocked;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const PRIMARY_ADDRESS: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const IMPORTED_ADDRESS: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
+
+fn accounts(config: &str, args: &[&str]) -> serde_json::Value {
+    let output = unlocked(config, args).args(["--output", "json"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// Test one keystore holds a derived and an imported account besides its own, and the
+/// selected one is what load and list show
+#[test]
+fn test_account_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let wallets = temp_dir.path().join("wallets");
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        format!("wallets_path = {:?}\n\n[kdf]\nalgorithm = \"pbkdf2\"\n", wallets.to_str().unwrap()),
+    )
+    .unwrap();
+    let config = config.to_str().unwrap();
+
+    unlocked(config, &["import", "--mnemonic", VALID_MNEMONIC_12, "--save", "hd"]).assert().success();
+
+    let added = accounts(config, &["account", "add", "hd.json", "savings"]);
+    assert_eq!(added["selected"], "main");
+    assert_eq!(added["accounts"][1]["name"], "savings");
+    assert_eq!(added["accounts"][1]["derivation_path"], "m/44'/60'/1'/0");
+    assert_ne!(added["accounts"][1]["address"], PRIMARY_ADDRESS);
+
+    let added = accounts(config, &["account", "add", "hd.json", "hot", "--private-key", VALID_PRIVATE_KEY, "--select"]);
+    assert_eq!(added["selected"], "hot");
+    assert_eq!(added["accounts"][2]["address"], IMPORTED_ADDRESS);
+    // The same key is not added twice, under any name
+    unlocked(config, &["account", "add", "hd.json", "again", "--private-key", VALID_PRIVATE_KEY])
+        .assert()
+        .code(2);
+
+    unlocked(config, &["load", "hd.json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(IMPORTED_ADDRESS));
+    unlocked(config, &["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(IMPORTED_ADDRESS));
+
+    unlocked(config, &["account", "select", "hd.json", "unknown"]).assert().code(2);
+    let listed = accounts(config, &["account", "select", "hd.json", "main"]);
+    assert_eq!(listed["accounts"].as_array().unwrap().len(), 3);
+    unlocked(config, &["load", "hd.json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(PRIMARY_ADDRESS));
+}
@@ -0,0 +1,42 @@
+use web3wallet_cli::services::address_format::{self, Base58Formatter, Bech32Formatter, Chain, Eip55Formatter};
+use web3wallet_cli::services::AddressFormatter;
+
+/// Test the P2WPKH vector of BIP-173 on mainnet and testnet
+#[test]
+fn test_address_format_bech32() {
+    let hash = "751e76e8199196d454941c45d1b3a323f1433bd6";
+    assert_eq!(Bech32Formatter::default().format_hex(hash).unwrap(), "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+    assert_eq!(Bech32Formatter::new("tb").format_hex(hash).unwrap(), "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx");
+
+    // A P2WPKH program is a 20-byte hash
+    assert!(Bech32Formatter::default().format(&[0u8; 32]).is_err());
+}
+
+/// Test well-known Solana program ids, leading zero bytes included
+#[test]
+fn test_address_format_base58() {
+    assert_eq!(Base58Formatter.format(&[0u8; 32]).unwrap(), "11111111111111111111111111111111");
+    assert_eq!(
+        Base58Formatter.format_hex("06ddf6e1d765a193d9cbe146ceeb79ac1cb485ed5f5b37913a8cf5857eff00a9").unwrap(),
+        "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+    );
+
+    // A Solana address is a 32-byte public key
+    assert!(Base58Formatter.format(&[0u8; 20]).is_err());
+}
+
+/// Test each chain gets its own formatter and display leaves undecodable input alone
+#[test]
+fn test_address_format_per_chain() {
+    for (name, chain) in [("eth", Chain::Evm), ("bitcoin", Chain::Bitcoin), ("SOL", Chain::Solana)] {
+        let parsed: Chain = name.parse().unwrap();
+        assert_eq!(parsed, chain);
+        assert_eq!(address_format::formatter_for(parsed).chain(), chain);
+    }
+    assert!("tron".parse::<Chain>().is_err());
+
+    let address = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed";
+    assert_eq!(Eip55Formatter.format_hex(address).unwrap(), "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+    assert_eq!(address_format::display(Chain::Evm, address), "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+    assert_eq!(address_format::display(Chain::Bitcoin, "not hex"), "not hex");
+}
@@ -0,0 +1,189 @@
+#![cfg(unix)]
+
+mod common;
+
+use assert_cmd::cargo::CommandCargoExt;
+use common::{web3wallet, write_config};
+use predicates::prelude::*;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const EXPECTED_ADDRESS: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+/// Account 0 of the same mnemonic under the BIP39 passphrase "TREZOR"
+const PASSPHRASE_ADDRESS: &str = "0x9c32F71D4DB8Fb9e1A58B0a80dF79935e7256FA6";
+
+/// Test a running agent supplies the password of a wallet unlocked once before
+#[test]
+fn test_agent_command_caches_password() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+
+    web3wallet(&config, &["import", "--mnemonic", VALID_MNEMONIC_12, "--save", "agent_wallet"])
+        .arg("--password-stdin")
+        .write_stdin("Test123!Password\n")
+        .assert()
+        .success();
+
+    let mut agent = std::process::Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["agent", "start", "--config", &config])
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let socket = temp_dir.path().join("state").join("agent.sock");
+    let started = Instant::now();
+    while !socket.exists() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    // A second agent for the same state directory is refused
+    web3wallet(&config, &["agent", "start"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already running"));
+
+    web3wallet(&config, &["load", "agent_wallet.json"])
+        .arg("--password-stdin")
+        .write_stdin("Test123!Password\n")
+        .assert()
+        .success();
+
+    web3wallet(&config, &["agent", "status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 password(s)"));
+
+    // No password on stdin: only the agent can unlock it
+    web3wallet(&config, &["load", "agent_wallet.json"])
+        .write_stdin("")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS));
+
+    // Derivation runs in the agent with the keys it now holds, account paths included
+    web3wallet(&config, &["derive", "--from-file", "agent_wallet.json", "--count", "2", "--output", "json"])
+        .write_stdin("")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS))
+        .stdout(predicate::str::contains("m/44'/60'/0'/0/1"));
+    web3wallet(&config, &["derive", "--from-file", "agent_wallet.json", "--account", "1", "--output", "json"])
+        .write_stdin("")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("m/44'/60'/1'/0/0"));
+
+    web3wallet(&config, &["agent", "status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 wallet(s) held decrypted"));
+
+    web3wallet(&config, &["agent", "lock"]).assert().success();
+    web3wallet(&config, &["agent", "status", "--output", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"keys\": 0"));
+
+    web3wallet(&config, &["agent", "stop"]).assert().success();
+    agent.wait().unwrap();
+    assert!(!socket.exists());
+
+    web3wallet(&config, &["agent", "status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No agent running"));
+}
+
+/// Test the agent opens a passphrase wallet with the passphrase handed over with its password,
+/// and keeps signing with it once the keys are held
+#[test]
+fn test_agent_command_passphrase_wallet() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+
+    web3wallet(&config, &["import", "--mnemonic", VALID_MNEMONIC_12, "--passphrase", "--save", "guarded"])
+        .arg("--password-stdin")
+        .write_stdin("TREZOR\nTREZOR\nTest123!Password\n")
+        .assert()
+        .success();
+
+    let mut agent = std::process::Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["agent", "start", "--config", &config])
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let socket = temp_dir.path().join("state").join("agent.sock");
+    let started = Instant::now();
+    while !socket.exists() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    // Password and passphrase are asked once, then handed to the agent, which decrypts the
+    // keystore itself with both
+    web3wallet(&config, &["derive", "--from-file", "guarded.json", "--output", "json"])
+        .arg("--password-stdin")
+        .write_stdin("Test123!Password\nTREZOR\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(PASSPHRASE_ADDRESS));
+
+    // Nothing on stdin: the keys the agent holds derive and sign under the passphrase
+    web3wallet(&config, &["derive", "--from-file", "guarded.json", "--count", "2", "--output", "json"])
+        .write_stdin("")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(PASSPHRASE_ADDRESS))
+        .stdout(predicate::str::contains("m/44'/60'/0'/0/1"));
+    web3wallet(&config, &["sign-message", "--from-file", "guarded.json", "--message", "hello", "--output", "json"])
+        .write_stdin("")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(PASSPHRASE_ADDRESS));
+
+    web3wallet(&config, &["agent", "stop"]).assert().success();
+    agent.wait().unwrap();
+}
+
+/// Test --confirm needs a terminal to ask at
+#[test]
+fn test_agent_command_confirm_requires_terminal() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+
+    web3wallet(&config, &["agent", "start", "--confirm"])
+        .write_stdin("")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("started from a terminal"));
+    assert!(!temp_dir.path().join("state").join("agent.sock").exists());
+}
+
+/// Test commands fall back to prompting without an agent or with --no-agent
+#[test]
+fn test_agent_command_fallback() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+
+    web3wallet(&config, &["import", "--mnemonic", VALID_MNEMONIC_12, "--save", "plain_wallet"])
+        .arg("--password-stdin")
+        .write_stdin("Test123!Password\n")
+        .assert()
+        .success();
+
+    web3wallet(&config, &["load", "plain_wallet.json", "--no-agent"])
+        .arg("--password-stdin")
+        .write_stdin("Test123!Password\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS));
+
+    // A pid file left behind by a dead agent is not mistaken for a running one
+    std::fs::create_dir_all(temp_dir.path().join("state")).unwrap();
+    std::fs::write(temp_dir.path().join("state").join("agent.pid"), "999999\n").unwrap();
+    web3wallet(&config, &["agent", "status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No agent running"));
+}
@@ -0,0 +1,74 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+/// Account 0 of the mnemonic, which the app key must never be
+const FUNDS_ADDRESS: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+/// sha256("github.com") starts 3aeb0024 60381c6f 258e8395 d3026f57, each word masked to 31 bits
+const APP_KEY_PATH: &str = "m/13'/988479524'/1614290031'/630096789'/1392668503'/0'";
+const APP_KEY_ADDRESS: &str = "0x72bd8C4D6F252Ee2AbE4ab2749f3908BbA1F9709";
+
+/// Test app key derivation lands under the app purpose with a hardened index
+#[test]
+fn test_app_key_command_path() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    // Piped stdin answers the mnemonic prompt
+    cmd.write_stdin(format!("{}\n", VALID_MNEMONIC_12));
+    cmd.args(["app-key", "--app", "github.com", "--index", "3", "--output", "json"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(r#""derivation_path": "m/13'/"#))
+        .stdout(predicate::str::contains(r#"/3'""#));
+}
+
+/// Test different apps get different keys
+#[test]
+fn test_app_key_command_isolated_per_app() {
+    let run = |app: &str| {
+        let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+        cmd.write_stdin(format!("{}\n", VALID_MNEMONIC_12));
+        cmd.args(["app-key", "--app", app, "--template", "{{address}}"]);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        String::from_utf8(output).unwrap().lines().last().unwrap().to_string()
+    };
+
+    assert_ne!(run("github.com"), run("example.org"));
+}
+
+/// Test the app id hashes to the same path and key as ever; a change would orphan every
+/// key users already registered with an app
+#[test]
+fn test_app_key_command_known_answer() {
+    use web3wallet_cli::models::Wallet;
+
+    assert_eq!(Wallet::app_key_path("github.com", 0), APP_KEY_PATH);
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.write_stdin(format!("{}\n", VALID_MNEMONIC_12));
+    cmd.args(["app-key", "--app", "github.com", "--output", "json"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!(r#""derivation_path": "{}""#, APP_KEY_PATH)))
+        .stdout(predicate::str::contains(APP_KEY_ADDRESS));
+}
+
+/// Test a message is signed by the app key, not by the funds key of the mnemonic
+#[test]
+fn test_app_key_command_sign_message() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.write_stdin(format!("{}\n", VALID_MNEMONIC_12));
+    cmd.args(["app-key", "--app", "github.com", "--message", "hello", "--template", "{{signature}}"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let signature = String::from_utf8(output).unwrap().lines().last().unwrap().to_string();
+
+    let verify = |address: &str| {
+        let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+        cmd.args(["verify-signature", "--message", "hello", "--signature", &signature, "--address", address]);
+        cmd.args(["--output", "json"]);
+        cmd.assert().success()
+    };
+    verify(APP_KEY_ADDRESS).stdout(predicate::str::contains(r#""valid": true"#));
+    verify(FUNDS_ADDRESS).stdout(predicate::str::contains(r#""valid": false"#));
+}
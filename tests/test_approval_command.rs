@@ -0,0 +1,144 @@
+mod common;
+
+use assert_cmd::Command;
+use common::unlocked;
+use predicates::prelude::*;
+use std::path::Path;
+use tempfile::TempDir;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const APPROVER_MNEMONIC: &str = "legal winner thank year wave sausage worth useful legal winner thank yellow";
+const RECIPIENT: &str = "0x000000000000000000000000000000000000dEaD";
+
+fn last_line(cmd: &mut Command) -> String {
+    let output = cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output).unwrap().lines().last().unwrap().trim().to_string()
+}
+
+/// Config with both wallets imported and approvals from 1 ETH on, sealed to a fresh companion key
+fn setup(dir: &Path) -> String {
+    let config = dir.join("config.toml");
+    let base = format!(
+        "wallets_path = {:?}\nstate_path = {:?}\n",
+        dir.join("wallets").to_str().unwrap(),
+        dir.join("state").to_str().unwrap()
+    );
+    std::fs::write(&config, &base).unwrap();
+    let config_str = config.to_str().unwrap().to_string();
+
+    unlocked(&config_str, &["import", "--mnemonic", VALID_MNEMONIC_12, "--save", "sender"])
+        .assert()
+        .success();
+    let approver = last_line(&mut unlocked(
+        &config_str,
+        &["import", "--mnemonic", APPROVER_MNEMONIC, "--save", "approver", "--template", "{{address}}"],
+    ));
+    let key = dir.join("companion.key");
+    let public_key = last_line(&mut unlocked(
+        &config_str,
+        &["approval", "keygen", "--out", key.to_str().unwrap(), "--template", "{{public_key}}"],
+    ));
+
+    std::fs::write(
+        &config,
+        format!(
+            "{}\n[approval]\nthreshold = \"1.0\"\napprovers = [\"{}\"]\npublic_key = \"{}\"\n",
+            base, approver, public_key
+        ),
+    )
+    .unwrap();
+    config_str
+}
+
+fn sign_tx(config: &str, value: &str, nonce: &str, token: Option<&str>) -> Command {
+    let mut cmd = unlocked(
+        config,
+        &[
+            "sign-tx", "--from-file", "sender.json", "--to", RECIPIENT, "--value", value, "--nonce", nonce,
+            "--gas", "21000", "--max-fee", "20000000000", "--max-priority-fee", "1000000000", "--chain-id", "1",
+        ],
+    );
+    if let Some(token) = token {
+        cmd.args(["--approval-token", token]);
+    }
+    cmd
+}
+
+/// The sealed request printed by a refused sign-tx, as there is no channel configured
+fn sealed_request(config: &str, value: &str, nonce: &str) -> String {
+    let output = sign_tx(config, value, nonce, None)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("AUTH_005"))
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    let mut lines = stdout.lines().skip_while(|line| !line.starts_with("Approval request"));
+    lines.nth(1).unwrap().trim().to_string()
+}
+
+/// Test a transaction over the threshold signs only with a token from the approver
+#[test]
+fn test_approval_command_two_person_rule() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = setup(temp_dir.path());
+    let key = temp_dir.path().join("companion.key");
+
+    // Below the threshold nothing changes
+    sign_tx(&config, "500000000000000000", "0", None).assert().success();
+
+    let request = sealed_request(&config, "2000000000000000000", "1");
+    let token = last_line(&mut unlocked(
+        &config,
+        &[
+            "approval", "approve", &request, "--key", key.to_str().unwrap(), "--from-file", "approver.json", "--yes",
+            "--template", "{{token}}",
+        ],
+    ));
+
+    sign_tx(&config, "2000000000000000000", "1", Some(&token))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Transaction signed"));
+
+    // The token covers that transaction only
+    sign_tx(&config, "2000000000000000000", "2", Some(&token))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("AUTH_006"));
+}
+
+/// Test the sender cannot approve itself and tokens from unknown keys are refused
+#[test]
+fn test_approval_command_rejects() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = setup(temp_dir.path());
+    let key = temp_dir.path().join("companion.key");
+
+    let request = sealed_request(&config, "5000000000000000000", "0");
+    unlocked(
+        &config,
+        &["approval", "approve", &request, "--key", key.to_str().unwrap(), "--from-file", "sender.json", "--yes"],
+    )
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("cannot approve its own transaction"));
+
+    let forged = format!("{}:0x{}", u32::MAX, "11".repeat(65));
+    sign_tx(&config, "5000000000000000000", "0", Some(&forged))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("AUTH_006"));
+
+    // A second key cannot open the request
+    let other = temp_dir.path().join("other.key");
+    unlocked(&config, &["approval", "keygen", "--out", other.to_str().unwrap()]).assert().success();
+    unlocked(
+        &config,
+        &["approval", "approve", &request, "--key", other.to_str().unwrap(), "--from-file", "approver.json", "--yes"],
+    )
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("CRYPTO_004"));
+}
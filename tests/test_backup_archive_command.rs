@@ -0,0 +1,81 @@
+mod common;
+
+use common::{web3wallet, write_config, PASSWORD};
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const NEW_PASSWORD: &str = "Rotated456!Password";
+const ARCHIVE_PASSWORD: &str = "Archive789!Password";
+
+/// Test backup create archives the wallets directory and backup restore brings back a deleted
+/// wallet, refusing to overwrite a keystore changed since the archive without --force
+#[test]
+fn test_backup_archive_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let wallets = temp_dir.path().join("wallets");
+    let config = &write_config(temp_dir.path(), "\n[kdf]\nalgorithm = \"pbkdf2\"\n");
+
+    web3wallet(config, &["import", "--mnemonic", VALID_MNEMONIC_12, "--save", "hd", "--password-stdin"])
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .success();
+    web3wallet(config, &["import", "--private-key", VALID_PRIVATE_KEY, "--save", "key", "--password-stdin"])
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .success();
+
+    let archive = temp_dir.path().join("wallets-2024.tar.enc");
+    let archive = archive.to_str().unwrap();
+    let archive_password = temp_dir.path().join("archive.pass");
+    std::fs::write(&archive_password, format!("{}\n{}\n", ARCHIVE_PASSWORD, ARCHIVE_PASSWORD)).unwrap();
+    let archive_password = archive_password.to_str().unwrap();
+
+    let output = web3wallet(config, &["backup", "create", "--out", archive, "--password-file", archive_password, "--output", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(value["files"], serde_json::json!(["hd.json", "key.json"]));
+    // The archive never holds a keystore in the clear
+    assert!(!std::fs::read_to_string(archive).unwrap().contains("\"address\""));
+
+    // An existing archive is never overwritten
+    web3wallet(config, &["backup", "create", "--out", archive, "--password-file", archive_password])
+        .assert()
+        .code(5);
+    web3wallet(config, &["backup", "restore", archive, "--password-stdin"])
+        .write_stdin("Wrong123!Password\n")
+        .assert()
+        .code(4);
+
+    std::fs::remove_file(wallets.join("hd.json")).unwrap();
+    let rotate = temp_dir.path().join("rotate.pass");
+    std::fs::write(&rotate, format!("{}\n{}\n", PASSWORD, NEW_PASSWORD)).unwrap();
+    web3wallet(config, &["passwd", "key.json", "--password-file", rotate.to_str().unwrap()])
+        .assert()
+        .success();
+
+    // key.json changed after the archive, so nothing is written without --force
+    web3wallet(config, &["backup", "restore", archive, "--password-file", archive_password])
+        .assert()
+        .code(5)
+        .stderr(predicate::str::contains("key.json changed after the archive"));
+    assert!(!wallets.join("hd.json").exists());
+
+    web3wallet(config, &["backup", "restore", archive, "--force", "--password-file", archive_password])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hd.json").and(predicate::str::contains("key.json")));
+    web3wallet(config, &["load", "key.json", "--password-stdin"])
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .success();
+
+    // Restoring again finds everything in place
+    web3wallet(config, &["backup", "restore", archive, "--password-file", archive_password, "--output", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"restored\": []"));
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use assert_cmd::Command;
+use common::word;
+use predicates::prelude::*;
+use serde_json::json;
+use tempfile::TempDir;
+
+const OWNER: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+/// Bundled USDC on mainnet
+const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+/// Neither registered nor bundled, returns its symbol as bytes32
+const LEGACY_TOKEN: &str = "0x00000000000000000000000000000000000012A4";
+/// No contract at all
+const EMPTY: &str = "0x000000000000000000000000000000000000dEaD";
+
+/// Result of an eth_call: `data` starts with the 4 byte selector
+fn answer_call(to: &str, data: &str) -> String {
+    let selector = &data[2..10];
+    let body = match (to, selector) {
+        (USDC, "313ce567") => word(6),
+        (USDC, "95d89b41") => format!("{}{}{:0<64}", word(32), word(4), hex::encode("USDC")),
+        (USDC, "70a08231") => word(1_234_500_000),
+        (LEGACY_TOKEN, "313ce567") => word(18),
+        (LEGACY_TOKEN, "95d89b41") => format!("{:0<64}", hex::encode("MKR")),
+        (LEGACY_TOKEN, "70a08231") => word(3_000_000_000_000_000_000),
+        _ => String::new(),
+    };
+    format!("0x{}", body)
+}
+
+/// JSON-RPC endpoint with the token contracts above and 2.5 ETH for every address
+fn fake_rpc() -> String {
+    common::fake_rpc(|call| match call["method"].as_str().unwrap() {
+        "eth_getBalance" => json!("0x22b1c8c1227a0000"),
+        "eth_call" => {
+            let to = call["params"][0]["to"].as_str().unwrap().to_lowercase();
+            let to = [USDC, LEGACY_TOKEN].into_iter().find(|a| a.to_lowercase() == to).unwrap_or(EMPTY);
+            json!(answer_call(to, call["params"][0]["data"].as_str().unwrap()))
+        }
+        _ => json!("0x1"),
+    })
+}
+
+fn balance(config: &str, args: &[&str]) -> serde_json::Value {
+    let output = Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["balance", OWNER])
+        .args(args)
+        .args(["--output", "json", "--config", config])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// Test native, bundled and unlisted token balances are read and formatted in whole units,
+/// and an address without a token contract is refused
+#[test]
+fn test_balance_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "state_path = {:?}\n\n[rpc.endpoints]\nmainnet = {:?}\n",
+            temp_dir.path().join("state").to_str().unwrap(),
+            fake_rpc()
+        ),
+    )
+    .unwrap();
+    let config = config.to_str().unwrap();
+
+    let native = balance(config, &["--chain", "mainnet"]);
+    assert_eq!(native["balances"][0]["symbol"], "ETH");
+    assert_eq!(native["balances"][0]["amount"], "2.5");
+    assert!(native["balances"][0]["token"].is_null());
+
+    let tokens = balance(config, &["--chain", "mainnet", "--token", "usdc", "--token", LEGACY_TOKEN]);
+    assert_eq!(tokens["balances"][0]["token"], USDC);
+    assert_eq!(tokens["balances"][0]["symbol"], "USDC");
+    assert_eq!(tokens["balances"][0]["balance"], "1234500000");
+    assert_eq!(tokens["balances"][0]["amount"], "1234.5");
+    assert_eq!(tokens["balances"][1]["symbol"], "MKR");
+    assert_eq!(tokens["balances"][1]["amount"], "3");
+
+    Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["balance", OWNER, "--chain", "mainnet", "--token", EMPTY, "--config", config])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not an ERC-20 token"));
+    Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["balance", OWNER, "--chain", "mainnet", "--token", "NOPE", "--config", config])
+        .assert()
+        .code(2);
+}
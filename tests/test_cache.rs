@@ -0,0 +1,58 @@
+use serde_json::json;
+use std::time::Duration;
+use web3wallet_cli::services::cache::{self, RpcCache};
+
+/// Test entries survive a persist and reopen, keyed by chain id, method and params
+#[tokio::test]
+async fn test_cache_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = RpcCache::default_path(dir.path());
+
+    let mut cache = RpcCache::open(&path).await;
+    assert!(cache.is_empty());
+    cache.insert("1", "eth_chainId", &json!([]), json!("0x1"), Duration::from_secs(60));
+    cache.insert("10", "eth_chainId", &json!([]), json!("0xa"), Duration::from_secs(60));
+    cache.persist().await.unwrap();
+
+    let cache = RpcCache::open(&path).await;
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get("1", "eth_chainId", &json!([])), Some(json!("0x1")));
+    assert_eq!(cache.get("10", "eth_chainId", &json!([])), Some(json!("0xa")));
+    assert_eq!(cache.get("137", "eth_chainId", &json!([])), None);
+    assert_eq!(cache.get("1", "eth_chainId", &json!(["latest"])), None);
+
+    // The file is replaced atomically, no temp file is left next to it
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+/// Test expired entries are never served and are dropped on persist
+#[tokio::test]
+async fn test_cache_ttl_expiry() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = RpcCache::default_path(dir.path());
+
+    let mut cache = RpcCache::open(&path).await;
+    cache.insert("1", "net_version", &json!([]), json!("1"), Duration::ZERO);
+    cache.insert("1", "eth_chainId", &json!([]), json!("0x1"), Duration::from_secs(60));
+    assert_eq!(cache.get("1", "net_version", &json!([])), None);
+    cache.persist().await.unwrap();
+    assert_eq!(cache.len(), 1);
+
+    assert_eq!(RpcCache::open(&path).await.len(), 1);
+    assert_eq!(cache::default_ttl("eth_chainId"), Some(Duration::from_secs(86_400)));
+    assert_eq!(cache::default_ttl("eth_getBalance"), None);
+}
+
+/// Test a corrupt cache file is ignored rather than failing the command
+#[tokio::test]
+async fn test_cache_corrupt_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = RpcCache::default_path(dir.path());
+    std::fs::write(&path, "{ not json").unwrap();
+
+    let mut cache = RpcCache::open(&path).await;
+    assert!(cache.is_empty());
+    cache.insert("1", "eth_chainId", &json!([]), json!("0x1"), Duration::from_secs(60));
+    cache.persist().await.unwrap();
+    assert_eq!(RpcCache::open(&path).await.len(), 1);
+}
@@ -0,0 +1,58 @@
+use serde_json::json;
+use web3wallet_cli::services::canonical_json;
+
+/// Test the number spellings of RFC 8785 appendix B
+#[test]
+fn test_canonical_json_numbers() {
+    let cases = [
+        ("0.0", "0"),
+        ("-0.0", "0"),
+        ("1e30", "1e+30"),
+        ("4.50", "4.5"),
+        ("2e-3", "0.002"),
+        ("0.000001", "0.000001"),
+        ("1e-7", "1e-7"),
+        ("1e21", "1e+21"),
+        ("1e20", "100000000000000000000"),
+        ("333333333.33333329", "333333333.3333333"),
+        ("-1.5e-10", "-1.5e-10"),
+        ("9007199254740991", "9007199254740991"),
+        ("-9007199254740991", "-9007199254740991"),
+    ];
+    for (input, expected) in cases {
+        let value: serde_json::Value = serde_json::from_str(input).unwrap();
+        assert_eq!(canonical_json::canonicalize(&value).unwrap(), expected, "{}", input);
+    }
+
+    // Not exact as a double, so no canonical form
+    let value: serde_json::Value = serde_json::from_str("9007199254740993").unwrap();
+    assert!(canonical_json::canonicalize(&value).is_err());
+}
+
+/// Test members are sorted by UTF-16 code units and strings minimally escaped
+#[test]
+fn test_canonical_json_sorting_and_strings() {
+    let value: serde_json::Value = serde_json::from_str(
+        r#"{"\u20ac": 1, "\r": 2, "\ufb33": 3, "1": 4, "\ud83d\ude00": 5, "\u0080": 6, "\u00f6": 7}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        canonical_json::canonicalize(&value).unwrap(),
+        "{\"\\r\":2,\"1\":4,\"\u{80}\":6,\"\u{f6}\":7,\"\u{20ac}\":1,\"\u{1f600}\":5,\"\u{fb33}\":3}"
+    );
+
+    let value = json!({ "s": "\u{8}\t\n\u{c}\r\u{1f}\"\\/é" });
+    assert_eq!(
+        canonical_json::canonicalize(&value).unwrap(),
+        "{\"s\":\"\\b\\t\\n\\f\\r\\u001f\\\"\\\\/é\"}"
+    );
+}
+
+/// Test the digest ignores key order and whitespace of the source document
+#[test]
+fn test_canonical_json_digest() {
+    let expected = "0x854ef06dc57f5dfed10206344ab2d02e0b6c84b0e19436703a5afd0f1f9f2687";
+    assert_eq!(canonical_json::digest_str(r#"{"a":[1,"x"],"b":null}"#).unwrap(), expected);
+    assert_eq!(canonical_json::digest_str("{\n  \"b\": null,\n  \"a\": [1.0, \"x\"]\n}").unwrap(), expected);
+    assert!(canonical_json::digest_str("{\"a\":").is_err());
+}
@@ -0,0 +1,120 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Test wallets are listed from the `wallets_path` set in the config file
+#[test]
+fn test_config_file_wallets_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let wallets = dir.path().join("wallets");
+    std::fs::create_dir_all(&wallets).unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "network = \"sepolia\"\nwallets_path = {:?}\n\n[kdf]\nmemory = 19456\n\n[rpc.endpoints]\nsepolia = \"https://sepolia.example.org\"\n",
+            wallets.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["list", "--config", config.to_str().unwrap()]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No wallet found."));
+}
+
+/// Test an unknown network in the config file is rejected
+#[test]
+fn test_config_file_unknown_network() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(&config, "network = \"not-a-chain\"\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["list", "--config", config.to_str().unwrap()]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("NETWORK_003"));
+}
+
+/// Test a non-URL RPC endpoint and unknown keys are rejected
+#[test]
+fn test_config_file_invalid_values() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let config = dir.path().join("endpoint.toml");
+    std::fs::write(&config, "[rpc.endpoints]\nmainnet = \"localhost:8545\"\n").unwrap();
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["list", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("rpc.endpoints.mainnet"));
+
+    let config = dir.path().join("unknown.toml");
+    std::fs::write(&config, "wallet_dir = \"/tmp\"\n").unwrap();
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["list", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("NETWORK_003"));
+}
+
+/// Test `config init` writes a template that validates, and refuses to overwrite it
+#[test]
+fn test_config_init_and_validate() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("nested").join("config.toml");
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["config", "init", "--path", config.to_str().unwrap()]);
+    cmd.assert().success();
+
+    let template = std::fs::read_to_string(&config).unwrap();
+    assert!(template.contains("# memory = 47104"));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["config", "validate", config.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("is valid"));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["config", "init", "--path", config.to_str().unwrap()]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("FS_005"));
+}
+
+/// Test `config show` prints the merged configuration and redacts API keys
+#[test]
+fn test_config_show() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(&config, "network = \"sepolia\"\n\n[explorer]\napi_key = \"secret-key\"\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["config", "show", "--timeout", "7", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(r#"network = "sepolia""#))
+        .stdout(predicate::str::contains("timeout_secs = 7"))
+        .stdout(predicate::str::contains("<redacted>"))
+        .stdout(predicate::str::contains("secret-key").not());
+}
+
+/// Test `config validate` reports a broken file
+#[test]
+fn test_config_validate_invalid() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(&config, "[kdf]\nmemory = 0\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["config", "validate", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("kdf.memory"));
+}
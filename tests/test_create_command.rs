@@ -1,12 +1,11 @@
 use assert_cmd::Command;
 use predicates::prelude::*;
-use tempfile::TempDir;
 
 #[test]
 fn test_create_command_default(){
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
 
-    cmd.args(&["create"]);
+    cmd.args(["create"]);
 
     cmd.assert()
         .success()
@@ -29,9 +28,9 @@ fn test_create_command_with_save(){
 
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
 
-    // Set environment variable for test mode
-    cmd.env("TEST_WALLET_PASSWORD", password);
-    cmd.args(&["create", "--save", wallet_name]);
+    // Answer the password prompts from stdin
+    cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    cmd.args(["create", "--save", wallet_name]);
 
     cmd.assert()
         .success()
@@ -48,10 +47,10 @@ fn test_create_command_with_save(){
 fn test_create_command_invalid_word_count(){
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
 
-    cmd.args(&["create", "--words", "16"]);
+    cmd.args(["create", "--words", "16"]);
     cmd.assert()
         .failure()
-        .stderr(predicate::str::contains("Word count must be 12 or 24"));
+        .stderr(predicate::str::contains("Word count must be 12, 15, 18, 21 or 24"));
 }
 
 #[test]
@@ -68,19 +67,19 @@ fn test_load_command_with_password(){
 
     // First create a wallet
     let mut create_cmd = Command::cargo_bin("web3wallet").unwrap();
-    create_cmd.env("TEST_WALLET_PASSWORD", password);
-    create_cmd.args(&["create", "--save", wallet_name]);
+    create_cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    create_cmd.args(["create", "--save", wallet_name]);
     create_cmd.assert().success();
     assert!(wallet_path.exists());
 
     // Then load the wallet
     let mut load_cmd = Command::cargo_bin("web3wallet").unwrap();
-    load_cmd.env("TEST_WALLET_PASSWORD", password);
-    load_cmd.args(&["load", &format!("{}.json", wallet_name)]); // Load command expects full filename
+    load_cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    load_cmd.args(["load", &format!("{}.json", wallet_name)]); // Load command expects full filename
 
     load_cmd.assert()
             .success()
-            .stdout(predicate::str::contains("Loading wallet from"))
+            .stderr(predicate::str::contains("Loading wallet from"))
             .stdout(predicate::str::contains("Address:"));
 
     // Clean up test wallet file
@@ -90,7 +89,7 @@ fn test_load_command_with_password(){
 #[test]
 fn test_create_command_12_words() {
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&["create", "--words", "12"]);
+    cmd.args(["create", "--words", "12"]);
 
     cmd.assert()
         .success()
@@ -101,7 +100,7 @@ fn test_create_command_12_words() {
 #[test]
 fn test_create_command_24_words() {
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&["create", "--words", "24"]);
+    cmd.args(["create", "--words", "24"]);
 
     cmd.assert()
         .success()
@@ -111,7 +110,7 @@ fn test_create_command_24_words() {
 #[test]
 fn test_create_command_json_output() {
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&["create", "--output", "json"]);
+    cmd.args(["create", "--output", "json"]);
 
     cmd.assert()
         .success()
@@ -124,14 +123,38 @@ fn test_create_command_json_output() {
 #[test]
 fn test_create_command_custom_network() {
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&["create", "--network", "sepolia"]);
+    cmd.args(["create", "--network", "sepolia"]);
 
     cmd.assert()
         .success()
-        .stdout(predicate::str::contains("sepolia network"))
+        .stderr(predicate::str::contains("sepolia network"))
         .stdout(predicate::str::contains("Network: sepolia"));
 }
 
+/// Test a mnemonic created in another wordlist imports back to the same address
+#[test]
+fn test_create_command_language() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["create", "--language", "japanese", "--template", "{{language}}|{{address}}|{{mnemonic}}"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let output = String::from_utf8(output).unwrap();
+    let fields: Vec<&str> = output.lines().last().unwrap().split('|').collect();
+    assert_eq!(fields[0], "japanese");
+    assert!(!fields[2].is_ascii());
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["import", "--mnemonic", fields[2]]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(fields[1]));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["create", "--language", "klingon"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown wordlist"));
+}
+
 /// Test wallet create performance requirement (<1s)
 #[test]
 fn test_create_command_performance() {
@@ -151,7 +174,7 @@ fn test_create_command_performance() {
 #[test]
 fn test_create_command_help() {
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&["create", "--help"]);
+    cmd.args(["create", "--help"]);
 
     cmd.assert()
         .success()
@@ -165,7 +188,7 @@ fn test_create_command_help() {
 #[test]
 fn test_create_command_metamask_compatibility() {
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&["create", "--output", "json"]);
+    cmd.args(["create", "--output", "json"]);
 
     let output = cmd.assert().success().get_output().stdout.clone();
     let output_str = String::from_utf8(output).unwrap();
@@ -184,4 +207,56 @@ fn test_create_command_metamask_compatibility() {
             assert!(address[2..].chars().all(|c| c.is_ascii_hexdigit()));
         }
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_create_command_cold_requires_verified_backup() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("extra words\nextra words\nTestPassword123!\n");
+    cmd.args(["create", "--save", "test_cold_unverified", "--cold", "--passphrase"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("AUTH_003"));
+}
+
+/// Test user entropy from --entropy-hex and --dice is mixed in, and too little of it is refused
+#[test]
+fn test_create_command_user_entropy() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["create", "--entropy-hex", "0f1e2d3c4b5a69788796a5b4c3d2e1f0", "--template", "{{mnemonic}}"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let mnemonic = String::from_utf8(output).unwrap().lines().last().unwrap().trim().to_string();
+    assert_eq!(mnemonic.split_whitespace().count(), 12);
+
+    // The system randomness still counts, so the same input gives another mnemonic
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["create", "--entropy-hex", "0f1e2d3c4b5a69788796a5b4c3d2e1f0", "--template", "{{mnemonic}}"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(mnemonic.as_str()).not());
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["create", "--words", "24", "--entropy-hex", "0f1e2d3c4b5a69788796a5b4c3d2e1f0"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("at least 32 bytes"));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["create", "--entropy-hex", "not hex at all"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("INPUT_001"));
+
+    let rolls = "3615244162 5531264413 2654136251 4612353146 1524632516\n";
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["create", "--dice", "--template", "{{mnemonic}}"]).write_stdin(rolls);
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("Roll a six-sided die 50 times"));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["create", "--dice"]).write_stdin("1234567\n\n");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("six-sided die, 1 to 6"));
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::{unlocked, write_config};
+use ethers::signers::LocalWallet;
+use ethers::types::{Address, H256, U256};
+use ethers::utils::keccak256;
+use ethers::utils::rlp::RlpStream;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const ADDRESS: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
+const RECIPIENT: &str = "0x000000000000000000000000000000000000dEaD";
+
+fn json(config: &str, args: &[&str]) -> serde_json::Value {
+    let output = unlocked(config, args).args(["--output", "json"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// Canonical and network form of a blob transaction on chain 1 signed by VALID_PRIVATE_KEY
+fn blob_transaction() -> (String, String) {
+    let fields = |stream: &mut RlpStream| {
+        stream.append(&1u64);
+        stream.append(&4u64);
+        stream.append(&U256::from(1_000_000_000u64));
+        stream.append(&U256::from(30_000_000_000u64));
+        stream.append(&21000u64);
+        stream.append(&RECIPIENT.parse::<Address>().unwrap());
+        stream.append(&U256::from(7u64));
+        stream.append(&vec![0xabu8, 0xcd]);
+        stream.begin_list(0);
+        stream.append(&U256::from(3u64));
+        stream.begin_list(1).append(&H256::repeat_byte(1));
+    };
+
+    let mut unsigned = RlpStream::new_list(11);
+    fields(&mut unsigned);
+    let wallet: LocalWallet = VALID_PRIVATE_KEY.parse().unwrap();
+    let sighash = H256(keccak256([&[3u8][..], &unsigned.out()[..]].concat()));
+    let signature = wallet.sign_hash(sighash).unwrap();
+
+    let mut signed = RlpStream::new_list(14);
+    fields(&mut signed);
+    signed.append(&(signature.v - 27)).append(&signature.r).append(&signature.s);
+    let body = signed.out();
+
+    let mut network = RlpStream::new_list(4);
+    network.append_raw(&body, 1);
+    network.begin_list(0);
+    network.begin_list(0);
+    network.begin_list(0);
+
+    (
+        format!("0x03{}", hex::encode(&body)),
+        format!("0x03{}", hex::encode(network.out())),
+    )
+}
+
+/// Test a transaction from sign-tx decodes to its fields and signer, and another chain is flagged
+#[test]
+fn test_decode_tx_command_signed() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = &write_config(temp_dir.path(), "offline = true\n");
+
+    unlocked(config, &["import", "--private-key", VALID_PRIVATE_KEY, "--save", "cold"]).assert().success();
+    let signed = json(
+        config,
+        &[
+            "sign-tx", "--from-file", "cold.json", "--to", RECIPIENT, "--value", "1000", "--nonce", "9",
+            "--chain-id", "1", "--max-fee", "30000000000", "--max-priority-fee", "1000000000", "--data", "0x1234",
+        ],
+    );
+    let raw = signed["raw"].as_str().unwrap();
+
+    let decoded = json(config, &["decode-tx", raw, "--chain-id", "1"]);
+    assert_eq!(decoded["type"], 2);
+    assert_eq!(decoded["from"], ADDRESS);
+    assert_eq!(decoded["to"], RECIPIENT);
+    assert_eq!(decoded["hash"], signed["hash"]);
+    assert_eq!(decoded["chain_id"], 1);
+    assert_eq!(decoded["nonce"], "9");
+    assert_eq!(decoded["value"], "1000");
+    assert_eq!(decoded["max_fee_per_gas"], "30000000000");
+    assert_eq!(decoded["data"], "0x1234");
+    assert_eq!(decoded["warnings"], serde_json::json!([]));
+
+    let elsewhere = json(config, &["decode-tx", raw, "--chain", "sepolia"]);
+    assert!(elsewhere["warnings"][0].as_str().unwrap().contains("signed for chain 1"));
+
+    unlocked(config, &["decode-tx", "0x02c0ffee"]).assert().code(2);
+}
+
+/// Test an EIP-4844 transaction decodes the same in canonical and network form
+#[test]
+fn test_decode_tx_command_blob() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = &write_config(temp_dir.path(), "offline = true\n");
+    let (canonical, network) = blob_transaction();
+
+    let decoded = json(config, &["decode-tx", &canonical, "--chain", "mainnet"]);
+    assert_eq!(decoded["type"], 3);
+    assert_eq!(decoded["from"], ADDRESS);
+    assert_eq!(decoded["nonce"], "4");
+    assert_eq!(decoded["value"], "7");
+    assert_eq!(decoded["max_fee_per_blob_gas"], "3");
+    assert_eq!(decoded["blob_versioned_hashes"][0], format!("0x{}", "01".repeat(32)));
+    assert!(decoded.get("blobs").is_none());
+
+    let file = temp_dir.path().join("blob.hex");
+    std::fs::write(&file, format!("{}\n", network)).unwrap();
+    let wrapped = json(config, &["decode-tx", "--file", file.to_str().unwrap(), "--chain", "mainnet"]);
+    assert_eq!(wrapped["from"], ADDRESS);
+    assert_eq!(wrapped["hash"], decoded["hash"]);
+    assert_eq!(wrapped["blobs"], 0);
+
+    unlocked(config, &["decode-tx", &canonical, "--chain", "sepolia"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("signed for chain 1, not chain 11155111"));
+}
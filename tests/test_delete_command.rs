@@ -0,0 +1,100 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const EXPECTED_ADDRESS: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
+
+fn saved_wallet(temp_dir: &TempDir, name: &str) -> (std::path::PathBuf, std::path::PathBuf) {
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(&config, format!("wallets_path = {:?}\n", temp_dir.path().join("wallets").to_str().unwrap())).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["import", "--private-key", VALID_PRIVATE_KEY, "--save", name, "--config", config.to_str().unwrap()]);
+    cmd.assert().success();
+
+    let path = temp_dir.path().join("wallets").join(format!("{}.json", name));
+    assert!(path.exists());
+    (config, path)
+}
+
+/// Test delete only proceeds once the address is typed back
+#[test]
+fn test_delete_command_requires_confirmation() {
+    let temp_dir = TempDir::new().unwrap();
+    let (config, path) = saved_wallet(&temp_dir, "to_delete");
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["delete", "to_delete.json", "--config", config.to_str().unwrap()]);
+    cmd.write_stdin("not-the-address\n");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Command failed"));
+    assert!(path.exists());
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["delete", "to_delete.json", "--config", config.to_str().unwrap()]);
+    cmd.write_stdin(format!("{}\n", EXPECTED_ADDRESS.to_lowercase()));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Wallet deleted"));
+    assert!(!path.exists());
+}
+
+/// Test the typed address matches whatever its case or `0x` prefix
+#[test]
+fn test_delete_command_confirmation_ignores_address_case() {
+    let temp_dir = TempDir::new().unwrap();
+    let (config, path) = saved_wallet(&temp_dir, "mixed_case");
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["delete", "mixed_case.json", "--config", config.to_str().unwrap()]);
+    cmd.write_stdin(format!("{}\n", EXPECTED_ADDRESS.trim_start_matches("0x").to_uppercase()));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Wallet deleted"));
+    assert!(!path.exists());
+}
+
+/// Test an unanswered confirmation aborts after --prompt-timeout instead of hanging
+#[test]
+fn test_delete_command_prompt_timeout() {
+    let temp_dir = TempDir::new().unwrap();
+    let (config, path) = saved_wallet(&temp_dir, "unattended");
+
+    // Keep stdin open without ever writing to it, like a terminal nobody is watching
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("web3wallet"))
+        .args(["delete", "unattended.json", "--prompt-timeout", "1", "--config", config.to_str().unwrap()])
+        .stdin(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdin = child.stdin.take();
+    let output = child.wait_with_output().unwrap();
+    drop(stdin);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("INPUT_008"));
+    assert!(path.exists());
+}
+
+/// Test `--yes --shred` deletes without prompting
+#[test]
+fn test_delete_command_yes_shred() {
+    let temp_dir = TempDir::new().unwrap();
+    let (config, path) = saved_wallet(&temp_dir, "to_shred");
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["delete", "to_shred.json", "--yes", "--shred", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("overwritten"));
+    assert!(!path.exists());
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["delete", "to_shred.json", "--yes", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("FS_002"));
+}
@@ -0,0 +1,67 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+/// Master key of the BIP85 test vectors
+const BIP85_ROOT_XPRV: &str = "xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb";
+const BIP85_CHILD_12: &str = "girl mad pet galaxy egg matter matrix prison refuse sense ordinary nose";
+
+fn child_mnemonic(args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    // Piped stdin answers the mnemonic prompt
+    cmd.write_stdin(format!("{}\n", VALID_MNEMONIC_12));
+    cmd.arg("derive-entropy").args(args).args(["--template", "{{mnemonic}}"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output).unwrap().lines().last().unwrap().to_string()
+}
+
+/// Test the BIP85 test vector from its published master key
+#[test]
+fn test_derive_entropy_command_vector() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(&config, format!("wallets_path = {:?}\n", temp_dir.path().join("wallets").to_str().unwrap())).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["import", "--xprv", BIP85_ROOT_XPRV, "--save", "bip85_root", "--config", config.to_str().unwrap()]);
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["derive-entropy", "--from-file", "bip85_root.json", "--words", "12", "--index", "0"]);
+    cmd.args(["--config", config.to_str().unwrap(), "--output", "json"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(BIP85_CHILD_12))
+        .stdout(predicate::str::contains(r#""derivation_path": "m/83696968'/39'/0'/12'/0'""#));
+}
+
+/// Test children are deterministic, differ per index and import as wallets
+#[test]
+fn test_derive_entropy_command_children() {
+    let first = child_mnemonic(&["--index", "1"]);
+    assert_eq!(first, child_mnemonic(&["--index", "1"]));
+    assert_ne!(first, child_mnemonic(&["--index", "2"]));
+    assert_eq!(first.split_whitespace().count(), 12);
+    assert_eq!(child_mnemonic(&["--words", "24"]).split_whitespace().count(), 24);
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["import", "--mnemonic", &first]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Address:"));
+}
+
+/// Test unsupported word counts are rejected
+#[test]
+fn test_derive_entropy_command_invalid_words() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.write_stdin(format!("{}\n", VALID_MNEMONIC_12));
+    cmd.args(["derive-entropy", "--words", "15"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("CRYPTO_002"));
+}
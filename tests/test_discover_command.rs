@@ -0,0 +1,105 @@
+mod common;
+
+use assert_cmd::Command;
+use common::write_config;
+use predicates::prelude::*;
+use serde_json::json;
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+/// Addresses of the mnemonic above with history, as (nonce, balance in wei)
+const USED: &[(&str, u64, u64)] = &[
+    // account 0, index 0
+    ("0x9858EfFD232B4033E47d90003D41EC34EcaEda94", 2, 0),
+    // account 0, index 3, received funds only
+    ("0xF3f50213C1d2e255e4B2bAD430F8A38EEF8D718E", 0, 1_000),
+    // account 1, index 1
+    ("0x61C1a3DD47433e58033cc812E520C0fFd9007198", 0, 5),
+];
+
+/// JSON-RPC endpoint answering nonce and balance queries from `USED`, every other address empty
+fn fake_rpc() -> String {
+    let used: HashMap<String, (u64, u64)> = USED.iter().map(|(a, n, b)| (a.to_lowercase(), (*n, *b))).collect();
+    common::fake_rpc(move |call| {
+        let address = call["params"][0].as_str().unwrap_or_default().to_lowercase();
+        let (nonce, balance) = used.get(&address).copied().unwrap_or_default();
+        let value = if call["method"] == "eth_getTransactionCount" { nonce } else { balance };
+        json!(format!("{:#x}", value))
+    })
+}
+
+fn discover(config: &str, args: &[&str]) -> Command {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.arg("discover").args(args).args(["--config", config]);
+    cmd
+}
+
+/// Test accounts are walked until the first unused one and each scan stops at the gap limit
+#[test]
+fn test_discover_command_gap_limit() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), &format!("\n[rpc.endpoints]\nmainnet = {:?}\n", fake_rpc()));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["import", "--mnemonic", VALID_MNEMONIC_12, "--save", "restored", "--config", &config]);
+    cmd.assert().success();
+
+    let output = discover(&config, &["restored.json", "--gap-limit", "3", "-o", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let result: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(result["used_count"], 3);
+    let accounts = result["accounts"].as_array().unwrap();
+    assert_eq!(accounts.len(), 3);
+    assert_eq!(accounts[0]["scanned"], 7);
+    assert_eq!(accounts[0]["used"][1]["index"], 3);
+    assert_eq!(accounts[0]["used"][1]["balance"], "1000");
+    assert_eq!(accounts[1]["used"][0]["derivation_path"], "m/44'/60'/1'/0/1");
+    assert_eq!(accounts[2]["used"].as_array().unwrap().len(), 0);
+
+    // A tighter gap limit misses the address behind the gap and account 1 behind an unused index 0
+    discover(&config, &["restored.json", "--gap-limit", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found 1 used address(es) in 1 account(s)"))
+        .stdout(predicate::str::contains("0xF3f50213C1d2e255e4B2bAD430F8A38EEF8D718E").not());
+
+    discover(&config, &["restored.json", "--account", "1", "--gap-limit", "3"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("0x61C1a3DD47433e58033cc812E520C0fFd9007198"))
+        .stdout(predicate::str::contains("Account 1 (m/44'/60'/1'): 1 used, next unused index 2"));
+}
+
+/// Test discovery needs the network and an HD wallet
+#[test]
+fn test_discover_command_rejects() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "\n[rpc.endpoints]\nmainnet = \"http://127.0.0.1:9\"\n");
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args([
+        "import", "--private-key", "0x1ab42cc412b618bdea3a599e3c9bae199ebf030895b039e9db1e30dafb12b727",
+        "--save", "single", "--config", &config,
+    ]);
+    cmd.assert().success();
+
+    discover(&config, &["single.json", "--offline"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("network access"));
+
+    discover(&config, &["single.json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("HD wallet with mnemonic"));
+}
@@ -0,0 +1,64 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{web3wallet, write_config, PASSWORD};
+use predicates::prelude::*;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const EXPECTED_ADDRESS: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
+
+fn mode(path: &Path) -> u32 {
+    std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+}
+
+/// Test saved keystores are owner only, a readable one is refused under --strict and
+/// doctor finds and fixes it
+#[test]
+fn test_doctor_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let wallets = temp_dir.path().join("wallets");
+    let config = &write_config(temp_dir.path(), "");
+
+    web3wallet(config, &["import", "--private-key", VALID_PRIVATE_KEY, "--save", "main", "--password-stdin"])
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .success();
+    let keystore = wallets.join("main.json");
+    assert_eq!(mode(&keystore), 0o600);
+
+    std::fs::set_permissions(&wallets, std::fs::Permissions::from_mode(0o700)).unwrap();
+    std::fs::set_permissions(&keystore, std::fs::Permissions::from_mode(0o644)).unwrap();
+    web3wallet(config, &["load", "main.json", "--password-stdin"])
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("readable by other users"));
+    web3wallet(config, &["load", "main.json", "--strict", "--password-stdin"])
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .code(5);
+
+    web3wallet(config, &["doctor", "--output", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"healthy\": false").and(predicate::str::contains("\"mode\": \"644\"")));
+    web3wallet(config, &["doctor", "--fix"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fixed"));
+    assert_eq!(mode(&keystore), 0o600);
+
+    web3wallet(config, &["doctor"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Permissions: ok"));
+    web3wallet(config, &["load", "main.json", "--strict", "--password-stdin"])
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS));
+}
@@ -0,0 +1,48 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use web3wallet_cli::services::ens::{is_ens_name, namehash, normalize};
+
+/// EIP-137 reference hashes
+#[test]
+fn test_namehash_vectors() {
+    assert_eq!(namehash(""), [0u8; 32]);
+    assert_eq!(
+        hex::encode(namehash("eth")),
+        "93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae"
+    );
+    assert_eq!(
+        hex::encode(namehash("foo.eth")),
+        "de9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f"
+    );
+}
+
+#[test]
+fn test_is_ens_name() {
+    assert!(is_ens_name("vitalik.eth"));
+    assert!(is_ens_name("pay.example.xyz"));
+    assert!(!is_ens_name("0x9858EfFD232B4033E47d90003D41EC34EcaEda94"));
+    assert!(!is_ens_name("vitalik"));
+    assert!(!is_ens_name("vitalik..eth"));
+    assert_eq!(normalize(" Vitalik.ETH. "), "vitalik.eth");
+}
+
+/// Names are rejected before any RPC call when they are not dot separated
+#[test]
+fn test_ens_resolve_rejects_plain_label() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["ens", "resolve", "vitalik"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("INPUT_001"));
+}
+
+#[test]
+fn test_ens_reverse_rejects_invalid_address() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["ens", "reverse", "0x1234"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("VALIDATION_001"));
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::{unlocked, write_config};
+use tempfile::TempDir;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const ACCOUNT_0_XPUB: &str = "xpub6DCoCpSuQZB2jawqnGMEPS63ePKWkwWPH4TU45Q7LPXWuNd8TMtVxRrgjtEshuqpK3mdhaWHPFsBngh5GFZaM6si3yZdUsT8ddYM3PwnATt";
+
+/// Test each error class exits with its documented status
+#[test]
+fn test_exit_codes_per_error_class() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "\n[rpc.endpoints]\nmainnet = \"http://127.0.0.1:9\"\n");
+    unlocked(&config, &["import", "--mnemonic", VALID_MNEMONIC_12, "--save", "main"]).assert().success();
+    unlocked(&config, &["import", "--xpub", ACCOUNT_0_XPUB, "--save", "watch"]).assert().success();
+
+    // Usage errors from the argument parser share the user input status
+    unlocked(&config, &["derive", "--account", "2147483648"]).assert().code(2);
+    unlocked(&config, &["discover", "main.json", "--offline"]).assert().code(2);
+
+    unlocked(&config, &["wallet", "delegate", "--from-file", "watch.json", "--derive-index", "0", "--export-to", "bot"])
+        .assert()
+        .code(3);
+    // Every word is in the list, the checksum is wrong
+    unlocked(&config, &["import", "--mnemonic", &["abandon"; 12].join(" ")])
+        .assert()
+        .code(4);
+    unlocked(&config, &["wallet", "delegate", "--from-file", "main.json", "--derive-index", "0", "--export-to", "watch"])
+        .assert()
+        .code(5);
+    unlocked(&config, &["discover", "main.json", "--timeout", "1"]).assert().code(6);
+    unlocked(&config, &["derive", "--from-file", "main.json", "--path", "5"]).assert().code(7);
+}
@@ -0,0 +1,109 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use tempfile::TempDir;
+
+const ADDRESS: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+const OTHER: &str = "0x000000000000000000000000000000000000dEaD";
+
+/// Etherscan-compatible API that answers a newest-first txlist query made with `api_key`,
+/// and reports any other query as an error
+fn fake_explorer(api_key: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/api", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+            }
+
+            let expected = ["action=txlist", "sort=desc", "offset=2", "chainid=11155111", &format!("apikey={}", api_key)];
+            let response = if expected.iter().all(|part| request_line.contains(part)) {
+                serde_json::json!({
+                    "status": "1",
+                    "message": "OK",
+                    "result": [
+                        {
+                            "blockNumber": "200", "timeStamp": "1735689600", "hash": format!("0x{}", "aa".repeat(32)),
+                            "from": ADDRESS.to_lowercase(), "to": OTHER.to_lowercase(), "value": "1500000000000000000",
+                            "gas": "21000", "gasUsed": "21000", "gasPrice": "1000000000", "isError": "0", "txreceipt_status": "1"
+                        },
+                        {
+                            "blockNumber": "100", "timeStamp": "1735603200", "hash": format!("0x{}", "bb".repeat(32)),
+                            "from": OTHER.to_lowercase(), "to": ADDRESS.to_lowercase(), "value": "0",
+                            "gas": "50000", "gasUsed": "30000", "gasPrice": "2000000000", "isError": "1", "txreceipt_status": "0"
+                        }
+                    ]
+                })
+            } else {
+                serde_json::json!({ "status": "0", "message": "NOTOK", "result": "unexpected query" })
+            }
+            .to_string();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .unwrap();
+        }
+    });
+    url
+}
+
+/// Test history reads the chain's own explorer API and shows direction, value, fee and status
+#[test]
+fn test_explorer_history_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "[explorer]\napi_key = \"default-key\"\n\n[explorer.networks.sepolia]\napi_url = {:?}\napi_key = \"sepolia-key\"\n",
+            fake_explorer("sepolia-key")
+        ),
+    )
+    .unwrap();
+    let config = config.to_str().unwrap();
+
+    let output = Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["history", ADDRESS, "--chain", "sepolia", "--limit", "2", "--output", "json", "--config", config])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    let history: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let transactions = history["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0]["direction"], "out");
+    assert_eq!(transactions[0]["to"], OTHER);
+    assert_eq!(transactions[0]["value"], "1.5");
+    assert_eq!(transactions[0]["fee"], "0.000021");
+    assert_eq!(transactions[0]["status"], "success");
+    assert_eq!(transactions[1]["direction"], "in");
+    assert_eq!(transactions[1]["status"], "failed");
+
+    Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["history", ADDRESS, "--chain", "sepolia", "--limit", "2", "--config", config])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1.5 ETH").and(predicate::str::contains("failed")));
+
+    Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["history", ADDRESS, "--chain", "sepolia", "--limit", "0", "--config", config])
+        .assert()
+        .code(2);
+}
@@ -0,0 +1,62 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Test a wallet exported to keystore v3 imports back to the same address
+#[test]
+fn test_export_command_v3_roundtrip() {
+    let wallet_name = "test_export_v3_wallet";
+    let password = "Test123!Password";
+
+    let home_dir = dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
+    let wallet_path = home_dir.join(".web3wallet").join("wallets").join(format!("{}.json", wallet_name));
+    let _ = std::fs::remove_file(&wallet_path);
+
+    let mut create_cmd = Command::cargo_bin("web3wallet").unwrap();
+    create_cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    create_cmd.args(["create", "--save", wallet_name, "--template", "{{address}}"]);
+    let assert = create_cmd.assert().success();
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout).to_string();
+    let address = stdout
+        .lines()
+        .find(|line| line.starts_with("0x"))
+        .expect("created address")
+        .trim()
+        .to_string();
+
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("exported.json");
+
+    let mut export_cmd = Command::cargo_bin("web3wallet").unwrap();
+    export_cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    export_cmd.args(["export", &format!("{}.json", wallet_name), "--format", "v3", "--light", "--out", out.to_str().unwrap()]);
+    export_cmd.assert().success();
+
+    let exported = std::fs::read_to_string(&out).unwrap();
+    assert!(exported.contains(r#""version": 3"#));
+    assert!(exported.contains(r#""kdf": "scrypt""#));
+
+    let mut import_cmd = Command::cargo_bin("web3wallet").unwrap();
+    import_cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    import_cmd.args(["import", "--keystore", out.to_str().unwrap()]);
+    import_cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(address));
+
+    let _ = std::fs::remove_file(&wallet_path);
+}
+
+/// Test export refuses to overwrite an existing file
+#[test]
+fn test_export_command_existing_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("exported.json");
+    std::fs::write(&out, "{}").unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["export", "nonexistent.json", "--out", out.to_str().unwrap()]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("FS_005"));
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "ffi")]
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+use web3wallet_cli::ffi::*;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const EXPECTED_ADDRESS: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+const PASSWORD: &str = "Test123!Password";
+
+/// Copy and free a string returned by the library
+unsafe fn take(value: *mut c_char) -> String {
+    let copy = CStr::from_ptr(value).to_str().unwrap().to_string();
+    w3w_string_free(value);
+    copy
+}
+
+/// Test import, encrypt, decrypt and derive round trip through the C functions
+#[test]
+fn test_ffi_round_trip() {
+    let mnemonic = CString::new(VALID_MNEMONIC_12).unwrap();
+    let password = CString::new(PASSWORD).unwrap();
+    unsafe {
+        let mut wallet = ptr::null_mut();
+        assert_eq!(w3w_import_mnemonic(mnemonic.as_ptr(), ptr::null(), &mut wallet), 0);
+
+        let mut keystore_json = ptr::null_mut();
+        assert_eq!(w3w_encrypt(wallet, password.as_ptr(), &mut keystore_json), 0);
+        w3w_wallet_free(wallet);
+
+        let mut decrypted = ptr::null_mut();
+        assert_eq!(w3w_decrypt(keystore_json, password.as_ptr(), &mut decrypted), 0);
+        let wrong = CString::new("Wrong123!Password").unwrap();
+        let mut unused = ptr::null_mut();
+        assert_eq!(w3w_decrypt(keystore_json, wrong.as_ptr(), &mut unused), 4);
+        assert!(unused.is_null());
+        w3w_string_free(keystore_json);
+
+        let mut address = ptr::null_mut();
+        assert_eq!(w3w_wallet_address(decrypted, &mut address), 0);
+        assert_eq!(take(address), EXPECTED_ADDRESS);
+
+        let mut list = W3wAddressList { items: ptr::null_mut(), len: 0 };
+        assert_eq!(w3w_derive(decrypted, 0, 2, &mut list), 0);
+        assert_eq!(list.len, 2);
+        let second = &*list.items.add(1);
+        assert_eq!(second.index, 1);
+        assert_eq!(CStr::from_ptr(second.derivation_path).to_str().unwrap(), "m/44'/60'/0'/0/1");
+        w3w_address_list_free(&mut list);
+        assert!(list.items.is_null());
+
+        w3w_wallet_free(decrypted);
+    }
+}
+
+/// Test a created wallet hands out its mnemonic, and errors carry a code and a message
+#[test]
+fn test_ffi_create_and_errors() {
+    unsafe {
+        let mut wallet = ptr::null_mut();
+        assert_eq!(w3w_create(24, &mut wallet), 0);
+        let mut mnemonic = ptr::null_mut();
+        assert_eq!(w3w_wallet_mnemonic(wallet, &mut mnemonic), 0);
+        assert_eq!(take(mnemonic).split_whitespace().count(), 24);
+        w3w_wallet_free(wallet);
+
+        let invalid = CString::new("abandon abandon abandon").unwrap();
+        let mut unused = ptr::null_mut();
+        assert_eq!(w3w_import_mnemonic(invalid.as_ptr(), ptr::null(), &mut unused), 4);
+        assert!(!w3w_last_error().is_null());
+
+        assert_eq!(w3w_import_private_key(ptr::null(), &mut unused), 2);
+        let message = CStr::from_ptr(w3w_last_error()).to_str().unwrap();
+        assert!(message.contains("INPUT"), "{}", message);
+        assert!(unused.is_null());
+    }
+}
+
+/// Test a passphrase wallet comes out of its keystore locked and derives once the passphrase is supplied
+#[test]
+fn test_ffi_unlock_passphrase() {
+    let mnemonic = CString::new(VALID_MNEMONIC_12).unwrap();
+    let passphrase = CString::new("TREZOR").unwrap();
+    let password = CString::new(PASSWORD).unwrap();
+    unsafe {
+        let mut wallet = ptr::null_mut();
+        assert_eq!(w3w_import_mnemonic(mnemonic.as_ptr(), passphrase.as_ptr(), &mut wallet), 0);
+        let mut address = ptr::null_mut();
+        assert_eq!(w3w_wallet_address(wallet, &mut address), 0);
+        let expected = take(address);
+        assert_ne!(expected, EXPECTED_ADDRESS);
+
+        let mut keystore_json = ptr::null_mut();
+        assert_eq!(w3w_encrypt(wallet, password.as_ptr(), &mut keystore_json), 0);
+        w3w_wallet_free(wallet);
+        assert!(!CStr::from_ptr(keystore_json).to_str().unwrap().contains("TREZOR"));
+
+        let mut decrypted = ptr::null_mut();
+        assert_eq!(w3w_decrypt(keystore_json, password.as_ptr(), &mut decrypted), 0);
+        w3w_string_free(keystore_json);
+        let mut needed = false;
+        assert_eq!(w3w_wallet_needs_passphrase(decrypted, &mut needed), 0);
+        assert!(needed);
+        let mut list = W3wAddressList { items: ptr::null_mut(), len: 0 };
+        assert_ne!(w3w_derive(decrypted, 0, 1, &mut list), 0);
+
+        let wrong = CString::new("trezor").unwrap();
+        assert_eq!(w3w_unlock_passphrase(decrypted, wrong.as_ptr()), 3);
+        assert!(CStr::from_ptr(w3w_last_error()).to_str().unwrap().contains("AUTH_009"));
+        assert_eq!(w3w_unlock_passphrase(decrypted, passphrase.as_ptr()), 0);
+        assert_eq!(w3w_wallet_needs_passphrase(decrypted, &mut needed), 0);
+        assert!(!needed);
+
+        assert_eq!(w3w_derive(decrypted, 0, 1, &mut list), 0);
+        assert_eq!(CStr::from_ptr((*list.items).address).to_str().unwrap(), expected);
+        w3w_address_list_free(&mut list);
+        w3w_wallet_free(decrypted);
+    }
+}
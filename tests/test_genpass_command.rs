@@ -0,0 +1,48 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+
+fn genpass(args: &[&str]) -> serde_json::Value {
+    let output = Command::cargo_bin("web3wallet")
+        .unwrap()
+        .arg("genpass")
+        .args(args)
+        .args(["--output", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// Test genpass makes fresh passwords and passphrases of the asked size that a keystore accepts
+#[test]
+fn test_genpass_command() {
+    let first = genpass(&[]);
+    let password = first["password"]["value"].as_str().unwrap();
+    assert_eq!(password.chars().count(), 24);
+    assert!(first["password"]["bits"].as_u64().unwrap() >= 128);
+    assert!(first.get("passphrase").is_none());
+    assert_ne!(genpass(&[])["password"]["value"], first["password"]["value"]);
+
+    let both = genpass(&["--length", "32", "--words", "6"]);
+    assert_eq!(both["password"]["value"].as_str().unwrap().chars().count(), 32);
+    assert_eq!(both["passphrase"]["value"].as_str().unwrap().split('-').count(), 6);
+    assert_eq!(both["passphrase"]["bits"], 66);
+    assert!(genpass(&["--words", "5"]).get("password").is_none());
+
+    Command::cargo_bin("web3wallet").unwrap().args(["genpass", "--length", "4"]).assert().code(2);
+    Command::cargo_bin("web3wallet").unwrap().args(["genpass", "--words", "2"]).assert().code(2);
+
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join("config.toml");
+    let wallets = temp_dir.path().join("wallets");
+    std::fs::write(&config, format!("wallets_path = {:?}\n", wallets.to_str().unwrap())).unwrap();
+    Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["import", "--private-key", VALID_PRIVATE_KEY, "--save", "main", "--password-stdin"])
+        .args(["--config", config.to_str().unwrap(), "--no-agent"])
+        .write_stdin(format!("{}\n", password))
+        .assert()
+        .success();
+}
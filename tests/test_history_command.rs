@@ -0,0 +1,158 @@
+mod common;
+
+use assert_cmd::Command;
+use common::write_config;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const EXPECTED_ADDRESS: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+const RECIPIENT: &str = "0x000000000000000000000000000000000000dEaD";
+
+fn entry(at: &str, amount: &str, note: &str) -> serde_json::Value {
+    serde_json::json!({
+        "at": at,
+        "chain": "mainnet",
+        "chain_id": 1,
+        "from": EXPECTED_ADDRESS,
+        "to": RECIPIENT,
+        "asset": "ETH",
+        "amount": amount,
+        "currency": "ETH",
+        "fee": "0.00042",
+        "hash": format!("0x{}", "ab".repeat(32)),
+        "note": note,
+        "broadcast": true
+    })
+}
+
+/// Test a signed transaction lands in the history and exports as a CSV row
+#[test]
+fn test_history_command_records_signed_transactions() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["import", "--mnemonic", VALID_MNEMONIC_12, "--save", "ledger_wallet", "--config", &config]);
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args([
+        "sign-tx", "--from-file", "ledger_wallet.json", "--to", RECIPIENT, "--value", "1500000000000000000",
+        "--nonce", "0", "--gas", "21000", "--max-fee", "20000000000", "--max-priority-fee", "1000000000",
+        "--chain-id", "1", "--note", "rent, March", "--config", &config,
+    ]);
+    cmd.assert().success();
+
+    let out = temp_dir.path().join("ledger.csv");
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["wallet", "history", "export", "--format", "csv", "--out", out.to_str().unwrap(), "--config", &config]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 1 transaction(s)"));
+
+    let csv = std::fs::read_to_string(&out).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], "date,chain,from,to,asset,amount,fee,fee_asset,tx_hash,note,status");
+    assert!(lines[1].contains(&format!(",mainnet,{},{},ETH,1.5,0.00042,ETH,0x", EXPECTED_ADDRESS, RECIPIENT)));
+    assert!(lines[1].ends_with(",\"rent, March\",signed"));
+}
+
+/// Test --period keeps only the entries inside it and notes cannot inject spreadsheet formulas
+#[test]
+fn test_history_command_period() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+    let state = temp_dir.path().join("state");
+    std::fs::create_dir_all(&state).unwrap();
+    let history = serde_json::json!({
+        "entries": [
+            entry("2024-12-31T23:59:59Z", "0.1", "last year"),
+            entry("2025-01-01T00:00:00Z", "0.2", "=HYPERLINK(\"http://example.com\")"),
+            entry("2025-03-31T12:00:00Z", "0.3", "payroll"),
+            entry("2025-04-01T00:00:00Z", "0.4", "next quarter"),
+        ]
+    });
+    std::fs::write(state.join("history.json"), history.to_string()).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["wallet", "history", "export", "--period", "2025-Q1", "--config", &config]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let csv = String::from_utf8(output).unwrap();
+
+    let rows: Vec<&str> = csv.lines().skip(1).collect();
+    assert_eq!(rows.len(), 2);
+    assert!(rows[0].starts_with("2025-01-01 00:00:00,mainnet,"));
+    assert!(rows[0].contains(",\"'=HYPERLINK(\"\"http://example.com\"\")\",broadcast"));
+    assert!(rows[1].contains(",0.3,") && rows[1].contains(",payroll,"));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["wallet", "history", "export", "--period", "2025-03", "--config", &config]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("payroll"))
+        .stdout(predicate::str::contains("HYPERLINK").not());
+}
+
+/// Test fiat columns from a fixed-rate file work offline, with dated rates ahead of the catch-all
+#[test]
+fn test_history_command_fixed_rates() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+    let state = temp_dir.path().join("state");
+    std::fs::create_dir_all(&state).unwrap();
+    let mut testnet = entry("2025-02-01T08:00:00Z", "5", "faucet");
+    testnet["chain"] = "sepolia".into();
+    testnet["chain_id"] = 11_155_111.into();
+    let history = serde_json::json!({
+        "entries": [
+            entry("2025-01-01T10:00:00Z", "0.2", "dated"),
+            testnet,
+            entry("2025-03-31T12:00:00Z", "0.3", "fallback"),
+        ]
+    });
+    std::fs::write(state.join("history.json"), history.to_string()).unwrap();
+    let rates = temp_dir.path().join("rates.csv");
+    std::fs::write(&rates, "date,asset,fiat,price\n*,ETH,usd,3000\n2025-01-01,ETH,usd,3500\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["wallet", "history", "export", "--fiat", "usd", "--rates-file", rates.to_str().unwrap()]);
+    cmd.args(["--offline", "--config", &config]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let csv = String::from_utf8(output).unwrap();
+
+    let lines: Vec<&str> = csv.lines().collect();
+    assert!(lines[0].ends_with(",status,price_usd,amount_usd,fee_usd"));
+    assert!(lines[1].ends_with(",dated,broadcast,3500.00,700.00,1.47"));
+    assert!(lines[2].ends_with(",faucet,broadcast,,,"));
+    assert!(lines[3].ends_with(",fallback,broadcast,3000.00,900.00,1.26"));
+
+    // Without a rates file the network sources are refused offline
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["wallet", "history", "export", "--fiat", "usd", "--price-source", "cryptocompare"]);
+    cmd.args(["--offline", "--config", &config]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--rates-file"));
+}
+
+/// Test malformed periods and fiat conversion without network access are rejected
+#[test]
+fn test_history_command_invalid() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["wallet", "history", "export", "--period", "2025-Q5"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("period"));
+
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["wallet", "history", "export", "--fiat", "usd", "--offline", "--config", &config]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--fiat"));
+}
@@ -0,0 +1,102 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use web3wallet_cli::errors::UserInputError;
+use web3wallet_cli::i18n::{tr_in, Lang};
+use web3wallet_cli::WalletError;
+
+fn message_ids(source: &str) -> Vec<&str> {
+    source
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.starts_with(' '))
+        .filter_map(|line| line.split_once(" =").map(|(id, _)| id.trim()))
+        .collect()
+}
+
+/// Test every English message has a Chinese translation
+#[test]
+fn test_locales_have_the_same_messages() {
+    let en = include_str!("../locales/en.ftl");
+    let zh = include_str!("../locales/zh.ftl");
+
+    let zh_ids = message_ids(zh);
+    for id in message_ids(en) {
+        assert!(zh_ids.contains(&id), "zh.ftl is missing {}", id);
+    }
+}
+
+/// Test messages are looked up in the requested language with arguments filled in
+#[test]
+fn test_translate_with_arguments() {
+    let en = tr_in(Lang::En, "prompt-password-for", &[("file", "alice.json")]);
+    assert_eq!(en, "Enter password for alice.json");
+
+    let zh = tr_in(Lang::Zh, "prompt-password-for", &[("file", "alice.json")]);
+    assert_eq!(zh, "请输入 alice.json 的密码");
+}
+
+/// Test an unknown message id falls back to the id itself
+#[test]
+fn test_unknown_message_falls_back_to_id() {
+    assert_eq!(tr_in(Lang::Zh, "no-such-message", &[]), "no-such-message");
+}
+
+/// Test the language is taken from POSIX locales and --lang values
+#[test]
+fn test_language_selection() {
+    assert_eq!(Lang::from_locale("zh_CN.UTF-8"), Lang::Zh);
+    assert_eq!(Lang::from_locale("zh_TW"), Lang::Zh);
+    assert_eq!(Lang::from_locale("en_US.UTF-8"), Lang::En);
+    assert_eq!(Lang::from_locale("C"), Lang::En);
+
+    assert_eq!("ZH".parse::<Lang>().unwrap(), Lang::Zh);
+    assert!("fr".parse::<Lang>().is_err());
+}
+
+/// Test errors carry a hint built from their own details
+#[test]
+fn test_error_suggestion() {
+    let err: WalletError = UserInputError::InvalidNetwork {
+        network: "notachain".to_string(),
+        supported: vec!["mainnet".to_string(), "sepolia".to_string()],
+    }
+    .into();
+
+    let hint = err.suggestion().unwrap();
+    assert!(hint.contains("mainnet, sepolia"));
+}
+
+/// Test --lang zh prints the hint of a failed command in Chinese
+#[test]
+fn test_failed_command_hint_in_chinese() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["--lang", "zh", "create", "--network", "notachain"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("INPUT_006"))
+        .stderr(predicate::str::contains("提示"))
+        .stderr(predicate::str::contains("web3wallet network add"));
+}
+
+/// Test the language is detected from LANG when --lang is not given
+#[test]
+fn test_language_detected_from_environment() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.env_remove("LC_ALL")
+        .env_remove("LC_MESSAGES")
+        .env("LANG", "zh_CN.UTF-8")
+        .args(["create", "--network", "notachain"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("已知网络"));
+}
+
+/// Test an unsupported --lang value is rejected
+#[test]
+fn test_unsupported_language() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["--lang", "fr", "list"]);
+
+    cmd.assert().failure();
+}
@@ -1,38 +1,53 @@
+mod common;
+
 use assert_cmd::Command;
+use common::write_config;
 use predicates::prelude::*;
 use tempfile::TempDir;
+use web3wallet_cli::services::crypto::CryptoService;
 
 const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
 const VALID_MNEMONIC_24: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
 const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
 const EXPECTED_ADDRESS: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
-const EXPECTED_PRIVATE_KEY_ADDRESS: &str = "0xc85117289fec250ddbab37f2a597af5bf950e3b0";
+const EXPECTED_PRIVATE_KEY_ADDRESS: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
 
 #[test]
 fn test_import_command_mnemonic_12(){
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&["import", "--mnemonic", VALID_MNEMONIC_12]);
+    cmd.args(["import", "--mnemonic", VALID_MNEMONIC_12]);
 
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("Address:"))
-        .stdout(predicate::str::contains(&EXPECTED_ADDRESS.to_lowercase()));
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS));
 }
 
 #[test]
 fn test_import_command_mnemonic_24(){
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&["import", "--mnemonic", VALID_MNEMONIC_24]);
+    cmd.args(["import", "--mnemonic", VALID_MNEMONIC_24]);
 
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("Address:"));
 }
 
+/// Test a Spanish mnemonic is detected and derives the standard BIP44 address
+#[test]
+fn test_import_command_mnemonic_spanish(){
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["import", "--mnemonic", "ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco abierto"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("0x97Eb7E2D802949D2739E08f9935Abd03A1e046Cb"));
+}
+
 #[test]
 fn test_import_command_private_key(){
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&["import", "--private-key", VALID_PRIVATE_KEY]);
+    cmd.args(["import", "--private-key", VALID_PRIVATE_KEY]);
 
     cmd.assert()
         .success()
@@ -43,28 +58,28 @@ fn test_import_command_private_key(){
 #[test]
 fn test_import_command_invalid_mnemonic() {
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&["import", "--mnemonic", "invalid mnemonic phrase"]);
+    cmd.args(["import", "--mnemonic", "invalid mnemonic phrase"]);
 
     cmd.assert()
         .failure()
-        .stdout(predicate::str::contains("CRYPTO_002"));
+        .stderr(predicate::str::contains("CRYPTO_002"));
 }
 
 
 #[test]
 fn test_import_command_invalid_private_key() {
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&["import", "--private-key", "invalid_key"]);
+    cmd.args(["import", "--private-key", "invalid_key"]);
 
     cmd.assert()
         .failure()
-        .stdout(predicate::str::contains("VALIDATION_001"));
+        .stderr(predicate::str::contains("VALIDATION_001"));
 }
 
 #[test]
 fn test_import_command_conflicting_options() {
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&[
+    cmd.args([
         "import",
         "--mnemonic", VALID_MNEMONIC_12,
         "--private-key", VALID_PRIVATE_KEY,
@@ -81,8 +96,8 @@ fn test_import_command_with_save() {
     let wallet_path = temp_dir.path().join("imported-wallet.json");
 
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.env("TEST_WALLET_PASSWORD", "TestPassword123!")
-        .args(&[
+    cmd.arg("--password-stdin").write_stdin("TestPassword123!\n")
+        .args([
             "import",
             "--mnemonic", VALID_MNEMONIC_12,
             "--save", wallet_path.to_str().unwrap(),
@@ -99,7 +114,7 @@ fn test_import_command_with_save() {
 #[test]
 fn test_import_command_json_output() {
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&[
+    cmd.args([
         "import",
         "--mnemonic", VALID_MNEMONIC_12,
         "--output", "json",
@@ -109,13 +124,13 @@ fn test_import_command_json_output() {
         .success()
         .stdout(predicate::str::contains(r#""success": true"#))
         .stdout(predicate::str::contains(r#""address":"#))
-        .stdout(predicate::str::contains(&EXPECTED_ADDRESS.to_lowercase()));
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS));
 }
 
 #[test]
 fn test_import_command_metamask_compatibility() {
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&[
+    cmd.args([
         "import",
         "--mnemonic", VALID_MNEMONIC_12,
         "--output", "json",
@@ -125,13 +140,13 @@ fn test_import_command_metamask_compatibility() {
     let output_str = String::from_utf8(output).unwrap();
 
     // Should generate the same address as MetaMask for this mnemonic
-    assert!(output_str.contains(&EXPECTED_ADDRESS.to_lowercase()));
+    assert!(output_str.contains(EXPECTED_ADDRESS));
 }
 
 #[test]
 fn test_import_command_custom_network() {
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&[
+    cmd.args([
         "import",
         "--mnemonic", VALID_MNEMONIC_12,
         "--network", "sepolia",
@@ -148,7 +163,7 @@ fn test_import_command_performance() {
 
     let start = Instant::now();
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&["import", "--mnemonic", VALID_MNEMONIC_12]);
+    cmd.args(["import", "--mnemonic", VALID_MNEMONIC_12]);
 
     cmd.assert().success();
 
@@ -160,7 +175,7 @@ fn test_import_command_performance() {
 #[test]
 fn test_import_command_help() {
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&["import", "--help"]);
+    cmd.args(["import", "--help"]);
 
     cmd.assert()
         .success()
@@ -178,5 +193,347 @@ fn test_import_command_missing_source() {
 
     cmd.assert()
         .failure()
-        .stdout(predicate::str::contains("INPUT_003")); // Missing required parameter
-}
\ No newline at end of file
+        .stderr(predicate::str::contains("INPUT_003")); // Missing required parameter
+}
+#[test]
+fn test_import_command_template_output() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args([
+        "import",
+        "--mnemonic", VALID_MNEMONIC_12,
+        "--network", "sepolia",
+        "--template", "{{address}} {{network}}",
+    ]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("{} sepolia", EXPECTED_ADDRESS)));
+}
+
+// Web3 Secret Storage spec pbkdf2 test vector, password "testpassword"
+const V3_KEYSTORE: &str = r#"{
+    "crypto": {
+        "cipher": "aes-128-ctr",
+        "cipherparams": { "iv": "6087dab2f9fdbbfaddc31a909735c1e6" },
+        "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+        "kdf": "pbkdf2",
+        "kdfparams": {
+            "c": 262144,
+            "dklen": 32,
+            "prf": "hmac-sha256",
+            "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+        },
+        "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+    },
+    "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+    "version": 3
+}"#;
+
+#[test]
+fn test_import_command_keystore_v3() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("geth-keystore.json");
+    std::fs::write(&path, V3_KEYSTORE).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("testpassword\n");
+    cmd.args(["import", "--keystore", path.to_str().unwrap()]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("0x008AeEda4D805471dF9b2A5B0f38A0C3bCBA786b"));
+}
+
+#[test]
+fn test_import_command_keystore_v3_wrong_password() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("geth-keystore.json");
+    std::fs::write(&path, V3_KEYSTORE).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("wrongpassword\n");
+    cmd.args(["import", "--keystore", path.to_str().unwrap()]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Command failed"));
+}
+
+/// Test a wallet saved with `--kdf scrypt` records scrypt and loads back
+#[test]
+fn test_import_command_save_with_scrypt() {
+    let temp_dir = TempDir::new().unwrap();
+    let wallets = temp_dir.path().join("wallets");
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        format!("wallets_path = {:?}\n\n[kdf]\nscrypt_log_n = 10\n", wallets.to_str().unwrap()),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args([
+        "import", "--private-key", VALID_PRIVATE_KEY, "--save", "scrypt_wallet", "--kdf", "scrypt",
+        "--config", config.to_str().unwrap(),
+    ]);
+    cmd.assert().success();
+
+    let keystore = std::fs::read_to_string(wallets.join("scrypt_wallet.json")).unwrap();
+    assert!(keystore.contains(r#""kdf": "scrypt""#));
+    assert!(keystore.contains(r#""n": 1024"#));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["load", "scrypt_wallet.json", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_PRIVATE_KEY_ADDRESS));
+}
+
+/// BIP39 test vector: root key of "abandon ... about" with passphrase "TREZOR"
+const TREZOR_ROOT_XPRV: &str = "xprv9s21ZrQH143K3h3fDYiay8mocZ3afhfULfb5GX8kCBdno77K4HiA15Tg23wpbeF1pLfs1c5SPmYHrEpTuuRhxMwvKDwqdKiGJS9XFKzUsAF";
+
+/// Passphrase "TREZOR" as typed at the prompt and its confirmation
+const TREZOR_PASSPHRASE_INPUT: &str = "TREZOR\nTREZOR\n";
+
+fn imported_address(args: &[&str], input: &str) -> String {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(args).args(["--template", "{{address}}"]).write_stdin(input);
+    let assert = cmd.assert().success();
+    String::from_utf8_lossy(&assert.get_output().stdout)
+        .lines()
+        .find(|line| line.starts_with("0x"))
+        .expect("imported address")
+        .trim()
+        .to_string()
+}
+
+/// Test an xprv import yields the same HD wallet as the mnemonic it came from
+#[test]
+fn test_import_command_xprv_matches_mnemonic() {
+    let from_mnemonic = imported_address(&["import", "--mnemonic", VALID_MNEMONIC_12, "--passphrase"], TREZOR_PASSPHRASE_INPUT);
+    let from_xprv = imported_address(&["import", "--xprv", TREZOR_ROOT_XPRV], "");
+    assert_eq!(from_mnemonic, from_xprv);
+
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(&config, format!("wallets_path = {:?}\n", temp_dir.path().join("wallets").to_str().unwrap())).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["import", "--xprv", TREZOR_ROOT_XPRV, "--save", "xprv_wallet", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("seed or root key"));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["load", "xprv_wallet.json", "--derive", "1", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(from_xprv))
+        .stdout(predicate::str::contains("m/44'/60'/0'/0/1"));
+}
+
+/// Test a malformed extended key is rejected
+#[test]
+fn test_import_command_invalid_xprv() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["import", "--xprv", "xprv-not-a-key"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Command failed"));
+}
+
+/// BIP39 test vector: seed of "abandon ... about" with passphrase "TREZOR"
+const TREZOR_SEED_HEX: &str = "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04";
+
+/// Test a seed import yields the same default account as the mnemonic it came from
+#[test]
+fn test_import_command_seed_hex_matches_mnemonic() {
+    let from_mnemonic = imported_address(&["import", "--mnemonic", VALID_MNEMONIC_12, "--passphrase"], TREZOR_PASSPHRASE_INPUT);
+    let from_seed = imported_address(&["import", "--seed-hex", TREZOR_SEED_HEX], "");
+    assert_eq!(from_mnemonic, from_seed);
+}
+
+/// Test the BIP39 passphrase is left out of the keystore and asked for again on unlock
+#[tokio::test]
+async fn test_import_command_passphrase_not_saved() {
+    let from_seed = imported_address(&["import", "--seed-hex", TREZOR_SEED_HEX], "");
+    let temp_dir = TempDir::new().unwrap();
+    let wallets = temp_dir.path().join("wallets");
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(&config, format!("wallets_path = {:?}\n", wallets.to_str().unwrap())).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin(format!("{}Test123!Password\n", TREZOR_PASSPHRASE_INPUT));
+    cmd.args(["import", "--mnemonic", VALID_MNEMONIC_12, "--passphrase", "--save", "passphrase_wallet", "--config", config.to_str().unwrap()]);
+    cmd.assert().success().stdout(predicate::str::contains(from_seed.as_str()));
+
+    // The password alone opens neither the passphrase nor a key derived under it
+    let keystore = CryptoService::load_keystore(wallets.join("passphrase_wallet.json")).await.unwrap();
+    let wallet = CryptoService::decrypt_wallet(&keystore, "Test123!Password").unwrap();
+    assert!(wallet.needs_passphrase());
+    assert!(wallet.signer().unwrap_err().to_string().contains("AUTH_009"));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\nTREZOR\n");
+    cmd.args(["load", "passphrase_wallet.json", "--config", config.to_str().unwrap()]);
+    cmd.assert().success().stdout(predicate::str::contains(from_seed.as_str()));
+
+    for input in ["Test123!Password\nwrong\n", "Test123!Password\n"] {
+        let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+        cmd.arg("--password-stdin").write_stdin(input);
+        cmd.args(["load", "passphrase_wallet.json", "--config", config.to_str().unwrap()]);
+        cmd.assert().code(3).stderr(predicate::str::contains("AUTH_009"));
+    }
+}
+
+/// Test seeds of the wrong length are rejected
+#[test]
+fn test_import_command_seed_hex_wrong_length() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["import", "--seed-hex", &TREZOR_SEED_HEX[..64]]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("CRYPTO_003"));
+}
+
+/// Account xpubs of "abandon ... about", m/44'/60'/0' and m/44'/60'/1'
+const ACCOUNT_0_XPUB: &str = "xpub6DCoCpSuQZB2jawqnGMEPS63ePKWkwWPH4TU45Q7LPXWuNd8TMtVxRrgjtEshuqpK3mdhaWHPFsBngh5GFZaM6si3yZdUsT8ddYM3PwnATt";
+const ACCOUNT_1_XPUB: &str = "xpub6DCoCpSuQZB2k9PnGSMK9tinTK8kx3hcv7F4BWwhs5N2wnwGiLg17r9J7j2JcYP9gkip3sC87J1F99YxeBHGuFMg6ejA8qQEKSuzzaKvqBR";
+
+/// Test an xpub imports a watch-only wallet that derives the mnemonic's addresses but never signs
+#[test]
+fn test_import_command_xpub_watch_only() {
+    assert_eq!(imported_address(&["import", "--xpub", ACCOUNT_0_XPUB], ""), EXPECTED_ADDRESS);
+    assert_eq!(
+        imported_address(&["import", "--xpub", ACCOUNT_1_XPUB], ""),
+        "0x78839F6054d7ed13918bAe0473BA31b1Ca9D7265"
+    );
+
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["import", "--xpub", ACCOUNT_0_XPUB, "--save", "watch", "--config", &config]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Watch-only"));
+    let keystore = std::fs::read_to_string(temp_dir.path().join("wallets").join("watch.json")).unwrap();
+    assert!(keystore.contains(r#""wallet_type": "watch_only""#));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["derive", "--from-file", "watch.json", "--count", "3", "--no-pager", "--config", &config]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("0x6Fac4D18c912343BF86fa7049364Dd4E424Ab9C0"))
+        .stdout(predicate::str::contains("0xb6716976A3ebe8D39aCEB04372f22Ff8e6802D7A"));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["list", "--config", &config]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("watch-only"));
+
+    // Refused before the password is asked for
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args([
+        "sign-tx", "--from-file", "watch.json", "--to", EXPECTED_PRIVATE_KEY_ADDRESS, "--value", "1", "--nonce", "0",
+        "--gas", "21000", "--max-fee", "20000000000", "--max-priority-fee", "1000000000", "--chain-id", "1",
+        "--config", &config,
+    ]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("AUTH_007"));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["sign-message", "--from-file", "watch.json", "--message", "hello", "--config", &config]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("AUTH_007"));
+}
+
+/// Test an address imports a watch-only wallet without derivation, and xpubs below the account level are refused
+#[test]
+fn test_import_command_address_watch_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["import", "--address", &EXPECTED_ADDRESS.to_lowercase(), "--save", "cold_watch", "--config", &config]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS))
+        .stdout(predicate::str::contains("single address"));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["derive", "--from-file", "cold_watch.json", "--config", &config]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("INPUT_001"));
+
+    let change_level = "xpub6EF8jXqFeFEW5bwMU7RpQtHkzE4KJxcqJtvkCjJumzW8CPpacXkb92ek4WzLQXjL93HycJwTPUAcuNxCqFPKKU5m5Z2Vq4nCyh5CyPeBFFr";
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["import", "--xpub", change_level]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("CRYPTO_003"));
+}
+
+/// Test --path-preset picks the primary address of that layout and the saved wallet keeps deriving in it
+#[test]
+fn test_import_command_path_preset() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["import", "--mnemonic", VALID_MNEMONIC_12, "--path-preset", "legacy"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("0xB8Fd42000d00202DCbCF5e18d6640d656345FD6A"));
+
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args([
+        "import", "--mnemonic", VALID_MNEMONIC_12, "--path-preset", "ledger-live", "--save", "live", "--config", &config,
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS))
+        .stdout(predicate::str::contains("ledger-live (m/44'/60'/{index}'/0/0)"));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["load", "live.json", "--derive", "2", "--config", &config]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("0x07B5FdfEB4E11826D233403Fe8Db0611CCF4c231"));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["import", "--private-key", VALID_PRIVATE_KEY, "--path-preset", "legacy"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+/// Test a wallet saved and unlocked with --harden still derives from its mnemonic
+#[test]
+fn test_import_command_harden() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["--harden", "import", "--mnemonic", VALID_MNEMONIC_12, "--save", "hardened", "--config", &config]);
+    cmd.assert().success().stdout(predicate::str::contains(EXPECTED_ADDRESS));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["derive", "--from-file", "hardened.json", "--count", "1", "--harden", "--no-agent", "--config", &config]);
+    cmd.assert().success().stdout(predicate::str::contains(EXPECTED_ADDRESS));
+}
@@ -0,0 +1,38 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Test a single KDF is benchmarked and reported
+#[test]
+fn test_kdf_bench_command_pbkdf2() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["kdf-bench", "--kdf", "pbkdf2", "--target-ms", "50", "--output", "json"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(r#""algorithm": "pbkdf2""#))
+        .stdout(predicate::str::contains(r#""iterations":"#))
+        .stdout(predicate::str::contains("argon2id").not());
+}
+
+/// Test `--write` records the suggestion in the config file, keeping its comments
+#[test]
+fn test_kdf_bench_command_write() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(&config, "# my settings\nnetwork = \"sepolia\"\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["kdf-bench", "--kdf", "scrypt", "--target-ms", "50", "--write", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote [kdf] settings"));
+
+    let written = std::fs::read_to_string(&config).unwrap();
+    assert!(written.contains("# my settings"));
+    assert!(written.contains(r#"algorithm = "scrypt""#));
+    assert!(written.contains("scrypt_log_n = "));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["config", "validate", config.to_str().unwrap()]);
+    cmd.assert().success();
+}
@@ -16,9 +16,81 @@ fn test_list_command_empty() {
 #[test]
 fn test_list_command_json() {
     let mut cmd = Command::cargo_bin("web3wallet").unwrap();
-    cmd.args(&["list", "--output", "json"]);
+    cmd.args(["list", "--output", "json"]);
 
     cmd.assert()
         .success()
         .stdout(predicate::str::contains(r#""success": true"#));
-}
\ No newline at end of file
+}
+/// Test the library returns a typed listing other frontends can render
+#[tokio::test]
+async fn test_list_outcome_from_library() {
+    let dir = tempfile::tempdir().unwrap();
+    let outcome = web3wallet_cli::prelude::commands::list(dir.path()).await.unwrap();
+
+    assert!(!outcome.created);
+    assert!(outcome.wallets.is_empty());
+    assert_eq!(outcome.directory, dir.path());
+}
+
+/// Test a manager built without a config file saves where names resolve and lists what it saved
+#[tokio::test]
+async fn test_wallet_manager_builder() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = web3wallet_cli::WalletManager::default()
+        .with_network("sepolia")
+        .with_wallet_dir(dir.path())
+        .with_kdf(web3wallet_cli::models::keystore::KdfAlgorithm::Pbkdf2);
+    assert_eq!(manager.save_path("alice"), dir.path().join("alice.json"));
+    assert_eq!(manager.wallet_path("/tmp/other.json"), std::path::PathBuf::from("/tmp/other.json"));
+
+    let mut answers = vec!["Test123!Password", "Other456!Password"].into_iter();
+    let mismatch = manager.save_options("alice", false, false, |_| Ok(answers.next().unwrap().to_string()));
+    assert!(mismatch.is_err());
+
+    let save = manager.save_options("alice", false, false, |_| Ok("Test123!Password".to_string())).unwrap();
+    let wallet = manager.create_wallet(12).await.unwrap();
+    manager.save_wallet(&wallet, &save.path, &save.password).await.unwrap();
+
+    let outcome = manager.list().await.unwrap();
+    assert_eq!(outcome.wallets.len(), 1);
+    assert_eq!(outcome.wallets[0].filename, "alice.json");
+    assert_eq!(outcome.wallets[0].network, "sepolia");
+}
+
+/// Test list is served from the index, notices changed keystores, and filters and sorts
+#[test]
+fn test_list_command_index() {
+    let dir = tempfile::tempdir().unwrap();
+    let wallets = dir.path().join("wallets");
+    let config = dir.path().join("config.toml");
+    std::fs::write(&config, format!("wallets_path = {:?}\n", wallets.to_str().unwrap())).unwrap();
+    let config = config.to_str().unwrap();
+
+    for (name, network) in [("b-main", "mainnet"), ("a-sepolia", "sepolia")] {
+        Command::cargo_bin("web3wallet")
+            .unwrap()
+            .args(["create", "--save", name, "--network", network, "--skip-verify", "--password-stdin", "--no-agent", "--config", config])
+            .write_stdin("Test123!Password\nTest123!Password\n")
+            .assert()
+            .success();
+    }
+    std::fs::write(wallets.join("notes.json"), "{}").unwrap();
+
+    let list = |args: &[&str]| {
+        let output = Command::cargo_bin("web3wallet").unwrap().args(["list", "-o", "json", "--config", config]).args(args).output().unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        value["wallets"].as_array().unwrap().iter().map(|w| w["filename"].as_str().unwrap().to_string()).collect::<Vec<_>>()
+    };
+    assert_eq!(list(&[]), ["a-sepolia.json", "b-main.json"]);
+    assert!(wallets.join(".wallets.index").exists());
+
+    assert_eq!(list(&["--network", "mainnet"]), ["b-main.json"]);
+    assert_eq!(list(&["--sort", "network"]), ["b-main.json", "a-sepolia.json"]);
+
+    // A deleted keystore leaves the listing even though the index still had it
+    std::fs::remove_file(wallets.join("b-main.json")).unwrap();
+    assert_eq!(list(&[]), ["a-sepolia.json"]);
+
+    Command::cargo_bin("web3wallet").unwrap().args(["list", "--sort", "size", "--config", config]).assert().failure();
+}
@@ -0,0 +1,91 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+use web3wallet_cli::models::keystore::{upgrade_json, KeystoreVersion};
+use web3wallet_cli::models::Keystore;
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const EXPECTED_ADDRESS: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
+const PASSWORD: &str = "Test123!Password";
+
+/// Save a wallet, then rewrite it the way version 1.0.0 laid keystores out
+fn legacy_keystore(temp_dir: &TempDir) -> (std::path::PathBuf, std::path::PathBuf) {
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(&config, format!("wallets_path = {:?}\n", temp_dir.path().join("wallets").to_str().unwrap())).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin(format!("{}\n", PASSWORD));
+    cmd.args(["import", "--private-key", VALID_PRIVATE_KEY, "--save", "legacy", "--config", config.to_str().unwrap()]);
+    cmd.assert().success();
+
+    let path = temp_dir.path().join("wallets").join("legacy.json");
+    let mut value: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    value["version"] = "1.0.0".into();
+    value["crypto"]["kdf"] = "argon2".into();
+    let metadata = value["metadata"].as_object_mut().unwrap();
+    metadata.remove("cold");
+    metadata.remove("backup_verified");
+    std::fs::write(&path, serde_json::to_string_pretty(&value).unwrap()).unwrap();
+
+    (config, path)
+}
+
+/// Test migrate upgrades a 1.0.0 keystore in place and it still decrypts
+#[test]
+fn test_migrate_command_upgrades_legacy_keystore() {
+    let temp_dir = TempDir::new().unwrap();
+    let (config, path) = legacy_keystore(&temp_dir);
+    let legacy = std::fs::read_to_string(&path).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["migrate", "legacy.json", "--dry-run", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("would migrate"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), legacy);
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["migrate", "--all", "--output", "json", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(r#""from_version": "1.0.0""#))
+        .stdout(predicate::str::contains(r#""migrated": true"#));
+
+    let migrated: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(migrated["version"], "1.1.0");
+    assert_eq!(migrated["crypto"]["kdf"], "argon2id");
+    assert_eq!(migrated["metadata"]["cold"], false);
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin(format!("{}\n", PASSWORD));
+    cmd.args(["load", "legacy.json", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS));
+}
+
+/// Test validation follows each version's rules and unknown versions are refused
+#[test]
+fn test_keystore_validation_dispatches_on_version() {
+    let temp_dir = TempDir::new().unwrap();
+    let (_, path) = legacy_keystore(&temp_dir);
+    let mut value: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+
+    // 1.0.0 treats `kdf` as informational
+    let legacy: Keystore = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(legacy.schema_version().unwrap(), KeystoreVersion::V1_0);
+    legacy.validate().unwrap();
+
+    // 1.1.0 requires it to match the parameters
+    let mut mismatched = legacy.clone();
+    mismatched.version = KeystoreVersion::LATEST.as_str().to_string();
+    assert!(mismatched.validate().unwrap_err().to_string().contains("does not match"));
+
+    assert_eq!(upgrade_json(&mut value).unwrap(), KeystoreVersion::V1_0);
+    let upgraded: Keystore = serde_json::from_value(value.clone()).unwrap();
+    upgraded.validate().unwrap();
+    assert_eq!(upgrade_json(&mut value).unwrap(), KeystoreVersion::LATEST);
+
+    value["version"] = "9.0.0".into();
+    assert!(upgrade_json(&mut value).unwrap_err().to_string().contains("Unsupported keystore version 9.0.0"));
+}
@@ -0,0 +1,105 @@
+use bip39::Language;
+use web3wallet_cli::services::mnemonic::{parse_language, wordlist_index, wordlist_word, MnemonicService, WordQuiz};
+
+const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+/// Test wordlist lookups and the bits each word encodes
+#[test]
+fn test_mnemonic_word_indices_and_bits() {
+    let mnemonic = MnemonicService::validate(MNEMONIC).unwrap();
+
+    let indices = mnemonic.word_indices().unwrap();
+    assert_eq!(indices[..11], [0; 11]);
+    assert_eq!(indices[11], 3);
+    assert_eq!(wordlist_index("zoo"), Some(2047));
+    assert_eq!(wordlist_word(3), Some("about"));
+    assert_eq!(wordlist_index("notaword"), None);
+
+    let bits = mnemonic.word_bits().unwrap();
+    assert_eq!(bits[0].bits, "00000000000");
+    assert_eq!(bits[11].position, 12);
+    assert_eq!(bits[11].bits, "00000000011");
+}
+
+/// Test the backup quiz accepts the right words and names the wrong positions
+#[test]
+fn test_mnemonic_quiz() {
+    let mnemonic = MnemonicService::validate(MNEMONIC).unwrap();
+
+    let quiz = mnemonic.quiz(3);
+    assert_eq!(quiz.positions().len(), 3);
+    assert!(quiz.positions().windows(2).all(|w| w[0] < w[1]));
+
+    let quiz = WordQuiz::with_positions(12, vec![2, 12]);
+    assert_eq!(quiz.prompts(), ["Word 2 of 12", "Word 12 of 12"]);
+    assert!(quiz.check(&mnemonic, &["abandon", "ABOUT"]).is_ok());
+
+    let err = quiz.check(&mnemonic, &["abandon", "zoo"]).unwrap_err().to_string();
+    assert!(err.contains("word 12 of 12"));
+    assert!(!err.contains("about"));
+}
+
+/// Test mnemonics generated in other wordlists are detected as such when read back
+#[test]
+fn test_mnemonic_languages() {
+    for language in [Language::Japanese, Language::Spanish, Language::SimplifiedChinese] {
+        let generated = MnemonicService::generate_in(12, language).unwrap();
+        assert_eq!(generated.language(), language);
+
+        let validated = MnemonicService::validate(generated.phrase()).unwrap();
+        assert_eq!(validated.language(), language);
+        assert_eq!(MnemonicService::detect_language(generated.phrase()).unwrap(), language);
+        assert_eq!(validated.word_indices().unwrap().len(), 12);
+    }
+
+    assert_eq!(MnemonicService::detect_language(MNEMONIC).unwrap(), Language::English);
+    assert_eq!(parse_language("chinese-simplified").unwrap(), Language::SimplifiedChinese);
+    assert!(parse_language("klingon").is_err());
+}
+
+/// Test every BIP39 length generates and reads back, and other lengths are refused
+#[test]
+fn test_mnemonic_word_counts() {
+    for words in [12u8, 15, 18, 21, 24] {
+        let generated = MnemonicService::generate(words).unwrap();
+        assert_eq!(generated.phrase().split_whitespace().count(), words as usize);
+        assert!(MnemonicService::validate(generated.phrase()).is_ok());
+    }
+
+    // 18-word vector of the reference implementation (all-zero entropy)
+    let eighteen = format!("{} agent", vec!["abandon"; 17].join(" "));
+    assert_eq!(MnemonicService::validate(&eighteen).unwrap().word_indices().unwrap().len(), 18);
+
+    assert!(MnemonicService::generate(16).is_err());
+}
+
+/// Test a healthy OS RNG passes the entropy check on repeated runs
+#[test]
+fn test_entropy_check_passes_repeatedly() {
+    for _ in 0..3 {
+        MnemonicService::check_entropy_availability(128).unwrap();
+    }
+}
+
+/// Test the cross-run sample history lives in the given state directory, readable by the
+/// owner only and capped at its configured length
+#[tokio::test]
+async fn test_entropy_history_in_state_dir() {
+    let state = tempfile::tempdir().unwrap();
+    let history = state.path().join(web3wallet_cli::config::crypto::ENTROPY_FINGERPRINT_FILE);
+
+    MnemonicService::check_entropy_history(state.path(), 12).await.unwrap();
+    assert_eq!(std::fs::read_to_string(&history).unwrap().lines().count(), 1);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&history).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    let limit = web3wallet_cli::config::crypto::ENTROPY_FINGERPRINT_HISTORY;
+    for _ in 0..limit {
+        MnemonicService::check_entropy_history(state.path(), 12).await.unwrap();
+    }
+    assert_eq!(std::fs::read_to_string(&history).unwrap().lines().count(), limit);
+    assert!(MnemonicService::check_entropy_history(state.path(), 16).await.is_err());
+}
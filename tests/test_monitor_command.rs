@@ -0,0 +1,24 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Test monitor requires at least one threshold
+#[test]
+fn test_monitor_command_requires_threshold() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["monitor", "--once"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Command failed"));
+}
+
+/// Test malformed thresholds are rejected
+#[test]
+fn test_monitor_command_invalid_threshold() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["monitor", "--once", "--min-balance", "mainnet:0.05"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Command failed"));
+}
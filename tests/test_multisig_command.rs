@@ -0,0 +1,120 @@
+mod common;
+
+use common::{unlocked, write_config};
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const ALICE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const BOB_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+const CAROL_KEY: &str = "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+const CAROL: &str = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+const RECIPIENT: &str = "0x000000000000000000000000000000000000dEaD";
+
+fn json(config: &str, args: &[&str]) -> serde_json::Value {
+    let output = unlocked(config, args).args(["--output", "json"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// Test a 2-of-3 multisig signs a proposed transaction from its own address once two owners
+/// approved it, and not with a single approval or an altered proposal
+#[test]
+fn test_multisig_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "offline = true\n\n[kdf]\nalgorithm = \"pbkdf2\"\n");
+    let file = |name: &str| temp_dir.path().join(name).to_str().unwrap().to_string();
+    for (name, key) in [("alice", ALICE_KEY), ("bob", BOB_KEY), ("carol", CAROL_KEY)] {
+        unlocked(&config, &["import", "--private-key", key, "--save", name]).assert().success();
+    }
+
+    // Carol takes part from another machine, with only her public owner key
+    let carol = json(&config, &["multisig", "owner-key", "-f", "carol.json"]);
+    assert_eq!(carol["address"], CAROL);
+    let carol_spec = carol["owner"].as_str().unwrap();
+    assert!(carol_spec.starts_with(&format!("{}:", CAROL)));
+
+    let created = json(
+        &config,
+        &[
+            "multisig", "create", "treasury", "--threshold", "2", "--owner", "alice.json", "--owner", "bob.json",
+            "--owner", carol_spec,
+        ],
+    );
+    assert_eq!(created["threshold"], 2);
+    assert_eq!(created["owners"].as_array().unwrap().len(), 3);
+    assert_eq!(created["owners"][2], CAROL);
+    let multisig = created["address"].as_str().unwrap();
+    let keystore = std::fs::read_to_string(created["file"].as_str().unwrap()).unwrap();
+    assert!(!keystore.contains(&ALICE_KEY[2..]));
+    unlocked(&config, &["multisig", "create", "treasury", "--threshold", "2", "--owner", carol_spec])
+        .assert()
+        .failure();
+
+    std::fs::write(
+        file("tx.json"),
+        serde_json::json!({
+            "to": RECIPIENT,
+            "value": "1000",
+            "nonce": "0",
+            "gas": "21000",
+            "max_fee_per_gas": "30000000000",
+            "max_priority_fee_per_gas": "1000000000",
+            "chain_id": 1,
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let proposed = json(
+        &config,
+        &[
+            "multisig", "propose", "treasury.json", "--tx-json", &file("tx.json"), "-f", "alice.json", "--note",
+            "payroll", "--out", &file("proposal.json"),
+        ],
+    );
+    assert_eq!(proposed["proposal"]["multisig"], multisig);
+    assert_eq!(proposed["proposal"]["note"], "payroll");
+
+    for owner in ["bob", "carol"] {
+        unlocked(
+            &config,
+            &[
+                "multisig", "approve", "treasury.json", &file("proposal.json"), "-f", &format!("{}.json", owner),
+                "--yes", "--out", &file(&format!("{}.approval.json", owner)),
+            ],
+        )
+        .assert()
+        .success();
+    }
+
+    let combine = |approvals: &[String]| {
+        let mut args = vec!["multisig", "combine", "treasury.json", "-f", "alice.json"];
+        let proposal = file("proposal.json");
+        args.push(&proposal);
+        args.extend(approvals.iter().map(String::as_str));
+        unlocked(&config, &args)
+    };
+    combine(&[file("bob.approval.json")])
+        .assert()
+        .code(7)
+        .stderr(predicate::str::contains("1 of the 2 approvals needed"));
+
+    let output = combine(&[file("bob.approval.json"), file("carol.approval.json")])
+        .args(["--output", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    let signed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(signed["from"], multisig);
+
+    let decoded = json(&config, &["decode-tx", signed["raw"].as_str().unwrap(), "--chain-id", "1"]);
+    assert_eq!(decoded["from"], multisig);
+    assert_eq!(decoded["to"], RECIPIENT);
+    assert_eq!(decoded["value"], "1000");
+
+    // Approvals do not carry over to a proposal with a different recipient
+    let mut altered: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(file("proposal.json")).unwrap()).unwrap();
+    altered["tx"]["to"] = serde_json::json!(CAROL);
+    std::fs::write(file("proposal.json"), altered.to_string()).unwrap();
+    combine(&[file("bob.approval.json"), file("carol.approval.json")]).assert().code(7);
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use assert_cmd::Command;
+use common::web3wallet;
+use predicates::prelude::*;
+
+/// Test a network added to the config file can be listed, used and removed again
+#[test]
+fn test_network_add_list_remove() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(&config_path, "# my settings\nnetwork = \"sepolia\"\n").unwrap();
+    let config = config_path.to_str().unwrap();
+
+    web3wallet(
+        config,
+        &["network", "add", "gnosis", "--chain-id", "100", "--rpc-url", "https://rpc.gnosischain.com", "--symbol", "xDAI"],
+    )
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("Added network gnosis"));
+
+    let written = std::fs::read_to_string(&config_path).unwrap();
+    assert!(written.contains("# my settings"));
+    assert!(written.contains("[networks.gnosis]"));
+
+    web3wallet(config, &["network", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("gnosis"))
+        .stdout(predicate::str::contains("xDAI"))
+        .stdout(predicate::str::contains("built-in"));
+
+    web3wallet(config, &["create", "--network", "gnosis"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Network: gnosis"));
+
+    web3wallet(config, &["network", "remove", "gnosis"])
+        .assert()
+        .success();
+
+    web3wallet(config, &["create", "--network", "gnosis"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("INPUT_006"));
+}
+
+/// Test create rejects a network that is neither built in nor configured
+#[test]
+fn test_create_unknown_network() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["create", "--network", "notachain"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("INPUT_006"));
+}
+
+/// Test built-in names and chain ids already in use cannot be added
+#[test]
+fn test_network_add_conflicts() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(&config_path, "").unwrap();
+    let config = config_path.to_str().unwrap();
+
+    web3wallet(config, &["network", "add", "polygon", "--chain-id", "137", "--rpc-url", "https://polygon.example.org"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("INPUT_001"));
+
+    web3wallet(config, &["network", "add", "mainnet-fork", "--chain-id", "1", "--rpc-url", "http://localhost:8545"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("NETWORK_003"));
+    // A rejected definition is never written
+    assert_eq!(std::fs::read_to_string(&config_path).unwrap(), "");
+
+    web3wallet(config, &["network", "remove", "mainnet"])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::{unlocked, write_config};
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const ADDRESS: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
+const RECIPIENT: &str = "0x000000000000000000000000000000000000dEaD";
+
+/// `sign-tx` with cold.json under `nonce`, offline with fixed fees
+fn sign(config: &str, nonce: &str) -> assert_cmd::assert::Assert {
+    unlocked(
+        config,
+        &[
+            "sign-tx", "--from-file", "cold.json", "--to", RECIPIENT, "--nonce", nonce, "--chain-id", "1",
+            "--max-fee", "30000000000", "--max-priority-fee", "1000000000", "--output", "json",
+        ],
+    )
+    .assert()
+}
+
+fn raw(assert: assert_cmd::assert::Assert) -> String {
+    let value: serde_json::Value = serde_json::from_slice(&assert.success().get_output().stdout).unwrap();
+    value["raw"].as_str().unwrap().to_string()
+}
+
+/// Test --nonce auto needs a tracked nonce, then signs with consecutive nonces continuing from
+/// an explicit one, and nonce reset forgets them
+#[test]
+fn test_nonce_command_auto_offline() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "offline = true\n");
+    unlocked(&config, &["import", "--private-key", VALID_PRIVATE_KEY, "--save", "cold"]).assert().success();
+
+    sign(&config, "auto")
+        .code(2)
+        .stderr(predicate::str::contains("nonce sync"));
+
+    sign(&config, "5").success();
+    assert_eq!(raw(sign(&config, "auto")), raw(sign(&config, "6")));
+    // The explicit 6 left the tracked nonce at 7
+    assert_eq!(raw(sign(&config, "auto")), raw(sign(&config, "7")));
+    // A replacement for an earlier nonce does not move it back
+    sign(&config, "2").success();
+    assert_eq!(raw(sign(&config, "auto")), raw(sign(&config, "8")));
+
+    unlocked(&config, &["nonce", "sync", "--address", ADDRESS]).assert().code(2);
+    unlocked(&config, &["nonce", "reset", "--address", ADDRESS, "--chain", "sepolia"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Cleared 0"));
+    unlocked(&config, &["nonce", "reset", "--from-file", "cold.json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Cleared 1"));
+    sign(&config, "auto").code(2);
+}
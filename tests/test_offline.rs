@@ -0,0 +1,79 @@
+mod common;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const OWNER: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+const RECIPIENT: &str = "0x000000000000000000000000000000000000dEaD";
+const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+const PRIVATE_KEY: &str = "0x1ab42cc412b618bdea3a599e3c9bae199ebf030895b039e9db1e30dafb12b727";
+
+/// Test no network command reaches its endpoint under --offline, whether RPC, explorer,
+/// quote API or an ENS name standing in for an address
+#[test]
+fn test_offline_never_contacts_the_network() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    let url = common::fake_http(move |request| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        (200, json!({ "jsonrpc": "2.0", "id": request["id"], "result": "0x1" }).to_string())
+    });
+
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "wallets_path = {:?}\nstate_path = {:?}\n\n[rpc.endpoints]\nmainnet = {:?}\n\n[explorer]\napi_url = {:?}\n\n[quote]\napi_url = {:?}\n",
+            temp_dir.path().join("wallets").to_str().unwrap(),
+            temp_dir.path().join("state").to_str().unwrap(),
+            url,
+            url,
+            url
+        ),
+    )
+    .unwrap();
+    let config = config.to_str().unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["import", "--private-key", PRIVATE_KEY, "--save", "hot", "--offline", "--config", config]);
+    cmd.assert().success();
+    Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["tokens", "add", "USDC", USDC, "--decimals", "6", "--chain", "mainnet", "--offline", "--config", config])
+        .assert()
+        .success();
+
+    let commands: &[&[&str]] = &[
+        &["balance", OWNER, "--chain", "mainnet"],
+        &["balance", OWNER, "--chain", "mainnet", "--token", "usdc"],
+        &["balance", "vitalik.eth", "--chain", "mainnet"],
+        &["ens", "resolve", "vitalik.eth"],
+        &["ens", "reverse", OWNER],
+        &["quote", "--sell", "ETH", "--buy", "USDC", "--amount", "1"],
+        &["activity", OWNER],
+        &["history", OWNER, "--chain", "mainnet"],
+        &["xchain-check", "--address", OWNER, "--chains", "mainnet"],
+        &["monitor", "--min-balance", "mainnet=1", "--once"],
+        &["nonce", "sync", "--address", OWNER, "--chain", "mainnet"],
+        &["send-token", "--from-file", "hot.json", "--token", "USDC", "--to", RECIPIENT, "--amount", "1"],
+        &["send-token", "--from-file", "hot.json", "--token", "USDC", "--to", "vitalik.eth", "--amount", "1"],
+        &["wrap", "--from-file", "hot.json", "1"],
+        &["discover", "hot.json"],
+    ];
+    for args in commands {
+        Command::cargo_bin("web3wallet")
+            .unwrap()
+            .args(*args)
+            .args(["--offline", "--config", config])
+            .assert()
+            .code(2)
+            .stderr(predicate::str::contains("--offline never contacts"));
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 0);
+}
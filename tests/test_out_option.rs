@@ -0,0 +1,51 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const ABANDON_KEY: &str = "0x1ab42cc412b618bdea3a599e3c9bae199ebf030895b039e9db1e30dafb12b727";
+
+/// Test --out captures only the command output, in an owner-only file, with logs left on the terminal
+#[test]
+fn test_out_option_writes_private_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let out = temp_dir.path().join("fixtures.json");
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["--out", out.to_str().unwrap(), "wallet", "fixtures", "--mnemonic", "abandon", "--count", "1"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(ABANDON_KEY).not());
+
+    // Nothing but the document, so it parses whole
+    let fixtures: serde_json::Value = serde_json::from_slice(&std::fs::read(&out).unwrap()).unwrap();
+    assert_eq!(fixtures["accounts"][0]["private_key"], ABANDON_KEY);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&out).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+}
+
+/// Test a failed command leaves no file behind and an existing file is never replaced
+#[test]
+fn test_out_option_failure_and_existing_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let out = temp_dir.path().join("fixtures.json");
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["--out", out.to_str().unwrap(), "wallet", "fixtures", "--mnemonic", "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong"]);
+    cmd.assert().failure();
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+
+    std::fs::write(&out, "keep me").unwrap();
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["--out", out.to_str().unwrap(), "wallet", "fixtures", "--mnemonic", "abandon"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("FS_005"));
+    assert_eq!(std::fs::read_to_string(&out).unwrap(), "keep me");
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use assert_cmd::Command;
+use common::{write_config, PASSWORD};
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const EXPECTED_ADDRESS: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
+
+/// Test passwd re-encrypts with the currently configured KDF and keeps the wallet loadable
+#[test]
+fn test_passwd_command_reencrypts_with_configured_kdf() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "\n[kdf]\nalgorithm = \"argon2id\"\nscrypt_log_n = 10\n");
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin(format!("{}\n", PASSWORD));
+    cmd.args(["import", "--private-key", VALID_PRIVATE_KEY, "--save", "passwd_wallet", "--config", &config]);
+    cmd.assert().success();
+
+    let keystore_path = temp_dir.path().join("wallets").join("passwd_wallet.json");
+    assert!(std::fs::read_to_string(&keystore_path).unwrap().contains(r#""kdf": "argon2id""#));
+
+    let config = write_config(temp_dir.path(), "\n[kdf]\nalgorithm = \"scrypt\"\nscrypt_log_n = 10\n");
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin(format!("{}\n", PASSWORD));
+    cmd.args(["passwd", "passwd_wallet.json", "--config", &config]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Password changed"))
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS));
+
+    let keystore = std::fs::read_to_string(&keystore_path).unwrap();
+    assert!(keystore.contains(r#""kdf": "scrypt""#));
+    assert!(!temp_dir.path().join("wallets").join(".passwd_wallet.json.tmp").exists());
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin(format!("{}\n", PASSWORD));
+    cmd.args(["load", "passwd_wallet.json", "--config", &config]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS));
+}
+
+/// Test passwd on a missing keystore fails without creating a file
+#[test]
+fn test_passwd_command_missing_wallet() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "\n[kdf]\nalgorithm = \"argon2id\"\nscrypt_log_n = 10\n");
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin(format!("{}\n", PASSWORD));
+    cmd.args(["passwd", "missing.json", "--config", &config]);
+    cmd.assert().failure();
+
+    assert!(!temp_dir.path().join("wallets").join("missing.json").exists());
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{web3wallet, write_config};
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const EXPECTED_ADDRESS: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
+
+/// Test a password file answers a password and its confirmation, and one line per prompt
+#[test]
+fn test_password_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+    let password_file = temp_dir.path().join("wallet.pass");
+    std::fs::write(&password_file, "Test123!Password\n").unwrap();
+    let password_file = password_file.to_str().unwrap();
+
+    web3wallet(&config, &["import", "--private-key", VALID_PRIVATE_KEY, "--save", "ci", "--password-file", password_file])
+        .assert()
+        .success();
+    web3wallet(&config, &["load", "ci.json", "--password-file", password_file])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS));
+
+    // passwd takes the current password, then the new one, which also confirms it
+    let rotate = temp_dir.path().join("rotate.pass");
+    std::fs::write(&rotate, "Test123!Password\nRotated456!Password\n").unwrap();
+    web3wallet(&config, &["passwd", "ci.json", "--password-file", rotate.to_str().unwrap()])
+        .assert()
+        .success();
+    web3wallet(&config, &["load", "ci.json", "--password-file", password_file])
+        .assert()
+        .failure();
+    web3wallet(&config, &["load", "ci.json", "--password-stdin"])
+        .write_stdin("Rotated456!Password\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS));
+}
+
+/// Test a missing or empty source fails instead of falling back to a prompt
+#[test]
+fn test_password_source_errors() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+    let args = ["import", "--private-key", VALID_PRIVATE_KEY, "--save", "ci"];
+
+    let missing = temp_dir.path().join("missing.pass");
+    web3wallet(&config, &args)
+        .args(["--password-file", missing.to_str().unwrap()])
+        .assert()
+        .code(5);
+    web3wallet(&config, &args)
+        .arg("--password-stdin")
+        .write_stdin("")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("password on the first line"));
+    web3wallet(&config, &args)
+        .args(["--password-stdin", "--password-file", missing.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    assert!(!temp_dir.path().join("wallets").join("ci.json").exists());
+}
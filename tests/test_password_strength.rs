@@ -0,0 +1,42 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+
+fn save(config: &str, name: &str, password: &str, extra: &[&str]) -> assert_cmd::assert::Assert {
+    Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["import", "--private-key", VALID_PRIVATE_KEY, "--save", name, "--password-stdin"])
+        .args(["--config", config, "--no-agent"])
+        .args(extra)
+        .write_stdin(format!("{}\n", password))
+        .assert()
+}
+
+/// Test a guessable password is refused with what it lacks, a long one without symbols is
+/// accepted, and --allow-weak-password saves under a weak one
+#[test]
+fn test_password_strength() {
+    let temp_dir = TempDir::new().unwrap();
+    let wallets = temp_dir.path().join("wallets");
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(&config, format!("wallets_path = {:?}\n", wallets.to_str().unwrap())).unwrap();
+    let config = config.to_str().unwrap();
+
+    // Every character class, yet a common password followed by a sequence
+    save(config, "weak", "Password123!", &[])
+        .code(3)
+        .stderr(predicate::str::contains("AUTH_002"))
+        .stderr(predicate::str::contains("No common passwords").and(predicate::str::contains("No sequences")));
+    save(config, "short", "x7#Kq", &[])
+        .code(3)
+        .stderr(predicate::str::contains("At least 8 characters"));
+    assert!(!wallets.join("weak.json").exists());
+
+    save(config, "phrase", "correct horse battery staple", &[]).success();
+    save(config, "weak", "Password123!", &["--allow-weak-password"])
+        .success()
+        .stderr(predicate::str::contains("weak password"));
+    assert!(wallets.join("weak.json").exists());
+}
@@ -0,0 +1,111 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::Path;
+use tempfile::TempDir;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const EXPECTED_ADDRESS: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+const DAI: &str = "0x6B175474E89094C44Da98b954EedeAC495271d0F";
+const UNIVERSAL_ROUTER: &str = "0x3fC91A3afd70395Cd496C647d5a6CC9D4B2b7FAD";
+/// 2^160, one past the largest Permit2 allowance
+const UINT160_OVERFLOW: &str = "1461501637330902918203684832716283019655932542976";
+
+fn setup(dir: &Path) -> String {
+    let config = dir.join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "wallets_path = {:?}\nstate_path = {:?}\n",
+            dir.join("wallets").to_str().unwrap(),
+            dir.join("state").to_str().unwrap()
+        ),
+    )
+    .unwrap();
+    let config = config.to_str().unwrap().to_string();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["import", "--mnemonic", VALID_MNEMONIC_12, "--save", "owner", "--config", &config]);
+    cmd.assert().success();
+    config
+}
+
+fn permit2(config: &str, args: &[&str]) -> Command {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["wallet", "permit2"]).args(args);
+    cmd.args([
+        "--from-file", "owner.json", "--spender", UNIVERSAL_ROUTER, "--expiration", "1767225600",
+        "--sig-deadline", "1767225600", "--offline", "--config", config,
+    ]);
+    cmd
+}
+
+fn last_line(cmd: &mut Command) -> String {
+    let output = cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output).unwrap().lines().last().unwrap().trim().to_string()
+}
+
+/// Test a PermitSingle hashes like Permit2 expects and the written document verifies
+#[test]
+fn test_permit2_command_single() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = setup(temp_dir.path());
+    let out = temp_dir.path().join("permit.json");
+
+    let hash = last_line(
+        permit2(&config, &["single", "--token", USDC, "--amount", "1000000", "--nonce", "0"])
+            .args(["--out", out.to_str().unwrap(), "--template", "{{hash}}"]),
+    );
+    assert_eq!(hash, "0xde0bf06cbc177164481f31fd4b814fd8dbb2dbfb962c5da7f90be449e6e2bfb4");
+
+    let signature = last_line(
+        permit2(&config, &["single", "--token", USDC, "--amount", "1000000", "--nonce", "0"])
+            .args(["--template", "{{signature}}"]),
+    );
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["verify-signature", "--typed-data", out.to_str().unwrap(), "--signature", &signature]);
+    cmd.args(["--address", EXPECTED_ADDRESS]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Signature valid"))
+        .stdout(predicate::str::contains(hash.as_str()));
+}
+
+/// Test a PermitBatch with an unlimited allowance hashes like Permit2 expects
+#[test]
+fn test_permit2_command_batch() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = setup(temp_dir.path());
+
+    let usdc = format!("{}:1000000:0", USDC);
+    let dai = format!("{}:max:3", DAI);
+    let hash = last_line(
+        permit2(&config, &["batch", "--permit", &usdc, "--permit", &dai]).args(["--template", "{{hash}}"]),
+    );
+    assert_eq!(hash, "0xf1612fe100329e34c799ca7d532e68e0f68be6eee3ebe1af214cff29b7726be2");
+}
+
+/// Test amounts past uint160 and a missing nonce offline are refused
+#[test]
+fn test_permit2_command_invalid() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = setup(temp_dir.path());
+
+    permit2(&config, &["single", "--token", USDC, "--amount", UINT160_OVERFLOW, "--nonce", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("2^160"));
+
+    permit2(&config, &["single", "--token", USDC, "--amount", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("INPUT_003"));
+
+    let bad = format!("{}-1000", DAI);
+    permit2(&config, &["batch", "--permit", &bad])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("<token>:<amount>"));
+}
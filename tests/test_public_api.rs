@@ -0,0 +1,70 @@
+use std::any::type_name;
+use std::path::Path;
+use web3wallet_cli::prelude::*;
+
+/// Snapshot of the types re-exported by the prelude; update `snapshots/public_api.txt`
+/// deliberately, together with the version bump a breaking change needs
+#[test]
+fn test_prelude_types_match_snapshot() {
+    let actual = [
+        type_name::<AuthenticationError>(),
+        type_name::<CryptographicError>(),
+        type_name::<FilesystemError>(),
+        type_name::<NetworkError>(),
+        type_name::<UserInputError>(),
+        type_name::<ValidationError>(),
+        type_name::<WalletError>(),
+        type_name::<CreateOutcome>(),
+        type_name::<DeleteOutcome>(),
+        type_name::<DeriveOutcome>(),
+        type_name::<DerivedEntry>(),
+        type_name::<ExportOutcome>(),
+        type_name::<ImportOutcome>(),
+        type_name::<KeystoreSummary>(),
+        type_name::<ListOutcome>(),
+        type_name::<LoadOutcome>(),
+        type_name::<MigrateOutcome>(),
+        type_name::<PasswordChangeOutcome>(),
+        type_name::<RenameOutcome>(),
+        type_name::<WalletEntry>(),
+        type_name::<DerivedAddress>(),
+        type_name::<Keystore>(),
+        type_name::<KeystoreFile>(),
+        type_name::<KeystoreV3>(),
+        type_name::<Wallet>(),
+        type_name::<WalletType>(),
+        type_name::<ImportSource>(),
+        type_name::<SaveOptions>(),
+        type_name::<LedgerSigner>(),
+        type_name::<SignerKind>(),
+        type_name::<SoftwareSigner>(),
+        type_name::<SignedTransaction>(),
+        type_name::<UnsignedTransaction>(),
+        type_name::<CryptoService>(),
+        type_name::<MnemonicService>(),
+        type_name::<RpcClient>(),
+        type_name::<TransactionService>(),
+        type_name::<WalletManager>(),
+        type_name::<WalletConfig>(),
+    ]
+    .join("\n");
+
+    let snapshot = std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots/public_api.txt")).unwrap();
+    assert_eq!(actual.trim(), snapshot.trim(), "public API changed, review and update the snapshot");
+}
+
+/// Signatures library consumers rely on; this fails to compile if they change
+#[test]
+fn test_prelude_signatures() {
+    let _: fn(WalletConfig) -> WalletManager = WalletManager::new;
+    let _: fn(Wallet) -> SoftwareSigner = SoftwareSigner::new;
+    let _: fn(&Wallet, &UnsignedTransaction) -> WalletResult<SignedTransaction> = TransactionService::sign;
+    let _: fn(&Keystore, &str) -> WalletResult<Wallet> = CryptoService::decrypt_wallet;
+    let _: fn(&KeystoreV3, &str) -> WalletResult<zeroize::Zeroizing<Vec<u8>>> = CryptoService::decrypt_v3;
+
+    fn assert_signer<T: Signer>() {}
+    assert_signer::<SoftwareSigner>();
+    assert_signer::<LedgerSigner>();
+
+    let _ = WalletConfig::default();
+}
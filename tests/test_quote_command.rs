@@ -0,0 +1,24 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Test a token missing from the registry is rejected before any request is made
+#[test]
+fn test_quote_command_unknown_token() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["quote", "--sell", "ETH", "--buy", "NOTATOKEN", "--amount", "1", "--chain", "sepolia"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Command failed"));
+}
+
+/// Test an unsupported aggregator flavor is rejected
+#[test]
+fn test_quote_command_invalid_aggregator() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["quote", "--sell", "ETH", "--buy", "USDC", "--amount", "1", "--aggregator", "uniswap"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("aggregator"));
+}
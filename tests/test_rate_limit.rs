@@ -0,0 +1,22 @@
+use web3wallet_cli::services::rate_limit::{SigningLimits, SigningRateLimiter};
+
+/// Test per-identity and global signing limits refuse with AUTH_004
+#[test]
+fn test_signing_rate_limits() {
+    let limiter = SigningRateLimiter::new(
+        SigningLimits { per_minute: Some(2), per_hour: None },
+        SigningLimits { per_minute: None, per_hour: Some(3) },
+    );
+
+    limiter.acquire("alice").unwrap();
+    limiter.acquire("alice").unwrap();
+    let err = limiter.acquire("alice").unwrap_err().to_string();
+    assert!(err.contains("AUTH_004"));
+    assert!(err.contains("2 per minute"));
+    assert!(err.contains("alice"));
+
+    limiter.acquire("bob").unwrap();
+    let err = limiter.acquire("bob").unwrap_err().to_string();
+    assert!(err.contains("3 per hour"));
+    assert!(err.contains("all identities"));
+}
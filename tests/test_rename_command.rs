@@ -0,0 +1,58 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+
+fn saved_wallet(temp_dir: &TempDir) -> std::path::PathBuf {
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(&config, format!("wallets_path = {:?}\n", temp_dir.path().join("wallets").to_str().unwrap())).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["import", "--private-key", VALID_PRIVATE_KEY, "--save", "original", "--config", config.to_str().unwrap()]);
+    cmd.assert().success();
+    config
+}
+
+/// Test the alias changes without a password and the file moves along with its overrides
+#[test]
+fn test_rename_command_alias_and_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = saved_wallet(&temp_dir);
+    let wallets = temp_dir.path().join("wallets");
+    std::fs::write(wallets.join("original.toml"), "network = \"sepolia\"\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["alias", "original.json", "--alias", "savings", "--to", "savings", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("savings"));
+
+    assert!(!wallets.join("original.json").exists());
+    assert!(wallets.join("savings.toml").exists());
+    let keystore = std::fs::read_to_string(wallets.join("savings.json")).unwrap();
+    assert!(keystore.contains(r#""alias": "savings""#));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["load", "savings.json", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Alias:    savings"));
+}
+
+/// Test `--require-password` refuses the change when the password is wrong
+#[test]
+fn test_rename_command_require_password() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = saved_wallet(&temp_dir);
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Wrong123!Password\n");
+    cmd.args(["rename", "original.json", "--alias", "stolen", "--require-password", "--config", config.to_str().unwrap()]);
+    cmd.assert().failure();
+
+    let keystore = std::fs::read_to_string(temp_dir.path().join("wallets").join("original.json")).unwrap();
+    assert!(!keystore.contains("stolen"));
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::{web3wallet, write_config, PASSWORD};
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const EXPECTED_ADDRESS: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
+const NEW_PASSWORD: &str = "Rotated456!Password";
+
+fn backups(dir: &std::path::Path) -> usize {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .filter(|entry| entry.as_ref().unwrap().file_name().to_str().unwrap().ends_with(".bak"))
+        .count()
+}
+
+/// Test passwd keeps a bounded number of backups and restore-backup brings the old password back
+#[test]
+fn test_restore_backup_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let wallets = temp_dir.path().join("wallets");
+    let config = &write_config(temp_dir.path(), "keystore_backups = 2\n\n[kdf]\nalgorithm = \"pbkdf2\"\n");
+
+    web3wallet(config, &["import", "--private-key", VALID_PRIVATE_KEY, "--save", "kept", "--password-stdin"])
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .success();
+    assert_eq!(backups(&wallets), 0);
+
+    let rotate = temp_dir.path().join("rotate.pass");
+    for (old, new) in [(PASSWORD, NEW_PASSWORD), (NEW_PASSWORD, PASSWORD), (PASSWORD, NEW_PASSWORD)] {
+        std::fs::write(&rotate, format!("{}\n{}\n", old, new)).unwrap();
+        web3wallet(config, &["passwd", "kept.json", "--password-file", rotate.to_str().unwrap()])
+            .assert()
+            .success();
+    }
+    assert_eq!(backups(&wallets), 2);
+
+    web3wallet(config, &["restore-backup", "kept.json", "--list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("kept.json.").count(2));
+
+    // The newest backup is from before the last change, under the previous password
+    web3wallet(config, &["restore-backup", "kept.json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS));
+    web3wallet(config, &["load", "kept.json", "--password-stdin"])
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS));
+    assert_eq!(backups(&wallets), 2);
+}
+
+/// Test restoring without any backup fails and leaves the keystore alone
+#[test]
+fn test_restore_backup_command_without_backups() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = &write_config(temp_dir.path(), "");
+
+    web3wallet(config, &["import", "--private-key", VALID_PRIVATE_KEY, "--save", "single", "--password-stdin"])
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .success();
+    web3wallet(config, &["restore-backup", "single.json"]).assert().code(5);
+    web3wallet(config, &["load", "single.json", "--password-stdin"])
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .success();
+}
@@ -0,0 +1,235 @@
+mod common;
+
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use web3wallet_cli::errors::{NetworkError, WalletError};
+use web3wallet_cli::services::cache::RpcCache;
+use web3wallet_cli::services::rpc::{RetryPolicy, RpcClient, RpcRequest};
+
+fn requests(count: u64) -> Vec<RpcRequest> {
+    (0..count)
+        .map(|n| RpcRequest::new("eth_getBalance", json!([format!("0x{:040x}", n), "latest"])))
+        .collect()
+}
+
+/// Test a batch goes out in one round trip per `max_batch_size` calls, results in request order
+#[tokio::test]
+async fn test_rpc_batch_round_trips() {
+    let round_trips = Arc::new(AtomicUsize::new(0));
+    let counter = round_trips.clone();
+    let url = common::fake_http(move |request| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        let answer = |call: &Value| json!({ "jsonrpc": "2.0", "id": call["id"], "result": call["params"][0] });
+        let response = match &request {
+            Value::Array(calls) => Value::Array(calls.iter().map(answer).collect()),
+            call => answer(call),
+        };
+        (200, response.to_string())
+    });
+
+    let results = RpcClient::new(&url).batch(requests(5)).await.unwrap();
+    assert_eq!(round_trips.load(Ordering::SeqCst), 1);
+    for (n, result) in results.into_iter().enumerate() {
+        assert_eq!(result.unwrap(), json!(format!("0x{:040x}", n)));
+    }
+
+    round_trips.store(0, Ordering::SeqCst);
+    let results = RpcClient::new(&url).with_max_batch_size(2).batch(requests(5)).await.unwrap();
+    assert_eq!(round_trips.load(Ordering::SeqCst), 3);
+    assert_eq!(results.len(), 5);
+    assert_eq!(results[4].as_ref().unwrap(), &json!(format!("0x{:040x}", 4)));
+}
+
+/// Test responses are matched to calls by id whatever order the provider answers in,
+/// a JSON-RPC error or a missing answer failing only its own call
+#[tokio::test]
+async fn test_rpc_batch_id_correlation() {
+    let url = common::fake_http(|request| {
+        let calls = request.as_array().unwrap();
+        let mut answers: Vec<Value> = calls
+            .iter()
+            .filter(|call| call["params"][0] != json!(format!("0x{:040x}", 2)))
+            .map(|call| match call["params"][0] == json!(format!("0x{:040x}", 1)) {
+                true => json!({ "jsonrpc": "2.0", "id": call["id"], "error": { "code": -32000, "message": "header not found" } }),
+                false => json!({ "jsonrpc": "2.0", "id": call["id"], "result": call["params"][0] }),
+            })
+            .collect();
+        answers.reverse();
+        (200, Value::Array(answers).to_string())
+    });
+
+    let results = RpcClient::new(&url).batch(requests(4)).await.unwrap();
+    assert_eq!(results[0].as_ref().unwrap(), &json!(format!("0x{:040x}", 0)));
+    assert!(matches!(
+        &results[1],
+        Err(WalletError::Network(NetworkError::RpcError { code: -32000, .. }))
+    ));
+    assert!(matches!(
+        &results[2],
+        Err(WalletError::Network(NetworkError::InvalidResponse { .. }))
+    ));
+    assert_eq!(results[3].as_ref().unwrap(), &json!(format!("0x{:040x}", 3)));
+}
+
+/// Test a cache that cannot be written still lets the read through
+#[tokio::test]
+async fn test_rpc_cache_write_failure_is_not_fatal() {
+    let dir = tempfile::tempdir().unwrap();
+    let blocker = dir.path().join("not-a-directory");
+    std::fs::write(&blocker, "").unwrap();
+    let cache = RpcCache::open(&blocker.join("rpc-cache.json")).await;
+
+    let url = common::fake_rpc(|_| json!("0x1"));
+    let client = RpcClient::new(&url).with_cache(1, Arc::new(Mutex::new(cache)));
+    assert_eq!(client.call("eth_chainId", json!([])).await.unwrap(), json!("0x1"));
+    assert_eq!(
+        client
+            .call_cached("eth_call", json!([]), Duration::from_secs(60))
+            .await
+            .unwrap(),
+        json!("0x1")
+    );
+}
+
+/// Test cacheable reads are answered from the cache of the same chain id only
+#[tokio::test]
+async fn test_rpc_cache_per_chain_id() {
+    let round_trips = Arc::new(AtomicUsize::new(0));
+    let counter = round_trips.clone();
+    let url = common::fake_rpc(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        json!("0x1")
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Arc::new(Mutex::new(RpcCache::open(&RpcCache::default_path(dir.path())).await));
+    let mainnet = RpcClient::new(&url).with_cache(1, cache.clone());
+    for _ in 0..2 {
+        assert_eq!(mainnet.call("eth_chainId", json!([])).await.unwrap(), json!("0x1"));
+    }
+    assert_eq!(round_trips.load(Ordering::SeqCst), 1);
+
+    let optimism = RpcClient::new(&url).with_cache(10, cache);
+    optimism.call("eth_chainId", json!([])).await.unwrap();
+    assert_eq!(round_trips.load(Ordering::SeqCst), 2);
+}
+
+fn retry_policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(40),
+        jitter: false,
+    }
+}
+
+/// Endpoint failing the first `failures` requests with `status`, then answering "0x1";
+/// returns its URL and the number of requests it received
+fn flaky_endpoint(failures: usize, status: u16) -> (String, Arc<AtomicUsize>) {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    let url = common::fake_http(move |request| {
+        if counter.fetch_add(1, Ordering::SeqCst) < failures {
+            return (status, "{}".to_string());
+        }
+        (200, json!({ "jsonrpc": "2.0", "id": request["id"], "result": "0x1" }).to_string())
+    });
+    (url, attempts)
+}
+
+/// Test backoff doubles per attempt up to the cap, and jitter stays within it
+#[test]
+fn test_rpc_retry_backoff() {
+    let policy = retry_policy(5);
+    let delays: Vec<u64> = (1..=4).map(|n| policy.backoff(n).as_millis() as u64).collect();
+    assert_eq!(delays, [10, 20, 40, 40]);
+
+    let jittered = RetryPolicy { jitter: true, ..policy };
+    assert!((1..=4).all(|n| jittered.backoff(n) <= Duration::from_millis(40)));
+}
+
+/// Test server errors and rate limits are retried until an attempt succeeds
+#[tokio::test]
+async fn test_rpc_retry_recovers() {
+    for status in [503, 429] {
+        let (url, attempts) = flaky_endpoint(2, status);
+        let client = RpcClient::new(&url).with_retry_policy(retry_policy(3));
+        assert_eq!(client.call("eth_blockNumber", json!([])).await.unwrap(), json!("0x1"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3, "HTTP {}", status);
+    }
+}
+
+/// Test a huge `Retry-After` is waited out only up to the backoff cap
+#[tokio::test]
+async fn test_rpc_retry_after_is_capped() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    let url = common::fake_http_with_headers(move |request| {
+        if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+            return (429, vec![("Retry-After", "86400".to_string())], "{}".to_string());
+        }
+        (200, Vec::new(), json!({ "jsonrpc": "2.0", "id": request["id"], "result": "0x1" }).to_string())
+    });
+
+    let client = RpcClient::new(&url).with_retry_policy(retry_policy(2));
+    let result = tokio::time::timeout(Duration::from_secs(5), client.call("eth_blockNumber", json!([]))).await;
+    assert_eq!(result.expect("Retry-After was not capped").unwrap(), json!("0x1"));
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+}
+
+/// Test the last error is returned once `max_attempts` is used up
+#[tokio::test]
+async fn test_rpc_retry_exhausted() {
+    let (url, attempts) = flaky_endpoint(usize::MAX, 503);
+    let client = RpcClient::new(&url).with_retry_policy(retry_policy(2));
+    let err = client.call("eth_blockNumber", json!([])).await.unwrap_err();
+    assert!(matches!(err, WalletError::Network(NetworkError::ConnectivityFailure { .. })));
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+    let (url, attempts) = flaky_endpoint(usize::MAX, 429);
+    let client = RpcClient::new(&url).with_retry_policy(retry_policy(1));
+    let err = client.call("eth_blockNumber", json!([])).await.unwrap_err();
+    assert!(matches!(err, WalletError::Network(NetworkError::RateLimitExceeded { .. })));
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+/// Test JSON-RPC errors are final and never retried
+#[tokio::test]
+async fn test_rpc_retry_skips_rpc_errors() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    let url = common::fake_http(move |request| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        let error = json!({ "code": -32601, "message": "method not found" });
+        (200, json!({ "jsonrpc": "2.0", "id": request["id"], "error": error }).to_string())
+    });
+
+    let client = RpcClient::new(&url).with_retry_policy(retry_policy(3));
+    let err = client.call("eth_unknown", json!([])).await.unwrap_err();
+    assert!(matches!(err, WalletError::Network(NetworkError::RpcError { code: -32601, .. })));
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+/// Test a slow endpoint times out with the elapsed time and the limit it exceeded
+#[tokio::test]
+async fn test_rpc_timeout() {
+    let url = common::fake_http(|request| {
+        std::thread::sleep(Duration::from_millis(500));
+        (200, json!({ "jsonrpc": "2.0", "id": request["id"], "result": "0x1" }).to_string())
+    });
+
+    let client = RpcClient::new(&url)
+        .with_timeout(Duration::from_millis(100))
+        .with_retry_policy(retry_policy(1));
+    match client.call("eth_blockNumber", json!([])).await.unwrap_err() {
+        WalletError::Network(NetworkError::RequestTimeout { request_type, elapsed, limit }) => {
+            assert_eq!(request_type, "eth_blockNumber");
+            assert_eq!(limit, Duration::from_millis(100));
+            assert!(elapsed >= limit);
+        }
+        other => panic!("expected a timeout, got {}", other),
+    }
+}
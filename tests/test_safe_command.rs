@@ -0,0 +1,162 @@
+mod common;
+
+use assert_cmd::Command;
+use common::{unlocked, write_config};
+use ethers::types::transaction::eip712::{Eip712, TypedData};
+use ethers::types::{Address, Signature, H256};
+use ethers::utils::{hash_message, keccak256};
+use std::str::FromStr;
+use tempfile::TempDir;
+use web3wallet_cli::services::safe::SafeService;
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const OWNER: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
+const SAFE: &str = "0x5aFE3855358E112B5647B952709E6165e1c1eEEe";
+const TO: &str = "0x000000000000000000000000000000000000dEaD";
+/// transfer(0xdead, 1000)
+const DATA: &str = "0xa9059cbb000000000000000000000000000000000000000000000000000000000000dead00000000000000000000000000000000000000000000000000000000000003e8";
+
+fn json(cmd: &mut Command) -> serde_json::Value {
+    let output = cmd.args(["--output", "json"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// SafeTxHash of the transaction the tests sign, from ethers' own EIP-712 encoder
+fn expected_hash(chain_id: u64) -> H256 {
+    let typed_data: TypedData = serde_json::from_value(serde_json::json!({
+        "types": {
+            "EIP712Domain": [
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" },
+            ],
+            "SafeTx": [
+                { "name": "to", "type": "address" },
+                { "name": "value", "type": "uint256" },
+                { "name": "data", "type": "bytes" },
+                { "name": "operation", "type": "uint8" },
+                { "name": "safeTxGas", "type": "uint256" },
+                { "name": "baseGas", "type": "uint256" },
+                { "name": "gasPrice", "type": "uint256" },
+                { "name": "gasToken", "type": "address" },
+                { "name": "refundReceiver", "type": "address" },
+                { "name": "nonce", "type": "uint256" },
+            ],
+        },
+        "primaryType": "SafeTx",
+        "domain": { "chainId": chain_id, "verifyingContract": SAFE },
+        "message": {
+            "to": TO,
+            "value": "1000",
+            "data": DATA,
+            "operation": 0,
+            "safeTxGas": "0",
+            "baseGas": "0",
+            "gasPrice": "0",
+            "gasToken": "0x0000000000000000000000000000000000000000",
+            "refundReceiver": "0x0000000000000000000000000000000000000000",
+            "nonce": "3",
+        },
+    }))
+    .unwrap();
+    H256::from(typed_data.encode_eip712().unwrap())
+}
+
+/// Test safe hash matches the EIP-712 SafeTx hash per chain, and safe sign produces an owner
+/// signature over it with a proposal the Safe transaction service accepts
+#[test]
+fn test_safe_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "offline = true\n\n[kdf]\nalgorithm = \"pbkdf2\"\n");
+    unlocked(&config, &["import", "--private-key", VALID_PRIVATE_KEY, "--save", "owner"]).assert().success();
+    let tx = ["--safe", SAFE, "--to", TO, "--value", "1000", "--data", DATA, "--nonce", "3"];
+
+    let hashed = json(unlocked(&config, &["safe", "hash", "--chain", "sepolia"]).args(tx));
+    assert_eq!(hashed["safe_tx_hash"], format!("{:?}", expected_hash(11155111)));
+    assert_eq!(hashed["nonce"], 3);
+
+    let proposal_file = temp_dir.path().join("proposal.json");
+    let signed = json(
+        unlocked(&config, &["safe", "sign", "-f", "owner.json", "--chain", "mainnet"])
+            .args(tx)
+            .args(["--out", proposal_file.to_str().unwrap()]),
+    );
+    let hash = expected_hash(1);
+    assert_eq!(signed["safe_tx_hash"], format!("{:?}", hash));
+    assert_eq!(signed["signer"], OWNER);
+    let signature = Signature::from_str(signed["signature"].as_str().unwrap()).unwrap();
+    assert!(signature.v == 27 || signature.v == 28);
+    assert_eq!(format!("{:?}", signature.recover(hash).unwrap()), OWNER.to_lowercase());
+
+    let proposal: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&proposal_file).unwrap()).unwrap();
+    assert_eq!(proposal, signed["proposal"]);
+    assert_eq!(proposal["contractTransactionHash"], signed["safe_tx_hash"]);
+    assert_eq!(proposal["sender"], OWNER);
+    assert_eq!(proposal["signature"], signed["signature"]);
+    assert_eq!(proposal["to"], TO);
+    assert_eq!(proposal["value"], "1000");
+    assert_eq!(proposal["data"], DATA);
+    assert_eq!(proposal["operation"], 0);
+    assert_eq!(proposal["nonce"], 3);
+
+    // Offline the nonce cannot be read from the Safe
+    unlocked(&config, &["safe", "hash", "--safe", SAFE, "--to", TO]).assert().code(2);
+    unlocked(&config, &["safe", "hash", "--operation", "staticcall"]).args(tx).assert().code(2);
+}
+
+/// SafeMessage hash of `message` for SAFE on mainnet, from ethers' own EIP-712 encoder
+fn expected_message_hash(message: &[u8]) -> H256 {
+    let typed_data: TypedData = serde_json::from_value(serde_json::json!({
+        "types": {
+            "EIP712Domain": [
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" },
+            ],
+            "SafeMessage": [{ "name": "message", "type": "bytes" }],
+        },
+        "primaryType": "SafeMessage",
+        "domain": { "chainId": 1, "verifyingContract": SAFE },
+        "message": { "message": format!("0x{}", hex::encode(message)) },
+    }))
+    .unwrap();
+    H256::from(typed_data.encode_eip712().unwrap())
+}
+
+/// Test the SafeMessage hash against the type hashes Safe's contracts declare and a pinned
+/// digest, and sign-safe-message signs it for text and hex messages
+#[test]
+fn test_safe_message_hash_known_answer() {
+    // SAFE_MSG_TYPEHASH of CompatibilityFallbackHandler and DOMAIN_SEPARATOR_TYPEHASH of Safe.sol
+    assert_eq!(
+        hex::encode(keccak256("SafeMessage(bytes message)")),
+        "60b3cbf8b4a223d68d641b3b6ddf9a298e7f33710cf3d3a9d1146b5a6150fbca"
+    );
+    assert_eq!(
+        hex::encode(keccak256("EIP712Domain(uint256 chainId,address verifyingContract)")),
+        "47e79534a245952e8b16893a336b85a3d9ea9fa8c573f3d803afb92a79469218"
+    );
+
+    let safe: Address = SAFE.parse().unwrap();
+    let message = SafeService::text_message("Hello Safe");
+    assert_eq!(message, hash_message("Hello Safe").as_bytes());
+    let hash = SafeService::safe_message_hash(safe, 1, &message);
+    assert_eq!(H256::from(hash), expected_message_hash(&message));
+    assert_eq!(format!("0x{}", hex::encode(hash)), "0x2dee127b3643279992358bed1a9ea792a7ef97cbd6c89971165e21dc5ab93637");
+
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "offline = true\n\n[kdf]\nalgorithm = \"pbkdf2\"\n");
+    unlocked(&config, &["import", "--private-key", VALID_PRIVATE_KEY, "--save", "owner"]).assert().success();
+    let sign = ["sign-safe-message", "-f", "owner.json", "--safe", SAFE, "--chain-id", "1"];
+
+    let signed = json(unlocked(&config, &sign).args(["--message", "Hello Safe"]));
+    assert_eq!(signed["safe_message_hash"], format!("0x{}", hex::encode(hash)));
+    assert_eq!(signed["signer"], OWNER);
+    let signature = Signature::from_str(signed["signature"].as_str().unwrap()).unwrap();
+    assert_eq!(format!("{:?}", signature.recover(H256::from(hash)).unwrap()), OWNER.to_lowercase());
+
+    // With --hex the bytes are signed as given, e.g. the hash of other typed data
+    let raw = [0xab; 32];
+    let signed = json(unlocked(&config, &sign).args(["--hex", "--message", &format!("0x{}", hex::encode(raw))]));
+    assert_eq!(signed["safe_message_hash"], format!("{:?}", expected_message_hash(&raw)));
+}
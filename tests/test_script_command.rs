@@ -0,0 +1,206 @@
+mod common;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const ADDRESS: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
+const RECIPIENT: &str = "0x000000000000000000000000000000000000dEaD";
+const BROADCAST_HASH: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+
+/// Test a script whose steps all succeed, with outputs referenced by later steps
+#[test]
+fn test_script_command_success() {
+    let temp_dir = TempDir::new().unwrap();
+    let script_path = temp_dir.path().join("ops.yaml");
+    std::fs::write(&script_path, format!(r#"
+name: smoke
+vars:
+  phrase: "{}"
+steps:
+  - id: imported
+    op: import
+    mnemonic: ${{phrase}}
+  - op: derive
+    mnemonic: ${{phrase}}
+    count: 2
+"#, VALID_MNEMONIC_12)).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["script", script_path.to_str().unwrap(), "--output", "json"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(r#""success": true"#))
+        .stdout(predicate::str::contains(r#""succeeded": 2"#));
+}
+
+/// Test fail-fast: a failing step skips the rest of the script
+#[test]
+fn test_script_command_fail_fast() {
+    let temp_dir = TempDir::new().unwrap();
+    let script_path = temp_dir.path().join("ops.yaml");
+    std::fs::write(&script_path, r#"
+steps:
+  - op: import
+    mnemonic: invalid mnemonic phrase
+  - op: create
+"#).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["script", script_path.to_str().unwrap(), "--output", "json"]);
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains(r#""skipped": 1"#));
+}
+
+/// Test --continue-on-error runs every step despite failures
+#[test]
+fn test_script_command_continue_on_error() {
+    let temp_dir = TempDir::new().unwrap();
+    let script_path = temp_dir.path().join("ops.yaml");
+    std::fs::write(&script_path, r#"
+steps:
+  - op: import
+    mnemonic: invalid mnemonic phrase
+  - op: create
+"#).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["script", script_path.to_str().unwrap(), "--continue-on-error", "--output", "json"]);
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains(r#""succeeded": 1"#))
+        .stdout(predicate::str::contains(r#""skipped": 0"#));
+}
+
+/// Test an operation the runner does not know fails its step instead of running
+#[test]
+fn test_script_command_unknown_op() {
+    let temp_dir = TempDir::new().unwrap();
+    let script_path = temp_dir.path().join("ops.yaml");
+    std::fs::write(&script_path, r#"
+steps:
+  - op: swap
+    to: "0x0000000000000000000000000000000000000001"
+"#).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["script", script_path.to_str().unwrap(), "--output", "json"]);
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains(r#""op": "swap""#))
+        .stdout(predicate::str::contains("VALIDATION_003"));
+}
+
+/// Test a start_index whose last derived index would overflow is refused, not wrapped
+#[test]
+fn test_script_command_derive_index_overflow() {
+    let temp_dir = TempDir::new().unwrap();
+    let script_path = temp_dir.path().join("ops.yaml");
+    std::fs::write(&script_path, format!(r#"
+steps:
+  - op: derive
+    mnemonic: "{}"
+    start_index: 4294967295
+    count: 2
+"#, VALID_MNEMONIC_12)).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["script", script_path.to_str().unwrap(), "--output", "json"]);
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("INPUT_001"));
+}
+
+/// Test a sign step signs a complete transaction offline and a send step fills in nonce,
+/// gas and fees from the chain before broadcasting what it signed
+#[test]
+fn test_script_command_sign_and_send() {
+    let broadcast = Arc::new(Mutex::new(Vec::new()));
+    let sent = broadcast.clone();
+    let url = common::fake_rpc(move |call| match call["method"].as_str().unwrap() {
+        "eth_getTransactionCount" => json!("0x7"),
+        "eth_estimateGas" => json!("0x5208"),
+        "eth_feeHistory" => json!({ "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00"], "reward": [["0x3b9aca00"]] }),
+        "eth_sendRawTransaction" => {
+            sent.lock().unwrap().push(call["params"][0].as_str().unwrap().to_string());
+            json!(BROADCAST_HASH)
+        }
+        _ => json!("0x3b9aca00"),
+    });
+
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "wallets_path = {:?}\nstate_path = {:?}\n\n[kdf]\nalgorithm = \"pbkdf2\"\n\n[rpc.endpoints]\nmainnet = {:?}\n",
+            temp_dir.path().join("wallets").to_str().unwrap(),
+            temp_dir.path().join("state").to_str().unwrap(),
+            url
+        ),
+    )
+    .unwrap();
+    let script_path = temp_dir.path().join("ops.yaml");
+    std::fs::write(&script_path, format!(r#"
+vars:
+  password: "Test123!Password"
+steps:
+  - op: import
+    private_key: "{key}"
+    save: hot
+    password: ${{password}}
+  - id: signed
+    op: sign
+    from_file: hot
+    password: ${{password}}
+    tx:
+      to: "{to}"
+      value: "1000"
+      nonce: "0"
+      max_fee_per_gas: "2000000000"
+      max_priority_fee_per_gas: "1000000000"
+      chain_id: 1
+  - id: sent
+    op: send
+    from_file: hot
+    password: ${{password}}
+    to: "{to}"
+    value: "1000"
+    chain: mainnet
+  - op: sign
+    from_file: hot
+    password: ${{password}}
+    tx:
+      to: "{to}"
+      nonce: "0"
+      chain_id: 1
+"#, key = VALID_PRIVATE_KEY, to = RECIPIENT)).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["script", script_path.to_str().unwrap(), "--continue-on-error", "--no-agent", "--output", "json"]);
+    cmd.args(["--config", config.to_str().unwrap()]);
+    let assert = cmd.assert().failure();
+    let report: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let steps = report["report"]["steps"].as_array().unwrap();
+
+    assert_eq!(steps[1]["status"], "ok", "{}", report);
+    assert_eq!(steps[1]["output"]["from"], ADDRESS);
+    assert_eq!(steps[2]["status"], "ok", "{}", report);
+    assert_eq!(steps[2]["output"]["from"], ADDRESS);
+    assert_eq!(steps[2]["output"]["nonce"], "0x7");
+    assert_eq!(steps[2]["output"]["hash"], BROADCAST_HASH);
+    assert_eq!(*broadcast.lock().unwrap(), [steps[2]["output"]["raw"].as_str().unwrap()]);
+    // Fees are never estimated by a sign step
+    assert_eq!(steps[3]["status"], "failed");
+    assert!(steps[3]["error"].as_str().unwrap().contains("INPUT_"), "{}", report);
+}
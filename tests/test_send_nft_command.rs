@@ -0,0 +1,39 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Test send-nft refuses to run without network access
+#[test]
+fn test_send_nft_command_offline() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args([
+        "send-nft",
+        "--offline",
+        "--from-file", "nonexistent.json",
+        "--contract", "0xBC4CA0EdA7647A8aB7C2061c2E118A18a936f13D",
+        "--token-id", "1",
+        "--to", "0x000000000000000000000000000000000000dEaD",
+    ]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Command failed"));
+}
+
+/// Test an unknown --standard is rejected
+#[test]
+fn test_send_nft_command_invalid_standard() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args([
+        "send-nft",
+        "--from-file", "nonexistent.json",
+        "--contract", "0xBC4CA0EdA7647A8aB7C2061c2E118A18a936f13D",
+        "--token-id", "1",
+        "--to", "0x000000000000000000000000000000000000dEaD",
+        "--standard", "erc20",
+    ]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("standard"));
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use assert_cmd::Command;
+use common::{unlocked, word, write_config, PASSWORD};
+use predicates::prelude::*;
+use serde_json::json;
+use tempfile::TempDir;
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const ADDRESS: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
+/// Bundled USDC on mainnet
+const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+const RECIPIENT: &str = "0x000000000000000000000000000000000000dEaD";
+const BROADCAST_HASH: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+
+/// JSON-RPC endpoint where USDC holds 250 tokens for every address
+fn fake_rpc() -> String {
+    common::fake_rpc(|call| match call["method"].as_str().unwrap() {
+        "eth_call" => {
+            let data = call["params"][0]["data"].as_str().unwrap();
+            let body = match &data[2..10] {
+                "313ce567" => word(6),
+                "95d89b41" => format!("{}{}{:0<64}", word(32), word(4), hex::encode("USDC")),
+                "70a08231" => word(250_000_000),
+                _ => String::new(),
+            };
+            json!(format!("0x{}", body))
+        }
+        "eth_getTransactionCount" => json!("0x7"),
+        "eth_estimateGas" => json!("0xfde8"),
+        "eth_feeHistory" => json!({
+            "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00"],
+            "reward": [["0x3b9aca00"]]
+        }),
+        "eth_sendRawTransaction" => json!(BROADCAST_HASH),
+        _ => json!("0x3b9aca00"),
+    })
+}
+
+fn send(config: &str, amount: &str, extra: &[&str]) -> assert_cmd::assert::Assert {
+    unlocked(
+        config,
+        &["send-token", "--from-file", "hot.json", "--token", "USDC", "--to", RECIPIENT, "--amount", amount],
+    )
+    .args(extra)
+    .assert()
+}
+
+fn json(assert: assert_cmd::assert::Assert) -> serde_json::Value {
+    serde_json::from_slice(&assert.success().get_output().stdout).unwrap()
+}
+
+/// Test a USDC transfer is signed to the token contract with `transfer` calldata in base
+/// units, written to a file or broadcast, and amounts above the balance or with too many
+/// decimals are refused
+#[test]
+fn test_send_token_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), &format!("\n[kdf]\nalgorithm = \"pbkdf2\"\n\n[rpc.endpoints]\nmainnet = {:?}\n", fake_rpc()));
+    unlocked(&config, &["import", "--private-key", VALID_PRIVATE_KEY, "--save", "hot"]).assert().success();
+
+    let raw_file = temp_dir.path().join("transfer.hex");
+    let signed = json(send(&config, "100.5", &["--out", raw_file.to_str().unwrap(), "--output", "json"]));
+    assert_eq!(signed["broadcast"], false);
+    assert_eq!(signed["token"], USDC);
+    assert_eq!(signed["symbol"], "USDC");
+    assert_eq!(signed["amount"], "100.5");
+    assert_eq!(signed["amount_base_units"], "100500000");
+    assert_eq!(signed["from"], ADDRESS);
+    let raw = std::fs::read_to_string(&raw_file).unwrap();
+    assert_eq!(raw.trim(), signed["raw"]);
+    let calldata = format!("a9059cbb{:0>64}{}", RECIPIENT[2..].to_lowercase(), word(100_500_000));
+    assert!(raw.contains(&calldata));
+    assert!(raw.contains(&USDC[2..].to_lowercase()));
+
+    let sent = json(send(&config, "250", &["--output", "json"]));
+    assert_eq!(sent["broadcast"], true);
+    assert!(sent["raw"].as_str().unwrap().contains(&word(250_000_000)));
+
+    send(&config, "250.000001", &[])
+        .code(7)
+        .stderr(predicate::str::contains("less than the 250.000001"));
+    send(&config, "1.0000001", &[]).code(2);
+    send(&config, "0", &[]).code(2);
+}
+
+/// Test send-token refuses to run without network access
+#[test]
+fn test_send_token_command_offline() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin(format!("{}\n", PASSWORD));
+    cmd.args([
+        "send-token", "--offline", "--from-file", "nonexistent.json", "--token", "USDC", "--to", RECIPIENT,
+        "--amount", "1",
+    ]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Command failed"));
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use assert_cmd::cargo::CommandCargoExt;
+use common::{web3wallet, write_config, PASSWORD};
+use predicates::prelude::*;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const EXPECTED_ADDRESS: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+/// Account 0 of the same mnemonic under the BIP39 passphrase "TREZOR"
+const PASSPHRASE_ADDRESS: &str = "0x9c32F71D4DB8Fb9e1A58B0a80dF79935e7256FA6";
+const TOKEN: &str = "test-token";
+
+/// Status line and body of one POST to the server
+fn post(port: u16, token: &str, body: &str) -> (String, serde_json::Value) {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    write!(
+        stream,
+        "POST / HTTP/1.1\r\nHost: 127.0.0.1\r\nAuthorization: Bearer {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        token,
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.lines().next().unwrap().to_string();
+    (status, serde_json::from_str(body).unwrap_or(serde_json::Value::Null))
+}
+
+fn call(port: u16, method: &str, params: serde_json::Value) -> serde_json::Value {
+    let request = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    post(port, TOKEN, &request.to_string()).1
+}
+
+/// Test list, derive and sign-message over JSON-RPC, and that the token is required
+#[test]
+fn test_serve_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+    web3wallet(&config, &["import", "--mnemonic", VALID_MNEMONIC_12, "--save", "served", "--no-agent"])
+        .arg("--password-stdin")
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .success();
+    web3wallet(&config, &["import", "--mnemonic", VALID_MNEMONIC_12, "--passphrase", "--save", "guarded", "--no-agent"])
+        .arg("--password-stdin")
+        .write_stdin(format!("TREZOR\nTREZOR\n{}\n", PASSWORD))
+        .assert()
+        .success();
+    let token_file = temp_dir.path().join("serve.token");
+    std::fs::write(&token_file, format!("{}\n", TOKEN)).unwrap();
+
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let mut server = std::process::Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["serve", "--listen", &format!("127.0.0.1:{}", port), "--no-agent", "--config", &config])
+        .args(["--token-file", token_file.to_str().unwrap()])
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let started = Instant::now();
+    while TcpStream::connect(("127.0.0.1", port)).is_err() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    let (status, _) = post(port, "wrong-token", r#"{"jsonrpc":"2.0","id":1,"method":"wallet_list"}"#);
+    assert!(status.contains("401"), "{}", status);
+
+    let list = call(port, "wallet_list", serde_json::json!({}));
+    assert!(list["result"].to_string().contains("served.json"), "{}", list);
+
+    let derived = call(port, "wallet_derive", serde_json::json!({ "wallet": "served.json", "count": 2, "password": PASSWORD }));
+    assert!(derived["result"].to_string().contains("m/44'/60'/0'/0/1"), "{}", derived);
+
+    let signed = call(port, "wallet_signMessage", serde_json::json!({ "wallet": "served.json", "message": "hello", "password": PASSWORD }));
+    assert_eq!(signed["result"]["signer"], EXPECTED_ADDRESS, "{}", signed);
+    assert!(signed["result"]["signature"].as_str().unwrap().starts_with("0x"));
+
+    // The passphrase is not in the keystore, the request supplies it along with the password
+    let guarded = serde_json::json!({ "wallet": "guarded.json", "message": "hello", "password": PASSWORD });
+    let locked = call(port, "wallet_signMessage", guarded.clone());
+    assert_eq!(locked["error"]["code"], -32000, "{}", locked);
+    let mut with_passphrase = guarded.clone();
+    with_passphrase["passphrase"] = "wrong".into();
+    let wrong = call(port, "wallet_signMessage", with_passphrase.clone());
+    assert_eq!(wrong["error"]["code"], -32000, "{}", wrong);
+    with_passphrase["passphrase"] = "TREZOR".into();
+    let signed = call(port, "wallet_signMessage", with_passphrase);
+    assert_eq!(signed["result"]["signer"], PASSPHRASE_ADDRESS, "{}", signed);
+    let derived = call(
+        port,
+        "wallet_derive",
+        serde_json::json!({ "wallet": "guarded.json", "password": PASSWORD, "passphrase": "TREZOR" }),
+    );
+    assert!(derived["result"].to_string().contains(PASSPHRASE_ADDRESS), "{}", derived);
+
+    // Without the agent or a password the wallet stays locked
+    let locked = call(port, "wallet_signMessage", serde_json::json!({ "wallet": "served.json", "message": "hello" }));
+    assert_eq!(locked["error"]["code"], -32000, "{}", locked);
+
+    let outside = call(port, "wallet_derive", serde_json::json!({ "wallet": "../config.toml", "password": PASSWORD }));
+    assert_eq!(outside["error"]["code"], -32000, "{}", outside);
+
+    let unknown = call(port, "eth_accounts", serde_json::json!({}));
+    assert_eq!(unknown["error"]["code"], -32601, "{}", unknown);
+
+    let (_, parse_error) = post(port, TOKEN, "{not json");
+    assert_eq!(parse_error["error"]["code"], -32700);
+
+    server.kill().unwrap();
+    server.wait().unwrap();
+}
+
+/// Test the server refuses to listen beyond this machine
+#[test]
+fn test_serve_command_loopback_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+
+    web3wallet(&config, &["serve", "--listen", "0.0.0.0:8575"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("loopback"));
+}
+
+/// Test a missing token file is created in the state directory, readable by the owner only
+#[cfg(unix)]
+#[test]
+fn test_serve_command_creates_private_token() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+    let token_file = temp_dir.path().join("state").join("serve.token");
+
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let mut server = std::process::Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["serve", "--listen", &format!("127.0.0.1:{}", port), "--no-agent", "--config", &config])
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let started = Instant::now();
+    while TcpStream::connect(("127.0.0.1", port)).is_err() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    let token = std::fs::read_to_string(&token_file).unwrap();
+    assert_eq!(token.trim().len(), 64);
+    assert_eq!(std::fs::metadata(&token_file).unwrap().permissions().mode() & 0o777, 0o600);
+}
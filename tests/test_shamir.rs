@@ -0,0 +1,111 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+use web3wallet_cli::services::shamir::ShamirService;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const EXPECTED_ADDRESS: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+
+/// SLIP-39 test vectors, passphrase "TREZOR"
+const SINGLE_SHARE: &str = "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard";
+const TWO_OF_THREE: [&str; 2] = [
+    "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+    "shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking",
+];
+
+fn shares(mnemonics: &[&str]) -> Vec<String> {
+    mnemonics.iter().map(|m| m.to_string()).collect()
+}
+
+/// Test the published vectors restore their master secrets
+#[test]
+fn test_shamir_vectors() {
+    let secret = ShamirService::combine(&shares(&[SINGLE_SHARE]), "TREZOR").unwrap();
+    assert_eq!(hex::encode(&*secret), "bb54aac4b89dc868ba37d9cc21b2cece");
+
+    let secret = ShamirService::combine(&shares(&TWO_OF_THREE), "TREZOR").unwrap();
+    assert_eq!(hex::encode(&*secret), "b43ceb7e57a0ea8766221624d01b0864");
+    assert_eq!(ShamirService::threshold_of(TWO_OF_THREE[0]).unwrap(), 2);
+}
+
+/// Test any threshold of split shares restores the secret and fewer do not
+#[test]
+fn test_shamir_split_combine() {
+    let secret: Vec<u8> = (0..64).collect();
+    let split = ShamirService::split(&secret, 3, 5, "").unwrap();
+    assert_eq!(split.len(), 5);
+
+    let restored = ShamirService::combine(&[split[4].clone(), split[0].clone(), split[2].clone()], "").unwrap();
+    assert_eq!(*restored, secret);
+
+    assert!(ShamirService::combine(&split[..2], "").is_err());
+    assert!(ShamirService::combine(&[split[1].clone(), split[1].clone(), split[3].clone()], "").is_err());
+    assert!(ShamirService::split(&secret, 1, 3, "").is_err());
+    assert!(ShamirService::split(&secret, 4, 3, "").is_err());
+}
+
+/// Test a mistyped word fails the share checksum
+#[test]
+fn test_shamir_share_checksum() {
+    let mistyped = TWO_OF_THREE[0].replace("oasis", "ocean");
+    assert!(ShamirService::combine(&shares(&[&mistyped, TWO_OF_THREE[1]]), "TREZOR").is_err());
+}
+
+/// Test `backup shamir` shares restore the same wallet through `import --shamir`
+#[test]
+fn test_shamir_backup_and_import() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(&config, format!("wallets_path = {:?}\n", temp_dir.path().join("wallets").to_str().unwrap())).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["import", "--mnemonic", VALID_MNEMONIC_12, "--save", "shamir_wallet", "--config", config.to_str().unwrap()]);
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args([
+        "backup", "shamir", "shamir_wallet.json", "--threshold", "2", "--shares", "3",
+        "--config", config.to_str().unwrap(),
+    ]);
+    let assert = cmd.assert().success();
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout).to_string();
+    let lines: Vec<&str> = stdout.lines().collect();
+    let split: Vec<String> = lines
+        .windows(2)
+        .filter(|pair| pair[0].starts_with("Share "))
+        .map(|pair| pair[1].trim().to_string())
+        .collect();
+    assert_eq!(split.len(), 3);
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["import", "--shamir"]);
+    cmd.write_stdin(format!("{}\n{}\n", split[2], split[0]));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS));
+}
+
+/// Test wallets without a seed cannot be split
+#[test]
+fn test_shamir_backup_private_key_wallet() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(&config, format!("wallets_path = {:?}\n", temp_dir.path().join("wallets").to_str().unwrap())).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args([
+        "import", "--private-key", "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a",
+        "--save", "key_wallet", "--config", config.to_str().unwrap(),
+    ]);
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["backup", "shamir", "key_wallet.json", "--threshold", "2", "--shares", "3", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("INPUT_"));
+}
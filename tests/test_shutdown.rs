@@ -0,0 +1,40 @@
+use std::time::Duration;
+use web3wallet_cli::services::shutdown::{DrainOutcome, Shutdown, ShutdownSignal};
+
+/// Test new work is refused once shutdown is triggered, and the first signal wins
+#[tokio::test]
+async fn test_shutdown_refuses_new_work() {
+    let shutdown = Shutdown::new();
+    let guard = shutdown.begin().expect("accepted before shutdown");
+    assert_eq!(shutdown.in_flight(), 1);
+
+    shutdown.trigger(ShutdownSignal::Terminate);
+    shutdown.trigger(ShutdownSignal::Interrupt);
+    assert_eq!(shutdown.triggered().await, ShutdownSignal::Terminate);
+    assert!(shutdown.begin().is_none());
+    assert_eq!(shutdown.in_flight(), 1);
+
+    drop(guard);
+    assert_eq!(shutdown.in_flight(), 0);
+}
+
+/// Test draining waits for in-flight operations and times out when they overrun the grace period
+#[tokio::test]
+async fn test_shutdown_drain() {
+    let shutdown = Shutdown::new();
+    let guard = shutdown.begin().unwrap();
+    shutdown.trigger(ShutdownSignal::Interrupt);
+
+    let outcome = shutdown.drain(Duration::from_millis(20)).await;
+    assert_eq!(outcome, DrainOutcome::TimedOut { in_flight: 1 });
+    assert_eq!(outcome.exit_code(ShutdownSignal::Interrupt), 1);
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(guard);
+    });
+    let outcome = shutdown.drain(Duration::from_secs(5)).await;
+    assert_eq!(outcome, DrainOutcome::Drained);
+    assert_eq!(outcome.exit_code(ShutdownSignal::Interrupt), 130);
+    assert_eq!(DrainOutcome::Drained.exit_code(ShutdownSignal::Terminate), 143);
+}
@@ -0,0 +1,56 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Test a personal_sign signature verifies against the signing wallet
+#[test]
+fn test_sign_message_command_roundtrip() {
+    let wallet_name = "test_sign_message_wallet";
+    let password = "Test123!Password";
+
+    let home_dir = dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
+    let wallet_path = home_dir.join(".web3wallet").join("wallets").join(format!("{}.json", wallet_name));
+    let _ = std::fs::remove_file(&wallet_path);
+
+    let mut create_cmd = Command::cargo_bin("web3wallet").unwrap();
+    create_cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    create_cmd.args(["create", "--save", wallet_name]);
+    create_cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    cmd.args([
+        "sign-message",
+        "--from-file", &format!("{}.json", wallet_name),
+        "--message", "hello",
+        "--template", "{{signer}} {{signature}}",
+    ]);
+    let assert = cmd.assert().success();
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout).to_string();
+    let line = stdout.lines().last().unwrap_or_default();
+    let (signer, signature) = line.split_once(' ').expect("signer and signature");
+
+    let mut verify_cmd = Command::cargo_bin("web3wallet").unwrap();
+    verify_cmd.args([
+        "verify-signature",
+        "--message", "hello",
+        "--signature", signature,
+        "--address", signer,
+        "--output", "json",
+    ]);
+    verify_cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(r#""valid": true"#));
+
+    let _ = std::fs::remove_file(&wallet_path);
+}
+
+/// Test an unknown signer backend is rejected
+#[test]
+fn test_sign_message_command_unknown_signer() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["sign-message", "--signer", "trezor", "--message", "hello"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("signer"));
+}
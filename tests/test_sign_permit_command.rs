@@ -0,0 +1,166 @@
+mod common;
+
+use assert_cmd::Command;
+use common::{word, write_config, PASSWORD};
+use ethers::types::transaction::eip712::{EIP712Domain, Eip712, TypedData};
+use ethers::types::{Address, Signature, H256, U256};
+use serde_json::json;
+use std::str::FromStr;
+use tempfile::TempDir;
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const ADDRESS: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
+/// Bundled USDC on mainnet
+const USDC: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+/// DAI, whose permit is not EIP-2612
+const DAI: &str = "0x6B175474E89094C44Da98b954EedeAC495271d0F";
+const SPENDER: &str = "0x000000000022D473030F116dDEE9F6B43aC78BA3";
+/// 2100-01-01
+const DEADLINE: &str = "4102444800";
+
+fn abi_string(value: &str) -> String {
+    format!("{}{}{:0<64}", word(32), word(value.len() as u128), hex::encode(value))
+}
+
+/// USDC's domain: name "USD Coin", version "2"
+fn domain_separator() -> H256 {
+    H256::from(
+        EIP712Domain {
+            name: Some("USD Coin".to_string()),
+            version: Some("2".to_string()),
+            chain_id: Some(1.into()),
+            verifying_contract: Some(USDC.parse::<Address>().unwrap()),
+            salt: None,
+        }
+        .separator(),
+    )
+}
+
+/// JSON-RPC endpoint where every token has USDC's permit domain and the owner's nonce is 7;
+/// DAI reports its own PERMIT_TYPEHASH
+fn fake_rpc() -> String {
+    let separator = hex::encode(domain_separator());
+    common::fake_rpc(move |call| match call["method"].as_str().unwrap() {
+        "eth_call" => {
+            let to = call["params"][0]["to"].as_str().unwrap();
+            let data = call["params"][0]["data"].as_str().unwrap();
+            let body = match &data[2..10] {
+                "3644e515" => separator.clone(),
+                "7ecebe00" => word(7),
+                "30adf81f" if to.eq_ignore_ascii_case(DAI) => {
+                    "ea2aa0a1be11a07ed86d755c93467f4f82362b452371d1ba94d1715123511acb".to_string()
+                }
+                "06fdde03" => abi_string("USD Coin"),
+                "54fd4d50" => abi_string("2"),
+                _ => String::new(),
+            };
+            json!(format!("0x{}", body))
+        }
+        _ => json!("0x1"),
+    })
+}
+
+fn bytes32(value: U256) -> String {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn sign_permit(config: &str, token: &str, extra: &[&str]) -> Command {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args([
+        "sign-permit", "--from-file", "owner.json", "--token", token, "--spender", SPENDER, "--value", "1000000",
+        "--chain", "mainnet",
+    ])
+    .args(extra)
+    .args(["--config", config, "--no-agent", "--password-stdin"]);
+    if !extra.contains(&"--deadline") {
+        cmd.args(["--deadline", DEADLINE]);
+    }
+    cmd.write_stdin(format!("{}\n", PASSWORD));
+    cmd
+}
+
+fn json(cmd: &mut Command) -> serde_json::Value {
+    let output = cmd.args(["--output", "json"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// The permit's EIP-712 digest, from ethers' own encoder
+fn expected_hash() -> H256 {
+    let typed_data: TypedData = serde_json::from_value(serde_json::json!({
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "version", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" },
+            ],
+            "Permit": [
+                { "name": "owner", "type": "address" },
+                { "name": "spender", "type": "address" },
+                { "name": "value", "type": "uint256" },
+                { "name": "nonce", "type": "uint256" },
+                { "name": "deadline", "type": "uint256" },
+            ],
+        },
+        "primaryType": "Permit",
+        "domain": { "name": "USD Coin", "version": "2", "chainId": 1, "verifyingContract": USDC },
+        "message": { "owner": ADDRESS, "spender": SPENDER, "value": "1000000", "nonce": "7", "deadline": DEADLINE },
+    }))
+    .unwrap();
+    H256::from(typed_data.encode_eip712().unwrap())
+}
+
+/// Test a permit read from the token signs the EIP-2612 digest with v/r/s and permit calldata,
+/// the same signature comes out offline from the nonce and domain given by hand, and tokens
+/// with another permit or missing offline inputs are refused
+#[test]
+fn test_sign_permit_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), &format!("\n[kdf]\nalgorithm = \"pbkdf2\"\n\n[rpc.endpoints]\nmainnet = {:?}\n", fake_rpc()));
+    Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["import", "--private-key", VALID_PRIVATE_KEY, "--save", "owner"])
+        .args(["--config", &config, "--no-agent", "--password-stdin"])
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .success();
+
+    let out = temp_dir.path().join("permit.json");
+    let signed = json(&mut sign_permit(&config, "USDC", &["--out", out.to_str().unwrap()]));
+    let hash = expected_hash();
+    assert_eq!(signed["hash"], format!("{:?}", hash));
+    assert_eq!(signed["token"], USDC);
+    assert_eq!(signed["owner"], ADDRESS);
+    assert_eq!(signed["nonce"], "7");
+    let signature = Signature::from_str(signed["signature"].as_str().unwrap()).unwrap();
+    assert_eq!(format!("{:?}", signature.recover(hash).unwrap()), ADDRESS.to_lowercase());
+    assert_eq!(signed["v"], signature.v);
+    assert_eq!(signed["r"], format!("0x{}", bytes32(signature.r)));
+    assert_eq!(signed["s"], format!("0x{}", bytes32(signature.s)));
+    let calldata = signed["calldata"].as_str().unwrap();
+    assert!(calldata.starts_with("0xd505accf"));
+    assert!(calldata.ends_with(&format!("{}{}{}", word(signature.v.into()), bytes32(signature.r), bytes32(signature.s))));
+    assert_eq!(signed["typed_data"]["domain"]["version"], "2");
+    let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+    assert_eq!(written["signature"], signed["signature"]);
+
+    let offline = json(&mut sign_permit(
+        &config,
+        USDC,
+        &["--offline", "--nonce", "7", "--domain-name", "USD Coin", "--domain-version", "2"],
+    ));
+    assert_eq!(offline["signature"], signed["signature"]);
+    assert_eq!(offline["typed_data"], signed["typed_data"]);
+    let separator = format!("{:?}", domain_separator());
+    let offline = json(&mut sign_permit(&config, USDC, &["--offline", "--nonce", "7", "--domain-separator", &separator]));
+    assert_eq!(offline["signature"], signed["signature"]);
+    assert!(offline["typed_data"].is_null());
+
+    sign_permit(&config, USDC, &["--offline", "--domain-separator", &separator]).assert().code(2);
+    sign_permit(&config, USDC, &["--offline", "--nonce", "7"]).assert().code(2);
+    sign_permit(&config, DAI, &[]).assert().code(7);
+    sign_permit(&config, USDC, &["--deadline", "1"]).assert().code(2);
+}
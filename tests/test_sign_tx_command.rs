@@ -0,0 +1,166 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Test signing a legacy transaction from individual fields
+#[test]
+fn test_sign_tx_command_fields() {
+    let wallet_name = "test_sign_tx_wallet";
+    let password = "Test123!Password";
+
+    let home_dir = dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
+    let wallet_path = home_dir.join(".web3wallet").join("wallets").join(format!("{}.json", wallet_name));
+    let _ = std::fs::remove_file(&wallet_path);
+
+    let mut create_cmd = Command::cargo_bin("web3wallet").unwrap();
+    create_cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    create_cmd.args(["create", "--network", "sepolia", "--save", wallet_name]);
+    create_cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    cmd.args([
+        "sign-tx",
+        "--from-file", &format!("{}.json", wallet_name),
+        "--to", "0x000000000000000000000000000000000000dEaD",
+        "--value", "1000000000000000",
+        "--nonce", "0",
+        "--gas-price", "20000000000",
+        "--chain-id", "11155111",
+        "--output", "json",
+    ]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(r#""raw": "0xf8"#))
+        .stdout(predicate::str::contains(r#""hash": "0x"#));
+
+    let _ = std::fs::remove_file(&wallet_path);
+}
+
+/// Test explicit EIP-1559 fees produce a type-2 transaction
+#[test]
+fn test_sign_tx_command_eip1559() {
+    let wallet_name = "test_sign_tx_1559_wallet";
+    let password = "Test123!Password";
+
+    let home_dir = dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
+    let wallet_path = home_dir.join(".web3wallet").join("wallets").join(format!("{}.json", wallet_name));
+    let _ = std::fs::remove_file(&wallet_path);
+
+    let mut create_cmd = Command::cargo_bin("web3wallet").unwrap();
+    create_cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    create_cmd.args(["create", "--network", "sepolia", "--save", wallet_name]);
+    create_cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    cmd.args([
+        "sign-tx",
+        "--from-file", &format!("{}.json", wallet_name),
+        "--to", "0x000000000000000000000000000000000000dEaD",
+        "--nonce", "1",
+        "--max-fee", "30000000000",
+        "--max-priority-fee", "1000000000",
+        "--chain-id", "11155111",
+        "--output", "json",
+    ]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(r#""raw": "0x02"#));
+
+    let _ = std::fs::remove_file(&wallet_path);
+}
+
+/// Test signing for another chain than the wallet's is refused unless forced
+#[test]
+fn test_sign_tx_command_chain_mismatch() {
+    let wallet_name = "test_sign_tx_chain_wallet";
+    let password = "Test123!Password";
+
+    let home_dir = dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
+    let wallet_path = home_dir.join(".web3wallet").join("wallets").join(format!("{}.json", wallet_name));
+    let _ = std::fs::remove_file(&wallet_path);
+
+    let mut create_cmd = Command::cargo_bin("web3wallet").unwrap();
+    create_cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    create_cmd.args(["create", "--network", "mainnet", "--save", wallet_name]);
+    create_cmd.assert().success();
+
+    let keystore = std::fs::read_to_string(&wallet_path).unwrap();
+    assert!(keystore.contains(r#""chain_id": 1"#));
+
+    let sign_args = [
+        "sign-tx",
+        "--from-file", &format!("{}.json", wallet_name),
+        "--to", "0x000000000000000000000000000000000000dEaD",
+        "--nonce", "0",
+        "--gas-price", "20000000000",
+        "--chain-id", "11155111",
+    ];
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    cmd.args(sign_args);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("VALIDATION_007"));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    cmd.args(sign_args).arg("--force");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Transaction signed"));
+
+    let _ = std::fs::remove_file(&wallet_path);
+}
+
+/// Test missing required fields are rejected
+#[test]
+fn test_sign_tx_command_missing_fields() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["sign-tx", "--from-file", "nonexistent.json", "--to", "0x000000000000000000000000000000000000dEaD"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--nonce"));
+}
+
+/// Test an invalid `<wallet>.toml` sidecar is reported instead of silently ignored
+#[test]
+fn test_sign_tx_command_invalid_wallet_overrides() {
+    let wallet_name = "test_sign_tx_overrides_wallet";
+    let password = "Test123!Password";
+
+    let home_dir = dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
+    let wallets_dir = home_dir.join(".web3wallet").join("wallets");
+    let wallet_path = wallets_dir.join(format!("{}.json", wallet_name));
+    let sidecar_path = wallets_dir.join(format!("{}.toml", wallet_name));
+    let _ = std::fs::remove_file(&wallet_path);
+
+    let mut create_cmd = Command::cargo_bin("web3wallet").unwrap();
+    create_cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    create_cmd.args(["create", "--save", wallet_name]);
+    create_cmd.assert().success();
+
+    std::fs::write(&sidecar_path, "network = \"notachain\"\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin(format!("{}\n", password));
+    cmd.args([
+        "sign-tx",
+        "--from-file", &format!("{}.json", wallet_name),
+        "--to", "0x000000000000000000000000000000000000dEaD",
+        "--nonce", "0",
+        "--gas-price", "20000000000",
+        "--chain-id", "11155111",
+    ]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Command failed"));
+
+    let _ = std::fs::remove_file(&wallet_path);
+    let _ = std::fs::remove_file(&sidecar_path);
+}
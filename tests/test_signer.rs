@@ -0,0 +1,48 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use web3wallet_cli::services::signer::{LedgerSigner, SignerKind};
+
+/// Test --signer names parse case-insensitively, software being the default
+#[test]
+fn test_signer_kind_parsing() {
+    assert_eq!(SignerKind::default(), SignerKind::Software);
+    assert_eq!("software".parse::<SignerKind>().unwrap(), SignerKind::Software);
+    assert_eq!("keystore".parse::<SignerKind>().unwrap(), SignerKind::Software);
+    assert_eq!("Ledger".parse::<SignerKind>().unwrap(), SignerKind::Ledger);
+    assert!("trezor".parse::<SignerKind>().unwrap_err().to_string().contains("INPUT_001"));
+}
+
+/// Test a Ledger signing path is checked before any device is opened
+#[tokio::test]
+async fn test_ledger_signer_rejects_invalid_path() {
+    for path in ["44'/60'/0'/0/0", "m/44'/60'/0'/0/x", "m/44'/60'/0'/0'"] {
+        assert!(LedgerSigner::connect(Some(path), 1).await.is_err(), "{}", path);
+    }
+}
+
+/// Test each backend is chosen by --signer: software needs a keystore, unknown names are refused
+/// and a Ledger is never asked for keys it does not reveal
+#[test]
+fn test_signer_selection() {
+    Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["sign-message", "--signer", "software", "--message", "hi"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("INPUT_003"))
+        .stderr(predicate::str::contains("--signer ledger"));
+
+    Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["sign-message", "--signer", "trezor", "--message", "hi"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("'trezor'"));
+
+    Command::cargo_bin("web3wallet")
+        .unwrap()
+        .args(["derive", "--signer", "ledger", "--show-private-keys"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("never reveals its keys"));
+}
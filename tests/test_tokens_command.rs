@@ -0,0 +1,69 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+const INVALID_TOKEN_ADDRESS: &str = "0x00000000000000000000000000000000000t0k3n";
+const TEST_TOKEN_ADDRESS: &str = "0x1111111111111111111111111111111111111111";
+
+/// Test adding, listing and removing a token in the registry
+#[test]
+fn test_tokens_command_add_list_remove() {
+    let symbol = "TSTTKN";
+
+    let mut add = Command::cargo_bin("web3wallet").unwrap();
+    add.args(["tokens", "add", symbol, TEST_TOKEN_ADDRESS, "--decimals", "6", "--chain", "sepolia"]);
+    add.assert().success();
+
+    let mut list = Command::cargo_bin("web3wallet").unwrap();
+    list.args(["tokens", "list", "--chain", "sepolia", "--output", "json"]);
+    list.assert()
+        .success()
+        .stdout(predicate::str::contains(symbol));
+
+    let mut remove = Command::cargo_bin("web3wallet").unwrap();
+    remove.args(["tokens", "remove", symbol, "--chain", "sepolia"]);
+    remove.assert()
+        .success()
+        .stdout(predicate::str::contains("Removed 1 token(s)"));
+}
+
+/// Test invalid token addresses are rejected
+#[test]
+fn test_tokens_command_invalid_address() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["tokens", "add", "BAD", INVALID_TOKEN_ADDRESS, "--decimals", "18"]);
+
+    cmd.assert().failure();
+}
+
+/// Test token addresses are printed with their EIP-55 checksum whatever case they were added in
+#[test]
+fn test_tokens_command_checksummed_output() {
+    let symbol = "TSTWETH";
+
+    let mut add = Command::cargo_bin("web3wallet").unwrap();
+    add.args([
+        "tokens", "add", symbol, "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+        "--decimals", "18", "--chain", "sepolia", "--output", "json",
+    ]);
+    add.assert()
+        .success()
+        .stdout(predicate::str::contains("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"));
+
+    let mut remove = Command::cargo_bin("web3wallet").unwrap();
+    remove.args(["tokens", "remove", symbol, "--chain", "sepolia"]);
+    remove.assert().success();
+}
+
+/// Test mixed-case addresses with a wrong EIP-55 checksum are rejected
+#[test]
+fn test_tokens_command_bad_checksum() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args([
+        "tokens", "add", "BADSUM", "0xc02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+        "--decimals", "18", "--chain", "sepolia",
+    ]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("VALIDATION_001"));
+}
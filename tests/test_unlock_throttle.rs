@@ -0,0 +1,49 @@
+mod common;
+
+use common::{web3wallet, write_config, PASSWORD};
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const EXPECTED_ADDRESS: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
+const WRONG_PASSWORD: &str = "Wrong123!Password";
+
+fn load(config: &str, password: &str) -> assert_cmd::assert::Assert {
+    web3wallet(config, &["load", "main.json", "--password-stdin"])
+        .write_stdin(format!("{}\n", password))
+        .assert()
+}
+
+/// Test wrong passwords are counted, the right one clears the count, and five wrong ones lock
+/// the keystore even for the right password until `unlock_throttle = false`
+#[test]
+fn test_unlock_throttle() {
+    let temp_dir = TempDir::new().unwrap();
+    let wallets = temp_dir.path().join("wallets");
+    let config = &write_config(temp_dir.path(), "\n[kdf]\nalgorithm = \"pbkdf2\"\n");
+
+    web3wallet(config, &["import", "--private-key", VALID_PRIVATE_KEY, "--save", "main", "--password-stdin"])
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .success();
+    let attempts = wallets.join(".unlock-attempts");
+
+    for _ in 0..2 {
+        load(config, WRONG_PASSWORD)
+            .code(3)
+            .stderr(predicate::str::contains("AUTH_001"));
+    }
+    assert!(attempts.exists());
+    load(config, PASSWORD).success().stdout(predicate::str::contains(EXPECTED_ADDRESS));
+    assert!(!attempts.exists());
+
+    for _ in 0..5 {
+        load(config, WRONG_PASSWORD).code(3);
+    }
+    load(config, PASSWORD)
+        .code(3)
+        .stderr(predicate::str::contains("AUTH_008").and(predicate::str::contains("locked after 5 wrong passwords")));
+
+    write_config(temp_dir.path(), "unlock_throttle = false\n\n[kdf]\nalgorithm = \"pbkdf2\"\n");
+    load(config, PASSWORD).success().stdout(predicate::str::contains(EXPECTED_ADDRESS));
+}
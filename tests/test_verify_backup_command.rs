@@ -0,0 +1,67 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Valid mnemonic of one repeated word, so the answers do not depend on the positions asked
+const REPEATED_MNEMONIC: &str = "yellow yellow yellow yellow yellow yellow yellow yellow yellow yellow yellow yellow";
+
+fn setup(dir: &Path) -> String {
+    let config = dir.join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "wallets_path = {:?}\nstate_path = {:?}\n",
+            dir.join("wallets").to_str().unwrap(),
+            dir.join("state").to_str().unwrap()
+        ),
+    )
+    .unwrap();
+    let config = config.to_str().unwrap().to_string();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["import", "--mnemonic", REPEATED_MNEMONIC, "--save", "backup", "--config", &config]);
+    cmd.assert().success();
+    config
+}
+
+fn backup_verified(dir: &Path) -> bool {
+    let keystore: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("wallets").join("backup.json")).unwrap()).unwrap();
+    keystore["metadata"]["backup_verified"].as_bool().unwrap()
+}
+
+/// Test the right words pass the quiz and mark the keystore's backup as verified
+#[test]
+fn test_verify_backup_command_success() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = setup(temp_dir.path());
+    assert!(!backup_verified(temp_dir.path()));
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["verify-backup", "backup.json", "--config", &config, "--password-stdin"]);
+    cmd.write_stdin("Test123!Password\nyellow\nYELLOW\n yellow \n");
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("of 12: "))
+        .stdout(predicate::str::contains("Backup verified for"));
+
+    assert!(backup_verified(temp_dir.path()));
+}
+
+/// Test a wrong word fails the quiz and leaves the keystore alone
+#[test]
+fn test_verify_backup_command_wrong_word() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = setup(temp_dir.path());
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["verify-backup", "backup.json", "--config", &config, "--password-stdin"]);
+    cmd.write_stdin("Test123!Password\nyellow\nzoo\nyellow\n");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("does not match the backup"));
+
+    assert!(!backup_verified(temp_dir.path()));
+}
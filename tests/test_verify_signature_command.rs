@@ -0,0 +1,40 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+// personal_sign of "Some data" by 0x2c7536E3605D9C16a7a3D7b1898e529396a65c23
+const SIGNATURE: &str = "0xb91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c";
+
+/// Test a signature recovering to the expected address is reported valid
+#[test]
+fn test_verify_signature_command_valid() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args([
+        "verify-signature",
+        "--message", "Some data",
+        "--signature", SIGNATURE,
+        "--address", "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23",
+        "--output", "json",
+    ]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(r#""valid": true"#))
+        .stdout(predicate::str::contains("0x2c7536E3605D9C16a7a3D7b1898e529396a65c23"));
+}
+
+/// Test a signature from a different key is reported invalid
+#[test]
+fn test_verify_signature_command_wrong_address() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args([
+        "verify-signature",
+        "--message", "Some other data",
+        "--signature", SIGNATURE,
+        "--address", "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23",
+        "--output", "json",
+    ]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(r#""valid": false"#));
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::{unlocked, write_config};
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const ACCOUNT_0_XPUB: &str = "xpub6DCoCpSuQZB2jawqnGMEPS63ePKWkwWPH4TU45Q7LPXWuNd8TMtVxRrgjtEshuqpK3mdhaWHPFsBngh5GFZaM6si3yZdUsT8ddYM3PwnATt";
+/// m/44'/60'/0'/0/7 of the mnemonic above
+const INDEX_7_ADDRESS: &str = "0x593814d3309e2dF31D112824F0bb5aa7Cb0D7d47";
+
+/// Test the delegate keystore holds exactly the derived key, without the mnemonic
+#[test]
+fn test_wallet_delegate_single_key() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+    unlocked(&config, &["import", "--mnemonic", VALID_MNEMONIC_12, "--save", "main"]).assert().success();
+
+    unlocked(&config, &["wallet", "delegate", "--from-file", "main.json", "--derive-index", "7", "--export-to", "bot"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(INDEX_7_ADDRESS))
+        .stdout(predicate::str::contains("m/44'/60'/0'/0/7"));
+
+    let keystore = std::fs::read_to_string(temp_dir.path().join("wallets").join("bot.json")).unwrap();
+    assert!(!keystore.contains("abandon"));
+
+    let output = unlocked(&config, &["load", "bot.json", "-o", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let loaded: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(loaded["address"], INDEX_7_ADDRESS);
+    assert_eq!(loaded["has_mnemonic"], false);
+    assert_eq!(loaded["is_hd"], false);
+
+    // Neither more keys from the delegate nor a second delegate over the first
+    unlocked(&config, &["wallet", "delegate", "--from-file", "bot.json", "--derive-index", "0", "--export-to", "bot2"])
+        .assert()
+        .failure();
+    unlocked(&config, &["wallet", "delegate", "--from-file", "main.json", "--derive-index", "8", "--export-to", "bot.json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("FS_005"));
+}
+
+/// Test a watch-only wallet has no key to delegate
+#[test]
+fn test_wallet_delegate_watch_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+    unlocked(&config, &["import", "--xpub", ACCOUNT_0_XPUB, "--save", "watch"]).assert().success();
+
+    unlocked(&config, &["wallet", "delegate", "--from-file", "watch.json", "--derive-index", "1", "--export-to", "bot"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("AUTH_007"));
+    assert!(!temp_dir.path().join("wallets").join("bot.json").exists());
+}
@@ -0,0 +1,54 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+/// Test the fixtures match the well-known accounts of the Hardhat and BIP39 test mnemonics
+#[test]
+fn test_wallet_fixtures_known_values() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["wallet", "fixtures", "--mnemonic", "hardhat"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let fixtures: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(fixtures["version"], 1);
+    assert_eq!(fixtures["base_path"], "m/44'/60'/0'/0");
+    let accounts = fixtures["accounts"].as_array().unwrap();
+    assert_eq!(accounts.len(), 20);
+    assert_eq!(accounts[0]["address"], "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+    assert_eq!(accounts[0]["private_key"], "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80");
+    assert_eq!(accounts[19]["derivation_path"], "m/44'/60'/0'/0/19");
+    assert_eq!(accounts[19]["address"], "0x8626f6940E2eb28930eFb4CeF49B2d1F2C9C1199");
+    assert!(accounts[0]["public_key"].as_str().unwrap().starts_with("0x04"));
+
+    // The phrase works as well as the name, and the output is the same on every run
+    let temp_dir = TempDir::new().unwrap();
+    let out = temp_dir.path().join("fixtures.json");
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["wallet", "fixtures", "--mnemonic", "test test test test test test test test test test test junk"]);
+    cmd.args(["--out", out.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote 20 hardhat fixture account(s)"));
+    assert_eq!(std::fs::read(&out).unwrap(), output);
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["wallet", "fixtures", "--mnemonic", "abandon", "--count", "1"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let fixtures: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(fixtures["accounts"][0]["address"], "0x9858EfFD232B4033E47d90003D41EC34EcaEda94");
+    assert_eq!(fixtures["accounts"][0]["private_key"], "0x1ab42cc412b618bdea3a599e3c9bae199ebf030895b039e9db1e30dafb12b727");
+}
+
+/// Test mnemonics that are not public test vectors are refused
+#[test]
+fn test_wallet_fixtures_rejects_real_mnemonics() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args([
+        "wallet", "fixtures", "--mnemonic",
+        "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong",
+    ]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("public test mnemonic"))
+        .stdout(predicate::str::contains("zoo").not());
+}
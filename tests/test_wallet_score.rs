@@ -0,0 +1,79 @@
+mod common;
+
+use common::{unlocked, write_config};
+use predicates::prelude::*;
+use std::path::Path;
+use tempfile::TempDir;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const ACCOUNT_0_XPUB: &str = "xpub6DCoCpSuQZB2jawqnGMEPS63ePKWkwWPH4TU45Q7LPXWuNd8TMtVxRrgjtEshuqpK3mdhaWHPFsBngh5GFZaM6si3yZdUsT8ddYM3PwnATt";
+
+#[cfg(unix)]
+fn chmod(path: &Path, mode: u32) {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).unwrap();
+}
+
+fn score(config: &str, file: &str) -> serde_json::Value {
+    let output = unlocked(config, &["wallet", "score", "--from-file", file, "-o", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    serde_json::from_slice(&output).unwrap()
+}
+
+fn check<'a>(report: &'a serde_json::Value, name: &str) -> &'a serde_json::Value {
+    report["checks"].as_array().unwrap().iter().find(|c| c["name"] == name).unwrap()
+}
+
+/// Test each finding costs points and comes with the command that fixes it
+#[cfg(unix)]
+#[test]
+fn test_wallet_score_findings() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "\n[rpc.endpoints]\nmainnet = \"https://rpc.example.org\"\n");
+    unlocked(&config, &["import", "--mnemonic", VALID_MNEMONIC_12, "--save", "main"]).assert().success();
+    let keystore = temp_dir.path().join("wallets").join("main.json");
+    chmod(&keystore, 0o600);
+
+    let report = score(&config, "main.json");
+    assert_eq!(check(&report, "kdf")["status"], "pass");
+    assert_eq!(check(&report, "password_age")["status"], "pass");
+    assert_eq!(check(&report, "backup")["status"], "fail");
+    assert_eq!(report["score"], 75);
+    assert_eq!(report["grade"], "C");
+    assert_eq!(report["remedies"][0], "web3wallet verify-backup main.json");
+
+    // Readable by others and signing over plain http
+    chmod(&keystore, 0o644);
+    let config = write_config(temp_dir.path(), "\n[rpc.endpoints]\nmainnet = \"http://rpc.example.org\"\n");
+    let report = score(&config, "main.json");
+    assert_eq!(check(&report, "permissions")["status"], "fail");
+    assert_eq!(check(&report, "network")["status"], "warn");
+    assert_eq!(report["score"], 45);
+    assert_eq!(report["grade"], "F");
+    assert_eq!(report["remedies"][1], format!("chmod 600 {}", keystore.display()));
+
+    unlocked(&config, &["wallet", "score", "--from-file", "main.json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wallet health: F (45/100)"))
+        .stdout(predicate::str::contains("To improve:"));
+}
+
+/// Test a watch-only wallet has no secret to lose and scores full marks
+#[cfg(unix)]
+#[test]
+fn test_wallet_score_watch_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "\n[rpc.endpoints]\nmainnet = \"http://127.0.0.1:8545\"\n");
+    unlocked(&config, &["import", "--xpub", ACCOUNT_0_XPUB, "--save", "watch"]).assert().success();
+    chmod(&temp_dir.path().join("wallets").join("watch.json"), 0o600);
+
+    let report = score(&config, "watch.json");
+    assert_eq!(report["score"], 100);
+    assert_eq!(report["grade"], "A");
+    assert!(report["remedies"].as_array().unwrap().is_empty());
+}
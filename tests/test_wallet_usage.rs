@@ -0,0 +1,90 @@
+use assert_cmd::Command;
+use chrono::{TimeZone, Utc};
+use predicates::prelude::*;
+use tempfile::TempDir;
+use web3wallet_cli::services::usage::{Anomaly, UsageKind, UsageLog, WalletUsage};
+
+const VALID_PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe512961708279c1e3ae83da5e56df1a";
+const EXPECTED_ADDRESS: &str = "0xc85117289FEc250dDbAB37F2A597af5BF950e3b0";
+
+/// Test signing outside a wallet's usual hours is flagged once a profile exists
+#[test]
+fn test_usage_flags_unusual_sign_hour() {
+    let mut usage = WalletUsage::default();
+    for day in 1..=20 {
+        let at = Utc.with_ymd_and_hms(2026, 3, day, 14, 0, 0).unwrap();
+        assert!(usage.record(UsageKind::Sign, at).is_empty());
+    }
+
+    let usual = Utc.with_ymd_and_hms(2026, 3, 21, 14, 30, 0).unwrap();
+    assert!(usage.record(UsageKind::Sign, usual).is_empty());
+
+    let night = Utc.with_ymd_and_hms(2026, 3, 21, 3, 0, 0).unwrap();
+    assert_eq!(usage.record(UsageKind::Sign, night), vec![Anomaly::UnusualHour { hour: 3 }]);
+    assert_eq!(usage.signs, 22);
+    assert_eq!(usage.anomalies.len(), 1);
+}
+
+/// Test a first export, and an export after a dormant stretch, are flagged
+#[test]
+fn test_usage_flags_sudden_export() {
+    let mut usage = WalletUsage::default();
+    let start = Utc.with_ymd_and_hms(2026, 1, 1, 9, 0, 0).unwrap();
+    usage.record(UsageKind::Unlock, start);
+
+    assert_eq!(usage.record(UsageKind::Export, start), vec![Anomaly::SuddenExport { idle_days: None }]);
+    assert!(usage.record(UsageKind::Export, start).is_empty());
+
+    let later = Utc.with_ymd_and_hms(2026, 3, 1, 9, 0, 0).unwrap();
+    assert_eq!(usage.record(UsageKind::Export, later), vec![Anomaly::SuddenExport { idle_days: Some(59) }]);
+    assert_eq!(usage.exports, 3);
+}
+
+/// Test the usage log persists counters keyed by address
+#[tokio::test]
+async fn test_usage_log_roundtrip() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = UsageLog::default_path(temp_dir.path());
+
+    let mut log = UsageLog::load(&path).await.unwrap();
+    log.record(EXPECTED_ADDRESS, UsageKind::Unlock);
+    log.record(&EXPECTED_ADDRESS.to_lowercase(), UsageKind::Sign);
+    log.save().await.unwrap();
+
+    let log = UsageLog::load(&path).await.unwrap();
+    let usage = log.get(EXPECTED_ADDRESS).unwrap();
+    assert_eq!((usage.unlocks, usage.signs, usage.exports), (1, 1, 0));
+}
+
+/// Test unlocking a saved wallet shows up in `wallet usage`
+#[test]
+fn test_wallet_usage_command_counts_unlocks() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "wallets_path = {:?}\nstate_path = {:?}\n",
+            temp_dir.path().join("wallets").to_str().unwrap(),
+            temp_dir.path().join("state").to_str().unwrap()
+        ),
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["import", "--private-key", VALID_PRIVATE_KEY, "--save", "usage", "--config", config.to_str().unwrap()]);
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["load", "usage.json", "--config", config.to_str().unwrap()]);
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["wallet", "usage", "usage.json", "--output", "json", "--config", config.to_str().unwrap()]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS))
+        .stdout(predicate::str::contains(r#""unlocks": 1"#));
+}
@@ -0,0 +1,34 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Test the short version report
+#[test]
+fn test_wallet_version() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["wallet", "version"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("web3wallet {}", env!("CARGO_PKG_VERSION"))))
+        .stdout(predicate::str::contains("Wallets:").not());
+}
+
+/// Test --verbose adds build, config and directory details to the JSON report
+#[test]
+fn test_wallet_version_verbose_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    let wallets = dir.path().join("wallets");
+    std::fs::write(&config, format!("wallets_path = {:?}\n", wallets.to_str().unwrap())).unwrap();
+
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.args(["wallet", "version", "--verbose", "--output", "json", "--config", config.to_str().unwrap()]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(r#""git_commit""#))
+        .stdout(predicate::str::contains(r#""target""#))
+        .stdout(predicate::str::contains(r#""crypto""#))
+        .stdout(predicate::str::contains(config.to_str().unwrap()))
+        .stdout(predicate::str::contains(wallets.to_str().unwrap()));
+}
@@ -0,0 +1,26 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Test an unknown network is rejected before any wallet is loaded
+#[test]
+fn test_wrap_command_unknown_network() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["wrap", "1.0", "--from-file", "nonexistent.json", "--network", "notachain"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Command failed"));
+}
+
+/// Test a malformed amount is rejected
+#[test]
+fn test_unwrap_command_invalid_amount() {
+    let mut cmd = Command::cargo_bin("web3wallet").unwrap();
+    cmd.arg("--password-stdin").write_stdin("Test123!Password\n");
+    cmd.args(["unwrap", "one", "--from-file", "nonexistent.json", "--network", "sepolia"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Command failed"));
+}
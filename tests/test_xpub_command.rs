@@ -0,0 +1,64 @@
+mod common;
+
+use common::{unlocked, write_config};
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+const VALID_MNEMONIC_12: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const EXPECTED_ADDRESS: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+const ACCOUNT_XPUB: &str =
+    "xpub6DCoCpSuQZB2jawqnGMEPS63ePKWkwWPH4TU45Q7LPXWuNd8TMtVxRrgjtEshuqpK3mdhaWHPFsBngh5GFZaM6si3yZdUsT8ddYM3PwnATt";
+
+/// Test the account xpub and per-index public keys of the BIP39 test mnemonic
+#[test]
+fn test_xpub_command_known_values() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+    unlocked(&config, &["import", "--mnemonic", VALID_MNEMONIC_12, "--save", "hd"]).assert().success();
+
+    unlocked(&config, &["xpub", "hd.json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Account path: m/44'/60'/0'"))
+        .stdout(predicate::str::contains(ACCOUNT_XPUB));
+
+    let output = unlocked(&config, &["xpub", "hd.json", "--public-keys", "2", "--output", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&output[output.find('{').unwrap()..]).unwrap();
+    assert_eq!(json["xpub"], ACCOUNT_XPUB);
+    // The xpub imports as a watch-only wallet with the same addresses
+    unlocked(&config, &["import", "--xpub", json["xpub"].as_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(EXPECTED_ADDRESS));
+    let keys = json["public_keys"].as_array().unwrap();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[0]["address"], EXPECTED_ADDRESS);
+    assert_eq!(keys[0]["derivation_path"], "m/44'/60'/0'/0/0");
+    assert_eq!(keys[0]["public_key"], "0x0237b0bb7a8288d38ed49a524b5dc98cff3eb5ca824c9f9dc0dfdb3d9cd600f299");
+    assert!(!output.contains("private"));
+}
+
+/// Test other accounts get their own xpub and key-only wallets are refused
+#[test]
+fn test_xpub_command_account_and_key_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = write_config(temp_dir.path(), "");
+    unlocked(&config, &["create", "--account", "1", "--save", "second"]).assert().success();
+    unlocked(&config, &["xpub", "second.json", "--template", "{{account_path}} {{xpub}}"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("m/44'/60'/1' xpub"));
+
+    let key = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    unlocked(&config, &["import", "--private-key", key, "--save", "key_only"]).assert().success();
+    unlocked(&config, &["xpub", "key_only.json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("HD wallet"));
+}